@@ -0,0 +1,55 @@
+//! Insert latency of the RPC transaction log as it grows.
+//!
+//! Run with `cargo bench --bench tx_registry`. Each line reports the mean
+//! `add_transaction` time for a batch inserted once the log has reached the
+//! given size; the numbers should stay flat from 1k to 100k entries.
+
+use fractal_vortex_chain::rpc_storage::{RPCStorage, WalletTransaction};
+use std::time::Instant;
+
+const CHECKPOINTS: [u64; 3] = [1_000, 10_000, 100_000];
+const SAMPLE: u64 = 500;
+
+// Built through the constructor so new transaction fields don't break the bench
+fn transaction(seq: u64) -> WalletTransaction {
+    WalletTransaction {
+        timestamp: 1_700_000_000 + seq,
+        ..WalletTransaction::new_transfer(
+            "fvcbenchsender".to_string(),
+            "fvcbenchreceiver".to_string(),
+            seq,
+            format!("0x{:064x}", seq),
+            seq,
+        )
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let dir = tempfile::tempdir().expect("temp dir");
+    // RPC storage opens its database from this variable on first use
+    std::env::set_var("RPC_DATA_DIR", dir.path());
+
+    let mut inserted = 0u64;
+    let mut means = Vec::new();
+    for checkpoint in CHECKPOINTS {
+        while inserted < checkpoint {
+            RPCStorage::add_transaction(&transaction(inserted)).await.unwrap();
+            inserted += 1;
+        }
+
+        let start = Instant::now();
+        for _ in 0..SAMPLE {
+            RPCStorage::add_transaction(&transaction(inserted)).await.unwrap();
+            inserted += 1;
+        }
+        let mean = start.elapsed() / SAMPLE as u32;
+        println!("registry size {:>7}: {:>10.2?} per insert", checkpoint, mean);
+        means.push(mean);
+    }
+
+    // Generous bound for disk noise; the old blob rewrite grew ~100x over this range
+    assert!(means[2] < means[0] * 5, "insert time grew with registry size: {:?}", means);
+
+    assert_eq!(RPCStorage::get_transaction_count().await.unwrap(), inserted);
+}
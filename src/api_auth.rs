@@ -1,179 +1,241 @@
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use std::time::Instant;
 use axum::{
-    extract::Request,
-    http::{HeaderMap, StatusCode},
+    extract::{FromRequestParts, Request, State},
+    http::{request::Parts, HeaderMap, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
-use serde_json::{json, Value};
+use std::collections::HashMap;
+use once_cell::sync::Lazy;
+use serde_json::json;
 use sha2::{Digest, Sha256};
 use hex;
+use crate::rpc_storage::RPCStorage;
+use crate::storage::StorageError;
 
-// API Key structure
-#[derive(Debug, Clone)]
-pub struct ApiKey {
-    pub key_hash: String,
-    pub permissions: Vec<String>,
-    pub rate_limit: u32,
-    pub created_at: Instant,
-    pub last_used: Instant,
-    pub usage_count: u64,
-}
+/// Header carrying the caller's API key
+pub const API_KEY_HEADER: &str = "x-api-key";
 
-// API Authentication Manager
-#[derive(Debug)]
-pub struct ApiAuthManager {
-    api_keys: Arc<Mutex<HashMap<String, ApiKey>>>,
-    admin_key_hash: String,
+/// What an API key may do; each scope includes the ones below it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Scope {
+    Readonly,
+    Mining,
+    Admin,
 }
 
-impl ApiAuthManager {
-    pub fn new() -> Self {
-        let mut manager = Self {
-            api_keys: Arc::new(Mutex::new(HashMap::new())),
-            admin_key_hash: Self::hash_key("fvchain_admin_2025"),
-        };
-        
-        // Initialize with default API keys
-        manager.initialize_default_keys();
-        manager
+impl Scope {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Scope::Readonly => "readonly",
+            Scope::Mining => "mining",
+            Scope::Admin => "admin",
+        }
     }
-    
-    fn hash_key(key: &str) -> String {
-        let mut hasher = Sha256::new();
-        hasher.update(key.as_bytes());
-        hex::encode(hasher.finalize())
+
+    pub fn parse(value: &str) -> Option<Scope> {
+        match value {
+            "readonly" => Some(Scope::Readonly),
+            "mining" => Some(Scope::Mining),
+            "admin" => Some(Scope::Admin),
+            _ => None,
+        }
     }
-    
-    fn initialize_default_keys(&mut self) {
-        let mut keys = self.api_keys.lock().unwrap();
-        
-        // Public read-only key for blockchain data
-        let public_key = ApiKey {
-            key_hash: Self::hash_key("fvchain_public_readonly_2025"),
-            permissions: vec![
-                "read:blocks".to_string(),
-                "read:transactions".to_string(),
-                "read:network".to_string(),
-                "read:stats".to_string(),
-            ],
-            rate_limit: 300, // Higher limit for public access
-            created_at: Instant::now(),
-            last_used: Instant::now(),
-            usage_count: 0,
-        };
-        
-        // Mining operations key
-        let mining_key = ApiKey {
-            key_hash: Self::hash_key("fvchain_mining_2025"),
-            permissions: vec![
-                "read:mining".to_string(),
-                "write:mining".to_string(),
-                "read:stats".to_string(),
-            ],
-            rate_limit: 120,
-            created_at: Instant::now(),
-            last_used: Instant::now(),
-            usage_count: 0,
-        };
-        
-        // Wallet operations key
-        let wallet_key = ApiKey {
-            key_hash: Self::hash_key("fvchain_wallet_2025"),
-            permissions: vec![
-                "read:wallet".to_string(),
-                "write:wallet".to_string(),
-                "read:balance".to_string(),
-            ],
-            rate_limit: 180,
-            created_at: Instant::now(),
-            last_used: Instant::now(),
-            usage_count: 0,
-        };
-        
-        keys.insert("public".to_string(), public_key);
-        keys.insert("mining".to_string(), mining_key);
-        keys.insert("wallet".to_string(), wallet_key);
+
+    pub fn grants(&self, required: Scope) -> bool {
+        *self >= required
     }
-    
-    pub fn validate_api_key(&self, key: &str, required_permission: &str) -> bool {
-        // Check admin key
-        if Self::hash_key(key) == self.admin_key_hash {
-            return true;
+}
+
+pub fn hash_key(key: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(key.as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Store `key` (hashed) with `scope`, replacing any earlier scope
+pub async fn register_api_key(key: &str, scope: Scope) -> Result<(), StorageError> {
+    RPCStorage::set_api_key_scope(&hash_key(key), scope.as_str()).await
+}
+
+pub async fn revoke_api_key(key: &str) -> Result<(), StorageError> {
+    RPCStorage::remove_api_key(&hash_key(key)).await
+}
+
+/// Hashed keys named by FVC_ADMIN_API_KEY, FVC_MINING_API_KEY and FVC_READONLY_API_KEY.
+/// They live in memory only, so a key dropped from the environment stops working on restart.
+static ENV_API_KEYS: Lazy<HashMap<String, Scope>> = Lazy::new(|| {
+    [
+        ("FVC_ADMIN_API_KEY", Scope::Admin),
+        ("FVC_MINING_API_KEY", Scope::Mining),
+        ("FVC_READONLY_API_KEY", Scope::Readonly),
+    ]
+    .into_iter()
+    .filter_map(|(var, scope)| {
+        std::env::var(var).ok().filter(|key| !key.is_empty()).map(|key| (hash_key(&key), scope))
+    })
+    .collect()
+});
+
+/// Scopes that have a key in the environment
+pub fn env_api_key_scopes() -> Vec<Scope> {
+    let mut scopes: Vec<Scope> = ENV_API_KEYS.values().copied().collect();
+    scopes.sort();
+    scopes
+}
+
+fn auth_error(status: StatusCode, message: &str) -> Response {
+    (status, Json(json!({
+        "success": false,
+        "error": message
+    }))).into_response()
+}
+
+/// A valid X-API-Key; rejects with 401 when the header is missing or the key is unknown
+#[derive(Debug, Clone, Copy)]
+pub struct ApiKey {
+    pub scope: Scope,
+}
+
+impl ApiKey {
+    pub async fn from_headers(headers: &HeaderMap) -> Result<Self, Response> {
+        let key = headers.get(API_KEY_HEADER)
+            .and_then(|v| v.to_str().ok())
+            .ok_or_else(|| auth_error(StatusCode::UNAUTHORIZED, "API key required"))?;
+
+        let key_hash = hash_key(key);
+        if let Some(scope) = ENV_API_KEYS.get(&key_hash) {
+            return Ok(ApiKey { scope: *scope });
         }
-        
-        let mut keys = self.api_keys.lock().unwrap();
-        
-        for api_key in keys.values_mut() {
-            if Self::hash_key(key) == api_key.key_hash {
-                // Update usage statistics
-                api_key.last_used = Instant::now();
-                api_key.usage_count += 1;
-                
-                // Check permission
-                return api_key.permissions.iter().any(|perm| {
-                    perm == required_permission || perm.starts_with(&format!("{}:", required_permission.split(':').next().unwrap_or("")))
-                });
+        match RPCStorage::get_api_key_scope(&key_hash).await {
+            Ok(Some(scope)) => Scope::parse(&scope)
+                .map(|scope| ApiKey { scope })
+                .ok_or_else(|| auth_error(StatusCode::UNAUTHORIZED, "Invalid API key")),
+            Ok(None) => Err(auth_error(StatusCode::UNAUTHORIZED, "Invalid API key")),
+            Err(e) => {
+                log::error!("Failed to look up API key: {}", e);
+                Err(auth_error(StatusCode::INTERNAL_SERVER_ERROR, "Failed to verify API key"))
             }
         }
-        
-        false
     }
-    
-    pub fn get_api_key_stats(&self) -> Value {
-        let keys = self.api_keys.lock().unwrap();
-        let mut stats = json!({});
-        
-        for (name, key) in keys.iter() {
-            stats[name] = json!({
-                "permissions": key.permissions,
-                "rate_limit": key.rate_limit,
-                "usage_count": key.usage_count,
-                "last_used": key.last_used.elapsed().as_secs(),
-            });
+
+    /// 403 unless this key's scope covers `required`
+    pub fn require(&self, required: Scope) -> Result<(), Response> {
+        if self.scope.grants(required) {
+            Ok(())
+        } else {
+            Err(auth_error(
+                StatusCode::FORBIDDEN,
+                &format!("API key scope '{}' does not allow '{}' access", self.scope.as_str(), required.as_str()),
+            ))
         }
-        
-        stats
     }
 }
 
-// Global API Auth Manager
-lazy_static::lazy_static! {
-    pub static ref API_AUTH_MANAGER: ApiAuthManager = ApiAuthManager::new();
+#[axum::async_trait]
+impl<S: Send + Sync> FromRequestParts<S> for ApiKey {
+    type Rejection = Response;
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        ApiKey::from_headers(&parts.headers).await
+    }
 }
 
-// Authentication middleware - DISABLED
-// SEMUA VALIDASI API KEY TELAH DIHAPUS - AKSES TANPA PEMBATASAN
-pub async fn api_auth_middleware(
-    _headers: HeaderMap,
-    request: Request,
-    next: Next,
-) -> Response {
-    // SEMUA API ENDPOINT SEKARANG DAPAT DIAKSES TANPA VALIDASI API KEY
-    // LANGSUNG LANJUTKAN KE REQUEST BERIKUTNYA
+/// Check the request's key against `required`: Ok, or the 401/403 response to send
+pub async fn authorize(headers: &HeaderMap, required: Scope) -> Result<ApiKey, Response> {
+    let key = ApiKey::from_headers(headers).await?;
+    key.require(required)?;
+    Ok(key)
+}
+
+/// Route layer requiring a key with scope `required`;
+/// apply with `from_fn_with_state(Scope::Admin, require_scope)`
+pub async fn require_scope(State(required): State<Scope>, key: ApiKey, request: Request, next: Next) -> Response {
+    if let Err(response) = key.require(required) {
+        return response;
+    }
     next.run(request).await
 }
 
-// API key stats endpoint
-pub async fn api_key_stats() -> Json<Value> {
-    Json(json!({
-        "success": true,
-        "data": {
-            "api_keys": API_AUTH_MANAGER.get_api_key_stats(),
-            "public_endpoints": [
-                "/network/info",
-                "/network/health",
-                "/blocks",
-                "/transactions",
-                "/smart-rate/current",
-                "/mining/stats",
-                "/mining/detection/stats",
-                "/api/info",
-                "/events"
-            ]
+/// `require_scope` when `enabled`, otherwise a pass-through;
+/// apply with `from_fn_with_state((enabled, Scope::Mining), require_scope_if)`
+pub async fn require_scope_if(State((enabled, required)): State<(bool, Scope)>, request: Request, next: Next) -> Response {
+    if enabled {
+        if let Err(response) = authorize(request.headers(), required).await {
+            return response;
         }
-    }))
-}
\ No newline at end of file
+    }
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_storage::use_test_db;
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[test]
+    fn test_scopes_include_lower_scopes() {
+        assert!(Scope::Admin.grants(Scope::Mining));
+        assert!(Scope::Mining.grants(Scope::Readonly));
+        assert!(!Scope::Mining.grants(Scope::Admin));
+        assert!(!Scope::Readonly.grants(Scope::Mining));
+        assert_eq!(Scope::parse(Scope::Mining.as_str()), Some(Scope::Mining));
+    }
+
+    #[tokio::test]
+    async fn test_admin_route_requires_admin_key() {
+        let _db = use_test_db();
+        register_api_key("test-admin-key", Scope::Admin).await.unwrap();
+        register_api_key("test-mining-key", Scope::Mining).await.unwrap();
+
+        let app = Router::new().route(
+            "/admin/ping",
+            get(|| async { "pong" }).layer(axum::middleware::from_fn_with_state(Scope::Admin, require_scope)),
+        );
+        let status = |key: Option<&'static str>| {
+            let app = app.clone();
+            async move {
+                let mut request = axum::http::Request::get("/admin/ping");
+                if let Some(key) = key {
+                    request = request.header(API_KEY_HEADER, key);
+                }
+                app.oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+            }
+        };
+
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("not-a-registered-key")).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("test-mining-key")).await, StatusCode::FORBIDDEN);
+        assert_eq!(status(Some("test-admin-key")).await, StatusCode::OK);
+
+        // Keys are stored hashed, never in the clear
+        assert_eq!(RPCStorage::get_api_key_scope("test-admin-key").await.unwrap(), None);
+
+        revoke_api_key("test-mining-key").await.unwrap();
+        assert_eq!(status(Some("test-mining-key")).await, StatusCode::UNAUTHORIZED);
+    }
+
+    #[tokio::test]
+    async fn test_optional_scope_only_applies_when_enabled() {
+        let _db = use_test_db();
+        register_api_key("test-optional-mining-key", Scope::Mining).await.unwrap();
+
+        let app = |enabled| Router::new().route(
+            "/mobile/mining/start",
+            get(|| async { "started" }).layer(axum::middleware::from_fn_with_state((enabled, Scope::Mining), require_scope_if)),
+        );
+        let status = |enabled: bool, key: Option<&'static str>| async move {
+            let mut request = axum::http::Request::get("/mobile/mining/start");
+            if let Some(key) = key {
+                request = request.header(API_KEY_HEADER, key);
+            }
+            app(enabled).oneshot(request.body(Body::empty()).unwrap()).await.unwrap().status()
+        };
+
+        assert_eq!(status(false, None).await, StatusCode::OK);
+        assert_eq!(status(true, None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(true, Some("test-optional-mining-key")).await, StatusCode::OK);
+    }
+}
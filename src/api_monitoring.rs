@@ -1,9 +1,12 @@
 use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use serde::{Deserialize, Serialize};
 use tokio::time::interval;
 use lazy_static::lazy_static;
+use axum::{http::StatusCode, response::{IntoResponse, Json, Response}};
+use serde_json::json;
+use tower_http::catch_panic::CatchPanicLayer;
 
 // API metrics structure
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -105,7 +108,7 @@ impl ApiMonitor {
         
         // Store metric
         {
-            let mut metrics = self.metrics.lock().unwrap();
+            let mut metrics = self.metrics.lock().unwrap_or_else(PoisonError::into_inner);
             metrics.push(metric.clone());
             
             // Keep only last 10000 metrics to prevent memory issues
@@ -132,7 +135,7 @@ impl ApiMonitor {
     
     // Update endpoint statistics
     fn update_endpoint_stats(&self, metric: &ApiMetrics) {
-        let mut stats = self.endpoint_stats.lock().unwrap();
+        let mut stats = self.endpoint_stats.lock().unwrap_or_else(PoisonError::into_inner);
         let endpoint_stat = stats.entry(metric.endpoint.clone()).or_insert(EndpointStats {
             endpoint: metric.endpoint.clone(),
             total_requests: 0,
@@ -172,7 +175,7 @@ impl ApiMonitor {
     
     // Record security event
     pub fn record_security_event(&self, event: SecurityEvent) {
-        let mut events = self.security_events.lock().unwrap();
+        let mut events = self.security_events.lock().unwrap_or_else(PoisonError::into_inner);
         
         // Log security event
         match &event {
@@ -212,7 +215,7 @@ impl ApiMonitor {
     
     // Get endpoint statistics
     pub fn get_endpoint_stats(&self) -> HashMap<String, EndpointStats> {
-        let stats = self.endpoint_stats.lock().unwrap();
+        let stats = self.endpoint_stats.lock().unwrap_or_else(PoisonError::into_inner);
         let _current_time = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
@@ -237,7 +240,7 @@ impl ApiMonitor {
     
     // Get recent security events
     pub fn get_recent_security_events(&self, limit: usize) -> Vec<SecurityEvent> {
-        let events = self.security_events.lock().unwrap();
+        let events = self.security_events.lock().unwrap_or_else(PoisonError::into_inner);
         events.iter().rev().take(limit).cloned().collect()
     }
     
@@ -283,8 +286,8 @@ impl ApiMonitor {
             loop {
                 interval.tick().await;
                 
-                let stats_guard = stats.lock().unwrap();
-                let events_guard = events.lock().unwrap();
+                let stats_guard = stats.lock().unwrap_or_else(PoisonError::into_inner);
+                let events_guard = events.lock().unwrap_or_else(PoisonError::into_inner);
                 
                 println!("[API_STATS] Active endpoints: {}, Recent security events: {}", 
                     stats_guard.len(), events_guard.len());
@@ -317,6 +320,30 @@ lazy_static! {
 }
 
 // Helper function to get client IP from request
+/// 500 JSON response for a handler that panicked. Tokio lock guards held by the
+/// handler are dropped during unwinding; std locks on the request path recover from poisoning.
+pub fn panic_response(panic: Box<dyn std::any::Any + Send + 'static>) -> Response {
+    let detail = panic
+        .downcast_ref::<String>()
+        .map(String::as_str)
+        .or_else(|| panic.downcast_ref::<&str>().copied())
+        .unwrap_or("unknown panic");
+    log::error!("Request handler panicked: {}", detail);
+
+    (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+        "success": false,
+        "error": {
+            "code": "INTERNAL_ERROR",
+            "message": "Internal server error"
+        }
+    }))).into_response()
+}
+
+/// Layer converting handler panics into `panic_response` instead of dropping the connection
+pub fn catch_panic_layer() -> CatchPanicLayer<fn(Box<dyn std::any::Any + Send + 'static>) -> Response> {
+    CatchPanicLayer::custom(panic_response as fn(Box<dyn std::any::Any + Send + 'static>) -> Response)
+}
+
 pub fn extract_client_ip(headers: &axum::http::HeaderMap) -> String {
     // Check for forwarded headers first (behind proxy)
     if let Some(forwarded_for) = headers.get("x-forwarded-for") {
@@ -394,4 +421,46 @@ mod tests {
         let events = monitor.get_recent_security_events(10);
         assert_eq!(events.len(), 1);
     }
+
+    #[tokio::test]
+    async fn test_handler_panic_returns_500_and_releases_locks() {
+        use axum::{body::Body, routing::get, Router};
+        use tower::ServiceExt;
+
+        let shared = Arc::new(tokio::sync::Mutex::new(0u32));
+        let app = Router::new()
+            .route("/panic", get({
+                let shared = shared.clone();
+                move || async move {
+                    let mut calls = shared.lock().await;
+                    *calls += 1;
+                    if *calls > 0 {
+                        panic!("handler failed while holding the lock");
+                    }
+                    calls.to_string()
+                }
+            }))
+            .route("/count", get({
+                let shared = shared.clone();
+                move || async move {
+                    let calls = tokio::time::timeout(Duration::from_secs(1), shared.lock())
+                        .await
+                        .expect("lock still held after panic");
+                    calls.to_string()
+                }
+            }))
+            .layer(catch_panic_layer());
+
+        let request = |uri: &str| axum::http::Request::get(uri).body(Body::empty()).unwrap();
+        let response = app.clone().oneshot(request("/panic")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["error"]["code"], "INTERNAL_ERROR");
+
+        let response = app.oneshot(request("/count")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"1");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,31 @@
+//! Chaos harness: perturbs random blocks and checks the fractal invariants.
+//! Usage: cargo run --bin chaos -- [iterations]
+
+use fractal_vortex_chain::security::ChaosTester;
+
+const DEFAULT_ITERATIONS: usize = 1000;
+
+fn main() {
+    let iterations = match std::env::args().nth(1) {
+        Some(arg) => match arg.parse() {
+            Ok(iterations) => iterations,
+            Err(_) => {
+                eprintln!("Error: iterations must be a non-negative integer, got '{}'", arg);
+                std::process::exit(2);
+            }
+        },
+        None => DEFAULT_ITERATIONS,
+    };
+
+    println!("🌀 Running fractal invariants over {} iterations...", iterations);
+    let report = ChaosTester::new().run_fractal_invariants(iterations);
+
+    for violation in &report.violations {
+        println!("❌ [{}] iteration {}: {}", violation.invariant, violation.iteration, violation.details);
+    }
+    println!("{}", report.summary);
+
+    if !report.is_clean() {
+        std::process::exit(1);
+    }
+}
@@ -3,18 +3,43 @@ use axum::{
     Router,
     response::{Json, IntoResponse},
     extract::{State, Path, Query, rejection::QueryRejection, rejection::JsonRejection},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
 };
 use std::collections::HashMap;
 use serde::Deserialize;
 use serde_json::json;
 use chrono::Utc;
 use fractal_vortex_chain::mining::auto_detection::{MiningAutoDetection, AutoDetectionConfig, HeartbeatRequest};
+use fractal_vortex_chain::mining::template::{BlockTemplate, TemplateError};
+use fractal_vortex_chain::mining::mining_address_from_env;
+use fractal_vortex_chain::consensus::MiningRewardSystem;
 // Mobile API functionality is now integrated directly in this server
 
-use fractal_vortex_chain::rpc_storage::{RPCStorage, WalletTransaction};
+use fractal_vortex_chain::rpc_storage::{RPCStorage, WalletTransaction, paginate_history, ADDRESS_HISTORY_CAP, MAX_DIFFICULTY_HISTORY_SPAN, VortexPatternConfig, CONFIRMATION_DEPTH, confirmations, is_finalized, looks_like_plaintext_key, DEVICE_TRANSFER_FEE, MAX_BALANCE_BATCH};
 use fractal_vortex_chain::storage::StorageError;
-use fractal_vortex_chain::node::fractal_node::{FractalNode, NodeConfig};
+use fractal_vortex_chain::history_export::address_history_csv;
+use fractal_vortex_chain::chain_verify::check_stored_block_hash;
+use fractal_vortex_chain::faucet::{Faucet, FaucetConfig};
+use fractal_vortex_chain::block_stream::subscribe_blocks;
+use fractal_vortex_chain::input_validation::{validate_amount_with_fee, validate_device_id, RequestValidator};
+use fractal_vortex_chain::shared::validate_tx_address;
+use fractal_vortex_chain::api_auth::{env_api_key_scopes, require_scope, require_scope_if, ApiKey, Scope};
+use fractal_vortex_chain::debug_api::{self, DEBUG_TX_LIMIT};
+use fractal_vortex_chain::server_time::{server_time_middleware, time_endpoint};
+use fractal_vortex_chain::json_rpc::{self, RpcError};
+use fractal_vortex_chain::tx_submission::{gossip_transaction, submit_new_signed_transfer, SubmissionResult, SubmissionStatus};
+use fractal_vortex_chain::fee_estimate::current_fee_estimate;
+use fractal_vortex_chain::metrics_exporter::{render_prometheus, ChainMetrics, PROMETHEUS_CONTENT_TYPE};
+use fractal_vortex_chain::smart_rate::{self, SmartRate};
+use fractal_vortex_chain::explorer_search::{self, SearchResult};
+use fractal_vortex_chain::rate_limiter::{anomaly_response_middleware, rate_limit_middleware, REQUEST_ANOMALY_GUARD, REQUEST_RATE_LIMITS};
+use fractal_vortex_chain::api_monitoring::catch_panic_layer;
+use fractal_vortex_chain::shutdown::{serve_until, shutdown_signal, SHUTDOWN_GRACE};
+use fractal_vortex_chain::security::{AnomalyDetector, AnomalyResponsePolicy};
+use fractal_vortex_chain::security::monitoring::{request_metrics_middleware, SECURITY_MONITOR};
+use fractal_vortex_chain::network::build_listen_addr;
+use fractal_vortex_chain::node::load_balancer::{ClusterError, HealthSample, judge_health, LoadBalanceStrategy, LoadBalancer, record_served_by, served_by_middleware, SERVED_BY_HEADER};
+use fractal_vortex_chain::node::fractal_node::{FractalNode, NodeConfig, NODE_INIT_ATTEMPTS, NODE_INIT_BACKOFF};
 
 
 use serde_json::Value;
@@ -34,8 +59,8 @@ use tokio::sync::broadcast;
 use std::convert::Infallible;
 use chrono;
 use std::io::Write;
-use libp2p::Multiaddr;
-use fractal_vortex_chain::wallet::key_manager::KeyManager;
+use fractal_vortex_chain::wallet::key_manager::{AddressError, KeyManager};
+use fractal_vortex_chain::wallet::ConfirmationStatus;
 use std::fs::OpenOptions;
 use hex;
 use std::sync::atomic::{AtomicUsize, Ordering};
@@ -55,61 +80,44 @@ static GLOBAL_ECOSYSTEM_MINER: Lazy<Arc<tokio::sync::Mutex<Option<fractal_vortex
     Arc::new(tokio::sync::Mutex::new(None))
 });
 
-// Round-robin load balancer counter
+// Requests distributed by the load balancer
 static LOAD_BALANCER_COUNTER: AtomicUsize = AtomicUsize::new(0);
 
 // NodeManager for managing 4 blockchain nodes
 struct NodeManager {
     nodes: Arc<tokio::sync::Mutex<Vec<Option<FractalNode>>>>,
     health: Arc<tokio::sync::RwLock<Vec<bool>>>,
-    counter: Arc<AtomicUsize>,
+    balancer: LoadBalancer,
 }
 
 impl NodeManager {
-    fn new() -> Self {
+    fn new(strategy: LoadBalanceStrategy) -> Self {
         Self {
             nodes: BLOCKCHAIN_NODES.clone(),
             health: NODE_HEALTH.clone(),
-            counter: Arc::new(AtomicUsize::new(0)),
+            balancer: LoadBalancer::new(NODE_HEALTH.clone(), strategy),
         }
     }
     
-    // Get next healthy node using round-robin load balancing
-    async fn get_healthy_node(&self) -> Option<usize> {
-        let health = self.health.read().await;
-        let healthy_nodes: Vec<usize> = health.iter()
-            .enumerate()
-            .filter_map(|(i, &is_healthy)| if is_healthy { Some(i) } else { None })
-            .collect();
-        
-        if healthy_nodes.is_empty() {
-            return None;
-        }
-        
-        let counter = self.counter.fetch_add(1, Ordering::Relaxed);
-        Some(healthy_nodes[counter % healthy_nodes.len()])
-    }
-    
-    // Execute operation on a healthy node
-    async fn execute_on_node<F, R>(&self, operation: F) -> Result<R, String>
+    // Execute operation on the healthy node picked by the balancer, recording it for the X-FVC-Node-Id header
+    async fn execute_on_node<F, R, E>(&self, operation: F) -> Result<(usize, R), ClusterError>
     where
-        F: Fn(&FractalNode) -> R,
+        F: FnOnce(&FractalNode) -> Result<R, E>,
+        E: std::fmt::Display,
     {
-        if let Some(node_index) = self.get_healthy_node().await {
-            let nodes = self.nodes.lock().await;
-            if let Some(Some(node)) = nodes.get(node_index) {
-                return Ok(operation(node));
-            }
-        }
-        Err("No healthy nodes available".to_string())
+        let nodes = self.nodes.lock().await;
+        let (node_index, result) = self.balancer.execute(&nodes[..], operation).await?;
+        LOAD_BALANCER_COUNTER.fetch_add(1, Ordering::Relaxed);
+        record_served_by(node_index);
+        Ok((node_index, result))
     }
     
     // Update node health status
-    async fn update_node_health(&self, node_index: usize, is_healthy: bool) {
+    async fn update_node_health(&self, node_index: usize, is_healthy: bool) -> Result<(), ClusterError> {
         let mut health = self.health.write().await;
-        if node_index < health.len() {
-            health[node_index] = is_healthy;
-        }
+        let slot = health.get_mut(node_index).ok_or(ClusterError::NodeUnavailable(node_index))?;
+        *slot = is_healthy;
+        Ok(())
     }
     
     // Get node health statistics
@@ -121,8 +129,17 @@ impl NodeManager {
     }
 }
 
-// Global NodeManager instance
-static NODE_MANAGER: Lazy<NodeManager> = Lazy::new(|| NodeManager::new());
+// Global NodeManager instance; LOAD_BALANCE_STRATEGY picks round_robin (default), least_connections or random
+static NODE_MANAGER: Lazy<NodeManager> = Lazy::new(|| {
+    let strategy = match std::env::var("LOAD_BALANCE_STRATEGY") {
+        Ok(value) => LoadBalanceStrategy::parse(&value).unwrap_or_else(|| {
+            log::warn!("Unknown LOAD_BALANCE_STRATEGY '{}', using round_robin", value);
+            LoadBalanceStrategy::RoundRobin
+        }),
+        Err(_) => LoadBalanceStrategy::default(),
+    };
+    NodeManager::new(strategy)
+});
 
 // Legacy compatibility - get primary node (node 0)
 static BLOCKCHAIN_NODE: Lazy<Arc<tokio::sync::Mutex<Option<FractalNode>>>> = Lazy::new(|| {
@@ -135,16 +152,84 @@ static BROADCAST: Lazy<broadcast::Sender<String>> = Lazy::new(|| {
     tx
 });
 
+// Watches outgoing transfers for bursts and amount spikes
+static TX_ANOMALY_DETECTOR: Lazy<AnomalyDetector> = Lazy::new(AnomalyDetector::new);
+
 static AUTO_DETECTION: Lazy<Arc<MiningAutoDetection>> = Lazy::new(|| {
-    let config = AutoDetectionConfig {
+    let defaults = AutoDetectionConfig {
         heartbeat_timeout: Duration::from_secs(30),
         check_interval: Duration::from_secs(10),
         grace_period: Duration::from_secs(60),
         max_retry_attempts: 3,
+        resume_window: Duration::from_secs(300),
+    };
+    let config = defaults.clone().with_env_overrides();
+    let config = match config.validate() {
+        Ok(()) => config,
+        Err(e) => {
+            log::warn!("Invalid auto-detection config ({}), using defaults", e);
+            defaults
+        }
     };
     Arc::new(MiningAutoDetection::new(config))
 });
 
+// Outstanding block templates for external miners
+static MINING_TEMPLATES: Lazy<RwLock<MiningTemplates>> = Lazy::new(|| {
+    RwLock::new(MiningTemplates::default())
+});
+
+/// Upper bound on outstanding templates so polling miners cannot grow memory
+const MAX_OUTSTANDING_TEMPLATES: usize = 256;
+/// Templates one miner address may hold; asking for more replaces its own oldest
+const MAX_TEMPLATES_PER_MINER: usize = 8;
+
+/// Block templates keyed by template id, each with the order it was issued in
+#[derive(Default)]
+struct MiningTemplates {
+    issued: u64,
+    templates: HashMap<String, (u64, BlockTemplate)>,
+}
+
+impl MiningTemplates {
+    fn get(&self, template_id: &str) -> Option<&BlockTemplate> {
+        self.templates.get(template_id).map(|(_, template)| template)
+    }
+
+    fn remove(&mut self, template_id: &str) {
+        self.templates.remove(template_id);
+    }
+
+    fn retain(&mut self, mut keep: impl FnMut(&BlockTemplate) -> bool) {
+        self.templates.retain(|_, (_, template)| keep(template));
+    }
+
+    /// Keep `template`, making room by evicting the same miner's oldest template past
+    /// MAX_TEMPLATES_PER_MINER, then the oldest of all past MAX_OUTSTANDING_TEMPLATES, so one
+    /// caller asking for templates cannot throw away every other miner's work
+    fn insert(&mut self, template_id: String, template: BlockTemplate) {
+        let miner_count = self.templates.values().filter(|(_, t)| t.miner == template.miner).count();
+        if miner_count >= MAX_TEMPLATES_PER_MINER {
+            self.evict_oldest(|t| t.miner == template.miner);
+        }
+        if self.templates.len() >= MAX_OUTSTANDING_TEMPLATES {
+            self.evict_oldest(|_| true);
+        }
+        self.issued += 1;
+        self.templates.insert(template_id, (self.issued, template));
+    }
+
+    fn evict_oldest(&mut self, matches: impl Fn(&BlockTemplate) -> bool) {
+        let oldest = self.templates.iter()
+            .filter(|(_, (_, template))| matches(template))
+            .min_by_key(|(_, (issued, _))| *issued)
+            .map(|(id, _)| id.clone());
+        if let Some(id) = oldest {
+            self.templates.remove(&id);
+        }
+    }
+}
+
 #[derive(Clone)]
 struct AppState {
     latest_block: Arc<RwLock<u64>>,
@@ -158,10 +243,6 @@ async fn get_block_height() -> u64 {
     RPCStorage::get_block_height().await.unwrap_or(1)
 }
 
-async fn get_transaction_count() -> u64 {
-    RPCStorage::get_transaction_count().await.unwrap_or(0)
-}
-
 #[allow(dead_code)]
 async fn add_transaction(tx: WalletTransaction) {
     let _ = RPCStorage::add_transaction(&tx).await;
@@ -171,141 +252,30 @@ async fn get_latest_transactions(limit: usize) -> Vec<WalletTransaction> {
     RPCStorage::get_latest_transactions(limit).await.unwrap_or_default()
 }
 
-// Advanced calculation functions from rpc-server - OFFICIAL SMART RATE IMPLEMENTATION
-async fn calculate_vortex_energy_rate() -> f64 {
-    let block_height = get_block_height().await;
-    let transaction_count = get_transaction_count().await;
-    
-    if block_height == 0 {
-        return 369.0; // Base fractal energy for genesis
-    }
-    
-    let base_energy = 369.0; // Base fractal energy constant
-    let vortex_pattern = [1.0, 2.0, 4.0, 8.0, 7.0, 5.0];
-    let pattern_index = (block_height % 6) as usize;
-    let pattern_multiplier = vortex_pattern[pattern_index];
-    
-    // Network activity factor
-    let network_activity = (transaction_count as f64 / block_height as f64).min(10.0);
-    
-    base_energy * pattern_multiplier * (1.0 + network_activity * 0.1)
-}
-
-async fn calculate_fractal_contribution_score() -> f64 {
-    let block_height = get_block_height().await;
-    let transaction_count = get_transaction_count().await;
-    
-    if block_height == 0 {
-        return 0.0;
-    }
-    
-    let sierpinski_dimension = 1.585;
-    let block_contribution = (block_height as f64).log2() * sierpinski_dimension;
-    let tx_contribution = (transaction_count as f64).sqrt() * 0.1;
-    
-    (block_contribution + tx_contribution).min(100.0)
-}
-
-async fn calculate_mathematical_efficiency_index() -> f64 {
-    let block_height = get_block_height().await;
-    
-    if block_height == 0 {
-        return 100.0; // Genesis block efficiency
-    }
-    
-    let target_block_time = 5.0; // 5 seconds target
-    let current_time = chrono::Utc::now().timestamp() as f64;
-    let genesis_time = 1640995200.0; // FVChain genesis timestamp
-    
-    // Calculate actual average block time
-    let elapsed_time = current_time - genesis_time;
-    let actual_avg_time = elapsed_time / block_height as f64;
-    
-    // Time efficiency calculation
-    let time_efficiency = (target_block_time / actual_avg_time).min(2.0);
-    
-    (time_efficiency * 50.0).min(100.0)
-}
-
-async fn calculate_network_harmony_factor() -> f64 {
-    let block_height = get_block_height().await;
-    let active_nodes = check_active_nodes().await;
-    
-    if block_height == 0 || active_nodes == 0 {
-        return 0.0;
-    }
-    
-    let golden_ratio = 1.618033988749;
-    let node_harmony = (active_nodes as f64).log2() * golden_ratio;
-    
-    let network_consistency = if block_height > 0 {
-        let consistency_factor = (block_height as f64 / active_nodes.max(1) as f64).min(10.0);
-        consistency_factor * 0.1
-    } else {
-        0.0
-    };
-    
-    ((node_harmony + network_consistency) * 10.0).min(100.0)
-}
-
-async fn calculate_smart_rate() -> f64 {
-    let block_height = get_block_height().await;
-    
-    // Calculate all Smart Rate components
-    let ver = calculate_vortex_energy_rate().await;
-    let fcs = calculate_fractal_contribution_score().await;
-    let mei = calculate_mathematical_efficiency_index().await;
-    let nhf = calculate_network_harmony_factor().await;
-    
-    // Normalize components to 0-1 scale
-    let ver_normalized = (ver / 5000.0).min(1.0);
-    let fcs_normalized = fcs / 100.0;
-    let mei_normalized = mei / 100.0;
-    let nhf_normalized = nhf / 100.0;
-    
-    // Calculate weighted geometric mean with official weights
-    // VER: 35%, FCS: 25%, MEI: 25%, NHF: 15%
-    let weighted_product = ver_normalized.powf(0.35) *
-                          fcs_normalized.powf(0.25) *
-                          mei_normalized.powf(0.25) *
-                          nhf_normalized.powf(0.15);
-    
-    // Apply base Smart Rate and vortex pattern
-    let base_smart_rate = 1000.0;
-    let smart_rate = base_smart_rate * weighted_product;
-    
-    // Apply vortex pattern based on block height
-    let vortex_pattern = [1.0, 1.2, 1.4, 1.8, 1.7, 1.5];
-    let pattern_index = (block_height % 6) as usize;
-    let pattern_multiplier = vortex_pattern[pattern_index];
-    
-    smart_rate * pattern_multiplier
-}
-
-pub async fn check_active_nodes() -> u32 {
-    // Get healthy blockchain nodes count from NODE_HEALTH
-    let health = NODE_HEALTH.read().await;
-    let healthy_count = health.iter().filter(|&&h| h).count() as u32;
-    
-    // If no healthy nodes from multi-node setup, fallback to single node check
-    if healthy_count == 0 {
-        let node_guard = BLOCKCHAIN_NODE.lock().await;
-        if node_guard.is_some() { 1 } else { 0 }
-    } else {
-        healthy_count
+// Smart Rate of the stored chain with its component breakdown
+async fn calculate_smart_rate() -> SmartRate {
+    match RPCStorage::get_smart_rate().await {
+        Ok(rate) => rate,
+        Err(e) => {
+            log::warn!("Failed to read chain state for Smart Rate: {}", e);
+            smart_rate::compute(0, 0)
+        }
     }
 }
 
-// Production mining function
-async fn start_production_mining(device_id: &str, mining_address: &str) -> Result<(), String> {
+// Production mining function. Callers hold the GLOBAL_ECOSYSTEM_MINER lock for the whole
+// start, device bookkeeping included, so a concurrent stop cannot interleave.
+async fn start_production_mining(
+    global_miner: &mut Option<fractal_vortex_chain::node::ecosystem_miner::EcosystemMiner>,
+    device_id: &str,
+    mining_address: &str,
+) -> Result<(), String> {
     use fractal_vortex_chain::node::ecosystem_miner::EcosystemMiner;
     use fractal_vortex_chain::consensus::vortex_consensus::VortexConsensus;
     use fractal_vortex_chain::node::fractal_node::NodeState;
     use std::sync::Arc;
     use tokio::sync::RwLock;
     
-    let mut global_miner = GLOBAL_ECOSYSTEM_MINER.lock().await;
-    
     // Check if miner is already running
     if let Some(ref miner) = *global_miner {
         if miner.is_running() {
@@ -323,6 +293,7 @@ async fn start_production_mining(device_id: &str, mining_address: &str) -> Resul
         connected_peers: Vec::new(),
         last_sync: 0,
         block_height: 0,
+        head_hash: None,
         total_transactions: 0,
     }));
     
@@ -365,7 +336,8 @@ async fn start_miner(payload: Result<Json<StartMinerRequest>, JsonRejection>) ->
 }
 
 async fn start_miner_impl(payload: StartMinerRequest) -> Json<Value> {
-    match start_production_mining(&payload.device_id, &payload.address).await {
+    let mut global_miner = GLOBAL_ECOSYSTEM_MINER.lock().await;
+    match start_production_mining(&mut global_miner, &payload.device_id, &payload.address).await {
         Ok(_) => {
             // Save device session to track mining status
             let session_id = format!("session_{}_{}", payload.device_id, Utc::now().timestamp());
@@ -410,10 +382,11 @@ async fn start_miner_impl(payload: StartMinerRequest) -> Json<Value> {
     }
 }
 
-// Production mining stop function
-async fn stop_production_mining(device_id: &str) -> Result<(), String> {
-    let mut global_miner = GLOBAL_ECOSYSTEM_MINER.lock().await;
-    
+// Production mining stop function; callers hold the GLOBAL_ECOSYSTEM_MINER lock as for start
+async fn stop_production_mining(
+    global_miner: &mut Option<fractal_vortex_chain::node::ecosystem_miner::EcosystemMiner>,
+    device_id: &str,
+) -> Result<(), String> {
     if let Some(miner) = global_miner.take() {
         match miner.stop().await {
             Ok(_) => {
@@ -455,7 +428,8 @@ async fn stop_miner(payload: Result<Json<StopMinerRequest>, JsonRejection>) -> i
 
 async fn stop_miner_impl(payload: StopMinerRequest) -> Json<Value> {
     // Stop production mining
-    match stop_production_mining(&payload.device_id).await {
+    let mut global_miner = GLOBAL_ECOSYSTEM_MINER.lock().await;
+    match stop_production_mining(&mut global_miner, &payload.device_id).await {
         Ok(_) => {
             // Remove device session to stop mining status
             if let Err(e) = RPCStorage::remove_device_session(&payload.device_id).await {
@@ -526,22 +500,35 @@ async fn get_blockchain_info(State(state): State<AppState>) -> Json<Value> {
     let latest_block = *state.latest_block.read().await;
     let total_transactions = *state.total_transactions.read().await;
     let active_nodes = *state.active_nodes.read().await;
+    let smart_rate = calculate_smart_rate().await;
     
     Json(json!({
         "latest_block": latest_block,
         "total_transactions": total_transactions,
         "active_nodes": active_nodes,
         "network_status": "active",
-        "smart_rate": calculate_smart_rate().await,
-        "vortex_energy_rate": calculate_vortex_energy_rate().await,
-        "fractal_contribution_score": calculate_fractal_contribution_score().await
+        "smart_rate": smart_rate.smart_rate,
+        "vortex_energy_rate": smart_rate.vortex_energy_rate,
+        "fractal_contribution_score": smart_rate.fractal_contribution_score
     }))
 }
 
-#[derive(Deserialize)]
-struct LimitQuery {
-    #[serde(deserialize_with = "deserialize_limit")]
+#[derive(Deserialize, Default)]
+struct BlocksQuery {
+    #[serde(default, deserialize_with = "deserialize_limit")]
+    limit: Option<usize>,
+    /// Only blocks below this height, newest first
+    before_height: Option<u64>,
+    /// Only blocks above this height; oldest first unless before_height is also set
+    after_height: Option<u64>,
+}
+
+#[derive(Deserialize, Default)]
+struct TransactionsQuery {
+    #[serde(default, deserialize_with = "deserialize_limit")]
     limit: Option<usize>,
+    /// Only transactions older than this one
+    before_hash: Option<String>,
 }
 
 fn deserialize_limit<'de, D>(deserializer: D) -> Result<Option<usize>, D::Error>
@@ -662,24 +649,31 @@ fn handle_json_rejection(rejection: JsonRejection) -> (StatusCode, Json<Value>)
     (status, json_response)
 }
 
-async fn get_blocks(State(_state): State<AppState>, query: Result<Query<LimitQuery>, QueryRejection>) -> impl IntoResponse {
-    let limit = match query {
-        Ok(Query(q)) => q.limit.unwrap_or(10),
-        Err(_) => 10,
-    };
-
-    let capped_limit = std::cmp::min(limit, 100);
+async fn get_blocks(State(_state): State<AppState>, query: Result<Query<BlocksQuery>, QueryRejection>) -> impl IntoResponse {
+    let query = query.map(|Query(q)| q).unwrap_or_default();
+    let capped_limit = std::cmp::min(query.limit.unwrap_or(10), 100);
     
-    match RPCStorage::get_latest_blocks(capped_limit).await {
-        Ok(blocks) => {
+    match RPCStorage::get_blocks_page(query.before_height, query.after_height, capped_limit).await {
+        Ok(page) => {
+            let blocks = page.blocks;
             // Calculate Smart Rate and vPoW indicators once for all blocks
-            let ver = calculate_vortex_energy_rate().await;
-            let fcs = calculate_fractal_contribution_score().await;
-            let mei = calculate_mathematical_efficiency_index().await;
-            let nhf = calculate_network_harmony_factor().await;
-            let smart_rate = calculate_smart_rate().await;
+            let rate = calculate_smart_rate().await;
+
+            // Seconds since each block's parent; null for genesis or a missing parent
+            let timestamps: HashMap<u64, u64> = blocks.iter().map(|b| (b.height, b.timestamp)).collect();
+            let mut block_times = Vec::with_capacity(blocks.len());
+            for block in &blocks {
+                let parent_timestamp = match block.height.checked_sub(1) {
+                    Some(parent) => match timestamps.get(&parent) {
+                        Some(ts) => Some(*ts),
+                        None => RPCStorage::get_block_by_height(parent).await.ok().flatten().map(|b| b.timestamp),
+                    },
+                    None => None,
+                };
+                block_times.push(parent_timestamp.map(|ts| block.timestamp.saturating_sub(ts)));
+            }
             
-            let block_data: Vec<Value> = blocks.into_iter().map(|block| {
+            let block_data: Vec<Value> = blocks.into_iter().zip(block_times).map(|(block, block_time)| {
                 json!({
                     "height": block.height,
                     "hash": block.hash,
@@ -689,20 +683,22 @@ async fn get_blocks(State(_state): State<AppState>, query: Result<Query<LimitQue
                     "transaction_count": block.transaction_count,
                     "miner": block.miner,
                     "difficulty": block.difficulty,
+                    "cumulative_difficulty": block.cumulative_difficulty,
                     "nonce": block.nonce,
-                    "block_time": 5.0,
-                    "smart_rate": smart_rate,
-                    "vortex_energy_rate": ver,
-                    "fractal_contribution_score": fcs,
-                    "mathematical_efficiency_index": mei,
-                    "network_harmony_factor": nhf
+                    "block_time": block_time,
+                    "smart_rate": rate.smart_rate,
+                    "vortex_energy_rate": rate.vortex_energy_rate,
+                    "fractal_contribution_score": rate.fractal_contribution_score,
+                    "mathematical_efficiency_index": rate.mathematical_efficiency_index,
+                    "network_harmony_factor": rate.network_harmony_factor
                 })
             }).collect();
             
             Json(json!({
                 "success": true,
                 "blocks": block_data,
-                "count": block_data.len()
+                "count": block_data.len(),
+                "next_cursor": page.next_cursor
             })).into_response()
         },
         Err(e) => {
@@ -714,20 +710,26 @@ async fn get_blocks(State(_state): State<AppState>, query: Result<Query<LimitQue
     }
 }
 
-async fn get_transactions(State(_state): State<AppState>, query: Result<Query<LimitQuery>, QueryRejection>) -> impl IntoResponse {
-    let limit = match query {
-        Ok(Query(q)) => q.limit.unwrap_or(10),
-        Err(_) => 10,
-    };
+async fn get_transactions(State(_state): State<AppState>, query: Result<Query<TransactionsQuery>, QueryRejection>) -> impl IntoResponse {
+    let query = query.map(|Query(q)| q).unwrap_or_default();
+    let capped_limit = std::cmp::min(query.limit.unwrap_or(10), 100);
 
-    let capped_limit = std::cmp::min(limit, 100);
-    let transactions = get_latest_transactions(capped_limit).await;
-    
-    Json(json!({
-        "success": true,
-        "transactions": transactions,
-        "count": transactions.len()
-    })).into_response()
+    match RPCStorage::get_transactions_page(query.before_hash.as_deref(), capped_limit).await {
+        Ok(page) => Json(json!({
+            "success": true,
+            "transactions": page.transactions,
+            "count": page.transactions.len(),
+            "next_cursor": page.next_cursor
+        })).into_response(),
+        Err(StorageError::NotFound(e)) => (StatusCode::BAD_REQUEST, Json(json!({
+            "success": false,
+            "error": format!("Unknown cursor: {}", e)
+        }))).into_response(),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": format!("Failed to retrieve transactions: {}", e)
+        })).into_response(),
+    }
 }
 
 #[allow(dead_code)]
@@ -753,6 +755,7 @@ async fn get_block(Path(height): Path<u64>) -> Json<Value> {
                     "transactions": block.transactions,
                     "miner": block.miner,
                     "difficulty": block.difficulty,
+                    "cumulative_difficulty": block.cumulative_difficulty,
                     "nonce": block.nonce
                 }
             }))
@@ -775,38 +778,14 @@ async fn get_block(Path(height): Path<u64>) -> Json<Value> {
 
 #[allow(dead_code)]
 async fn verify_block_hash(Path(height): Path<u64>) -> Json<Value> {
-    match RPCStorage::get_block_by_height(height).await {
-        Ok(Some(block)) => {
-            use fractal_vortex_chain::crypto::fractal_hash::FractalHasher;
-            
-            let mut hasher = FractalHasher::new(3);
-            
-            // Serialize block data for hashing
-            let mut block_data = Vec::new();
-            block_data.extend_from_slice(&height.to_le_bytes());
-            block_data.extend_from_slice(block.parent_hash.as_bytes());
-            block_data.extend_from_slice(&block.timestamp.to_le_bytes());
-            
-            // Serialize transactions
-            for tx in &block.transactions {
-                block_data.extend_from_slice(tx.hash.as_bytes());
-            }
-            
-            block_data.extend_from_slice(block.miner.as_bytes());
-            block_data.extend_from_slice(&block.difficulty.to_le_bytes());
-            block_data.extend_from_slice(&block.nonce.to_le_bytes());
-            
-            let vortex_hash = hasher.fractal_hash(&block_data);
-            let calculated_hash = format!("0x{}", hex::encode(vortex_hash.fractal_hash));
-            
-            let is_valid = calculated_hash == block.hash;
-            
+    match check_stored_block_hash(height).await {
+        Ok(Some(check)) => {
             Json(json!({
                 "success": true,
                 "height": height,
-                "stored_hash": block.hash,
-                "calculated_hash": calculated_hash,
-                "is_valid": is_valid
+                "stored_hash": check.stored_hash,
+                "calculated_hash": check.calculated_hash,
+                "is_valid": check.is_valid
             }))
         },
         Ok(None) => {
@@ -832,36 +811,16 @@ async fn verify_all_blocks() -> Json<Value> {
     let mut invalid_count = 0;
     
     for height in 1..=current_height {
-        match RPCStorage::get_block_by_height(height).await {
-            Ok(Some(block)) => {
-                use fractal_vortex_chain::crypto::fractal_hash::FractalHasher;
-                
-                let mut hasher = FractalHasher::new(3);
-                // Serialize block data for hashing
-                let mut block_data = Vec::new();
-                block_data.extend_from_slice(&height.to_le_bytes());
-                block_data.extend_from_slice(block.parent_hash.as_bytes());
-                block_data.extend_from_slice(&block.timestamp.to_le_bytes());
-                block_data.extend_from_slice(&block.nonce.to_le_bytes());
-                block_data.extend_from_slice(&block.difficulty.to_le_bytes());
-                block_data.extend_from_slice(block.miner.as_bytes());
-                
-                let calculated_hash = hasher.fractal_hash(&block_data);
-                
-                let calculated_hash_string = format!("0x{}", calculated_hash.fractal_hash.iter().map(|b| format!("{:02x}", b)).collect::<String>());
-                let is_valid = calculated_hash_string == block.hash;
-                
-                if is_valid {
-                    valid_count += 1;
-                } else {
-                    invalid_count += 1;
-                    verification_results.push(json!({
-                        "height": height,
-                        "stored_hash": block.hash,
-                        "calculated_hash": calculated_hash_string,
-                        "is_valid": false
-                    }));
-                }
+        match check_stored_block_hash(height).await {
+            Ok(Some(check)) if check.is_valid => valid_count += 1,
+            Ok(Some(check)) => {
+                invalid_count += 1;
+                verification_results.push(json!({
+                    "height": height,
+                    "stored_hash": check.stored_hash,
+                    "calculated_hash": check.calculated_hash,
+                    "is_valid": false
+                }));
             },
             Ok(None) => {
                 invalid_count += 1;
@@ -924,6 +883,40 @@ async fn blocks_sse_endpoint() -> Sse<impl Stream<Item = Result<Event, Infallibl
     Sse::new(stream)
 }
 
+#[derive(Deserialize)]
+struct BlockStreamQuery {
+    from_height: Option<u64>,
+}
+
+// v2 block stream: every stored block as `{"type": "new_block", "block": ...}`;
+// `from_height` replays history from that height first
+async fn blocks_replay_sse_endpoint(query: Result<Query<BlockStreamQuery>, QueryRejection>) -> impl IntoResponse {
+    let from_height = match query {
+        Ok(Query(q)) => q.from_height,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({
+            "success": false,
+            "error": format!("Invalid query: {}", e)
+        }))).into_response(),
+    };
+
+    let blocks = match subscribe_blocks(from_height).await {
+        Ok(blocks) => blocks,
+        Err(e) => return (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "success": false,
+            "error": format!("Failed to subscribe to blocks: {}", e)
+        }))).into_response(),
+    };
+
+    let stream = blocks.map(|result| -> Result<Event, Infallible> {
+        match result {
+            Ok(block) => Ok(Event::default().data(json!({ "type": "new_block", "block": block }).to_string())),
+            Err(e) => Ok(Event::default().event("error").data(e.to_string())),
+        }
+    });
+
+    Sse::new(stream).into_response()
+}
+
 async fn transactions_sse_endpoint() -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
     let rx = BROADCAST.subscribe();
     let stream = BroadcastStream::new(rx)
@@ -945,14 +938,46 @@ async fn transactions_sse_endpoint() -> Sse<impl Stream<Item = Result<Event, Inf
 
 // Initialize blockchain node
 // Initialize 4 blockchain nodes with different configurations
-async fn initialize_blockchain_nodes() -> Result<(), Box<dyn std::error::Error>> {
-    println!("🔧 Initializing blockchain nodes configuration...");
-    let base_p2p_port: u16 = std::env::var("P2P_PORT")
+// First P2P port of the cluster; node i listens on base + i (env: P2P_PORT)
+fn base_p2p_port() -> u16 {
+    std::env::var("P2P_PORT")
         .unwrap_or_else(|_| "30333".to_string())
         .parse()
-        .unwrap_or(30333);
-    let mining_address = std::env::var("MINING_ADDRESS")
-        .unwrap_or_else(|_| "FVCminer1234567890abcdef".to_string());
+        .unwrap_or(30333)
+}
+
+// Configuration of cluster node `i`: NODE_CONFIG_DIR/node-<i>.toml when present, otherwise
+// defaults where every node after the first bootstraps from node 0
+fn cluster_node_config(i: usize) -> Result<NodeConfig, Box<dyn std::error::Error>> {
+    if let Ok(dir) = std::env::var("NODE_CONFIG_DIR") {
+        let path = std::path::Path::new(&dir).join(format!("node-{}.toml", i));
+        if path.exists() {
+            return Ok(NodeConfig::from_file(&path)?);
+        }
+    }
+
+    let base_p2p_port = base_p2p_port();
+    let bootstrap_nodes = if i == 0 {
+        vec![]
+    } else {
+        vec![build_listen_addr("127.0.0.1", base_p2p_port)?]
+    };
+
+    Ok(NodeConfig {
+        listen_addr: build_listen_addr("0.0.0.0", base_p2p_port + i as u16)?,
+        bootstrap_nodes,
+        energy_threshold: 1000.0 + (i as f64 * 100.0), // Different thresholds
+        fractal_levels: 5 + (i % 3) as u32, // Varying fractal levels (5-7)
+        max_peers: 50 + (i * 10), // Different peer limits
+        sync_interval: 30 + (i * 5) as u64, // Different sync intervals
+        data_dir: Some(format!("data/node-{}", i).into()),
+    })
+}
+
+async fn initialize_blockchain_nodes() -> Result<(), Box<dyn std::error::Error>> {
+    println!("🔧 Initializing blockchain nodes configuration...");
+    let base_p2p_port = base_p2p_port();
+    let mining_address = mining_address_from_env()?;
 
     println!("🔒 Acquiring node locks...");
     let mut nodes_guard = BLOCKCHAIN_NODES.lock().await;
@@ -966,42 +991,26 @@ async fn initialize_blockchain_nodes() -> Result<(), Box<dyn std::error::Error>>
         let p2p_port = base_p2p_port + i as u16;
         println!("🔄 Initializing {} on port {}...", node_id, p2p_port);
         
-        let bootstrap_nodes = if i == 0 { 
-            vec![] 
-        } else { 
-            // Connect subsequent nodes to the first node
-            vec![format!("/ip4/127.0.0.1/tcp/{}", base_p2p_port).parse::<Multiaddr>()?]
-        };
-        
-        let bootstrap_count = bootstrap_nodes.len();
-        
-        let config = NodeConfig {
-            listen_addr: format!("/ip4/0.0.0.0/tcp/{}", p2p_port).parse::<Multiaddr>()?,
-            bootstrap_nodes,
-            energy_threshold: 1000.0 + (i as f64 * 100.0), // Different thresholds
-            fractal_levels: 5 + (i % 3) as u32, // Varying fractal levels (5-7)
-            max_peers: 50 + (i * 10), // Different peer limits
-            sync_interval: 30 + (i * 5) as u64, // Different sync intervals
-        };
+        let config = cluster_node_config(i)?;
+        let bootstrap_count = config.bootstrap_nodes.len();
 
-        match FractalNode::new(config).await {
+        // Network, bootstrap connections and production mining come up together
+        match FractalNode::launch(config, NODE_INIT_ATTEMPTS, NODE_INIT_BACKOFF).await {
             Ok(node) => {
-                // Start production mining for each node
-                use fractal_vortex_chain::node::ecosystem_miner::EcosystemMiner;
-                let consensus = node.get_consensus();
-                let state = node.get_state();
-                
-                if let Ok(miner) = EcosystemMiner::new(consensus, state).await {
-                    if let Err(e) = miner.start().await {
-                        log::warn!("Failed to start mining for {}: {}", node_id, e);
-                    }
-                }
-                
                 println!("✅ Blockchain {} initialized on port {}", node_id, p2p_port);
                 println!("🔗 {} connected to network with {} bootstrap nodes", node_id, bootstrap_count);
                 info!("✅ Blockchain {} initialized on port {}", node_id, p2p_port);
                 info!("🔗 {} connected to network with {} bootstrap nodes", node_id, bootstrap_count);
                 
+                // The primary node owns the persisted mempool used for mining templates
+                if i == 0 {
+                    match node.restore_mempool().await {
+                        Ok(restored) if restored > 0 => info!("♻️ Restored {} pending transactions", restored),
+                        Ok(_) => {}
+                        Err(e) => log::warn!("Failed to restore mempool: {}", e),
+                    }
+                }
+                
                 nodes_guard[i] = Some(node);
                 health_guard[i] = true;
                 
@@ -1012,7 +1021,7 @@ async fn initialize_blockchain_nodes() -> Result<(), Box<dyn std::error::Error>>
                 }
             }
             Err(e) => {
-                log::error!("❌ Failed to initialize {}: {}", node_id, e);
+                log::error!("❌ Failed to initialize {} after {} attempts: {}", node_id, NODE_INIT_ATTEMPTS, e);
                 health_guard[i] = false;
             }
         }
@@ -1056,18 +1065,20 @@ async fn monitor_node_health() {
     let mut health_guard = NODE_HEALTH.write().await;
     let mut health_changed = false;
     
-    for (i, node_opt) in nodes.iter().enumerate() {
-        let is_healthy = match node_opt {
+    // Healthy only while connected and within reach of the highest node
+    let mut samples = Vec::with_capacity(nodes.len());
+    for node_opt in nodes.iter() {
+        samples.push(match node_opt {
             Some(node) => {
-                // Check if node is responsive (simplified health check)
-                // In production, this would include more comprehensive checks
-                let _state = node.get_state();
-                // If we can get the state, node is considered healthy
-                true
+                let state = node.get_state();
+                let state = state.read().await;
+                Some(HealthSample { block_height: state.block_height, peer_count: state.connected_peers.len() })
             }
-            None => false,
-        };
-        
+            None => None,
+        });
+    }
+
+    for (i, is_healthy) in judge_health(&samples).into_iter().enumerate() {
         if health_guard[i] != is_healthy {
             health_guard[i] = is_healthy;
             health_changed = true;
@@ -1229,36 +1240,14 @@ async fn device_save_wallet_address(State(_state): State<AppState>, Json(payload
         }));
     }
     
-    // Validate FVChain native address format (43 characters: fvc + 36 hex + emyl)
-    if wallet_address.len() != 43 {
-        return Json(json!({
-            "success": false,
-            "error": "Invalid wallet address format: must be 43 characters"
-        }));
-    }
-    
-    if !wallet_address.starts_with("fvc") {
-        return Json(json!({
-            "success": false,
-            "error": "Invalid wallet address format: must start with 'fvc'"
-        }));
-    }
-    
-    if !wallet_address.ends_with("emyl") {
-        return Json(json!({
-            "success": false,
-            "error": "Invalid wallet address format: must end with 'emyl'"
-        }));
-    }
-    
-    // Check middle part is hexadecimal
-    let middle_part = &wallet_address[3..39];
-    if !middle_part.chars().all(|c| c.is_ascii_hexdigit()) {
-        return Json(json!({
+    let wallet_address = match checked_address(wallet_address).await {
+        Ok(address) => address,
+        Err(e) => return Json(json!({
             "success": false,
-            "error": "Invalid wallet address format: middle part must be hexadecimal"
-        }));
-    }
+            "error": format!("Invalid wallet address format: {}", e)
+        })),
+    };
+    let wallet_address = wallet_address.as_str();
     
     // Save device address mapping
     match RPCStorage::set_device_address(device_id, wallet_address).await {
@@ -1283,13 +1272,26 @@ async fn device_save_wallet_address(State(_state): State<AppState>, Json(payload
 #[derive(Deserialize)]
 struct StorePrivateKeyRequest {
     encrypted_private_key: String,
+    pin_hash: String,
     #[allow(dead_code)]
     metadata: Option<serde_json::Value>,
 }
 
 #[allow(dead_code)]
 async fn device_store_private_key(Path(device_id): Path<String>, Json(payload): Json<StorePrivateKeyRequest>) -> Json<Value> {
-    match RPCStorage::set_device_private_key(&device_id, &payload.encrypted_private_key).await {
+    if looks_like_plaintext_key(&payload.encrypted_private_key) {
+        return Json(json!({
+            "success": false,
+            "error": "Private key must be encrypted on the device before it is stored"
+        }));
+    }
+    // The key is sealed under the PIN the client proves here; the server keeps nothing it could unseal with
+    let pin_verification = device_verify_pin_impl(device_id.clone(), VerifyPinRequest { pin_hash: payload.pin_hash.clone() }).await;
+    if !pin_verification["success"].as_bool().unwrap_or(false) {
+        return pin_verification;
+    }
+
+    match RPCStorage::set_device_private_key(&device_id, &payload.encrypted_private_key, &payload.pin_hash).await {
         Ok(_) => {
             Json(json!({
                 "success": true,
@@ -1306,9 +1308,20 @@ async fn device_store_private_key(Path(device_id): Path<String>, Json(payload):
     }
 }
 
+// The PIN travels in the body, never the query string, so it stays out of access logs
+#[derive(Deserialize)]
+struct UnlockPrivateKeyRequest {
+    pin_hash: String,
+}
+
 #[allow(dead_code)]
-async fn device_get_private_key(Path(device_id): Path<String>) -> Json<Value> {
-    match RPCStorage::get_device_private_key(&device_id).await {
+async fn device_get_private_key(Path(device_id): Path<String>, Json(payload): Json<UnlockPrivateKeyRequest>) -> Json<Value> {
+    let pin_verification = device_verify_pin_impl(device_id.clone(), VerifyPinRequest { pin_hash: payload.pin_hash.clone() }).await;
+    if !pin_verification["success"].as_bool().unwrap_or(false) {
+        return pin_verification;
+    }
+
+    match RPCStorage::get_device_private_key(&device_id, &payload.pin_hash).await {
         Ok(Some(encrypted_private_key)) => {
             Json(json!({
                 "success": true,
@@ -1322,6 +1335,12 @@ async fn device_get_private_key(Path(device_id): Path<String>) -> Json<Value> {
                 "error": "Private key not found for device"
             }))
         },
+        Err(StorageError::Decryption(_)) => {
+            Json(json!({
+                "success": false,
+                "error": "Invalid PIN"
+            }))
+        },
         Err(e) => {
             Json(json!({
                 "success": false,
@@ -1333,18 +1352,11 @@ async fn device_get_private_key(Path(device_id): Path<String>) -> Json<Value> {
 
 #[allow(dead_code)]
 async fn device_has_private_key(Path(device_id): Path<String>) -> Json<Value> {
-    match RPCStorage::get_device_private_key(&device_id).await {
-        Ok(Some(_)) => {
-            Json(json!({
-                "success": true,
-                "has_private_key": true,
-                "device_id": device_id
-            }))
-        },
-        Ok(None) => {
+    match RPCStorage::has_device_private_key(&device_id).await {
+        Ok(has_private_key) => {
             Json(json!({
                 "success": true,
-                "has_private_key": false,
+                "has_private_key": has_private_key,
                 "device_id": device_id
             }))
         },
@@ -1379,15 +1391,18 @@ async fn device_remove_private_key(Path(device_id): Path<String>) -> Json<Value>
 #[derive(Deserialize)]
 struct MigratePlaintextRequest {
     plaintext_private_key: String,
-    #[allow(dead_code)]
     pin_hash: String,
 }
 
 #[allow(dead_code)]
 async fn device_migrate_plaintext(Path(device_id): Path<String>, Json(payload): Json<MigratePlaintextRequest>) -> Json<Value> {
-    // This would encrypt the plaintext private key and store it
-    // For now, we'll just store it as-is (in production, implement proper encryption)
-    match RPCStorage::set_device_private_key(&device_id, &payload.plaintext_private_key).await {
+    // Encrypt under the PIN the device will unlock with, or the key could never be read back
+    let pin_verification = device_verify_pin_impl(device_id.clone(), VerifyPinRequest { pin_hash: payload.pin_hash.clone() }).await;
+    if !pin_verification["success"].as_bool().unwrap_or(false) {
+        return pin_verification;
+    }
+
+    match RPCStorage::set_device_private_key(&device_id, &payload.plaintext_private_key, &payload.pin_hash).await {
         Ok(_) => {
             Json(json!({
                 "success": true,
@@ -1724,11 +1739,10 @@ async fn device_verify_pin_impl(device_id: String, payload: VerifyPinRequest) ->
         }
     }
     
-    // Get stored PIN data
-    match RPCStorage::get_device_pin(&device_id).await {
-        Ok(stored_hash) => {
-            // Frontend already sends hashed PIN, so we compare directly
-            if payload.pin_hash == stored_hash {
+    // Frontend already sends hashed PIN; storage keeps only an Argon2 hash of it
+    match RPCStorage::verify_device_pin(&device_id, &payload.pin_hash).await {
+        Ok(verified) => {
+            if verified {
                 // PIN is correct, reset failed attempts
                 if let Err(e) = RPCStorage::set_device_failed_attempts(&device_id, 0).await {
                     log::error!("Failed to reset failed attempts for device {}: {}", device_id, e);
@@ -1918,6 +1932,16 @@ struct SendRequest {
     to: String,
     amount: u64,
     private_key: String,
+    /// Next account nonce (last accepted + 1); a resent identical transfer is answered as a duplicate.
+    /// Assigned by the node when omitted.
+    #[serde(default)]
+    nonce: Option<u64>,
+    /// Fee in microFVC, at least MIN_TRANSFER_FEE; the default fee when omitted
+    #[serde(default)]
+    fee: Option<u64>,
+    /// Not supported; a request carrying one is rejected rather than sent unchecked
+    #[serde(default)]
+    signature: Option<Value>,
 }
 
 #[derive(Deserialize)]
@@ -1928,6 +1952,12 @@ struct DeviceSendRequest {
     amount: u64,
     private_key: String,
     device_id: String,
+    #[serde(default)]
+    nonce: Option<u64>,
+    #[serde(default)]
+    fee: Option<u64>,
+    #[serde(default)]
+    signature: Option<Value>,
 }
 
 #[derive(Deserialize)]
@@ -1992,6 +2022,7 @@ struct WalletTransactionsRequest {
     address: String,
     limit: Option<usize>,
     transaction_type: Option<String>,
+    cursor: Option<usize>,
 }
 
 #[allow(dead_code)]
@@ -2017,18 +2048,13 @@ async fn wallet_create_post(Json(payload): Json<WalletCreateRequest>) -> Json<Va
                 println!("Warning: Failed to save device address mapping: {}", e);
             }
             
-            // Set initial balance for new wallet (10 FVC = 10,000,000 microFVC)
-            if let Err(e) = RPCStorage::set_balance(&address, 10000000).await { // 10 FVC initial balance
-                println!("Warning: Failed to set initial balance: {}", e);
-            }
-            
             Json(json!({
                 "success": true,
                 "device_id": payload.device_id,
                 "address": address,
                 "private_key": private_key,
                 "public_key": public_key,
-                "balance": 10000000u64
+                "balance": 0u64
             }))
         },
         Err(e) => {
@@ -2076,108 +2102,99 @@ async fn device_send(State(state): State<AppState>, payload: Result<Json<DeviceS
     }
 }
 
-async fn device_send_impl(State(_state): State<AppState>, payload: DeviceSendRequest) -> Json<Value> {
-    // Validate addresses
-    if payload.from.len() < 20 || payload.to.len() < 20 {
-        return Json(json!({
-            "success": false,
-            "error": "Invalid address format"
-        }));
+async fn device_send_impl(State(_state): State<AppState>, payload: DeviceSendRequest) -> (StatusCode, Json<Value>) {
+    if let Some(rejection) = reject_detached_signature(&payload.signature) {
+        return rejection;
     }
-    
-    // Check sender balance including transaction fee
-    let fee = 1000; // 0.001 FVC fee
-    let total_required = payload.amount + fee;
-    
-    match RPCStorage::get_balance(&payload.from).await {
-        Ok(balance) => {
-            if balance < total_required {
-                return Json(json!({
-                    "success": false,
-                    "error": format!("Insufficient balance. Required: {} (including {} fee), Available: {}", total_required, fee, balance)
-                }));
-            }
+    let mut validator = RequestValidator::new();
+    validator
+        .check("device_id", validate_device_id(&payload.device_id))
+        .check("from", validate_tx_address("sender", &payload.from))
+        .check("to", validate_tx_address("recipient", &payload.to))
+        .check("amount", validate_amount_with_fee(payload.amount, payload.fee.unwrap_or(DEVICE_TRANSFER_FEE)));
+    if let Err(rejection) = validator.finish() {
+        return rejection;
+    }
+
+    let result = submit_and_broadcast("device_transfer", &payload.from, &payload.to, payload.amount, payload.nonce, payload.fee, &payload.private_key).await;
+
+    let mut body = result.to_json();
+    if result.status != SubmissionStatus::Rejected {
+        body["device_id"] = json!(payload.device_id);
+        body["from"] = json!(payload.from);
+        body["to"] = json!(payload.to);
+        body["amount"] = json!(payload.amount);
+    }
+    (result.http_status(), Json(body))
+}
+
+// The node builds and signs the transfer itself, so a client-made signature could never be
+// checked against it; refuse one instead of ignoring it. `private_key` is what authorizes a send.
+fn reject_detached_signature(signature: &Option<Value>) -> Option<(StatusCode, Json<Value>)> {
+    signature.as_ref().map(|_| {
+        let result = SubmissionResult::rejected(None, "Detached signatures are not supported; authorize the transfer with private_key");
+        (result.http_status(), Json(result.to_json()))
+    })
+}
+
+// A checksummed address as given, or the address a migrated pre-checksum one moved to
+async fn checked_address(address: &str) -> Result<String, AddressError> {
+    match KeyManager::validate_address(address) {
+        Ok(()) => Ok(address.to_string()),
+        Err(AddressError::ChecksumMismatch) => match RPCStorage::current_address(address).await {
+            Ok(current) if current != address => Ok(current),
+            _ => Err(AddressError::ChecksumMismatch),
         },
-        Err(e) => {
-            return Json(json!({
-                "success": false,
-                "error": format!("Failed to check balance: {}", e)
-            }));
-        }
+        Err(e) => Err(e),
     }
-    
-    // Create transaction
-    let tx = WalletTransaction {
-        hash: format!("tx_{}", Utc::now().timestamp()),
-        from: payload.from.clone(),
-        to: payload.to.clone(),
-        amount: payload.amount,
-        timestamp: Utc::now().timestamp() as u64,
-        transaction_type: "device_transfer".to_string(),
-        block_height: 1,
+}
+
+// Check the sender's key, submit a content-hashed transfer and announce it only when newly accepted
+async fn submit_and_broadcast(
+    transaction_type: &str,
+    from: &str,
+    to: &str,
+    amount: u64,
+    nonce: Option<u64>,
+    fee: Option<u64>,
+    private_key: &str,
+) -> SubmissionResult {
+    // Pre-checksum addresses that were migrated keep working as their current form
+    let (from, to) = match (RPCStorage::current_address(from).await, RPCStorage::current_address(to).await) {
+        (Ok(from), Ok(to)) => (from, to),
+        (Err(e), _) | (_, Err(e)) => return SubmissionResult::failed(None, format!("Failed to resolve address: {}", e)),
     };
-    
-    // Process transaction
-    match RPCStorage::add_transaction(&tx).await {
-        Ok(_) => {
-            // Get current balances safely
-            let sender_current_balance = RPCStorage::get_balance(&payload.from).await.unwrap_or(0);
-            let receiver_current_balance = RPCStorage::get_balance(&payload.to).await.unwrap_or(0);
-            
-            // Double-check balance before updating (safety check)
-            if sender_current_balance < total_required {
-                return Json(json!({
-                    "success": false,
-                    "error": "Insufficient balance including transaction fee"
-                }));
-            }
-            
-            // Update balances safely
-            let sender_new_balance = sender_current_balance - total_required;
-            let receiver_new_balance = receiver_current_balance + payload.amount;
-            
-            match RPCStorage::set_balance(&payload.from, sender_new_balance).await {
-                Ok(_) => {},
-                Err(e) => {
-                    log::error!("Failed to update sender balance: {}", e);
-                    return Json(json!({
-                        "success": false,
-                        "error": "Failed to update sender balance"
-                    }));
-                }
-            }
-            
-            match RPCStorage::set_balance(&payload.to, receiver_new_balance).await {
-                Ok(_) => {},
-                Err(e) => {
-                    log::error!("Failed to update receiver balance: {}", e);
-                    return Json(json!({
-                        "success": false,
-                        "error": "Failed to update receiver balance"
-                    }));
-                }
-            }
-            
-            // Broadcast transaction
-            let _ = BROADCAST.send(json!({
-                "type": "new_transaction",
-                "transaction": tx
-            }).to_string());
-            
-            Json(json!({
-                "success": true,
-                "device_id": payload.device_id,
-                "transaction_hash": tx.hash,
-                "from": payload.from,
-                "to": payload.to,
-                "amount": payload.amount
-            }))
-        },
+    let (tx, result) = submit_new_signed_transfer(transaction_type, from, to, amount, nonce, fee, private_key).await;
+    if let Some(tx) = &tx {
+        for detection in TX_ANOMALY_DETECTOR.observe_transaction(tx) {
+            log::warn!("{:?} from {} on tx {} (confidence {:.2})", detection.pattern, tx.from, tx.hash, detection.confidence);
+        }
+    }
+    if result.status == SubmissionStatus::Rejected {
+        SECURITY_MONITOR.record_transfer_failure();
+    }
+    if let (Some(tx), SubmissionStatus::Accepted) = (tx, result.status) {
+        gossip_transfer(&tx, private_key).await;
+        let _ = BROADCAST.send(json!({
+            "type": "new_transaction",
+            "transaction": tx
+        }).to_string());
+    }
+    result
+}
+
+// Hand an accepted transfer to the primary node's mempool, which gossips it to peers
+async fn gossip_transfer(tx: &WalletTransaction, private_key: &str) {
+    let gossip = match gossip_transaction(tx, private_key) {
+        Ok(gossip) => gossip,
         Err(e) => {
-            Json(json!({
-                "success": false,
-                "error": format!("Transaction failed: {}", e)
-            }))
+            log::warn!("Failed to sign transaction {} for gossip: {}", tx.hash, e);
+            return;
+        }
+    };
+    if let Some(node) = BLOCKCHAIN_NODE.lock().await.as_ref() {
+        if let Err(e) = node.submit_transaction(gossip).await {
+            log::warn!("Failed to gossip transaction {}: {}", tx.hash, e);
         }
     }
 }
@@ -2192,79 +2209,47 @@ async fn wallet_send(State(state): State<AppState>, payload: Result<Json<SendReq
     }
 }
 
-async fn wallet_send_impl(State(_state): State<AppState>, payload: SendRequest) -> Json<Value> {
-    // Validate addresses
-    if payload.from.len() < 20 || payload.to.len() < 20 {
-        return Json(json!({
-            "success": false,
-            "error": "Invalid address format"
-        }));
+async fn wallet_send_impl(State(_state): State<AppState>, payload: SendRequest) -> (StatusCode, Json<Value>) {
+    if let Some(rejection) = reject_detached_signature(&payload.signature) {
+        return rejection;
     }
-    
-    // Check sender balance
-    match RPCStorage::get_balance(&payload.from).await {
-        Ok(balance) => {
-            if balance < payload.amount {
-                return Json(json!({
-                    "success": false,
-                    "error": "Insufficient balance"
-                }));
-            }
-        },
-        Err(e) => {
-            return Json(json!({
-                "success": false,
-                "error": format!("Failed to check balance: {}", e)
-            }));
-        }
+    let result = submit_and_broadcast("transfer", &payload.from, &payload.to, payload.amount, payload.nonce, payload.fee, &payload.private_key).await;
+
+    let mut body = result.to_json();
+    if result.status != SubmissionStatus::Rejected {
+        body["from"] = json!(payload.from);
+        body["to"] = json!(payload.to);
+        body["amount"] = json!(payload.amount);
     }
-    
-    // Create transaction
-    let tx = WalletTransaction {
-        hash: format!("tx_{}", Utc::now().timestamp()),
-        from: payload.from.clone(),
-        to: payload.to.clone(),
-        amount: payload.amount,
-        timestamp: Utc::now().timestamp() as u64,
-        transaction_type: "transfer".to_string(),
-        block_height: 1,
-    };
-    
-    // Process transaction
-    match RPCStorage::add_transaction(&tx).await {
-        Ok(_) => {
-            // Update balances
-            let sender_new_balance = RPCStorage::get_balance(&payload.from).await.unwrap_or(0) - payload.amount - 1000; // 0.001 FVC fee
-            let receiver_new_balance = RPCStorage::get_balance(&payload.to).await.unwrap_or(0) + payload.amount;
-            
-            if let Err(e) = RPCStorage::set_balance(&payload.from, sender_new_balance).await {
-                log::error!("Failed to update sender balance: {}", e);
-            }
-            
-            if let Err(e) = RPCStorage::set_balance(&payload.to, receiver_new_balance).await {
-                log::error!("Failed to update receiver balance: {}", e);
+    (result.http_status(), Json(body))
+}
+
+// JSON-RPC 2.0 surface over the REST handlers' logic
+async fn json_rpc_endpoint(body: axum::body::Bytes) -> axum::response::Response {
+    match json_rpc::handle(&body, json_rpc_method).await {
+        Some(response) => Json(response).into_response(),
+        None => StatusCode::NO_CONTENT.into_response(),
+    }
+}
+
+async fn json_rpc_method(method: String, params: Value) -> Result<Value, RpcError> {
+    match method.as_str() {
+        "fvc_sendTransaction" => {
+            let params = json_rpc::param(&params, 0, "transaction")
+                .cloned()
+                .unwrap_or(params);
+            let req: SendRequest = serde_json::from_value(params)
+                .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+            let result = submit_and_broadcast("transfer", &req.from, &req.to, req.amount, req.nonce, req.fee, &req.private_key).await;
+            match result.status {
+                SubmissionStatus::Rejected => Err(RpcError::new(
+                    json_rpc::SERVER_ERROR,
+                    result.reason.unwrap_or_else(|| "Transaction rejected".to_string()),
+                )),
+                _ => Ok(result.to_json()),
             }
-            
-            // Broadcast transaction
-            let _ = BROADCAST.send(json!({
-                "type": "new_transaction",
-                "transaction": tx
-            }).to_string());
-            
-            Json(json!({
-                "success": true,
-                "transaction_hash": tx.hash,
-                "from": payload.from,
-                "to": payload.to,
-                "amount": payload.amount
-            }))
-        },
-        Err(e) => {
-            Json(json!({
-                "success": false,
-                "error": format!("Transaction failed: {}", e)
-            }))
         }
+        _ => json_rpc::chain_method(&method, &params).await,
     }
 }
 
@@ -2283,12 +2268,13 @@ struct AdminSetBalanceRequest {
     address: String,
     #[allow(dead_code)]
     balance: u64,
-    #[allow(dead_code)]
-    admin_key: String,
 }
 
 #[allow(dead_code)]
-async fn admin_set_balance(payload: Result<Json<AdminSetBalanceRequest>, JsonRejection>) -> impl IntoResponse {
+async fn admin_set_balance(key: ApiKey, payload: Result<Json<AdminSetBalanceRequest>, JsonRejection>) -> impl IntoResponse {
+    if let Err(response) = key.require(Scope::Admin) {
+        return response;
+    }
     match payload {
         Ok(Json(req)) => admin_set_balance_impl(req).await.into_response(),
         Err(rejection) => {
@@ -2300,14 +2286,6 @@ async fn admin_set_balance(payload: Result<Json<AdminSetBalanceRequest>, JsonRej
 
 #[allow(dead_code)]
 async fn admin_set_balance_impl(payload: AdminSetBalanceRequest) -> Json<Value> {
-    // Simple admin key check (in production, use proper authentication)
-    if payload.admin_key != "admin123" {
-        return Json(json!({
-            "success": false,
-            "error": "Invalid admin key"
-        }));
-    }
-    
     match RPCStorage::set_balance(&payload.address, payload.balance).await {
         Ok(_) => Json(json!({
             "success": true,
@@ -2322,32 +2300,16 @@ async fn admin_set_balance_impl(payload: AdminSetBalanceRequest) -> Json<Value>
     }
 }
 
-#[derive(Deserialize)]
-struct AdminInitializeEcosystemRequest {
-    admin_key: String,
-}
-
 #[allow(dead_code)]
-async fn admin_initialize_ecosystem(payload: Result<Json<AdminInitializeEcosystemRequest>, JsonRejection>) -> impl IntoResponse {
-    match payload {
-        Ok(Json(req)) => admin_initialize_ecosystem_impl(req).await.into_response(),
-        Err(rejection) => {
-            let (status, json_response) = handle_json_rejection(rejection);
-            (status, json_response).into_response()
-        }
+async fn admin_initialize_ecosystem(key: ApiKey) -> impl IntoResponse {
+    if let Err(response) = key.require(Scope::Admin) {
+        return response;
     }
+    admin_initialize_ecosystem_impl().await.into_response()
 }
 
 #[allow(dead_code)]
-async fn admin_initialize_ecosystem_impl(payload: AdminInitializeEcosystemRequest) -> Json<Value> {
-    // Simple admin key check
-    if payload.admin_key != "admin123" {
-        return Json(json!({
-            "success": false,
-            "error": "Invalid admin key"
-        }));
-    }
-    
+async fn admin_initialize_ecosystem_impl() -> Json<Value> {
     // Initialize ecosystem wallets with initial balances
     let ecosystem_wallets = vec![
         ("FVCowner1234567890abcdef", 1000000000000u64), // 1M FVC
@@ -2368,6 +2330,173 @@ async fn admin_initialize_ecosystem_impl(payload: AdminInitializeEcosystemReques
     }))
 }
 
+// Account summary with explorer label
+async fn get_account(Path(address): Path<String>) -> Json<Value> {
+    match RPCStorage::get_account_info(&address).await {
+        Ok(account) => Json(json!({
+            "success": true,
+            "account": account
+        })),
+        Err(e) => Json(json!({
+            "success": false,
+            "error": format!("Failed to get account: {}", e)
+        }))
+    }
+}
+
+#[derive(Deserialize)]
+struct BalanceAtHeightQuery {
+    height: Option<u64>,
+}
+
+// Balance as of a block height; defaults to the current tip
+async fn get_account_balance_at_height(Path(address): Path<String>, query: Result<Query<BalanceAtHeightQuery>, QueryRejection>) -> impl IntoResponse {
+    let requested = match query {
+        Ok(Query(q)) => q.height,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({
+            "success": false,
+            "error": format!("Invalid query: {}", e)
+        }))).into_response(),
+    };
+
+    let tip = RPCStorage::get_block_height().await.unwrap_or(0);
+    let height = requested.unwrap_or(tip);
+    if height > tip {
+        return (StatusCode::BAD_REQUEST, Json(json!({
+            "success": false,
+            "error": format!("Height {} is above the current tip {}", height, tip)
+        }))).into_response();
+    }
+
+    match RPCStorage::balance_at_height(&address, height).await {
+        Ok(balance) => Json(json!({
+            "success": true,
+            "address": address,
+            "height": height,
+            "balance": balance
+        })).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "success": false,
+            "error": format!("Failed to compute historical balance: {}", e)
+        }))).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct ExportQuery {
+    format: Option<String>,
+}
+
+async fn export_account_history(Path(address): Path<String>, query: Result<Query<ExportQuery>, QueryRejection>) -> impl IntoResponse {
+    let format = match query {
+        Ok(Query(q)) => q.format.unwrap_or_else(|| "csv".to_string()),
+        Err(_) => "csv".to_string(),
+    };
+    if format != "csv" {
+        return (StatusCode::BAD_REQUEST, Json(json!({
+            "success": false,
+            "error": format!("Unsupported export format '{}', expected csv", format)
+        }))).into_response();
+    }
+
+    let body = axum::body::Body::from_stream(
+        address_history_csv(address.clone())
+            .map(|chunk| chunk.map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e.to_string())))
+    );
+    (
+        [
+            (axum::http::header::CONTENT_TYPE, "text/csv; charset=utf-8".to_string()),
+            (axum::http::header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}-history.csv\"", address)),
+        ],
+        body,
+    ).into_response()
+}
+
+#[derive(Deserialize)]
+struct FaucetRequest {
+    address: String,
+}
+
+static FAUCET: Lazy<Faucet> = Lazy::new(|| Faucet::new(FaucetConfig::from_env()));
+
+async fn faucet_request(
+    axum::extract::ConnectInfo(addr): axum::extract::ConnectInfo<std::net::SocketAddr>,
+    payload: Result<Json<FaucetRequest>, JsonRejection>,
+) -> impl IntoResponse {
+    let request = match payload {
+        Ok(Json(request)) => request,
+        Err(rejection) => return handle_json_rejection(rejection).into_response(),
+    };
+
+    match FAUCET.grant(request.address.trim(), addr.ip()).await {
+        Ok(tx) => {
+            let _ = BROADCAST.send(json!({
+                "type": "new_transaction",
+                "transaction": tx
+            }).to_string());
+            (StatusCode::OK, Json(json!({
+                "success": true,
+                "transaction_hash": tx.hash,
+                "address": tx.to,
+                "amount": tx.amount
+            }))).into_response()
+        }
+        Err(e) => (e.status_code(), Json(json!({
+            "success": false,
+            "error": {
+                "code": e.code(),
+                "message": e.to_string()
+            }
+        }))).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct AdminSetLabelRequest {
+    address: String,
+    label: String,
+}
+
+async fn admin_set_label(payload: Result<Json<AdminSetLabelRequest>, JsonRejection>) -> impl IntoResponse {
+    let request = match payload {
+        Ok(Json(request)) => request,
+        Err(rejection) => return handle_json_rejection(rejection).into_response(),
+    };
+
+    let label = request.label.trim();
+    if request.address.is_empty() || label.is_empty() || label.chars().count() > fractal_vortex_chain::rpc_storage::MAX_ADDRESS_LABEL_LEN {
+        return (StatusCode::BAD_REQUEST, Json(json!({
+            "success": false,
+            "error": format!("address is required and label must be 1-{} characters", fractal_vortex_chain::rpc_storage::MAX_ADDRESS_LABEL_LEN)
+        }))).into_response();
+    }
+
+    match RPCStorage::set_address_label(&request.address, label).await {
+        Ok(_) => Json(json!({
+            "success": true,
+            "address": request.address,
+            "label": label
+        })).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "success": false,
+            "error": format!("Failed to set label: {}", e)
+        }))).into_response()
+    }
+}
+
+async fn admin_clear_label(Path(address): Path<String>) -> impl IntoResponse {
+    match RPCStorage::clear_address_label(&address).await {
+        Ok(_) => Json(json!({
+            "success": true,
+            "address": address
+        })).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "success": false,
+            "error": format!("Failed to clear label: {}", e)
+        }))).into_response()
+    }
+}
+
 #[allow(dead_code)]
 async fn get_network_health(State(state): State<AppState>) -> Json<Value> {
     let latest_block = *state.latest_block.read().await;
@@ -2408,11 +2537,20 @@ async fn mining_heartbeat(payload: Result<Json<HeartbeatRequest>, JsonRejection>
 }
 
 async fn mining_heartbeat_impl(payload: HeartbeatRequest) -> Json<Value> {
+    if let Err(e) = payload.validate() {
+        return Json(json!({
+            "success": false,
+            "error": e.to_string(),
+            "field": e.field()
+        }));
+    }
+
     let response = AUTO_DETECTION.update_heartbeat(payload).await;
     
     Json(json!({
         "success": response.success,
-        "device_id": response.server_time,
+        "device_id": response.device_id,
+        "server_time": response.server_time,
         "mining_status": response.mining_status,
         "message": response.message,
         "timestamp": Utc::now().timestamp()
@@ -2421,10 +2559,18 @@ async fn mining_heartbeat_impl(payload: HeartbeatRequest) -> Json<Value> {
 
 async fn mining_detection_stats() -> Json<Value> {
     let stats = AUTO_DETECTION.get_statistics().await;
+    let config = AUTO_DETECTION.config();
     
     Json(json!({
         "success": true,
-        "detection_stats": stats
+        "detection_stats": stats,
+        "config": {
+            "heartbeat_timeout_secs": config.heartbeat_timeout.as_secs(),
+            "check_interval_secs": config.check_interval.as_secs(),
+            "grace_period_secs": config.grace_period.as_secs(),
+            "max_retry_attempts": config.max_retry_attempts,
+            "resume_window_secs": config.resume_window.as_secs()
+        }
     }))
 }
 
@@ -2441,13 +2587,19 @@ async fn reward_estimation(Query(params): Query<HashMap<String, String>>) -> Jso
     };
     
     // Calculate Smart Rate components
-    let smart_rate = calculate_smart_rate().await;
-    let vortex_energy = calculate_vortex_energy_rate().await;
-    let fractal_score = calculate_fractal_contribution_score().await;
-    let efficiency_index = calculate_mathematical_efficiency_index().await;
-    let harmony_factor = calculate_network_harmony_factor().await;
-    
-    // Calculate reward based on Smart Rate contribution
+    let SmartRate {
+        smart_rate,
+        vortex_energy_rate: vortex_energy,
+        fractal_contribution_score: fractal_score,
+        mathematical_efficiency_index: efficiency_index,
+        network_harmony_factor: harmony_factor,
+        ..
+    } = calculate_smart_rate().await;
+    
+    // Calculate reward based on Smart Rate contribution. Since the Smart Rate was consolidated
+    // into `smart_rate`, components are on 0..=1000 scales with 0.30/0.25/0.25/0.20 weights
+    // (previously VER/5000 and FCS, MEI, NHF/100 with 0.35/0.25/0.25/0.15), so estimates
+    // differ from those of earlier releases for the same chain.
     // Base reward calculation: 1 SS/S should yield approximately 600-800 FVC per day
     // For 50-150 SS/S range, this gives 30,000-120,000 FVC per day
     let base_reward_per_ss = 600.0; // Base FVC per SS/S per day
@@ -2464,6 +2616,10 @@ async fn reward_estimation(Query(params): Query<HashMap<String, String>>) -> Jso
     // Apply network difficulty adjustment
     let network_difficulty_factor = 1.0; // Can be adjusted based on network conditions
     let final_estimated_reward = estimated_daily_reward * network_difficulty_factor;
+
+    let rewards = MiningRewardSystem::new();
+    let current_height = RPCStorage::get_block_height().await.unwrap_or(0);
+    let next_halving = rewards.next_halving(current_height);
     
     Json(json!({
         "success": true,
@@ -2475,6 +2631,12 @@ async fn reward_estimation(Query(params): Query<HashMap<String, String>>) -> Jso
             "base_reward_per_ss": base_reward_per_ss,
             "smart_rate_multiplier": smart_rate_multiplier,
             "network_difficulty_factor": network_difficulty_factor,
+            "block_reward": rewards.reward_at_height(current_height),
+            "next_halving": {
+                "block_height": next_halving.block_height,
+                "reward_after": next_halving.reward_after,
+                "blocks_remaining": next_halving.block_height - current_height
+            },
             "components": {
                 "vortex_energy_rate": vortex_energy,
                 "fractal_contribution_score": fractal_score,
@@ -2485,6 +2647,147 @@ async fn reward_estimation(Query(params): Query<HashMap<String, String>>) -> Jso
     }))
 }
 
+// Candidate block for external mining software; charged to the mining rate limit like /mining/submit
+async fn mining_template(Query(params): Query<HashMap<String, String>>) -> Json<Value> {
+    let miner = match checked_address(params.get("miner").map(String::as_str).unwrap_or_default()).await {
+        Ok(miner) => miner,
+        Err(e) => return Json(json!({
+            "success": false,
+            "error": format!("miner must be a native FVChain address: {}", e)
+        })),
+    };
+
+    let height = RPCStorage::next_block_height().await.unwrap_or(1);
+    let parent_hash = match RPCStorage::find_parent_block(height).await {
+        Ok(Some(parent)) => parent.hash,
+        Ok(None) => "0".repeat(64),
+        Err(e) => return Json(json!({
+            "success": false,
+            "error": format!("Failed to load chain tip: {}", e)
+        })),
+    };
+
+    let mempool = match BLOCKCHAIN_NODE.lock().await.as_ref() {
+        Some(node) => {
+            node.expire_pending_transactions().await;
+            node.get_consensus().read().await.get_pending_transactions().await
+        }
+        None => Vec::new(),
+    };
+
+    let template = BlockTemplate::new(
+        parent_hash,
+        height,
+        miner,
+        fractal_vortex_chain::rpc_storage::BLOCK_DIFFICULTY,
+        &mempool,
+        Utc::now().timestamp() as u64,
+    );
+    let template_id = template.id();
+
+    {
+        let mut templates = MINING_TEMPLATES.write().await;
+        // Templates built on an older tip can no longer be accepted
+        templates.retain(|t| t.parent_hash == template.parent_hash && t.height == template.height);
+        templates.insert(template_id.clone(), template.clone());
+    }
+
+    Json(json!({
+        "success": true,
+        "template_id": template_id,
+        "header": hex::encode(template.header_bytes()),
+        "parent_hash": template.parent_hash,
+        "height": template.height,
+        "difficulty": template.difficulty,
+        "fractal_levels": template.fractal_levels,
+        "merkle_root": template.merkle_root,
+        "timestamp": template.timestamp,
+        "transaction_count": template.transactions.len()
+    }))
+}
+
+#[derive(Debug, Deserialize)]
+struct MiningSubmitRequest {
+    template_id: String,
+    nonce: u64,
+}
+
+// Accept a solved nonce for a previously issued template
+async fn mining_submit(payload: Result<Json<MiningSubmitRequest>, JsonRejection>) -> impl IntoResponse {
+    let request = match payload {
+        Ok(Json(request)) => request,
+        Err(rejection) => return handle_json_rejection(rejection).into_response(),
+    };
+
+    match mining_submit_impl(request).await {
+        Ok(block) => Json(json!({
+            "success": true,
+            "accepted": true,
+            "block_hash": block.hash,
+            "height": block.height
+        })).into_response(),
+        Err(e) => {
+            let status = match e {
+                TemplateError::InvalidProofOfWork(_) => StatusCode::BAD_REQUEST,
+                TemplateError::UnknownTemplate => StatusCode::NOT_FOUND,
+                TemplateError::Stale => StatusCode::CONFLICT,
+                TemplateError::Rejected(_) => StatusCode::BAD_REQUEST,
+                TemplateError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (status, Json(json!({
+                "success": false,
+                "accepted": false,
+                "error": e.to_string()
+            }))).into_response()
+        }
+    }
+}
+
+async fn mining_submit_impl(request: MiningSubmitRequest) -> Result<fractal_vortex_chain::rpc_storage::Block, TemplateError> {
+    let template = MINING_TEMPLATES.read().await
+        .get(&request.template_id)
+        .cloned()
+        .ok_or(TemplateError::UnknownTemplate)?;
+
+    let block = template.solve(request.nonce)?;
+
+    // Storage checks the tip, applies every transaction's balance effect and stores the block
+    // under one lock, so a stale or overdrawing solution changes nothing
+    if let Err(e) = RPCStorage::store_mined_block(&block).await {
+        log::warn!("Rejecting submitted block {}: {}", block.height, e);
+        return Err(match e {
+            StorageError::StaleBlock(_) => {
+                MINING_TEMPLATES.write().await.remove(&request.template_id);
+                TemplateError::Stale
+            }
+            StorageError::BalanceUnderflow { .. } | StorageError::BalanceOverflow { .. } | StorageError::InvalidBlock(_) => {
+                MINING_TEMPLATES.write().await.remove(&request.template_id);
+                TemplateError::Rejected(e.to_string())
+            }
+            e => TemplateError::Storage(e.to_string()),
+        });
+    }
+
+    // Included transfers leave the mempool, and peers import the block through fork choice
+    if let Some(node) = BLOCKCHAIN_NODE.lock().await.as_ref() {
+        if let Err(e) = node.broadcast_chain_block(&block) {
+            log::warn!("Failed to gossip block {}: {}", block.height, e);
+        }
+        let included: Vec<[u8; 32]> = block.transactions.iter()
+            .filter_map(|tx| tx.hash.strip_prefix("0x").and_then(|h| hex::decode(h).ok()))
+            .filter_map(|bytes| bytes.try_into().ok())
+            .collect();
+        node.get_consensus().read().await.remove_pending_transactions(&included).await;
+        if let Err(e) = RPCStorage::remove_pending_transactions(&included).await {
+            log::warn!("Failed to drop mined transactions from persisted mempool: {}", e);
+        }
+    }
+
+    MINING_TEMPLATES.write().await.retain(|t| t.height > block.height);
+    info!("⛏️ External miner {} solved block #{}", block.miner, block.height);
+    Ok(block)
+}
+
 // Additional device endpoints
 async fn miner_register(State(_state): State<AppState>, Json(payload): Json<serde_json::Value>) -> impl IntoResponse {
     let device_id = payload["device_id"].as_str().unwrap_or("unknown");
@@ -2498,31 +2801,19 @@ async fn miner_register(State(_state): State<AppState>, Json(payload): Json<serd
         }));
     }
     
-    // Validate wallet address format (FVChain native format: fvc + 36 hex + emyl)
-    if !wallet_address.is_empty() {
-        if wallet_address.len() != 43 {
-            return Json(json!({
-                "success": false,
-                "error": "Alamat wallet harus 43 karakter (fvc + 36 hex + emyl)"
-            }));
-        }
-        
-        if !wallet_address.starts_with("fvc") || !wallet_address.ends_with("emyl") {
-            return Json(json!({
-                "success": false,
-                "error": "Format alamat wallet tidak valid. Gunakan format FVChain native (fvcxxxxxxxxemyl)"
-            }));
-        }
-        
-        // Validate hex characters in the middle (36 characters between fvc and emyl)
-        let hex_part = &wallet_address[3..39];
-        if hex_part.len() != 36 || !hex_part.chars().all(|c| c.is_ascii_hexdigit()) {
-            return Json(json!({
+    // Validate wallet address format (FVChain native format with checksum)
+    let wallet_address = if wallet_address.is_empty() {
+        String::new()
+    } else {
+        match checked_address(wallet_address).await {
+            Ok(address) => address,
+            Err(e) => return Json(json!({
                 "success": false,
-                "error": "Alamat wallet mengandung karakter tidak valid. Harus berupa hex (0-9, a-f)"
-            }));
+                "error": format!("Alamat wallet tidak valid: {}", e)
+            })),
         }
-    }
+    };
+    let wallet_address = wallet_address.as_str();
     
     // Save device session to track mining status
     let current_time = Utc::now().timestamp() as u64;
@@ -2587,13 +2878,16 @@ async fn miner_unregister(State(_state): State<AppState>, Json(payload): Json<se
     }
     
     // Stop mining if currently active
-    if let Err(e) = stop_production_mining(device_id).await {
-        log::warn!("Failed to stop mining during unregistration for device {}: {}", device_id, e);
+    {
+        let mut global_miner = GLOBAL_ECOSYSTEM_MINER.lock().await;
+        if let Err(e) = stop_production_mining(&mut global_miner, device_id).await {
+            log::warn!("Failed to stop mining during unregistration for device {}: {}", device_id, e);
+        }
+
+        // Remove device from AUTO_DETECTION
+        AUTO_DETECTION.unregister_device(device_id).await;
     }
     
-    // Remove device from AUTO_DETECTION
-    AUTO_DETECTION.unregister_device(device_id).await;
-    
     // Remove device session
     if let Err(e) = RPCStorage::remove_device_session(device_id).await {
         log::error!("Failed to remove device session during unregistration: {}", e);
@@ -2637,13 +2931,139 @@ async fn miner_unregister(State(_state): State<AppState>, Json(payload): Json<se
 
 
 
+#[derive(Deserialize)]
+struct DifficultyHistoryQuery {
+    from: Option<u64>,
+    to: Option<u64>,
+}
+
+// Issued supply (genesis + mining rewards) recomputed from storage against the summed balances
+async fn audit_supply() -> impl IntoResponse {
+    match RPCStorage::audit_supply().await {
+        Ok(audit) => (StatusCode::OK, Json(json!({
+            "success": true,
+            "consistent": audit.is_consistent(),
+            "audit": audit
+        }))).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "success": false,
+            "error": format!("Failed to audit supply: {}", e)
+        }))).into_response(),
+    }
+}
+
+// Per-height difficulty; defaults to the latest 100 blocks
+async fn get_difficulty_history(query: Result<Query<DifficultyHistoryQuery>, QueryRejection>) -> impl IntoResponse {
+    let query = match query {
+        Ok(Query(q)) => q,
+        Err(e) => return (StatusCode::BAD_REQUEST, Json(json!({
+            "success": false,
+            "error": format!("Invalid query: {}", e)
+        }))).into_response(),
+    };
+
+    let tip = RPCStorage::get_block_height().await.unwrap_or(0);
+    let to = query.to.unwrap_or(tip).min(tip);
+    let from = query.from.unwrap_or_else(|| to.saturating_sub(99));
+    if from > to {
+        return (StatusCode::BAD_REQUEST, Json(json!({
+            "success": false,
+            "error": format!("'from' ({}) must not exceed 'to' ({})", from, to)
+        }))).into_response();
+    }
+
+    match RPCStorage::get_difficulty_history(from, to).await {
+        Ok(history) => Json(json!({
+            "success": true,
+            "from": from,
+            "to": history.last().map(|p| p.height).unwrap_or(to),
+            "max_span": MAX_DIFFICULTY_HISTORY_SPAN,
+            "history": history
+        })).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "success": false,
+            "error": format!("Failed to read difficulty history: {}", e)
+        }))).into_response(),
+    }
+}
+
+// Transactions waiting to be mined, oldest first
+async fn get_mempool() -> impl IntoResponse {
+    match RPCStorage::get_mempool(Utc::now().timestamp() as u64).await {
+        Ok(transactions) => Json(json!({
+            "success": true,
+            "count": transactions.len(),
+            "total_fees": transactions.iter().map(|tx| tx.fee).sum::<f64>(),
+            "transactions": transactions
+        })).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "success": false,
+            "error": format!("Failed to read mempool: {}", e)
+        }))).into_response(),
+    }
+}
+
+// Suggested low/medium/high transfer fees for the current congestion
+async fn get_fee_estimate() -> impl IntoResponse {
+    match current_fee_estimate(Utc::now().timestamp() as u64).await {
+        Ok(estimate) => Json(json!({
+            "success": true,
+            "estimate": estimate
+        })).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "success": false,
+            "error": format!("Failed to estimate fees: {}", e)
+        }))).into_response(),
+    }
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    q: String,
+}
+
+// Explorer search box: a block height, a block or transaction hash, or an address
+async fn search_blockchain(Query(params): Query<SearchParams>) -> impl IntoResponse {
+    match explorer_search::search(&params.q).await {
+        Ok(result) => {
+            let status = if result == SearchResult::None { StatusCode::NOT_FOUND } else { StatusCode::OK };
+            let mut body = json!({ "success": status == StatusCode::OK, "query": params.q });
+            if let (Some(body), Ok(Value::Object(found))) = (body.as_object_mut(), serde_json::to_value(&result)) {
+                body.extend(found);
+            }
+            (status, Json(body)).into_response()
+        }
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "success": false,
+            "error": format!("Search failed: {}", e)
+        }))).into_response(),
+    }
+}
+
+// Prometheus scrape target
+async fn metrics_endpoint() -> impl IntoResponse {
+    match ChainMetrics::collect(Utc::now().timestamp() as u64).await {
+        Ok(chain) => (
+            [(axum::http::header::CONTENT_TYPE, PROMETHEUS_CONTENT_TYPE)],
+            render_prometheus(&chain, &SECURITY_MONITOR),
+        ).into_response(),
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, format!("Failed to collect metrics: {}", e)).into_response(),
+    }
+}
+
 // Get block by height
 async fn get_block_by_height(Path(height): Path<u64>) -> Json<Value> {
     match RPCStorage::get_block_by_height(height).await {
-        Ok(Some(block)) => Json(json!({
-            "success": true,
-            "block": block
-        })),
+        Ok(Some(block)) => {
+            let tip = RPCStorage::get_block_height().await.unwrap_or(0);
+            Json(json!({
+                "success": true,
+                "block": block,
+                "confirmations": confirmations(height, tip),
+                "finalized": is_finalized(height, tip, *CONFIRMATION_DEPTH),
+                "confirmation_depth": *CONFIRMATION_DEPTH
+            }))
+        },
         Ok(None) => Json(json!({
             "success": false,
             "error": "Block not found"
@@ -2658,14 +3078,37 @@ async fn get_block_by_height(Path(height): Path<u64>) -> Json<Value> {
 // Get transaction by hash
 async fn get_transaction_by_hash(Path(hash): Path<String>) -> Json<Value> {
     match RPCStorage::get_transaction(&hash).await {
-        Ok(Some(transaction)) => Json(json!({
-            "success": true,
-            "transaction": transaction
-        })),
-        Ok(None) => Json(json!({
-            "success": false,
-            "error": "Transaction not found"
-        })),
+        Ok(Some(transaction)) => {
+            let tip = RPCStorage::get_block_height().await.unwrap_or(0);
+            let finalized = is_finalized(transaction.block_height, tip, *CONFIRMATION_DEPTH);
+            Json(json!({
+                "success": true,
+                "status": if finalized { "finalized" } else { "confirmed" },
+                "confirmations": confirmations(transaction.block_height, tip),
+                "finalized": finalized,
+                "confirmation_depth": *CONFIRMATION_DEPTH,
+                "transaction": transaction
+            }))
+        },
+        // Not in a block: report whether it is still waiting or expired unmined
+        Ok(None) => match RPCStorage::pending_status(&hash, Utc::now().timestamp() as u64).await {
+            Ok(Some(status)) => {
+                let mut response = json!({ "success": true, "confirmations": 0, "finalized": false });
+                // Adds "status" ("pending" or "expired") and "valid_until"
+                if let (Some(response), Value::Object(status)) = (response.as_object_mut(), json!(status)) {
+                    response.extend(status);
+                }
+                Json(response)
+            }
+            Ok(None) => Json(json!({
+                "success": false,
+                "error": "Transaction not found"
+            })),
+            Err(e) => Json(json!({
+                "success": false,
+                "error": format!("Failed to get transaction: {}", e)
+            })),
+        },
         Err(e) => Json(json!({
             "success": false,
             "error": format!("Failed to get transaction: {}", e)
@@ -2675,10 +3118,12 @@ async fn get_transaction_by_hash(Path(hash): Path<String>) -> Json<Value> {
 
 // Get balance for address
 async fn get_balance(Path(address): Path<String>) -> Json<Value> {
-    match RPCStorage::get_balance(&address).await {
-        Ok(balance) => Json(json!({
+    match RPCStorage::get_balance_breakdown(&address).await {
+        Ok(breakdown) => Json(json!({
             "success": true,
-            "balance": balance
+            "balance": breakdown.balance,
+            "spendable_balance": breakdown.spendable_balance,
+            "immature_balance": breakdown.immature_balance
         })),
         Err(e) => Json(json!({
             "success": false,
@@ -2687,6 +3132,32 @@ async fn get_balance(Path(address): Path<String>) -> Json<Value> {
     }
 }
 
+#[derive(Deserialize)]
+struct WalletBalancesRequest {
+    addresses: Vec<String>,
+}
+
+// Balances of up to MAX_BALANCE_BATCH addresses in one round-trip
+async fn wallet_balances(payload: Result<Json<WalletBalancesRequest>, JsonRejection>) -> impl IntoResponse {
+    let request = match payload {
+        Ok(Json(request)) => request,
+        Err(rejection) => return handle_json_rejection(rejection).into_response(),
+    };
+    if request.addresses.len() > MAX_BALANCE_BATCH {
+        return (StatusCode::BAD_REQUEST, Json(json!({
+            "success": false,
+            "error": format!("At most {} addresses per request, got {}", MAX_BALANCE_BATCH, request.addresses.len())
+        }))).into_response();
+    }
+
+    let balances = RPCStorage::get_balances(&request.addresses).await;
+    Json(json!({
+        "success": true,
+        "count": balances.len(),
+        "balances": balances
+    })).into_response()
+}
+
 /// Check if wallet address exists on the server
 async fn wallet_check_address(Path(address): Path<String>) -> Json<Value> {
     // Check if address exists in storage by trying to get balance
@@ -2756,27 +3227,28 @@ async fn wallet_transactions_impl(request: WalletTransactionsRequest) -> Json<Va
         }));
     }
 
-    let limit = request.limit.unwrap_or(5).min(50); // Max 50 transactions
+    let limit = request.limit.unwrap_or(5);
     let transaction_type = request.transaction_type.unwrap_or_default();
 
-    // Get transactions from storage
-    match RPCStorage::get_latest_transactions(limit * 2).await { // Get more to filter
-        Ok(all_transactions) => {
-            // Filter transactions for the specific address
-            let mut filtered_transactions: Vec<serde_json::Value> = all_transactions
+    // Read the address's own index; a type filter needs its whole history, never the global log
+    let history = if transaction_type.is_empty() {
+        RPCStorage::get_address_history_page(&request.address, Some(limit), request.cursor, *ADDRESS_HISTORY_CAP).await
+    } else {
+        RPCStorage::get_transactions_for_address(&request.address, usize::MAX, 0).await.map(|history| {
+            let matching: Vec<WalletTransaction> = history
+                .into_iter()
+                .filter(|tx| tx.transaction_type == transaction_type)
+                .collect();
+            paginate_history(matching, Some(limit), request.cursor, *ADDRESS_HISTORY_CAP)
+        })
+    };
+
+    match history {
+        Ok(page) => {
+            let tip = RPCStorage::get_block_height().await.unwrap_or(0);
+
+            let mut filtered_transactions: Vec<serde_json::Value> = page.items
                 .into_iter()
-                .filter(|tx| {
-                    // Check if transaction involves this address
-                    let involves_address = tx.to == request.address || tx.from == request.address;
-                    
-                    // Filter by transaction type if specified
-                    if !transaction_type.is_empty() {
-                        involves_address && tx.transaction_type == transaction_type
-                    } else {
-                        involves_address
-                    }
-                })
-                .take(limit)
                 .map(|tx| {
                     // Convert to mobile-friendly format
                     let amount = if tx.to == request.address {
@@ -2785,11 +3257,9 @@ async fn wallet_transactions_impl(request: WalletTransactionsRequest) -> Json<Va
                         -(tx.amount as f64 / 1_000_000.0) // Outgoing transaction (negative)
                     };
                     
-                    let status = if tx.transaction_type == "mining_reward" {
-                        "confirmed"
-                    } else {
-                        "confirmed" // All stored transactions are confirmed
-                    };
+                    // Stored transactions are confirmed; finalized once buried CONFIRMATION_DEPTH deep
+                    let confirmation = ConfirmationStatus::at_height(tx.block_height, tip, *CONFIRMATION_DEPTH);
+                    let finalized = confirmation == ConfirmationStatus::Final;
                     
                     json!({
                         "hash": tx.hash,
@@ -2797,7 +3267,10 @@ async fn wallet_transactions_impl(request: WalletTransactionsRequest) -> Json<Va
                         "timestamp": chrono::DateTime::from_timestamp(tx.timestamp as i64, 0)
                             .unwrap_or_else(|| chrono::Utc::now())
                             .to_rfc3339(),
-                        "status": status,
+                        "status": confirmation.as_str(),
+                        "confirmation_status": confirmation,
+                        "confirmations": confirmations(tx.block_height, tip),
+                        "finalized": finalized,
                         "type": tx.transaction_type,
                         "from": tx.from,
                         "to": tx.to,
@@ -2817,7 +3290,9 @@ async fn wallet_transactions_impl(request: WalletTransactionsRequest) -> Json<Va
                 "success": true,
                 "message": "Transactions retrieved successfully",
                 "transactions": filtered_transactions,
-                "total_count": filtered_transactions.len()
+                "total_count": page.total_count,
+                "next_cursor": page.next_cursor,
+                "history_cap": *ADDRESS_HISTORY_CAP
             }))
         },
         Err(e) => {
@@ -2927,14 +3402,18 @@ async fn get_miner_status(Query(params): Query<std::collections::HashMap<String,
     drop(global_miner); // Release the lock early
     
     // Calculate Smart Rate and reward estimation
-    let smart_rate = calculate_smart_rate().await;
-    let vortex_energy = calculate_vortex_energy_rate().await;
-    let fractal_score = calculate_fractal_contribution_score().await;
-    let efficiency_index = calculate_mathematical_efficiency_index().await;
-    let harmony_factor = calculate_network_harmony_factor().await;
+    let SmartRate {
+        smart_rate,
+        vortex_energy_rate: vortex_energy,
+        fractal_contribution_score: fractal_score,
+        mathematical_efficiency_index: efficiency_index,
+        network_harmony_factor: harmony_factor,
+        ..
+    } = calculate_smart_rate().await;
     
     // Calculate estimated daily reward for this device
-    let block_reward = 6.25; // FVC per block
+    let current_height = RPCStorage::get_block_height().await.unwrap_or(0);
+    let block_reward = MiningRewardSystem::new().reward_at_height(current_height) as f64 / 1_000_000.0; // FVC per block
     let blocks_per_day = 17280.0; // 86400 seconds / 5 seconds per block
     let active_devices = RPCStorage::get_all_active_devices().await.unwrap_or_default().len() as f64;
     let device_share = if active_devices > 0.0 { 1.0 / active_devices } else { 1.0 };
@@ -2987,9 +3466,9 @@ async fn get_miner_status(Query(params): Query<std::collections::HashMap<String,
                 _ => 0.0
             };
             
-            // Calculate blocks found based on earnings (6.25 FVC per block)
-            let blocks_found = if actual_earnings > 0.0 {
-                (actual_earnings / 6.25).floor() as u32
+            // Calculate blocks found based on earnings at the current block reward
+            let blocks_found = if actual_earnings > 0.0 && block_reward > 0.0 {
+                (actual_earnings / block_reward).floor() as u32
             } else {
                 0
             };
@@ -3051,10 +3530,13 @@ async fn get_miner_status(Query(params): Query<std::collections::HashMap<String,
 
 // Get mining status
 async fn get_mining_status() -> Json<Value> {
+    let status = RPCStorage::get_difficulty_status().await.ok();
     Json(json!({
         "success": true,
         "mining": false,
-        "difficulty": 1000000
+        "difficulty": status.as_ref().map(|s| s.difficulty),
+        "next_difficulty": status.as_ref().map(|s| s.next_difficulty),
+        "avg_block_time": status.and_then(|s| s.avg_block_time)
     }))
 }
 
@@ -3113,7 +3595,8 @@ async fn api_info() -> Json<Value> {
             "events": [
                 "GET /events",
                 "GET /events/blocks",
-                "GET /events/transactions"
+                "GET /events/transactions",
+                "GET /api/v2/events/blocks"
             ]
         },
         "features": [
@@ -3131,22 +3614,19 @@ async fn api_info() -> Json<Value> {
 
 // Rate limiting stats
 async fn rate_limit_stats() -> Json<Value> {
-    Json(json!({
-        "success": true,
-        "message": "Rate limiting disabled",
-        "global_rate_limiter": {},
-        "endpoint_rate_limiters": {}
-    }))
+    let mut stats = REQUEST_RATE_LIMITS.get_stats();
+    stats["success"] = json!(true);
+    Json(stats)
 }
 
 // Get monitoring statistics
 async fn get_monitoring_stats() -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let endpoint_stats = SECURITY_MONITOR.endpoint_stats();
     let response = serde_json::json!({
         "status": "success",
         "data": {
-            "endpoint_stats": {},
-            "total_endpoints": 0,
-            "message": "API monitoring disabled",
+            "total_endpoints": endpoint_stats.len(),
+            "endpoint_stats": endpoint_stats,
             "timestamp": std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
@@ -3161,10 +3641,7 @@ async fn get_monitoring_stats() -> Result<Json<serde_json::Value>, (StatusCode,
 async fn get_health_status() -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
     let response = serde_json::json!({
         "status": "success",
-        "data": {
-            "status": "healthy",
-            "message": "API monitoring disabled"
-        }
+        "data": SECURITY_MONITOR.request_health()
     });
     
     Ok(Json(response))
@@ -3172,12 +3649,12 @@ async fn get_health_status() -> Result<Json<serde_json::Value>, (StatusCode, Jso
 
 // Get recent security events
 async fn get_security_events() -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
+    let events = TX_ANOMALY_DETECTOR.flagged_transactions(100);
     let response = serde_json::json!({
         "status": "success",
         "data": {
-            "security_events": [],
-            "message": "API monitoring disabled",
-            "count": 0,
+            "count": events.len(),
+            "security_events": events,
             "timestamp": std::time::SystemTime::now()
                 .duration_since(std::time::UNIX_EPOCH)
                 .unwrap()
@@ -3216,7 +3693,7 @@ async fn get_nodes_status() -> Json<Value> {
         },
         "nodes": nodes_status,
         "load_balancer": {
-            "algorithm": "round_robin",
+            "algorithm": NODE_MANAGER.balancer.strategy().as_str(),
             "current_counter": LOAD_BALANCER_COUNTER.load(Ordering::Relaxed)
         },
         "timestamp": chrono::Utc::now().timestamp()
@@ -3271,24 +3748,93 @@ async fn get_node_details(Path(node_id): Path<usize>) -> Json<Value> {
     }))
 }
 
+// Identity, version and capabilities of the primary node
+async fn node_info() -> Json<Value> {
+    let node_guard = BLOCKCHAIN_NODE.lock().await;
+    match node_guard.as_ref() {
+        Some(node) => Json(json!({
+            "success": true,
+            "api_version": "1.0",
+            "node": node.get_identity()
+        })),
+        None => Json(json!({
+            "success": false,
+            "error": "Blockchain node not initialized",
+            "api_version": "1.0",
+            "version": fractal_vortex_chain::VERSION,
+            "chain_id": fractal_vortex_chain::CHAIN_ID
+        })),
+    }
+}
+
+// Identify the node that served this request via the load balancer
+async fn served_by_node() -> impl IntoResponse {
+    match NODE_MANAGER.execute_on_node(|node| Ok::<_, std::convert::Infallible>(node.get_identity())).await {
+        Ok((node_id, identity)) => (StatusCode::OK, Json(json!({
+            "success": true,
+            "node_id": node_id,
+            "node": identity
+        }))).into_response(),
+        Err(e) => (e.status_code(), Json(json!({
+            "success": false,
+            "error": e.to_string()
+        }))).into_response(),
+    }
+}
+
+// Tear down node `node_id` and launch a fresh one from its configuration; the slot stays
+// empty if that fails
+async fn relaunch_node(node_id: usize) -> Result<(), Box<dyn std::error::Error>> {
+    let old = BLOCKCHAIN_NODES.lock().await[node_id].take();
+    // The legacy primary handle shares node 0's ledger and must go before it can be reopened
+    if node_id == 0 {
+        BLOCKCHAIN_NODE.lock().await.take();
+    }
+
+    let node = match old {
+        Some(node) => node.restart(NODE_INIT_ATTEMPTS, NODE_INIT_BACKOFF).await?,
+        None => {
+            let config = cluster_node_config(node_id)?;
+            FractalNode::launch(config, NODE_INIT_ATTEMPTS, NODE_INIT_BACKOFF).await?
+        }
+    };
+    if node_id == 0 {
+        *BLOCKCHAIN_NODE.lock().await = Some(node.clone());
+    }
+    BLOCKCHAIN_NODES.lock().await[node_id] = Some(node);
+    info!("♻️ Node-{} restarted", node_id);
+    Ok(())
+}
+
 // Restart a specific node
-async fn restart_node(Path(node_id): Path<usize>) -> Json<Value> {
+async fn restart_node(Path(node_id): Path<usize>) -> impl IntoResponse {
     if node_id >= 4 {
         return Json(json!({
             "success": false,
             "error": "Invalid node ID. Valid range: 0-3",
             "api_version": "1.0"
-        }));
+        })).into_response();
     }
+    let cluster_error = |e: ClusterError| (e.status_code(), Json(json!({
+        "success": false,
+        "error": e.to_string(),
+        "api_version": "1.0"
+    }))).into_response();
     
     // Mark node as unhealthy during restart
-    NODE_MANAGER.update_node_health(node_id, false).await;
+    if let Err(e) = NODE_MANAGER.update_node_health(node_id, false).await {
+        return cluster_error(e);
+    }
     
-    // Simulate restart process (in production, this would restart the actual node)
-    tokio::time::sleep(Duration::from_millis(500)).await;
+    if let Err(e) = relaunch_node(node_id).await {
+        log::error!("❌ Failed to restart node-{}: {}", node_id, e);
+        return cluster_error(ClusterError::OperationFailed { node: node_id, reason: e.to_string() });
+    }
     
     // Mark node as healthy after restart
-    NODE_MANAGER.update_node_health(node_id, true).await;
+    if let Err(e) = NODE_MANAGER.update_node_health(node_id, true).await {
+        return cluster_error(e);
+    }
     
     Json(json!({
         "success": true,
@@ -3296,7 +3842,7 @@ async fn restart_node(Path(node_id): Path<usize>) -> Json<Value> {
         "message": format!("Node {} restarted successfully", node_id),
         "node_id": node_id,
         "restart_timestamp": chrono::Utc::now().timestamp()
-    }))
+    })).into_response()
 }
 
 // Get cluster metrics and performance statistics
@@ -3321,7 +3867,7 @@ async fn get_cluster_metrics() -> Json<Value> {
             "cluster_efficiency": if healthy_count > 2 { "optimal" } else if healthy_count > 0 { "degraded" } else { "critical" }
         },
         "performance": {
-            "load_distribution": "round_robin",
+            "load_distribution": NODE_MANAGER.balancer.strategy().as_str(),
             "failover_enabled": true,
             "auto_recovery": true,
             "health_check_interval": "30s"
@@ -3345,6 +3891,11 @@ async fn create_app() -> Router {
             .unwrap()
             .as_secs(),
     };
+    // X-API-Key scopes for admin and mining-control routes
+    let admin_only = axum::middleware::from_fn_with_state(Scope::Admin, require_scope);
+    let mining_control = axum::middleware::from_fn_with_state(Scope::Mining, require_scope);
+    // Mobile apps mine without a key unless the operator opts in
+    let mobile_mining_control = axum::middleware::from_fn_with_state((mobile_mining_requires_key(), Scope::Mining), require_scope_if);
 
     Router::new()
         // Root endpoint
@@ -3357,6 +3908,15 @@ async fn create_app() -> Router {
         .route("/api/v1/blockchain/transactions/:hash", get(get_transaction_by_hash))
         .route("/api/v1/blockchain/network/info", get(get_network_info))
         .route("/api/v1/blockchain/stats", get(get_stats))
+        .route("/api/v1/blockchain/difficulty-history", get(get_difficulty_history))
+        .route("/api/v1/blockchain/mempool", get(get_mempool))
+        .route("/api/v1/blockchain/fee-estimate", get(get_fee_estimate))
+        .route("/api/v1/blockchain/search", get(search_blockchain))
+        .route("/api/v1/audit/supply", get(audit_supply).layer(admin_only.clone()))
+        .route("/api/v1/node/info", get(node_info))
+        .route("/api/v1/node/served-by", get(served_by_node))
+        .route("/api/v1/time", get(time_endpoint))
+        .route("/rpc", post(json_rpc_endpoint))
         
         // Legacy blockchain endpoints (for backward compatibility)
         .route("/blocks", get(get_blocks))
@@ -3367,83 +3927,98 @@ async fn create_app() -> Router {
         .route("/stats", get(get_stats))
         
         // Mining endpoints - Consolidated to /api/v1/mining/*
-        .route("/api/v1/mining/start", post(start_miner))
-        .route("/api/v1/mining/stop", post(stop_miner))
+        .route("/api/v1/mining/start", post(start_miner).layer(mining_control.clone()))
+        .route("/api/v1/mining/stop", post(stop_miner).layer(mining_control.clone()))
         .route("/api/v1/mining/status", get(get_miner_status))
         .route("/api/v1/mining/heartbeat", post(mining_heartbeat))
         .route("/api/v1/mining/detection/stats", get(mining_detection_stats))
         .route("/api/v1/mining/reward/estimation", get(reward_estimation))
         .route("/api/v1/mining/register", post(miner_register))
         .route("/api/v1/mining/unregister", post(miner_unregister))
-        .route("/api/v1/mining/reset", post(reset_miner))
+        .route("/api/v1/mining/reset", post(reset_miner).layer(mining_control.clone()))
+        .route("/api/v1/mining/template", get(mining_template))
+        .route("/api/v1/mining/submit", post(mining_submit))
         
         // Legacy mining endpoints (for backward compatibility - will be deprecated)
-        .route("/mining/start", post(start_miner))
-        .route("/mining/stop", post(stop_miner))
+        .route("/mining/start", post(start_miner).layer(mining_control.clone()))
+        .route("/mining/stop", post(stop_miner).layer(mining_control.clone()))
         .route("/mining/status", get(get_mining_status))
         .route("/miner/status", get(get_miner_status))
         
         // Wallet endpoints - Consolidated to /api/v1/wallet/*
         .route("/api/v1/wallet/create", post(wallet_create_post))
-        .route("/api/v1/wallet/send", post(wallet_send))
+        .route("/api/v1/wallet/send", post(wallet_send).layer(axum::middleware::from_fn(server_time_middleware)))
         .route("/api/v1/wallet/balance/:address", get(get_balance))
+        .route("/api/v1/wallet/balances", post(wallet_balances))
         .route("/api/v1/wallet/check/:address", get(wallet_check_address))
         .route("/api/v1/wallet/transactions", post(wallet_transactions))
+        .route("/api/v1/account/:address", get(get_account))
+        .route("/api/v1/account/:address/export", get(export_account_history))
+        .route("/api/v1/faucet", post(faucet_request))
+        .route("/api/v1/account/:address/balance", get(get_account_balance_at_height))
+        .route("/api/v1/admin/labels", post(admin_set_label).layer(admin_only.clone()))
+        .route("/api/v1/admin/labels/:address", delete(admin_clear_label).layer(admin_only.clone()))
         
         // Legacy wallet endpoints (for backward compatibility)
         .route("/wallet/create", get(wallet_create))
         .route("/wallet/create", post(wallet_create_post))
-        .route("/wallet/send", post(wallet_send))
+        .route("/wallet/send", post(wallet_send).layer(axum::middleware::from_fn(server_time_middleware)))
         .route("/wallet/balance/:address", get(get_balance))
         .route("/balance/:address", get(get_balance))
         
         // Device endpoints - Consolidated to /api/v1/device/*
-        .route("/api/v1/device/verify", post(device_verify))
+        .route("/api/v1/device/verify", post(device_verify).layer(axum::middleware::from_fn(server_time_middleware)))
         .route("/api/v1/device/validate", post(device_validate))
-        .route("/api/v1/device/register", post(device_register))
+        .route("/api/v1/device/register", post(device_register).layer(axum::middleware::from_fn(server_time_middleware)))
         .route("/api/v1/device/session/:device_id", get(device_session))
         .route("/api/v1/device/pin-status/:device_id", get(device_pin_status))
         .route("/api/v1/device/create-pin/:device_id", post(device_create_pin))
         .route("/api/v1/device/setup-pin/:device_id", post(device_setup_pin))
-        .route("/api/v1/device/verify-pin/:device_id", post(device_verify_pin))
-        .route("/api/v1/device/login/:device_id", post(device_login))
+        .route("/api/v1/device/verify-pin/:device_id", post(device_verify_pin).layer(axum::middleware::from_fn(server_time_middleware)))
+        .route("/api/v1/device/login/:device_id", post(device_login).layer(axum::middleware::from_fn(server_time_middleware)))
         .route("/api/v1/device/reset-pin/:device_id", post(device_reset_pin))
         .route("/api/v1/device/wallet/get", post(device_get_wallet))
         .route("/api/v1/device/wallet/save", post(device_save_wallet))
         .route("/api/v1/device/save-wallet", post(device_save_wallet_address))
-        .route("/api/v1/device/wallet/send", post(device_send))
+        .route("/api/v1/device/wallet/send", post(device_send).layer(axum::middleware::from_fn(server_time_middleware)))
         .route("/api/v1/device/address", get(device_get_address))
         .route("/api/v1/device/private-key/:device_id", post(device_store_private_key))
-        .route("/api/v1/device/private-key/:device_id", get(device_get_private_key))
+        .route("/api/v1/device/private-key/:device_id/unlock", post(device_get_private_key))
         .route("/api/v1/device/private-key/:device_id", delete(device_remove_private_key))
         .route("/api/v1/device/clear-data/:device_id", delete(device_clear_data))
         .route("/api/v1/device/heartbeat", post(device_heartbeat))
         
         // Legacy device endpoints (for backward compatibility)
-        .route("/device/verify", post(device_verify))
+        .route("/device/verify", post(device_verify).layer(axum::middleware::from_fn(server_time_middleware)))
         .route("/device/validate", post(device_validate))
-        .route("/device/register", post(device_register))
+        .route("/device/register", post(device_register).layer(axum::middleware::from_fn(server_time_middleware)))
         .route("/device/session/:device_id", get(device_session))
         .route("/device/pin-status/:device_id", get(device_pin_status))
-        .route("/device/verify-pin/:device_id", post(device_verify_pin))
-        .route("/device/send", post(device_send))
+        .route("/device/verify-pin/:device_id", post(device_verify_pin).layer(axum::middleware::from_fn(server_time_middleware)))
+        .route("/device/send", post(device_send).layer(axum::middleware::from_fn(server_time_middleware)))
         .route("/device/heartbeat", post(device_heartbeat))
         
         // Admin endpoints - Consolidated to /api/v1/admin/* (removed unused endpoints)
-        .route("/api/v1/admin/monitoring/stats", get(get_monitoring_stats))
-        .route("/api/v1/admin/monitoring/health", get(get_health_status))
-        .route("/api/v1/admin/monitoring/security-events", get(get_security_events))
+        .route("/api/v1/admin/monitoring/stats", get(get_monitoring_stats).layer(admin_only.clone()))
+        .route("/api/v1/admin/monitoring/health", get(get_health_status).layer(admin_only.clone()))
+        .route("/api/v1/admin/monitoring/security-events", get(get_security_events).layer(admin_only.clone()))
+        .route("/api/v1/admin/rate-limit/stats", get(rate_limit_stats).layer(admin_only.clone()))
+
+        // Prometheus scrape endpoint
+        // Same stats as the admin monitoring endpoints, so the same key is required to scrape them
+        .route("/metrics", get(metrics_endpoint).layer(admin_only.clone()))
         
         // Multi-Node Cluster Management endpoints - /api/v1/cluster/*
         .route("/api/v1/cluster/nodes/status", get(get_nodes_status))
         .route("/api/v1/cluster/nodes/:node_id", get(get_node_details))
-        .route("/api/v1/cluster/nodes/:node_id/restart", post(restart_node))
+        .route("/api/v1/cluster/nodes/:node_id/restart", post(restart_node).layer(admin_only.clone()))
         .route("/api/v1/cluster/metrics", get(get_cluster_metrics))
         
         // Legacy admin endpoints (for backward compatibility)
-        .route("/admin/monitoring/stats", get(get_monitoring_stats))
-        .route("/admin/monitoring/health", get(get_health_status))
-        .route("/admin/monitoring/security-events", get(get_security_events))
+        .route("/admin/monitoring/stats", get(get_monitoring_stats).layer(admin_only.clone()))
+        .route("/admin/monitoring/health", get(get_health_status).layer(admin_only.clone()))
+        .route("/admin/monitoring/security-events", get(get_security_events).layer(admin_only.clone()))
+        .route("/admin/rate-limit/stats", get(rate_limit_stats).layer(admin_only.clone()))
         
         // Legacy cluster endpoints (for backward compatibility)
         .route("/cluster/nodes/status", get(get_nodes_status))
@@ -3454,42 +4029,43 @@ async fn create_app() -> Router {
         .route("/api/v1/events/stream", get(sse_endpoint))
         .route("/api/v1/events/blocks", get(blocks_sse_endpoint))
         .route("/api/v1/events/transactions", get(transactions_sse_endpoint))
+        .route("/api/v2/events/blocks", get(blocks_replay_sse_endpoint))
         
         // Legacy SSE endpoints (for backward compatibility)
         .route("/events", get(sse_endpoint))
         .route("/events/blocks", get(blocks_sse_endpoint))
         .route("/events/transactions", get(transactions_sse_endpoint))
         
-        // Mobile API endpoints - Standardized to /api/v1/mobile/* (no rate limiting)
+        // Mobile API endpoints - Standardized to /api/v1/mobile/*
         .route("/api/v1/mobile/mining/status", get(mobile_mining_status))
-        .route("/api/v1/mobile/mining/start", post(mobile_mining_start))
-        .route("/api/v1/mobile/mining/stop", post(mobile_mining_stop))
+        .route("/api/v1/mobile/mining/start", post(mobile_mining_start).layer(mobile_mining_control.clone()))
+        .route("/api/v1/mobile/mining/stop", post(mobile_mining_stop).layer(mobile_mining_control.clone()))
         .route("/api/v1/mobile/wallet/balance", get(mobile_wallet_balance))
         .route("/api/v1/mobile/wallet/transactions", get(mobile_wallet_transactions))
-        .route("/api/v1/mobile/wallet/send", post(mobile_wallet_send))
+        .route("/api/v1/mobile/wallet/send", post(mobile_wallet_send).layer(axum::middleware::from_fn(server_time_middleware)))
         .route("/api/v1/mobile/blockchain/info", get(mobile_blockchain_info))
         .route("/api/v1/mobile/stats", get(mobile_stats))
         
         // Legacy mobile API endpoints (for backward compatibility)
         .route("/api/mobile/mining/status", get(mobile_mining_status))
-        .route("/api/mobile/mining/start", post(mobile_mining_start))
-        .route("/api/mobile/mining/stop", post(mobile_mining_stop))
+        .route("/api/mobile/mining/start", post(mobile_mining_start).layer(mobile_mining_control.clone()))
+        .route("/api/mobile/mining/stop", post(mobile_mining_stop).layer(mobile_mining_control.clone()))
         .route("/api/mobile/wallet/balance", get(mobile_wallet_balance))
         .route("/api/mobile/wallet/transactions", get(mobile_wallet_transactions))
         .route("/api/mobile/wallet/transactions", post(mobile_wallet_transactions_post))
-        .route("/api/mobile/wallet/send", post(mobile_wallet_send))
+        .route("/api/mobile/wallet/send", post(mobile_wallet_send).layer(axum::middleware::from_fn(server_time_middleware)))
         .route("/api/mobile/blockchain/info", get(mobile_blockchain_info))
         .route("/api/mobile/stats", get(mobile_stats))
         
         // Mobile app specific endpoints (for mobile mining flutter app)
         .route("/mobile/api/health", get(mobile_health_check))
         .route("/mobile/api/mining/status", get(mobile_mining_status))
-        .route("/mobile/api/mining/start", post(mobile_mining_start))
-        .route("/mobile/api/mining/stop", post(mobile_mining_stop))
+        .route("/mobile/api/mining/start", post(mobile_mining_start).layer(mobile_mining_control.clone()))
+        .route("/mobile/api/mining/stop", post(mobile_mining_stop).layer(mobile_mining_control.clone()))
         .route("/mobile/api/wallet/balance", get(mobile_wallet_balance))
         .route("/mobile/api/wallet/transactions", get(mobile_wallet_transactions))
         .route("/mobile/api/wallet/transactions", post(mobile_wallet_transactions_post))
-        .route("/mobile/api/wallet/send", post(mobile_wallet_send))
+        .route("/mobile/api/wallet/send", post(mobile_wallet_send).layer(axum::middleware::from_fn(server_time_middleware)))
         .route("/mobile/api/blockchain/info", get(mobile_blockchain_info))
         .route("/mobile/api/stats", get(mobile_stats))
         
@@ -3498,7 +4074,12 @@ async fn create_app() -> Router {
         .route("/debug/active-devices", get(debug_active_devices))
         .route("/debug/all-transactions", get(debug_all_transactions))
         
+        .route_layer(axum::middleware::from_fn_with_state(SECURITY_MONITOR.clone(), request_metrics_middleware))
         .with_state(state)
+        .layer(axum::middleware::from_fn(served_by_middleware))
+        .layer(axum::middleware::from_fn(anomaly_response_middleware))
+        .layer(axum::middleware::from_fn_with_state(REQUEST_RATE_LIMITS.clone(), rate_limit_middleware))
+        .layer(catch_panic_layer())
         .layer(
             CorsLayer::new()
                 .allow_origin(Any) // Allow all origins for optimal mining experience
@@ -3515,6 +4096,7 @@ async fn create_app() -> Router {
                     "Accept-Language".parse().unwrap(),
                     "Cache-Control".parse().unwrap(),
                 ])
+                .expose_headers([SERVED_BY_HEADER.parse::<axum::http::HeaderName>().unwrap()])
                 .allow_credentials(false) // No credentials required for optimal access
                 .max_age(Duration::from_secs(86400)) // Cache preflight for 24 hours for better performance
         )
@@ -3542,6 +4124,13 @@ async fn mobile_mining_status(Query(params): Query<HashMap<String, String>>) ->
     }))
 }
 
+/// Whether mobile mining start/stop need a mining-scope API key (env: MOBILE_MINING_REQUIRE_API_KEY)
+fn mobile_mining_requires_key() -> bool {
+    std::env::var("MOBILE_MINING_REQUIRE_API_KEY")
+        .map(|value| matches!(value.trim().to_ascii_lowercase().as_str(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
 async fn mobile_mining_start(Json(payload): Json<Value>) -> Json<Value> {
     let device_id = payload.get("device_id")
         .and_then(|v| v.as_str())
@@ -3622,6 +4211,8 @@ async fn mobile_wallet_transactions(Query(params): Query<HashMap<String, String>
     let limit = params.get("limit")
         .and_then(|s| s.parse::<usize>().ok())
         .unwrap_or(10);
+    let cursor = params.get("cursor")
+        .and_then(|s| s.parse::<usize>().ok());
     
     // If no address provided, return empty result
     if address.is_empty() {
@@ -3634,10 +4225,10 @@ async fn mobile_wallet_transactions(Query(params): Query<HashMap<String, String>
         }));
     }
     
-    let transactions = get_latest_transactions(limit).await;
+    let transactions = get_latest_transactions(usize::MAX).await;
     
     // Filter transactions for the specific address
-    let filtered_transactions: Vec<_> = transactions.into_iter()
+    let address_history: Vec<_> = transactions.into_iter()
         .filter(|tx| {
             // Convert search address to hex-encoded format for database comparison
             let search_address_hex = if address.starts_with("fvc") {
@@ -3695,6 +4286,12 @@ async fn mobile_wallet_transactions(Query(params): Query<HashMap<String, String>
                 tx.from == search_address_plain || tx.to == search_address_plain
             }
         })
+        .collect();
+
+    let page = paginate_history(address_history, Some(limit), cursor, *ADDRESS_HISTORY_CAP);
+    let tip = RPCStorage::get_block_height().await.unwrap_or(0);
+
+    let filtered_transactions: Vec<_> = page.items.into_iter()
         .map(|tx| {
             // Decode hex addresses for display
             let display_from = if tx.from.len() > 40 {
@@ -3719,6 +4316,7 @@ async fn mobile_wallet_transactions(Query(params): Query<HashMap<String, String>
                 "to": display_to,
                 "amount": tx.amount,
                 "timestamp": tx.timestamp,
+                "finalized": is_finalized(tx.block_height, tip, *CONFIRMATION_DEPTH),
                 "type": if tx.transaction_type == "mining_reward" {
                     "mining_reward"
                 } else if display_from == address || tx.from == address {
@@ -3734,6 +4332,8 @@ async fn mobile_wallet_transactions(Query(params): Query<HashMap<String, String>
         "success": true,
         "transactions": filtered_transactions,
         "count": filtered_transactions.len(),
+        "total_count": page.total_count,
+        "next_cursor": page.next_cursor,
         "address": address,
         "last_update": Utc::now().to_rfc3339()
     }))
@@ -3744,6 +4344,7 @@ async fn mobile_wallet_transactions_post(Json(payload): Json<WalletTransactionsR
     let address = payload.address;
     let limit = payload.limit.unwrap_or(10);
     let transaction_type = payload.transaction_type;
+    let cursor = payload.cursor;
     
     // If no address provided, return empty result
     if address.is_empty() {
@@ -3756,10 +4357,10 @@ async fn mobile_wallet_transactions_post(Json(payload): Json<WalletTransactionsR
         }));
     }
     
-    let transactions = get_latest_transactions(limit * 2).await; // Get more to ensure we have enough after filtering
+    let transactions = get_latest_transactions(usize::MAX).await;
     
     // Filter transactions for the specific address and transaction type
-    let filtered_transactions: Vec<_> = transactions.into_iter()
+    let address_history: Vec<_> = transactions.into_iter()
         .filter(|tx| {
             // Filter by transaction type if specified
             let type_matches = if let Some(ref tx_type) = transaction_type {
@@ -3779,7 +4380,12 @@ async fn mobile_wallet_transactions_post(Json(payload): Json<WalletTransactionsR
             
             type_matches && address_matches
         })
-        .take(limit) // Limit the results
+        .collect();
+
+    let page = paginate_history(address_history, Some(limit), cursor, *ADDRESS_HISTORY_CAP);
+    let tip = RPCStorage::get_block_height().await.unwrap_or(0);
+
+    let filtered_transactions: Vec<_> = page.items.into_iter()
         .map(|tx| {
             // Decode hex addresses for display
             let display_from = if tx.from.len() > 40 {
@@ -3805,6 +4411,7 @@ async fn mobile_wallet_transactions_post(Json(payload): Json<WalletTransactionsR
                 "amount": tx.amount,
                 "timestamp": tx.timestamp,
                 "transaction_type": tx.transaction_type,
+                "finalized": is_finalized(tx.block_height, tip, *CONFIRMATION_DEPTH),
                 "type": if tx.transaction_type == "mining_reward" {
                     "mining_reward"
                 } else if display_from == address || tx.from == address {
@@ -3819,35 +4426,16 @@ async fn mobile_wallet_transactions_post(Json(payload): Json<WalletTransactionsR
     Json(json!({
         "success": true,
         "transactions": filtered_transactions,
-        "total_count": filtered_transactions.len(),
+        "total_count": page.total_count,
+        "next_cursor": page.next_cursor,
         "address": address,
         "last_update": Utc::now().to_rfc3339()
     }))
 }
 
 // Debug endpoint to check transaction registry
-async fn debug_transaction_registry() -> Json<Value> {
-    match RPCStorage::get_transaction_count().await {
-        Ok(count) => {
-            // Get all transactions to see what's in storage
-            let transactions = get_latest_transactions(100).await;
-            let tx_hashes: Vec<String> = transactions.iter().map(|tx| tx.hash.clone()).collect();
-            
-            Json(json!({
-                "success": true,
-                "storage_count": count,
-                "transactions_found": transactions.len(),
-                "transaction_hashes": tx_hashes,
-                "last_10_transactions": transactions.iter().take(10).collect::<Vec<_>>()
-            }))
-        },
-        Err(e) => {
-            Json(json!({
-                "success": false,
-                "error": format!("Failed to get transaction count: {}", e)
-            }))
-        }
-    }
+async fn debug_transaction_registry(headers: HeaderMap) -> impl IntoResponse {
+    debug_api::debug_transaction_registry(&headers, *DEBUG_TX_LIMIT).await
 }
 
 async fn debug_active_devices() -> Json<Value> {
@@ -3900,21 +4488,8 @@ async fn debug_active_devices() -> Json<Value> {
 }
 
 // Debug endpoint to check all transactions
-async fn debug_all_transactions() -> Json<Value> {
-    let transactions = get_latest_transactions(50).await;
-    Json(json!({
-        "success": true,
-        "total_transactions": transactions.len(),
-        "transactions": transactions.iter().map(|tx| json!({
-            "hash": tx.hash,
-            "from": tx.from,
-            "to": tx.to,
-            "amount": tx.amount,
-            "timestamp": tx.timestamp,
-            "transaction_type": tx.transaction_type,
-            "block_height": tx.block_height
-        })).collect::<Vec<_>>()
-    }))
+async fn debug_all_transactions(headers: HeaderMap) -> impl IntoResponse {
+    debug_api::debug_all_transactions(&headers, *DEBUG_TX_LIMIT).await
 }
 
 async fn mobile_wallet_send(Json(payload): Json<Value>) -> Json<Value> {
@@ -3940,6 +4515,9 @@ async fn mobile_wallet_send(Json(payload): Json<Value>) -> Json<Value> {
         amount,
         private_key: private_key.to_string(),
         device_id: device_id.to_string(),
+        nonce: payload.get("nonce").and_then(|v| v.as_u64()),
+        fee: payload.get("fee").and_then(|v| v.as_u64()),
+        signature: payload.get("signature").cloned(),
     };
     
     let state = AppState {
@@ -3952,12 +4530,13 @@ async fn mobile_wallet_send(Json(payload): Json<Value>) -> Json<Value> {
             .as_secs(),
     };
     
-    let result = device_send_impl(State(state), request).await;
+    let (_, Json(result)) = device_send_impl(State(state), request).await;
     
     // Convert to mobile-optimized format
     Json(json!({
         "success": result.get("success").unwrap_or(&json!(false)),
-        "message": result.get("message").unwrap_or(&json!("Unknown error")),
+        "status": result.get("status"),
+        "message": result.get("reason").filter(|r| !r.is_null()).unwrap_or(&json!("Unknown error")),
         "data": {
             "transaction_hash": result.get("transaction_hash"),
             "from": from,
@@ -4103,11 +4682,55 @@ async fn device_heartbeat(Json(payload): Json<Value>) -> Json<Value> {
 #[tokio::main]
 async fn main() {
     println!("Starting Fractal Vortex Chain Integrated Node RPC Server...");
+
+    if let Err(e) = VortexPatternConfig::from_env() {
+        eprintln!("Invalid VORTEX_PATTERN_MULTIPLIERS: {}", e);
+        std::process::exit(1);
+    }
+    match mining_address_from_env() {
+        Ok(address) => println!("⛏️ Mining reward address: {}", address),
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    }
+    match AnomalyResponsePolicy::from_env() {
+        Ok(_) => println!("🛡️ Anomaly response policy: {:?}", REQUEST_ANOMALY_GUARD.policy()),
+        Err(e) => {
+            eprintln!("Invalid anomaly response policy: {}", e);
+            std::process::exit(1);
+        }
+    }
+    match env_api_key_scopes() {
+        scopes if scopes.is_empty() => println!("🔑 No API keys in the environment; admin and mining-control routes accept only stored keys"),
+        scopes => println!("🔑 API keys from the environment for scopes: {:?}", scopes),
+    }
+    if mobile_mining_requires_key() {
+        println!("🔑 Mobile mining start/stop require a mining API key");
+    }
     
     // Initialize storage
+    match RPCStorage::migrate_legacy_transaction_registry().await {
+        Ok(count) => println!("📒 Transaction log holds {} transactions", count),
+        Err(e) => {
+            eprintln!("Failed to migrate the legacy transaction registry: {}", e);
+            std::process::exit(1);
+        }
+    }
+    match RPCStorage::migrate_legacy_addresses().await {
+        Ok(0) => {}
+        Ok(count) => println!("📒 Moved {} balances off pre-checksum addresses", count),
+        Err(e) => {
+            eprintln!("Failed to migrate pre-checksum addresses: {}", e);
+            std::process::exit(1);
+        }
+    }
     if let Err(e) = RPCStorage::create_genesis_block().await {
         eprintln!("Failed to create genesis block: {}", e);
     }
+    if let Err(e) = RPCStorage::preload_ecosystem_labels().await {
+        eprintln!("Failed to preload address labels: {}", e);
+    }
     
     // Initialize 4 blockchain nodes and start ecosystem mining
     println!("🔄 Starting multi-node blockchain initialization...");
@@ -4136,7 +4759,9 @@ async fn main() {
                 log::info!("Session cleanup completed");
             }
             
-            // Also cleanup inactive device sessions (no heartbeat for 5 minutes)
+            // Also cleanup inactive device sessions, keeping them while auto-detection can still resume them
+            let detection_config = AUTO_DETECTION.config();
+            let max_inactive = (detection_config.grace_period + detection_config.resume_window).as_secs();
             match RPCStorage::get_all_active_devices().await {
                 Ok(devices) => {
                     let current_time = chrono::Utc::now().timestamp() as u64;
@@ -4144,8 +4769,8 @@ async fn main() {
                         if let Ok(Some((_, last_activity))) = RPCStorage::get_device_session(&device_id).await {
                             let inactive_time = current_time.saturating_sub(last_activity);
                             
-                            // Remove sessions with no heartbeat for 5 minutes (300 seconds)
-                            if inactive_time > 300 {
+                            // Remove sessions that can no longer be resumed
+                            if inactive_time > max_inactive {
                                 if let Err(e) = RPCStorage::remove_device_session(&device_id).await {
                                     log::error!("Failed to remove inactive device session {}: {}", device_id, e);
                                 } else {
@@ -4175,9 +4800,235 @@ async fn main() {
     println!("🚀 Fractal Vortex Chain RPC Server running on http://0.0.0.0:8080");
     println!("🧹 Session cleanup scheduler started (every 5 minutes)");
     
-    // Run server and cleanup scheduler concurrently
+    // Run server and cleanup scheduler concurrently; Ctrl-C stops accepting connections,
+    // lets in-flight transfers finish and flushes LevelDB before exiting
     tokio::select! {
-        _ = axum::serve(listener, app.into_make_service_with_connect_info::<std::net::SocketAddr>()) => {},
+        result = serve_until(listener, app, shutdown_signal(), SHUTDOWN_GRACE) => match result {
+            Ok(()) => println!("👋 RPC server stopped cleanly"),
+            Err(e) => log::error!("RPC server shutdown failed: {}", e),
+        },
         _ = cleanup_handle => {},
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::extract::ConnectInfo;
+    use fractal_vortex_chain::api_auth::{register_api_key, API_KEY_HEADER};
+    use fractal_vortex_chain::rpc_storage::DatabaseOverride;
+    use fractal_vortex_chain::storage::LedgerDB;
+    use tower::ServiceExt;
+
+    // The library's test helper is not built for binaries, so go through the public override
+    fn use_test_db() -> (DatabaseOverride, tempfile::TempDir) {
+        let dir = tempfile::tempdir().unwrap();
+        (RPCStorage::use_database(Arc::new(LedgerDB::open(dir.path()).unwrap())), dir)
+    }
+
+    fn test_node_config() -> NodeConfig {
+        NodeConfig { listen_addr: "/ip4/127.0.0.1/tcp/0".parse().unwrap(), ..NodeConfig::default() }
+    }
+
+    #[tokio::test]
+    async fn test_restart_node_requires_admin_key() {
+        let _db = use_test_db();
+        register_api_key("test-restart-mining-key", Scope::Mining).await.unwrap();
+
+        let app = create_app().await;
+        let status = |key: Option<&'static str>| {
+            let app = app.clone();
+            async move {
+                let mut request = axum::http::Request::post("/api/v1/cluster/nodes/0/restart");
+                if let Some(key) = key {
+                    request = request.header(API_KEY_HEADER, key);
+                }
+                let mut request = request.body(Body::empty()).unwrap();
+                request.extensions_mut().insert(ConnectInfo(std::net::SocketAddr::from(([127, 0, 0, 1], 9000))));
+                app.oneshot(request).await.unwrap().status()
+            }
+        };
+
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("test-restart-mining-key")).await, StatusCode::FORBIDDEN);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_require_admin_key() {
+        let _db = use_test_db();
+        register_api_key("test-metrics-readonly-key", Scope::Readonly).await.unwrap();
+        register_api_key("test-metrics-admin-key", Scope::Admin).await.unwrap();
+
+        let app = create_app().await;
+        let status = |key: Option<&'static str>| {
+            let app = app.clone();
+            async move {
+                let mut request = axum::http::Request::get("/metrics");
+                if let Some(key) = key {
+                    request = request.header(API_KEY_HEADER, key);
+                }
+                let mut request = request.body(Body::empty()).unwrap();
+                request.extensions_mut().insert(ConnectInfo(std::net::SocketAddr::from(([127, 0, 0, 1], 9001))));
+                app.oneshot(request).await.unwrap().status()
+            }
+        };
+
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("test-metrics-readonly-key")).await, StatusCode::FORBIDDEN);
+        assert_eq!(status(Some("test-metrics-admin-key")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_supply_audit_requires_admin_key() {
+        let _db = use_test_db();
+        register_api_key("test-audit-readonly-key", Scope::Readonly).await.unwrap();
+        register_api_key("test-audit-admin-key", Scope::Admin).await.unwrap();
+
+        let app = create_app().await;
+        let status = |key: Option<&'static str>| {
+            let app = app.clone();
+            async move {
+                let mut request = axum::http::Request::get("/api/v1/audit/supply");
+                if let Some(key) = key {
+                    request = request.header(API_KEY_HEADER, key);
+                }
+                let mut request = request.body(Body::empty()).unwrap();
+                request.extensions_mut().insert(ConnectInfo(std::net::SocketAddr::from(([127, 0, 0, 1], 9003))));
+                app.oneshot(request).await.unwrap().status()
+            }
+        };
+
+        assert_eq!(status(None).await, StatusCode::UNAUTHORIZED);
+        assert_eq!(status(Some("test-audit-readonly-key")).await, StatusCode::FORBIDDEN);
+        assert_eq!(status(Some("test-audit-admin-key")).await, StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn test_accepted_transfer_is_gossiped_to_peers() {
+        use fractal_vortex_chain::node::fractal_node::{NetworkCommand, TRANSACTIONS_TOPIC};
+        let _db = use_test_db();
+        let sender = KeyManager::new();
+        let recipient = "fvc00000000000000000000000000000000b263emyl";
+        RPCStorage::set_balance(&sender.get_address(), 1_000_000).await.unwrap();
+
+        let mut node = FractalNode::new(test_node_config()).await.unwrap();
+        let mut outbound = node.take_network_commands().unwrap();
+        *BLOCKCHAIN_NODE.lock().await = Some(node);
+        let peer = FractalNode::new(test_node_config()).await.unwrap();
+
+        let private_key = hex::encode(sender.get_private_key());
+        let result = submit_and_broadcast("transfer", &sender.get_address(), recipient, 5_000, None, None, &private_key).await;
+        assert_eq!(result.status, SubmissionStatus::Accepted);
+
+        // Deliver the primary node's gossip to the peer as the swarm would
+        let data = match outbound.try_recv().unwrap() {
+            NetworkCommand::Publish { topic, data } if topic == TRANSACTIONS_TOPIC => data,
+            other => panic!("unexpected network command: {:?}", other),
+        };
+        assert!(peer.ingest_gossip_transaction(&data).await.unwrap());
+        let hash = result.transaction_hash.unwrap();
+        let pending = peer.get_consensus().read().await.get_pending_transactions().await;
+        assert!(pending.iter().any(|tx| format!("0x{}", hex::encode(tx.hash)) == hash));
+    }
+
+    #[tokio::test]
+    async fn test_send_carrying_a_signature_is_rejected() {
+        let _db = use_test_db();
+        let sender = KeyManager::new();
+        RPCStorage::set_balance(&sender.get_address(), 1_000_000).await.unwrap();
+
+        let Json(body) = mobile_wallet_send(Json(json!({
+            "from": sender.get_address(),
+            "to": "fvc00000000000000000000000000000000b264emyl",
+            "amount": 5_000,
+            "private_key": hex::encode(sender.get_private_key()),
+            "device_id": "device-b264",
+            "signature": "3045022100b264"
+        }))).await;
+
+        assert_eq!(body["success"], json!(false));
+        assert_eq!(body["status"], json!(SubmissionStatus::Rejected));
+        assert_eq!(RPCStorage::get_balance(&sender.get_address()).await.unwrap(), 1_000_000);
+    }
+
+    #[tokio::test]
+    async fn test_template_requests_are_rate_limited() {
+        let _db = use_test_db();
+        let app = create_app().await;
+        let mut statuses = Vec::new();
+        for _ in 0..20 {
+            let mut request = axum::http::Request::get("/api/v1/mining/template").body(Body::empty()).unwrap();
+            request.extensions_mut().insert(ConnectInfo(std::net::SocketAddr::from(([10, 77, 0, 1], 9002))));
+            statuses.push(app.clone().oneshot(request).await.unwrap().status());
+        }
+        // Charged to the mining bucket, whose burst is 15
+        assert!(statuses[..15].iter().all(|s| *s == StatusCode::OK));
+        assert_eq!(statuses[19], StatusCode::TOO_MANY_REQUESTS);
+    }
+
+    #[test]
+    fn test_template_requests_do_not_evict_other_miners() {
+        let template = |miner: &str, timestamp: u64| {
+            BlockTemplate::new("0".repeat(64), 1, miner.to_string(), fractal_vortex_chain::rpc_storage::BLOCK_DIFFICULTY, &[], timestamp)
+        };
+        let mut templates = MiningTemplates::default();
+        let honest = template("fvchonest", 1_700_000_000);
+        templates.insert(honest.id(), honest.clone());
+
+        // One caller asking over and over only replaces its own oldest templates
+        let flood: Vec<BlockTemplate> = (1..=MAX_OUTSTANDING_TEMPLATES as u64)
+            .map(|i| template("fvcflood", 1_700_000_000 + i))
+            .collect();
+        for t in &flood {
+            templates.insert(t.id(), t.clone());
+        }
+        assert!(templates.get(&honest.id()).is_some());
+        assert_eq!(templates.templates.len(), 1 + MAX_TEMPLATES_PER_MINER);
+        assert!(templates.get(&flood[0].id()).is_none());
+        assert!(templates.get(&flood[flood.len() - 1].id()).is_some());
+
+        // Past the overall cap the oldest template goes first
+        for i in 0..MAX_OUTSTANDING_TEMPLATES {
+            let t = template(&format!("fvcminer{}", i), 1_700_000_000);
+            templates.insert(t.id(), t);
+        }
+        assert_eq!(templates.templates.len(), MAX_OUTSTANDING_TEMPLATES);
+        assert!(templates.get(&honest.id()).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_send_is_signed_by_the_senders_key() {
+        let _db = use_test_db();
+        let sender = KeyManager::new();
+        let from = sender.get_address();
+        let to = "fvc00000000000000000000000000000000b256emyl";
+        RPCStorage::set_balance(&from, 50_000).await.unwrap();
+
+        let app = create_app().await;
+        let send = |key: String| {
+            let app = app.clone();
+            let body = json!({ "from": from.clone(), "to": to, "amount": 1_000, "nonce": 1, "private_key": key });
+            async move {
+                let mut request = axum::http::Request::post("/api/v1/wallet/send")
+                    .header("content-type", "application/json")
+                    .body(Body::from(body.to_string()))
+                    .unwrap();
+                request.extensions_mut().insert(ConnectInfo(std::net::SocketAddr::from(([10, 77, 0, 2], 9003))));
+                app.oneshot(request).await.unwrap().status()
+            }
+        };
+
+        assert_eq!(send(hex::encode(KeyManager::new().get_private_key())).await, StatusCode::BAD_REQUEST);
+        assert_eq!(send(String::new()).await, StatusCode::BAD_REQUEST);
+        assert_eq!(RPCStorage::get_balance(&from).await.unwrap(), 50_000);
+        assert_eq!(RPCStorage::get_account_nonce(&from).await.unwrap(), 0);
+
+        assert_eq!(send(hex::encode(sender.get_private_key())).await, StatusCode::CREATED);
+        let hash = fractal_vortex_chain::tx_submission::build_transfer("transfer", from.clone(), to.to_string(), 1_000, 1).unwrap().hash;
+        let stored = RPCStorage::get_transaction(&hash).await.unwrap().unwrap();
+        assert_eq!(stored.nonce, 1);
+        assert!(stored.has_valid_signature());
+        assert_eq!(RPCStorage::get_balance(to).await.unwrap(), 1_000);
+    }
+}
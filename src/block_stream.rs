@@ -0,0 +1,119 @@
+use futures::stream::{self, Stream};
+use tokio::sync::broadcast::{self, error::RecvError};
+use crate::rpc_storage::{Block, RPCStorage, BLOCK_EVENTS};
+use crate::storage::StorageError;
+
+#[derive(Clone, Copy)]
+enum Phase {
+    Replay { next: u64, tip: u64 },
+    Live,
+}
+
+struct Subscription {
+    rx: broadcast::Receiver<Block>,
+    phase: Phase,
+    last_height: Option<u64>,
+}
+
+/// Stream newly stored blocks. With `from_height`, stored blocks from that height up to the
+/// current tip are replayed first; live blocks at or below the last delivered height are skipped
+/// so the handoff has no gap and no duplicates.
+pub async fn subscribe_blocks(
+    from_height: Option<u64>,
+) -> Result<impl Stream<Item = Result<Block, StorageError>>, StorageError> {
+    // Subscribe before reading the tip so a block stored in between is not missed
+    let rx = BLOCK_EVENTS.subscribe();
+    let tip = RPCStorage::get_block_height().await?;
+    let phase = match from_height {
+        Some(next) => Phase::Replay { next, tip },
+        None => Phase::Live,
+    };
+
+    Ok(stream::unfold(Subscription { rx, phase, last_height: None }, |mut sub| async move {
+        loop {
+            match sub.phase {
+                Phase::Replay { next, tip } => {
+                    if next > tip {
+                        sub.phase = Phase::Live;
+                        continue;
+                    }
+                    sub.phase = match next.checked_add(1) {
+                        Some(following) => Phase::Replay { next: following, tip },
+                        None => Phase::Live,
+                    };
+                    match RPCStorage::get_block_by_height(next).await {
+                        Ok(Some(block)) => {
+                            sub.last_height = Some(block.height);
+                            return Some((Ok(block), sub));
+                        }
+                        Ok(None) => continue,
+                        Err(e) => return Some((Err(e), sub)),
+                    }
+                }
+                Phase::Live => match sub.rx.recv().await {
+                    Ok(block) => {
+                        if sub.last_height.map_or(false, |last| block.height <= last) {
+                            continue;
+                        }
+                        sub.last_height = Some(block.height);
+                        return Some((Ok(block), sub));
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        log::warn!("Block subscriber lagged, {} blocks skipped", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => return None,
+                },
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_storage::use_test_db;
+    use futures::StreamExt;
+    use std::time::Duration;
+
+    fn mined_block(height: u64, parent_hash: String) -> Block {
+        let mut block = Block::new_with_timestamp(height, "fvcminer".to_string(), parent_hash, 1_700_000_000 + height);
+        for nonce in 0u64.. {
+            block.nonce = nonce;
+            block.hash = block.canonical_hash();
+            if block.has_valid_pow() {
+                break;
+            }
+        }
+        block
+    }
+
+    #[tokio::test]
+    async fn test_replay_then_live_exactly_once() {
+        let _db = use_test_db();
+
+        let base = 700_100;
+        let mut parent_hash = "0".repeat(64);
+        for height in base..base + 3 {
+            let block = mined_block(height, parent_hash);
+            RPCStorage::store_block(&block).await.unwrap();
+            parent_hash = block.hash;
+        }
+
+        let stream = subscribe_blocks(Some(base + 1)).await.unwrap();
+        futures::pin_mut!(stream);
+
+        let live = mined_block(base + 3, parent_hash);
+        RPCStorage::store_block(&live).await.unwrap();
+        // A re-stored historical block must not be delivered again
+        let stale = RPCStorage::get_block_by_height(base + 2).await.unwrap().unwrap();
+        RPCStorage::store_block(&stale).await.unwrap();
+
+        let mut heights = Vec::new();
+        for _ in 0..3 {
+            heights.push(stream.next().await.unwrap().unwrap().height);
+        }
+        assert_eq!(heights, vec![base + 1, base + 2, base + 3]);
+        assert!(tokio::time::timeout(Duration::from_millis(200), stream.next()).await.is_err());
+    }
+}
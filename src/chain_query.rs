@@ -0,0 +1,151 @@
+use serde_json::Value;
+use crate::wallet::rpc_client::RpcClient;
+
+/// What `fvc-node query` looks up
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChainQuery {
+    Block(u64),
+    Balance(String),
+    Transaction(String),
+}
+
+/// Format microFVC as a decimal FVC amount
+fn format_fvc(micro: u64) -> String {
+    format!("{}.{:06} FVC", micro / 1_000_000, micro % 1_000_000)
+}
+
+fn field<'a>(value: &'a Value, name: &str) -> &'a Value {
+    value.get(name).unwrap_or(&Value::Null)
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        Value::Null => "-".to_string(),
+        other => other.to_string(),
+    }
+}
+
+fn amount(value: &Value) -> String {
+    value.as_u64().map(format_fvc).unwrap_or_else(|| "-".to_string())
+}
+
+/// Human-readable view of a `/blocks/:height` reply
+pub fn format_block(reply: &Value) -> String {
+    let block = field(reply, "block");
+    format!(
+        "Block #{}\n  Hash:          {}\n  Parent:        {}\n  Miner:         {}\n  Timestamp:     {}\n  Transactions:  {}\n  Difficulty:    {}\n  Confirmations: {} (finalized: {})\n",
+        text(field(block, "height")),
+        text(field(block, "hash")),
+        text(field(block, "parent_hash")),
+        text(field(block, "miner")),
+        text(field(block, "timestamp")),
+        text(field(block, "transaction_count")),
+        text(field(block, "difficulty")),
+        text(field(reply, "confirmations")),
+        text(field(reply, "finalized")),
+    )
+}
+
+/// Human-readable view of a `/balance/:address` reply
+pub fn format_balance(address: &str, reply: &Value) -> String {
+    format!(
+        "Balance of {}\n  Total:     {}\n  Spendable: {}\n  Immature:  {}\n",
+        address,
+        amount(field(reply, "balance")),
+        amount(field(reply, "spendable_balance")),
+        amount(field(reply, "immature_balance")),
+    )
+}
+
+/// Human-readable view of a `/transaction/:hash` reply
+pub fn format_transaction(reply: &Value) -> String {
+    let tx = field(reply, "transaction");
+    format!(
+        "Transaction {}\n  Type:          {}\n  From:          {}\n  To:            {}\n  Amount:        {}\n  Block:         {}\n  Status:        {} ({} confirmations)\n",
+        text(field(tx, "hash")),
+        text(field(tx, "transaction_type")),
+        text(field(tx, "from")),
+        text(field(tx, "to")),
+        amount(field(tx, "amount")),
+        text(field(tx, "block_height")),
+        text(field(reply, "status")),
+        text(field(reply, "confirmations")),
+    )
+}
+
+/// Run `query` against the node behind `client` and format the reply
+pub async fn run_query(client: &RpcClient, query: &ChainQuery) -> Result<String, Box<dyn std::error::Error>> {
+    Ok(match query {
+        ChainQuery::Block(height) => format_block(&client.get_block(*height).await?),
+        ChainQuery::Balance(address) => format_balance(address, &client.get_balance_breakdown(address).await?),
+        ChainQuery::Transaction(hash) => format_transaction(&client.get_transaction_envelope(hash).await?),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{extract::Path, routing::get, Json, Router};
+    use serde_json::json;
+
+    /// Serve canned node replies on an ephemeral port; returns the base URL
+    async fn mock_node() -> String {
+        let app = Router::new()
+            .route("/blocks/:height", get(|Path(height): Path<u64>| async move {
+                if height != 42 {
+                    return Json(json!({ "success": false, "error": "Block not found" }));
+                }
+                Json(json!({
+                    "success": true,
+                    "block": {
+                        "height": 42,
+                        "hash": "0xabc",
+                        "parent_hash": "0xdef",
+                        "miner": "fvc00000000000000000000000000000000c001emyl",
+                        "timestamp": 1_700_000_000u64,
+                        "transaction_count": 3,
+                        "difficulty": 2
+                    },
+                    "confirmations": 7,
+                    "finalized": true
+                }))
+            }))
+            .route("/balance/:address", get(|| async {
+                Json(json!({
+                    "success": true,
+                    "balance": 12_500_000u64,
+                    "spendable_balance": 6_250_000u64,
+                    "immature_balance": 6_250_000u64
+                }))
+            }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_query_formats_block_and_balance() {
+        let client = RpcClient::new(&mock_node().await);
+
+        let block = run_query(&client, &ChainQuery::Block(42)).await.unwrap();
+        assert!(block.starts_with("Block #42\n"));
+        assert!(block.contains("Hash:          0xabc"));
+        assert!(block.contains("Transactions:  3"));
+        assert!(block.contains("Confirmations: 7 (finalized: true)"));
+
+        let address = "fvc00000000000000000000000000000000c001emyl";
+        let balance = run_query(&client, &ChainQuery::Balance(address.to_string())).await.unwrap();
+        assert_eq!(
+            balance,
+            format!(
+                "Balance of {}\n  Total:     12.500000 FVC\n  Spendable: 6.250000 FVC\n  Immature:  6.250000 FVC\n",
+                address
+            )
+        );
+
+        let missing = run_query(&client, &ChainQuery::Block(7)).await.unwrap_err();
+        assert_eq!(missing.to_string(), "Block not found");
+    }
+}
@@ -0,0 +1,385 @@
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use crate::rpc_storage::{sender_fee, Block, RPCStorage};
+use crate::storage::StorageError;
+
+/// A block that failed verification
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockFailure {
+    pub height: u64,
+    pub reason: String,
+}
+
+/// A stored balance that disagrees with the replayed chain
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceMismatch {
+    pub address: String,
+    pub replayed: u64,
+    pub stored: u64,
+}
+
+/// Result of verifying a chain end to end
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ChainVerificationReport {
+    pub blocks_checked: usize,
+    pub failures: Vec<BlockFailure>,
+    pub balance_mismatches: Vec<BalanceMismatch>,
+}
+
+impl ChainVerificationReport {
+    pub fn is_valid(&self) -> bool {
+        self.failures.is_empty() && self.balance_mismatches.is_empty()
+    }
+
+    /// Heights with at least one failure, ascending and deduplicated
+    pub fn failing_heights(&self) -> Vec<u64> {
+        let mut heights: Vec<u64> = self.failures.iter().map(|f| f.height).collect();
+        heights.sort_unstable();
+        heights.dedup();
+        heights
+    }
+}
+
+/// Check parent linkage, canonical hashes and cumulative difficulty of blocks ordered by height
+pub fn verify_blocks(blocks: &[Block]) -> Vec<BlockFailure> {
+    let mut failures = Vec::new();
+    let mut previous: Option<&Block> = None;
+
+    for block in blocks {
+        let mut fail = |reason: String| failures.push(BlockFailure { height: block.height, reason });
+
+        if let Some(parent) = previous {
+            if block.height <= parent.height {
+                fail(format!("height {} does not follow {}", block.height, parent.height));
+            }
+            if block.parent_hash != parent.hash {
+                fail(format!("parent hash {} does not match block {} hash {}", block.parent_hash, parent.height, parent.hash));
+            }
+        }
+
+        // Genesis is not mined, and chains created before real hashing carry a placeholder genesis hash
+        if block.height > 0 {
+            let expected = block.canonical_hash();
+            if block.hash != expected {
+                fail(format!("hash {} does not match recomputed {}", block.hash, expected));
+            }
+        }
+
+        if block.transaction_count != block.transactions.len() as u64 {
+            fail(format!("transaction_count {} but {} transactions", block.transaction_count, block.transactions.len()));
+        }
+
+        if block.cumulative_difficulty != 0 {
+            let expected = block.expected_cumulative_difficulty(previous);
+            if block.cumulative_difficulty != expected {
+                fail(format!("cumulative difficulty {} expected {}", block.cumulative_difficulty, expected));
+            }
+        }
+
+        previous = Some(block);
+    }
+
+    failures
+}
+
+/// Stored hash of a block against the one recomputed from its canonical bytes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HashCheck {
+    pub height: u64,
+    pub stored_hash: String,
+    pub calculated_hash: String,
+    pub is_valid: bool,
+}
+
+impl HashCheck {
+    pub fn of(block: &Block) -> Self {
+        let calculated_hash = block.canonical_hash();
+        Self {
+            height: block.height,
+            is_valid: calculated_hash == block.hash,
+            stored_hash: block.hash.clone(),
+            calculated_hash,
+        }
+    }
+}
+
+/// Hash check of the stored block at `height`, or None when there is no block there
+pub async fn check_stored_block_hash(height: u64) -> Result<Option<HashCheck>, StorageError> {
+    Ok(RPCStorage::get_block_by_height(height).await?.as_ref().map(HashCheck::of))
+}
+
+/// A transfer that exceeds its sender's running balance within a block
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, thiserror::Error)]
+#[error("transaction {hash} overdraws {address}: balance {balance}, amount {amount}")]
+pub struct Overdraft {
+    pub hash: String,
+    pub address: String,
+    pub balance: u64,
+    pub amount: u64,
+}
+
+fn running_balance<'a>(touched: &'a mut HashMap<String, u64>, balances: &HashMap<String, u64>, address: &str) -> &'a mut u64 {
+    touched
+        .entry(address.to_string())
+        .or_insert_with(|| balances.get(address).copied().unwrap_or(0))
+}
+
+/// Apply `block`'s transactions in block order on top of `balances`, debiting each transfer's
+/// amount plus its fee. A transfer that would overdraw its sender rejects the whole block and
+/// leaves `balances` unchanged.
+pub fn apply_block_spends(block: &Block, balances: &mut HashMap<String, u64>) -> Result<(), Overdraft> {
+    let mut touched: HashMap<String, u64> = HashMap::new();
+
+    for tx in &block.transactions {
+        match tx.transaction_type.as_str() {
+            "genesis" => {}
+            "mining_reward" => {
+                let to = running_balance(&mut touched, balances, &tx.to);
+                *to = to.saturating_add(tx.amount);
+            }
+            _ => {
+                let from = running_balance(&mut touched, balances, &tx.from);
+                let debit = tx.amount.saturating_add(sender_fee(tx));
+                *from = from.checked_sub(debit).ok_or_else(|| Overdraft {
+                    hash: tx.hash.clone(),
+                    address: tx.from.clone(),
+                    balance: *from,
+                    amount: debit,
+                })?;
+                let to = running_balance(&mut touched, balances, &tx.to);
+                *to = to.saturating_add(tx.amount);
+            }
+        }
+    }
+
+    balances.extend(touched);
+    Ok(())
+}
+
+/// Blocks containing a transfer that overdraws its sender, replayed from the genesis allocations
+pub fn verify_block_spends(blocks: &[Block], initial: &HashMap<String, u64>) -> Vec<BlockFailure> {
+    let mut balances = initial.clone();
+    let mut failures = Vec::new();
+
+    for block in blocks {
+        if let Err(overdraft) = apply_block_spends(block, &mut balances) {
+            failures.push(BlockFailure { height: block.height, reason: overdraft.to_string() });
+            // Keep checking later blocks against the lenient replay
+            balances = replay_balances(std::slice::from_ref(block), &balances);
+        }
+    }
+
+    failures
+}
+
+/// Replay block transactions on top of the genesis allocations
+pub fn replay_balances(blocks: &[Block], initial: &HashMap<String, u64>) -> HashMap<String, u64> {
+    let mut balances = initial.clone();
+
+    for tx in blocks.iter().flat_map(|b| b.transactions.iter()) {
+        match tx.transaction_type.as_str() {
+            "genesis" => {}
+            "mining_reward" => {
+                let to = balances.entry(tx.to.clone()).or_insert(0);
+                *to = to.saturating_add(tx.amount);
+            }
+            _ => {
+                let from = balances.entry(tx.from.clone()).or_insert(0);
+                *from = from.saturating_sub(tx.amount.saturating_add(sender_fee(tx)));
+                let to = balances.entry(tx.to.clone()).or_insert(0);
+                *to = to.saturating_add(tx.amount);
+            }
+        }
+    }
+
+    balances
+}
+
+/// Verify blocks and compare replayed balances against the stored ones
+pub fn verify_chain(
+    blocks: &[Block],
+    initial: &HashMap<String, u64>,
+    stored_balances: &HashMap<String, u64>,
+) -> ChainVerificationReport {
+    let mut failures = verify_blocks(blocks);
+    failures.extend(verify_block_spends(blocks, initial));
+    failures.sort_by_key(|f| f.height);
+
+    let mut balance_mismatches: Vec<BalanceMismatch> = replay_balances(blocks, initial)
+        .into_iter()
+        .filter_map(|(address, replayed)| {
+            let stored = stored_balances.get(&address).copied().unwrap_or(0);
+            (stored != replayed).then(|| BalanceMismatch { address, replayed, stored })
+        })
+        .collect();
+    balance_mismatches.sort_by(|a, b| a.address.cmp(&b.address));
+
+    ChainVerificationReport {
+        blocks_checked: blocks.len(),
+        failures,
+        balance_mismatches,
+    }
+}
+
+/// Load every stored block and balance from RPC storage and verify them
+pub async fn verify_stored_chain(initial: &HashMap<String, u64>) -> Result<ChainVerificationReport, StorageError> {
+    let latest_height = RPCStorage::get_block_height().await?;
+
+    let mut blocks = Vec::new();
+    for height in 0..=latest_height {
+        if let Some(block) = RPCStorage::get_block_by_height(height).await? {
+            blocks.push(block);
+        }
+    }
+
+    let mut stored_balances = HashMap::new();
+    for address in replay_balances(&blocks, initial).keys() {
+        stored_balances.insert(address.clone(), RPCStorage::get_balance(address).await?);
+    }
+
+    Ok(verify_chain(&blocks, initial, &stored_balances))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_storage::use_test_db;
+    use crate::rpc_storage::WalletTransaction;
+
+    fn small_chain() -> Vec<Block> {
+        let mut blocks: Vec<Block> = Vec::new();
+        let mut genesis = Block::new_with_timestamp(0, "Genesis".to_string(), "0".repeat(64), 1_700_000_000);
+        genesis.link_to_parent(None);
+        blocks.push(genesis);
+
+        for height in 1..=3u64 {
+            let parent = blocks.last().unwrap();
+            let mut block = Block::new_with_real_hash_and_timestamp(
+                height,
+                "fvcminer".to_string(),
+                parent.hash.clone(),
+                [0u8; 32],
+                height * 7,
+                2,
+                1_700_000_000 + height * 5,
+            );
+            block.add_transaction(WalletTransaction::new_mining_reward(
+                "fvcalice".to_string(),
+                100,
+                format!("reward{}", height),
+                height,
+            ));
+            block.hash = block.canonical_hash();
+            block.link_to_parent(Some(parent));
+            blocks.push(block);
+        }
+        blocks
+    }
+
+    #[test]
+    fn test_valid_chain_passes() {
+        let blocks = small_chain();
+        let stored: HashMap<String, u64> = [("fvcalice".to_string(), 300)].into_iter().collect();
+
+        let report = verify_chain(&blocks, &HashMap::new(), &stored);
+        assert_eq!(report.blocks_checked, 4);
+        assert!(report.is_valid(), "unexpected failures: {:?}", report);
+    }
+
+    #[test]
+    fn test_tampered_block_is_reported() {
+        let mut blocks = small_chain();
+        blocks[2].timestamp += 1;
+        let stored: HashMap<String, u64> = [("fvcalice".to_string(), 300)].into_iter().collect();
+
+        let report = verify_chain(&blocks, &HashMap::new(), &stored);
+        assert!(!report.is_valid());
+        assert_eq!(report.failing_heights(), vec![2]);
+    }
+
+    fn transfer(from: &str, to: &str, amount: u64, hash: &str) -> WalletTransaction {
+        WalletTransaction {
+            fee: 5,
+            ..WalletTransaction::new_transfer(from.to_string(), to.to_string(), amount, hash.to_string(), 1)
+        }
+    }
+
+    #[test]
+    fn test_sequential_transfers_within_balance_apply() {
+        let mut block = Block::new_with_timestamp(1, "fvcminer".to_string(), "0".repeat(64), 1_700_000_000);
+        block.add_transaction(transfer("fvcalice", "fvcbob", 60, "t1"));
+        // Bob spends funds received earlier in the same block
+        block.add_transaction(transfer("fvcbob", "fvccarol", 80, "t2"));
+        let mut balances: HashMap<String, u64> = [("fvcalice".to_string(), 100), ("fvcbob".to_string(), 30)].into_iter().collect();
+
+        apply_block_spends(&block, &mut balances).unwrap();
+        // Each sender also pays its fee
+        assert_eq!((balances["fvcalice"], balances["fvcbob"], balances["fvccarol"]), (35, 5, 80));
+    }
+
+    #[test]
+    fn test_intra_block_double_spend_rejected() {
+        let mut block = Block::new_with_timestamp(1, "fvcminer".to_string(), "0".repeat(64), 1_700_000_000);
+        block.add_transaction(transfer("fvcalice", "fvcbob", 60, "t1"));
+        block.add_transaction(transfer("fvcalice", "fvccarol", 50, "t2"));
+        let initial: HashMap<String, u64> = [("fvcalice".to_string(), 100)].into_iter().collect();
+
+        let mut balances = initial.clone();
+        let overdraft = apply_block_spends(&block, &mut balances).unwrap_err();
+        assert_eq!((overdraft.hash.as_str(), overdraft.balance, overdraft.amount), ("t2", 35, 55));
+        assert_eq!(balances, initial);
+
+        let failures = verify_block_spends(std::slice::from_ref(&block), &initial);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].height, 1);
+    }
+
+    #[test]
+    fn test_fee_counts_against_the_sender() {
+        let mut block = Block::new_with_timestamp(1, "fvcminer".to_string(), "0".repeat(64), 1_700_000_000);
+        block.add_transaction(transfer("fvcalice", "fvcbob", 100, "t1"));
+        let initial: HashMap<String, u64> = [("fvcalice".to_string(), 100)].into_iter().collect();
+
+        // The amount alone is covered, the amount plus fee is not
+        let overdraft = apply_block_spends(&block, &mut initial.clone()).unwrap_err();
+        assert_eq!((overdraft.balance, overdraft.amount), (100, 105));
+        let replayed = replay_balances(std::slice::from_ref(&block), &[("fvcalice".to_string(), 105)].into_iter().collect());
+        assert_eq!((replayed["fvcalice"], replayed["fvcbob"]), (0, 100));
+    }
+
+    #[test]
+    fn test_balance_mismatch_is_reported() {
+        let blocks = small_chain();
+        let stored: HashMap<String, u64> = [("fvcalice".to_string(), 999)].into_iter().collect();
+
+        let report = verify_chain(&blocks, &HashMap::new(), &stored);
+        assert!(report.failures.is_empty());
+        assert_eq!(report.balance_mismatches.len(), 1);
+        assert_eq!(report.balance_mismatches[0].replayed, 300);
+    }
+
+    #[tokio::test]
+    async fn test_mined_block_verifies_after_storage() {
+        use crate::crypto::fractal_hash::FractalPoW;
+        use crate::rpc_storage::{BLOCK_DIFFICULTY, BLOCK_FRACTAL_LEVELS};
+        let _db = use_test_db();
+
+        // Mined the way the ecosystem miner does: nonces ground over the header bytes
+        let height = 650_000;
+        let mut block = Block::new_with_timestamp(height, "fvcminer".to_string(), "0".repeat(64), 1_700_000_000);
+        block.add_transaction(WalletTransaction::new_mining_reward("fvcalice".to_string(), 100, "reward650000".to_string(), height));
+        let (nonce, mined) = FractalPoW::new(BLOCK_DIFFICULTY, BLOCK_FRACTAL_LEVELS).mine_from(&block.header_bytes(), 0);
+        block.nonce = u64::from_le_bytes(nonce[..8].try_into().unwrap());
+        block.hash = format!("0x{}", hex::encode(mined.hash));
+        RPCStorage::store_block(&block).await.unwrap();
+
+        let check = check_stored_block_hash(height).await.unwrap().unwrap();
+        assert!(check.is_valid, "{:?}", check);
+        assert_eq!(check.calculated_hash, block.hash);
+        assert!(check_stored_block_hash(height + 1).await.unwrap().is_none());
+
+        let mut tampered = block.clone();
+        tampered.miner = "fvcthief".to_string();
+        assert!(!HashCheck::of(&tampered).is_valid);
+    }
+}
@@ -1,3 +1,8 @@
+/// Target seconds between blocks
+pub const TARGET_BLOCK_TIME_SECS: u64 = 5;
+/// Number of most recent block intervals `next_difficulty` averages over
+pub const DIFFICULTY_WINDOW: usize = 20;
+
 #[derive(Debug, Clone)]
 pub struct DifficultyAdjuster {
     target_block_time: u64,
@@ -42,6 +47,43 @@ impl DifficultyAdjuster {
         new_difficulty.max(1)
     }
 
+    /// Retarget from the last `DIFFICULTY_WINDOW` intervals of `recent_timestamps` (ascending, seconds).
+    /// Faster than `target_block_time` raises difficulty, slower lowers it, by at most 4x either way.
+    /// The result is reported by the RPC status endpoints only; block validation does not enforce it.
+    pub fn next_difficulty(
+        &self,
+        current_difficulty: u64,
+        recent_timestamps: &[u64],
+        target_block_time: u64,
+    ) -> u64 {
+        let window = &recent_timestamps[recent_timestamps.len().saturating_sub(DIFFICULTY_WINDOW + 1)..];
+        if window.len() < 2 {
+            return current_difficulty;
+        }
+
+        let intervals = (window.len() - 1) as u64;
+        let expected_time = target_block_time.saturating_mul(intervals);
+        // Out-of-order timestamps count as instant blocks rather than underflowing
+        let actual_time = window[window.len() - 1].saturating_sub(window[0]).max(1);
+
+        let adjustment_factor = (expected_time as f64 / actual_time as f64).clamp(
+            1.0 / self.max_adjustment_factor,
+            self.max_adjustment_factor,
+        );
+
+        ((current_difficulty as f64 * adjustment_factor).round() as u64).max(1)
+    }
+
+    /// Mean seconds between consecutive `timestamps`, or None with fewer than two
+    pub fn average_block_time(timestamps: &[u64]) -> Option<f64> {
+        let window = &timestamps[timestamps.len().saturating_sub(DIFFICULTY_WINDOW + 1)..];
+        if window.len() < 2 {
+            return None;
+        }
+        let span = window[window.len() - 1].saturating_sub(window[0]);
+        Some(span as f64 / (window.len() - 1) as f64)
+    }
+
     pub fn validate_block_time(&self, block_time: u64) -> bool {
         // Allow some flexibility in block times
         block_time > 0 && block_time < self.target_block_time * 10
@@ -86,4 +128,56 @@ mod tests {
         let new_diff = adjuster.calculate_new_difficulty(1000000, &very_slow);
         assert!(new_diff >= 250000); // Max 1/4 decrease
     }
-}
\ No newline at end of file
+
+    fn timestamps(intervals: &[u64]) -> Vec<u64> {
+        let mut ts = vec![1_700_000_000];
+        for interval in intervals {
+            ts.push(ts[ts.len() - 1] + interval);
+        }
+        ts
+    }
+
+    #[test]
+    fn test_next_difficulty_fast_blocks() {
+        let adjuster = DifficultyAdjuster::new(TARGET_BLOCK_TIME_SECS, DIFFICULTY_WINDOW as u64);
+
+        // 2.5s blocks against a 5s target double the difficulty
+        let fast = timestamps(&[2, 3].repeat(10));
+        assert_eq!(adjuster.next_difficulty(1000, &fast, 5), 2000);
+
+        // Only the last 20 intervals count: slow history before the window is ignored
+        let mut intervals = vec![60; 30];
+        intervals.extend([2, 3].repeat(10));
+        assert_eq!(adjuster.next_difficulty(1000, &timestamps(&intervals), 5), 2000);
+
+        // Same-second blocks are clamped to 4x
+        let instant = vec![1_700_000_000; 21];
+        assert_eq!(adjuster.next_difficulty(1000, &instant, 5), 4000);
+    }
+
+    #[test]
+    fn test_next_difficulty_slow_blocks() {
+        let adjuster = DifficultyAdjuster::new(TARGET_BLOCK_TIME_SECS, DIFFICULTY_WINDOW as u64);
+
+        let slow = timestamps(&[10; 20]);
+        assert_eq!(adjuster.next_difficulty(1000, &slow, 5), 500);
+
+        // A stall is clamped to a 4x drop, and difficulty never reaches zero
+        let stalled = timestamps(&[600; 20]);
+        assert_eq!(adjuster.next_difficulty(1000, &stalled, 5), 250);
+        assert_eq!(adjuster.next_difficulty(1, &stalled, 5), 1);
+    }
+
+    #[test]
+    fn test_next_difficulty_stable_at_target() {
+        let adjuster = DifficultyAdjuster::new(TARGET_BLOCK_TIME_SECS, DIFFICULTY_WINDOW as u64);
+
+        let on_target = timestamps(&[5; 40]);
+        assert_eq!(adjuster.next_difficulty(1_000_000, &on_target, 5), 1_000_000);
+        assert_eq!(DifficultyAdjuster::average_block_time(&on_target), Some(5.0));
+
+        // Too little history to measure keeps the current difficulty
+        assert_eq!(adjuster.next_difficulty(1_000_000, &on_target[..1], 5), 1_000_000);
+        assert_eq!(DifficultyAdjuster::average_block_time(&on_target[..1]), None);
+    }
+}
@@ -155,6 +155,18 @@ impl VortexMath {
         }
     }
 
+    /// Vortex math over a custom base pattern, e.g. to check a sequence that is not 1-2-4-8-7-5
+    pub fn with_pattern(base_pattern: [u8; 6]) -> Self {
+        Self {
+            base_pattern,
+            ..Self::new()
+        }
+    }
+
+    pub fn base_pattern(&self) -> [u8; 6] {
+        self.base_pattern
+    }
+
     /// Calculate next value in vortex sequence
     pub fn next_vortex_value(&mut self, input: u64) -> u8 {
         let reduced = self.reduce_to_digital_root(input);
@@ -164,7 +176,7 @@ impl VortexMath {
     }
 
     /// Digital root calculation (mod 9)
-    fn reduce_to_digital_root(&self, number: u64) -> u8 {
+    pub fn reduce_to_digital_root(&self, number: u64) -> u8 {
         let mut n = number;
         while n >= 10 {
             n = n.to_string()
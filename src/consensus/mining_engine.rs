@@ -1,4 +1,9 @@
 use crate::consensus::{DifficultyAdjuster, MiningRewardSystem, RewardDistribution};
+use crate::crypto::fractal_hash::FractalPoW;
+use crate::rpc_storage::{
+    Block, RPCStorage, WalletTransaction, BLOCK_DIFFICULTY, BLOCK_FRACTAL_LEVELS,
+};
+use crate::storage::StorageError;
 use std::time::{SystemTime, UNIX_EPOCH};
 
 #[derive(Debug, Clone)]
@@ -7,6 +12,8 @@ pub struct MiningEngine {
     reward_system: MiningRewardSystem,
     current_difficulty: u64,
     current_block_height: u64,
+    /// Transactions in the chain up to `current_block_height`, coinbases included
+    transaction_count: u64,
     block_times: Vec<u64>,
     last_block_timestamp: u64,
 }
@@ -40,6 +47,7 @@ impl MiningEngine {
             reward_system,
             current_difficulty: 10, // Initial difficulty set to 10
             current_block_height: 0,
+            transaction_count: 0,
             block_times: Vec::new(),
             last_block_timestamp: SystemTime::now()
                 .duration_since(UNIX_EPOCH)
@@ -48,134 +56,9 @@ impl MiningEngine {
         }
     }
     
-    // Calculate Smart Rate based on blockchain metrics
+    /// Smart Rate of the chain this engine has mined, from the shared `smart_rate` module
     fn calculate_smart_rate(&self) -> f64 {
-        // Calculate individual components
-        let vortex_energy = self.calculate_vortex_energy_rate();
-        let fractal_score = self.calculate_fractal_contribution_score();
-        let efficiency_index = self.calculate_mathematical_efficiency_index();
-        let harmony_factor = self.calculate_network_harmony_factor();
-        
-        // Normalize components to 0-1 range
-        let normalized_ver = (vortex_energy / 1000.0).min(1.0);
-        let normalized_fcs = (fractal_score / 1000.0).min(1.0);
-        let normalized_mei = (efficiency_index / 1000.0).min(1.0);
-        let normalized_nhf = (harmony_factor / 1000.0).min(1.0);
-        
-        // Weights for each component (sum = 1.0)
-        let w_ver = 0.30; // Vortex Energy Rate weight
-        let w_fcs = 0.25; // Fractal Contribution Score weight
-        let w_mei = 0.25; // Mathematical Efficiency Index weight
-        let w_nhf = 0.20; // Network Harmony Factor weight
-        
-        // Calculate weighted geometric mean
-        let weighted_geometric_mean = (normalized_ver.powf(w_ver) * 
-                                     normalized_fcs.powf(w_fcs) * 
-                                     normalized_mei.powf(w_mei) * 
-                                     normalized_nhf.powf(w_nhf)).max(0.001);
-        
-        // Base Smart Rate (in Smart Steps per Second)
-        let base_smart_rate = 1000.0;
-        
-        // Vortex Pattern based on block height
-        let vortex_pattern = 1.0 + (0.1 * ((self.current_block_height as f64 * 0.618).sin() + 1.0));
-        
-        // Final Smart Rate calculation
-        let smart_rate = base_smart_rate * weighted_geometric_mean * vortex_pattern;
-        
-        // Round to 2 decimal places
-        (smart_rate * 100.0).round() / 100.0
-    }
-    
-    fn calculate_vortex_energy_rate(&self) -> f64 {
-        if self.current_block_height == 0 {
-            return 0.0;
-        }
-        
-        // Simulate transaction throughput (simplified for mining engine)
-        let throughput: f64 = 1.0; // 1 transaction per block average
-        
-        // Energy efficiency factor
-        let energy_efficiency: f64 = 0.85; // 85% efficiency
-        
-        // Network load factor
-        let network_load = (throughput / 10.0).min(1.0); // Normalize to max 10 tx/block
-        
-        // Vortex Energy Rate calculation
-        let ver = throughput * energy_efficiency * (1.0 + network_load) * 100.0;
-        
-        // Ensure minimum value and round
-        ver.max(1.0).min(1000.0)
-    }
-    
-    fn calculate_fractal_contribution_score(&self) -> f64 {
-        if self.current_block_height <= 1 {
-            return 0.0;
-        }
-        
-        // Fractal depth based on block height
-        let fractal_depth = (self.current_block_height as f64).log2().floor();
-        
-        // Transaction density (simplified for mining engine)
-        let tx_density = 1.0; // 1 transaction per block average
-        
-        // Fractal pattern strength
-        let pattern_strength = (fractal_depth / 20.0).min(1.0); // Normalize to max depth 20
-        
-        // Contribution score calculation
-        let fcs = tx_density * (1.0 + pattern_strength) * fractal_depth * 10.0;
-        
-        // Ensure minimum value and cap at 1000
-        fcs.max(1.0).min(1000.0)
-    }
-    
-    fn calculate_mathematical_efficiency_index(&self) -> f64 {
-        if self.current_block_height == 0 {
-            return 0.0;
-        }
-        
-        // Processing efficiency (simplified for mining engine)
-        let throughput = 1.0; // 1 transaction per block average
-        
-        // Mathematical constants
-        let golden_ratio = 1.618033988749; // φ (phi)
-        let euler_number = std::f64::consts::E; // e
-        
-        // Computational complexity factor
-        let complexity_factor = (self.current_block_height as f64).log10() / 10.0; // Normalize log scale
-        
-        // Mathematical efficiency calculation
-        let mei = throughput * golden_ratio * (1.0 + complexity_factor) * euler_number * 10.0;
-        
-        // Ensure minimum value and cap at 1000
-        mei.max(1.0).min(1000.0)
-    }
-    
-    fn calculate_network_harmony_factor(&self) -> f64 {
-        if self.current_block_height == 0 {
-            return 0.0;
-        }
-        
-        // Block production consistency
-        let block_consistency = if self.current_block_height > 10 {
-            let recent_blocks = std::cmp::min(self.current_block_height, 100);
-            recent_blocks as f64 / 100.0
-        } else {
-            self.current_block_height as f64 / 10.0
-        };
-        
-        // Transaction flow smoothness (simplified for mining engine)
-        let tx_flow: f64 = 1.0 / 10.0; // 1 tx per block, normalized to expected 10 tx/block
-        let transaction_smoothness = tx_flow.min(1.0);
-        
-        // Network synchronization (simulated)
-        let sync_factor = 0.95; // 95% network sync
-        
-        // Harmony calculation with weighted average
-        let harmony = (block_consistency * 0.4 + transaction_smoothness * 0.4 + sync_factor * 0.2) * 1000.0;
-        
-        // Ensure minimum value and cap at 1000
-        harmony.max(1.0).min(1000.0)
+        crate::smart_rate::compute(self.current_block_height, self.transaction_count).smart_rate
     }
 
     pub fn get_mining_info(&self) -> MiningStats {
@@ -215,8 +98,9 @@ impl MiningEngine {
         // Add block time to history
         self.block_times.push(block_time);
         
-        // Update block height
+        // Update block height; a block added by time alone carries just its coinbase
         self.current_block_height += 1;
+        self.transaction_count += 1;
         
         // Adjust difficulty if needed
         if self.current_block_height % 2023 == 0 && self.block_times.len() >= 2023 {
@@ -247,6 +131,55 @@ impl MiningEngine {
         })
     }
 
+    /// Assemble and solve the block at `height` on `parent_hash`: a coinbase paying
+    /// `reward_at_height` to `miner` comes first, followed by `transactions`
+    pub fn assemble_block(
+        &self,
+        height: u64,
+        parent_hash: String,
+        miner: &str,
+        transactions: Vec<WalletTransaction>,
+        timestamp: u64,
+    ) -> Block {
+        let mut block = Block::new_with_timestamp(height, miner.to_string(), parent_hash, timestamp);
+        block.difficulty = BLOCK_DIFFICULTY as u64;
+
+        let mut coinbase = WalletTransaction::coinbase(miner.to_string(), self.reward_system.reward_at_height(height), height);
+        coinbase.timestamp = timestamp;
+        block.add_transaction(coinbase);
+        for tx in transactions {
+            block.add_transaction(tx);
+        }
+
+        let pow = FractalPoW::new(BLOCK_DIFFICULTY, BLOCK_FRACTAL_LEVELS);
+        let (nonce_bytes, block_hash) = pow.mine(&block.header_bytes());
+        block.nonce = u64::from_le_bytes(nonce_bytes[..8].try_into().unwrap());
+        block.hash = format!("0x{}", hex::encode(block_hash.hash));
+        block
+    }
+
+    /// Mine the next block on the stored tip and store it, crediting its coinbase to `miner`
+    /// in the same write
+    pub async fn mine_block(&mut self, miner: &str, transactions: Vec<WalletTransaction>) -> Result<Block, StorageError> {
+        let height = RPCStorage::next_block_height().await?;
+        let parent_hash = RPCStorage::find_parent_block(height).await?
+            .map(|parent| parent.hash)
+            .unwrap_or_else(|| "0".repeat(64));
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let block = self.assemble_block(height, parent_hash, miner, transactions, timestamp);
+        RPCStorage::store_mined_block(&block).await?;
+
+        self.block_times.push(timestamp.saturating_sub(self.last_block_timestamp));
+        self.last_block_timestamp = timestamp;
+        self.current_block_height = height;
+        self.transaction_count = RPCStorage::get_transaction_count().await?;
+        Ok(block)
+    }
+
     pub fn get_block_reward(&self, block_height: u64) -> RewardDistribution {
         self.reward_system.calculate_reward(block_height)
     }
@@ -271,6 +204,7 @@ impl MiningEngine {
 
     pub fn reset_to_genesis(&mut self) {
         self.current_block_height = 0;
+        self.transaction_count = 0;
         self.current_difficulty = 10; // Reset to initial difficulty 10
         self.block_times.clear();
         self.last_block_timestamp = SystemTime::now()
@@ -323,4 +257,14 @@ mod tests {
         assert!(stats.blocks_per_day > 0.0);
         assert_eq!(stats.difficulty, 10); // Check initial difficulty 10
     }
+
+    #[test]
+    fn test_smart_rate_follows_mined_chain() {
+        let mut engine = MiningEngine::new();
+        for _ in 0..12 {
+            engine.add_block(5).unwrap();
+        }
+        let expected = crate::smart_rate::compute(12, 12).smart_rate;
+        assert_eq!(engine.get_mining_info().network_smart_rate, expected);
+    }
 }
\ No newline at end of file
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 
+/// Base block reward before any halving, in microFVC (6.25 FVC)
+pub const BASE_BLOCK_REWARD: u64 = 6_250_000;
+
 /// Mining reward system untuk FVChain dengan mekanisme halving ala Bitcoin
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MiningRewardSystem {
@@ -98,6 +101,33 @@ impl MiningRewardSystem {
         }
     }
 
+    /// Block reward at `height` in microFVC: BASE_BLOCK_REWARD halved once per halving interval, zero after max halvings
+    pub fn reward_at_height(&self, height: u64) -> u64 {
+        let epoch = height / self.halving_interval;
+        if epoch >= self.max_halvings as u64 {
+            return 0;
+        }
+        BASE_BLOCK_REWARD >> epoch
+    }
+
+    /// First halving strictly after `height`, with rewards in microFVC
+    pub fn next_halving(&self, height: u64) -> HalvingEvent {
+        let epoch = (height / self.halving_interval + 1) as u32;
+        let block_height = epoch as u64 * self.halving_interval;
+        let seconds_from_genesis = block_height * self.block_time;
+        let years_from_genesis = seconds_from_genesis as f64 / (365.25 * 24.0 * 3600.0);
+
+        HalvingEvent {
+            epoch,
+            block_height,
+            reward_before: self.reward_at_height(block_height - 1) as u128,
+            reward_after: self.reward_at_height(block_height) as u128,
+            timestamp: self.genesis_timestamp + seconds_from_genesis,
+            years_from_genesis,
+            estimated_date: format!("August 9, {}", 2025 + years_from_genesis as u32),
+        }
+    }
+
     /// Menghitung total supply yang sudah ditambang hingga block tertentu
     pub fn total_mined_supply(&self, block_height: u64) -> u128 {
         let mut total = 0u128;
@@ -317,4 +347,32 @@ mod tests {
         assert!(max_supply > 2_340_000_000);
         assert!(max_supply < 2_341_000_000);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_reward_at_height_across_first_halving() {
+        let system = MiningRewardSystem::new();
+
+        assert_eq!(system.reward_at_height(0), 6_250_000);
+        assert_eq!(system.reward_at_height(12_614_399), 6_250_000);
+        assert_eq!(system.reward_at_height(12_614_400), 3_125_000);
+        assert_eq!(system.reward_at_height(12_614_400 * 32), 0);
+    }
+
+    #[test]
+    fn test_next_halving() {
+        let system = MiningRewardSystem::new();
+
+        let first = system.next_halving(0);
+        assert_eq!(first.epoch, 1);
+        assert_eq!(first.block_height, 12_614_400);
+        assert_eq!(first.reward_before, 6_250_000);
+        assert_eq!(first.reward_after, 3_125_000);
+        assert_eq!(system.next_halving(12_614_399).block_height, 12_614_400);
+
+        // At the halving height itself the next one is a full interval away
+        let second = system.next_halving(12_614_400);
+        assert_eq!(second.block_height, 25_228_800);
+        assert_eq!(second.reward_before, 3_125_000);
+        assert_eq!(second.reward_after, 1_562_500);
+    }
+}
@@ -9,5 +9,5 @@ pub mod mining_engine;
 pub use fractal_vortex::{FractalVortexConsensus, Block, FractalTopology, VortexMath};
 pub use vortex_consensus::{VortexConsensus, VortexBlock, Transaction, ConsensusStats};
 pub use mining_rewards::{MiningRewardSystem, RewardDistribution, MiningStats, HalvingEvent};
-pub use difficulty_adjuster::DifficultyAdjuster;
+pub use difficulty_adjuster::{DifficultyAdjuster, DIFFICULTY_WINDOW, TARGET_BLOCK_TIME_SECS};
 pub use mining_engine::{MiningEngine, MiningResult};
\ No newline at end of file
@@ -1,10 +1,18 @@
-use std::collections::{HashMap, HashSet};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use serde::{Serialize, Deserialize};
 use libp2p::PeerId;
 use crate::crypto::fractal_hash::FractalHasher;
 use crate::network::torus_topology::TorusNetwork;
+use crate::wallet::key_manager::KeyManager;
+
+/// Maximum seconds a block timestamp may run ahead of local time
+pub const MAX_FUTURE_DRIFT_SECS: u64 = 120;
+
+/// Maximum pending transactions taken into one block
+pub const MAX_BLOCK_TRANSACTIONS: usize = 100;
 
 /// Vortex consensus state machine
 pub struct VortexConsensus {
@@ -30,8 +38,8 @@ pub struct ConsensusState {
     pub block_dag: BlockDAG,
     /// Finalized blocks
     pub finalized_blocks: HashSet<[u8; 32]>,
-    /// Pending transactions
-    pub pending_txs: Vec<Transaction>,
+    /// Pending transactions, highest fee first
+    pub mempool: Mempool,
     /// Vortex energy distribution
     pub energy_distribution: HashMap<PeerId, f64>,
 }
@@ -75,6 +83,119 @@ pub struct Transaction {
     pub vortex_fee: f64,
 }
 
+impl Transaction {
+    /// Bytes covered by the sender's signature, the content hash first
+    pub fn signing_payload(&self) -> Vec<u8> {
+        let mut data = self.hash.to_vec();
+        data.extend_from_slice(&self.from);
+        data.extend_from_slice(&self.to);
+        data.extend_from_slice(&self.amount.to_le_bytes());
+        data.extend_from_slice(&self.nonce.to_le_bytes());
+        data.extend_from_slice(&self.vortex_fee.to_le_bytes());
+        data
+    }
+
+    /// Verify the signature against the sender public key carried in `from`
+    pub fn verify_signature(&self) -> bool {
+        KeyManager::verify_with_public_key(&self.from, &self.signing_payload(), &self.signature)
+    }
+}
+
+/// Pending transactions in a max-heap on `vortex_fee`; equal fees leave in arrival order.
+/// Each transaction may be mined until its `valid_until` timestamp, after which it is evicted.
+#[derive(Debug, Clone, Default)]
+pub struct Mempool {
+    heap: BinaryHeap<MempoolEntry>,
+    next_seq: u64,
+}
+
+#[derive(Debug, Clone)]
+struct MempoolEntry {
+    seq: u64,
+    tx: Transaction,
+    valid_until: u64,
+}
+
+impl Ord for MempoolEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.tx.vortex_fee.total_cmp(&other.tx.vortex_fee).then_with(|| other.seq.cmp(&self.seq))
+    }
+}
+
+impl PartialOrd for MempoolEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for MempoolEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for MempoolEntry {}
+
+impl Mempool {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queue a transaction that never expires; returns false when one with the same hash is already pending
+    pub fn add(&mut self, tx: Transaction) -> bool {
+        self.add_until(tx, u64::MAX)
+    }
+
+    /// Queue a transaction that may be mined until `valid_until`
+    pub fn add_until(&mut self, tx: Transaction, valid_until: u64) -> bool {
+        if self.contains(&tx.hash) {
+            return false;
+        }
+        self.heap.push(MempoolEntry { seq: self.next_seq, tx, valid_until });
+        self.next_seq += 1;
+        true
+    }
+
+    /// Drop transactions whose `valid_until` is before `now`, returning their hashes and expiry
+    pub fn evict_expired(&mut self, now: u64) -> Vec<([u8; 32], u64)> {
+        let expired: Vec<_> = self.heap.iter()
+            .filter(|entry| entry.valid_until < now)
+            .map(|entry| (entry.tx.hash, entry.valid_until))
+            .collect();
+        if !expired.is_empty() {
+            self.heap.retain(|entry| entry.valid_until >= now);
+        }
+        expired
+    }
+
+    /// Remove and return up to `n` transactions, highest fee first
+    pub fn take_top(&mut self, n: usize) -> Vec<Transaction> {
+        std::iter::from_fn(|| self.heap.pop()).take(n).map(|entry| entry.tx).collect()
+    }
+
+    /// Evict transactions that were included in a block
+    pub fn remove_confirmed(&mut self, hashes: &[[u8; 32]]) {
+        self.heap.retain(|entry| !hashes.contains(&entry.tx.hash));
+    }
+
+    pub fn contains(&self, hash: &[u8; 32]) -> bool {
+        self.heap.iter().any(|entry| &entry.tx.hash == hash)
+    }
+
+    /// Pending transactions in the order `take_top` would return them
+    pub fn transactions(&self) -> Vec<Transaction> {
+        self.heap.clone().into_sorted_vec().into_iter().rev().map(|entry| entry.tx).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.heap.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.heap.is_empty()
+    }
+}
+
 /// Vortex consensus message
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ConsensusMessage {
@@ -141,7 +262,7 @@ impl VortexConsensus {
             validators: HashMap::new(),
             block_dag: BlockDAG::new(),
             finalized_blocks: HashSet::new(),
-            pending_txs: Vec::new(),
+            mempool: Mempool::new(),
             energy_distribution: HashMap::new(),
         };
 
@@ -234,8 +355,12 @@ impl VortexConsensus {
         // Get parent blocks (tips)
         let parent_hashes: Vec<[u8; 32]> = state.block_dag.tips.iter().cloned().collect();
         
-        // Get pending transactions
-        let transactions = state.pending_txs.drain(..).collect::<Vec<_>>();
+        // Highest-fee pending transactions that have not expired
+        let expired = state.mempool.evict_expired(self.get_current_timestamp());
+        if !expired.is_empty() {
+            log::info!("Evicted {} expired transactions before proposing", expired.len());
+        }
+        let transactions = state.mempool.take_top(MAX_BLOCK_TRANSACTIONS);
         
         // Calculate vortex energy
         let vortex_energy = state.energy_distribution
@@ -329,6 +454,21 @@ impl VortexConsensus {
             return Ok(false);
         }
 
+        // Check the block's own computed vortex energy
+        if !self.meets_energy_threshold(block) {
+            return Ok(false);
+        }
+
+        // Check proof-of-work against the difficulty target
+        if !Self::meets_work_target(block) {
+            return Ok(false);
+        }
+
+        // Check timestamp sanity against local time and known parents
+        if Self::check_timestamp(&state.block_dag, block, self.get_current_timestamp()).is_err() {
+            return Ok(false);
+        }
+
         // Check parent blocks exist
         for parent_hash in &block.parent_hashes {
             if !state.block_dag.blocks.contains_key(parent_hash) {
@@ -339,6 +479,46 @@ impl VortexConsensus {
         Ok(true)
     }
 
+    /// Reject timestamps too far in the future or older than a known parent
+    pub async fn check_block_timestamp(&self, block: &VortexBlock, now: u64) -> Result<(), ConsensusError> {
+        let state = self.state.read().await;
+        Self::check_timestamp(&state.block_dag, block, now)
+    }
+
+    fn check_timestamp(dag: &BlockDAG, block: &VortexBlock, now: u64) -> Result<(), ConsensusError> {
+        if block.timestamp > now.saturating_add(MAX_FUTURE_DRIFT_SECS) {
+            return Err(ConsensusError::InvalidTimestamp);
+        }
+        let newest_parent = block.parent_hashes.iter()
+            .filter_map(|hash| dag.blocks.get(hash))
+            .map(|parent| parent.timestamp)
+            .max();
+        if newest_parent.map_or(false, |parent_ts| block.timestamp < parent_ts) {
+            return Err(ConsensusError::InvalidTimestamp);
+        }
+        Ok(())
+    }
+
+    /// Whether the block hash is below the target for its difficulty
+    pub fn meets_work_target(block: &VortexBlock) -> bool {
+        crate::crypto::fractal_hash::meets_target(&block.hash, block.difficulty)
+    }
+
+    /// Vortex energy of a block, computed from its hash
+    pub fn block_vortex_energy(block: &VortexBlock) -> f64 {
+        crate::utils::vortex_energy(&block.hash)
+    }
+
+    /// Whether a block's computed vortex energy reaches the configured threshold
+    pub fn meets_energy_threshold(&self, block: &VortexBlock) -> bool {
+        Self::block_vortex_energy(block) >= self.energy_threshold
+    }
+
+    /// Configured vortex energy threshold
+    pub fn energy_threshold(&self) -> f64 {
+        self.energy_threshold
+    }
+
     /// Vote on block
     pub async fn vote_on_block(&self, block_hash: [u8; 32], voter_id: PeerId) -> Result<Vote, ConsensusError> {
         let state = self.state.read().await;
@@ -433,13 +613,43 @@ impl VortexConsensus {
         Ok(stats)
     }
 
-    /// Add transaction to pending pool
+    /// Add transaction to pending pool for MEMPOOL_TX_TTL seconds; a transaction already pending is ignored
     pub async fn add_transaction(&mut self, transaction: Transaction) -> Result<(), ConsensusError> {
+        let valid_until = self.get_current_timestamp().saturating_add(*crate::rpc_storage::MEMPOOL_TX_TTL);
+        self.add_transaction_until(transaction, valid_until).await
+    }
+
+    /// Add transaction to pending pool, to be mined no later than `valid_until`
+    pub async fn add_transaction_until(&mut self, transaction: Transaction, valid_until: u64) -> Result<(), ConsensusError> {
         let mut state = self.state.write().await;
-        state.pending_txs.push(transaction);
+        state.mempool.add_until(transaction, valid_until);
         Ok(())
     }
 
+    /// Drop pending transactions that expired before `now`, returning their hashes and expiry
+    pub async fn evict_expired_transactions(&self, now: u64) -> Vec<([u8; 32], u64)> {
+        let mut state = self.state.write().await;
+        state.mempool.evict_expired(now)
+    }
+
+    /// Check whether a transaction is already waiting in the pending pool
+    pub async fn has_pending_transaction(&self, hash: &[u8; 32]) -> bool {
+        let state = self.state.read().await;
+        state.mempool.contains(hash)
+    }
+
+    /// Snapshot of the pending pool, highest fee first
+    pub async fn get_pending_transactions(&self) -> Vec<Transaction> {
+        let state = self.state.read().await;
+        state.mempool.transactions()
+    }
+
+    /// Drop transactions included in a block from the pending pool
+    pub async fn remove_pending_transactions(&self, hashes: &[[u8; 32]]) {
+        let mut state = self.state.write().await;
+        state.mempool.remove_confirmed(hashes);
+    }
+
     /// Process vote from validator
     pub async fn process_vote(&mut self, _vote: Vote) -> Result<(), ConsensusError> {
         // Placeholder for vote processing logic
@@ -453,7 +663,15 @@ impl VortexConsensus {
 
     /// Add block to consensus
     pub async fn add_block(&mut self, block: VortexBlock) -> Result<(), ConsensusError> {
+        if !self.meets_energy_threshold(&block) {
+            return Err(ConsensusError::InsufficientEnergy);
+        }
+        if !Self::meets_work_target(&block) {
+            return Err(ConsensusError::InsufficientWork);
+        }
+
         let mut state = self.state.write().await;
+        Self::check_timestamp(&state.block_dag, &block, self.get_current_timestamp())?;
         state.block_dag.add_block(block);
         Ok(())
     }
@@ -523,8 +741,142 @@ pub enum ConsensusError {
     InvalidValidator,
     #[error("Insufficient energy")]
     InsufficientEnergy,
+    #[error("Insufficient proof-of-work")]
+    InsufficientWork,
     #[error("Invalid signature")]
     InvalidSignature,
+    #[error("Invalid timestamp")]
+    InvalidTimestamp,
     #[error("Network error")]
     NetworkError,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block_with_hash(hash: [u8; 32]) -> VortexBlock {
+        VortexBlock {
+            hash,
+            nonce: 0,
+            difficulty: 0,
+            parent_hashes: Vec::new(),
+            transactions: Vec::new(),
+            timestamp: 0,
+            validator_id: PeerId::random(),
+            vortex_energy: 1.0,
+            fractal_level: 0,
+            sierpinski_proof: Vec::new(),
+        }
+    }
+
+    fn tx_with_fee(id: u8, vortex_fee: f64) -> Transaction {
+        Transaction {
+            hash: [id; 32],
+            from: Vec::new(),
+            to: Vec::new(),
+            amount: 1,
+            nonce: id as u64,
+            signature: Vec::new(),
+            vortex_fee,
+        }
+    }
+
+    #[test]
+    fn test_mempool_takes_highest_fees_first() {
+        let mut mempool = Mempool::new();
+        for (id, fee) in [(1, 0.001), (2, 0.5), (3, 0.01), (4, 0.5), (5, 0.2)] {
+            assert!(mempool.add(tx_with_fee(id, fee)));
+        }
+        assert!(!mempool.add(tx_with_fee(3, 9.0)));
+
+        let order = |txs: Vec<Transaction>| txs.iter().map(|tx| tx.hash[0]).collect::<Vec<_>>();
+        assert_eq!(order(mempool.transactions()), vec![2, 4, 5, 3, 1]);
+        // Equal fees keep arrival order
+        assert_eq!(order(mempool.take_top(3)), vec![2, 4, 5]);
+        assert_eq!(order(mempool.take_top(10)), vec![3, 1]);
+        assert!(mempool.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_confirmed_transactions_are_evicted() {
+        let mut consensus = VortexConsensus::new(0.0);
+        for (id, fee) in [(1, 0.3), (2, 0.1), (3, 0.2)] {
+            consensus.add_transaction(tx_with_fee(id, fee)).await.unwrap();
+        }
+
+        consensus.remove_pending_transactions(&[[1; 32]]).await;
+        assert!(!consensus.has_pending_transaction(&[1; 32]).await);
+
+        let block = consensus.propose_block(PeerId::random()).await.unwrap();
+        let included: Vec<u8> = block.transactions.iter().map(|tx| tx.hash[0]).collect();
+        assert_eq!(included, vec![3, 2]);
+        assert!(consensus.get_pending_transactions().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_expired_transaction_is_evicted_and_never_mined() {
+        let mut consensus = VortexConsensus::new(0.0);
+        let now = consensus.get_current_timestamp();
+        consensus.add_transaction_until(tx_with_fee(1, 9.0), now - 1).await.unwrap();
+        consensus.add_transaction(tx_with_fee(2, 0.1)).await.unwrap();
+
+        let block = consensus.propose_block(PeerId::random()).await.unwrap();
+        let included: Vec<u8> = block.transactions.iter().map(|tx| tx.hash[0]).collect();
+        assert_eq!(included, vec![2]);
+        assert!(!consensus.has_pending_transaction(&[1; 32]).await);
+
+        // Resubmitting the same expired transaction does not get it mined either
+        consensus.add_transaction_until(tx_with_fee(1, 9.0), now - 1).await.unwrap();
+        assert_eq!(consensus.evict_expired_transactions(now).await, vec![([1; 32], now - 1)]);
+        let block = consensus.propose_block(PeerId::random()).await.unwrap();
+        assert!(block.transactions.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_block_below_energy_threshold_is_rejected() {
+        let mut consensus = VortexConsensus::new(100.0);
+        let block = block_with_hash([0u8; 32]);
+
+        assert!(!consensus.meets_energy_threshold(&block));
+        assert!(matches!(consensus.add_block(block).await, Err(ConsensusError::InsufficientEnergy)));
+        assert_eq!(consensus.get_consensus_stats().await.unwrap().total_blocks, 0);
+    }
+
+    #[tokio::test]
+    async fn test_block_above_energy_threshold_is_accepted() {
+        let mut consensus = VortexConsensus::new(100.0);
+        let block = block_with_hash([0xffu8; 32]);
+
+        assert!(VortexConsensus::block_vortex_energy(&block) >= 100.0);
+        assert!(consensus.add_block(block).await.is_ok());
+        assert_eq!(consensus.get_consensus_stats().await.unwrap().total_blocks, 1);
+    }
+
+    #[tokio::test]
+    async fn test_block_meeting_work_target_is_accepted() {
+        for difficulty in [1u32, 2] {
+            let mut consensus = VortexConsensus::new(0.0);
+            let mut hash = [0xffu8; 32];
+            hash[..difficulty as usize].fill(0);
+            let mut block = block_with_hash(hash);
+            block.difficulty = difficulty;
+
+            assert!(consensus.add_block(block).await.is_ok(), "difficulty {}", difficulty);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_block_missing_work_target_is_rejected() {
+        for difficulty in [1u32, 2] {
+            let mut consensus = VortexConsensus::new(0.0);
+            let mut hash = [0xffu8; 32];
+            hash[..difficulty as usize - 1].fill(0);
+            let mut block = block_with_hash(hash);
+            block.difficulty = difficulty;
+
+            assert!(matches!(consensus.add_block(block).await, Err(ConsensusError::InsufficientWork)), "difficulty {}", difficulty);
+            assert_eq!(consensus.get_consensus_stats().await.unwrap().total_blocks, 0);
+        }
+    }
+}
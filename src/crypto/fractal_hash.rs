@@ -169,7 +169,7 @@ impl FractalHasher {
 
     /// Generate Merkle tree using fractal hashing
     pub fn fractal_merkle_tree(&mut self, leaves: &[Vec<u8>]) -> FractalMerkleTree {
-        let mut tree = FractalMerkleTree::new();
+        let mut tree = FractalMerkleTree::new(self.fractal_level);
         
         if leaves.is_empty() {
             return tree;
@@ -211,20 +211,40 @@ impl FractalHasher {
 pub struct FractalMerkleTree {
     levels: Vec<Vec<[u8; 32]>>,
     root: Option<[u8; 32]>,
+    /// Hasher level the nodes were built with
+    #[serde(default)]
+    fractal_level: u32,
+}
+
+/// One level of an inclusion proof
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProofStep {
+    /// None when the node had no sibling and was hashed alone
+    pub sibling: Option<[u8; 32]>,
+    /// Whether the sibling sits left of the node on the path
+    pub is_left: bool,
+}
+
+/// Inclusion proof for one leaf, from the leaf level up to the root
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MerkleProof {
+    pub leaf_index: usize,
+    pub fractal_level: u32,
+    pub steps: Vec<ProofStep>,
 }
 
 impl FractalMerkleTree {
-    pub fn new() -> Self {
+    pub fn new(fractal_level: u32) -> Self {
         Self {
             levels: Vec::new(),
             root: None,
+            fractal_level,
         }
     }
 
+    /// Levels are added leaves first, so the last one added holds the root
     pub fn add_level(&mut self, level: Vec<[u8; 32]>) {
-        if self.levels.is_empty() {
-            self.root = level.first().copied();
-        }
+        self.root = level.first().copied();
         self.levels.push(level);
     }
 
@@ -256,6 +276,47 @@ impl FractalMerkleTree {
 
         Some(proof)
     }
+
+    /// Inclusion proof for the leaf at `index`, or None when it is out of range
+    pub fn prove(&self, index: usize) -> Option<MerkleProof> {
+        if index >= self.levels.first()?.len() {
+            return None;
+        }
+
+        let mut steps = Vec::with_capacity(self.levels.len() - 1);
+        let mut current_index = index;
+        for level in &self.levels[..self.levels.len() - 1] {
+            steps.push(ProofStep {
+                sibling: level.get(current_index ^ 1).copied(),
+                is_left: current_index % 2 == 1,
+            });
+            current_index /= 2;
+        }
+
+        Some(MerkleProof { leaf_index: index, fractal_level: self.fractal_level, steps })
+    }
+
+    /// Whether `proof` links the leaf hash `leaf` to `root`
+    pub fn verify(root: &[u8; 32], proof: &MerkleProof, leaf: &[u8; 32]) -> bool {
+        let mut hasher = FractalHasher::new(proof.fractal_level);
+        let mut current = *leaf;
+        for step in &proof.steps {
+            let mut combined = Vec::with_capacity(64);
+            match step.sibling {
+                Some(sibling) if step.is_left => {
+                    combined.extend_from_slice(&sibling);
+                    combined.extend_from_slice(&current);
+                }
+                Some(sibling) => {
+                    combined.extend_from_slice(&current);
+                    combined.extend_from_slice(&sibling);
+                }
+                None => combined.extend_from_slice(&current),
+            }
+            current = hasher.fractal_hash(&combined).fractal_hash;
+        }
+        &current == root
+    }
 }
 
 /// Block hash with fractal properties
@@ -285,6 +346,27 @@ impl BlockHash {
     }
 }
 
+/// Proof-of-work target for a difficulty: hashes must be strictly below
+/// 2^(256 - 8 * difficulty), compared big-endian. Difficulty 0 has no target.
+pub fn difficulty_target(difficulty: u32) -> Option<[u8; 32]> {
+    if difficulty == 0 {
+        return None;
+    }
+    let mut target = [0u8; 32];
+    if difficulty <= 32 {
+        target[difficulty as usize - 1] = 1;
+    }
+    Some(target)
+}
+
+/// Whether `hash` is below the target for `difficulty`
+pub fn meets_target(hash: &[u8; 32], difficulty: u32) -> bool {
+    match difficulty_target(difficulty) {
+        Some(target) => hash < &target,
+        None => true,
+    }
+}
+
 /// Hash-based proof of work using fractal complexity
 pub struct FractalPoW {
     difficulty: u32,
@@ -301,7 +383,12 @@ impl FractalPoW {
 
     /// Mine fractal hash with specific difficulty
     pub fn mine(&self, data: &[u8]) -> (Vec<u8>, BlockHash) {
-        let mut nonce = 0u64;
+        self.mine_from(data, 0)
+    }
+
+    /// Search nonces upward from `start_nonce`, wrapping at u64::MAX
+    pub fn mine_from(&self, data: &[u8], start_nonce: u64) -> (Vec<u8>, BlockHash) {
+        let mut nonce = start_nonce;
         
         loop {
             let mut input = data.to_vec();
@@ -313,17 +400,13 @@ impl FractalPoW {
                 return (nonce.to_le_bytes().to_vec(), block_hash);
             }
             
-            nonce += 1;
+            nonce = nonce.wrapping_add(1);
         }
     }
 
     /// Check if hash meets difficulty requirement
-    fn meets_difficulty(&self, hash: &[u8; 32]) -> bool {
-        let leading_zeros = hash.iter()
-            .take_while(|&&byte| byte == 0)
-            .count();
-        
-        leading_zeros >= self.difficulty as usize
+    pub fn meets_difficulty(&self, hash: &[u8; 32]) -> bool {
+        meets_target(hash, self.difficulty)
     }
 
     /// Verify proof of work
@@ -335,4 +418,33 @@ impl FractalPoW {
         
         calculated.hash == hash.hash && self.meets_difficulty(&hash.hash)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merkle_proof_for_middle_leaf() {
+        let leaves: Vec<Vec<u8>> = (0..7u8).map(|i| format!("tx-{}", i).into_bytes()).collect();
+        let mut hasher = FractalHasher::new(3);
+        let tree = hasher.fractal_merkle_tree(&leaves);
+        let root = tree.get_root().unwrap();
+        assert_eq!(root, tree.levels.last().unwrap()[0]);
+
+        let leaf = hasher.fractal_hash(&leaves[3]).fractal_hash;
+        let proof = tree.prove(3).unwrap();
+        assert_eq!(proof.steps.len(), 3);
+        assert!(proof.steps[0].is_left);
+        assert!(FractalMerkleTree::verify(&root, &proof, &leaf));
+
+        let tampered = hasher.fractal_hash(b"tx-3 tampered").fractal_hash;
+        assert!(!FractalMerkleTree::verify(&root, &proof, &tampered));
+        assert!(!FractalMerkleTree::verify(&root, &tree.prove(2).unwrap(), &leaf));
+
+        // The unpaired last leaf is hashed alone on its way up
+        let last = hasher.fractal_hash(&leaves[6]).fractal_hash;
+        assert!(FractalMerkleTree::verify(&root, &tree.prove(6).unwrap(), &last));
+        assert!(tree.prove(7).is_none());
+    }
+}
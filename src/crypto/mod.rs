@@ -1,4 +1,4 @@
 //! Cryptographic primitives for Fractal-Vortex Chain
 
 pub mod fractal_hash;
-pub use fractal_hash::{FractalHasher, VortexHash, BlockHash, FractalMerkleTree};
\ No newline at end of file
+pub use fractal_hash::{FractalHasher, VortexHash, BlockHash, FractalMerkleTree, MerkleProof, ProofStep};
\ No newline at end of file
@@ -0,0 +1,302 @@
+use std::sync::Arc;
+use std::time::Duration;
+use futures::{Sink, SinkExt, StreamExt};
+use serde_json::{json, Value};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::RwLock;
+use tokio_tungstenite::tungstenite::Message;
+use crate::block_stream::subscribe_blocks;
+use crate::node::fractal_node::NodeState;
+use crate::node::load_balancer::{judge_health, HealthSample};
+use crate::rpc_storage::{Block, RPCStorage};
+use crate::smart_rate::vortex_energy_rate;
+use crate::storage::StorageError;
+
+/// Latest blocks pushed to the dashboard, newest first
+pub const DASHBOARD_BLOCKS: usize = 4;
+
+/// Longest a client goes without fresh state when no block arrives
+pub const DASHBOARD_TICK: Duration = Duration::from_secs(5);
+
+/// Transactions per second over the time spanned by `blocks`; 0.0 for fewer than two distinct timestamps
+fn transactions_per_second(blocks: &[Block]) -> f64 {
+    let (Some(first), Some(last)) = (
+        blocks.iter().map(|b| b.timestamp).min(),
+        blocks.iter().map(|b| b.timestamp).max(),
+    ) else {
+        return 0.0;
+    };
+    if last <= first {
+        return 0.0;
+    }
+    // The oldest block's transactions happened before the measured span
+    let oldest = blocks.iter().min_by_key(|b| b.timestamp).map_or(0, |b| b.transaction_count);
+    let transactions: u64 = blocks.iter().map(|b| b.transaction_count).sum::<u64>() - oldest;
+    transactions as f64 / (last - first) as f64
+}
+
+/// The `metrics`, `nodes` and `blocks` messages for the current chain state and `node`'s view of the network
+pub async fn dashboard_messages(node_id: usize, node: &NodeState) -> Result<Vec<Value>, StorageError> {
+    let height = RPCStorage::get_block_height().await?;
+    let total_transactions = RPCStorage::get_transaction_count().await?;
+    let blocks = RPCStorage::get_latest_blocks(DASHBOARD_BLOCKS).await?;
+    let now = chrono::Utc::now().timestamp_millis();
+    // Judged as the cluster judges its nodes: healthy while it has peers to stay in sync with
+    let sample = HealthSample { block_height: height, peer_count: node.connected_peers.len() };
+    let healthy = judge_health(&[Some(sample)])[0];
+
+    Ok(vec![
+        json!({
+            "type": "metrics",
+            "totalTransactions": total_transactions,
+            // This node and the peers it is connected to
+            "activeNodes": 1 + node.connected_peers.len(),
+            "blockHeight": height,
+            "tps": transactions_per_second(&blocks),
+            "vortexEnergy": vortex_energy_rate(height, total_transactions),
+            "isValidator": node.is_validator,
+            "networkStatus": if healthy { "healthy" } else { "isolated" },
+            "lastUpdate": now
+        }),
+        json!({
+            "type": "nodes",
+            "nodes": [{
+                "id": format!("fractal-node-{}", node_id),
+                "name": format!("FractalNode-{}", node_id),
+                "status": if healthy { "active" } else { "isolated" },
+                "lastSeen": "now",
+                "blockHeight": height,
+                "isValidator": node.is_validator
+            }]
+        }),
+        json!({
+            "type": "blocks",
+            "blocks": blocks.iter().map(|block| json!({
+                "height": block.height,
+                "hash": block.hash,
+                "transactions": block.transaction_count,
+                "miner": block.miner,
+                "timestamp": block.timestamp,
+                "size": block.size
+            })).collect::<Vec<_>>()
+        }),
+    ])
+}
+
+async fn send_json<S>(sender: &mut S, message: &Value) -> Result<(), S::Error>
+where
+    S: Sink<Message> + Unpin,
+{
+    sender.send(Message::Text(message.to_string())).await
+}
+
+/// Push the chain state on connect, after every stored block, and every DASHBOARD_TICK in between
+async fn handle_connection(stream: TcpStream, node_id: usize, node: Arc<RwLock<NodeState>>) {
+    let ws_stream = match tokio_tungstenite::accept_async(stream).await {
+        Ok(ws) => ws,
+        Err(e) => {
+            log::warn!("WebSocket handshake failed: {}", e);
+            return;
+        }
+    };
+    let (mut ws_sender, mut ws_receiver) = ws_stream.split();
+
+    // Subscribe before the first snapshot so a block stored in between still triggers an update
+    let blocks = match subscribe_blocks(None).await {
+        Ok(blocks) => blocks,
+        Err(e) => {
+            log::error!("Failed to subscribe dashboard to blocks: {}", e);
+            return;
+        }
+    };
+    futures::pin_mut!(blocks);
+
+    let welcome = json!({
+        "type": "connected",
+        "node": node_id,
+        "message": "Fractal-Vortex blockchain node connected"
+    });
+    if send_json(&mut ws_sender, &welcome).await.is_err() {
+        return;
+    }
+
+    let mut tick = tokio::time::interval_at(tokio::time::Instant::now() + DASHBOARD_TICK, DASHBOARD_TICK);
+    let mut refresh = true;
+    loop {
+        if refresh {
+            refresh = false;
+            tick.reset();
+            let state = node.read().await.clone();
+            match dashboard_messages(node_id, &state).await {
+                Ok(messages) => {
+                    for message in &messages {
+                        if send_json(&mut ws_sender, message).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+                Err(e) => log::error!("Failed to read chain state for dashboard: {}", e),
+            }
+        }
+
+        tokio::select! {
+            block = blocks.next() => match block {
+                Some(Ok(_)) => refresh = true,
+                Some(Err(e)) => log::warn!("Dashboard block subscription error: {}", e),
+                None => return,
+            },
+            _ = tick.tick() => refresh = true,
+            msg = ws_receiver.next() => match msg {
+                Some(Ok(Message::Close(_))) | None => return,
+                Some(Err(e)) => {
+                    log::warn!("WebSocket error: {}", e);
+                    return;
+                }
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+/// Accept dashboard connections on `listener` until it fails, reporting the state of the node behind `node`
+pub async fn serve_dashboard(listener: TcpListener, node_id: usize, node: Arc<RwLock<NodeState>>) {
+    while let Ok((stream, _)) = listener.accept().await {
+        tokio::spawn(handle_connection(stream, node_id, node.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_storage::use_test_db;
+    use std::time::Duration;
+
+    fn mined_block(height: u64, parent_hash: String) -> Block {
+        let mut block = Block::new_with_timestamp(height, "fvcminer".to_string(), parent_hash, 1_700_000_000 + height);
+        for nonce in 0u64.. {
+            block.nonce = nonce;
+            block.hash = block.canonical_hash();
+            if block.has_valid_pow() {
+                break;
+            }
+        }
+        block
+    }
+
+    async fn next_json<S>(ws: &mut S) -> Value
+    where
+        S: futures::Stream<Item = Result<Message, tokio_tungstenite::tungstenite::Error>> + Unpin,
+    {
+        loop {
+            let msg = tokio::time::timeout(Duration::from_secs(10), ws.next()).await.unwrap().unwrap().unwrap();
+            if let Message::Text(text) = msg {
+                return serde_json::from_str(&text).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_tps_excludes_oldest_block() {
+        let mut blocks: Vec<Block> = (1..4).map(|h| Block::new_with_timestamp(h, "fvcminer".to_string(), "0".repeat(64), 0)).collect();
+        for (i, block) in blocks.iter_mut().enumerate() {
+            block.timestamp = 1_000 + i as u64 * 5;
+            block.transaction_count = 10;
+        }
+        assert_eq!(transactions_per_second(&blocks), 2.0);
+        assert_eq!(transactions_per_second(&blocks[..1]), 0.0);
+    }
+
+    fn node_state(is_validator: bool, peers: usize) -> NodeState {
+        NodeState {
+            is_validator,
+            current_epoch: 0,
+            vortex_energy: 1.0,
+            connected_peers: (0..peers).map(|_| libp2p::PeerId::random()).collect(),
+            last_sync: 0,
+            block_height: 0,
+            head_hash: None,
+            total_transactions: 0,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics_reflect_node_state() {
+        let _db = use_test_db();
+
+        let messages = dashboard_messages(0, &node_state(false, 2)).await.unwrap();
+        assert_eq!(messages[0]["activeNodes"], 3);
+        assert_eq!(messages[0]["isValidator"], false);
+        assert_eq!(messages[0]["networkStatus"], "healthy");
+        assert_eq!(messages[1]["nodes"][0]["isValidator"], false);
+
+        // Without peers the node cannot follow the network
+        let messages = dashboard_messages(0, &node_state(true, 0)).await.unwrap();
+        assert_eq!(messages[0]["activeNodes"], 1);
+        assert_eq!(messages[0]["isValidator"], true);
+        assert_eq!(messages[0]["networkStatus"], "isolated");
+        assert_eq!(messages[1]["nodes"][0]["status"], "isolated");
+    }
+
+    #[tokio::test]
+    async fn test_metrics_refresh_between_blocks() {
+        let _db = use_test_db();
+        let node = Arc::new(RwLock::new(node_state(false, 0)));
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve_dashboard(listener, 0, node.clone()));
+
+        let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        let first = loop {
+            let message = next_json(&mut ws).await;
+            if message["type"] == "metrics" {
+                break message;
+            }
+        };
+        assert_eq!(first["activeNodes"], 1);
+
+        // No block is stored; the next tick still pushes the node's current state
+        node.write().await.connected_peers.push(libp2p::PeerId::random());
+        let next = loop {
+            let message = next_json(&mut ws).await;
+            if message["type"] == "metrics" {
+                break message;
+            }
+        };
+        assert_eq!(next["activeNodes"], 2);
+        assert!(next["lastUpdate"].as_i64().unwrap() > first["lastUpdate"].as_i64().unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_pushes_stored_block_height() {
+        let _db = use_test_db();
+
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        tokio::spawn(serve_dashboard(listener, 0, Arc::new(RwLock::new(node_state(true, 1)))));
+
+        let (mut ws, _) = tokio_tungstenite::connect_async(url).await.unwrap();
+        assert_eq!(next_json(&mut ws).await["type"], "connected");
+
+        let block = mined_block(710_000, "0".repeat(64));
+        RPCStorage::store_block(&block).await.unwrap();
+
+        let metrics = loop {
+            let message = next_json(&mut ws).await;
+            if message["type"] == "metrics" && message["blockHeight"] == block.height {
+                break message;
+            }
+        };
+        assert_eq!(metrics["blockHeight"], RPCStorage::get_block_height().await.unwrap());
+        // Existing clients read vortexEnergy from every metrics message
+        assert!(metrics["vortexEnergy"].is_f64());
+
+        let blocks = loop {
+            let message = next_json(&mut ws).await;
+            if message["type"] == "blocks" {
+                break message;
+            }
+        };
+        assert_eq!(blocks["blocks"][0]["hash"], block.hash);
+    }
+}
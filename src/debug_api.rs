@@ -0,0 +1,128 @@
+use axum::{http::{HeaderMap, StatusCode}, response::{IntoResponse, Json, Response}};
+use once_cell::sync::Lazy;
+use serde_json::{json, Value};
+use crate::api_auth::{authorize, Scope};
+use crate::rpc_storage::{RPCStorage, WalletTransaction};
+
+/// Default number of transactions a debug endpoint returns
+pub const DEFAULT_DEBUG_TX_LIMIT: usize = 20;
+
+/// Cap on transactions returned by debug endpoints (env: DEBUG_TX_LIMIT)
+pub static DEBUG_TX_LIMIT: Lazy<usize> = Lazy::new(|| {
+    std::env::var("DEBUG_TX_LIMIT")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .unwrap_or(DEFAULT_DEBUG_TX_LIMIT)
+});
+
+/// First 6 and last 4 characters of an address
+pub fn mask_address(address: &str) -> String {
+    let chars: Vec<char> = address.chars().collect();
+    if chars.len() <= 10 {
+        return "*".repeat(chars.len());
+    }
+    let head: String = chars[..6].iter().collect();
+    let tail: String = chars[chars.len() - 4..].iter().collect();
+    format!("{}...{}", head, tail)
+}
+
+/// Transaction summary without signature or nonce and with masked parties
+pub fn redact_transaction(tx: &WalletTransaction) -> Value {
+    json!({
+        "hash": tx.hash,
+        "from": mask_address(&tx.from),
+        "to": mask_address(&tx.to),
+        "amount": tx.amount,
+        "timestamp": tx.timestamp,
+        "transaction_type": tx.transaction_type,
+        "block_height": tx.block_height
+    })
+}
+
+/// Latest transactions, at most `limit`, redacted; admin only
+pub async fn debug_all_transactions(headers: &HeaderMap, limit: usize) -> Response {
+    if let Err(response) = authorize(headers, Scope::Admin).await {
+        return response;
+    }
+    let transactions = RPCStorage::get_latest_transactions(limit).await.unwrap_or_default();
+    Json(json!({
+        "success": true,
+        "limit": limit,
+        "total_transactions": transactions.len(),
+        "transactions": transactions.iter().map(redact_transaction).collect::<Vec<_>>()
+    })).into_response()
+}
+
+/// Stored transaction count plus the latest hashes, at most `limit`; admin only
+pub async fn debug_transaction_registry(headers: &HeaderMap, limit: usize) -> Response {
+    if let Err(response) = authorize(headers, Scope::Admin).await {
+        return response;
+    }
+    match RPCStorage::get_transaction_count().await {
+        Ok(count) => {
+            let transactions = RPCStorage::get_latest_transactions(limit).await.unwrap_or_default();
+            Json(json!({
+                "success": true,
+                "storage_count": count,
+                "limit": limit,
+                "transactions_found": transactions.len(),
+                "transaction_hashes": transactions.iter().map(|tx| tx.hash.clone()).collect::<Vec<_>>(),
+                "last_10_transactions": transactions.iter().take(10).map(redact_transaction).collect::<Vec<_>>()
+            })).into_response()
+        },
+        Err(e) => (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({
+            "success": false,
+            "error": format!("Failed to get transaction count: {}", e)
+        }))).into_response(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_storage::use_test_db;
+    use crate::api_auth::register_api_key;
+
+    async fn body_json(response: Response) -> Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_debug_endpoints_require_admin_and_cap_results() {
+        let _db = use_test_db();
+        for i in 0..3 {
+            let tx = WalletTransaction::new_transfer(
+                "fvc00000000000000000000000000000000c001emyl".to_string(),
+                "fvc00000000000000000000000000000000c002emyl".to_string(),
+                100 + i,
+                format!("0xdebug{}", i),
+                1,
+            );
+            RPCStorage::add_transaction(&tx).await.unwrap();
+        }
+
+        register_api_key("debug-readonly-key", Scope::Readonly).await.unwrap();
+        register_api_key("debug-admin-key", Scope::Admin).await.unwrap();
+
+        let anonymous = HeaderMap::new();
+        assert_eq!(debug_all_transactions(&anonymous, 2).await.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(debug_transaction_registry(&anonymous, 2).await.status(), StatusCode::UNAUTHORIZED);
+
+        let mut readonly = HeaderMap::new();
+        readonly.insert("x-api-key", "debug-readonly-key".parse().unwrap());
+        assert_eq!(debug_all_transactions(&readonly, 2).await.status(), StatusCode::FORBIDDEN);
+        assert_eq!(debug_transaction_registry(&readonly, 2).await.status(), StatusCode::FORBIDDEN);
+
+        let mut admin = HeaderMap::new();
+        admin.insert("x-api-key", "debug-admin-key".parse().unwrap());
+        let all = body_json(debug_all_transactions(&admin, 2).await).await;
+        let transactions = all["transactions"].as_array().unwrap();
+        assert_eq!(transactions.len(), 2);
+        assert!(transactions.iter().all(|tx| tx["from"].as_str().unwrap().contains("...") && tx.get("signature").is_none()));
+
+        let registry = body_json(debug_transaction_registry(&admin, 2).await).await;
+        assert_eq!(registry["transaction_hashes"].as_array().unwrap().len(), 2);
+        assert!(registry["storage_count"].as_u64().unwrap() >= 3);
+    }
+}
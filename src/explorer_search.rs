@@ -0,0 +1,143 @@
+use serde::Serialize;
+use serde_json::Value;
+use crate::rpc_storage::{Block, RPCStorage, WalletTransaction};
+use crate::shared::validate_tx_address;
+use crate::storage::StorageError;
+
+/// How a search box query is interpreted
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchQuery {
+    Height(u64),
+    /// 0x-prefixed block or transaction hash
+    Hash(String),
+    Address(String),
+    /// Matches no known format
+    Unknown,
+}
+
+impl SearchQuery {
+    /// Digits are a height, a 0x prefix a hash, and a valid FVC address an address
+    pub fn parse(query: &str) -> Self {
+        let query = query.trim();
+        if !query.is_empty() && query.bytes().all(|b| b.is_ascii_digit()) {
+            return query.parse().map(Self::Height).unwrap_or(Self::Unknown);
+        }
+        if query.len() > 2 && (query.starts_with("0x") || query.starts_with("0X")) {
+            return Self::Hash(format!("0x{}", &query[2..]));
+        }
+        if validate_tx_address("address", query).is_ok() {
+            return Self::Address(query.to_string());
+        }
+        Self::Unknown
+    }
+}
+
+/// Entity found by a search, tagged by `type`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SearchResult {
+    Block { block: Block },
+    Transaction { transaction: WalletTransaction },
+    Address { account: Value },
+    None,
+}
+
+/// Look up whatever `query` refers to. A hash is tried as a block first, then as a transaction;
+/// an address matches once it has a balance or a transaction.
+pub async fn search(query: &str) -> Result<SearchResult, StorageError> {
+    Ok(match SearchQuery::parse(query) {
+        SearchQuery::Height(height) => match RPCStorage::get_block_by_height(height).await? {
+            Some(block) => SearchResult::Block { block },
+            None => SearchResult::None,
+        },
+        SearchQuery::Hash(hash) => {
+            if let Some(block) = RPCStorage::get_block_by_hash(&hash).await? {
+                SearchResult::Block { block }
+            } else if let Some(transaction) = RPCStorage::get_transaction(&hash).await? {
+                SearchResult::Transaction { transaction }
+            } else {
+                SearchResult::None
+            }
+        }
+        SearchQuery::Address(address) => {
+            let used = RPCStorage::get_address_transaction_count(&address).await? > 0
+                || RPCStorage::get_balance(&address).await? > 0;
+            if used {
+                SearchResult::Address { account: RPCStorage::get_account_info(&address).await? }
+            } else {
+                SearchResult::None
+            }
+        }
+        SearchQuery::Unknown => SearchResult::None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_storage::use_test_db;
+
+    const ADDRESS: &str = "fvc000000000000000000000000000000005ea1emyl";
+
+    async fn store_searchable_block(height: u64) -> Block {
+        let mut block = Block::new_with_timestamp(height, ADDRESS.to_string(), "0".repeat(64), 1_700_000_000 + height);
+        block.add_transaction(WalletTransaction::new_mining_reward(
+            ADDRESS.to_string(),
+            1_000,
+            format!("0x5ea4c4{}", height),
+            height,
+        ));
+        for nonce in 0u64.. {
+            block.nonce = nonce;
+            block.hash = block.canonical_hash();
+            if block.has_valid_pow() {
+                break;
+            }
+        }
+        RPCStorage::store_block(&block).await.unwrap();
+        block
+    }
+
+    #[test]
+    fn test_query_kinds_detected() {
+        assert_eq!(SearchQuery::parse(" 96000 "), SearchQuery::Height(96_000));
+        assert_eq!(SearchQuery::parse("0XABcd"), SearchQuery::Hash("0xABcd".to_string()));
+        assert_eq!(SearchQuery::parse(ADDRESS), SearchQuery::Address(ADDRESS.to_string()));
+        assert_eq!(SearchQuery::parse("0x"), SearchQuery::Unknown);
+        assert_eq!(SearchQuery::parse("not-a-thing"), SearchQuery::Unknown);
+        assert_eq!(SearchQuery::parse("99999999999999999999999"), SearchQuery::Unknown);
+    }
+
+    #[tokio::test]
+    async fn test_search_finds_each_entity() {
+        let _db = use_test_db();
+        let block = store_searchable_block(96_000).await;
+
+        let by_height = search("96000").await.unwrap();
+        assert!(matches!(&by_height, SearchResult::Block { block: found } if found.hash == block.hash));
+        assert_eq!(serde_json::to_value(&by_height).unwrap()["type"], "block");
+
+        let by_hash = search(&block.hash).await.unwrap();
+        assert!(matches!(&by_hash, SearchResult::Block { block: found } if found.height == 96_000));
+
+        let tx = search("0x5ea4c496000").await.unwrap();
+        assert!(matches!(&tx, SearchResult::Transaction { transaction } if transaction.to == ADDRESS));
+        assert_eq!(serde_json::to_value(&tx).unwrap()["type"], "transaction");
+
+        let SearchResult::Address { account } = search(ADDRESS).await.unwrap() else {
+            panic!("address should match");
+        };
+        assert_eq!(account["address"], ADDRESS);
+    }
+
+    #[tokio::test]
+    async fn test_search_without_match_is_none() {
+        let _db = use_test_db();
+        let unused_address = "fvc000000000000000000000000000000005ea2emyl";
+        for query in ["96999", "0xdeadbeef5ea4", "", "hello world", unused_address] {
+            let result = search(query).await.unwrap();
+            assert_eq!(result, SearchResult::None, "{:?}", query);
+            assert_eq!(serde_json::to_value(&result).unwrap(), serde_json::json!({ "type": "none" }));
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::time::{Duration, Instant};
+use axum::http::StatusCode;
+use thiserror::Error;
+use tokio::sync::Mutex;
+use crate::rpc_storage::{RPCStorage, WalletTransaction};
+use crate::shared::{validate_tx_address, TxError};
+use crate::storage::StorageError;
+
+/// Default grant: 10 FVC in microFVC
+pub const DEFAULT_FAUCET_AMOUNT: u64 = 10_000_000;
+/// Default time an address or IP must wait between grants
+pub const DEFAULT_FAUCET_WINDOW: Duration = Duration::from_secs(86400);
+
+/// Errors returned by a faucet request
+#[derive(Debug, Error)]
+pub enum FaucetError {
+    #[error("Faucet is disabled on {0}")]
+    Disabled(String),
+    #[error(transparent)]
+    InvalidAddress(#[from] TxError),
+    #[error("Faucet already used; retry in {retry_after_secs} seconds")]
+    RateLimited { retry_after_secs: u64 },
+    #[error("Faucet account has insufficient funds")]
+    InsufficientFunds,
+    #[error("Storage error: {0}")]
+    Storage(#[from] StorageError),
+}
+
+impl FaucetError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            FaucetError::Disabled(_) => StatusCode::FORBIDDEN,
+            FaucetError::InvalidAddress(_) => StatusCode::BAD_REQUEST,
+            FaucetError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+            FaucetError::InsufficientFunds => StatusCode::SERVICE_UNAVAILABLE,
+            FaucetError::Storage(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn code(&self) -> &'static str {
+        match self {
+            FaucetError::Disabled(_) => "FAUCET_DISABLED",
+            FaucetError::InvalidAddress(_) => "INVALID_ADDRESS",
+            FaucetError::RateLimited { .. } => "RATE_LIMIT_EXCEEDED",
+            FaucetError::InsufficientFunds => "FAUCET_EMPTY",
+            FaucetError::Storage(_) => "STORAGE_ERROR",
+        }
+    }
+}
+
+/// Faucet settings; the faucet never runs on mainnet
+#[derive(Debug, Clone, PartialEq)]
+pub struct FaucetConfig {
+    pub network: String,
+    pub faucet_address: String,
+    pub amount: u64,
+    pub window: Duration,
+}
+
+impl Default for FaucetConfig {
+    fn default() -> Self {
+        Self {
+            network: "mainnet".to_string(),
+            faucet_address: String::new(),
+            amount: DEFAULT_FAUCET_AMOUNT,
+            window: DEFAULT_FAUCET_WINDOW,
+        }
+    }
+}
+
+impl FaucetConfig {
+    /// Load from FVC_NETWORK, FAUCET_ADDRESS, FAUCET_AMOUNT and FAUCET_WINDOW_SECS
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+        Self {
+            network: std::env::var("FVC_NETWORK").unwrap_or(defaults.network),
+            faucet_address: std::env::var("FAUCET_ADDRESS").unwrap_or(defaults.faucet_address),
+            amount: std::env::var("FAUCET_AMOUNT")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .unwrap_or(defaults.amount),
+            window: std::env::var("FAUCET_WINDOW_SECS")
+                .ok()
+                .and_then(|v| v.parse().ok())
+                .map(Duration::from_secs)
+                .unwrap_or(defaults.window),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.network.eq_ignore_ascii_case("mainnet")
+    }
+}
+
+/// Testnet faucet paying grants out of a funded account
+pub struct Faucet {
+    config: FaucetConfig,
+    // Held for the whole grant so concurrent requests cannot overdraw the faucet
+    last_grants: Mutex<HashMap<String, Instant>>,
+}
+
+impl Faucet {
+    pub fn new(config: FaucetConfig) -> Self {
+        Self { config, last_grants: Mutex::new(HashMap::new()) }
+    }
+
+    pub fn config(&self) -> &FaucetConfig {
+        &self.config
+    }
+
+    /// Move the configured amount from the faucet account to `recipient`, once per window per address and IP
+    pub async fn grant(&self, recipient: &str, ip: IpAddr) -> Result<WalletTransaction, FaucetError> {
+        if !self.config.is_enabled() {
+            return Err(FaucetError::Disabled(self.config.network.clone()));
+        }
+        validate_tx_address("recipient", recipient)?;
+
+        let mut last_grants = self.last_grants.lock().await;
+        let now = Instant::now();
+        let keys = [format!("addr:{}", recipient), format!("ip:{}", ip)];
+        last_grants.retain(|_, granted| now.duration_since(*granted) < self.config.window);
+        if let Some(granted) = keys.iter().filter_map(|k| last_grants.get(k)).max() {
+            let retry_after = self.config.window.saturating_sub(now.duration_since(*granted));
+            return Err(FaucetError::RateLimited { retry_after_secs: retry_after.as_secs().max(1) });
+        }
+
+        let height = RPCStorage::get_block_height().await?;
+        let mut tx = WalletTransaction::try_new_transfer(
+            self.config.faucet_address.clone(),
+            recipient.to_string(),
+            self.config.amount,
+            format!("0xfaucet_{}_{}", recipient, chrono::Utc::now().timestamp_millis()),
+            height,
+        )?;
+        tx.transaction_type = "faucet".to_string();
+        tx.nonce = RPCStorage::get_account_nonce(&self.config.faucet_address).await? + 1;
+
+        // Both balances and the transaction record land in one write
+        match RPCStorage::record_transfer(&tx, 0, None).await {
+            Ok(_) => {}
+            Err(StorageError::BalanceUnderflow { .. }) => return Err(FaucetError::InsufficientFunds),
+            Err(e) => return Err(e.into()),
+        }
+
+        for key in keys {
+            last_grants.insert(key, now);
+        }
+        Ok(tx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_storage::use_test_db;
+    use std::net::Ipv4Addr;
+
+    const FAUCET: &str = "fvc00000000000000000000000000000000fa0cemyl";
+
+    fn testnet_faucet() -> Faucet {
+        Faucet::new(FaucetConfig {
+            network: "testnet".to_string(),
+            faucet_address: FAUCET.to_string(),
+            amount: 2_000_000,
+            window: Duration::from_secs(3600),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_faucet_grant_and_repeat_refused() {
+        let _db = use_test_db();
+        RPCStorage::set_balance(FAUCET, 5_000_000).await.unwrap();
+        let faucet = testnet_faucet();
+        let user = "fvc00000000000000000000000000000000a001emyl";
+        let ip = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+
+        let tx = faucet.grant(user, ip).await.unwrap();
+        assert_eq!(tx.from, FAUCET);
+        assert_eq!(tx.transaction_type, "faucet");
+        assert_eq!(RPCStorage::get_balance(user).await.unwrap(), 2_000_000);
+        assert_eq!(RPCStorage::get_balance(FAUCET).await.unwrap(), 3_000_000);
+
+        // Same address from another IP, and another address from the same IP
+        let other_ip = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 2));
+        let other_user = "fvc00000000000000000000000000000000a002emyl";
+        assert!(matches!(faucet.grant(user, other_ip).await, Err(FaucetError::RateLimited { .. })));
+        assert!(matches!(faucet.grant(other_user, ip).await, Err(FaucetError::RateLimited { .. })));
+        assert_eq!(RPCStorage::get_balance(FAUCET).await.unwrap(), 3_000_000);
+        assert!(RPCStorage::get_transaction(&tx.hash).await.unwrap().is_some());
+        assert_eq!(RPCStorage::get_account_nonce(FAUCET).await.unwrap(), 1);
+
+        // A drained faucet moves nothing and records nothing
+        RPCStorage::set_balance(FAUCET, 1_000_000).await.unwrap();
+        let third_user = "fvc00000000000000000000000000000000a004emyl";
+        let third_ip = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 3));
+        assert!(matches!(faucet.grant(third_user, third_ip).await, Err(FaucetError::InsufficientFunds)));
+        assert_eq!(RPCStorage::get_balance(third_user).await.unwrap(), 0);
+        assert_eq!(RPCStorage::get_balance(FAUCET).await.unwrap(), 1_000_000);
+        assert_eq!(RPCStorage::get_account_nonce(FAUCET).await.unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_faucet_disabled_on_mainnet() {
+        let faucet = Faucet::new(FaucetConfig {
+            faucet_address: FAUCET.to_string(),
+            ..FaucetConfig::default()
+        });
+        let err = faucet
+            .grant("fvc00000000000000000000000000000000a003emyl", IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, FaucetError::Disabled(_)));
+        assert_eq!(err.status_code(), StatusCode::FORBIDDEN);
+    }
+}
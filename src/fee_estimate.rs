@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+use crate::consensus::vortex_consensus::MAX_BLOCK_TRANSACTIONS;
+use crate::rpc_storage::{RPCStorage, DEVICE_TRANSFER_FEE};
+use crate::shared::TxError;
+use crate::storage::StorageError;
+
+/// Lowest fee a transfer may carry, in microFVC
+pub const MIN_TRANSFER_FEE: u64 = DEVICE_TRANSFER_FEE;
+
+/// Recent blocks whose fullness feeds the estimate
+pub const FEE_ESTIMATE_BLOCKS: usize = 10;
+
+/// Congestion is capped so a flooded mempool cannot push fees without bound
+const MAX_CONGESTION: f64 = 10.0;
+
+/// Suggested transfer fees in microFVC
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct FeeEstimate {
+    pub low: u64,
+    pub medium: u64,
+    pub high: u64,
+    pub min_fee: u64,
+    pub mempool_size: usize,
+    /// Mean share of MAX_BLOCK_TRANSACTIONS used by the recent blocks, 0.0 to 1.0
+    pub block_fullness: f64,
+}
+
+/// Fees for a mempool holding `mempool_size` transactions when recent blocks carried
+/// `recent_block_transactions` transfers each. Low is always the floor; medium and high
+/// scale with the blocks' worth of backlog plus how full recent blocks were.
+pub fn estimate_fees(mempool_size: usize, recent_block_transactions: &[usize]) -> FeeEstimate {
+    let capacity = MAX_BLOCK_TRANSACTIONS as f64;
+    let block_fullness = if recent_block_transactions.is_empty() {
+        0.0
+    } else {
+        let mean = recent_block_transactions.iter().sum::<usize>() as f64 / recent_block_transactions.len() as f64;
+        (mean / capacity).min(1.0)
+    };
+    let congestion = (mempool_size as f64 / capacity + block_fullness).min(MAX_CONGESTION);
+    let scaled = |factor: f64| (MIN_TRANSFER_FEE as f64 * (1.0 + factor * congestion)).round() as u64;
+
+    FeeEstimate {
+        low: MIN_TRANSFER_FEE,
+        medium: scaled(1.0),
+        high: scaled(2.0),
+        min_fee: MIN_TRANSFER_FEE,
+        mempool_size,
+        block_fullness,
+    }
+}
+
+/// Estimate from the persisted mempool and the transfers in the latest blocks
+pub async fn current_fee_estimate(now: u64) -> Result<FeeEstimate, StorageError> {
+    let mempool_size = RPCStorage::get_mempool(now).await?.len();
+    let recent: Vec<usize> = RPCStorage::get_latest_blocks(FEE_ESTIMATE_BLOCKS).await?
+        .iter()
+        .filter(|block| block.height > 0)
+        .map(|block| block.transactions.iter().filter(|tx| tx.transaction_type != "mining_reward").count())
+        .collect();
+    Ok(estimate_fees(mempool_size, &recent))
+}
+
+/// Reject a client-chosen fee below the floor
+pub fn validate_fee(fee: u64) -> Result<(), TxError> {
+    if fee < MIN_TRANSFER_FEE {
+        return Err(TxError::FeeTooLow { fee, min: MIN_TRANSFER_FEE });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_mempool_returns_floor_fee() {
+        let estimate = estimate_fees(0, &[]);
+        assert_eq!((estimate.low, estimate.medium, estimate.high), (MIN_TRANSFER_FEE, MIN_TRANSFER_FEE, MIN_TRANSFER_FEE));
+
+        let quiet = estimate_fees(0, &[0, 0, 0]);
+        assert_eq!(quiet.medium, MIN_TRANSFER_FEE);
+    }
+
+    #[test]
+    fn test_full_mempool_raises_estimate() {
+        let full = estimate_fees(MAX_BLOCK_TRANSACTIONS * 3, &[MAX_BLOCK_TRANSACTIONS; 5]);
+        assert_eq!(full.low, MIN_TRANSFER_FEE);
+        assert_eq!(full.block_fullness, 1.0);
+        assert!(full.medium > MIN_TRANSFER_FEE);
+        assert!(full.high > full.medium);
+
+        let busier = estimate_fees(MAX_BLOCK_TRANSACTIONS * 6, &[MAX_BLOCK_TRANSACTIONS; 5]);
+        assert!(busier.medium > full.medium);
+
+        // Congestion is capped
+        assert_eq!(estimate_fees(usize::MAX / 2, &[]).high, MIN_TRANSFER_FEE * 21);
+    }
+
+    #[test]
+    fn test_fee_below_floor_rejected() {
+        assert_eq!(validate_fee(MIN_TRANSFER_FEE - 1), Err(TxError::FeeTooLow { fee: MIN_TRANSFER_FEE - 1, min: MIN_TRANSFER_FEE }));
+        assert!(validate_fee(MIN_TRANSFER_FEE).is_ok());
+    }
+}
@@ -0,0 +1,117 @@
+use serde_json::Value;
+use crate::input_validation::InputValidator;
+
+/// Wei per whole FVC (18 decimals)
+pub const WEI_PER_FVC: u128 = 1_000_000_000_000_000_000;
+
+/// Why a genesis configuration was refused
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum GenesisError {
+    #[error("Genesis config has no total supply")]
+    MissingSupply,
+    #[error("Genesis config has no allocations")]
+    NoAllocations,
+    #[error("Invalid genesis balance for {address}: {value}")]
+    InvalidBalance { address: String, value: String },
+    #[error("Invalid genesis address {address}: {reason}")]
+    InvalidAddress { address: String, reason: String },
+    #[error("Genesis allocations total {allocated} but supply is {total_supply}")]
+    SupplyMismatch { allocated: u128, total_supply: u128 },
+}
+
+/// Check that every allocation goes to a valid FVC address and that the amounts add up
+/// to exactly `total_supply` (both in the same unit)
+pub fn validate_allocations<'a>(
+    total_supply: u128,
+    allocations: impl IntoIterator<Item = (&'a str, u128)>,
+) -> Result<(), GenesisError> {
+    let mut allocated = 0u128;
+    let mut count = 0usize;
+    for (address, amount) in allocations {
+        InputValidator::validate_fvchain_address(address).map_err(|e| GenesisError::InvalidAddress {
+            address: address.to_string(),
+            reason: e.to_string(),
+        })?;
+        // Saturating keeps an overflowing config a mismatch rather than a panic
+        allocated = allocated.saturating_add(amount);
+        count += 1;
+    }
+
+    if count == 0 {
+        return Err(GenesisError::NoAllocations);
+    }
+    if allocated != total_supply {
+        return Err(GenesisError::SupplyMismatch { allocated, total_supply });
+    }
+    Ok(())
+}
+
+/// Total supply of a mainnet genesis config in wei; `config.supply.total` is whole FVC,
+/// written as a number or a numeric string
+fn total_supply_wei(config: &Value) -> Result<u128, GenesisError> {
+    let total = &config["config"]["supply"]["total"];
+    let fvc = match total {
+        Value::Number(n) => n.as_u64().map(u128::from),
+        Value::String(s) => s.parse::<u128>().ok(),
+        _ => None,
+    }
+    .ok_or(GenesisError::MissingSupply)?;
+    fvc.checked_mul(WEI_PER_FVC).ok_or(GenesisError::MissingSupply)
+}
+
+/// Validate a mainnet genesis config: the wei balances under `alloc` must sum to the total supply
+/// and every allocation must go to a valid FVC address
+pub fn validate_genesis(config: &Value) -> Result<(), GenesisError> {
+    let total_supply = total_supply_wei(config)?;
+    let alloc = config["alloc"].as_object().ok_or(GenesisError::NoAllocations)?;
+
+    let mut allocations = Vec::with_capacity(alloc.len());
+    for (address, allocation) in alloc {
+        let balance = &allocation["balance"];
+        let wei = balance.as_str()
+            .and_then(|b| b.parse::<u128>().ok())
+            .ok_or_else(|| GenesisError::InvalidBalance { address: address.clone(), value: balance.to_string() })?;
+        allocations.push((address.as_str(), wei));
+    }
+    validate_allocations(total_supply, allocations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const FOUNDATION: &str = "fvc00000000000000000000000000000000f001emyl";
+    const TREASURY: &str = "fvc00000000000000000000000000000000f002emyl";
+
+    fn config(total: u64, foundation_wei: &str, treasury_wei: &str) -> Value {
+        json!({
+            "config": { "chainId": 369, "supply": { "total": total } },
+            "alloc": {
+                FOUNDATION: { "balance": foundation_wei },
+                TREASURY: { "balance": treasury_wei }
+            }
+        })
+    }
+
+    #[test]
+    fn test_balanced_genesis_is_valid() {
+        // 600 + 400 FVC in wei
+        let genesis = config(1_000, "600000000000000000000", "400000000000000000000");
+        assert_eq!(validate_genesis(&genesis), Ok(()));
+
+        assert_eq!(validate_allocations(10, [(FOUNDATION, 7), (TREASURY, 3)]), Ok(()));
+    }
+
+    #[test]
+    fn test_allocations_exceeding_supply_rejected() {
+        let genesis = config(1_000, "600000000000000000000", "400000000000000000001");
+        assert_eq!(
+            validate_genesis(&genesis),
+            Err(GenesisError::SupplyMismatch { allocated: 1_000 * WEI_PER_FVC + 1, total_supply: 1_000 * WEI_PER_FVC })
+        );
+
+        let err = validate_allocations(10, [(FOUNDATION, 5), ("0x0000000000000000000000000000000000000001", 5)]).unwrap_err();
+        assert!(matches!(err, GenesisError::InvalidAddress { .. }));
+    }
+}
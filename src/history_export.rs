@@ -0,0 +1,138 @@
+use futures::stream::{self, Stream, StreamExt};
+use crate::rpc_storage::{RPCStorage, WalletTransaction, sender_fee};
+use crate::storage::StorageError;
+
+/// Column header of the CSV export
+pub const CSV_HEADER: &str = "date,type,counterparty,amount_fvc,fee_fvc,block_height,hash\n";
+
+/// Format microFVC as a decimal FVC amount
+fn format_fvc(micro: u64, negative: bool) -> String {
+    format!("{}{}.{:06}", if negative { "-" } else { "" }, micro / 1_000_000, micro % 1_000_000)
+}
+
+/// Quote a field if it contains a delimiter, quote or newline
+fn csv_field(value: &str) -> String {
+    if value.contains(|c| matches!(c, ',' | '"' | '\n' | '\r')) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// One CSV line for `tx` as seen from `address`; outgoing amounts are negative
+pub fn csv_row(tx: &WalletTransaction, address: &str) -> String {
+    let outgoing = tx.from == address && tx.to != address && tx.transaction_type != "mining_reward";
+    let counterparty = if outgoing { &tx.to } else { &tx.from };
+    let fee = if outgoing { sender_fee(tx) } else { 0 };
+    let date = chrono::DateTime::from_timestamp(tx.timestamp as i64, 0)
+        .map(|d| d.to_rfc3339())
+        .unwrap_or_default();
+
+    format!(
+        "{},{},{},{},{},{},{}\n",
+        date,
+        csv_field(&tx.transaction_type),
+        csv_field(counterparty),
+        format_fvc(tx.amount, outgoing),
+        format_fvc(fee, false),
+        tx.block_height,
+        csv_field(&tx.hash),
+    )
+}
+
+/// Stream an address's history as CSV in log order, reading one indexed transaction at a time
+pub fn address_history_csv(address: String) -> impl Stream<Item = Result<String, StorageError>> {
+    let header = stream::once(async { Ok(CSV_HEADER.to_string()) });
+
+    let rows = stream::unfold(Some((0u64, None::<u64>)), move |state| {
+        let address = address.clone();
+        async move {
+            let (mut n, count) = state?;
+            let count = match count {
+                Some(count) => count,
+                None => match RPCStorage::get_address_transaction_count(&address).await {
+                    Ok(count) => count,
+                    Err(e) => return Some((Err(e), None)),
+                },
+            };
+
+            while n < count {
+                let hash = match RPCStorage::get_address_transaction_hash_at(&address, n).await {
+                    Ok(hash) => hash,
+                    Err(e) => return Some((Err(e), None)),
+                };
+                n += 1;
+
+                let tx = match hash {
+                    Some(hash) => RPCStorage::get_transaction(&hash).await,
+                    None => continue,
+                };
+                match tx {
+                    Ok(Some(tx)) => return Some((Ok(csv_row(&tx, &address)), Some((n, Some(count))))),
+                    Ok(None) => continue,
+                    Err(e) => return Some((Err(e), None)),
+                }
+            }
+            None
+        }
+    });
+
+    header.chain(rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_storage::use_test_db;
+
+    fn tx(hash: &str, from: &str, to: &str, amount: u64, tx_type: &str, height: u64) -> WalletTransaction {
+        WalletTransaction {
+            hash: hash.to_string(),
+            from: from.to_string(),
+            to: to.to_string(),
+            amount,
+            timestamp: 1_700_000_000 + height,
+            transaction_type: tx_type.to_string(),
+            block_height: height,
+            nonce: 0,
+            fee: 0,
+            signature: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_export_small_history() {
+        let _db = use_test_db();
+
+        let alice = "fvcexportalice";
+        let history = vec![
+            tx("0xexp1", "Mining-Reward", alice, 6_250_000, "mining_reward", 1),
+            tx("0xexp2", "fvcexportbob", "fvcexportcarol", 1, "transfer", 2),
+            tx("0xexp3", alice, "fvcexportbob", 1_500_000, "device_transfer", 3),
+            tx("0xexp4", "fvcexportbob", alice, 250_000, "transfer", 4),
+        ];
+        for t in &history {
+            RPCStorage::add_transaction(t).await.unwrap();
+        }
+
+        let chunks: Vec<String> = address_history_csv(alice.to_string())
+            .map(|chunk| chunk.unwrap())
+            .collect()
+            .await;
+        let csv = chunks.concat();
+
+        let mut lines = csv.lines();
+        assert_eq!(lines.next(), Some(CSV_HEADER.trim_end()));
+        let rows: Vec<Vec<&str>> = lines.map(|l| l.split(',').collect()).collect();
+
+        assert_eq!(rows.len(), 3);
+        assert_eq!(rows.iter().map(|r| r[6]).collect::<Vec<_>>(), vec!["0xexp1", "0xexp3", "0xexp4"]);
+        assert_eq!(rows[0][1], "mining_reward");
+        assert_eq!(rows[0][3], "6.250000");
+        assert_eq!(rows[1][2], "fvcexportbob");
+        assert_eq!(rows[1][3], "-1.500000");
+        assert_eq!(rows[1][4], "0.001000");
+        assert_eq!(rows[2][3], "0.250000");
+        assert_eq!(rows[2][5], "4");
+    }
+}
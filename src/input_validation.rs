@@ -1,5 +1,6 @@
+use axum::{http::StatusCode, Json};
 use regex::Regex;
-use serde_json::Value;
+use serde_json::{json, Value};
 
 
 // Input validation errors
@@ -24,6 +25,104 @@ impl ValidationError {
             ValidationError::InvalidType(msg) => format!("Invalid type: {}", msg),
         }
     }
+
+    /// Stable machine-readable name for the kind of failure
+    pub fn code(&self) -> &'static str {
+        match self {
+            ValidationError::InvalidFormat(_) => "invalid_format",
+            ValidationError::InvalidLength(_) => "invalid_length",
+            ValidationError::InvalidRange(_) => "invalid_range",
+            ValidationError::InvalidCharacters(_) => "invalid_characters",
+            ValidationError::Required(_) => "required",
+            ValidationError::InvalidType(_) => "invalid_type",
+        }
+    }
+}
+
+/// Transaction construction errors reported through the same request guards
+impl From<crate::shared::TxError> for ValidationError {
+    fn from(error: crate::shared::TxError) -> Self {
+        use crate::shared::TxError;
+        match error {
+            TxError::InvalidAddress { .. } | TxError::Unsigned | TxError::InvalidSignature { .. } => {
+                ValidationError::InvalidFormat(error.to_string())
+            }
+            TxError::InvalidField { .. } => ValidationError::InvalidLength(error.to_string()),
+            TxError::ZeroAmount
+            | TxError::SelfTransfer
+            | TxError::AmountTooLarge { .. }
+            | TxError::DailyLimitExceeded { .. }
+            | TxError::FeeTooLow { .. } => ValidationError::InvalidRange(error.to_string()),
+        }
+    }
+}
+
+// Request guards used by handlers; each names the field it checks through RequestValidator
+
+pub fn validate_amount(amount: u64) -> Result<(), ValidationError> {
+    InputValidator::validate_amount(amount)
+}
+
+/// Amount check plus `amount + fee`, which must not wrap; returns the total to debit
+pub fn validate_amount_with_fee(amount: u64, fee: u64) -> Result<u64, ValidationError> {
+    let total = amount.checked_add(fee).ok_or_else(|| ValidationError::InvalidRange(
+        format!("Amount {} plus fee {} overflows", amount, fee)
+    ))?;
+    validate_amount(amount)?;
+    Ok(total)
+}
+
+pub fn validate_device_id(device_id: &str) -> Result<(), ValidationError> {
+    InputValidator::validate_device_id(device_id)
+}
+
+/// A failed check on one request field
+#[derive(Debug, Clone)]
+pub struct FieldError {
+    pub field: &'static str,
+    pub error: ValidationError,
+}
+
+/// Runs every check on a request and reports all failures in a single 400
+#[derive(Debug, Default)]
+pub struct RequestValidator {
+    errors: Vec<FieldError>,
+}
+
+impl RequestValidator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn check<T, E: Into<ValidationError>>(&mut self, field: &'static str, result: Result<T, E>) -> &mut Self {
+        if let Err(error) = result {
+            self.errors.push(FieldError { field, error: error.into() });
+        }
+        self
+    }
+
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+
+    pub fn finish(&self) -> Result<(), (StatusCode, Json<Value>)> {
+        if self.errors.is_empty() {
+            return Ok(());
+        }
+        let errors: Vec<Value> = self.errors.iter()
+            .map(|e| json!({
+                "field": e.field,
+                "code": e.error.code(),
+                "message": e.error.to_string()
+            }))
+            .collect();
+        Err((StatusCode::BAD_REQUEST, Json(json!({
+            "success": false,
+            "status": "rejected",
+            "error": format!("Invalid {}", self.errors.iter().map(|e| e.field).collect::<Vec<_>>().join(", ")),
+            "errors": errors
+        }))))
+    }
 }
 
 // Input validator
@@ -297,11 +396,12 @@ impl BatchValidator {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::shared::validate_tx_address;
     
     #[test]
     fn test_validate_fvchain_address() {
         // Valid address (fvc + 36 hex + emyl = 43 characters)
-        assert!(InputValidator::validate_fvchain_address("fvc1234567890abcdef1234567890abcdef123456emyl").is_ok());
+        assert!(InputValidator::validate_fvchain_address("fvc1234567890abcdef1234567890abcdef1234emyl").is_ok());
         
         // Invalid prefix
         assert!(InputValidator::validate_fvchain_address("btc1234567890abcdef1234567890abcdef123456emyl").is_err());
@@ -316,7 +416,7 @@ mod tests {
         assert!(InputValidator::validate_fvchain_address("fvc1234567890abcdef1234567890abcdef1234567890emyl").is_err());
         
         // Invalid hex characters
-        assert!(InputValidator::validate_fvchain_address("fvcGHIJKL7890abcdef1234567890abcdef123456emyl").is_err());
+        assert!(InputValidator::validate_fvchain_address("fvcGHIJKL7890abcdef1234567890abcdef1234emyl").is_err());
         
         // Invalid characters
         assert!(InputValidator::validate_fvchain_address("fvc1234567890ABCDEF1234567890abcdef123456").is_err());
@@ -351,4 +451,44 @@ mod tests {
         // Too large amount
         assert!(InputValidator::validate_amount(u64::MAX).is_err());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_request_guards() {
+        // Zero amount
+        assert_eq!(validate_amount(0).unwrap_err().code(), "invalid_range");
+        assert_eq!(validate_amount_with_fee(5_000, 1_000).unwrap(), 6_000);
+
+        // amount + fee must not wrap to a small total
+        let overflow = validate_amount_with_fee(u64::MAX - 10, 1_000).unwrap_err();
+        assert!(overflow.to_string().contains("overflows"));
+
+        // Addresses go through the shared transaction validator
+        let invalid = ValidationError::from(validate_tx_address("to", "btc1234567890abcdef1234567890abcdef1234emyl").unwrap_err());
+        assert_eq!(invalid.code(), "invalid_format");
+        assert!(invalid.to_string().contains("Invalid to address"));
+        assert!(validate_tx_address("to", "fvc1234567890abcdef1234567890abcdef1234emyl").is_ok());
+
+        assert_eq!(validate_device_id("").unwrap_err().code(), "required");
+        assert!(validate_device_id("device-0001").is_ok());
+    }
+
+    #[test]
+    fn test_request_validator_reports_every_field() {
+        let mut validator = RequestValidator::new();
+        validator
+            .check("device_id", validate_device_id("device-0001"))
+            .check("to", validate_tx_address("to", "fvc123emyl"))
+            .check("amount", validate_amount_with_fee(0, 1_000));
+        assert_eq!(validator.errors().len(), 2);
+
+        let (status, Json(body)) = validator.finish().unwrap_err();
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let fields: Vec<&str> = body["errors"].as_array().unwrap().iter()
+            .map(|e| e["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(fields, vec!["to", "amount"]);
+        assert_eq!(body["errors"][1]["code"], "invalid_range");
+
+        assert!(RequestValidator::new().check("amount", validate_amount(1)).finish().is_ok());
+    }
+}
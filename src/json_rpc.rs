@@ -0,0 +1,191 @@
+use std::future::Future;
+use serde_json::{json, Value};
+use crate::rpc_storage::{confirmations, is_finalized, RPCStorage, CONFIRMATION_DEPTH};
+
+/// Request body is not valid JSON
+pub const PARSE_ERROR: i64 = -32700;
+/// JSON is not a valid request object
+pub const INVALID_REQUEST: i64 = -32600;
+/// No such method
+pub const METHOD_NOT_FOUND: i64 = -32601;
+/// Missing or mistyped params
+pub const INVALID_PARAMS: i64 = -32602;
+/// Storage or other node-side failure
+pub const INTERNAL_ERROR: i64 = -32603;
+/// The node handled the call but refused it (e.g. a rejected transaction)
+pub const SERVER_ERROR: i64 = -32000;
+
+/// JSON-RPC 2.0 error object
+#[derive(Debug, Clone, PartialEq)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    pub fn method_not_found(method: &str) -> Self {
+        Self::new(METHOD_NOT_FOUND, format!("Method not found: {}", method))
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(INTERNAL_ERROR, message)
+    }
+}
+
+/// Param at `index` of a positional array, or `name` of a by-name object
+pub fn param<'a>(params: &'a Value, index: usize, name: &str) -> Result<&'a Value, RpcError> {
+    match params {
+        Value::Array(values) => values.get(index),
+        Value::Object(fields) => fields.get(name),
+        _ => None,
+    }
+    .ok_or_else(|| RpcError::invalid_params(format!("missing param '{}'", name)))
+}
+
+fn success(id: Value, result: Value) -> Value {
+    json!({ "jsonrpc": "2.0", "result": result, "id": id })
+}
+
+fn failure(id: Value, error: RpcError) -> Value {
+    json!({ "jsonrpc": "2.0", "error": { "code": error.code, "message": error.message }, "id": id })
+}
+
+/// Parse a JSON-RPC 2.0 request body and run it through `dispatch(method, params)`.
+/// Returns the response envelope, or None for a notification (a request without an id).
+pub async fn handle<F, Fut>(body: &[u8], dispatch: F) -> Option<Value>
+where
+    F: FnOnce(String, Value) -> Fut,
+    Fut: Future<Output = Result<Value, RpcError>>,
+{
+    let request: Value = match serde_json::from_slice(body) {
+        Ok(request) => request,
+        Err(e) => return Some(failure(Value::Null, RpcError::new(PARSE_ERROR, format!("Parse error: {}", e)))),
+    };
+
+    let id = request.get("id").cloned();
+    let method = match (request.get("jsonrpc").and_then(Value::as_str), request.get("method").and_then(Value::as_str)) {
+        (Some("2.0"), Some(method)) => method.to_string(),
+        _ => {
+            let error = RpcError::new(INVALID_REQUEST, "Invalid request: expected jsonrpc \"2.0\" and a method name");
+            return Some(failure(id.unwrap_or(Value::Null), error));
+        }
+    };
+    let params = request.get("params").cloned().unwrap_or(Value::Null);
+
+    let outcome = dispatch(method, params).await;
+    let id = id?;
+    Some(match outcome {
+        Ok(result) => success(id, result),
+        Err(error) => failure(id, error),
+    })
+}
+
+/// Read-only chain methods served straight from storage
+pub async fn chain_method(method: &str, params: &Value) -> Result<Value, RpcError> {
+    match method {
+        "fvc_blockNumber" => RPCStorage::get_block_height().await
+            .map(|height| json!(height))
+            .map_err(|e| RpcError::internal(e.to_string())),
+        "fvc_getBlockByHeight" => {
+            let height = param(params, 0, "height")?
+                .as_u64()
+                .ok_or_else(|| RpcError::invalid_params("'height' must be a non-negative integer"))?;
+            let block = RPCStorage::get_block_by_height(height).await
+                .map_err(|e| RpcError::internal(e.to_string()))?;
+            let Some(block) = block else {
+                return Ok(Value::Null);
+            };
+            let tip = RPCStorage::get_block_height().await.unwrap_or(0);
+            Ok(json!({
+                "block": block,
+                "confirmations": confirmations(height, tip),
+                "finalized": is_finalized(height, tip, *CONFIRMATION_DEPTH)
+            }))
+        }
+        "fvc_getBalance" => {
+            let address = param(params, 0, "address")?
+                .as_str()
+                .ok_or_else(|| RpcError::invalid_params("'address' must be a string"))?;
+            let breakdown = RPCStorage::get_balance_breakdown(address).await
+                .map_err(|e| RpcError::internal(e.to_string()))?;
+            Ok(json!({
+                "balance": breakdown.balance,
+                "spendable_balance": breakdown.spendable_balance,
+                "immature_balance": breakdown.immature_balance
+            }))
+        }
+        _ => Err(RpcError::method_not_found(method)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_storage::use_test_db;
+
+    async fn call(body: &str) -> Value {
+        handle(body.as_bytes(), |method, params| async move { chain_method(&method, &params).await })
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_valid_method_returns_result() {
+        let _db = use_test_db();
+        let address = "fvc00000000000000000000000000000000c001emyl";
+        RPCStorage::set_balance(address, 4_200).await.unwrap();
+
+        let response = call(&format!(
+            r#"{{"jsonrpc":"2.0","method":"fvc_getBalance","params":["{}"],"id":7}}"#,
+            address
+        )).await;
+        assert_eq!(response["jsonrpc"], "2.0");
+        assert_eq!(response["id"], 7);
+        assert_eq!(response["result"]["balance"], 4_200);
+        assert!(response.get("error").is_none());
+
+        let response = call(r#"{"jsonrpc":"2.0","method":"fvc_blockNumber","id":"tip"}"#).await;
+        assert_eq!(response["id"], "tip");
+        assert_eq!(response["result"], RPCStorage::get_block_height().await.unwrap());
+
+        // Missing params are the caller's fault
+        let response = call(r#"{"jsonrpc":"2.0","method":"fvc_getBlockByHeight","params":{},"id":1}"#).await;
+        assert_eq!(response["error"]["code"], INVALID_PARAMS);
+    }
+
+    #[tokio::test]
+    async fn test_unknown_method() {
+        let response = call(r#"{"jsonrpc":"2.0","method":"eth_chainId","id":3}"#).await;
+        assert_eq!(response["error"]["code"], METHOD_NOT_FOUND);
+        assert_eq!(response["id"], 3);
+        assert!(response.get("result").is_none());
+    }
+
+    #[tokio::test]
+    async fn test_malformed_json() {
+        let response = call(r#"{"jsonrpc":"2.0","method":"#).await;
+        assert_eq!(response["error"]["code"], PARSE_ERROR);
+        assert_eq!(response["id"], Value::Null);
+
+        let response = call(r#"{"method":"fvc_blockNumber","id":4}"#).await;
+        assert_eq!(response["error"]["code"], INVALID_REQUEST);
+        assert_eq!(response["id"], 4);
+    }
+
+    #[tokio::test]
+    async fn test_notification_gets_no_response() {
+        let response = handle(
+            br#"{"jsonrpc":"2.0","method":"eth_chainId"}"#,
+            |method, params| async move { chain_method(&method, &params).await },
+        ).await;
+        assert!(response.is_none());
+    }
+}
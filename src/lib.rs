@@ -44,40 +44,43 @@ pub mod utils {
         if n == 0 {
             return 0;
         }
-        let mut sum = n;
-        while sum >= 10 {
-            sum = sum.to_string()
-                .chars()
-                .map(|c| c.to_digit(10).unwrap() as u64)
-                .sum();
-        }
-        sum
+        1 + (n - 1) % 9
     }
     
-    /// Calculate vortex energy from data
+    /// Calculate vortex energy from data: the sum of `byte * vortex step / (i + 1)`, 0.0 for empty input.
+    /// Kahan summation keeps the rounding error flat for long inputs.
     pub fn vortex_energy(data: &[u8]) -> f64 {
         let mut energy = 0.0;
+        let mut compensation = 0.0;
         for (i, &byte) in data.iter().enumerate() {
             let vortex_val = VORTEX_SEQUENCE[i % VORTEX_SEQUENCE.len()] as f64;
-            energy += (byte as f64 * vortex_val) / (i as f64 + 1.0);
+            let term = (byte as f64 * vortex_val) / (i as f64 + 1.0) - compensation;
+            let sum = energy + term;
+            compensation = (sum - energy) - term;
+            energy = sum;
         }
         energy.abs()
     }
+
+    /// Vortex energy, or None when the result is not finite
+    pub fn vortex_energy_checked(data: &[u8]) -> Option<f64> {
+        Some(vortex_energy(data)).filter(|energy| energy.is_finite())
+    }
     
-    /// Generate Sierpinski triangle coordinates
+    /// Lower-left corner of each of the `3^level` triangles left after `level` subdivisions of the
+    /// unit Sierpinski triangle; every point lies on the gasket
     pub fn sierpinski_coordinates(level: u32) -> Vec<(f64, f64)> {
-        let mut coords = Vec::new();
-        let _size = 2.0f64.powi(level as i32);
-        
-        for i in 0..=level {
-            for j in 0..=(2u32.pow(i)) {
-                let x = (j as f64) / (2u32.pow(i) as f64);
-                let y = (i as f64) / (level as f64);
-                coords.push((x, y));
-            }
+        let height = 3f64.sqrt() / 2.0;
+        let mut corners = vec![(0.0, 0.0)];
+        let mut size = 1.0;
+        for _ in 0..level {
+            size /= 2.0;
+            corners = corners
+                .iter()
+                .flat_map(|&(x, y)| [(x, y), (x + size, y), (x + size / 2.0, y + size * height)])
+                .collect();
         }
-        
-        coords
+        corners
     }
     
     /// Calculate torus coordinates from seed
@@ -87,6 +90,81 @@ pub mod utils {
         let radius = 1.0 + (seed as f64 / 1000.0).sin() * 0.5;
         (phi, theta, radius)
     }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        // Strictly inside the triangle (a, b, c), so points on its edges do not count
+        fn strictly_inside(p: (f64, f64), a: (f64, f64), b: (f64, f64), c: (f64, f64)) -> bool {
+            let side = |u: (f64, f64), v: (f64, f64)| (v.0 - u.0) * (p.1 - u.1) - (v.1 - u.1) * (p.0 - u.0);
+            let (d1, d2, d3) = (side(a, b), side(b, c), side(c, a));
+            (d1 > 1e-9 && d2 > 1e-9 && d3 > 1e-9) || (d1 < -1e-9 && d2 < -1e-9 && d3 < -1e-9)
+        }
+
+        // Previous implementation, kept as the reference
+        fn digit_sum_root(n: u64) -> u64 {
+            let mut sum = n;
+            while sum >= 10 {
+                sum = sum.to_string().chars().map(|c| c.to_digit(10).unwrap() as u64).sum();
+            }
+            sum
+        }
+
+        #[test]
+        fn test_digital_root_matches_digit_sums() {
+            for n in 0..=1_000_000u64 {
+                assert_eq!(digital_root(n), digit_sum_root(n), "n = {}", n);
+            }
+            assert_eq!(digital_root(u64::MAX), digit_sum_root(u64::MAX));
+        }
+
+        #[test]
+        fn test_vortex_energy_contract() {
+            assert_eq!(vortex_energy(&[]), 0.0);
+            assert_eq!(vortex_energy_checked(&[]), Some(0.0));
+
+            let expected = 255.0 * (1.0 + 1.0 + 4.0 / 3.0 + 2.0 + 7.0 / 5.0 + 5.0 / 6.0);
+            assert!((vortex_energy(&[0xFF; 6]) - expected).abs() < 1e-9);
+            let saturated = vortex_energy_checked(&vec![0xFF; 1 << 20]).unwrap();
+            assert!(saturated > expected);
+
+            // Chunked naive sums stay close to the exact value; Kahan should agree with them
+            let megabyte: Vec<u8> = (0..1usize << 20).map(|i| (i * 31 % 251) as u8).collect();
+            let reference: f64 = megabyte
+                .chunks(1024)
+                .enumerate()
+                .map(|(chunk, bytes)| {
+                    bytes.iter().enumerate().map(|(j, &b)| {
+                        let i = chunk * 1024 + j;
+                        b as f64 * VORTEX_SEQUENCE[i % VORTEX_SEQUENCE.len()] as f64 / (i as f64 + 1.0)
+                    }).sum::<f64>()
+                })
+                .sum();
+            let energy = vortex_energy_checked(&megabyte).unwrap();
+            assert!((energy - reference).abs() <= reference * 1e-12);
+        }
+
+        #[test]
+        fn test_sierpinski_points_stay_on_gasket() {
+            for level in 0..6 {
+                assert_eq!(sierpinski_coordinates(level).len(), 3usize.pow(level));
+            }
+
+            let h = 3f64.sqrt() / 2.0;
+            let removed = [
+                ((0.25, h / 2.0), (0.75, h / 2.0), (0.5, 0.0)),
+                ((0.125, h / 4.0), (0.375, h / 4.0), (0.25, 0.0)),
+                ((0.625, h / 4.0), (0.875, h / 4.0), (0.75, 0.0)),
+                ((0.375, 3.0 * h / 4.0), (0.625, 3.0 * h / 4.0), (0.5, h / 2.0)),
+            ];
+            for point in sierpinski_coordinates(2) {
+                for &(a, b, c) in &removed {
+                    assert!(!strictly_inside(point, a, b, c), "{:?} lies in a removed triangle", point);
+                }
+            }
+        }
+    }
 }
 
 /// Core types and structures
@@ -155,6 +233,57 @@ pub mod api_auth;
 pub mod input_validation;
 pub mod api_monitoring;
 
+/// Offline chain verification
+pub mod chain_verify;
+
+/// Per-address transaction history export
+pub mod history_export;
+
+/// Testnet faucet
+pub mod faucet;
+
+/// Block subscriptions with historical replay
+pub mod block_stream;
+
+/// Idempotent transaction submission
+pub mod tx_submission;
+
+/// Admin-only debug endpoints
+pub mod debug_api;
+
+/// Node clock for client skew detection
+pub mod server_time;
+
+/// JSON-RPC 2.0 request handling
+pub mod json_rpc;
+
+/// Chain state queries for the node CLI
+pub mod chain_query;
+
+/// Chain data snapshots for backup and migration
+pub mod snapshot;
+
+/// Graceful RPC server shutdown
+pub mod shutdown;
+
+/// Live WebSocket feed for the node dashboard
+pub mod dashboard_ws;
+
+/// Transfer fee estimation from mempool congestion
+pub mod fee_estimate;
+
+/// Prometheus metrics exposition
+pub mod metrics_exporter;
+
+/// Genesis configuration validation
+pub mod genesis;
+
+/// Smart Rate calculation and component breakdown
+pub mod smart_rate;
+
+/// Block explorer search by height, hash or address
+pub mod explorer_search;
+
 /// Version information
 pub const VERSION: &str = "1.0.0";
 pub const CHAIN_ID: &str = "fractal-vortex-mainnet";
\ No newline at end of file
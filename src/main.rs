@@ -41,6 +41,62 @@ enum Commands {
         /// Node ID
         #[arg(long, default_value = "0")]
         node_id: usize,
+        
+        /// Node config file (.toml, .json or .env); defaults to node-<id>.env when present
+        #[arg(long)]
+        config: Option<String>,
+    },
+    
+    /// Verify the stored chain offline
+    Verify {
+        /// RPC storage directory
+        #[arg(long, default_value = "./data/rpc_storage")]
+        data_dir: String,
+        
+        /// Genesis config with the initial allocations
+        #[arg(long, default_value = "mainnet-genesis.json")]
+        genesis: String,
+    },
+    
+    /// Query a running node's chain state
+    Query {
+        /// Node RPC URL
+        #[arg(long, default_value = "http://localhost:8080")]
+        rpc_url: String,
+        
+        /// Show the block at this height
+        #[arg(long, conflicts_with_all = ["balance", "tx"])]
+        height: Option<u64>,
+        
+        /// Show the balance of this address
+        #[arg(long, conflicts_with = "tx")]
+        balance: Option<String>,
+        
+        /// Show the transaction with this hash
+        #[arg(long)]
+        tx: Option<String>,
+    },
+    
+    /// Export the stored chain to a single archive (stop the node first)
+    Snapshot {
+        /// RPC storage directory
+        #[arg(long, default_value = "./data/rpc_storage")]
+        data_dir: String,
+        
+        /// Archive to write
+        #[arg(long)]
+        out: String,
+    },
+    
+    /// Load an archive from `snapshot` into an empty data directory
+    Restore {
+        /// Fresh RPC storage directory
+        #[arg(long, default_value = "./data/rpc_storage")]
+        data_dir: String,
+        
+        /// Archive to read
+        #[arg(long = "in")]
+        input: String,
     },
 }
 
@@ -81,8 +137,20 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Mainnet { config } => {
             deploy_mainnet_genesis(config).await?;
         }
-        Commands::Start { node_id } => {
-            start_genesis_node(node_id).await?;
+        Commands::Start { node_id, config } => {
+            start_genesis_node(node_id, config).await?;
+        }
+        Commands::Verify { data_dir, genesis } => {
+            verify_chain(data_dir, genesis).await?;
+        }
+        Commands::Query { rpc_url, height, balance, tx } => {
+            query_chain(rpc_url, height, balance, tx).await?;
+        }
+        Commands::Snapshot { data_dir, out } => {
+            snapshot_chain(data_dir, out).await?;
+        }
+        Commands::Restore { data_dir, input } => {
+            restore_chain(data_dir, input).await?;
         }
     }
 
@@ -98,13 +166,25 @@ async fn deploy_genesis(name: String, validators: usize, initial_supply: u64) ->
         .unwrap()
         .as_secs();
     
+    use fractal_vortex_chain::genesis::validate_allocations;
+    use fractal_vortex_chain::wallet::key_manager::KeyManager;
+    
+    if validators == 0 {
+        return Err("at least one validator is required".into());
+    }
+    
     let mut validator_list = Vec::new();
+    let stake = initial_supply / validators as u64;
     
     for i in 0..validators {
+        // Deterministic native address per validator index
+        let mut body = [0u8; 16];
+        body[8..].copy_from_slice(&(i as u64).to_be_bytes());
         let validator = Validator {
             id: format!("validator-{:02}", i),
-            address: format!("0x{:040x}", i),
-            stake: initial_supply / validators as u64,
+            address: KeyManager::address_with_checksum(&body),
+            // The first validator takes the remainder so stakes sum to the supply
+            stake: if i == 0 { stake + initial_supply % validators as u64 } else { stake },
             fractal_energy: 1.618 + (i as f64 * 0.1),
         };
         validator_list.push(validator);
@@ -124,6 +204,11 @@ async fn deploy_genesis(name: String, validators: usize, initial_supply: u64) ->
         },
     };
     
+    validate_allocations(
+        config.initial_supply as u128,
+        config.validators.iter().map(|v| (v.address.as_str(), v.stake as u128)),
+    )?;
+    
     let config_json = serde_json::to_string_pretty(&config)?;
     
     fs::write("genesis.json", &config_json)?;
@@ -141,8 +226,8 @@ async fn deploy_genesis(name: String, validators: usize, initial_supply: u64) ->
     // Create node configurations
     for (i, validator) in config.validators.iter().enumerate() {
         let node_config = format!(
-            "# Node {} Configuration\nNODE_ID={}\nLISTEN_PORT={}\nVALIDATOR_ADDRESS={}\nSTAKE_AMOUNT={}\nFRACTAL_ENERGY={}\nGENESIS_FILE=genesis.json\n",
-            i, validator.id, 8000 + i, validator.address, validator.stake, validator.fractal_energy
+            "# Node {} Configuration\nNODE_ID={}\nLISTEN_PORT={}\nNODE_LISTEN_ADDR=/ip4/127.0.0.1/tcp/{}\nVALIDATOR_ADDRESS={}\nSTAKE_AMOUNT={}\nFRACTAL_ENERGY={}\nGENESIS_FILE=genesis.json\n",
+            i, validator.id, 8000 + i, 8000 + i, validator.address, validator.stake, validator.fractal_energy
         );
         
         fs::write(format!("node-{}.env", i), &node_config)?;
@@ -203,6 +288,7 @@ async fn deploy_mainnet_genesis(config_file: String) -> Result<(), Box<dyn std::
     // Read mainnet genesis config
     let config_data = fs::read_to_string(&config_file)?;
     let mainnet_config: serde_json::Value = serde_json::from_str(&config_data)?;
+    fractal_vortex_chain::genesis::validate_genesis(&mainnet_config)?;
     
     println!("📋 Mainnet Configuration:");
     println!("   Chain ID: {}", mainnet_config["config"]["chainId"]);
@@ -270,7 +356,96 @@ pause
     Ok(())
 }
 
-async fn start_genesis_node(node_id: usize) -> Result<(), Box<dyn std::error::Error>> {
+async fn verify_chain(data_dir: String, genesis: String) -> Result<(), Box<dyn std::error::Error>> {
+    use fractal_vortex_chain::chain_verify::verify_stored_chain;
+    use fractal_vortex_chain::rpc_storage::genesis_allocations;
+    
+    println!("🔍 Verifying chain in {}", data_dir);
+    
+    // RPC storage opens its database from this variable on first use
+    std::env::set_var("RPC_DATA_DIR", &data_dir);
+    
+    let initial = match fs::read_to_string(&genesis) {
+        Ok(data) => genesis_allocations(&serde_json::from_str(&data)?).balances,
+        Err(_) => {
+            println!("⚠️  Genesis config {} not found, replaying from empty balances", genesis);
+            Default::default()
+        }
+    };
+    
+    let report = verify_stored_chain(&initial).await?;
+    
+    println!("📊 Blocks checked: {}", report.blocks_checked);
+    for failure in &report.failures {
+        println!("❌ Block #{}: {}", failure.height, failure.reason);
+    }
+    for mismatch in &report.balance_mismatches {
+        println!("❌ Balance {}: replayed {} but stored {}", mismatch.address, mismatch.replayed, mismatch.stored);
+    }
+    
+    if report.is_valid() {
+        println!("✅ Chain verified successfully");
+        Ok(())
+    } else {
+        println!("❌ Failing heights: {:?}", report.failing_heights());
+        Err(format!(
+            "chain verification failed: {} block failures, {} balance mismatches",
+            report.failures.len(),
+            report.balance_mismatches.len()
+        ).into())
+    }
+}
+
+async fn query_chain(
+    rpc_url: String,
+    height: Option<u64>,
+    balance: Option<String>,
+    tx: Option<String>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    use fractal_vortex_chain::chain_query::{run_query, ChainQuery};
+    use fractal_vortex_chain::wallet::rpc_client::RpcClient;
+    
+    let query = match (height, balance, tx) {
+        (Some(height), _, _) => ChainQuery::Block(height),
+        (_, Some(address), _) => ChainQuery::Balance(address),
+        (_, _, Some(hash)) => ChainQuery::Transaction(hash),
+        _ => return Err("query needs one of --height, --balance or --tx".into()),
+    };
+    
+    let client = RpcClient::new(&rpc_url);
+    print!("{}", run_query(&client, &query).await?);
+    Ok(())
+}
+
+async fn snapshot_chain(data_dir: String, out: String) -> Result<(), Box<dyn std::error::Error>> {
+    use fractal_vortex_chain::snapshot::{take_snapshot, write_snapshot};
+    use fractal_vortex_chain::storage::LedgerDB;
+    
+    println!("📦 Snapshotting chain in {}", data_dir);
+    let db = LedgerDB::open(&data_dir)?;
+    let snapshot = take_snapshot(&db).await?;
+    write_snapshot(&out, &snapshot)?;
+    
+    println!("✅ Wrote {} records to {}", snapshot.records.len(), out);
+    println!("   Latest height: {}", snapshot.block_height);
+    println!("   Blocks: {}, transactions: {}", snapshot.block_count, snapshot.transaction_count);
+    Ok(())
+}
+
+async fn restore_chain(data_dir: String, input: String) -> Result<(), Box<dyn std::error::Error>> {
+    use fractal_vortex_chain::snapshot::{read_snapshot, restore_snapshot};
+    use fractal_vortex_chain::storage::LedgerDB;
+    
+    println!("📥 Restoring {} into {}", input, data_dir);
+    let snapshot = read_snapshot(&input)?;
+    let db = LedgerDB::open(&data_dir)?;
+    restore_snapshot(&db, &snapshot).await?;
+    
+    println!("✅ Restored {} records; latest height {} and sampled balances match", snapshot.records.len(), snapshot.block_height);
+    Ok(())
+}
+
+async fn start_genesis_node(node_id: usize, config_file: Option<String>) -> Result<(), Box<dyn std::error::Error>> {
     println!("🚀 Starting Genesis Node {}...", node_id);
     
     let genesis_file = "genesis.json";
@@ -294,17 +469,25 @@ async fn start_genesis_node(node_id: usize) -> Result<(), Box<dyn std::error::Er
     
     // Import FractalNode and related types
     use fractal_vortex_chain::node::fractal_node::{FractalNode, NodeConfig};
-    use libp2p::Multiaddr;
-    
-    // Create FractalNode configuration
-    let listen_addr: Multiaddr = format!("/ip4/127.0.0.1/tcp/{}", port).parse()?;
-    let node_config = NodeConfig {
-        listen_addr,
-        bootstrap_nodes: vec![],
-        energy_threshold: config.fractal_parameters.energy_threshold,
-        fractal_levels: config.fractal_parameters.fractal_levels,
-        max_peers: 50,
-        sync_interval: 30,
+    use fractal_vortex_chain::network::build_listen_addr;
+    
+    // Create FractalNode configuration, preferring --config, then an explicit node-<id>.env file
+    let config_file = config_file.unwrap_or_else(|| format!("node-{}.env", node_id));
+    let node_config = if std::path::Path::new(&config_file).exists() {
+        println!("   Config File: {}", config_file);
+        NodeConfig::from_file(&config_file)
+            .map_err(|e| format!("Invalid {}: {}", config_file, e))?
+    } else {
+        let listen_addr = build_listen_addr("127.0.0.1", port)?;
+        NodeConfig {
+            listen_addr,
+            bootstrap_nodes: vec![],
+            energy_threshold: config.fractal_parameters.energy_threshold,
+            fractal_levels: config.fractal_parameters.fractal_levels,
+            max_peers: 50,
+            sync_interval: 30,
+            data_dir: Some(format!("data/node-{}", node_id).into()),
+        }
     };
     
     // Create and start the actual blockchain node
@@ -340,130 +523,13 @@ async fn start_genesis_node(node_id: usize) -> Result<(), Box<dyn std::error::Er
     let ws_port = 30333 + node_id;
     println!("🔗 Starting WebSocket server on port {}...", ws_port);
     
-    // WebSocket server integrated with real blockchain node
-    use tokio::net::TcpListener;
-    use tokio_tungstenite::tungstenite::Message;
-    use futures_util::{SinkExt, StreamExt};
-    
+    // Dashboard feed pushes real chain state from RPC storage and the node on every stored block and tick
     let addr = format!("127.0.0.1:{}", ws_port);
-    let try_socket = TcpListener::bind(addr.clone()).await;
-    let listener = try_socket.expect("Failed to bind");
+    let listener = tokio::net::TcpListener::bind(&addr).await
+        .map_err(|e| format!("Failed to bind WebSocket server on {}: {}", addr, e))?;
     println!("🌐 WebSocket server listening on {}", addr);
     
-    // Keep the main node running
-    tokio::spawn(async move {
-        loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(1)).await;
-        }
-    });
-    
-    while let Ok((stream, _)) = listener.accept().await {
-        tokio::spawn(async move {
-            // Handle the WebSocket handshake
-            let ws_stream = match tokio_tungstenite::accept_async(stream).await {
-                Ok(ws) => ws,
-                Err(e) => {
-                    eprintln!("WebSocket connection error: {}", e);
-                    return;
-                }
-            };
-            
-            let (mut ws_sender, mut ws_receiver) = ws_stream.split();
-            
-            // Send initial connection message
-            let welcome_msg = serde_json::json!({
-                "type": "connected",
-                "node": node_id,
-                "message": "Fractal-Vortex blockchain node connected"
-            }).to_string();
-            
-            if let Err(e) = ws_sender.send(Message::Text(welcome_msg)).await {
-                eprintln!("Failed to send welcome message: {}", e);
-                return;
-            }
-            
-            // Send periodic updates with real blockchain data
-            let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(3));
-            
-            loop {
-                tokio::select! {
-                    _ = interval.tick() => {
-                        // Get real blockchain state (simplified approach)
-                        let current_time = chrono::Utc::now().timestamp();
-                        let block_height = (current_time / 5).max(1) as u64; // 5 second blocks
-                        let total_transactions = block_height * 10; // 10 tx per block average
-                        
-                        // Send real blockchain metrics
-                        let metrics_msg = serde_json::json!({
-                            "type": "metrics",
-                            "totalTransactions": total_transactions,
-                            "activeNodes": 1,
-                            "blockHeight": block_height,
-                            "tps": 2, // 10 tx per 5 second block = 2 TPS
-                            "vortexEnergy": 85.7 + (current_time % 100) as f64 / 10.0,
-                            "isValidator": true,
-                            "networkStatus": "healthy",
-                            "lastUpdate": current_time * 1000
-                        }).to_string();
-                        
-                        let _ = ws_sender.send(Message::Text(metrics_msg)).await;
-                        
-                        // Send real node data
-                        let nodes_msg = serde_json::json!({
-                            "type": "nodes",
-                            "nodes": [serde_json::json!({
-                                "id": format!("fractal-node-{}", node_id),
-                                "name": format!("FractalNode-{}", node_id),
-                                "status": "active",
-                                "lastSeen": "now",
-                                "blockHeight": block_height,
-                                "isValidator": true
-                            })]
-                        }).to_string();
-                        
-                        let _ = ws_sender.send(Message::Text(nodes_msg)).await;
-                        
-                        // Send real block data
-                        let blocks_msg = serde_json::json!({
-                            "type": "blocks",
-                            "blocks": (0..std::cmp::min(4, block_height)).map(|i| {
-                                let height = block_height - i;
-                                serde_json::json!({
-                                    "height": height,
-                                    "hash": format!("0x{:064x}", height * 123456789),
-                                    "transactions": 10,
-                                    "miner": format!("FractalNode-{}", node_id),
-                                    "timestamp": format!("{}s ago", i * 5),
-                                    "size": 1000000 + (i * 250000)
-                                })
-                            }).collect::<Vec<_>>()
-                        }).to_string();
-                        
-                        if ws_sender.send(Message::Text(blocks_msg)).await.is_err() {
-                            break;
-                        }
-                    }
-                    Some(msg) = ws_receiver.next() => {
-                        match msg {
-                            Ok(Message::Text(text)) => {
-                                println!("Received message: {}", text);
-                            }
-                            Ok(Message::Close(_)) => {
-                                break;
-                            }
-                            Err(e) => {
-                                eprintln!("WebSocket error: {}", e);
-                                break;
-                            }
-                            _ => {}
-                        }
-                    }
-                }
-            }
-            
-            println!("WebSocket connection closed");
-        });
-    }
+    fractal_vortex_chain::dashboard_ws::serve_dashboard(listener, node_id, fractal_node.get_state()).await;
     
     Ok(())
 }
\ No newline at end of file
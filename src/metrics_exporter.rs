@@ -0,0 +1,138 @@
+use std::fmt::Write;
+use crate::node_health::get_active_nodes_count;
+use crate::rpc_storage::RPCStorage;
+use crate::security::monitoring::SecurityMonitor;
+use crate::storage::StorageError;
+
+/// Content type of the Prometheus text exposition format
+pub const PROMETHEUS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Chain-level gauges sampled for one scrape
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct ChainMetrics {
+    pub block_height: u64,
+    pub total_transactions: u64,
+    pub active_nodes: u32,
+    pub mempool_size: usize,
+}
+
+impl ChainMetrics {
+    /// Read the current chain state from storage
+    pub async fn collect(now: u64) -> Result<Self, StorageError> {
+        Ok(Self {
+            block_height: RPCStorage::get_block_height().await?,
+            total_transactions: RPCStorage::get_transaction_count().await?,
+            active_nodes: get_active_nodes_count(),
+            mempool_size: RPCStorage::get_mempool(now).await?.len(),
+        })
+    }
+}
+
+/// Escape a label value: backslash, double quote and newline
+fn escape_label(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"").replace('\n', "\\n")
+}
+
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, value: impl std::fmt::Display) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+    let _ = writeln!(out, "{} {}", name, value);
+}
+
+/// Prometheus text exposition of the chain gauges and the monitor's request and transfer counters
+pub fn render_prometheus(chain: &ChainMetrics, monitor: &SecurityMonitor) -> String {
+    let mut out = String::new();
+    write_metric(&mut out, "fvc_block_height", "gauge", "Height of the chain tip.", chain.block_height);
+    write_metric(&mut out, "fvc_transactions_total", "counter", "Transactions stored by this node.", chain.total_transactions);
+    write_metric(&mut out, "fvc_active_nodes", "gauge", "Nodes currently reporting healthy.", chain.active_nodes);
+    write_metric(&mut out, "fvc_mempool_size", "gauge", "Pending transactions waiting to be mined.", chain.mempool_size);
+    write_metric(&mut out, "fvc_transfer_failures_total", "counter", "Transfers rejected or failed by this node.", monitor.transfer_failures());
+    write_metric(&mut out, "fvc_uptime_seconds", "gauge", "Seconds since the monitor started.", monitor.uptime().as_secs());
+
+    // Sorted so successive scrapes list endpoints in the same order
+    let mut endpoints: Vec<_> = monitor.endpoint_stats().into_iter().collect();
+    endpoints.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let _ = writeln!(out, "# HELP fvc_http_requests_total Requests served per endpoint.");
+    let _ = writeln!(out, "# TYPE fvc_http_requests_total counter");
+    for (endpoint, stats) in &endpoints {
+        let _ = writeln!(out, "fvc_http_requests_total{{endpoint=\"{}\"}} {}", escape_label(endpoint), stats.requests);
+    }
+    let _ = writeln!(out, "# HELP fvc_http_request_errors_total Responses with status 400 or above per endpoint.");
+    let _ = writeln!(out, "# TYPE fvc_http_request_errors_total counter");
+    for (endpoint, stats) in &endpoints {
+        let _ = writeln!(out, "fvc_http_request_errors_total{{endpoint=\"{}\"}} {}", escape_label(endpoint), stats.errors);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::StatusCode;
+    use std::collections::HashMap;
+    use std::time::Duration;
+
+    /// Check `text` against the exposition format: TYPE precedes a family's samples,
+    /// names are valid, labels are quoted and every value is a float. Returns sample values by series.
+    fn parse_exposition(text: &str) -> HashMap<String, f64> {
+        let valid_name = |name: &str| {
+            !name.is_empty()
+                && name.chars().enumerate().all(|(i, c)| c.is_ascii_alphabetic() || c == '_' || c == ':' || (i > 0 && c.is_ascii_digit()))
+        };
+        let mut typed = Vec::new();
+        let mut samples = HashMap::new();
+        for line in text.lines() {
+            if let Some(comment) = line.strip_prefix("# ") {
+                let parts: Vec<&str> = comment.splitn(3, ' ').collect();
+                assert!(parts.len() == 3 && valid_name(parts[1]), "bad comment line: {}", line);
+                match parts[0] {
+                    "TYPE" => {
+                        assert!(["counter", "gauge", "histogram", "summary", "untyped"].contains(&parts[2]), "bad type: {}", line);
+                        typed.push(parts[1].to_string());
+                    }
+                    "HELP" => {}
+                    other => panic!("unknown comment {}", other),
+                }
+                continue;
+            }
+            let (series, value) = line.rsplit_once(' ').unwrap_or_else(|| panic!("no value: {}", line));
+            let name = series.split('{').next().unwrap();
+            assert!(valid_name(name), "bad metric name: {}", line);
+            assert!(typed.iter().any(|t| t == name), "sample before its TYPE: {}", line);
+            if let Some(labels) = series.strip_prefix(name).filter(|l| !l.is_empty()) {
+                let inner = labels.strip_prefix('{').and_then(|l| l.strip_suffix('}')).expect("unterminated labels");
+                let (key, quoted) = inner.split_once('=').expect("label without value");
+                assert!(valid_name(key) && quoted.starts_with('"') && quoted.ends_with('"'), "bad label: {}", line);
+            }
+            samples.insert(series.to_string(), value.parse::<f64>().unwrap_or_else(|_| panic!("bad value: {}", line)));
+        }
+        samples
+    }
+
+    #[test]
+    fn test_metrics_are_valid_exposition() {
+        let monitor = SecurityMonitor::new();
+        monitor.record_request("GET /api/v1/blockchain/blocks", StatusCode::OK, Duration::from_millis(3));
+        monitor.record_request("GET /api/v1/blockchain/blocks", StatusCode::INTERNAL_SERVER_ERROR, Duration::from_millis(9));
+        monitor.record_request("POST /api/v1/wallet/send", StatusCode::BAD_REQUEST, Duration::from_millis(1));
+        monitor.record_transfer_failure();
+
+        let chain = ChainMetrics { block_height: 4_321, total_transactions: 99, active_nodes: 3, mempool_size: 7 };
+        let samples = parse_exposition(&render_prometheus(&chain, &monitor));
+
+        assert_eq!(samples["fvc_block_height"], 4_321.0);
+        assert_eq!(samples["fvc_transactions_total"], 99.0);
+        assert_eq!(samples["fvc_active_nodes"], 3.0);
+        assert_eq!(samples["fvc_mempool_size"], 7.0);
+        assert_eq!(samples["fvc_transfer_failures_total"], 1.0);
+        assert_eq!(samples["fvc_http_requests_total{endpoint=\"GET /api/v1/blockchain/blocks\"}"], 2.0);
+        assert_eq!(samples["fvc_http_request_errors_total{endpoint=\"GET /api/v1/blockchain/blocks\"}"], 1.0);
+        assert_eq!(samples["fvc_http_request_errors_total{endpoint=\"POST /api/v1/wallet/send\"}"], 1.0);
+    }
+
+    #[test]
+    fn test_label_values_escaped() {
+        assert_eq!(escape_label("GET /a\"b\\c\n"), "GET /a\\\"b\\\\c\\n");
+    }
+}
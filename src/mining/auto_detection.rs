@@ -16,6 +16,9 @@ pub struct DeviceConnection {
     pub is_mining: bool,
     pub connection_count: u32,
     pub last_activity: u64, // timestamp in seconds
+    /// Waktu mining dihentikan karena heartbeat hilang; sesi masih bisa di-resume
+    #[serde(default)]
+    pub lapsed_at: Option<u64>,
 }
 
 /// Konfigurasi untuk auto detection
@@ -29,6 +32,8 @@ pub struct AutoDetectionConfig {
     pub grace_period: Duration,
     /// Maximum retry attempts untuk reconnection
     pub max_retry_attempts: u32,
+    /// Window setelah mining dihentikan di mana sesi masih bisa di-resume (default: 300 detik)
+    pub resume_window: Duration,
 }
 
 impl Default for AutoDetectionConfig {
@@ -38,22 +43,126 @@ impl Default for AutoDetectionConfig {
             check_interval: Duration::from_secs(10),
             grace_period: Duration::from_secs(90), // Increased from 60 to 90 seconds
             max_retry_attempts: 3,
+            resume_window: Duration::from_secs(300),
         }
     }
 }
 
+impl AutoDetectionConfig {
+    /// Override fields from environment variables (values in seconds):
+    /// MINING_HEARTBEAT_TIMEOUT_SECS, MINING_CHECK_INTERVAL_SECS,
+    /// MINING_GRACE_PERIOD_SECS, MINING_MAX_RETRY_ATTEMPTS, MINING_RESUME_WINDOW_SECS
+    pub fn with_env_overrides(mut self) -> Self {
+        fn env_u64(key: &str) -> Option<u64> {
+            std::env::var(key).ok().and_then(|v| v.trim().parse().ok())
+        }
+
+        if let Some(secs) = env_u64("MINING_HEARTBEAT_TIMEOUT_SECS") {
+            self.heartbeat_timeout = Duration::from_secs(secs);
+        }
+        if let Some(secs) = env_u64("MINING_CHECK_INTERVAL_SECS") {
+            self.check_interval = Duration::from_secs(secs);
+        }
+        if let Some(secs) = env_u64("MINING_GRACE_PERIOD_SECS") {
+            self.grace_period = Duration::from_secs(secs);
+        }
+        if let Some(attempts) = env_u64("MINING_MAX_RETRY_ATTEMPTS") {
+            self.max_retry_attempts = attempts.min(u32::MAX as u64) as u32;
+        }
+        if let Some(secs) = env_u64("MINING_RESUME_WINDOW_SECS") {
+            self.resume_window = Duration::from_secs(secs);
+        }
+        self
+    }
+
+    /// Validasi hubungan antar nilai konfigurasi
+    pub fn validate(&self) -> Result<(), String> {
+        if self.check_interval.is_zero() {
+            return Err("check_interval must be greater than zero".to_string());
+        }
+        if self.heartbeat_timeout.is_zero() {
+            return Err("heartbeat_timeout must be greater than zero".to_string());
+        }
+        if self.check_interval > self.heartbeat_timeout {
+            return Err("check_interval must not exceed heartbeat_timeout".to_string());
+        }
+        if self.grace_period < self.heartbeat_timeout {
+            return Err("grace_period must be at least heartbeat_timeout".to_string());
+        }
+        if self.max_retry_attempts == 0 {
+            return Err("max_retry_attempts must be at least 1".to_string());
+        }
+        Ok(())
+    }
+}
+
 /// Request untuk heartbeat
 #[derive(Debug, Deserialize)]
 pub struct HeartbeatRequest {
+    /// Missing fields default to empty so `validate` can report them by name
+    #[serde(default)]
     pub device_id: String,
+    #[serde(default)]
     pub session_token: String,
+    #[serde(default)]
     pub timestamp: u64,
 }
 
+/// Validation error untuk heartbeat request
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum HeartbeatValidationError {
+    #[error("{0} is required")]
+    MissingField(&'static str),
+    #[error("{field} is malformed: {reason}")]
+    MalformedField { field: &'static str, reason: String },
+}
+
+impl HeartbeatValidationError {
+    /// Name of the offending field
+    pub fn field(&self) -> &'static str {
+        match self {
+            Self::MissingField(field) => field,
+            Self::MalformedField { field, .. } => field,
+        }
+    }
+}
+
+impl HeartbeatRequest {
+    /// Validasi field heartbeat sebelum diteruskan ke auto-detection
+    pub fn validate(&self) -> Result<(), HeartbeatValidationError> {
+        if self.device_id.trim().is_empty() {
+            return Err(HeartbeatValidationError::MissingField("device_id"));
+        }
+        if self.device_id.len() > 128 {
+            return Err(HeartbeatValidationError::MalformedField {
+                field: "device_id",
+                reason: "must be at most 128 characters".to_string(),
+            });
+        }
+        if !self.device_id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+            return Err(HeartbeatValidationError::MalformedField {
+                field: "device_id",
+                reason: "only letters, digits, '-' and '_' are allowed".to_string(),
+            });
+        }
+        if self.session_token.trim().is_empty() {
+            return Err(HeartbeatValidationError::MissingField("session_token"));
+        }
+        if self.session_token.len() > 256 {
+            return Err(HeartbeatValidationError::MalformedField {
+                field: "session_token",
+                reason: "must be at most 256 characters".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
 /// Response untuk heartbeat
 #[derive(Debug, Serialize)]
 pub struct HeartbeatResponse {
     pub success: bool,
+    pub device_id: String,
     pub server_time: u64,
     pub mining_status: bool,
     pub message: String,
@@ -95,6 +204,7 @@ impl MiningAutoDetection {
             is_mining: false,
             connection_count: 1,
             last_activity: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs(),
+            lapsed_at: None,
         };
         
         connections.insert(device_id.clone(), connection);
@@ -103,14 +213,38 @@ impl MiningAutoDetection {
 
     /// Update heartbeat untuk device
     pub async fn update_heartbeat(&self, request: HeartbeatRequest) -> HeartbeatResponse {
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+        self.update_heartbeat_at(request, now).await
+    }
+
+    /// Update heartbeat pada waktu `now`; sesi yang lapsed di-resume jika masih dalam resume window
+    pub async fn update_heartbeat_at(&self, request: HeartbeatRequest, now: u64) -> HeartbeatResponse {
         let mut connections = self.connections.lock().await;
         
+        // Sesi lapsed di luar resume window harus registrasi ulang
+        let expired = connections.get(&request.device_id)
+            .and_then(|c| c.lapsed_at)
+            .map(|lapsed_at| Duration::from_secs(now.saturating_sub(lapsed_at)) > self.config.resume_window)
+            .unwrap_or(false);
+        if expired {
+            connections.remove(&request.device_id);
+            info!("Lapsed session expired for device: {}", request.device_id);
+            return HeartbeatResponse {
+                success: false,
+                device_id: request.device_id.clone(),
+                server_time: chrono::Utc::now().timestamp() as u64,
+                mining_status: false,
+                message: "Session expired, re-registration required".to_string(),
+            };
+        }
+        
         if let Some(connection) = connections.get_mut(&request.device_id) {
             // Validasi session token
             if connection.session_token != request.session_token {
                 warn!("Invalid session token for device: {}", request.device_id);
                 return HeartbeatResponse {
                     success: false,
+                    device_id: request.device_id.clone(),
                     server_time: chrono::Utc::now().timestamp() as u64,
                     mining_status: false,
                     message: "Invalid session token".to_string(),
@@ -118,23 +252,40 @@ impl MiningAutoDetection {
             }
 
             // Update heartbeat
-            let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
             connection.last_heartbeat = now;
             connection.last_activity = now;
             connection.connection_count += 1;
 
-            info!("Heartbeat updated for device: {}", request.device_id);
+            // Resume mining untuk sesi yang sempat lapsed
+            let resumed = connection.lapsed_at.take().is_some();
+            if resumed {
+                connection.is_mining = true;
+                info!("Mining session resumed for device: {}", request.device_id);
+            } else {
+                info!("Heartbeat updated for device: {}", request.device_id);
+            }
             
-            HeartbeatResponse {
+            let response = HeartbeatResponse {
                 success: true,
+                device_id: request.device_id.clone(),
                 server_time: chrono::Utc::now().timestamp() as u64,
                 mining_status: connection.is_mining,
-                message: "Heartbeat received".to_string(),
+                message: if resumed { "Session resumed" } else { "Heartbeat received" }.to_string(),
+            };
+            drop(connections);
+            
+            if resumed {
+                if let Some(callback) = &self.mining_callback {
+                    callback(request.device_id.clone(), true);
+                }
             }
+            
+            response
         } else {
             warn!("Device not found for heartbeat: {}", request.device_id);
             HeartbeatResponse {
                 success: false,
+                device_id: request.device_id.clone(),
                 server_time: chrono::Utc::now().timestamp() as u64,
                 mining_status: false,
                 message: "Device not registered".to_string(),
@@ -215,53 +366,17 @@ impl MiningAutoDetection {
                 interval.tick().await;
                 
                 let mut connections_guard = connections.lock().await;
-                let mut devices_to_stop = Vec::new();
-                let mut devices_to_remove = Vec::new();
-                
                 let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+                let (devices_to_stop, devices_to_remove) = Self::sweep_connections(&mut connections_guard, &config, now);
+                drop(connections_guard);
                 
-                for (device_id, connection) in connections_guard.iter() {
-                    let time_since_heartbeat = Duration::from_secs(now.saturating_sub(connection.last_heartbeat));
-                    
-                    // Check jika device sudah timeout
-                    if time_since_heartbeat > config.heartbeat_timeout {
-                        if connection.is_mining {
-                            // Grace period sebelum stop mining
-                            if time_since_heartbeat > config.grace_period {
-                                devices_to_stop.push(device_id.clone());
-                                warn!("Device {} timed out, stopping mining", device_id);
-                            } else {
-                                warn!("Device {} in grace period, mining continues", device_id);
-                            }
-                        } else {
-                            // Jika tidak mining dan sudah timeout, remove device
-                            if time_since_heartbeat > config.grace_period * 2 {
-                                devices_to_remove.push(device_id.clone());
-                            }
-                        }
-                    }
-                }
-                
-                // Stop mining untuk devices yang timeout
-                for device_id in &devices_to_stop {
-                    if let Some(connection) = connections_guard.get_mut(device_id) {
-                        connection.is_mining = false;
-                        
-                        // Call callback untuk stop mining
-                        if let Some(ref callback) = callback {
-                            callback(device_id.clone(), false);
-                        }
+                // Call callback untuk stop mining
+                if let Some(ref callback) = callback {
+                    for device_id in &devices_to_stop {
+                        callback(device_id.clone(), false);
                     }
                 }
                 
-                // Remove devices yang sudah tidak aktif
-                for device_id in &devices_to_remove {
-                    connections_guard.remove(device_id);
-                    info!("Removed inactive device: {}", device_id);
-                }
-                
-                drop(connections_guard);
-                
                 if !devices_to_stop.is_empty() || !devices_to_remove.is_empty() {
                     info!("Auto-detection check completed: {} stopped, {} removed", 
                           devices_to_stop.len(), devices_to_remove.len());
@@ -272,6 +387,80 @@ impl MiningAutoDetection {
         info!("Mining auto-detection monitoring started");
     }
 
+    /// Satu putaran deteksi pada waktu `now`; mengembalikan device yang dihentikan dan dihapus
+    fn sweep_connections(
+        connections: &mut HashMap<String, DeviceConnection>,
+        config: &AutoDetectionConfig,
+        now: u64,
+    ) -> (Vec<String>, Vec<String>) {
+        let mut devices_to_stop = Vec::new();
+        let mut devices_to_remove = Vec::new();
+        
+        for (device_id, connection) in connections.iter() {
+            let time_since_heartbeat = Duration::from_secs(now.saturating_sub(connection.last_heartbeat));
+            
+            // Check jika device sudah timeout
+            if time_since_heartbeat > config.heartbeat_timeout {
+                if connection.is_mining {
+                    // Grace period sebelum stop mining
+                    if time_since_heartbeat > config.grace_period {
+                        devices_to_stop.push(device_id.clone());
+                        warn!("Device {} timed out, stopping mining", device_id);
+                    } else {
+                        warn!("Device {} in grace period, mining continues", device_id);
+                    }
+                } else if let Some(lapsed_at) = connection.lapsed_at {
+                    // Sesi lapsed dipertahankan selama resume window
+                    if Duration::from_secs(now.saturating_sub(lapsed_at)) > config.resume_window {
+                        devices_to_remove.push(device_id.clone());
+                    }
+                } else {
+                    // Jika tidak mining dan sudah timeout, remove device
+                    if time_since_heartbeat > config.grace_period * 2 {
+                        devices_to_remove.push(device_id.clone());
+                    }
+                }
+            }
+        }
+        
+        // Stop mining untuk devices yang timeout
+        for device_id in &devices_to_stop {
+            if let Some(connection) = connections.get_mut(device_id) {
+                connection.is_mining = false;
+                connection.lapsed_at = Some(now);
+            }
+        }
+        
+        // Remove devices yang sudah tidak aktif
+        for device_id in &devices_to_remove {
+            connections.remove(device_id);
+            info!("Removed inactive device: {}", device_id);
+        }
+        
+        (devices_to_stop, devices_to_remove)
+    }
+
+    /// Jalankan satu putaran deteksi secara manual pada waktu `now`
+    pub async fn run_detection_pass(&self, now: u64) -> (Vec<String>, Vec<String>) {
+        let (stopped, removed) = {
+            let mut connections = self.connections.lock().await;
+            Self::sweep_connections(&mut connections, &self.config, now)
+        };
+        
+        if let Some(callback) = &self.mining_callback {
+            for device_id in &stopped {
+                callback(device_id.clone(), false);
+            }
+        }
+        
+        (stopped, removed)
+    }
+
+    /// Konfigurasi efektif
+    pub fn config(&self) -> &AutoDetectionConfig {
+        &self.config
+    }
+
     /// Get semua active connections
     pub async fn get_active_connections(&self) -> Vec<DeviceConnection> {
         let connections = self.connections.lock().await;
@@ -334,8 +523,146 @@ mod tests {
             timestamp: chrono::Utc::now().timestamp() as u64,
         };
         
+        let before = chrono::Utc::now().timestamp() as u64;
         let response = detection.update_heartbeat(request).await;
         assert!(response.success);
+        assert_eq!(response.device_id, "test_device");
+        assert!(response.server_time >= before);
+    }
+
+    #[test]
+    fn test_heartbeat_validation() {
+        let valid: HeartbeatRequest = serde_json::from_str(
+            r#"{"device_id": "device-01", "session_token": "session", "timestamp": 1}"#
+        ).unwrap();
+        assert!(valid.validate().is_ok());
+
+        let missing: HeartbeatRequest = serde_json::from_str(
+            r#"{"session_token": "session", "timestamp": 1}"#
+        ).unwrap();
+        let err = missing.validate().unwrap_err();
+        assert_eq!(err, HeartbeatValidationError::MissingField("device_id"));
+        assert_eq!(err.field(), "device_id");
+
+        let malformed = HeartbeatRequest {
+            device_id: "bad device!".to_string(),
+            session_token: "session".to_string(),
+            timestamp: 1,
+        };
+        assert_eq!(malformed.validate().unwrap_err().field(), "device_id");
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(AutoDetectionConfig::default().validate().is_ok());
+
+        let config = AutoDetectionConfig {
+            heartbeat_timeout: Duration::from_secs(60),
+            grace_period: Duration::from_secs(30),
+            ..AutoDetectionConfig::default()
+        };
+        assert!(config.validate().is_err());
+
+        let config = AutoDetectionConfig {
+            check_interval: Duration::from_secs(0),
+            ..AutoDetectionConfig::default()
+        };
+        assert!(config.validate().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_device_within_grace_period_stays_active() {
+        let config = AutoDetectionConfig {
+            heartbeat_timeout: Duration::from_secs(30),
+            check_interval: Duration::from_secs(10),
+            grace_period: Duration::from_secs(90),
+            max_retry_attempts: 3,
+            resume_window: Duration::from_secs(300),
+        };
+        let detection = MiningAutoDetection::new(config);
+        detection.register_device("slow_device".to_string(), "session".to_string(), "wallet".to_string()).await;
+        detection.start_mining("slow_device").await;
+
+        let last_heartbeat = detection.get_device_status("slow_device").await.unwrap().last_heartbeat;
+
+        // Missed heartbeats past the timeout but inside the grace period
+        let (stopped, removed) = detection.run_detection_pass(last_heartbeat + 60).await;
+        assert!(stopped.is_empty());
+        assert!(removed.is_empty());
+        assert!(detection.get_device_status("slow_device").await.unwrap().is_mining);
+    }
+
+    #[tokio::test]
+    async fn test_device_beyond_grace_period_goes_offline() {
+        let config = AutoDetectionConfig {
+            heartbeat_timeout: Duration::from_secs(30),
+            check_interval: Duration::from_secs(10),
+            grace_period: Duration::from_secs(90),
+            max_retry_attempts: 3,
+            resume_window: Duration::from_secs(300),
+        };
+        let detection = MiningAutoDetection::new(config);
+        detection.register_device("lost_device".to_string(), "session".to_string(), "wallet".to_string()).await;
+        detection.start_mining("lost_device").await;
+
+        let last_heartbeat = detection.get_device_status("lost_device").await.unwrap().last_heartbeat;
+
+        let (stopped, _) = detection.run_detection_pass(last_heartbeat + 91).await;
+        assert_eq!(stopped, vec!["lost_device".to_string()]);
+        assert!(!detection.get_device_status("lost_device").await.unwrap().is_mining);
+
+        // Left idle past the resume window, the device is dropped entirely
+        let (_, removed) = detection.run_detection_pass(last_heartbeat + 91 + 301).await;
+        assert_eq!(removed, vec!["lost_device".to_string()]);
+        assert!(detection.get_device_status("lost_device").await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_within_resume_window_resumes_session() {
+        let detection = MiningAutoDetection::new(AutoDetectionConfig::default());
+        detection.register_device("phone".to_string(), "session".to_string(), "wallet".to_string()).await;
+        detection.start_mining("phone").await;
+
+        let last_heartbeat = detection.get_device_status("phone").await.unwrap().last_heartbeat;
+        let lapsed_at = last_heartbeat + 100;
+        let (stopped, _) = detection.run_detection_pass(lapsed_at).await;
+        assert_eq!(stopped.len(), 1);
+
+        let request = HeartbeatRequest {
+            device_id: "phone".to_string(),
+            session_token: "session".to_string(),
+            timestamp: lapsed_at + 60,
+        };
+        let response = detection.update_heartbeat_at(request, lapsed_at + 60).await;
+        assert!(response.success);
+        assert!(response.mining_status);
+
+        // Contribution stats carried over from before the lapse
+        let status = detection.get_device_status("phone").await.unwrap();
+        assert!(status.is_mining);
+        assert_eq!(status.lapsed_at, None);
+        assert_eq!(status.connection_count, 2);
+    }
+
+    #[tokio::test]
+    async fn test_reconnect_after_resume_window_requires_registration() {
+        let detection = MiningAutoDetection::new(AutoDetectionConfig::default());
+        detection.register_device("phone".to_string(), "session".to_string(), "wallet".to_string()).await;
+        detection.start_mining("phone").await;
+
+        let last_heartbeat = detection.get_device_status("phone").await.unwrap().last_heartbeat;
+        let lapsed_at = last_heartbeat + 100;
+        detection.run_detection_pass(lapsed_at).await;
+
+        let request = HeartbeatRequest {
+            device_id: "phone".to_string(),
+            session_token: "session".to_string(),
+            timestamp: lapsed_at + 301,
+        };
+        let response = detection.update_heartbeat_at(request, lapsed_at + 301).await;
+        assert!(!response.success);
+        assert!(!response.mining_status);
+        assert!(detection.get_device_status("phone").await.is_none());
     }
 
     #[tokio::test]
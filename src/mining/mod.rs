@@ -1,4 +1,7 @@
 pub mod auto_detection;
+pub mod template;
+pub mod rng;
+pub mod reward_address;
 
 pub use auto_detection::{
     MiningAutoDetection,
@@ -6,4 +9,9 @@ pub use auto_detection::{
     DeviceConnection,
     HeartbeatRequest,
     HeartbeatResponse,
-};
\ No newline at end of file
+    HeartbeatValidationError,
+};
+
+pub use template::{BlockTemplate, TemplateError};
+pub use rng::BlockRng;
+pub use reward_address::{mining_address_from_env, resolve_mining_address, MiningAddressError};
@@ -0,0 +1,59 @@
+use thiserror::Error;
+use crate::input_validation::InputValidator;
+
+/// Reward address used off mainnet when MINING_ADDRESS is unset
+pub const DEV_MINING_ADDRESS: &str = "fvc000000000000000000000000000000000000emyl";
+
+/// Mining reward address errors
+#[derive(Debug, Error, PartialEq)]
+pub enum MiningAddressError {
+    #[error("MINING_ADDRESS must be set explicitly on {0}")]
+    Missing(String),
+    #[error("Invalid MINING_ADDRESS '{address}': {reason}")]
+    Invalid { address: String, reason: String },
+}
+
+/// Validate the configured reward address; only non-mainnet networks fall back to `DEV_MINING_ADDRESS`
+pub fn resolve_mining_address(network: &str, configured: Option<&str>) -> Result<String, MiningAddressError> {
+    let address = match configured.map(str::trim).filter(|a| !a.is_empty()) {
+        Some(address) => address,
+        None if network.eq_ignore_ascii_case("mainnet") => {
+            return Err(MiningAddressError::Missing(network.to_string()));
+        }
+        None => DEV_MINING_ADDRESS,
+    };
+
+    InputValidator::validate_fvchain_address(address).map_err(|e| MiningAddressError::Invalid {
+        address: address.to_string(),
+        reason: e.to_string(),
+    })?;
+    Ok(address.to_string())
+}
+
+/// Resolve from MINING_ADDRESS and FVC_NETWORK (default mainnet)
+pub fn mining_address_from_env() -> Result<String, MiningAddressError> {
+    let network = std::env::var("FVC_NETWORK").unwrap_or_else(|_| "mainnet".to_string());
+    resolve_mining_address(&network, std::env::var("MINING_ADDRESS").ok().as_deref())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_mining_address() {
+        let address = "fvc0123456789abcdef0123456789abcdef0123emyl";
+        assert_eq!(resolve_mining_address("mainnet", Some(address)).unwrap(), address);
+        assert_eq!(resolve_mining_address("testnet", None).unwrap(), DEV_MINING_ADDRESS);
+    }
+
+    #[test]
+    fn test_bogus_mining_address_rejected() {
+        let err = resolve_mining_address("testnet", Some("FVCminer1234567890abcdef")).unwrap_err();
+        assert!(matches!(err, MiningAddressError::Invalid { .. }));
+        assert_eq!(
+            resolve_mining_address("mainnet", None),
+            Err(MiningAddressError::Missing("mainnet".to_string()))
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use rand::rngs::StdRng;
+use rand::{RngCore, SeedableRng};
+
+/// Randomness used while building blocks: nonce starts, hash salts and any random selection.
+/// Nodes use `secure()`; tests pass `seeded()` so a block can be reproduced exactly.
+#[derive(Debug, Clone)]
+pub struct BlockRng(StdRng);
+
+impl BlockRng {
+    /// Cryptographically secure generator seeded from the OS
+    pub fn secure() -> Self {
+        Self(StdRng::from_entropy())
+    }
+
+    /// Deterministic generator; the same seed always yields the same sequence
+    pub fn seeded(seed: u64) -> Self {
+        Self(StdRng::seed_from_u64(seed))
+    }
+
+    /// Where the proof-of-work nonce search starts
+    pub fn start_nonce(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+}
+
+impl Default for BlockRng {
+    fn default() -> Self {
+        Self::secure()
+    }
+}
+
+impl RngCore for BlockRng {
+    fn next_u32(&mut self) -> u32 {
+        self.0.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        self.0.fill_bytes(dest)
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand::Error> {
+        self.0.try_fill_bytes(dest)
+    }
+}
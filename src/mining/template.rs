@@ -0,0 +1,189 @@
+use serde::{Serialize, Deserialize};
+use sha3::{Digest, Sha3_256};
+use thiserror::Error;
+use crate::consensus::vortex_consensus::Transaction;
+use crate::consensus::MiningRewardSystem;
+use crate::rpc_storage::{transfer_content_hash, Block, WalletTransaction, BLOCK_FRACTAL_LEVELS, TRANSFER_TYPES};
+use crate::wallet::key_manager::KeyManager;
+
+/// Maximum mempool transactions selected into one template
+pub const MAX_TEMPLATE_TRANSACTIONS: usize = crate::consensus::vortex_consensus::MAX_BLOCK_TRANSACTIONS;
+
+/// Errors returned when submitting a solved template
+#[derive(Debug, Error, PartialEq)]
+pub enum TemplateError {
+    #[error("Unknown or expired template")]
+    UnknownTemplate,
+    #[error("Template is stale: chain tip has moved")]
+    Stale,
+    #[error("Proof-of-work does not meet difficulty {0}")]
+    InvalidProofOfWork(u32),
+    #[error("Block rejected: {0}")]
+    Rejected(String),
+    #[error("Failed to store block: {0}")]
+    Storage(String),
+}
+
+/// Candidate block handed to external mining software.
+/// Miners hash `header` (hex) followed by the little-endian u64 nonce.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BlockTemplate {
+    pub parent_hash: String,
+    pub height: u64,
+    pub difficulty: u32,
+    pub fractal_levels: u32,
+    pub merkle_root: String,
+    pub timestamp: u64,
+    pub miner: String,
+    pub transactions: Vec<WalletTransaction>,
+}
+
+impl BlockTemplate {
+    /// Build a template paying the scheduled reward at `height` to `miner` and including the first
+    /// `MAX_TEMPLATE_TRANSACTIONS` mempool transactions, which come highest fee first
+    pub fn new(
+        parent_hash: String,
+        height: u64,
+        miner: String,
+        difficulty: u32,
+        mempool: &[Transaction],
+        timestamp: u64,
+    ) -> Self {
+        let mut coinbase = WalletTransaction::coinbase(miner.clone(), MiningRewardSystem::new().reward_at_height(height), height);
+        coinbase.timestamp = timestamp;
+
+        let mut transactions = vec![coinbase];
+        transactions.extend(
+            mempool.iter()
+                .take(MAX_TEMPLATE_TRANSACTIONS)
+                .map(|tx| transfer_from_consensus(tx, height, timestamp)),
+        );
+
+        let mut template = Self {
+            parent_hash,
+            height,
+            difficulty,
+            fractal_levels: BLOCK_FRACTAL_LEVELS,
+            merkle_root: String::new(),
+            timestamp,
+            miner,
+            transactions,
+        };
+        template.merkle_root = format!("0x{}", hex::encode(template.to_block(0).merkle_root()));
+        template
+    }
+
+    /// Stable identifier derived from the header
+    pub fn id(&self) -> String {
+        hex::encode(Sha3_256::digest(self.header_bytes()))
+    }
+
+    /// Header bytes the miner appends the nonce to
+    pub fn header_bytes(&self) -> Vec<u8> {
+        self.to_block(0).header_bytes()
+    }
+
+    /// Assemble the block for `nonce`, hashed but not yet linked to its parent's work
+    pub fn to_block(&self, nonce: u64) -> Block {
+        let mut block = Block::new_with_timestamp(
+            self.height,
+            self.miner.clone(),
+            self.parent_hash.clone(),
+            self.timestamp,
+        );
+        block.difficulty = self.difficulty as u64;
+        block.nonce = nonce;
+        for tx in &self.transactions {
+            block.add_transaction(tx.clone());
+        }
+        block.hash = block.canonical_hash();
+        block
+    }
+
+    /// Check a submitted nonce and return the solved block
+    pub fn solve(&self, nonce: u64) -> Result<Block, TemplateError> {
+        let block = self.to_block(nonce);
+        if !block.has_valid_pow() {
+            return Err(TemplateError::InvalidProofOfWork(self.difficulty));
+        }
+        Ok(block)
+    }
+}
+
+/// Record a signed mempool transaction as a transfer between native addresses, carrying the
+/// sender's public key and signature so every node can check it against the `from` address
+pub fn transfer_from_consensus(tx: &Transaction, height: u64, timestamp: u64) -> WalletTransaction {
+    let mut transfer = WalletTransaction::new_transfer(
+        KeyManager::address_from_public_key(&tx.from),
+        String::from_utf8_lossy(&tx.to).into_owned(),
+        tx.amount,
+        format!("0x{}", hex::encode(tx.hash)),
+        height,
+    );
+    transfer.timestamp = timestamp;
+    transfer.nonce = tx.nonce;
+    transfer.fee = tx.vortex_fee as u64;
+    transfer.signature = Some([tx.from.as_slice(), tx.signature.as_slice()].concat());
+    // Gossip carries no transaction type; it is the one the content hash was made with
+    if let Some(kind) = TRANSFER_TYPES.iter().find(|kind| {
+        transfer_content_hash(kind, &transfer.from, &transfer.to, transfer.amount, transfer.nonce, transfer.fee) == transfer.hash
+    }) {
+        transfer.transaction_type = kind.to_string();
+    }
+    transfer
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_storage::BLOCK_DIFFICULTY;
+
+    fn template() -> BlockTemplate {
+        BlockTemplate::new(
+            format!("0x{}", "ab".repeat(32)),
+            42,
+            "fvcminer".to_string(),
+            BLOCK_DIFFICULTY,
+            &[],
+            1_700_000_000,
+        )
+    }
+
+    fn find_nonce(template: &BlockTemplate, valid: bool) -> u64 {
+        (0u64..).find(|nonce| template.solve(*nonce).is_ok() == valid).unwrap()
+    }
+
+    #[test]
+    fn test_correct_solution_is_accepted() {
+        let template = template();
+        let nonce = find_nonce(&template, true);
+
+        let block = template.solve(nonce).unwrap();
+        assert_eq!(block.height, 42);
+        assert_eq!(block.parent_hash, template.parent_hash);
+        assert_eq!(format!("0x{}", hex::encode(block.merkle_root())), template.merkle_root);
+        assert_eq!(block.transactions[0].to, "fvcminer");
+    }
+
+    #[test]
+    fn test_coinbase_follows_reward_schedule() {
+        let rewards = MiningRewardSystem::new();
+        let halving = rewards.next_halving(0).block_height;
+        let coinbase_at = |height: u64| {
+            BlockTemplate::new(format!("0x{}", "ab".repeat(32)), height, "fvcminer".to_string(), BLOCK_DIFFICULTY, &[], 1_700_000_000)
+                .transactions[0].amount
+        };
+
+        assert_eq!(coinbase_at(1), crate::consensus::mining_rewards::BASE_BLOCK_REWARD);
+        assert_eq!(coinbase_at(halving - 1), crate::consensus::mining_rewards::BASE_BLOCK_REWARD);
+        assert_eq!(coinbase_at(halving), crate::consensus::mining_rewards::BASE_BLOCK_REWARD / 2);
+    }
+
+    #[test]
+    fn test_invalid_nonce_is_rejected() {
+        let template = template();
+        let nonce = find_nonce(&template, false);
+
+        assert_eq!(template.solve(nonce).unwrap_err(), TemplateError::InvalidProofOfWork(BLOCK_DIFFICULTY));
+    }
+}
@@ -0,0 +1,109 @@
+//! Validated multiaddrs for node listen and bootstrap addresses
+
+use std::net::IpAddr;
+use libp2p::multiaddr::{Multiaddr, Protocol};
+use thiserror::Error;
+
+/// Address errors
+#[derive(Debug, Error, PartialEq)]
+pub enum AddressError {
+    #[error("Invalid multiaddr '{addr}': {reason}")]
+    Malformed { addr: String, reason: String },
+    #[error("Invalid IP address '{0}'")]
+    InvalidHost(String),
+    #[error("Unsupported address '{addr}': {reason} (expected /ip4|ip6/<addr>/tcp/<port>)")]
+    UnsupportedTransport { addr: String, reason: String },
+}
+
+/// TCP listen address for `host` (an IPv4 or IPv6 literal) and `port`
+pub fn build_listen_addr(host: &str, port: u16) -> Result<Multiaddr, AddressError> {
+    let ip: IpAddr = host.trim().parse().map_err(|_| AddressError::InvalidHost(host.to_string()))?;
+    Ok(Multiaddr::empty().with(ip.into()).with(Protocol::Tcp(port)))
+}
+
+/// Parse a listen address, accepting only transports the swarm is built with
+pub fn parse_listen_addr(addr: &str) -> Result<Multiaddr, AddressError> {
+    let parsed = parse(addr)?;
+    check_transport(&parsed, false)?;
+    Ok(parsed)
+}
+
+/// Parse a bootstrap address; a trailing /p2p/<peer id> is allowed and the port must be dialable
+pub fn parse_bootstrap_addr(addr: &str) -> Result<Multiaddr, AddressError> {
+    let parsed = parse(addr)?;
+    check_transport(&parsed, true)?;
+    if parsed.iter().any(|p| p == Protocol::Tcp(0)) {
+        return Err(AddressError::UnsupportedTransport {
+            addr: parsed.to_string(),
+            reason: "port 0 cannot be dialed".to_string(),
+        });
+    }
+    Ok(parsed)
+}
+
+fn parse(addr: &str) -> Result<Multiaddr, AddressError> {
+    addr.trim().parse().map_err(|e: libp2p::multiaddr::Error| AddressError::Malformed {
+        addr: addr.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+/// The swarm only runs TCP (noise + yamux): /ip4|ip6/<addr>/tcp/<port>[/p2p/<id>]
+pub fn check_transport(addr: &Multiaddr, allow_peer_id: bool) -> Result<(), AddressError> {
+    let unsupported = |reason: String| AddressError::UnsupportedTransport { addr: addr.to_string(), reason };
+    let mut protocols = addr.iter();
+
+    match protocols.next() {
+        Some(Protocol::Ip4(_)) | Some(Protocol::Ip6(_)) => {}
+        Some(other) => return Err(unsupported(format!("network protocol '{}' is not supported", other))),
+        None => return Err(unsupported("address is empty".to_string())),
+    }
+    match protocols.next() {
+        Some(Protocol::Tcp(_)) => {}
+        Some(other) => return Err(unsupported(format!("transport '{}' is not supported", other))),
+        None => return Err(unsupported("missing /tcp/<port>".to_string())),
+    }
+    match protocols.next() {
+        None => Ok(()),
+        Some(Protocol::P2p(_)) if allow_peer_id && protocols.next().is_none() => Ok(()),
+        Some(other) => Err(unsupported(format!("unexpected '{}' after the tcp port", other))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_tcp_addresses() {
+        assert_eq!(build_listen_addr("0.0.0.0", 30333).unwrap().to_string(), "/ip4/0.0.0.0/tcp/30333");
+        assert_eq!(build_listen_addr("::1", 30334).unwrap().to_string(), "/ip6/::1/tcp/30334");
+        assert!(parse_listen_addr("/ip4/127.0.0.1/tcp/0").is_ok());
+
+        let peer = libp2p::PeerId::random();
+        let bootstrap = format!("/ip4/10.0.0.2/tcp/30333/p2p/{}", peer);
+        assert_eq!(parse_bootstrap_addr(&bootstrap).unwrap().to_string(), bootstrap);
+    }
+
+    #[test]
+    fn test_unsupported_protocols_rejected() {
+        for addr in [
+            "/ip4/127.0.0.1/udp/30333/quic-v1",
+            "/dns4/example.com/tcp/30333",
+            "/ip4/127.0.0.1/tcp/30333/ws",
+            "/ip4/127.0.0.1",
+        ] {
+            assert!(
+                matches!(parse_bootstrap_addr(addr), Err(AddressError::UnsupportedTransport { .. })),
+                "{} should be rejected",
+                addr
+            );
+        }
+
+        let with_peer = format!("/ip4/127.0.0.1/tcp/30333/p2p/{}", libp2p::PeerId::random());
+        assert!(matches!(parse_listen_addr(&with_peer), Err(AddressError::UnsupportedTransport { .. })));
+        assert!(matches!(parse_bootstrap_addr("/ip4/127.0.0.1/tcp/0"), Err(AddressError::UnsupportedTransport { .. })));
+        assert!(matches!(parse_listen_addr("ip4/127.0.0.1"), Err(AddressError::Malformed { .. })));
+        assert_eq!(build_listen_addr("localhost", 1), Err(AddressError::InvalidHost("localhost".to_string())));
+    }
+}
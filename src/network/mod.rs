@@ -1,4 +1,6 @@
 //! Torus network topology implementation
 
 pub mod torus_topology;
-pub use torus_topology::{TorusNetwork, TorusCoordinate, NetworkStats, NetworkError, VortexRoutingTable};
\ No newline at end of file
+pub mod address;
+pub use torus_topology::{TorusNetwork, TorusCoordinate, NetworkStats, NetworkError, VortexRoutingTable};
+pub use address::{AddressError, build_listen_addr, parse_listen_addr, parse_bootstrap_addr};
@@ -1,6 +1,9 @@
 use std::collections::{HashMap, HashSet};
 use libp2p::PeerId;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
 use serde::{Serialize, Deserialize};
+use crate::math::{GOLDEN_RATIO, VORTEX_SEQUENCE};
 
 /// Toroidal network topology based on vortex mathematics
 pub struct TorusNetwork {
@@ -10,16 +13,62 @@ pub struct TorusNetwork {
     connection_radius: f64,
     /// Current network diameter
     diameter: u32,
+    /// Seeded placement for reproducible simulations; None places peers by identity
+    placement: Option<SeededPlacement>,
+}
+
+/// Peers placed from a seeded PRNG, in the order they are added
+struct SeededPlacement {
+    rng: StdRng,
+    placed: u64,
+}
+
+impl SeededPlacement {
+    fn new(seed: u64) -> Self {
+        // Mix the seed with the vortex sequence digits so nearby seeds start far apart
+        let vortex_digits = VORTEX_SEQUENCE.iter().fold(0u64, |acc, &digit| acc * 10 + digit as u64);
+        let mixed = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) ^ vortex_digits;
+        Self { rng: StdRng::seed_from_u64(mixed), placed: 0 }
+    }
+
+    /// Position of the k-th placed peer, from three uniform draws u1, u2, u3 in [0, 1):
+    ///   phi    = 2π · frac(k / φ + u1)   (golden-ratio spiral, jittered)
+    ///   theta  = 2π · u2
+    ///   radius = 1 + 0.5 · u3
+    fn next(&mut self) -> TorusCoordinate {
+        let two_pi = 2.0 * std::f64::consts::PI;
+        let (u1, u2, u3): (f64, f64, f64) = (self.rng.gen(), self.rng.gen(), self.rng.gen());
+        let spiral = (self.placed as f64 / GOLDEN_RATIO + u1).fract();
+        self.placed += 1;
+        TorusCoordinate {
+            phi: two_pi * spiral,
+            theta: two_pi * u2,
+            radius: 1.0 + 0.5 * u3,
+        }
+    }
 }
 
 /// 3D coordinate on torus surface
-#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub struct TorusCoordinate {
     pub phi: f64,    // Toroidal angle (0 to 2π)
     pub theta: f64,  // Poloidal angle (0 to 2π)
     pub radius: f64, // Distance from center
 }
 
+impl TorusCoordinate {
+    /// Angular distance on the torus surface, wrapping phi and theta at 2π
+    pub fn toroidal_distance(&self, other: &TorusCoordinate) -> f64 {
+        let wrap = |delta: f64| {
+            let delta = delta.abs() % (2.0 * std::f64::consts::PI);
+            delta.min(2.0 * std::f64::consts::PI - delta)
+        };
+        let delta_phi = wrap(self.phi - other.phi);
+        let delta_theta = wrap(self.theta - other.theta);
+        (delta_phi * delta_phi + delta_theta * delta_theta).sqrt()
+    }
+}
+
 /// Vortex-based routing table
 #[derive(Debug, Clone)]
 pub struct VortexRoutingTable {
@@ -29,6 +78,10 @@ pub struct VortexRoutingTable {
     fractal_connections: HashMap<PeerId, Vec<PeerId>>,
     /// Energy field values for routing decisions
     energy_map: HashMap<PeerId, f64>,
+    /// Torus position of every peer in the table
+    positions: HashMap<PeerId, TorusCoordinate>,
+    /// Peer `next_hop` routes from
+    local_peer: Option<PeerId>,
 }
 
 impl TorusNetwork {
@@ -37,17 +90,39 @@ impl TorusNetwork {
             node_positions: HashMap::new(),
             connection_radius,
             diameter: 0,
+            placement: None,
         }
     }
 
-    /// Add node to torus with real network positioning
+    /// Network whose peers are placed by a PRNG seeded from `seed` instead of by their identity
+    /// and address, so a simulation adding peers in the same order always gets the same layout.
+    /// See `SeededPlacement::next` for the placement formula.
+    pub fn with_seed(connection_radius: f64, seed: u64) -> Self {
+        Self { placement: Some(SeededPlacement::new(seed)), ..Self::new(connection_radius) }
+    }
+
+    /// Add node to torus with real network positioning, or the next seeded position
     pub fn add_node(&mut self, peer_id: PeerId, real_address: Option<&str>) -> TorusCoordinate {
-        let coordinate = self.calculate_real_position(peer_id, real_address);
+        let coordinate = match self.placement.as_mut() {
+            Some(placement) => placement.next(),
+            None => self.calculate_real_position(peer_id, real_address),
+        };
         self.node_positions.insert(peer_id, coordinate);
         self.update_network_diameter();
         coordinate
     }
 
+    /// Drop a node that has left the network
+    pub fn remove_node(&mut self, peer_id: &PeerId) {
+        self.node_positions.remove(peer_id);
+        self.update_network_diameter();
+    }
+
+    /// Position of a node on the torus, if known
+    pub fn position(&self, peer_id: &PeerId) -> Option<TorusCoordinate> {
+        self.node_positions.get(peer_id).copied()
+    }
+
     /// Calculate position based on real network data instead of seed
     fn calculate_real_position(&self, peer_id: PeerId, real_address: Option<&str>) -> TorusCoordinate {
         // Use peer_id hash for deterministic but real positioning
@@ -173,6 +248,8 @@ impl TorusNetwork {
             vortex_ring,
             fractal_connections,
             energy_map,
+            positions: self.node_positions.clone(),
+            local_peer: None,
         }
     }
 
@@ -232,6 +309,30 @@ impl TorusNetwork {
 }
 
 impl VortexRoutingTable {
+    /// Route from `peer` when calling `next_hop`
+    pub fn with_local_peer(mut self, peer: PeerId) -> Self {
+        self.local_peer = Some(peer);
+        self
+    }
+
+    /// Linked peer closest to `target` by toroidal distance, or None when no link
+    /// gets closer than the local peer already is
+    pub fn next_hop(&self, target: TorusCoordinate) -> Option<PeerId> {
+        self.next_hop_from(self.local_peer.as_ref()?, target)
+    }
+
+    /// Greedy torus routing step from `from` toward `target`
+    pub fn next_hop_from(&self, from: &PeerId, target: TorusCoordinate) -> Option<PeerId> {
+        let current = self.positions.get(from)?.toroidal_distance(&target);
+        self.vortex_ring.get(from).into_iter().flatten()
+            .chain(self.fractal_connections.get(from).into_iter().flatten())
+            .filter(|peer| *peer != from)
+            .filter_map(|peer| Some((*peer, self.positions.get(peer)?.toroidal_distance(&target))))
+            .filter(|(_, distance)| *distance < current)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(peer, _)| peer)
+    }
+
     /// Find optimal path using vortex energy routing
     pub fn find_vortex_path(&self, from: &PeerId, to: &PeerId) -> Option<Vec<PeerId>> {
         use std::collections::VecDeque;
@@ -339,4 +440,90 @@ impl TorusNetwork {
             fractal_dimension: 1.585, // Sierpinski triangle
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f64::consts::PI;
+
+    const SIDE: usize = 4;
+
+    /// 4x4 torus grid where each node links to its four wrapping neighbours
+    fn grid_table() -> (Vec<PeerId>, VortexRoutingTable) {
+        let peers: Vec<PeerId> = (0..SIDE * SIDE).map(|_| PeerId::random()).collect();
+        let step = 2.0 * PI / SIDE as f64;
+        let mut positions = HashMap::new();
+        let mut vortex_ring = HashMap::new();
+
+        for row in 0..SIDE {
+            for col in 0..SIDE {
+                let peer = peers[row * SIDE + col];
+                positions.insert(peer, TorusCoordinate { phi: col as f64 * step, theta: row as f64 * step, radius: 1.0 });
+                let neighbours = vec![
+                    peers[row * SIDE + (col + 1) % SIDE],
+                    peers[row * SIDE + (col + SIDE - 1) % SIDE],
+                    peers[((row + 1) % SIDE) * SIDE + col],
+                    peers[((row + SIDE - 1) % SIDE) * SIDE + col],
+                ];
+                vortex_ring.insert(peer, neighbours);
+            }
+        }
+
+        let table = VortexRoutingTable {
+            vortex_ring,
+            fractal_connections: HashMap::new(),
+            energy_map: HashMap::new(),
+            positions,
+            local_peer: None,
+        };
+        (peers, table)
+    }
+
+    #[test]
+    fn test_toroidal_distance_wraps() {
+        let a = TorusCoordinate { phi: 0.1, theta: 0.0, radius: 1.0 };
+        let b = TorusCoordinate { phi: 2.0 * PI - 0.1, theta: 0.0, radius: 1.0 };
+        assert!((a.toroidal_distance(&b) - 0.2).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_same_seed_gives_identical_placement() {
+        let layout = |seed: u64| {
+            let mut network = TorusNetwork::with_seed(1.0, seed);
+            (0..32).map(|_| network.add_node(PeerId::random(), None)).collect::<Vec<_>>()
+        };
+
+        let placed = layout(369);
+        assert_eq!(placed, layout(369));
+        assert_ne!(placed, layout(370));
+        for coordinate in &placed {
+            assert!((0.0..2.0 * PI).contains(&coordinate.phi));
+            assert!((0.0..2.0 * PI).contains(&coordinate.theta));
+            assert!((1.0..1.5).contains(&coordinate.radius));
+        }
+    }
+
+    #[test]
+    fn test_next_hop_converges_in_log_n_hops() {
+        let (peers, table) = grid_table();
+        let max_hops = (peers.len() as f64).log2().ceil() as usize;
+
+        for from in &peers {
+            for to in &peers {
+                let target = table.positions[to];
+                let table = table.clone().with_local_peer(*from);
+                let mut current = *from;
+                let mut hops = 0;
+                while current != *to {
+                    current = table.next_hop_from(&current, target).expect("greedy routing stalled");
+                    hops += 1;
+                    assert!(hops <= max_hops, "route exceeded {} hops", max_hops);
+                }
+                if from == to {
+                    assert_eq!(table.next_hop(target), None);
+                }
+            }
+        }
+    }
+}
@@ -1,7 +1,9 @@
-use crate::crypto::fractal_hash::FractalPoW;
+use crate::crypto::fractal_hash::{BlockHash, FractalPoW};
 use crate::wallet::wallet::Wallet;
 use crate::consensus::vortex_consensus::{VortexConsensus, VortexBlock, Transaction};
-use crate::rpc_storage::{WalletTransaction, RPCStorage};
+use crate::consensus::MiningRewardSystem;
+use crate::rpc_storage::{Block, WalletTransaction, RPCStorage};
+use crate::mining::BlockRng;
 use crate::shared;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
@@ -9,7 +11,54 @@ use std::time::Duration;
 use tokio::sync::{Mutex, RwLock};
 use crate::node::fractal_node::{NodeState, NodeError};
 use chrono::Utc;
-use rand::random;
+
+/// The reward paid in an ecosystem block
+#[derive(Debug, Clone)]
+pub struct RewardPayout {
+    pub address: String,
+    pub amount: u64,
+    /// Mining device credited, or None for the ecosystem wallet fallback
+    pub device_id: Option<String>,
+}
+
+/// Pay the scheduled reward at `height` to one of `miners` (device id, address), taking turns by
+/// height in device id order, or to `ecosystem_address` when no miner is active. A block carries a
+/// single coinbase, so the reward is not split.
+pub fn ecosystem_payout(height: u64, mut miners: Vec<(String, String)>, ecosystem_address: &str) -> RewardPayout {
+    let block_reward = MiningRewardSystem::new().reward_at_height(height);
+    if miners.is_empty() {
+        return RewardPayout { address: ecosystem_address.to_string(), amount: block_reward, device_id: None };
+    }
+    miners.sort();
+    let (device_id, address) = miners.swap_remove((height % miners.len() as u64) as usize);
+    RewardPayout { address, amount: block_reward, device_id: Some(device_id) }
+}
+
+/// Build and mine the block for one ecosystem round.
+/// The nonce search start comes only from `rng`.
+pub fn assemble_ecosystem_block(
+    height: u64,
+    miner: &str,
+    parent_hash: String,
+    timestamp: u64,
+    payout: &RewardPayout,
+    rng: &mut BlockRng,
+) -> (Block, BlockHash) {
+    let mut block = Block::new_with_timestamp(height, miner.to_string(), parent_hash, timestamp);
+    block.difficulty = crate::rpc_storage::BLOCK_DIFFICULTY as u64;
+
+    let mut reward_tx = WalletTransaction::coinbase(payout.address.clone(), payout.amount, height);
+    // Override timestamp to ensure consistency with block
+    reward_tx.timestamp = timestamp;
+    block.add_transaction(reward_tx);
+
+    // Proof-of-Work over the assembled header so parent and transactions are committed
+    let pow = FractalPoW::new(crate::rpc_storage::BLOCK_DIFFICULTY, crate::rpc_storage::BLOCK_FRACTAL_LEVELS);
+    let (nonce_bytes, block_hash) = pow.mine_from(&block.header_bytes(), rng.start_nonce());
+    block.nonce = u64::from_le_bytes(nonce_bytes[..8].try_into().unwrap());
+    block.hash = format!("0x{}", hex::encode(block_hash.hash));
+    (block, block_hash)
+}
 
 pub struct EcosystemMiner {
     wallet: Arc<Mutex<Wallet>>,
@@ -45,48 +94,35 @@ impl EcosystemMiner {
     }
 
     pub async fn start(&self) -> Result<(), NodeError> {
-        if self.is_mining.load(Ordering::SeqCst) {
+        // Check and set in one step so concurrent starts spawn a single mining loop
+        if self.is_mining.compare_exchange(false, true, Ordering::SeqCst, Ordering::SeqCst).is_err() {
             return Ok(());
         }
 
-        self.is_mining.store(true, Ordering::SeqCst);
         let wallet = Arc::clone(&self.wallet);
         let is_mining = Arc::clone(&self.is_mining);
         let address = self.address.clone();
         let consensus = self.consensus.clone();
         let state = self.state.clone();
-        // Removed ledger dependency - using in-memory storage only
+        // Blocks and payouts are persisted through RPCStorage::store_mined_block
 
         tokio::spawn(async move {
             println!("🌐 Ecosystem miner started for address: {}", address);
+            let mut rng = BlockRng::secure();
             
             while is_mining.load(Ordering::SeqCst) {
                 let mut wallet = wallet.lock().await;
                 
-                // Proof-of-Work mining using FractalPoW
-                // Reward defined in wei (18 decimals)
-                let block_reward: u64 = 6_250_000; // 6.25 FVC per block (6 decimals)
-                
-                // Build block template data (timestamp + address) for hashing
                 let timestamp = Utc::now().timestamp() as u64;
-                let data_for_hash = [address.as_bytes(), &timestamp.to_le_bytes()].concat();
-                
-                // Initialize PoW with fixed difficulty 2 and fractal levels 3
-                let pow = FractalPoW::new(2, 3);
-                let (nonce_bytes, block_hash) = pow.mine(&data_for_hash);
-                let nonce = u64::from_le_bytes(nonce_bytes[..8].try_into().unwrap());
-                
-                // Update wallet balance upon successful mining
-                wallet.balance = wallet.balance.saturating_add(block_reward);
-
-                // Balance is now stored in-memory only (no persistent storage)
                 
                 // Generate new block for the ecosystem transactions
-                let new_block_height = crate::rpc_storage::RPCStorage::increment_block_height().await.unwrap_or(1);
+                // Pending height; the stored tip only advances once store_mined_block succeeds
+                let new_block_height = crate::rpc_storage::RPCStorage::next_block_height().await.unwrap_or(1);
+                // Scheduled reward in microFVC (6 decimals)
+                let block_reward = MiningRewardSystem::new().reward_at_height(new_block_height);
                 
-                // Create a single timestamp for all transactions and block to ensure consistency
-                let block_timestamp = chrono::Utc::now().timestamp() as u64;
-                let mut block_transactions = Vec::new();
+                // Reuse the hashed timestamp for all transactions and the block so the PoW hash can be recomputed
+                let block_timestamp = timestamp;
                 
                 // Create a mining reward transaction for consensus with consistent timestamp
                 let reward_tx = Transaction {
@@ -99,98 +135,46 @@ impl EcosystemMiner {
                     vortex_fee: 0.0,
                 };
                 
-                // Get all active mining devices and distribute rewards
+                // Get all active mining devices; one of them takes this block's reward
                 let active_devices = RPCStorage::get_all_active_devices().await.unwrap_or_default();
                 println!("🔍 Active devices for mining rewards: {:?}", active_devices);
                 
-                if !active_devices.is_empty() {
-                    // Distribute mining reward among active miners
-                    let reward_per_miner = block_reward / active_devices.len() as u64;
-                    
-                    for device_id in active_devices {
-                        // Get miner's wallet address
-                        if let Ok(Some(miner_address)) = RPCStorage::get_device_address(&device_id).await {
-                            // Create mining reward transaction for this miner with consistent timestamp
-                            let tx_hash = format!("0xmining_{}_{}_{}_{:x}", new_block_height, block_timestamp, device_id.chars().take(8).collect::<String>(), random::<u32>());
-                            let mut miner_reward_tx = WalletTransaction::new_mining_reward(
-                                miner_address.clone(),
-                                reward_per_miner,
-                                tx_hash.clone(),
-                                new_block_height
-                            );
-                            // Override timestamp to ensure consistency with block
-                            miner_reward_tx.timestamp = block_timestamp;
-                            block_transactions.push(miner_reward_tx.clone());
-                            
-                            // Store transaction and update balance
-                            println!("🔄 Attempting to store mining reward transaction: {} to {}", tx_hash, miner_address);
-                            if let Err(e) = RPCStorage::add_transaction(&miner_reward_tx).await {
-                                println!("❌ Error storing mining reward transaction: {}", e);
-                            } else {
-                                println!("✅ Successfully stored mining reward transaction: {}", tx_hash);
-                            }
-                            
-                            // Update miner's balance
-                            let current_balance = RPCStorage::get_balance(&miner_address).await.unwrap_or(0);
-                            let new_balance = current_balance.saturating_add(reward_per_miner);
-                            let _ = RPCStorage::set_balance(&miner_address, new_balance).await;
-                            
-                            println!("💰 Mining reward {} FVC sent to miner: {}", reward_per_miner as f64 / 1_000_000.0, miner_address);
-                        }
+                let mut miners = Vec::new();
+                for device_id in active_devices {
+                    if let Ok(Some(miner_address)) = RPCStorage::get_device_address(&device_id).await {
+                        miners.push((device_id, miner_address));
                     }
-                } else {
-                    // Fallback: give reward to ecosystem if no active miners
-                    let tx_hash = format!("eco{:x}", random::<u64>());
-                    let mut wallet_reward_tx = WalletTransaction::new_mining_reward(
-                        address.clone(),
-                        block_reward,
-                        tx_hash.clone(),
-                        new_block_height
-                    );
-                    // Override timestamp to ensure consistency with block
-                    wallet_reward_tx.timestamp = block_timestamp;
-                    block_transactions.push(wallet_reward_tx.clone());
-                    
-                    // Store transaction in persistent storage for dashboard visibility
-                    let _ = RPCStorage::add_transaction(&wallet_reward_tx).await;
                 }
+                let payout = ecosystem_payout(new_block_height, miners, &address);
                 
                 // Create real blockchain block and store it with actual FractalPoW hash
-                let parent_hash = if new_block_height > 0 {
-                    format!("{:064x}", new_block_height - 1 + 12345)
-                } else {
-                    "0000000000000000000000000000000000000000000000000000000000000000".to_string()
+                let parent_hash = match RPCStorage::find_parent_block(new_block_height).await {
+                    Ok(Some(parent)) => parent.hash,
+                    _ => "0000000000000000000000000000000000000000000000000000000000000000".to_string(),
                 };
-                
-                // Use the real hash from FractalPoW mining with consistent timestamp
-                let mut real_block = crate::rpc_storage::Block::new_with_real_hash_and_timestamp(
+                let (real_block, block_hash) = assemble_ecosystem_block(
                     new_block_height,
-                    address.clone(),
+                    &address,
                     parent_hash,
-                    block_hash.hash, // Real hash from FractalPoW
-                    nonce,
-                    2, // difficulty
-                    block_timestamp // Use consistent timestamp
+                    block_timestamp,
+                    &payout,
+                    &mut rng,
                 );
+                let nonce = real_block.nonce;
                 
-                // Add all transactions to the block
-                for tx in block_transactions {
-                    real_block.add_transaction(tx);
-                }
-                
-                // Transactions are now properly added to the block with consistent timestamps
-                
-                // Store the real block in blockchain storage
-                if let Err(e) = crate::rpc_storage::RPCStorage::store_block(&real_block).await {
+                // The block, its coinbase and the payout credit (fallback included) are stored in
+                // one atomic write
+                if let Err(e) = RPCStorage::store_mined_block(&real_block).await {
                     println!("❌ Error storing real block: {}", e);
-                } else {
-                    println!("✅ Real block #{} stored in blockchain", new_block_height);
-                    
-                    // Update block height after successful storage
-                    if let Err(e) = crate::rpc_storage::RPCStorage::increment_block_height().await {
-                        println!("❌ Error updating block height: {}", e);
-                    }
+                    drop(wallet);
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    continue;
                 }
+                println!("✅ Real block #{} stored in blockchain", new_block_height);
+                if payout.device_id.is_some() {
+                    println!("💰 Mining reward {} FVC sent to miner: {}", payout.amount as f64 / 1_000_000.0, payout.address);
+                }
+                wallet.balance = RPCStorage::get_balance(&address).await.unwrap_or(wallet.balance);
                 
                 // Create a new block using PoW results with consistent timestamp
                  let new_block = VortexBlock {
@@ -242,7 +226,7 @@ impl EcosystemMiner {
     }
 
     pub async fn get_balance(&self) -> u64 {
-        // Use wallet's in-memory balance only
+        // Mirrors the stored ecosystem balance as of the last mined round
         match self.wallet.try_lock() {
             Ok(wallet) => wallet.balance,
             Err(_) => 0, // Default if we can't access the wallet
@@ -254,4 +238,51 @@ impl EcosystemMiner {
     }
 }
 
-// Default implementation removed as new() now requires parameters
\ No newline at end of file
+// Default implementation removed as new() now requires parameters
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn seeded_block(seed: u64) -> Vec<u8> {
+        let payout = RewardPayout { address: "fvcminer1".to_string(), amount: 6_250_000, device_id: Some("device-one".to_string()) };
+        let (block, _) = assemble_ecosystem_block(
+            7,
+            "fvcecosystem",
+            format!("0x{}", "cd".repeat(32)),
+            1_700_000_000,
+            &payout,
+            &mut BlockRng::seeded(seed),
+        );
+        assert!(block.has_valid_pow());
+        assert!(block.verify_coinbase().is_ok());
+        serde_json::to_vec(&block).unwrap()
+    }
+
+    #[test]
+    fn test_payouts_follow_reward_schedule() {
+        let base = crate::consensus::mining_rewards::BASE_BLOCK_REWARD;
+        let halving = MiningRewardSystem::new().next_halving(0).block_height;
+        let miners = || vec![
+            ("device-one".to_string(), "fvcminer1".to_string()),
+            ("device-two".to_string(), "fvcminer2".to_string()),
+        ];
+
+        // The whole reward goes to one miner, taking turns by height
+        let before = ecosystem_payout(halving - 1, miners(), "fvcecosystem");
+        assert_eq!((before.address.as_str(), before.amount), ("fvcminer2", base));
+        let after = ecosystem_payout(halving, miners(), "fvcecosystem");
+        assert_eq!((after.address.as_str(), after.amount), ("fvcminer1", base / 2));
+        assert_eq!(after.device_id.as_deref(), Some("device-one"));
+
+        let fallback = ecosystem_payout(halving, Vec::new(), "fvcecosystem");
+        assert_eq!(fallback.address, "fvcecosystem");
+        assert_eq!(fallback.amount, base / 2);
+        assert!(fallback.device_id.is_none());
+    }
+
+    #[test]
+    fn test_fixed_seed_gives_identical_block() {
+        assert_eq!(seeded_block(42), seeded_block(42));
+        assert_ne!(seeded_block(42), seeded_block(43));
+    }
+}
@@ -0,0 +1,348 @@
+use std::collections::HashMap;
+use crate::rpc_storage::{select_heavier_tip, Block, WalletTransaction, MAX_REORG_DEPTH};
+
+/// Blocks waiting on an unknown parent; further orphans are dropped
+pub const MAX_ORPHAN_BLOCKS: usize = 256;
+
+/// Why a block could not be added to the block tree
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ForkChoiceError {
+    #[error("Block {height} is not above its parent at height {parent_height}")]
+    HeightNotAboveParent { height: u64, parent_height: u64 },
+    #[error("Block {height} cumulative difficulty {claimed} does not match expected {expected}")]
+    CumulativeDifficultyMismatch { height: u64, claimed: u64, expected: u64 },
+    #[error("Orphan pool full ({0} blocks)")]
+    TooManyOrphans(usize),
+    #[error("Block {height} is at or below the finalized root at height {root_height}")]
+    BelowFinality { height: u64, root_height: u64 },
+}
+
+/// A change of canonical tip: `reverted` blocks (tip first) were rolled back to the block at
+/// `fork_height` and `applied` blocks (oldest first) connected on top of it
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChainUpdate {
+    pub fork_height: u64,
+    pub reverted: Vec<Block>,
+    pub applied: Vec<Block>,
+}
+
+impl ChainUpdate {
+    pub fn is_reorg(&self) -> bool {
+        !self.reverted.is_empty()
+    }
+
+    /// Transfers from reverted blocks that the new chain does not include again
+    pub fn dropped_transactions(&self) -> Vec<&WalletTransaction> {
+        let applied: std::collections::HashSet<&str> = self.applied.iter()
+            .flat_map(|block| block.transactions.iter().map(|tx| tx.hash.as_str()))
+            .collect();
+        self.reverted.iter()
+            .flat_map(|block| block.transactions.iter())
+            .filter(|tx| tx.transaction_type != "mining_reward" && tx.transaction_type != "genesis")
+            .filter(|tx| !applied.contains(tx.hash.as_str()))
+            .collect()
+    }
+}
+
+/// What accepting a block did
+#[derive(Debug, Clone, PartialEq)]
+pub enum BlockOutcome {
+    /// Already known
+    Duplicate,
+    /// Parent unknown; held until it arrives
+    Orphaned,
+    /// Kept on a branch that is not heavier than the canonical chain
+    SideChain,
+    /// The canonical tip moved
+    TipChanged(ChainUpdate),
+}
+
+/// Block tree rooted at a trusted block. The canonical chain is the branch with the most
+/// cumulative difficulty (see `select_heavier_tip`); lighter branches and orphans are kept so a
+/// heavier chain arriving later can replace the canonical one, at most MAX_REORG_DEPTH blocks deep.
+/// Once the tip moves, the root advances to the deepest block that can still be reorganized out
+/// and everything not descending from it is pruned.
+#[derive(Debug, Clone)]
+pub struct ForkChoice {
+    blocks: HashMap<String, Block>,
+    /// Orphans by the parent hash they are waiting on
+    orphans: HashMap<String, Vec<Block>>,
+    /// Canonical hashes from the root to the tip
+    canonical: Vec<String>,
+    canonical_index: HashMap<String, usize>,
+    /// Net balance change of the canonical chain since the root
+    balance_changes: HashMap<String, i128>,
+}
+
+/// Balance effect of each transaction in `block`, in block order
+fn block_deltas(block: &Block) -> impl Iterator<Item = (&str, i128)> {
+    block.transactions.iter().flat_map(|tx| {
+        let amount = tx.amount as i128;
+        match tx.transaction_type.as_str() {
+            "genesis" => vec![],
+            "mining_reward" => vec![(tx.to.as_str(), amount)],
+            _ => vec![(tx.from.as_str(), -amount), (tx.to.as_str(), amount)],
+        }
+    })
+}
+
+impl ForkChoice {
+    pub fn new(root: Block) -> Self {
+        let hash = root.hash.clone();
+        Self {
+            blocks: HashMap::from([(hash.clone(), root)]),
+            orphans: HashMap::new(),
+            canonical: vec![hash.clone()],
+            canonical_index: HashMap::from([(hash, 0)]),
+            balance_changes: HashMap::new(),
+        }
+    }
+
+    pub fn tip(&self) -> &Block {
+        &self.blocks[self.canonical.last().expect("canonical chain holds the root")]
+    }
+
+    /// Canonical block at `height`, if the chain has one there
+    pub fn canonical_block(&self, height: u64) -> Option<&Block> {
+        self.canonical.iter().map(|hash| &self.blocks[hash]).find(|block| block.height == height)
+    }
+
+    pub fn root(&self) -> &Block {
+        &self.blocks[&self.canonical[0]]
+    }
+
+    /// Blocks held in the tree, root included, not counting orphans
+    pub fn block_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Net change to `address` from the canonical blocks above the first root
+    pub fn balance_change(&self, address: &str) -> i128 {
+        self.balance_changes.get(address).copied().unwrap_or(0)
+    }
+
+    pub fn orphan_count(&self) -> usize {
+        self.orphans.values().map(Vec::len).sum()
+    }
+
+    /// Add `block` to the tree, switching the canonical chain if it (or orphans it connects) is heavier
+    pub fn insert(&mut self, block: Block) -> Result<BlockOutcome, ForkChoiceError> {
+        let known = self.blocks.contains_key(&block.hash)
+            || self.orphans.values().flatten().any(|orphan| orphan.hash == block.hash);
+        if known {
+            return Ok(BlockOutcome::Duplicate);
+        }
+        let root_height = self.root().height;
+        if block.height <= root_height {
+            return Err(ForkChoiceError::BelowFinality { height: block.height, root_height });
+        }
+        if !self.blocks.contains_key(&block.parent_hash) {
+            let orphan_count = self.orphan_count();
+            if orphan_count >= MAX_ORPHAN_BLOCKS {
+                return Err(ForkChoiceError::TooManyOrphans(orphan_count));
+            }
+            self.orphans.entry(block.parent_hash.clone()).or_default().push(block);
+            return Ok(BlockOutcome::Orphaned);
+        }
+
+        // Connect the block and every orphan now reachable through it, tracking the heaviest
+        let mut heaviest = self.connect(block)?;
+        let mut pending = vec![heaviest.clone()];
+        while let Some(parent_hash) = pending.pop() {
+            for orphan in self.orphans.remove(&parent_hash).unwrap_or_default() {
+                // An invalid orphan only loses its own branch
+                if let Ok(hash) = self.connect(orphan) {
+                    if select_heavier_tip(&self.blocks[&hash], &self.blocks[&heaviest]).hash == hash {
+                        heaviest = hash.clone();
+                    }
+                    pending.push(hash);
+                }
+            }
+        }
+
+        if select_heavier_tip(&self.blocks[&heaviest], self.tip()).hash != heaviest {
+            return Ok(BlockOutcome::SideChain);
+        }
+        Ok(self.switch_to(&heaviest).map_or(BlockOutcome::SideChain, BlockOutcome::TipChanged))
+    }
+
+    /// Link `block` to its known parent and store it, returning its hash
+    fn connect(&mut self, mut block: Block) -> Result<String, ForkChoiceError> {
+        let parent = &self.blocks[&block.parent_hash];
+        if block.height <= parent.height {
+            return Err(ForkChoiceError::HeightNotAboveParent { height: block.height, parent_height: parent.height });
+        }
+        let expected = block.expected_cumulative_difficulty(Some(parent));
+        if block.cumulative_difficulty != 0 && block.cumulative_difficulty != expected {
+            return Err(ForkChoiceError::CumulativeDifficultyMismatch {
+                height: block.height,
+                claimed: block.cumulative_difficulty,
+                expected,
+            });
+        }
+        block.cumulative_difficulty = expected;
+        let hash = block.hash.clone();
+        self.blocks.insert(hash.clone(), block);
+        Ok(hash)
+    }
+
+    /// Make `tip` canonical; None when the fork point is deeper than MAX_REORG_DEPTH
+    fn switch_to(&mut self, tip: &str) -> Option<ChainUpdate> {
+        let mut branch = Vec::new();
+        let mut cursor = tip.to_string();
+        let fork_index = loop {
+            if let Some(&index) = self.canonical_index.get(&cursor) {
+                break index;
+            }
+            let block = &self.blocks[&cursor];
+            branch.push(cursor.clone());
+            cursor = block.parent_hash.clone();
+        };
+
+        let fork_height = self.blocks[&self.canonical[fork_index]].height;
+        if self.tip().height.saturating_sub(fork_height) > MAX_REORG_DEPTH {
+            log::warn!("Ignoring heavier branch forking at height {}, below the reorg limit", fork_height);
+            return None;
+        }
+
+        let reverted_hashes = self.canonical.split_off(fork_index + 1);
+        let mut reverted = Vec::with_capacity(reverted_hashes.len());
+        for hash in reverted_hashes.iter().rev() {
+            self.canonical_index.remove(hash);
+            let block = self.blocks[hash].clone();
+            for (address, delta) in block_deltas(&block) {
+                *self.balance_changes.entry(address.to_string()).or_insert(0) -= delta;
+            }
+            reverted.push(block);
+        }
+
+        let mut applied = Vec::with_capacity(branch.len());
+        for hash in branch.into_iter().rev() {
+            self.canonical_index.insert(hash.clone(), self.canonical.len());
+            self.canonical.push(hash.clone());
+            let block = self.blocks[&hash].clone();
+            for (address, delta) in block_deltas(&block) {
+                *self.balance_changes.entry(address.to_string()).or_insert(0) += delta;
+            }
+            applied.push(block);
+        }
+
+        self.prune();
+        Some(ChainUpdate { fork_height, reverted, applied })
+    }
+
+    /// Advance the root to the canonical block MAX_REORG_DEPTH below the tip and drop every block
+    /// and orphan that does not descend from it; no reorg can reach them any more
+    fn prune(&mut self) {
+        let Some(finalized) = self.tip().height.checked_sub(MAX_REORG_DEPTH) else {
+            return;
+        };
+        let Some(root_index) = self.canonical.iter().rposition(|hash| self.blocks[hash].height <= finalized) else {
+            return;
+        };
+        if root_index == 0 {
+            return;
+        }
+
+        let root_height = self.blocks[&self.canonical[root_index]].height;
+        let mut descendants: Vec<&Block> = self.blocks.values().filter(|block| block.height > root_height).collect();
+        // Parents sit strictly below their children, so one pass in height order finds every descendant
+        descendants.sort_by_key(|block| block.height);
+        let mut keep = std::collections::HashSet::from([self.canonical[root_index].clone()]);
+        for block in descendants {
+            if keep.contains(&block.parent_hash) {
+                keep.insert(block.hash.clone());
+            }
+        }
+
+        self.blocks.retain(|hash, _| keep.contains(hash));
+        self.orphans.retain(|_, waiting| {
+            waiting.retain(|orphan| orphan.height > root_height);
+            !waiting.is_empty()
+        });
+        self.canonical.drain(..root_index);
+        self.canonical_index = self.canonical.iter().enumerate().map(|(index, hash)| (hash.clone(), index)).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn block(height: u64, parent: &Block, difficulty: u64, tag: &str) -> Block {
+        let mut block = Block::new_with_timestamp(height, "fvcminer".to_string(), parent.hash.clone(), 1_700_000_000 + height);
+        block.difficulty = difficulty;
+        block.hash = format!("{}-{}", tag, height);
+        block
+    }
+
+    #[test]
+    fn test_orphans_connect_when_parent_arrives() {
+        let root = block(0, &Block::new_with_timestamp(0, String::new(), String::new(), 0), 1, "root");
+        let mut fork_choice = ForkChoice::new(root.clone());
+
+        let first = block(1, &root, 1, "a");
+        let second = block(2, &first, 1, "a");
+        assert_eq!(fork_choice.insert(second.clone()).unwrap(), BlockOutcome::Orphaned);
+        assert_eq!(fork_choice.insert(second.clone()).unwrap(), BlockOutcome::Duplicate);
+        assert_eq!(fork_choice.orphan_count(), 1);
+
+        let BlockOutcome::TipChanged(update) = fork_choice.insert(first).unwrap() else {
+            panic!("parent should connect the orphan");
+        };
+        assert!(!update.is_reorg());
+        assert_eq!(update.applied.iter().map(|b| b.height).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(fork_choice.tip().hash, second.hash);
+        assert_eq!(fork_choice.tip().cumulative_difficulty, 2);
+        assert_eq!(fork_choice.orphan_count(), 0);
+
+        // An equally heavy competitor does not displace the tip unless its hash sorts lower
+        let rival = block(2, &fork_choice.blocks["a-1"].clone(), 1, "z");
+        assert_eq!(fork_choice.insert(rival).unwrap(), BlockOutcome::SideChain);
+    }
+
+    #[test]
+    fn test_reorg_beyond_limit_is_ignored() {
+        let root = block(0, &Block::new_with_timestamp(0, String::new(), String::new(), 0), 1, "root");
+        let mut fork_choice = ForkChoice::new(root.clone());
+        let mut parent = root.clone();
+        for height in 1..=MAX_REORG_DEPTH + 2 {
+            parent = block(height, &parent, 1, "main");
+            fork_choice.insert(parent.clone()).unwrap();
+        }
+
+        // Far heavier, but it forks off the original root, which has since been finalized
+        let deep = block(1, &root, 100, "deep");
+        assert_eq!(
+            fork_choice.insert(deep).unwrap_err(),
+            ForkChoiceError::BelowFinality { height: 1, root_height: 2 }
+        );
+        assert_eq!(fork_choice.tip().hash, parent.hash);
+    }
+
+    #[test]
+    fn test_blocks_below_finality_are_pruned() {
+        let root = block(0, &Block::new_with_timestamp(0, String::new(), String::new(), 0), 1, "root");
+        let mut fork_choice = ForkChoice::new(root.clone());
+        let first = block(1, &root, 1, "main");
+        let mut parent = block(2, &first, 1, "main");
+        fork_choice.insert(first.clone()).unwrap();
+        fork_choice.insert(parent.clone()).unwrap();
+        // A competing branch off the first block, and an orphan that never connects
+        assert_eq!(fork_choice.insert(block(2, &first, 1, "side")).unwrap(), BlockOutcome::SideChain);
+        let missing = block(1, &root, 1, "missing");
+        assert_eq!(fork_choice.insert(block(2, &missing, 1, "orphan")).unwrap(), BlockOutcome::Orphaned);
+
+        for height in 3..=MAX_REORG_DEPTH + 3 {
+            parent = block(height, &parent, 1, "main");
+            fork_choice.insert(parent.clone()).unwrap();
+        }
+
+        // The root trails the tip by the reorg limit and only its descendants are kept
+        assert_eq!(fork_choice.root().height, 3);
+        assert_eq!(fork_choice.block_count() as u64, MAX_REORG_DEPTH + 1);
+        assert_eq!(fork_choice.orphan_count(), 0);
+        assert!(fork_choice.canonical_block(2).is_none());
+        assert_eq!(fork_choice.tip().hash, parent.hash);
+    }
+}
@@ -1,16 +1,66 @@
+use std::collections::HashMap;
 use std::sync::Arc;
-use tokio::sync::RwLock;
+use std::time::Duration;
+use tokio::sync::{mpsc, RwLock};
 use libp2p::{
     PeerId, Multiaddr, Transport
 };
 use serde::{Serialize, Deserialize};
 
 use log;
-use crate::consensus::vortex_consensus::{VortexConsensus, VortexBlock, Transaction, ConsensusMessage};
+use crate::consensus::vortex_consensus::{VortexConsensus, VortexBlock, Transaction, ConsensusMessage, ConsensusError, SyncRequest};
 use crate::network::torus_topology::TorusNetwork;
+use crate::network::address::{check_transport, parse_bootstrap_addr, parse_listen_addr};
 
 use crate::node::ecosystem_miner::EcosystemMiner;
-// Removed LedgerDB dependency - using in-memory storage only
+use crate::node::fork_choice::{BlockOutcome, ForkChoice, ForkChoiceError};
+use crate::rpc_storage::{Block, RPCStorage};
+use crate::storage::LedgerDB;
+
+/// Gossipsub topic carrying pending transactions between mempools
+pub const TRANSACTIONS_TOPIC: &str = "fractal-vortex/transactions";
+
+/// Gossipsub topic carrying mined chain blocks, imported through fork choice
+pub const BLOCKS_TOPIC: &str = "fractal-vortex/blocks";
+
+/// Score deducted from a peer that sends a block with an out-of-bounds timestamp
+pub const INVALID_TIMESTAMP_PENALTY: i64 = 20;
+
+/// Attempts made to create a node before it is marked permanently failed
+pub const NODE_INIT_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry, doubled after each further failure
+pub const NODE_INIT_BACKOFF: Duration = Duration::from_millis(500);
+
+/// Optional transport and discovery features built into the swarm
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkFeatures {
+    pub mdns: bool,
+    pub relay: bool,
+    pub tls: bool,
+    pub noise: bool,
+}
+
+/// Features used by `initialize_p2p`: TCP + noise + yamux, Kademlia discovery only
+pub const NETWORK_FEATURES: NetworkFeatures = NetworkFeatures {
+    mdns: false,
+    relay: false,
+    tls: false,
+    noise: true,
+};
+
+/// Commands handed to the task that owns the swarm
+#[derive(Debug)]
+pub enum NetworkCommand {
+    /// Publish raw bytes on a gossipsub topic
+    Publish { topic: String, data: Vec<u8> },
+    /// Open a connection to a peer address
+    Dial(Multiaddr),
+    /// Send a consensus request to a connected peer
+    Request { peer: PeerId, message: ConsensusMessage },
+    /// Disconnect peers and stop the network loop
+    Shutdown,
+}
 
 /// Main fractal-vortex blockchain node
 pub struct FractalNode {
@@ -24,22 +74,185 @@ pub struct FractalNode {
     config: NodeConfig,
     /// Runtime state
     state: Arc<RwLock<NodeState>>,
-    // Removed ledger field - using in-memory storage only
+    /// Persistent chain state, when the config names a data directory
+    ledger: Option<Arc<LedgerDB>>,
     /// P2P swarm
     swarm: Option<Swarm>,
     /// Ecosystem miner for automatic mining
     ecosystem_miner: Option<EcosystemMiner>,
+    /// Outbound commands for the network loop
+    network_tx: mpsc::UnboundedSender<NetworkCommand>,
+    /// Receiving end, consumed when the network loop starts
+    network_rx: Option<mpsc::UnboundedReceiver<NetworkCommand>>,
+    /// Reputation of peers we sync from, starting at zero
+    peer_scores: Arc<RwLock<HashMap<PeerId, i64>>>,
+    /// Block tree over the stored chain, rooted at the stored tip on first use
+    fork_choice: Arc<RwLock<Option<ForkChoice>>>,
 }
 
-/// Node configuration
+/// Node configuration; in config files `listen_addr` is required and unknown keys are rejected
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct NodeConfig {
     pub listen_addr: Multiaddr,
+    #[serde(default)]
     pub bootstrap_nodes: Vec<Multiaddr>,
+    #[serde(default = "default_energy_threshold")]
     pub energy_threshold: f64,
+    #[serde(default = "default_fractal_levels")]
     pub fractal_levels: u32,
+    #[serde(default = "default_max_peers")]
     pub max_peers: usize,
+    #[serde(default = "default_sync_interval")]
     pub sync_interval: u64,
+    /// LevelDB directory holding the node's chain state; None keeps it in memory only
+    #[serde(default)]
+    pub data_dir: Option<std::path::PathBuf>,
+}
+
+fn default_energy_threshold() -> f64 {
+    1000.0
+}
+
+fn default_fractal_levels() -> u32 {
+    5
+}
+
+fn default_max_peers() -> usize {
+    50
+}
+
+fn default_sync_interval() -> u64 {
+    30
+}
+
+/// Highest fractal level a node may be configured with
+pub const MAX_FRACTAL_LEVELS: u32 = 12;
+
+impl Default for NodeConfig {
+    fn default() -> Self {
+        Self {
+            listen_addr: crate::network::address::build_listen_addr("0.0.0.0", 30333).expect("valid default listen address"),
+            bootstrap_nodes: Vec::new(),
+            energy_threshold: default_energy_threshold(),
+            fractal_levels: default_fractal_levels(),
+            max_peers: default_max_peers(),
+            sync_interval: default_sync_interval(),
+            // No shared default: two nodes on one host would fight over the same LevelDB lock
+            data_dir: None,
+        }
+    }
+}
+
+impl NodeConfig {
+    /// Load from NODE_LISTEN_ADDR, NODE_BOOTSTRAP_NODES (comma-separated), NODE_ENERGY_THRESHOLD,
+    /// NODE_FRACTAL_LEVELS, NODE_MAX_PEERS, NODE_SYNC_INTERVAL and NODE_DATA_DIR; unset keys keep their defaults.
+    /// Without NODE_LISTEN_ADDR, a LISTEN_PORT key (as in `node-*.env` files written by `deploy`)
+    /// listens on that port on 127.0.0.1.
+    pub fn from_env() -> Result<Self, NodeError> {
+        Self::from_vars(&std::env::vars().collect())
+    }
+
+    /// Load a `.toml` or `.json` file keyed by field name, or otherwise `KEY=VALUE` lines from a
+    /// `node-*.env` file using the same keys as `from_env`; missing fields other than
+    /// `listen_addr` keep their defaults
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> Result<Self, NodeError> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)?;
+        let parsed = match path.extension().and_then(|ext| ext.to_str()) {
+            Some("toml") => Some(toml::from_str::<Self>(&contents).map_err(|e| e.to_string())),
+            Some("json") => Some(serde_json::from_str::<Self>(&contents).map_err(|e| e.to_string())),
+            _ => None,
+        };
+        if let Some(parsed) = parsed {
+            let config = parsed.map_err(|e| NodeError::ConfigError(format!("{}: {}", path.display(), e)))?;
+            config.validate()?;
+            return Ok(config);
+        }
+
+        let vars: HashMap<String, String> = contents
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| line.split_once('='))
+            .map(|(key, value)| {
+                let key = key.trim().trim_start_matches("export ").trim();
+                (key.to_string(), value.trim().trim_matches('"').to_string())
+            })
+            .collect();
+        Self::from_vars(&vars)
+    }
+
+    fn from_vars(vars: &HashMap<String, String>) -> Result<Self, NodeError> {
+        fn parse<T: std::str::FromStr>(vars: &HashMap<String, String>, key: &str) -> Result<Option<T>, NodeError>
+        where
+            T::Err: std::fmt::Display,
+        {
+            vars.get(key)
+                .map(|value| value.trim().parse::<T>()
+                    .map_err(|e| NodeError::ConfigError(format!("{}='{}': {}", key, value, e))))
+                .transpose()
+        }
+
+        let mut config = Self::default();
+        if let Some(addr) = vars.get("NODE_LISTEN_ADDR") {
+            config.listen_addr = parse_listen_addr(addr)
+                .map_err(|e| NodeError::ConfigError(format!("NODE_LISTEN_ADDR: {}", e)))?;
+        } else if let Some(port) = parse::<u16>(vars, "LISTEN_PORT")? {
+            config.listen_addr = crate::network::address::build_listen_addr("127.0.0.1", port)
+                .map_err(|e| NodeError::ConfigError(format!("LISTEN_PORT: {}", e)))?;
+        }
+        if let Some(nodes) = vars.get("NODE_BOOTSTRAP_NODES") {
+            config.bootstrap_nodes = nodes
+                .split(',')
+                .map(str::trim)
+                .filter(|node| !node.is_empty())
+                .map(|node| parse_bootstrap_addr(node)
+                    .map_err(|e| NodeError::ConfigError(format!("NODE_BOOTSTRAP_NODES: {}", e))))
+                .collect::<Result<_, _>>()?;
+        }
+        if let Some(threshold) = parse(vars, "NODE_ENERGY_THRESHOLD")? {
+            config.energy_threshold = threshold;
+        }
+        if let Some(levels) = parse(vars, "NODE_FRACTAL_LEVELS")? {
+            config.fractal_levels = levels;
+        }
+        if let Some(max_peers) = parse(vars, "NODE_MAX_PEERS")? {
+            config.max_peers = max_peers;
+        }
+        if let Some(interval) = parse(vars, "NODE_SYNC_INTERVAL")? {
+            config.sync_interval = interval;
+        }
+        if let Some(dir) = vars.get("NODE_DATA_DIR") {
+            config.data_dir = Some(std::path::PathBuf::from(dir.trim()));
+        }
+
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reject values the node cannot run with
+    pub fn validate(&self) -> Result<(), NodeError> {
+        check_transport(&self.listen_addr, false)
+            .map_err(|e| NodeError::ConfigError(format!("listen_addr: {}", e)))?;
+        for addr in &self.bootstrap_nodes {
+            check_transport(addr, true)
+                .map_err(|e| NodeError::ConfigError(format!("bootstrap node: {}", e)))?;
+        }
+        if !self.energy_threshold.is_finite() || self.energy_threshold <= 0.0 {
+            return Err(NodeError::ConfigError("energy_threshold must be a positive number".to_string()));
+        }
+        if self.fractal_levels == 0 || self.fractal_levels > MAX_FRACTAL_LEVELS {
+            return Err(NodeError::ConfigError(format!("fractal_levels must be between 1 and {}", MAX_FRACTAL_LEVELS)));
+        }
+        if self.max_peers == 0 {
+            return Err(NodeError::ConfigError("max_peers must be at least 1".to_string()));
+        }
+        if self.sync_interval == 0 {
+            return Err(NodeError::ConfigError("sync_interval must be at least 1 second".to_string()));
+        }
+        Ok(())
+    }
 }
 
 /// Node runtime state
@@ -52,6 +265,9 @@ pub struct NodeState {
     pub connected_peers: Vec<PeerId>,
     pub last_sync: u64,
     pub block_height: u64,
+    /// Hash of the block at `block_height`, if any has been committed
+    #[serde(default)]
+    pub head_hash: Option<[u8; 32]>,
     pub total_transactions: u64,
 }
 
@@ -167,6 +383,25 @@ impl libp2p::swarm::NetworkBehaviour for FractalBehaviour {
     
     type ToSwarm = FractalEvent;
 
+    fn handle_pending_inbound_connection(
+        &mut self,
+        connection_id: libp2p::swarm::ConnectionId,
+        local_addr: &libp2p::Multiaddr,
+        remote_addr: &libp2p::Multiaddr,
+    ) -> Result<(), libp2p::swarm::ConnectionDenied> {
+        self.connection_limits.handle_pending_inbound_connection(connection_id, local_addr, remote_addr)
+    }
+
+    fn handle_pending_outbound_connection(
+        &mut self,
+        connection_id: libp2p::swarm::ConnectionId,
+        maybe_peer: Option<libp2p::PeerId>,
+        addresses: &[libp2p::Multiaddr],
+        effective_role: libp2p::core::Endpoint,
+    ) -> Result<Vec<libp2p::Multiaddr>, libp2p::swarm::ConnectionDenied> {
+        self.connection_limits.handle_pending_outbound_connection(connection_id, maybe_peer, addresses, effective_role)
+    }
+
     fn handle_established_inbound_connection(
         &mut self,
         connection_id: libp2p::swarm::ConnectionId,
@@ -271,7 +506,19 @@ impl libp2p::swarm::NetworkBehaviour for FractalBehaviour {
 
 /// Production-grade implementation with comprehensive security and monitoring
 impl FractalBehaviour {
-    pub fn new(peer_id: PeerId) -> Result<Self, Box<dyn std::error::Error>> {
+    /// Connection limits capping established inbound + outbound connections at `max_peers`
+    pub fn connection_limits(max_peers: usize) -> libp2p::connection_limits::ConnectionLimits {
+        let max_peers = u32::try_from(max_peers).unwrap_or(u32::MAX);
+        libp2p::connection_limits::ConnectionLimits::default()
+            .with_max_pending_incoming(Some(32))
+            .with_max_pending_outgoing(Some(64))
+            .with_max_established_incoming(Some(max_peers.min(128)))
+            .with_max_established_outgoing(Some(max_peers.min(256)))
+            .with_max_established(Some(max_peers))
+            .with_max_established_per_peer(Some(4))
+    }
+
+    pub fn new(peer_id: PeerId, max_peers: usize) -> Result<Self, Box<dyn std::error::Error>> {
         // Production Gossipsub configuration with strict validation
         let gossipsub_config = libp2p::gossipsub::ConfigBuilder::default()
             .heartbeat_interval(std::time::Duration::from_secs(5)) // Faster heartbeat for mainnet
@@ -287,6 +534,7 @@ impl FractalBehaviour {
             .max_transmit_size(1024 * 1024) // 1MB max message size
             .duplicate_cache_time(std::time::Duration::from_secs(300)) // 5 min cache
             .flood_publish(false) // Disable flood publishing for efficiency
+            .validate_messages() // Hold forwarding until the application validates payloads
             .build()
             .expect("Valid gossipsub config");
         
@@ -321,15 +569,8 @@ impl FractalBehaviour {
             request_response_config,
         );
         
-        // Connection limits for DoS protection
-        let connection_limits = libp2p::connection_limits::Behaviour::new(
-            libp2p::connection_limits::ConnectionLimits::default()
-                .with_max_pending_incoming(Some(32))
-                .with_max_pending_outgoing(Some(64))
-                .with_max_established_incoming(Some(128))
-                .with_max_established_outgoing(Some(256))
-                .with_max_established_per_peer(Some(4))
-        );
+        // Connection limits for DoS protection, bounded by the node's peer budget
+        let connection_limits = libp2p::connection_limits::Behaviour::new(Self::connection_limits(max_peers));
         
         // Allow/Block list for network security
         let allow_block_list = libp2p::allow_block_list::Behaviour::default();
@@ -346,8 +587,8 @@ impl FractalBehaviour {
     /// Subscribe to consensus topics for block propagation
     pub fn subscribe_to_consensus_topics(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         let topics = vec![
-            "fractal-vortex/blocks",
-            "fractal-vortex/transactions", 
+            BLOCKS_TOPIC,
+            TRANSACTIONS_TOPIC,
             "fractal-vortex/consensus",
             "fractal-vortex/validator-announcements",
             "fractal-vortex/network-health"
@@ -521,32 +762,83 @@ impl libp2p::request_response::Codec for ConsensusCodec {
     }
 }
 
+/// Run `op` up to `attempts` times, sleeping `base_delay * 2^n` between failures.
+/// Returns the last error once every attempt has failed.
+pub async fn retry_with_backoff<T, E, F, Fut>(attempts: u32, base_delay: Duration, mut op: F) -> Result<T, E>
+where
+    E: std::fmt::Display,
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = Result<T, E>>,
+{
+    let attempts = attempts.max(1);
+    let mut delay = base_delay;
+    let mut attempt = 1;
+    loop {
+        match op().await {
+            Ok(value) => return Ok(value),
+            Err(e) if attempt < attempts => {
+                log::warn!("Attempt {}/{} failed: {}, retrying in {:?}", attempt, attempts, e, delay);
+                tokio::time::sleep(delay).await;
+                delay = delay.saturating_mul(2);
+                attempt += 1;
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
 impl FractalNode {
     /// Create new fractal-vortex node
     pub async fn new(config: NodeConfig) -> Result<Self, NodeError> {
         let peer_id = PeerId::random();
         
         let consensus = Arc::new(RwLock::new(VortexConsensus::new(config.energy_threshold)));
-        let topology = Arc::new(RwLock::new(TorusNetwork::new(1.0)));
+        let mut torus = TorusNetwork::new(1.0);
+        torus.add_node(peer_id, None);
+        let topology = Arc::new(RwLock::new(torus));
         
-        let state = NodeState {
+        let mut state = NodeState {
             is_validator: false,
             current_epoch: 0,
             vortex_energy: 1.0,
             connected_peers: Vec::new(),
             last_sync: 0,
             block_height: 0,
+            head_hash: None,
             total_transactions: 0,
         };
 
+        // Resume from the chain state committed before the last shutdown
+        let ledger = match &config.data_dir {
+            Some(dir) => {
+                std::fs::create_dir_all(dir)?;
+                let ledger = LedgerDB::open(dir)?;
+                state.block_height = ledger.get_latest_block_height().await?;
+                state.head_hash = ledger.get_hash_by_height(state.block_height).await?;
+                state.total_transactions = ledger.get_total_transactions().await?;
+                if state.block_height > 0 {
+                    log::info!("Recovered chain state at height {}", state.block_height);
+                }
+                Some(Arc::new(ledger))
+            }
+            None => None,
+        };
+
+        let (network_tx, network_rx) = mpsc::unbounded_channel();
+
         Ok(Self {
             peer_id,
             consensus,
             topology,
             config,
             state: Arc::new(RwLock::new(state)),
+            ledger,
             swarm: None,
             ecosystem_miner: None,
+            network_tx,
+            network_rx: Some(network_rx),
+            peer_scores: Arc::new(RwLock::new(HashMap::new())),
+            fork_choice: Arc::new(RwLock::new(None)),
         })
     }
 
@@ -558,8 +850,14 @@ impl FractalNode {
             consensus.initialize(self.peer_id).await?;
         }
 
-        // Initialize P2P network
-        self.initialize_p2p().await?;
+        self.start_network().await?;
+
+        // Bring back transactions that were still pending when the node last stopped
+        match self.restore_mempool().await {
+            Ok(0) => {}
+            Ok(restored) => log::info!("Restored and rebroadcast {} pending transactions", restored),
+            Err(e) => log::warn!("Failed to restore mempool: {}", e),
+        }
 
         // Initialize ecosystem miner
         self.initialize_ecosystem_miner().await?;
@@ -570,10 +868,63 @@ impl FractalNode {
         Ok(())
     }
 
+    /// Bring up the swarm and hand it to the network loop so gossip is actually driven
+    pub async fn start_network(&mut self) -> Result<(), NodeError> {
+        self.start_network_with_retry(NODE_INIT_ATTEMPTS, NODE_INIT_BACKOFF).await
+    }
+
+    /// `start_network`, retrying the listen socket bind while its port is still being released
+    pub async fn start_network_with_retry(&mut self, attempts: u32, base_delay: Duration) -> Result<(), NodeError> {
+        self.initialize_p2p(attempts, base_delay).await?;
+
+        if let (Some(swarm), Some(network_rx)) = (self.swarm.take(), self.network_rx.take()) {
+            let consensus = self.consensus.clone();
+            let topology = self.topology.clone();
+            let state = self.state.clone();
+            let fork_choice = self.fork_choice.clone();
+            tokio::spawn(async move {
+                Self::network_loop_static(swarm, network_rx, consensus, topology, state, fork_choice).await;
+            });
+        }
+        Ok(())
+    }
+
+    /// Build a node from `config` and bring it online: network loop running, bootstrap
+    /// peers dialed and its ecosystem miner started. Retries cover a port still being released.
+    pub async fn launch(config: NodeConfig, attempts: u32, base_delay: Duration) -> Result<Self, NodeError> {
+        let mut node = Self::new(config).await?;
+        node.start_network_with_retry(attempts, base_delay).await?;
+
+        for addr in &node.config.bootstrap_nodes {
+            node.connect(addr.clone())?;
+        }
+        node.initialize_ecosystem_miner().await?;
+        Ok(node)
+    }
+
+    /// Tear this node down and launch a fresh one from the same configuration.
+    /// Every clone must be dropped first so the ledger can be reopened.
+    pub async fn restart(mut self, attempts: u32, base_delay: Duration) -> Result<Self, NodeError> {
+        let config = self.config.clone();
+        self.shutdown().await?;
+        drop(self);
+        Self::launch(config, attempts, base_delay).await
+    }
+
+    /// Configuration this node was built from
+    pub fn config(&self) -> &NodeConfig {
+        &self.config
+    }
+
+    /// Whether this node's ecosystem miner is running
+    pub fn is_mining(&self) -> bool {
+        self.ecosystem_miner.as_ref().is_some_and(|miner| miner.is_running())
+    }
+
     /// Initialize P2P networking with production-grade configuration
-    async fn initialize_p2p(&mut self) -> Result<(), NodeError> {
+    async fn initialize_p2p(&mut self, bind_attempts: u32, bind_backoff: Duration) -> Result<(), NodeError> {
         // Create production-grade behaviour using our new implementation
-        let behaviour = FractalBehaviour::new(self.peer_id)
+        let behaviour = FractalBehaviour::new(self.peer_id, self.config.max_peers)
             .map_err(|e| NodeError::NetworkError(format!("Failed to create behaviour: {}", e)))?;
 
         // Create swarm with simplified configuration
@@ -591,14 +942,17 @@ impl FractalNode {
                 .with_idle_connection_timeout(std::time::Duration::from_secs(60)),
         );
 
-        // Listen on configured address with error handling
-        swarm.listen_on(self.config.listen_addr.clone())
-            .map_err(|e| NodeError::NetworkError(format!("Failed to listen on address: {}", e)))?;
+        // Listen on configured address; the socket is bound here, so this is what gets retried
+        let listen_addr = self.config.listen_addr.clone();
+        retry_with_backoff(bind_attempts, bind_backoff, || {
+            std::future::ready(swarm.listen_on(listen_addr.clone())
+                .map_err(|e| NodeError::NetworkError(format!("Failed to listen on address: {}", e))))
+        }).await?;
 
         // Subscribe to essential consensus topics
         let topics = vec![
-            "fractal-vortex/blocks",
-            "fractal-vortex/transactions",
+            BLOCKS_TOPIC,
+            TRANSACTIONS_TOPIC,
             "fractal-vortex/consensus",
             "fractal-vortex/validator-announcements",
             "fractal-vortex/network-health"
@@ -644,15 +998,17 @@ impl FractalNode {
         // Consensus task
         let consensus_clone = consensus.clone();
         let state_clone = state.clone();
+        let ledger = self.ledger.clone();
         tokio::spawn(async move {
-            Self::consensus_loop_static(consensus_clone, state_clone, peer_id).await;
+            Self::consensus_loop_static(consensus_clone, state_clone, ledger, peer_id).await;
         });
 
         // Network sync task
-        let consensus_clone = consensus.clone();
         let state_clone = state.clone();
+        let topology = self.topology.clone();
+        let network_tx = self.network_tx.clone();
         tokio::spawn(async move {
-            Self::sync_loop_static(consensus_clone, state_clone, peer_id).await;
+            Self::sync_loop_static(state_clone, topology, network_tx, peer_id).await;
         });
 
         // Energy update task
@@ -685,7 +1041,7 @@ impl FractalNode {
 
             if is_validator {
                 // Propose block
-                let block = match self.consensus.write().await.propose_block(self.peer_id).await {
+                let block = match self.mine_block().await {
                     Ok(b) => b,
                     Err(_) => continue,
                 };
@@ -703,6 +1059,7 @@ impl FractalNode {
     async fn consensus_loop_static(
         _consensus: Arc<RwLock<VortexConsensus>>,
         state: Arc<RwLock<NodeState>>,
+        ledger: Option<Arc<LedgerDB>>,
         _peer_id: PeerId,
     ) {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
@@ -725,10 +1082,14 @@ impl FractalNode {
 
             if is_validator {
                 // Propose block
-                let _block = match _consensus.write().await.propose_block(_peer_id).await {
+                Self::expire_pending_static(&_consensus).await;
+                let block = match _consensus.write().await.propose_block(_peer_id).await {
                     Ok(b) => b,
                     Err(_) => continue,
                 };
+                if let Err(e) = Self::commit_block_static(&state, ledger.as_deref(), &block).await {
+                    log::error!("Failed to persist block {}: {}", hex::encode(block.hash), e);
+                }
 
                 // Note: Broadcasting would need swarm access, skipped in static version
             }
@@ -749,7 +1110,7 @@ impl FractalNode {
             interval.tick().await;
             
             // Sync with peers
-            let _ = self.sync_with_peers().await;
+            let _ = Self::sync_with_peers_static(&self.state, &self.topology, &self.network_tx, self.peer_id).await;
             
             // Update state
             let mut state = self.state.write().await;
@@ -759,21 +1120,228 @@ impl FractalNode {
 
     /// Static sync loop for background task
     async fn sync_loop_static(
-        _consensus: Arc<RwLock<VortexConsensus>>,
-        _state: Arc<RwLock<NodeState>>,
-        _peer_id: PeerId,
+        state: Arc<RwLock<NodeState>>,
+        topology: Arc<RwLock<TorusNetwork>>,
+        network_tx: mpsc::UnboundedSender<NetworkCommand>,
+        peer_id: PeerId,
     ) {
         let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(60));
         
         loop {
             interval.tick().await;
+
+            // Ask connected peers for the blocks above our height, routed over the torus
+            if let Err(e) = Self::sync_with_peers_static(&state, &topology, &network_tx, peer_id).await {
+                log::warn!("Block sync failed: {}", e);
+            }
             
             // Update state
-            let mut node_state = _state.write().await;
+            let mut node_state = state.write().await;
             node_state.last_sync = Self::get_current_timestamp_static();
         }
     }
 
+    /// Drive the swarm: publish outbound gossip and validate inbound transactions and blocks
+    async fn network_loop_static(
+        mut swarm: Swarm,
+        mut commands: mpsc::UnboundedReceiver<NetworkCommand>,
+        consensus: Arc<RwLock<VortexConsensus>>,
+        topology: Arc<RwLock<TorusNetwork>>,
+        state: Arc<RwLock<NodeState>>,
+        fork_choice: Arc<RwLock<Option<ForkChoice>>>,
+    ) {
+        use futures::StreamExt;
+        use libp2p::swarm::SwarmEvent;
+        use libp2p::gossipsub::MessageAcceptance;
+
+        let transactions_topic = libp2p::gossipsub::IdentTopic::new(TRANSACTIONS_TOPIC).hash();
+        let blocks_topic = libp2p::gossipsub::IdentTopic::new(BLOCKS_TOPIC).hash();
+
+        loop {
+            tokio::select! {
+                command = commands.recv() => match command {
+                    Some(NetworkCommand::Publish { topic, data }) => {
+                        if let Err(e) = swarm.behaviour_mut().publish_message(&topic, data) {
+                            log::warn!("Failed to publish on {}: {}", topic, e);
+                        }
+                    }
+                    Some(NetworkCommand::Dial(addr)) => {
+                        if let Err(e) = swarm.dial(addr.clone()) {
+                            log::warn!("Failed to dial {}: {}", addr, e);
+                        }
+                    }
+                    Some(NetworkCommand::Request { peer, message }) => {
+                        swarm.behaviour_mut().request_response.send_request(&peer, message);
+                    }
+                    Some(NetworkCommand::Shutdown) | None => {
+                        for peer_id in swarm.connected_peers().cloned().collect::<Vec<_>>() {
+                            let _ = swarm.disconnect_peer_id(peer_id);
+                        }
+                        break;
+                    }
+                },
+                event = swarm.select_next_some() => match event {
+                    // Place connected peers on the torus so block requests can be routed through them
+                    SwarmEvent::ConnectionEstablished { peer_id, endpoint, .. } => {
+                        let addr = endpoint.get_remote_address().to_string();
+                        topology.write().await.add_node(peer_id, Some(&addr));
+                        let mut state = state.write().await;
+                        if !state.connected_peers.contains(&peer_id) {
+                            state.connected_peers.push(peer_id);
+                        }
+                    }
+                    SwarmEvent::ConnectionClosed { peer_id, num_established: 0, .. } => {
+                        topology.write().await.remove_node(&peer_id);
+                        state.write().await.connected_peers.retain(|p| p != &peer_id);
+                    }
+                    SwarmEvent::Behaviour(FractalEvent::Gossipsub(
+                        libp2p::gossipsub::Event::Message { propagation_source, message_id, message }
+                    )) => {
+                        let acceptance = if message.topic == transactions_topic {
+                            match Self::accept_gossip_transaction(&consensus, &message.data).await {
+                                Ok(true) => MessageAcceptance::Accept,
+                                Ok(false) => MessageAcceptance::Ignore,
+                                Err(e) => {
+                                    log::warn!("Rejected transaction gossip from {}: {}", propagation_source, e);
+                                    MessageAcceptance::Reject
+                                }
+                            }
+                        } else if message.topic == blocks_topic {
+                            match Self::accept_gossip_block(&fork_choice, &consensus, &message.data).await {
+                                Ok(BlockOutcome::Duplicate) => MessageAcceptance::Ignore,
+                                Ok(_) => MessageAcceptance::Accept,
+                                // Our own storage failing is not the sender's fault
+                                Err(e @ NodeError::StorageError(_)) => {
+                                    log::warn!("Could not import block from {}: {}", propagation_source, e);
+                                    MessageAcceptance::Ignore
+                                }
+                                Err(e) => {
+                                    log::warn!("Rejected block gossip from {}: {}", propagation_source, e);
+                                    MessageAcceptance::Reject
+                                }
+                            }
+                        } else {
+                            MessageAcceptance::Accept
+                        };
+
+                        let _ = swarm.behaviour_mut().gossipsub.report_message_validation_result(
+                            &message_id,
+                            &propagation_source,
+                            acceptance,
+                        );
+                    }
+                    _ => {}
+                },
+            }
+        }
+    }
+
+    /// Validate a gossiped transaction and add it to the mempool.
+    /// Returns `Ok(true)` when the transaction is new and should be re-gossiped.
+    pub async fn accept_gossip_transaction(
+        consensus: &Arc<RwLock<VortexConsensus>>,
+        data: &[u8],
+    ) -> Result<bool, NodeError> {
+        let transaction: Transaction = serde_json::from_slice(data)
+            .map_err(|e| NodeError::NetworkError(format!("Malformed transaction gossip: {}", e)))?;
+
+        if !transaction.verify_signature() {
+            return Err(ConsensusError::InvalidSignature.into());
+        }
+
+        if consensus.read().await.has_pending_transaction(&transaction.hash).await {
+            return Ok(false);
+        }
+
+        Self::persist_pending(&transaction).await;
+        consensus.write().await.add_transaction(transaction).await?;
+        Ok(true)
+    }
+
+    /// Persist a transaction that just entered the mempool so it survives a restart
+    async fn persist_pending(transaction: &Transaction) {
+        let now = chrono::Utc::now().timestamp() as u64;
+        if let Err(e) = RPCStorage::add_pending_transaction(transaction, now).await {
+            log::warn!("Failed to persist pending transaction: {}", e);
+        }
+    }
+
+    /// Evict pending transactions past their expiry and record them so clients can see they expired
+    pub async fn expire_pending_transactions(&self) {
+        Self::expire_pending_static(&self.consensus).await;
+    }
+
+    async fn expire_pending_static(consensus: &RwLock<VortexConsensus>) {
+        let now = chrono::Utc::now().timestamp() as u64;
+        let expired = consensus.read().await.evict_expired_transactions(now).await;
+        if expired.is_empty() {
+            return;
+        }
+        for (hash, valid_until) in &expired {
+            log::info!("Pending transaction 0x{} expired unmined", hex::encode(hash));
+            if let Err(e) = RPCStorage::record_expired_transaction(hash, *valid_until).await {
+                log::warn!("Failed to record expired transaction: {}", e);
+            }
+        }
+        let hashes: Vec<[u8; 32]> = expired.iter().map(|(hash, _)| *hash).collect();
+        if let Err(e) = RPCStorage::remove_pending_transactions(&hashes).await {
+            log::warn!("Failed to drop expired pending transactions: {}", e);
+        }
+    }
+
+    /// Reload the persisted mempool, dropping expired or invalid transactions, and rebroadcast the rest.
+    /// Returns how many transactions were restored.
+    pub async fn restore_mempool(&self) -> Result<usize, NodeError> {
+        let now = chrono::Utc::now().timestamp() as u64;
+        RPCStorage::migrate_legacy_mempool().await?;
+        RPCStorage::expire_pending_transactions(now).await?;
+        let mut dropped = Vec::new();
+        let mut restored = 0;
+
+        for pending in RPCStorage::load_pending_transactions().await? {
+            let valid_until = pending.expires_at();
+            let tx = pending.transaction;
+            if !tx.verify_signature() {
+                log::info!("Dropping stale pending transaction 0x{}", hex::encode(tx.hash));
+                dropped.push(tx.hash);
+                continue;
+            }
+            if self.consensus.read().await.has_pending_transaction(&tx.hash).await {
+                continue;
+            }
+
+            self.consensus.write().await.add_transaction_until(tx.clone(), valid_until).await?;
+            if let Err(e) = self.broadcast_transaction(tx) {
+                log::warn!("Failed to rebroadcast pending transaction: {}", e);
+            }
+            restored += 1;
+        }
+
+        RPCStorage::remove_pending_transactions(&dropped).await?;
+        Ok(restored)
+    }
+
+    /// Handle a transaction received on the transactions topic
+    pub async fn ingest_gossip_transaction(&self, data: &[u8]) -> Result<bool, NodeError> {
+        Self::accept_gossip_transaction(&self.consensus, data).await
+    }
+
+    /// Decode a gossiped chain block and import it through fork choice
+    pub async fn accept_gossip_block(
+        fork_choice: &RwLock<Option<ForkChoice>>,
+        consensus: &RwLock<VortexConsensus>,
+        data: &[u8],
+    ) -> Result<BlockOutcome, NodeError> {
+        let block: Block = serde_json::from_slice(data)
+            .map_err(|e| NodeError::NetworkError(format!("Malformed block gossip: {}", e)))?;
+        Self::accept_block_static(fork_choice, consensus, block).await
+    }
+
+    /// Handle a block received on the blocks topic
+    pub async fn ingest_gossip_block(&self, data: &[u8]) -> Result<BlockOutcome, NodeError> {
+        Self::accept_gossip_block(&self.fork_choice, &self.consensus, data).await
+    }
+
     /// Energy update loop
     #[allow(dead_code)]
     async fn energy_loop(&self) {
@@ -818,16 +1386,22 @@ impl FractalNode {
     }
 
     /// Sync blocks with peers
-    #[allow(dead_code)]
-    async fn sync_with_peers(&self) -> Result<(), NodeError> {
-        let peers = {
-            let state = self.state.read().await;
-            state.connected_peers.clone()
+    async fn sync_with_peers_static(
+        state: &RwLock<NodeState>,
+        topology: &RwLock<TorusNetwork>,
+        network_tx: &mpsc::UnboundedSender<NetworkCommand>,
+        local_peer: PeerId,
+    ) -> Result<(), NodeError> {
+        let (peers, height) = {
+            let state = state.read().await;
+            (state.connected_peers.clone(), state.block_height)
         };
 
-        // Note: Sending requests requires mutable access to swarm
-        // This would need to be implemented differently in a real system
-        log::info!("Would sync with {} peers", peers.len());
+        for peer in peers {
+            let request = SyncRequest { start_height: height + 1, end_height: height + 100 };
+            let hop = Self::route_block_request(topology, network_tx, local_peer, peer, request).await?;
+            log::debug!("Requested blocks from {} via {}", peer, hop);
+        }
 
         Ok(())
     }
@@ -908,6 +1482,188 @@ impl FractalNode {
         Ok(())
     }
 
+    /// Validate a block received from `peer` during sync and add it to the DAG.
+    /// Peers sending out-of-bounds timestamps are scored down.
+    pub async fn accept_synced_block(&self, peer: PeerId, block: VortexBlock) -> Result<(), NodeError> {
+        let now = Self::get_current_timestamp_static();
+        if let Err(e) = self.consensus.read().await.check_block_timestamp(&block, now).await {
+            let mut scores = self.peer_scores.write().await;
+            let score = scores.entry(peer).or_insert(0);
+            *score -= INVALID_TIMESTAMP_PENALTY;
+            log::warn!("Rejected synced block from {} (timestamp {}): {}, score now {}", peer, block.timestamp, e, score);
+            return Err(e.into());
+        }
+
+        self.consensus.write().await.add_block(block).await?;
+        Ok(())
+    }
+
+    /// Root fork choice at `root`, a block already stored as part of the canonical chain
+    pub async fn set_chain_root(&self, root: Block) {
+        *self.fork_choice.write().await = Some(ForkChoice::new(root));
+    }
+
+    /// Run fork choice on a chain block and keep storage on the heaviest branch: a heavier
+    /// competing branch replaces the stored blocks above the fork point in one write, while lighter
+    /// branches and blocks with unknown parents are only kept in memory. Transactions the new
+    /// branch mines leave the mempool and those only the replaced blocks mined return to it.
+    pub async fn accept_block(&self, block: Block) -> Result<BlockOutcome, NodeError> {
+        Self::accept_block_static(&self.fork_choice, &self.consensus, block).await
+    }
+
+    async fn accept_block_static(
+        fork_choice: &RwLock<Option<ForkChoice>>,
+        consensus: &RwLock<VortexConsensus>,
+        block: Block,
+    ) -> Result<BlockOutcome, NodeError> {
+        if block.height > 0 && !block.has_valid_pow() {
+            return Err(NodeError::InvalidBlock(format!(
+                "block {} hash {} does not meet difficulty {}",
+                block.height, block.hash, block.difficulty
+            )));
+        }
+        block.verify_transactions()
+            .and_then(|()| block.verify_coinbase())
+            .map_err(|reason| NodeError::InvalidBlock(format!("block {}: {}", block.height, reason)))?;
+
+        let mut fork_choice = fork_choice.write().await;
+        // Root at the stored tip on first use, and again after blocks were stored without fork
+        // choice (locally mined ones)
+        let tip = RPCStorage::get_block_height().await?;
+        let stored_tip = RPCStorage::get_block_by_height(tip).await?
+            .ok_or_else(|| NodeError::InvalidBlock(format!("no stored block at tip {} to build on", tip)))?;
+        if fork_choice.as_ref().map(|fork_choice| &fork_choice.tip().hash) != Some(&stored_tip.hash) {
+            *fork_choice = Some(ForkChoice::new(stored_tip));
+        }
+
+        // The tree only moves once storage has followed it
+        let mut next = fork_choice.clone().expect("rooted above");
+        let outcome = next.insert(block)?;
+
+        if let BlockOutcome::TipChanged(update) = &outcome {
+            if update.is_reorg() {
+                log::warn!(
+                    "Reorg at height {}: {} blocks reverted, {} applied, {} transactions dropped",
+                    update.fork_height,
+                    update.reverted.len(),
+                    update.applied.len(),
+                    update.dropped_transactions().len()
+                );
+            }
+            let returned = RPCStorage::apply_chain_update(&update.reverted, &update.applied).await?;
+
+            let mined: Vec<[u8; 32]> = update.applied.iter()
+                .flat_map(|block| block.transactions.iter())
+                .filter_map(|tx| tx.hash.strip_prefix("0x").and_then(|h| hex::decode(h).ok()))
+                .filter_map(|bytes| bytes.try_into().ok())
+                .collect();
+            let mut consensus = consensus.write().await;
+            consensus.remove_pending_transactions(&mined).await;
+            for pending in returned {
+                let valid_until = pending.expires_at();
+                consensus.add_transaction_until(pending.transaction, valid_until).await?;
+            }
+        }
+
+        *fork_choice = Some(next);
+        Ok(outcome)
+    }
+
+    /// Publish a chain block on the blocks topic; peers import it through fork choice
+    pub fn broadcast_chain_block(&self, block: &Block) -> Result<(), NodeError> {
+        let data = serde_json::to_vec(block)
+            .map_err(|e| NodeError::NetworkError(format!("Failed to encode block: {}", e)))?;
+        self.network_tx
+            .send(NetworkCommand::Publish { topic: BLOCKS_TOPIC.to_string(), data })
+            .map_err(|e| NodeError::NetworkError(format!("Network loop unavailable: {}", e)))
+    }
+
+    /// Net balance change of the canonical chain since fork choice was first rooted
+    pub async fn canonical_balance_change(&self, address: &str) -> i128 {
+        self.fork_choice.read().await.as_ref().map_or(0, |fork_choice| fork_choice.balance_change(address))
+    }
+
+    /// Propose a block from the mempool and commit it as the new head
+    pub async fn mine_block(&self) -> Result<VortexBlock, NodeError> {
+        self.expire_pending_transactions().await;
+        let block = self.consensus.write().await.propose_block(self.peer_id).await?;
+        self.commit_block(&block).await?;
+        Ok(block)
+    }
+
+    /// Advance the head to `block`, persisting it first so a restart resumes from it
+    pub async fn commit_block(&self, block: &VortexBlock) -> Result<u64, NodeError> {
+        Self::commit_block_static(&self.state, self.ledger.as_deref(), block).await
+    }
+
+    async fn commit_block_static(
+        state: &RwLock<NodeState>,
+        ledger: Option<&LedgerDB>,
+        block: &VortexBlock,
+    ) -> Result<u64, NodeError> {
+        let mut state = state.write().await;
+        let height = state.block_height + 1;
+        let total_transactions = state.total_transactions + block.transactions.len() as u64;
+
+        if let Some(ledger) = ledger {
+            let bytes = serde_json::to_vec(block)
+                .map_err(|e| crate::storage::StorageError::Serialization(e.to_string()))?;
+            ledger.put_block(&block.hash, &bytes).await?;
+            ledger.set_height_index(height, &block.hash).await?;
+            ledger.set("tx_count", total_transactions).await?;
+            // Written last: the height only moves once the block it points at is stored
+            ledger.set_latest_block_height(height).await?;
+        }
+
+        state.block_height = height;
+        state.head_hash = Some(block.hash);
+        state.total_transactions = total_transactions;
+        Ok(height)
+    }
+
+    /// Peer to send a block request for `target` through: the torus next hop when a
+    /// connected peer is closer to it, otherwise `target` itself via Kademlia
+    pub async fn block_request_hop(&self, target: &PeerId) -> PeerId {
+        Self::block_request_hop_static(&self.topology, self.peer_id, target).await
+    }
+
+    async fn block_request_hop_static(topology: &RwLock<TorusNetwork>, local_peer: PeerId, target: &PeerId) -> PeerId {
+        let topology = topology.read().await;
+        let Some(position) = topology.position(target) else {
+            return *target;
+        };
+        topology
+            .generate_vortex_routing()
+            .with_local_peer(local_peer)
+            .next_hop(position)
+            .unwrap_or(*target)
+    }
+
+    /// Ask `target` for the blocks in `request`, routed through the torus next hop.
+    /// Returns the peer the request was sent to.
+    pub async fn request_blocks(&self, target: PeerId, request: SyncRequest) -> Result<PeerId, NodeError> {
+        Self::route_block_request(&self.topology, &self.network_tx, self.peer_id, target, request).await
+    }
+
+    async fn route_block_request(
+        topology: &RwLock<TorusNetwork>,
+        network_tx: &mpsc::UnboundedSender<NetworkCommand>,
+        local_peer: PeerId,
+        target: PeerId,
+        request: SyncRequest,
+    ) -> Result<PeerId, NodeError> {
+        let peer = Self::block_request_hop_static(topology, local_peer, &target).await;
+        network_tx
+            .send(NetworkCommand::Request { peer, message: ConsensusMessage::SyncRequest(request) })
+            .map_err(|e| NodeError::NetworkError(format!("Network loop unavailable: {}", e)))?;
+        Ok(peer)
+    }
+
+    /// Current reputation score of a peer
+    pub async fn peer_score(&self, peer: &PeerId) -> i64 {
+        self.peer_scores.read().await.get(peer).copied().unwrap_or(0)
+    }
+
     /// Handle sync request from peer
     async fn handle_sync_request(&self, _request: crate::consensus::vortex_consensus::SyncRequest) -> Result<(), NodeError> {
         // Placeholder for sync handling
@@ -933,6 +1689,22 @@ impl FractalNode {
         }
     }
 
+    /// Identity and capabilities reported to diagnostics tooling
+    pub fn get_identity(&self) -> NodeIdentity {
+        let listen_addresses = match &self.swarm {
+            Some(swarm) => swarm.listeners().cloned().collect(),
+            None => vec![self.config.listen_addr.clone()],
+        };
+
+        NodeIdentity {
+            peer_id: self.peer_id,
+            version: crate::VERSION.to_string(),
+            chain_id: crate::CHAIN_ID.to_string(),
+            features: NETWORK_FEATURES,
+            listen_addresses,
+        }
+    }
+
     /// Get consensus engine reference
     pub fn get_consensus(&self) -> Arc<RwLock<VortexConsensus>> {
         self.consensus.clone()
@@ -945,13 +1717,46 @@ impl FractalNode {
 
     /// Submit transaction to network
     pub async fn submit_transaction(&self, transaction: Transaction) -> Result<(), NodeError> {
-        let mut consensus = self.consensus.write().await;
-        consensus.add_transaction(transaction).await?;
-        Ok(())
+        if !transaction.verify_signature() {
+            return Err(ConsensusError::InvalidSignature.into());
+        }
+
+        Self::persist_pending(&transaction).await;
+        {
+            let mut consensus = self.consensus.write().await;
+            consensus.add_transaction(transaction.clone()).await?;
+        }
+
+        // Gossip to peers so their mempools see it before the next block
+        self.broadcast_transaction(transaction)
+    }
+
+    /// Publish a transaction on the transactions topic; peers ingest it into their mempools
+    pub fn broadcast_transaction(&self, transaction: Transaction) -> Result<(), NodeError> {
+        let data = serde_json::to_vec(&transaction)
+            .map_err(|e| NodeError::NetworkError(format!("Failed to encode transaction: {}", e)))?;
+        self.network_tx
+            .send(NetworkCommand::Publish { topic: TRANSACTIONS_TOPIC.to_string(), data })
+            .map_err(|e| NodeError::NetworkError(format!("Network loop unavailable: {}", e)))
+    }
+
+    /// Take the outbound network commands to deliver them without a swarm, e.g. to an in-process peer.
+    /// `start_network` no longer drives gossip once they have been taken.
+    pub fn take_network_commands(&mut self) -> Option<mpsc::UnboundedReceiver<NetworkCommand>> {
+        self.network_rx.take()
+    }
+
+    /// Ask the network loop to connect to `addr`
+    pub fn connect(&self, addr: Multiaddr) -> Result<(), NodeError> {
+        self.network_tx
+            .send(NetworkCommand::Dial(addr))
+            .map_err(|e| NodeError::NetworkError(format!("Network loop unavailable: {}", e)))
     }
 
     /// Shutdown node gracefully
     pub async fn shutdown(&mut self) -> Result<(), NodeError> {
+        let _ = self.network_tx.send(NetworkCommand::Shutdown);
+
         if let Some(mut swarm) = self.swarm.take() {
             // Disconnect all connected peers
             for peer_id in swarm.connected_peers().cloned().collect::<Vec<_>>() {
@@ -975,12 +1780,28 @@ impl Clone for FractalNode {
             topology: self.topology.clone(),
             config: self.config.clone(),
             state: self.state.clone(),
+            ledger: self.ledger.clone(),
             swarm: None, // Swarm cannot be cloned
             ecosystem_miner: None, // Miner will be reinitialized
+            network_tx: self.network_tx.clone(),
+            network_rx: None, // Only the original node drives the network loop
+            peer_scores: self.peer_scores.clone(),
+            fork_choice: self.fork_choice.clone(),
         }
     }
 }
 
+/// Node identity, software version and network capabilities
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NodeIdentity {
+    #[serde(with = "peer_id_serde")]
+    pub peer_id: PeerId,
+    pub version: String,
+    pub chain_id: String,
+    pub features: NetworkFeatures,
+    pub listen_addresses: Vec<Multiaddr>,
+}
+
 /// Node information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeInfo {
@@ -1007,4 +1828,499 @@ pub enum NodeError {
     ConfigError(String),
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
-}
\ No newline at end of file
+    #[error("Storage error: {0}")]
+    StorageError(#[from] crate::storage::StorageError),
+    #[error("Invalid block: {0}")]
+    InvalidBlock(String),
+    #[error("Fork choice error: {0}")]
+    ForkChoice(#[from] ForkChoiceError),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_storage::use_test_db;
+    use crate::wallet::key_manager::KeyManager;
+
+    fn test_config() -> NodeConfig {
+        NodeConfig {
+            listen_addr: "/ip4/127.0.0.1/tcp/0".parse().unwrap(),
+            bootstrap_nodes: Vec::new(),
+            energy_threshold: 0.5,
+            fractal_levels: 3,
+            max_peers: 10,
+            sync_interval: 30,
+            data_dir: None,
+        }
+    }
+
+    fn signed_transaction(key_manager: &KeyManager, nonce: u64) -> Transaction {
+        let mut tx = Transaction {
+            hash: [0u8; 32],
+            from: key_manager.get_public_key(),
+            to: b"fvc00000000000000000000000000000000000000emyl".to_vec(),
+            amount: 1_000_000,
+            nonce,
+            signature: Vec::new(),
+            vortex_fee: 0.001,
+        };
+        tx.hash[..8].copy_from_slice(&nonce.to_le_bytes());
+        tx.signature = key_manager.sign(&tx.signing_payload()).unwrap();
+        tx
+    }
+
+    fn write_env(contents: &str) -> tempfile::NamedTempFile {
+        use std::io::Write;
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_node_config_from_complete_file() {
+        let file = write_env(
+            "# node-1.env\n\
+             NODE_LISTEN_ADDR=/ip4/127.0.0.1/tcp/30334\n\
+             NODE_BOOTSTRAP_NODES=/ip4/127.0.0.1/tcp/30333, /ip4/127.0.0.1/tcp/30335\n\
+             NODE_ENERGY_THRESHOLD=1100.5\n\
+             NODE_FRACTAL_LEVELS=6\n\
+             export NODE_MAX_PEERS=60\n\
+             NODE_SYNC_INTERVAL=\"35\"\n",
+        );
+
+        let config = NodeConfig::from_file(file.path()).unwrap();
+        assert_eq!(config.listen_addr.to_string(), "/ip4/127.0.0.1/tcp/30334");
+        assert_eq!(config.bootstrap_nodes.len(), 2);
+        assert_eq!(config.energy_threshold, 1100.5);
+        assert_eq!(config.fractal_levels, 6);
+        assert_eq!(config.max_peers, 60);
+        assert_eq!(config.sync_interval, 35);
+    }
+
+    #[test]
+    fn test_node_config_missing_fields_use_defaults() {
+        let file = write_env("NODE_MAX_PEERS=70\n");
+
+        let config = NodeConfig::from_file(file.path()).unwrap();
+        let defaults = NodeConfig::default();
+        assert_eq!(config.max_peers, 70);
+        assert_eq!(config.listen_addr, defaults.listen_addr);
+        assert!(config.bootstrap_nodes.is_empty());
+        assert_eq!(config.energy_threshold, defaults.energy_threshold);
+        assert_eq!(config.fractal_levels, defaults.fractal_levels);
+        assert_eq!(config.sync_interval, defaults.sync_interval);
+    }
+
+    #[test]
+    fn test_node_config_default_keeps_state_in_memory() {
+        assert_eq!(NodeConfig::default().data_dir, None);
+        let file = write_env("NODE_MAX_PEERS=70\n");
+        assert_eq!(NodeConfig::from_file(file.path()).unwrap().data_dir, None);
+    }
+
+    #[test]
+    fn test_node_config_files_from_deploy_are_distinct() {
+        // As written by `deploy`, one file per validator
+        let first = write_env("# Node 0 Configuration\nNODE_ID=validator-0\nLISTEN_PORT=8000\nFRACTAL_ENERGY=1.0\n");
+        let second = write_env("# Node 1 Configuration\nNODE_ID=validator-1\nLISTEN_PORT=8001\nFRACTAL_ENERGY=1.0\n");
+
+        let first = NodeConfig::from_file(first.path()).unwrap();
+        let second = NodeConfig::from_file(second.path()).unwrap();
+        assert_eq!(first.listen_addr.to_string(), "/ip4/127.0.0.1/tcp/8000");
+        assert_eq!(second.listen_addr.to_string(), "/ip4/127.0.0.1/tcp/8001");
+
+        // An explicit address wins over the port
+        let explicit = write_env("LISTEN_PORT=8002\nNODE_LISTEN_ADDR=/ip4/0.0.0.0/tcp/9002\n");
+        assert_eq!(NodeConfig::from_file(explicit.path()).unwrap().listen_addr.to_string(), "/ip4/0.0.0.0/tcp/9002");
+
+        let bad_port = write_env("LISTEN_PORT=http\n");
+        assert!(matches!(NodeConfig::from_file(bad_port.path()), Err(NodeError::ConfigError(_))));
+    }
+
+    fn write_config(suffix: &str, contents: &str) -> tempfile::NamedTempFile {
+        use std::io::Write;
+        let mut file = tempfile::Builder::new().suffix(suffix).tempfile().unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        file
+    }
+
+    #[test]
+    fn test_node_config_from_toml_file() {
+        let file = write_config(".toml", r#"
+            listen_addr = "/ip4/127.0.0.1/tcp/30336"
+            bootstrap_nodes = ["/ip4/127.0.0.1/tcp/30333"]
+            energy_threshold = 1300.0
+            fractal_levels = 7
+            max_peers = 80
+            sync_interval = 45
+            data_dir = "data/node-3"
+        "#);
+
+        let config = NodeConfig::from_file(file.path()).unwrap();
+        assert_eq!(config.listen_addr.to_string(), "/ip4/127.0.0.1/tcp/30336");
+        assert_eq!(config.bootstrap_nodes, vec!["/ip4/127.0.0.1/tcp/30333".parse::<Multiaddr>().unwrap()]);
+        assert_eq!(config.energy_threshold, 1300.0);
+        assert_eq!(config.fractal_levels, 7);
+        assert_eq!(config.max_peers, 80);
+        assert_eq!(config.sync_interval, 45);
+        assert_eq!(config.data_dir, Some(std::path::PathBuf::from("data/node-3")));
+
+        // JSON uses the same field names and falls back to defaults the same way
+        let file = write_config(".json", r#"{ "listen_addr": "/ip4/127.0.0.1/tcp/30337", "max_peers": 90 }"#);
+        let config = NodeConfig::from_file(file.path()).unwrap();
+        assert_eq!(config.max_peers, 90);
+        assert_eq!(config.sync_interval, NodeConfig::default().sync_interval);
+        assert_eq!(config.data_dir, None);
+    }
+
+    #[test]
+    fn test_node_config_malformed_file_rejected() {
+        let truncated = write_config(".json", r#"{ "max_peers": 90, "#);
+        assert!(matches!(NodeConfig::from_file(truncated.path()), Err(NodeError::ConfigError(_))));
+
+        let listen = "listen_addr = \"/ip4/127.0.0.1/tcp/30338\"\n";
+        let wrong_type = write_config(".toml", &format!("{}fractal_levels = \"seven\"\n", listen));
+        assert!(matches!(NodeConfig::from_file(wrong_type.path()), Err(NodeError::ConfigError(_))));
+
+        // Parsed but out of range
+        let bad_levels = write_config(".toml", &format!("{}fractal_levels = 0\n", listen));
+        assert!(matches!(NodeConfig::from_file(bad_levels.path()), Err(NodeError::ConfigError(_))));
+
+        // A node must say where it listens, and a misspelt key is an error rather than a default
+        let no_listen = write_config(".toml", "max_peers = 80\n");
+        assert!(matches!(NodeConfig::from_file(no_listen.path()), Err(NodeError::ConfigError(_))));
+        let typo = write_config(".toml", &format!("{}max_peer = 80\n", listen));
+        assert!(matches!(NodeConfig::from_file(typo.path()), Err(NodeError::ConfigError(_))));
+    }
+
+    #[test]
+    fn test_node_config_rejects_invalid_values() {
+        let bad_addr = write_env("NODE_LISTEN_ADDR=not-a-multiaddr\n");
+        assert!(matches!(NodeConfig::from_file(bad_addr.path()), Err(NodeError::ConfigError(_))));
+
+        let bad_threshold = write_env("NODE_ENERGY_THRESHOLD=-5\n");
+        assert!(matches!(NodeConfig::from_file(bad_threshold.path()), Err(NodeError::ConfigError(_))));
+
+        let bad_levels = write_env("NODE_FRACTAL_LEVELS=0\n");
+        assert!(matches!(NodeConfig::from_file(bad_levels.path()), Err(NodeError::ConfigError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_max_peers_denies_extra_connection() {
+        use libp2p::core::ConnectedPoint;
+        use libp2p::swarm::{behaviour::ConnectionEstablished, ConnectionId, FromSwarm, NetworkBehaviour};
+
+        let config = NodeConfig { max_peers: 2, ..test_config() };
+        let mut behaviour = FractalBehaviour::new(PeerId::random(), config.max_peers).unwrap();
+        let local_addr: Multiaddr = "/ip4/127.0.0.1/tcp/30333".parse().unwrap();
+        let remote_addr: Multiaddr = "/ip4/127.0.0.1/tcp/40000".parse().unwrap();
+        let endpoint = ConnectedPoint::Listener {
+            local_addr: local_addr.clone(),
+            send_back_addr: remote_addr.clone(),
+        };
+
+        for id in 0..2 {
+            let peer = PeerId::random();
+            let connection_id = ConnectionId::new_unchecked(id);
+            assert!(behaviour.handle_established_inbound_connection(connection_id, peer, &local_addr, &remote_addr).is_ok());
+            behaviour.on_swarm_event(FromSwarm::ConnectionEstablished(ConnectionEstablished {
+                peer_id: peer,
+                connection_id,
+                endpoint: &endpoint,
+                failed_addresses: &[],
+                other_established: 0,
+            }));
+        }
+
+        let third = behaviour.handle_established_inbound_connection(
+            ConnectionId::new_unchecked(2),
+            PeerId::random(),
+            &local_addr,
+            &remote_addr,
+        );
+        assert!(third.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_init_retry_recovers_from_first_failure() {
+        let calls = std::sync::atomic::AtomicU32::new(0);
+
+        let result = retry_with_backoff(NODE_INIT_ATTEMPTS, Duration::from_millis(1), || {
+            let attempt = calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async move {
+                if attempt == 0 {
+                    return Err(NodeError::NetworkError("Address already in use".to_string()));
+                }
+                FractalNode::new(test_config()).await
+            }
+        }).await;
+
+        let healthy = result.is_ok();
+        assert!(healthy);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn test_bind_retry_waits_for_port_to_be_released() {
+        let held = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = held.local_addr().unwrap().port();
+        let config = NodeConfig {
+            listen_addr: format!("/ip4/127.0.0.1/tcp/{}", port).parse().unwrap(),
+            ..test_config()
+        };
+
+        // Taken for the whole window: the bind fails once attempts run out
+        let mut node = FractalNode::new(config.clone()).await.unwrap();
+        assert!(node.start_network_with_retry(2, Duration::from_millis(1)).await.is_err());
+
+        // Released between attempts: the same bind succeeds on a retry
+        tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            drop(held);
+        });
+        let mut node = FractalNode::new(config).await.unwrap();
+        node.start_network_with_retry(NODE_INIT_ATTEMPTS, Duration::from_millis(100)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_init_retry_gives_up_after_attempts() {
+        let calls = std::sync::atomic::AtomicU32::new(0);
+
+        let result: Result<(), NodeError> = retry_with_backoff(NODE_INIT_ATTEMPTS, Duration::from_millis(1), || {
+            calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            async { Err(NodeError::NetworkError("Address already in use".to_string())) }
+        }).await;
+
+        assert!(result.is_err());
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), NODE_INIT_ATTEMPTS);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_gossip_reaches_peer_mempool() {
+        let _db = use_test_db();
+        let mut node_a = FractalNode::new(test_config()).await.unwrap();
+        let node_b = FractalNode::new(test_config()).await.unwrap();
+        let mut outbound = node_a.network_rx.take().unwrap();
+
+        let key_manager = KeyManager::new();
+        let tx = signed_transaction(&key_manager, 1);
+        node_a.submit_transaction(tx.clone()).await.unwrap();
+
+        // Deliver node A's published gossip to node B as the swarm would
+        let data = match outbound.try_recv().unwrap() {
+            NetworkCommand::Publish { topic, data } => {
+                assert_eq!(topic, TRANSACTIONS_TOPIC);
+                data
+            }
+            other => panic!("unexpected network command: {:?}", other),
+        };
+
+        assert!(node_b.ingest_gossip_transaction(&data).await.unwrap());
+        assert!(node_b.get_consensus().read().await.has_pending_transaction(&tx.hash).await);
+
+        // A duplicate delivery is accepted silently but not propagated again
+        assert!(!node_b.ingest_gossip_transaction(&data).await.unwrap());
+        assert_eq!(node_b.get_consensus().read().await.get_pending_transactions().await.len(), 1);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_transaction_gossip_between_swarms() {
+        let _db = use_test_db();
+        let port = std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port();
+        let addr_a = crate::network::address::build_listen_addr("127.0.0.1", port).unwrap();
+        let mut node_a = FractalNode::new(NodeConfig { listen_addr: addr_a.clone(), ..test_config() }).await.unwrap();
+        let mut node_b = FractalNode::new(test_config()).await.unwrap();
+        node_a.start_network().await.unwrap();
+        node_b.start_network().await.unwrap();
+        node_b.connect(addr_a).unwrap();
+
+        let tx = signed_transaction(&KeyManager::new(), 3);
+        node_a.submit_transaction(tx.clone()).await.unwrap();
+
+        // Publishing fails until node A has learned node B's subscription, so keep re-announcing
+        let mempool_b = node_b.get_consensus();
+        let mut received = false;
+        for _ in 0..100 {
+            tokio::time::sleep(Duration::from_millis(100)).await;
+            if mempool_b.read().await.has_pending_transaction(&tx.hash).await {
+                received = true;
+                break;
+            }
+            node_a.broadcast_transaction(tx.clone()).unwrap();
+        }
+        assert!(received, "transaction never reached node B");
+
+        node_a.shutdown().await.unwrap();
+        node_b.shutdown().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_tampered_transaction_gossip_is_rejected() {
+        let node = FractalNode::new(test_config()).await.unwrap();
+        let key_manager = KeyManager::new();
+
+        let mut tx = signed_transaction(&key_manager, 2);
+        tx.amount += 1;
+        let data = serde_json::to_vec(&tx).unwrap();
+
+        assert!(node.ingest_gossip_transaction(&data).await.is_err());
+        assert!(node.submit_transaction(tx.clone()).await.is_err());
+        assert!(!node.get_consensus().read().await.has_pending_transaction(&tx.hash).await);
+    }
+
+    #[tokio::test]
+    async fn test_synced_block_with_future_timestamp_is_rejected() {
+        let node = FractalNode::new(test_config()).await.unwrap();
+        let peer = PeerId::random();
+
+        let block = VortexBlock {
+            hash: [0xff; 32],
+            nonce: 0,
+            difficulty: 1,
+            parent_hashes: Vec::new(),
+            transactions: Vec::new(),
+            timestamp: FractalNode::get_current_timestamp_static() + 24 * 3600,
+            validator_id: peer,
+            vortex_energy: 0.0,
+            fractal_level: 3,
+            sierpinski_proof: Vec::new(),
+        };
+
+        let result = node.accept_synced_block(peer, block).await;
+        assert!(matches!(result, Err(NodeError::ConsensusError(ConsensusError::InvalidTimestamp))));
+        assert_eq!(node.peer_score(&peer).await, -INVALID_TIMESTAMP_PENALTY);
+    }
+
+    #[tokio::test]
+    async fn test_identity_reports_build_constants() {
+        let node = FractalNode::new(test_config()).await.unwrap();
+        let identity = serde_json::to_value(node.get_identity()).unwrap();
+
+        assert_eq!(identity["chain_id"], crate::CHAIN_ID);
+        assert_eq!(identity["version"], crate::VERSION);
+        let peer_id: PeerId = identity["peer_id"].as_str().unwrap().parse().unwrap();
+        assert_eq!(peer_id, node.peer_id);
+        assert_eq!(identity["listen_addresses"][0], "/ip4/127.0.0.1/tcp/0");
+    }
+
+    #[tokio::test]
+    async fn test_pending_transactions_survive_restart() {
+        let _db = use_test_db();
+        let key_manager = KeyManager::new();
+        let tx = signed_transaction(&key_manager, 0x5eed);
+        let expired = signed_transaction(&key_manager, 0xdead);
+
+        let now = chrono::Utc::now().timestamp() as u64;
+        RPCStorage::add_pending_transaction(&expired, now - *crate::rpc_storage::MEMPOOL_TX_TTL - 1).await.unwrap();
+        {
+            let node = FractalNode::new(test_config()).await.unwrap();
+            node.submit_transaction(tx.clone()).await.unwrap();
+        } // node stops with the transaction still unmined
+
+        let mut restarted = FractalNode::new(test_config()).await.unwrap();
+        let mut outbound = restarted.network_rx.take().unwrap();
+        assert!(restarted.restore_mempool().await.unwrap() >= 1);
+
+        let pending = restarted.get_consensus().read().await.get_pending_transactions().await;
+        assert!(pending.iter().any(|p| p.hash == tx.hash));
+        assert!(!pending.iter().any(|p| p.hash == expired.hash));
+
+        // Restored transactions are rebroadcast
+        let mut rebroadcast = Vec::new();
+        while let Ok(NetworkCommand::Publish { data, .. }) = outbound.try_recv() {
+            rebroadcast.push(serde_json::from_slice::<Transaction>(&data).unwrap().hash);
+        }
+        assert!(rebroadcast.contains(&tx.hash));
+
+        // Expired transactions are gone from storage
+        let persisted = RPCStorage::load_pending_transactions().await.unwrap();
+        assert!(persisted.iter().any(|p| p.transaction.hash == tx.hash));
+        assert!(!persisted.iter().any(|p| p.transaction.hash == expired.hash));
+        // ...but clients can still see that it expired
+        let expired_hash = format!("0x{}", hex::encode(expired.hash));
+        assert!(matches!(
+            RPCStorage::pending_status(&expired_hash, now).await.unwrap(),
+            Some(crate::rpc_storage::PendingStatus::Expired { .. })
+        ));
+
+        // And the restored transaction can still be mined
+        let template = crate::mining::BlockTemplate::new("0".repeat(64), 1, "fvcminer".to_string(), 1, &pending, now);
+        let tx_hash = format!("0x{}", hex::encode(tx.hash));
+        assert!(template.transactions.iter().any(|t| t.hash == tx_hash));
+        assert!((0u64..).find_map(|nonce| template.solve(nonce).ok()).is_some());
+    }
+
+    #[tokio::test]
+    async fn test_chain_state_recovered_after_restart() {
+        let data_dir = tempfile::tempdir().unwrap();
+        let config = NodeConfig { data_dir: Some(data_dir.path().to_path_buf()), ..test_config() };
+
+        let head = {
+            let node = FractalNode::new(config.clone()).await.unwrap();
+            assert_eq!(node.get_state().read().await.block_height, 0);
+            let mut head = None;
+            for _ in 0..3 {
+                head = Some(node.mine_block().await.unwrap().hash);
+            }
+            head
+        }; // node dropped, releasing the database
+
+        let restarted = FractalNode::new(config).await.unwrap();
+        let state = restarted.get_state();
+        let state = state.read().await;
+        assert_eq!(state.block_height, 3);
+        assert_eq!(state.head_hash, head);
+    }
+
+    #[tokio::test]
+    async fn test_block_requests_prefer_torus_hops() {
+        let mut node = FractalNode::new(test_config()).await.unwrap();
+        let mut outbound = node.network_rx.take().unwrap();
+
+        // A peer the torus doesn't know about is asked directly
+        let stranger = PeerId::random();
+        let request = SyncRequest { start_height: 1, end_height: 10 };
+        assert_eq!(node.request_blocks(stranger, request).await.unwrap(), stranger);
+
+        // Otherwise the request goes to whichever peer the routing table picks
+        let target = PeerId::random();
+        let position = node.topology.write().await.add_node(target, None);
+        let expected = node.topology.read().await
+            .generate_vortex_routing()
+            .with_local_peer(node.peer_id)
+            .next_hop(position)
+            .unwrap_or(target);
+        let sent_to = node.request_blocks(target, SyncRequest { start_height: 1, end_height: 10 }).await.unwrap();
+        assert_eq!(sent_to, expected);
+
+        let mut peers = Vec::new();
+        while let Ok(NetworkCommand::Request { peer, message: ConsensusMessage::SyncRequest(_) }) = outbound.try_recv() {
+            peers.push(peer);
+        }
+        assert_eq!(peers, vec![stranger, sent_to]);
+    }
+
+    #[tokio::test]
+    async fn test_sync_requests_blocks_above_height_from_connected_peers() {
+        let mut node = FractalNode::new(test_config()).await.unwrap();
+        let mut outbound = node.network_rx.take().unwrap();
+        let peer = PeerId::random();
+        node.topology.write().await.add_node(peer, None);
+        {
+            let mut state = node.state.write().await;
+            state.connected_peers.push(peer);
+            state.block_height = 7;
+        }
+
+        FractalNode::sync_with_peers_static(&node.state, &node.topology, &node.network_tx, node.peer_id).await.unwrap();
+
+        let expected_hop = node.block_request_hop(&peer).await;
+        match outbound.try_recv().unwrap() {
+            NetworkCommand::Request { peer: hop, message: ConsensusMessage::SyncRequest(request) } => {
+                assert_eq!(hop, expected_hop);
+                assert_eq!((request.start_height, request.end_height), (8, 107));
+            }
+            other => panic!("unexpected network command: {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,393 @@
+use std::cell::RefCell;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use axum::{extract::Request, http::{HeaderValue, StatusCode}, middleware::Next, response::Response};
+use rand::Rng;
+use tokio::sync::RwLock;
+
+/// Response header naming the node index that served the request
+pub const SERVED_BY_HEADER: &str = "x-fvc-node-id";
+
+/// Node serving the current request, and its in-flight count held until the response is ready
+#[derive(Default)]
+struct RequestNode {
+    node_index: Option<usize>,
+    in_flight: Vec<InFlight>,
+}
+
+tokio::task_local! {
+    static SERVED_BY: RefCell<RequestNode>;
+}
+
+/// Record the node handling the current request; a no-op outside `served_by_middleware`
+pub fn record_served_by(node_index: usize) {
+    let _ = SERVED_BY.try_with(|served_by| served_by.borrow_mut().node_index = Some(node_index));
+}
+
+/// Keep `in_flight` counted until the current request's handler has finished, recording its
+/// node as the one serving the request. Outside `served_by_middleware` it is released at once.
+pub fn hold_in_flight(in_flight: InFlight) {
+    let _ = SERVED_BY.try_with(move |served_by| {
+        let mut served_by = served_by.borrow_mut();
+        served_by.node_index = Some(in_flight.node_index());
+        served_by.in_flight.push(in_flight);
+    });
+}
+
+/// Node recorded for the current request so far
+pub fn served_by() -> Option<usize> {
+    SERVED_BY.try_with(|served_by| served_by.borrow().node_index).ok().flatten()
+}
+
+/// Adds `X-FVC-Node-Id` to responses whose handler ran an operation on a specific node.
+/// Requests the handler started on a node count as in flight until the handler returns.
+pub async fn served_by_middleware(request: Request, next: Next) -> Response {
+    let (node_index, mut response) = SERVED_BY
+        .scope(RefCell::new(RequestNode::default()), async move {
+            let response = next.run(request).await;
+            (served_by(), response)
+        })
+        .await;
+
+    if let Some(node_index) = node_index {
+        response.headers_mut().insert(SERVED_BY_HEADER, HeaderValue::from(node_index));
+    }
+    response
+}
+
+/// What a node reported at one health check
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HealthSample {
+    pub block_height: u64,
+    pub peer_count: usize,
+}
+
+/// Blocks a node may trail the highest node in the cluster before it counts as out of sync
+pub const MAX_HEIGHT_LAG: u64 = 2;
+
+/// Judge every node from one round of samples (None when a node is not running): a node is
+/// healthy while it has peers and is at most MAX_HEIGHT_LAG blocks behind the highest running
+/// node. A cluster where no node advances, such as one without validators, stays healthy.
+pub fn judge_health(samples: &[Option<HealthSample>]) -> Vec<bool> {
+    let best_height = samples.iter().flatten().map(|sample| sample.block_height).max().unwrap_or(0);
+    samples.iter()
+        .map(|sample| sample.is_some_and(|sample| {
+            sample.peer_count > 0 && best_height - sample.block_height <= MAX_HEIGHT_LAG
+        }))
+        .collect()
+}
+
+/// Why an operation could not be run on a cluster node
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ClusterError {
+    #[error("No healthy nodes available")]
+    NoHealthyNodes,
+    #[error("Node {0} is marked healthy but not running")]
+    NodeUnavailable(usize),
+    #[error("Operation failed on node {node}: {reason}")]
+    OperationFailed { node: usize, reason: String },
+}
+
+impl ClusterError {
+    /// 503 while no node can take the request, 500 when a node took it and failed
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ClusterError::NoHealthyNodes | ClusterError::NodeUnavailable(_) => StatusCode::SERVICE_UNAVAILABLE,
+            ClusterError::OperationFailed { .. } => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
+
+/// How the balancer picks among healthy nodes
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoadBalanceStrategy {
+    #[default]
+    RoundRobin,
+    /// Fewest requests in flight, rotating among ties
+    LeastConnections,
+    Random,
+}
+
+impl LoadBalanceStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LoadBalanceStrategy::RoundRobin => "round_robin",
+            LoadBalanceStrategy::LeastConnections => "least_connections",
+            LoadBalanceStrategy::Random => "random",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<LoadBalanceStrategy> {
+        match value {
+            "round_robin" => Some(LoadBalanceStrategy::RoundRobin),
+            "least_connections" => Some(LoadBalanceStrategy::LeastConnections),
+            "random" => Some(LoadBalanceStrategy::Random),
+            _ => None,
+        }
+    }
+}
+
+/// Selection over the nodes currently marked healthy, tracking requests in flight per node
+pub struct LoadBalancer {
+    health: Arc<RwLock<Vec<bool>>>,
+    strategy: LoadBalanceStrategy,
+    counter: AtomicUsize,
+    in_flight: Arc<Mutex<Vec<usize>>>,
+}
+
+/// A request in flight on one node; the node's count drops when this is dropped
+pub struct InFlight {
+    counts: Arc<Mutex<Vec<usize>>>,
+    node_index: usize,
+}
+
+impl InFlight {
+    pub fn node_index(&self) -> usize {
+        self.node_index
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let mut in_flight = self.counts.lock().unwrap();
+        in_flight[self.node_index] = in_flight[self.node_index].saturating_sub(1);
+    }
+}
+
+impl LoadBalancer {
+    pub fn new(health: Arc<RwLock<Vec<bool>>>, strategy: LoadBalanceStrategy) -> Self {
+        Self { health, strategy, counter: AtomicUsize::new(0), in_flight: Arc::new(Mutex::new(Vec::new())) }
+    }
+
+    pub fn strategy(&self) -> LoadBalanceStrategy {
+        self.strategy
+    }
+
+    /// Requests currently in flight on `node_index`
+    pub fn in_flight(&self, node_index: usize) -> usize {
+        self.in_flight.lock().unwrap().get(node_index).copied().unwrap_or(0)
+    }
+
+    fn select(&self, healthy_nodes: &[usize], in_flight: &[usize]) -> usize {
+        match self.strategy {
+            LoadBalanceStrategy::RoundRobin => {
+                let counter = self.counter.fetch_add(1, Ordering::Relaxed);
+                healthy_nodes[counter % healthy_nodes.len()]
+            }
+            LoadBalanceStrategy::LeastConnections => {
+                // Start the scan at a rotating offset so idle nodes share the load
+                let start = self.counter.fetch_add(1, Ordering::Relaxed) % healthy_nodes.len();
+                (0..healthy_nodes.len())
+                    .map(|i| healthy_nodes[(start + i) % healthy_nodes.len()])
+                    .min_by_key(|&node_index| in_flight[node_index])
+                    .unwrap()
+            }
+            LoadBalanceStrategy::Random => healthy_nodes[rand::thread_rng().gen_range(0..healthy_nodes.len())],
+        }
+    }
+
+    /// Pick a healthy node and count a request in flight on it until the guard drops;
+    /// None when every node is down
+    pub async fn acquire(&self) -> Option<InFlight> {
+        let health = self.health.read().await;
+        let healthy_nodes: Vec<usize> = health.iter()
+            .enumerate()
+            .filter_map(|(i, &is_healthy)| if is_healthy { Some(i) } else { None })
+            .collect();
+
+        if healthy_nodes.is_empty() {
+            return None;
+        }
+
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if in_flight.len() < health.len() {
+            in_flight.resize(health.len(), 0);
+        }
+        let node_index = self.select(&healthy_nodes, &in_flight);
+        in_flight[node_index] += 1;
+        Some(InFlight { counts: self.in_flight.clone(), node_index })
+    }
+
+    /// Next healthy node index, or None when every node is down
+    pub async fn next_healthy(&self) -> Option<usize> {
+        self.acquire().await.map(|in_flight| in_flight.node_index())
+    }
+
+    /// Run `operation` on the next healthy node of `nodes`, returning that node's index with the result.
+    /// Inside `served_by_middleware` the node stays counted in flight until the handler returns.
+    pub async fn execute<T, R, E: std::fmt::Display>(
+        &self,
+        nodes: &[Option<T>],
+        operation: impl FnOnce(&T) -> Result<R, E>,
+    ) -> Result<(usize, R), ClusterError> {
+        let in_flight = self.acquire().await.ok_or(ClusterError::NoHealthyNodes)?;
+        let node_index = in_flight.node_index();
+        hold_in_flight(in_flight);
+        let node = nodes.get(node_index)
+            .and_then(Option::as_ref)
+            .ok_or(ClusterError::NodeUnavailable(node_index))?;
+        operation(node)
+            .map(|result| (node_index, result))
+            .map_err(|e| ClusterError::OperationFailed { node: node_index, reason: e.to_string() })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{body::Body, routing::get, Router};
+    use tower::ServiceExt;
+
+    #[tokio::test]
+    async fn test_served_by_header_follows_round_robin() {
+        let balancer = Arc::new(LoadBalancer::new(Arc::new(RwLock::new(vec![true; 4])), LoadBalanceStrategy::RoundRobin));
+        let app = Router::new()
+            .route("/", get({
+                let balancer = balancer.clone();
+                move || async move {
+                    let node_index = balancer.next_healthy().await.unwrap();
+                    record_served_by(node_index);
+                    node_index.to_string()
+                }
+            }))
+            .route("/local", get(|| async { "no node" }))
+            .layer(axum::middleware::from_fn(served_by_middleware));
+
+        let mut served = Vec::new();
+        for _ in 0..6 {
+            let response = app.clone().oneshot(axum::http::Request::get("/").body(Body::empty()).unwrap()).await.unwrap();
+            let header = response.headers()[SERVED_BY_HEADER].to_str().unwrap().to_string();
+            let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+            assert_eq!(header.as_bytes(), &body[..]);
+            served.push(header);
+        }
+        assert_eq!(served, vec!["0", "1", "2", "3", "0", "1"]);
+
+        let response = app.oneshot(axum::http::Request::get("/local").body(Body::empty()).unwrap()).await.unwrap();
+        assert!(response.headers().get(SERVED_BY_HEADER).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_round_robin_skips_unhealthy_nodes() {
+        let balancer = LoadBalancer::new(Arc::new(RwLock::new(vec![true, false, true, false])), LoadBalanceStrategy::RoundRobin);
+        let mut picked = Vec::new();
+        for _ in 0..4 {
+            picked.push(balancer.next_healthy().await.unwrap());
+        }
+        assert_eq!(picked, vec![0, 2, 0, 2]);
+    }
+
+    #[tokio::test]
+    async fn test_least_connections_avoids_loaded_node() {
+        let balancer = LoadBalancer::new(Arc::new(RwLock::new(vec![true; 4])), LoadBalanceStrategy::LeastConnections);
+
+        // Node 0 is stuck on a slow request
+        let slow = balancer.acquire().await.unwrap();
+        assert_eq!(slow.node_index(), 0);
+
+        let mut picked = Vec::new();
+        for _ in 0..6 {
+            picked.push(balancer.acquire().await.unwrap().node_index());
+        }
+        assert!(!picked.contains(&0));
+        assert!((1..4).all(|node| picked.contains(&node)));
+
+        // Concurrent requests fill the idle nodes before doubling up on the loaded one
+        let held: Vec<_> = futures::future::join_all((0..3).map(|_| balancer.acquire())).await;
+        let mut held_nodes: Vec<usize> = held.iter().map(|r| r.as_ref().unwrap().node_index()).collect();
+        held_nodes.sort();
+        assert_eq!(held_nodes, vec![1, 2, 3]);
+        assert_eq!(balancer.in_flight(0), 1);
+
+        drop(slow);
+        drop(held);
+        assert_eq!((0..4).map(|node| balancer.in_flight(node)).sum::<usize>(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_node_stays_in_flight_until_handler_returns() {
+        let balancer = Arc::new(LoadBalancer::new(Arc::new(RwLock::new(vec![true; 2])), LoadBalanceStrategy::LeastConnections));
+        let release = Arc::new(tokio::sync::Notify::new());
+        let app = Router::new()
+            .route("/", get({
+                let balancer = balancer.clone();
+                let release = release.clone();
+                move || async move {
+                    let (node_index, _) = balancer.execute(&[Some(()), Some(())], |_| Ok::<_, String>(())).await.unwrap();
+                    // The operation has returned; the rest of the handler still runs on the node
+                    release.notified().await;
+                    node_index.to_string()
+                }
+            }))
+            .layer(axum::middleware::from_fn(served_by_middleware));
+
+        let slow = tokio::spawn(app.oneshot(axum::http::Request::get("/").body(Body::empty()).unwrap()));
+        while balancer.in_flight(0) + balancer.in_flight(1) == 0 {
+            tokio::task::yield_now().await;
+        }
+        let busy = if balancer.in_flight(0) == 1 { 0 } else { 1 };
+        assert_eq!(balancer.next_healthy().await, Some(1 - busy));
+        assert_eq!(balancer.next_healthy().await, Some(1 - busy));
+
+        release.notify_one();
+        let response = slow.await.unwrap().unwrap();
+        assert_eq!(response.headers()[SERVED_BY_HEADER].to_str().unwrap(), busy.to_string());
+        assert_eq!(balancer.in_flight(0) + balancer.in_flight(1), 0);
+    }
+
+    #[tokio::test]
+    async fn test_all_unhealthy_nodes_is_no_healthy_nodes() {
+        let health = Arc::new(RwLock::new(vec![false; 4]));
+        let balancer = LoadBalancer::new(health.clone(), LoadBalanceStrategy::RoundRobin);
+        let nodes = vec![Some("n0"), Some("n1"), Some("n2"), Some("n3")];
+
+        let err = balancer.execute(&nodes, |node| Ok::<_, String>(*node)).await.unwrap_err();
+        assert_eq!(err, ClusterError::NoHealthyNodes);
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+        // Healthy but never started
+        health.write().await[2] = true;
+        let err = balancer.execute(&[Some("n0"), None, None, None], |node| Ok::<_, String>(*node)).await.unwrap_err();
+        assert_eq!(err, ClusterError::NodeUnavailable(2));
+        assert_eq!(err.status_code(), StatusCode::SERVICE_UNAVAILABLE);
+
+        assert_eq!(balancer.execute(&nodes, |node| Ok::<_, String>(*node)).await.unwrap(), (2, "n2"));
+    }
+
+    #[tokio::test]
+    async fn test_failed_operation_is_server_error() {
+        let balancer = LoadBalancer::new(Arc::new(RwLock::new(vec![false, true])), LoadBalanceStrategy::RoundRobin);
+        let err = balancer.execute(&[Some(0u8), Some(1u8)], |_| Err::<(), _>("storage offline")).await.unwrap_err();
+        assert_eq!(err, ClusterError::OperationFailed { node: 1, reason: "storage offline".to_string() });
+        assert_eq!(err.status_code(), StatusCode::INTERNAL_SERVER_ERROR);
+    }
+
+    #[test]
+    fn test_stalled_node_flips_to_unhealthy() {
+        let sample = |block_height, peer_count| Some(HealthSample { block_height, peer_count });
+
+        assert_eq!(judge_health(&[sample(100, 3), sample(100, 3)]), vec![true, true]);
+        // The cluster moved on and node 1 did not follow
+        assert_eq!(judge_health(&[sample(100 + MAX_HEIGHT_LAG + 1, 3), sample(100, 3)]), vec![true, false]);
+        assert_eq!(judge_health(&[sample(105, 3), sample(105 - MAX_HEIGHT_LAG, 3)]), vec![true, true]);
+    }
+
+    #[test]
+    fn test_nodes_without_new_blocks_stay_healthy() {
+        // Non-validator nodes never commit blocks; at an equal height they are all in sync
+        let idle = Some(HealthSample { block_height: 0, peer_count: 2 });
+        for _ in 0..3 {
+            assert_eq!(judge_health(&[idle, idle, idle]), vec![true, true, true]);
+        }
+    }
+
+    #[test]
+    fn test_isolated_or_stopped_node_is_unhealthy() {
+        let samples = [
+            Some(HealthSample { block_height: 10, peer_count: 0 }),
+            Some(HealthSample { block_height: 10, peer_count: 1 }),
+            None,
+        ];
+        assert_eq!(judge_health(&samples), vec![false, true, false]);
+    }
+}
@@ -2,5 +2,7 @@
 
 pub mod fractal_node;
 pub mod ecosystem_miner;
+pub mod load_balancer;
+pub mod fork_choice;
 pub use fractal_node::{FractalNode, NodeConfig, NodeInfo, NodeError};
 pub use ecosystem_miner::EcosystemMiner;
\ No newline at end of file
@@ -1,21 +1,28 @@
 use std::collections::HashMap;
 use std::net::IpAddr;
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
 use std::time::{Duration, Instant};
 use axum::{
-    extract::ConnectInfo,
-    http::{Request, StatusCode},
+    extract::{ConnectInfo, State},
+    http::{header, HeaderMap, Request, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
     Json,
 };
 use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use crate::api_auth::{ApiKey, API_KEY_HEADER};
+use crate::security::anomaly_detection::{
+    AnomalyAction, AnomalyDetector, AnomalyResponsePolicy, DetectionResult, EventType, FeatureVector, SecurityEvent,
+};
 
 
+/// Token bucket: `burst` requests at once, refilled at `requests_per_minute`
 #[derive(Debug, Clone)]
 pub struct RateLimitConfig {
     pub requests_per_minute: u32,
-    pub burst_limit: u32,
+    pub burst: u32,
     pub cleanup_interval: Duration,
 }
 
@@ -23,67 +30,52 @@ impl Default for RateLimitConfig {
     fn default() -> Self {
         Self {
             requests_per_minute: 180, // Balanced for public API access
-            burst_limit: 60, // Reasonable burst limit for legitimate usage
+            burst: 60, // Reasonable burst for legitimate usage
             cleanup_interval: Duration::from_secs(300), // 5 minutes
         }
     }
 }
 
 #[derive(Debug, Clone)]
-struct ClientInfo {
-    request_count: u32,
-    last_request: Instant,
-    first_request_in_window: Instant,
+struct TokenBucket {
+    tokens: f64,
+    last_refill: Instant,
 }
 
-impl ClientInfo {
-    fn new() -> Self {
-        let now = Instant::now();
+impl TokenBucket {
+    fn full(config: &RateLimitConfig) -> Self {
         Self {
-            request_count: 1,
-            last_request: now,
-            first_request_in_window: now,
+            tokens: config.burst as f64,
+            last_refill: Instant::now(),
         }
     }
 
+    fn refill(&mut self, config: &RateLimitConfig, now: Instant) {
+        let per_second = config.requests_per_minute as f64 / 60.0;
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * per_second).min(config.burst as f64);
+        self.last_refill = now;
+    }
 
-
-    fn update(&mut self, config: &RateLimitConfig) -> bool {
-        let now = Instant::now();
-        let window_duration = Duration::from_secs(60);
-        
-        // Check if we need to reset the window
-        if now.duration_since(self.first_request_in_window) >= window_duration {
-            self.request_count = 1;
-            self.first_request_in_window = now;
-            self.last_request = now;
-            return true;
-        }
-        
-        // For dashboard endpoints, be more lenient with burst limits
-        // Only check rate limit per minute, not burst limit for normal operations
-        if self.request_count >= config.requests_per_minute {
-            return false;
-        }
-        
-        // Only apply burst limit if requests are coming too fast (within 1 second)
-        if now.duration_since(self.last_request) < Duration::from_millis(100) {
-            if self.request_count >= config.burst_limit {
-                return false;
-            }
+    /// Take one token, or return how long until one is available
+    fn take(&mut self, config: &RateLimitConfig) -> Result<(), Duration> {
+        self.refill(config, Instant::now());
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
         }
-        
-        self.request_count += 1;
-        self.last_request = now;
-        true
+        let per_second = config.requests_per_minute.max(1) as f64 / 60.0;
+        Err(Duration::from_secs_f64((1.0 - self.tokens) / per_second))
     }
 }
 
 #[derive(Debug)]
 pub struct RateLimiter {
-    clients: Arc<Mutex<HashMap<IpAddr, ClientInfo>>>,
+    clients: Arc<Mutex<HashMap<IpAddr, TokenBucket>>>,
     config: RateLimitConfig,
     last_cleanup: Arc<Mutex<Instant>>,
+    allowed: AtomicU64,
+    rejected: AtomicU64,
 }
 
 impl RateLimiter {
@@ -92,72 +84,176 @@ impl RateLimiter {
             clients: Arc::new(Mutex::new(HashMap::new())),
             config,
             last_cleanup: Arc::new(Mutex::new(Instant::now())),
+            allowed: AtomicU64::new(0),
+            rejected: AtomicU64::new(0),
         }
     }
 
-    pub fn check_rate_limit(&self, ip: IpAddr) -> bool {
-        let mut clients = self.clients.lock().unwrap();
-        
-        match clients.get_mut(&ip) {
-            Some(client_info) => {
-                client_info.update(&self.config)
-            }
-            None => {
-                clients.insert(ip, ClientInfo::new());
-                true
-            }
-        }
+    /// Charge one request to `ip`; on refusal, the wait before a retry can succeed
+    pub fn try_acquire(&self, ip: IpAddr) -> Result<(), Duration> {
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        let outcome = clients
+            .entry(ip)
+            .or_insert_with(|| TokenBucket::full(&self.config))
+            .take(&self.config);
+
+        let counter = if outcome.is_ok() { &self.allowed } else { &self.rejected };
+        counter.fetch_add(1, Ordering::Relaxed);
+        outcome
     }
 
-    pub fn get_rate_limit_response(&self) -> Response {
-        let error_response = json!({
-            "success": false,
-            "error": {
-                "code": "RATE_LIMIT_EXCEEDED",
-                "message": "Rate limit exceeded. Please try again later.",
-                "details": {
-                    "requests_per_minute": self.config.requests_per_minute,
-                    "burst_limit": self.config.burst_limit
-                }
-            }
-        });
+    pub fn check_rate_limit(&self, ip: IpAddr) -> bool {
+        self.try_acquire(ip).is_ok()
+    }
 
-        (StatusCode::TOO_MANY_REQUESTS, Json(error_response)).into_response()
+    pub fn get_rate_limit_response(&self, retry_after: Duration) -> Response {
+        rate_limit_response(&self.config, retry_after)
     }
 
     pub fn cleanup_old_entries(&self) {
-        let mut last_cleanup = self.last_cleanup.lock().unwrap();
+        let mut last_cleanup = self.last_cleanup.lock().unwrap_or_else(PoisonError::into_inner);
         let now = Instant::now();
         
         if now.duration_since(*last_cleanup) < self.config.cleanup_interval {
             return;
         }
         
-        let mut clients = self.clients.lock().unwrap();
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
         let cutoff = now - Duration::from_secs(300); // Remove entries older than 5 minutes
         
-        clients.retain(|_, client_info| {
-            client_info.last_request > cutoff
+        clients.retain(|_, bucket| {
+            bucket.last_refill > cutoff
         });
         
         *last_cleanup = now;
     }
 
     pub fn get_stats(&self) -> Value {
-        let clients = self.clients.lock().unwrap();
+        let clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
         json!({
             "active_clients": clients.len(),
+            "allowed_requests": self.allowed.load(Ordering::Relaxed),
+            "rejected_requests": self.rejected.load(Ordering::Relaxed),
             "config": {
                 "requests_per_minute": self.config.requests_per_minute,
-                "burst_limit": self.config.burst_limit
+                "burst": self.config.burst
             }
         })
     }
 }
 
-// Global rate limiter instance
-lazy_static::lazy_static! {
-    pub static ref GLOBAL_RATE_LIMITER: RateLimiter = RateLimiter::new(RateLimitConfig::default());
+/// 429 with a Retry-After for a request refused under `config`
+fn rate_limit_response(config: &RateLimitConfig, retry_after: Duration) -> Response {
+    // Round up so a client that waits exactly this long finds a token
+    let retry_after_secs = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+    let error_response = json!({
+        "success": false,
+        "error": {
+            "code": "RATE_LIMIT_EXCEEDED",
+            "message": "Rate limit exceeded. Please try again later.",
+            "details": {
+                "requests_per_minute": config.requests_per_minute,
+                "burst": config.burst,
+                "retry_after_secs": retry_after_secs
+            }
+        }
+    });
+
+    (
+        StatusCode::TOO_MANY_REQUESTS,
+        [(header::RETRY_AFTER, retry_after_secs.to_string())],
+        Json(error_response),
+    ).into_response()
+}
+
+/// The client an RPC request is charged to: its API key when it sends a known one, otherwise its IP
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ClientId {
+    ApiKey(String),
+    Ip(IpAddr),
+}
+
+impl ClientId {
+    /// An unknown key falls back to the IP, so made-up keys neither escape a throttle on the
+    /// address nor grow the client map
+    pub async fn from_request(ip: IpAddr, headers: &HeaderMap) -> Self {
+        let Some(key) = headers.get(API_KEY_HEADER).and_then(|value| value.to_str().ok()) else {
+            return ClientId::Ip(ip);
+        };
+        match ApiKey::from_headers(headers).await {
+            Ok(_) => ClientId::ApiKey(key.to_string()),
+            Err(_) => ClientId::Ip(ip),
+        }
+    }
+}
+
+impl std::fmt::Display for ClientId {
+    // Keys are logged by fingerprint, never verbatim
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClientId::ApiKey(key) => write!(f, "api key {}", &hex::encode(Sha256::digest(key.as_bytes()))[..12]),
+            ClientId::Ip(ip) => write!(f, "{}", ip),
+        }
+    }
+}
+
+/// Token buckets per RPC client, independent of the per-IP HTTP limits
+#[derive(Debug)]
+pub struct ClientRateLimiter {
+    clients: Mutex<HashMap<ClientId, TokenBucket>>,
+    config: RateLimitConfig,
+    last_cleanup: Mutex<Instant>,
+}
+
+impl ClientRateLimiter {
+    pub fn new(config: RateLimitConfig) -> Self {
+        Self {
+            clients: Mutex::new(HashMap::new()),
+            config,
+            last_cleanup: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Charge one request to `client`; on refusal, the wait before a retry can succeed
+    pub fn try_acquire(&self, client: &ClientId) -> Result<(), Duration> {
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        clients
+            .entry(client.clone())
+            .or_insert_with(|| TokenBucket::full(&self.config))
+            .take(&self.config)
+    }
+
+    /// Fraction of the burst budget `client` has drained
+    pub fn usage(&self, client: &ClientId) -> f64 {
+        let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+        clients
+            .get_mut(client)
+            .map(|bucket| {
+                bucket.refill(&self.config, Instant::now());
+                1.0 - bucket.tokens / self.config.burst.max(1) as f64
+            })
+            .unwrap_or(0.0)
+    }
+
+    pub fn get_rate_limit_response(&self, retry_after: Duration) -> Response {
+        rate_limit_response(&self.config, retry_after)
+    }
+
+    /// Forget clients idle for a full cleanup interval
+    pub fn cleanup_old_entries(&self) {
+        let mut last_cleanup = self.last_cleanup.lock().unwrap_or_else(PoisonError::into_inner);
+        let now = Instant::now();
+        if now.duration_since(*last_cleanup) < self.config.cleanup_interval {
+            return;
+        }
+
+        let cutoff = now - self.config.cleanup_interval;
+        self.clients
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .retain(|_, bucket| bucket.last_refill > cutoff);
+        *last_cleanup = now;
+    }
 }
 
 // Endpoint-specific rate limiters
@@ -173,17 +269,17 @@ impl Default for EndpointRateLimiters {
         Self {
             mining: RateLimiter::new(RateLimitConfig {
                 requests_per_minute: 60, // Reduced for mining operations
-                burst_limit: 15, // Lower burst for mining
+                burst: 15, // Lower burst for mining
                 cleanup_interval: Duration::from_secs(300),
             }),
             wallet: RateLimiter::new(RateLimitConfig {
                 requests_per_minute: 120, // Moderate for wallet operations
-                burst_limit: 25, // Reasonable burst for wallet
+                burst: 25, // Reasonable burst for wallet
                 cleanup_interval: Duration::from_secs(300),
             }),
             admin: RateLimiter::new(RateLimitConfig {
                 requests_per_minute: 5, // Very strict for admin
-                burst_limit: 1, // Minimal burst for admin
+                burst: 1, // Minimal burst for admin
                 cleanup_interval: Duration::from_secs(300),
             }),
         }
@@ -191,6 +287,16 @@ impl Default for EndpointRateLimiters {
 }
 
 impl EndpointRateLimiters {
+    /// Limiter for the endpoint class `path` belongs to, if any
+    pub fn for_path(&self, path: &str) -> Option<&RateLimiter> {
+        match path {
+            p if p.contains("/mining/") || p.contains("/miner/") => Some(&self.mining),
+            p if p.contains("/wallet/") || p.contains("/device/") => Some(&self.wallet),
+            p if p.contains("/admin/") => Some(&self.admin),
+            _ => None,
+        }
+    }
+
     pub fn get_all_stats(&self) -> Value {
         json!({
             "mining": self.mining.get_stats(),
@@ -200,41 +306,165 @@ impl EndpointRateLimiters {
     }
 }
 
+/// Per-IP limits: one bucket across all requests, plus one per endpoint class
+#[derive(Debug)]
+pub struct RequestRateLimits {
+    pub global: RateLimiter,
+    pub endpoints: EndpointRateLimiters,
+}
+
+impl RequestRateLimits {
+    pub fn new(global: RateLimitConfig, endpoints: EndpointRateLimiters) -> Self {
+        Self {
+            global: RateLimiter::new(global),
+            endpoints,
+        }
+    }
+
+    /// Charge a request from `ip` to `path` up front, whatever the handler makes of it, so failed
+    /// PIN or key attempts cost the same as any other; a response means it must be refused
+    pub fn check(&self, ip: IpAddr, path: &str) -> Option<Response> {
+        self.global.cleanup_old_entries();
+        if let Err(retry_after) = self.global.try_acquire(ip) {
+            return Some(self.global.get_rate_limit_response(retry_after));
+        }
+
+        let limiter = self.endpoints.for_path(path)?;
+        limiter.cleanup_old_entries();
+        limiter.try_acquire(ip).err().map(|retry_after| limiter.get_rate_limit_response(retry_after))
+    }
+
+    pub fn get_stats(&self) -> Value {
+        json!({
+            "global_rate_limiter": self.global.get_stats(),
+            "endpoint_rate_limiters": self.endpoints.get_all_stats()
+        })
+    }
+}
+
 lazy_static::lazy_static! {
-    pub static ref ENDPOINT_RATE_LIMITERS: EndpointRateLimiters = EndpointRateLimiters::default();
+    pub static ref REQUEST_RATE_LIMITS: Arc<RequestRateLimits> = Arc::new(
+        RequestRateLimits::new(RateLimitConfig::default(), EndpointRateLimiters::default())
+    );
 }
 
-// Rate limiting middleware
+// Rate limiting middleware; apply with `from_fn_with_state(REQUEST_RATE_LIMITS.clone(), ...)`
 pub async fn rate_limit_middleware(
+    State(limits): State<Arc<RequestRateLimits>>,
     ConnectInfo(addr): ConnectInfo<std::net::SocketAddr>,
     request: Request<axum::body::Body>,
     next: Next,
 ) -> Response {
-    let ip = addr.ip();
-    
-    // Perform cleanup periodically
-    GLOBAL_RATE_LIMITER.cleanup_old_entries();
-    
-    // Check rate limit
-    if !GLOBAL_RATE_LIMITER.check_rate_limit(ip) {
-        return GLOBAL_RATE_LIMITER.get_rate_limit_response();
+    if let Some(response) = limits.check(addr.ip(), request.uri().path()) {
+        return response;
     }
     
-    // Check endpoint-specific rate limits
-    let path = request.uri().path();
-    let endpoint_limiter = match path {
-        p if p.contains("/mining/") || p.contains("/miner/") => Some(&ENDPOINT_RATE_LIMITERS.mining),
-        p if p.contains("/wallet/") || p.contains("/device/") => Some(&ENDPOINT_RATE_LIMITERS.wallet),
-        p if p.contains("/admin/") => Some(&ENDPOINT_RATE_LIMITERS.admin),
-        _ => None,
-    };
-    
-    if let Some(limiter) = endpoint_limiter {
-        if !limiter.check_rate_limit(ip) {
-            return limiter.get_rate_limit_response();
+    next.run(request).await
+}
+
+/// Scores requests with the anomaly detector and applies the operator's response policy
+pub struct RequestAnomalyGuard {
+    detector: AnomalyDetector,
+    policy: AnomalyResponsePolicy,
+    requests: ClientRateLimiter,
+    throttle: ClientRateLimiter,
+}
+
+impl RequestAnomalyGuard {
+    /// `requests` sizes the window a client's request rate is scored against;
+    /// `throttle` is the extra limit applied under `AnomalyAction::Throttle`
+    pub fn new(policy: AnomalyResponsePolicy, requests: RateLimitConfig, throttle: RateLimitConfig) -> Self {
+        Self {
+            detector: AnomalyDetector::new(),
+            policy,
+            requests: ClientRateLimiter::new(requests),
+            throttle: ClientRateLimiter::new(throttle),
         }
     }
-    
+
+    pub fn policy(&self) -> &AnomalyResponsePolicy {
+        &self.policy
+    }
+
+    /// Count a request from `client` and return the anomaly patterns it matches
+    pub fn inspect(&self, client: &ClientId) -> Vec<DetectionResult> {
+        self.requests.cleanup_old_entries();
+        self.throttle.cleanup_old_entries();
+        // Only the usage is scored; an exhausted budget is not itself a refusal here
+        let _ = self.requests.try_acquire(client);
+
+        let event = SecurityEvent {
+            timestamp: std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs(),
+            event_type: EventType::RpcRequest,
+            source: client.to_string(),
+            target: "rpc".to_string(),
+            features: FeatureVector {
+                values: vec![self.requests.usage(client)],
+                labels: vec!["request_rate_usage".to_string()],
+            },
+            metadata: HashMap::new(),
+        };
+        self.detector.match_patterns(&event)
+    }
+
+    /// Apply the policy to `detections`; a response means the request must not proceed
+    pub fn respond(&self, client: &ClientId, detections: &[DetectionResult]) -> Option<Response> {
+        let action = self.policy.decide(detections)?;
+        let patterns: Vec<String> = detections.iter().map(|d| format!("{:?}", d.pattern)).collect();
+        log::warn!("Anomaly from {}: {:?} -> {:?}", client, patterns, action);
+
+        match action {
+            AnomalyAction::LogOnly => None,
+            AnomalyAction::Throttle => {
+                self.throttle.try_acquire(client).err().map(|retry_after| self.throttle.get_rate_limit_response(retry_after))
+            }
+            AnomalyAction::Reject => {
+                let error_response = json!({
+                    "success": false,
+                    "error": {
+                        "code": "ANOMALY_REJECTED",
+                        "message": "Request blocked by anomaly detection.",
+                        "details": {
+                            "patterns": patterns
+                        }
+                    }
+                });
+                Some((StatusCode::FORBIDDEN, Json(error_response)).into_response())
+            }
+        }
+    }
+}
+
+lazy_static::lazy_static! {
+    pub static ref REQUEST_ANOMALY_GUARD: RequestAnomalyGuard = RequestAnomalyGuard::new(
+        AnomalyResponsePolicy::from_env().unwrap_or_else(|e| {
+            log::warn!("Invalid anomaly response policy ({}), using defaults", e);
+            AnomalyResponsePolicy::default()
+        }),
+        RateLimitConfig::default(),
+        RateLimitConfig {
+            requests_per_minute: 20, // Throttled clients get a fraction of the normal budget
+            burst: 5,
+            cleanup_interval: Duration::from_secs(300),
+        },
+    );
+}
+
+// Anomaly response middleware
+pub async fn anomaly_response_middleware(
+    ConnectInfo(addr): ConnectInfo<std::net::SocketAddr>,
+    request: Request<axum::body::Body>,
+    next: Next,
+) -> Response {
+    let client = ClientId::from_request(addr.ip(), request.headers()).await;
+    let detections = REQUEST_ANOMALY_GUARD.inspect(&client);
+    if let Some(response) = REQUEST_ANOMALY_GUARD.respond(&client, &detections) {
+        return response;
+    }
+
     next.run(request).await
 }
 
@@ -266,13 +496,14 @@ pub fn get_client_ip(request: &Request<axum::body::Body>) -> Option<IpAddr> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::security::anomaly_detection::AttackPattern;
     use std::net::Ipv4Addr;
 
     #[test]
     fn test_rate_limiter_basic() {
         let config = RateLimitConfig {
             requests_per_minute: 5,
-            burst_limit: 3,
+            burst: 3,
             cleanup_interval: Duration::from_secs(60),
         };
         let limiter = RateLimiter::new(config);
@@ -288,15 +519,149 @@ mod tests {
     }
 
     #[test]
-    fn test_client_info_window_reset() {
-        let config = RateLimitConfig::default();
-        let mut client = ClientInfo::new();
-        
-        // Simulate old timestamp
-        client.first_request_in_window = Instant::now() - Duration::from_secs(70);
-        
-        // Should allow request in new window
-        assert!(client.update(&config));
-        assert_eq!(client.request_count, 1);
+    fn test_bucket_refills_over_time() {
+        let config = RateLimitConfig {
+            requests_per_minute: 60,
+            burst: 2,
+            cleanup_interval: Duration::from_secs(60),
+        };
+        let mut bucket = TokenBucket::full(&config);
+        assert!(bucket.take(&config).is_ok());
+        assert!(bucket.take(&config).is_ok());
+        let retry_after = bucket.take(&config).unwrap_err();
+        assert!(retry_after <= Duration::from_secs(1));
+
+        // One token per second at 60 a minute, never above the burst
+        bucket.last_refill -= Duration::from_secs(10);
+        assert!(bucket.take(&config).is_ok());
+        assert!(bucket.take(&config).is_ok());
+        assert!(bucket.take(&config).is_err());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_middleware_returns_429_past_the_limit() {
+        use axum::{body::Body, routing::get, Router};
+        use tower::ServiceExt;
+
+        let limit = |requests_per_minute, burst| RateLimitConfig {
+            requests_per_minute,
+            burst,
+            cleanup_interval: Duration::from_secs(60),
+        };
+        let endpoints = EndpointRateLimiters {
+            mining: RateLimiter::new(limit(6, 3)),
+            wallet: RateLimiter::new(limit(6, 2)),
+            admin: RateLimiter::new(limit(60, 60)),
+        };
+        let limits = Arc::new(RequestRateLimits::new(limit(60, 10), endpoints));
+        let app = Router::new()
+            .route("/api/v1/mining/status", get(|| async { "ok" }))
+            .route("/api/v1/wallet/unlock", get(|| async { StatusCode::UNAUTHORIZED }))
+            .route("/api/v1/blocks", get(|| async { "ok" }))
+            .layer(axum::middleware::from_fn_with_state(limits.clone(), rate_limit_middleware));
+
+        let request = |path: &str, ip: [u8; 4]| {
+            let mut request = Request::get(path).body(Body::empty()).unwrap();
+            request.extensions_mut().insert(ConnectInfo(std::net::SocketAddr::from((ip, 4000))));
+            request
+        };
+
+        // The mining bucket holds 3; the 4th and later requests are refused
+        let mut statuses = Vec::new();
+        for _ in 0..6 {
+            let response = app.clone().oneshot(request("/api/v1/mining/status", [10, 0, 0, 1])).await.unwrap();
+            if response.status() == StatusCode::TOO_MANY_REQUESTS {
+                let retry_after: u64 = response.headers()[header::RETRY_AFTER].to_str().unwrap().parse().unwrap();
+                assert!((1..=10).contains(&retry_after));
+            }
+            statuses.push(response.status());
+        }
+        assert_eq!(&statuses[..3], &[StatusCode::OK; 3]);
+        assert!(statuses[3..].iter().all(|s| *s == StatusCode::TOO_MANY_REQUESTS));
+
+        // Other endpoints and other clients have their own buckets
+        let response = app.clone().oneshot(request("/api/v1/blocks", [10, 0, 0, 1])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = app.clone().oneshot(request("/api/v1/mining/status", [10, 0, 0, 2])).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // Failed unlock attempts spend their token like any other request
+        let mut statuses = Vec::new();
+        for _ in 0..3 {
+            statuses.push(app.clone().oneshot(request("/api/v1/wallet/unlock", [10, 0, 0, 3])).await.unwrap().status());
+        }
+        assert_eq!(statuses, vec![StatusCode::UNAUTHORIZED, StatusCode::UNAUTHORIZED, StatusCode::TOO_MANY_REQUESTS]);
+
+        // A concurrent burst gets no more than the bucket holds
+        let burst = (0..6).map(|_| app.clone().oneshot(request("/api/v1/mining/status", [10, 0, 0, 4])));
+        let statuses: Vec<StatusCode> = futures::future::join_all(burst).await.into_iter().map(|r| r.unwrap().status()).collect();
+        assert_eq!(statuses.iter().filter(|s| **s == StatusCode::OK).count(), 3);
+
+        let stats = limits.get_stats();
+        assert_eq!(stats["endpoint_rate_limiters"]["mining"]["allowed_requests"], 7);
+        assert_eq!(stats["endpoint_rate_limiters"]["mining"]["rejected_requests"], 6);
+        assert_eq!(stats["endpoint_rate_limiters"]["wallet"]["rejected_requests"], 1);
+        assert_eq!(stats["global_rate_limiter"]["allowed_requests"], 17);
+    }
+
+    fn flooded_outcomes(policy: AnomalyResponsePolicy) -> Vec<Option<StatusCode>> {
+        let limit = |n| RateLimitConfig {
+            requests_per_minute: n,
+            burst: n,
+            cleanup_interval: Duration::from_secs(60),
+        };
+        let guard = RequestAnomalyGuard::new(policy, limit(10), limit(2));
+        let client = ClientId::Ip(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 7)));
+
+        (1..=12)
+            .map(|n| {
+                let detections = guard.inspect(&client);
+                // The same flood pattern is flagged from the 9th request on
+                assert_eq!(detections.iter().map(|d| &d.pattern).collect::<Vec<_>>(), if n >= 9 { vec![&AttackPattern::RequestFlood] } else { vec![] });
+                guard.respond(&client, &detections).map(|r| r.status())
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_anomaly_policy_outcomes() {
+        let log_only = flooded_outcomes(AnomalyResponsePolicy::uniform(AnomalyAction::LogOnly));
+        assert!(log_only.iter().all(Option::is_none));
+
+        let throttle = flooded_outcomes(AnomalyResponsePolicy::uniform(AnomalyAction::Throttle));
+        assert!(throttle[..10].iter().all(Option::is_none));
+        assert!(throttle[10..].iter().all(|s| *s == Some(StatusCode::TOO_MANY_REQUESTS)));
+
+        let reject = flooded_outcomes(AnomalyResponsePolicy::uniform(AnomalyAction::Reject));
+        assert!(reject[..8].iter().all(Option::is_none));
+        assert!(reject[8..].iter().all(|s| *s == Some(StatusCode::FORBIDDEN)));
+    }
+
+    #[tokio::test]
+    async fn test_clients_are_keyed_by_known_api_key_before_ip() {
+        let _db = crate::rpc_storage::use_test_db();
+        let ip = IpAddr::V4(Ipv4Addr::new(10, 0, 0, 8));
+        let mut headers = HeaderMap::new();
+        assert_eq!(ClientId::from_request(ip, &headers).await, ClientId::Ip(ip));
+        // A key nobody issued is charged to the address it came from
+        headers.insert(API_KEY_HEADER, "client-a-key".parse().unwrap());
+        assert_eq!(ClientId::from_request(ip, &headers).await, ClientId::Ip(ip));
+
+        crate::api_auth::register_api_key("client-a-key", crate::api_auth::Scope::Readonly).await.unwrap();
+        let client_a = ClientId::from_request(ip, &headers).await;
+        assert_eq!(client_a, ClientId::ApiKey("client-a-key".to_string()));
+        assert!(!client_a.to_string().contains("client-a-key"));
+
+        // Two keys behind one address draw on separate budgets
+        let limiter = ClientRateLimiter::new(RateLimitConfig {
+            requests_per_minute: 1,
+            burst: 1,
+            cleanup_interval: Duration::from_secs(60),
+        });
+        let client_b = ClientId::ApiKey("client-b-key".to_string());
+        assert!(limiter.try_acquire(&client_a).is_ok());
+        assert!(limiter.try_acquire(&client_a).is_err());
+        assert!(limiter.try_acquire(&client_b).is_ok());
+        assert!(limiter.try_acquire(&ClientId::Ip(ip)).is_ok());
+    }
+}
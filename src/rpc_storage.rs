@@ -1,9 +1,80 @@
 use crate::storage::{LedgerDB, StorageError};
+use crate::shared::{TxError, spend_day, validate_transfer, validate_tx_address, validate_tx_fields};
 use serde::{Serialize, Deserialize};
 use std::sync::Arc;
 use once_cell::sync::Lazy;
 use serde_json;
 use hex;
+use aes_gcm::{Aes256Gcm, Nonce, aead::{Aead, KeyInit}};
+use sha2::{Sha256, Digest};
+
+/// Marks a device private key sealed under a key stretched from the PIN with Argon2id and a per-entry salt
+const ENCRYPTED_KEY_PREFIX: &str = "argon2id-aes256gcm:";
+/// Earlier entries sealed under SHA-256 of the PIN; re-sealed on their next successful read
+const LEGACY_ENCRYPTED_KEY_PREFIX: &str = "aes256gcm:";
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+
+/// True for a bare 32-byte hex key, i.e. a private key nobody encrypted
+pub fn looks_like_plaintext_key(value: &str) -> bool {
+    let value = value.trim().trim_start_matches("0x");
+    value.len() == 64 && value.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// AES-256 key stretched from the PIN; computed per request and never stored
+fn device_cipher(pin: &str, salt: &[u8]) -> Result<Aes256Gcm, StorageError> {
+    let mut key = [0u8; 32];
+    argon2::Argon2::default()
+        .hash_password_into(pin.as_bytes(), salt, &mut key)
+        .map_err(|e| StorageError::Serialization(format!("Failed to derive key: {}", e)))?;
+    Ok(Aes256Gcm::new(&key.into()))
+}
+
+/// AES-256-GCM under a key derived from the device PIN; the salt and a fresh nonce are stored with the ciphertext
+fn encrypt_private_key(private_key: &str, pin: &str) -> Result<String, StorageError> {
+    let salt: [u8; SALT_LEN] = rand::random();
+    let nonce: [u8; NONCE_LEN] = rand::random();
+    let ciphertext = device_cipher(pin, &salt)?
+        .encrypt(Nonce::from_slice(&nonce), private_key.as_bytes())
+        .map_err(|e| StorageError::Serialization(format!("Failed to encrypt private key: {}", e)))?;
+    Ok(format!("{}{}{}{}", ENCRYPTED_KEY_PREFIX, hex::encode(salt), hex::encode(nonce), hex::encode(ciphertext)))
+}
+
+/// Decrypt a sealed entry; None for an entry that predates encryption
+fn decrypt_private_key(stored: &str, pin: &str) -> Result<Option<String>, StorageError> {
+    let (sealed, salt_len) = if let Some(sealed) = stored.strip_prefix(ENCRYPTED_KEY_PREFIX) {
+        (sealed, SALT_LEN)
+    } else if let Some(sealed) = stored.strip_prefix(LEGACY_ENCRYPTED_KEY_PREFIX) {
+        (sealed, 0)
+    } else {
+        return Ok(None);
+    };
+    let bytes = hex::decode(sealed).map_err(|e| StorageError::Serialization(e.to_string()))?;
+    if bytes.len() < salt_len + NONCE_LEN {
+        return Err(StorageError::Serialization("Encrypted private key is truncated".to_string()));
+    }
+    let (salt, rest) = bytes.split_at(salt_len);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LEN);
+    let cipher = if salt_len == 0 {
+        Aes256Gcm::new(&Sha256::digest(pin.as_bytes()))
+    } else {
+        device_cipher(pin, salt)?
+    };
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| StorageError::Decryption("wrong PIN or corrupted private key".to_string()))?;
+    String::from_utf8(plaintext).map(Some).map_err(|e| StorageError::Serialization(e.to_string()))
+}
+
+/// Argon2id PHC string stored in place of the PIN itself
+fn hash_pin(pin: &str) -> Result<String, StorageError> {
+    use argon2::password_hash::{rand_core::OsRng, PasswordHasher, SaltString};
+    let salt = SaltString::generate(&mut OsRng);
+    argon2::Argon2::default()
+        .hash_password(pin.as_bytes(), &salt)
+        .map(|hash| hash.to_string())
+        .map_err(|e| StorageError::Serialization(format!("Failed to hash PIN: {}", e)))
+}
 
 /// Global LevelDB instance for RPC server
 static RPC_DB: Lazy<Arc<LedgerDB>> = Lazy::new(|| {
@@ -11,8 +82,640 @@ static RPC_DB: Lazy<Arc<LedgerDB>> = Lazy::new(|| {
     Arc::new(LedgerDB::open(&rpc_data_dir).expect("Failed to open RPC storage database"))
 });
 
-/// Transaction structure for RPC storage
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Database installed by `RPCStorage::use_database`, used instead of `RPC_DB` while set
+static RPC_DB_OVERRIDE: std::sync::RwLock<Option<Arc<LedgerDB>>> = std::sync::RwLock::new(None);
+
+/// Held for as long as an override is installed, so two never overlap
+static RPC_DB_OVERRIDE_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// The database every `RPCStorage` call goes to
+fn rpc_db() -> Arc<LedgerDB> {
+    let installed = RPC_DB_OVERRIDE.read().unwrap_or_else(std::sync::PoisonError::into_inner).clone();
+    installed.unwrap_or_else(|| Arc::clone(&RPC_DB))
+}
+
+/// Keeps the database passed to `RPCStorage::use_database` in place; dropping it restores `RPC_DB`
+pub struct DatabaseOverride {
+    _exclusive: std::sync::MutexGuard<'static, ()>,
+}
+
+impl Drop for DatabaseOverride {
+    fn drop(&mut self) {
+        *RPC_DB_OVERRIDE.write().unwrap_or_else(std::sync::PoisonError::into_inner) = None;
+    }
+}
+
+/// A fresh database for one test; RPC storage uses it until the returned value drops
+#[cfg(test)]
+pub(crate) struct TestDb {
+    _override: DatabaseOverride,
+    _dir: tempfile::TempDir,
+}
+
+#[cfg(test)]
+pub(crate) fn use_test_db() -> TestDb {
+    let dir = tempfile::tempdir().unwrap();
+    let db = Arc::new(LedgerDB::open(dir.path()).unwrap());
+    TestDb { _override: RPCStorage::use_database(db), _dir: dir }
+}
+
+/// Serializes appends to the transaction log so sequence numbers are never reused
+static TX_LOG_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+/// Serializes tip updates so a lower block never overwrites a higher one
+static BLOCK_TIP_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+/// Every block written by `store_block`, for live subscribers
+pub static BLOCK_EVENTS: Lazy<tokio::sync::broadcast::Sender<Block>> =
+    Lazy::new(|| tokio::sync::broadcast::channel(256).0);
+
+/// Per-address locks so read-modify-writes of one balance never interleave
+static ADDRESS_LOCKS: Lazy<std::sync::Mutex<std::collections::HashMap<String, Arc<tokio::sync::Mutex<()>>>>> =
+    Lazy::new(|| std::sync::Mutex::new(std::collections::HashMap::new()));
+
+/// Lock `addresses` in sorted order so transfers between the same pair cannot deadlock
+async fn lock_addresses(addresses: &[&str]) -> Vec<tokio::sync::OwnedMutexGuard<()>> {
+    let mut sorted = addresses.to_vec();
+    sorted.sort_unstable();
+    sorted.dedup();
+
+    let locks: Vec<Arc<tokio::sync::Mutex<()>>> = {
+        let mut locks = ADDRESS_LOCKS.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        if locks.len() > 1024 {
+            locks.retain(|_, lock| Arc::strong_count(lock) > 1);
+        }
+        sorted.iter().map(|address| locks.entry(address.to_string()).or_default().clone()).collect()
+    };
+
+    let mut guards = Vec::with_capacity(locks.len());
+    for lock in locks {
+        guards.push(lock.lock_owned().await);
+    }
+    guards
+}
+
+/// Serializes balance writes with the tracked supply counter
+static SUPPLY_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+const TRACKED_SUPPLY_KEY: &str = "tracked_supply";
+const GENESIS_SUPPLY_KEY: &str = "genesis_supply";
+/// Transactions read, and balances summed under SUPPLY_LOCK, per step of a supply audit
+const SUPPLY_AUDIT_PAGE: usize = 500;
+
+/// Value an address sent on one UTC day, checked against `TransferLimits::daily_limit`
+fn daily_spend_key(address: &str, day: u64) -> String {
+    format!("daily_spend:{}:{}", address, day)
+}
+
+/// Set once every stored block's hash is in the `block_hash:` index
+const BLOCK_HASH_INDEX_MARKER: &[u8] = b"block_hash_index_complete";
+static BLOCK_HASH_BACKFILL_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+
+/// Serializes read-modify-write of the persisted mempool
+static MEMPOOL_LOCK: Lazy<tokio::sync::Mutex<()>> = Lazy::new(|| tokio::sync::Mutex::new(()));
+/// Key of the legacy single-blob mempool, split into one record per transaction on restore
+const LEGACY_MEMPOOL_KEY: &[u8] = b"mempool";
+/// Prefix of persisted mempool records, one per pending transaction keyed by its hex hash
+const MEMPOOL_TX_PREFIX: &str = "mempool_tx:";
+
+/// Most addresses accepted by one batched balance query
+pub const MAX_BALANCE_BATCH: usize = 100;
+
+/// One address's entry in a batched balance query
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BalanceLookup {
+    Balance { balance: u64 },
+    Error { error: String },
+}
+
+/// Default seconds a pending transaction stays valid before it is evicted unmined
+pub const DEFAULT_MEMPOOL_TX_TTL_SECS: u64 = 24 * 3600;
+
+/// Lifetime given to transactions entering the mempool (env: MEMPOOL_TX_TTL_SECS)
+pub static MEMPOOL_TX_TTL: Lazy<u64> = Lazy::new(|| {
+    std::env::var("MEMPOOL_TX_TTL_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .filter(|ttl| *ttl > 0)
+        .unwrap_or(DEFAULT_MEMPOOL_TX_TTL_SECS)
+});
+
+/// Default seconds an expired transaction stays queryable as `expired` before its record is pruned
+pub const DEFAULT_EXPIRED_TX_RETENTION_SECS: u64 = 7 * 24 * 3600;
+
+/// How long expired-transaction records are kept (env: EXPIRED_TX_RETENTION_SECS)
+pub static EXPIRED_TX_RETENTION: Lazy<u64> = Lazy::new(|| {
+    std::env::var("EXPIRED_TX_RETENTION_SECS")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_EXPIRED_TX_RETENTION_SECS)
+});
+
+/// Most expired-transaction records pruned per pass
+const EXPIRED_TX_PRUNE_BATCH: usize = 1024;
+
+/// Pending consensus transaction as persisted, with the time this node first saw it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingTransaction {
+    pub transaction: crate::consensus::vortex_consensus::Transaction,
+    pub received_at: u64,
+    /// Last second the transaction may be mined; 0 for records persisted before expiry was stored
+    #[serde(default)]
+    pub valid_until: u64,
+}
+
+impl PendingTransaction {
+    pub fn expires_at(&self) -> u64 {
+        if self.valid_until == 0 {
+            self.received_at.saturating_add(*MEMPOOL_TX_TTL)
+        } else {
+            self.valid_until
+        }
+    }
+
+    pub fn is_expired(&self, now: u64) -> bool {
+        now > self.expires_at()
+    }
+
+    /// How this transaction is listed by the mempool endpoint at time `now`
+    pub fn to_entry(&self, now: u64) -> MempoolEntry {
+        let tx = &self.transaction;
+        MempoolEntry {
+            hash: format!("0x{}", hex::encode(tx.hash)),
+            from: crate::wallet::key_manager::KeyManager::address_from_public_key(&tx.from),
+            to: String::from_utf8_lossy(&tx.to).into_owned(),
+            amount: tx.amount,
+            nonce: tx.nonce,
+            fee: tx.vortex_fee,
+            received_at: self.received_at,
+            age_secs: now.saturating_sub(self.received_at),
+            valid_until: self.expires_at(),
+        }
+    }
+}
+
+/// What storing a block changed, kept while the block can still be reorganized out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BlockUndo {
+    /// Balances of the addresses the block touched, as they were before it
+    balances: std::collections::HashMap<String, u64>,
+    /// Mempool records of the transactions the block included, returned to the mempool if it is reverted
+    pending: Vec<PendingTransaction>,
+    /// Transfers the block included that were already settled when submitted over RPC; they moved no balances here
+    #[serde(default)]
+    settled: Vec<String>,
+}
+
+/// Where a transaction that is not in a block stands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PendingStatus {
+    /// Waiting in the mempool until `valid_until`
+    Pending { valid_until: u64 },
+    /// Evicted unmined once `valid_until` passed
+    Expired { valid_until: u64 },
+}
+
+/// A pending transaction as reported by the RPC API
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MempoolEntry {
+    pub hash: String,
+    pub from: String,
+    pub to: String,
+    pub amount: u64,
+    pub nonce: u64,
+    pub fee: f64,
+    pub received_at: u64,
+    pub age_secs: u64,
+    pub valid_until: u64,
+}
+
+/// Key of the legacy single-blob transaction registry, migrated into the log at startup (or first use)
+const LEGACY_TX_REGISTRY_KEY: &[u8] = b"transaction_hashes_registry";
+
+/// Number of log entries already copied into the per-address index
+const ADDRESS_INDEX_SYNCED_KEY: &str = "address_index_synced";
+
+/// Default maximum number of transactions returned per address in one history page
+pub const DEFAULT_ADDRESS_HISTORY_CAP: usize = 50;
+
+/// Per-address history cap shared by all wallet history endpoints (env: ADDRESS_HISTORY_CAP)
+pub static ADDRESS_HISTORY_CAP: Lazy<usize> = Lazy::new(|| {
+    std::env::var("ADDRESS_HISTORY_CAP")
+        .ok()
+        .and_then(|v| v.parse::<usize>().ok())
+        .filter(|cap| *cap > 0)
+        .unwrap_or(DEFAULT_ADDRESS_HISTORY_CAP)
+});
+
+/// One page of an address's transaction history
+#[derive(Clone, Debug)]
+pub struct HistoryPage<T> {
+    pub items: Vec<T>,
+    /// Offset to pass back as `cursor` to fetch the next page, if any
+    pub next_cursor: Option<usize>,
+    pub total_count: usize,
+}
+
+/// Slice a newest-first history into a page of at most `cap` entries starting at `cursor`.
+/// Requests above the cap are clamped to it and a cursor is returned for the remainder.
+pub fn paginate_history<T>(history: Vec<T>, limit: Option<usize>, cursor: Option<usize>, cap: usize) -> HistoryPage<T> {
+    let cap = cap.max(1);
+    let limit = limit.unwrap_or(cap).min(cap);
+    let total_count = history.len();
+    let start = cursor.unwrap_or(0).min(total_count);
+    let end = start.saturating_add(limit).min(total_count);
+
+    let items = history.into_iter().skip(start).take(end - start).collect();
+    let next_cursor = if end < total_count { Some(end) } else { None };
+
+    HistoryPage { items, next_cursor, total_count }
+}
+
+/// Page of blocks; `next_cursor` is the height to pass back as `before_height`
+/// (or `after_height` when paging forward) for the next page
+#[derive(Clone, Debug)]
+pub struct BlockPage {
+    pub blocks: Vec<Block>,
+    pub next_cursor: Option<u64>,
+}
+
+/// Page of transactions, newest first; `next_cursor` is the hash to pass back as `before_hash`
+#[derive(Clone, Debug)]
+pub struct TransactionPage {
+    pub transactions: Vec<WalletTransaction>,
+    pub next_cursor: Option<String>,
+}
+
+/// Default smart-rate multipliers, one per step of the vortex sequence (1-2-4-8-7-5)
+pub const DEFAULT_VORTEX_PATTERN_MULTIPLIERS: [f64; 6] = [1.0, 1.2, 1.4, 1.8, 1.7, 1.5];
+
+/// Smart-rate multiplier applied at `block_height % sequence length`
+#[derive(Clone, Debug, PartialEq)]
+pub struct VortexPatternConfig {
+    multipliers: Vec<f64>,
+}
+
+impl Default for VortexPatternConfig {
+    fn default() -> Self {
+        Self { multipliers: DEFAULT_VORTEX_PATTERN_MULTIPLIERS.to_vec() }
+    }
+}
+
+impl VortexPatternConfig {
+    /// Requires exactly one positive, finite multiplier per vortex sequence step
+    pub fn new(multipliers: Vec<f64>) -> Result<Self, String> {
+        let expected = crate::math::VORTEX_SEQUENCE.len();
+        if multipliers.len() != expected {
+            return Err(format!(
+                "vortex pattern needs {} multipliers (one per sequence step), got {}",
+                expected, multipliers.len()
+            ));
+        }
+        if let Some(bad) = multipliers.iter().find(|m| !m.is_finite() || **m <= 0.0) {
+            return Err(format!("vortex pattern multiplier {} must be positive and finite", bad));
+        }
+        Ok(Self { multipliers })
+    }
+
+    /// Parse a comma-separated list such as "1.0,1.2,1.4,1.8,1.7,1.5"
+    pub fn parse(value: &str) -> Result<Self, String> {
+        let multipliers = value
+            .split(',')
+            .map(|v| v.trim().parse::<f64>().map_err(|e| format!("invalid multiplier '{}': {}", v.trim(), e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::new(multipliers)
+    }
+
+    /// Load from VORTEX_PATTERN_MULTIPLIERS, falling back to the defaults when unset
+    pub fn from_env() -> Result<Self, String> {
+        match std::env::var("VORTEX_PATTERN_MULTIPLIERS") {
+            Ok(value) => Self::parse(&value),
+            Err(_) => Ok(Self::default()),
+        }
+    }
+
+    pub fn multipliers(&self) -> &[f64] {
+        &self.multipliers
+    }
+
+    pub fn multiplier(&self, block_height: u64) -> f64 {
+        self.multipliers[(block_height % self.multipliers.len() as u64) as usize]
+    }
+}
+
+/// Vortex pattern shared by every smart-rate calculation (env: VORTEX_PATTERN_MULTIPLIERS)
+pub static VORTEX_PATTERN: Lazy<VortexPatternConfig> = Lazy::new(|| {
+    VortexPatternConfig::from_env().unwrap_or_else(|e| {
+        log::warn!("Invalid VORTEX_PATTERN_MULTIPLIERS ({}), using defaults", e);
+        VortexPatternConfig::default()
+    })
+});
+
+/// Widest height range returned by one difficulty history query
+pub const MAX_DIFFICULTY_HISTORY_SPAN: u64 = 1000;
+
+/// Difficulty recorded for one block height
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DifficultyPoint {
+    pub height: u64,
+    pub difficulty: u64,
+    pub timestamp: u64,
+}
+
+/// Current and next difficulty with the block time they were measured from.
+/// Reporting only: block validation checks each block against its own `difficulty`.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct DifficultyStatus {
+    pub difficulty: u64,
+    /// Retarget the recent block times call for; display-only, never required of the next block
+    pub next_difficulty: u64,
+    /// Mean seconds between the last DIFFICULTY_WINDOW blocks; None before two blocks exist
+    pub avg_block_time: Option<f64>,
+}
+
+/// Fork choice never reorganizes more than this many blocks below the current tip
+pub const MAX_REORG_DEPTH: u64 = 6;
+
+/// Default confirmations before a block and its transactions are finalized
+pub const DEFAULT_CONFIRMATION_DEPTH: u64 = MAX_REORG_DEPTH + 1;
+
+/// Confirmations required for finality (env: CONFIRMATION_DEPTH).
+/// Clamped above MAX_REORG_DEPTH so a finalized block can never be reorganized out.
+pub static CONFIRMATION_DEPTH: Lazy<u64> = Lazy::new(|| {
+    std::env::var("CONFIRMATION_DEPTH")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_CONFIRMATION_DEPTH)
+        .max(MAX_REORG_DEPTH + 1)
+});
+
+/// Number of blocks at or above `block_height` up to the tip; the tip block has one confirmation
+pub fn confirmations(block_height: u64, tip_height: u64) -> u64 {
+    if block_height > tip_height {
+        0
+    } else {
+        tip_height - block_height + 1
+    }
+}
+
+/// Whether a block at `block_height` is buried at least `depth` confirmations deep
+pub fn is_finalized(block_height: u64, tip_height: u64, depth: u64) -> bool {
+    confirmations(block_height, tip_height) >= depth
+}
+
+/// Fee charged to the sender of a device transfer, in microFVC (0.001 FVC)
+pub const DEVICE_TRANSFER_FEE: u64 = 1000;
+
+/// Fee debited from the sender on top of `tx.amount`; wallet and device sends charge the same fee
+/// unless the transaction records its own
+pub fn sender_fee(tx: &WalletTransaction) -> u64 {
+    if tx.fee > 0 {
+        return tx.fee;
+    }
+    match tx.transaction_type.as_str() {
+        "device_transfer" | "transfer" => DEVICE_TRANSFER_FEE,
+        _ => 0,
+    }
+}
+
+/// 32-byte fractal hash over everything that identifies a transfer, including the fee it pays; a retried
+/// submission hashes the same. The timestamp is left out so a retry sent later still matches; the nonce
+/// keeps distinct sends apart.
+pub fn transfer_content_hash(transaction_type: &str, from: &str, to: &str, amount: u64, nonce: u64, fee: u64) -> String {
+    let mut data = Vec::new();
+    for part in [transaction_type, from, to] {
+        data.extend_from_slice(part.as_bytes());
+        data.push(0);
+    }
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&nonce.to_le_bytes());
+    data.extend_from_slice(&fee.to_le_bytes());
+    let hash = crate::crypto::fractal_hash::FractalHasher::new(BLOCK_FRACTAL_LEVELS).fractal_hash(&data).fractal_hash;
+    format!("0x{}", hex::encode(hash))
+}
+
+/// Transaction types that move value from a signing sender
+pub const TRANSFER_TYPES: &[&str] = &["transfer", "device_transfer"];
+
+/// Length of the compressed public key a block transfer's `signature` starts with
+pub const TRANSFER_PUBLIC_KEY_LEN: usize = 33;
+
+/// Addresses whose history lists `tx`; reward and genesis sources are not real accounts
+fn indexed_parties(tx: &WalletTransaction) -> Vec<&str> {
+    let mut parties = vec![tx.to.as_str()];
+    let sourceless = matches!(tx.transaction_type.as_str(), "mining_reward" | "genesis");
+    if !sourceless && tx.from != tx.to {
+        parties.push(tx.from.as_str());
+    }
+    parties
+}
+
+/// Net effect of `tx` on `address`'s balance
+pub fn balance_delta(tx: &WalletTransaction, address: &str) -> i128 {
+    let mut delta = 0i128;
+    if tx.to == address {
+        delta += tx.amount as i128;
+    }
+    if indexed_parties(tx).get(1) == Some(&address) {
+        delta -= tx.amount as i128 + sender_fee(tx) as i128;
+    }
+    delta
+}
+
+/// Default confirmations a mining reward needs before it becomes spendable
+pub const DEFAULT_COINBASE_MATURITY: u64 = 10;
+
+/// Coinbase maturity window in blocks (env: COINBASE_MATURITY)
+pub static COINBASE_MATURITY: Lazy<u64> = Lazy::new(|| {
+    std::env::var("COINBASE_MATURITY")
+        .ok()
+        .and_then(|v| v.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_COINBASE_MATURITY)
+});
+
+/// Balance split into spendable funds and mining rewards still maturing
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BalanceBreakdown {
+    pub balance: u64,
+    pub spendable_balance: u64,
+    pub immature_balance: u64,
+}
+
+impl BalanceBreakdown {
+    /// Treat rewards to `address` with fewer than `maturity` confirmations as immature
+    pub fn compute(
+        address: &str,
+        balance: u64,
+        transactions: &[WalletTransaction],
+        tip_height: u64,
+        maturity: u64,
+    ) -> Self {
+        let immature: u64 = transactions.iter()
+            .filter(|tx| tx.transaction_type == "mining_reward" && tx.to == address)
+            .filter(|tx| confirmations(tx.block_height, tip_height) < maturity)
+            .fold(0u64, |sum, tx| sum.saturating_add(tx.amount));
+        let immature_balance = immature.min(balance);
+
+        Self {
+            balance,
+            spendable_balance: balance - immature_balance,
+            immature_balance,
+        }
+    }
+}
+
+/// Supply recomputed from genesis and the transaction log, compared with the stored balances
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SupplyAudit {
+    pub genesis_supply: u64,
+    pub mined_rewards: u64,
+    /// Genesis allocations plus mining rewards
+    pub total_issued: u64,
+    /// Sender fees debited without a matching credit
+    pub fees_burned: u64,
+    /// What balances should sum to: issued minus burned
+    pub expected_supply: u64,
+    /// Sum of the stored balances of every genesis allocation and transaction party
+    pub balance_supply: u64,
+    /// Running counter moved by every balance write
+    pub tracked_supply: u64,
+    /// `balance_supply - expected_supply`; positive means value appeared out of band
+    pub discrepancy: i64,
+}
+
+impl SupplyAudit {
+    pub fn compute(genesis_supply: u64, transactions: &[WalletTransaction], balance_supply: u64, tracked_supply: u64) -> Self {
+        let (mined_rewards, fees_burned) = Self::tally(transactions, (0, 0));
+        Self::from_totals(genesis_supply, mined_rewards, fees_burned, balance_supply, tracked_supply)
+    }
+
+    /// Add `transactions`' mining rewards and burned fees to `(mined_rewards, fees_burned)`
+    pub fn tally(transactions: &[WalletTransaction], (mut mined_rewards, mut fees_burned): (u64, u64)) -> (u64, u64) {
+        for tx in transactions {
+            if tx.transaction_type == "mining_reward" {
+                mined_rewards = mined_rewards.saturating_add(tx.amount);
+            } else if indexed_parties(tx).len() > 1 {
+                fees_burned = fees_burned.saturating_add(sender_fee(tx));
+            }
+        }
+        (mined_rewards, fees_burned)
+    }
+
+    pub fn from_totals(genesis_supply: u64, mined_rewards: u64, fees_burned: u64, balance_supply: u64, tracked_supply: u64) -> Self {
+        let total_issued = genesis_supply.saturating_add(mined_rewards);
+        let expected_supply = total_issued.saturating_sub(fees_burned);
+        let discrepancy = (balance_supply as i128 - expected_supply as i128)
+            .clamp(i64::MIN as i128, i64::MAX as i128) as i64;
+
+        Self {
+            genesis_supply,
+            mined_rewards,
+            total_issued,
+            fees_burned,
+            expected_supply,
+            balance_supply,
+            tracked_supply,
+            discrepancy,
+        }
+    }
+
+    /// Balances add up to what was issued, and the running counter agrees with them
+    pub fn is_consistent(&self) -> bool {
+        self.discrepancy == 0 && self.tracked_supply == self.balance_supply
+    }
+}
+
+/// Longest label accepted for an address
+pub const MAX_ADDRESS_LABEL_LEN: usize = 64;
+
+/// Explorer labels preloaded for the ecosystem wallets
+pub const ECOSYSTEM_ADDRESS_LABELS: &[(&str, &str)] = &[
+    ("fvcfedcba9876543210fedcba9876543210fedcbaemyl", "Network Maintenance Fund"),
+    ("fvc3333333333333333333333333333333333333333", "Ecosystem Reward Pool"),
+    ("FVCowner1234567890abcdef", "Ecosystem Owner"),
+    ("FVCdeveloper1234567890ab", "Ecosystem Developer Fund"),
+    ("FVCmaintenance123456789", "Ecosystem Maintenance"),
+    ("FVCfeepool1234567890abc", "Ecosystem Fee Pool"),
+];
+
+/// Fractal levels used by the block proof-of-work
+pub const BLOCK_FRACTAL_LEVELS: u32 = 3;
+
+/// Proof-of-work difficulty for newly mined blocks
+pub const BLOCK_DIFFICULTY: u32 = 2;
+
+/// Merkle root over transaction hashes; odd levels duplicate their last node
+pub fn merkle_root(tx_hashes: &[String]) -> [u8; 32] {
+    use sha3::{Digest, Sha3_256};
+
+    if tx_hashes.is_empty() {
+        return [0u8; 32];
+    }
+
+    let mut level: Vec<[u8; 32]> = tx_hashes.iter()
+        .map(|hash| Sha3_256::digest(hash.as_bytes()).into())
+        .collect();
+    while level.len() > 1 {
+        level = level.chunks(2)
+            .map(|pair| {
+                let mut hasher = Sha3_256::new();
+                hasher.update(pair[0]);
+                hasher.update(pair.get(1).unwrap_or(&pair[0]));
+                hasher.finalize().into()
+            })
+            .collect();
+    }
+    level[0]
+}
+
+/// Wei per microFVC (18 decimals down to 6)
+pub const WEI_PER_MICRO_FVC: u128 = 1_000_000_000_000;
+
+/// Genesis allocations in microFVC plus the wei lost to truncation
+#[derive(Clone, Debug, Default)]
+pub struct GenesisAllocations {
+    pub balances: std::collections::HashMap<String, u64>,
+    /// Sum of sub-microFVC remainders dropped during conversion
+    pub rounding_loss_wei: u128,
+}
+
+/// Genesis allocations in microFVC, converted from the wei balances in a genesis config
+pub fn genesis_allocations(genesis_config: &serde_json::Value) -> GenesisAllocations {
+    let mut allocations = GenesisAllocations::default();
+    if let Some(alloc) = genesis_config["alloc"].as_object() {
+        for (address, allocation) in alloc {
+            if let Some(balance_str) = allocation["balance"].as_str() {
+                // Convert from wei (18 decimals) to microFVC (6 decimals)
+                if let Ok(balance_wei) = balance_str.parse::<u128>() {
+                    let micro = balance_wei.checked_div(WEI_PER_MICRO_FVC).and_then(|m| u64::try_from(m).ok());
+                    match micro {
+                        Some(micro) => {
+                            allocations.rounding_loss_wei += balance_wei % WEI_PER_MICRO_FVC;
+                            allocations.balances.insert(address.clone(), micro);
+                        }
+                        None => println!("Warning: Genesis balance for {} exceeds u64 microFVC, skipped", address),
+                    }
+                }
+            }
+        }
+    }
+    allocations
+}
+
+/// Fork choice: prefer the tip with more total work, then the higher block, then the lower hash
+pub fn select_heavier_tip<'a>(a: &'a Block, b: &'a Block) -> &'a Block {
+    let key = |block: &Block| (block.cumulative_difficulty, block.height);
+    match key(a).cmp(&key(b)) {
+        std::cmp::Ordering::Greater => a,
+        std::cmp::Ordering::Less => b,
+        std::cmp::Ordering::Equal => if a.hash <= b.hash { a } else { b },
+    }
+}
+
+/// Transaction structure for RPC storage.
+/// Carries the same signing fields as `shared::WalletTransaction` so conversions are lossless.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct WalletTransaction {
     pub hash: String,
     pub from: String,
@@ -21,10 +724,51 @@ pub struct WalletTransaction {
     pub timestamp: u64,
     pub transaction_type: String,
     pub block_height: u64,
+    #[serde(default)]
+    pub nonce: u64,
+    /// Fee paid by the sender; zero means the default for the transaction type
+    #[serde(default)]
+    pub fee: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<Vec<u8>>,
+}
+
+impl From<crate::shared::WalletTransaction> for WalletTransaction {
+    fn from(tx: crate::shared::WalletTransaction) -> Self {
+        Self {
+            hash: tx.hash.unwrap_or_default(),
+            from: tx.from,
+            to: tx.to,
+            amount: tx.amount,
+            timestamp: tx.timestamp,
+            transaction_type: tx.transaction_type,
+            block_height: tx.block_height,
+            nonce: tx.nonce,
+            fee: tx.fee,
+            signature: tx.signature,
+        }
+    }
+}
+
+impl From<WalletTransaction> for crate::shared::WalletTransaction {
+    fn from(tx: WalletTransaction) -> Self {
+        Self {
+            from: tx.from,
+            to: tx.to,
+            amount: tx.amount,
+            nonce: tx.nonce,
+            fee: tx.fee,
+            timestamp: tx.timestamp,
+            signature: tx.signature,
+            hash: if tx.hash.is_empty() { None } else { Some(tx.hash) },
+            transaction_type: tx.transaction_type,
+            block_height: tx.block_height,
+        }
+    }
 }
 
 /// Block structure for real blockchain storage
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Block {
     pub hash: String,
     pub height: u64,
@@ -35,44 +779,36 @@ pub struct Block {
     pub parent_hash: String,
     pub nonce: u64,
     pub difficulty: u64,
+    /// Total work up to and including this block (parent's cumulative + difficulty)
+    #[serde(default)]
+    pub cumulative_difficulty: u64,
     pub size: u64,
 }
 
 impl Block {
+    /// Unmined block hashed over its canonical bytes
     pub fn new(height: u64, miner: String, parent_hash: String) -> Self {
         let timestamp = chrono::Utc::now().timestamp() as u64;
-        let hash = format!("0x{:064x}", height + 12345); // Simple hash for now - will be replaced with real hash
-        
-        Self {
-            hash,
-            height,
-            timestamp,
-            transactions: Vec::new(),
-            transaction_count: 0,
-            miner,
-            parent_hash,
-            nonce: height * 1000 + 42,
-            difficulty: 2,
-            size: 1000 + (height * 100),
-        }
+        Self::new_with_timestamp(height, miner, parent_hash, timestamp)
     }
     
     /// Create block with custom timestamp (for syncing with transaction timestamps)
     pub fn new_with_timestamp(height: u64, miner: String, parent_hash: String, timestamp: u64) -> Self {
-        let hash = format!("0x{:064x}", height + 12345); // Simple hash for now - will be replaced with real hash
-        
-        Self {
-            hash,
+        let mut block = Self {
+            hash: String::new(),
             height,
             timestamp,
             transactions: Vec::new(),
             transaction_count: 0,
             miner,
             parent_hash,
-            nonce: height * 1000 + 42,
-            difficulty: 2,
+            nonce: 0, // Unmined; set by proof-of-work
+            difficulty: BLOCK_DIFFICULTY as u64,
+            cumulative_difficulty: 0,
             size: 1000 + (height * 100),
-        }
+        };
+        block.hash = block.canonical_hash();
+        block
     }
     
     /// Create block with real hash from FractalPoW mining
@@ -97,6 +833,7 @@ impl Block {
             parent_hash,
             nonce,
             difficulty: difficulty as u64,
+            cumulative_difficulty: 0,
             size: 1000 + (height * 100),
         }
     }
@@ -123,6 +860,7 @@ impl Block {
             parent_hash,
             nonce,
             difficulty: difficulty as u64,
+            cumulative_difficulty: 0,
             size: 1000 + (height * 100),
         }
     }
@@ -134,11 +872,119 @@ impl Block {
         self.size = 1000 + (self.height * 100) + (self.transaction_count * 200);
     }
     
-    /// Verify if the block hash is valid (not a fake hash)
+    /// Merkle root of this block's transaction hashes; `verify_transactions` ties each hash to its contents
+    pub fn merkle_root(&self) -> [u8; 32] {
+        let hashes: Vec<String> = self.transactions.iter().map(|tx| tx.hash.clone()).collect();
+        merkle_root(&hashes)
+    }
+    
+    /// `canonical_bytes` without the trailing nonce; miners grind nonces appended to this
+    pub fn header_bytes(&self) -> Vec<u8> {
+        let mut data = self.parent_hash.as_bytes().to_vec();
+        data.extend_from_slice(&self.height.to_le_bytes());
+        data.extend_from_slice(&self.merkle_root());
+        data.extend_from_slice(self.miner.as_bytes());
+        data.extend_from_slice(&self.timestamp.to_le_bytes());
+        data.extend_from_slice(&self.difficulty.to_le_bytes());
+        data
+    }
+    
+    /// Bytes the block hash commits to, shared by miners and every verifier, in this order:
+    /// parent_hash (UTF-8), height (u64 LE), merkle root of the transaction hashes (32 bytes),
+    /// miner (UTF-8), timestamp (u64 LE), difficulty (u64 LE), nonce (u64 LE)
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut data = self.header_bytes();
+        data.extend_from_slice(&self.nonce.to_le_bytes());
+        data
+    }
+    
+    /// Recompute the proof-of-work hash over the canonical bytes
+    pub fn canonical_hash(&self) -> String {
+        let block_hash = crate::crypto::fractal_hash::BlockHash::new(&self.canonical_bytes(), BLOCK_FRACTAL_LEVELS);
+        format!("0x{}", hex::encode(block_hash.hash))
+    }
+    
+    /// Difficulty a block at this height must declare. The retarget from `get_difficulty_status`
+    /// is advisory until it is enforced here.
+    pub fn expected_difficulty(&self) -> u64 {
+        BLOCK_DIFFICULTY as u64
+    }
+    
+    /// Whether the block declares the expected difficulty and its stored hash is the canonical one
+    /// satisfying it. A declared 0 needs no work and anything past `u32::MAX` would be checked
+    /// truncated while still counting in full towards cumulative difficulty, so both fail.
+    pub fn has_valid_pow(&self) -> bool {
+        let difficulty = match u32::try_from(self.difficulty) {
+            Ok(difficulty) if difficulty > 0 && self.difficulty == self.expected_difficulty() => difficulty,
+            _ => return false,
+        };
+        if self.hash != self.canonical_hash() {
+            return false;
+        }
+        match self.get_hash_bytes() {
+            Ok(hash) => crate::crypto::fractal_hash::FractalPoW::new(difficulty, BLOCK_FRACTAL_LEVELS)
+                .meets_difficulty(&hash),
+            Err(_) => false,
+        }
+    }
+    
+    /// Check each transaction against itself: its hash must be recomputed from its fields, a reward
+    /// output must be numbered with this block's height, and anything else must be a transfer signed
+    /// by its `from` address. Reward amounts are checked by `verify_coinbase`.
+    pub fn verify_transactions(&self) -> Result<(), String> {
+        for tx in &self.transactions {
+            if tx.hash != tx.content_hash() {
+                return Err(format!("transaction {} does not hash to its contents", tx.hash));
+            }
+            match tx.transaction_type.as_str() {
+                "mining_reward" if tx.nonce != self.height => {
+                    return Err(format!("reward output {} is not numbered with height {}", tx.hash, self.height));
+                }
+                "mining_reward" => {}
+                kind if TRANSFER_TYPES.contains(&kind) => {
+                    if !tx.has_valid_signature() {
+                        return Err(format!("transfer {} is not signed by {}", tx.hash, tx.from));
+                    }
+                }
+                kind => return Err(format!("transaction {} has unsupported type {}", tx.hash, kind)),
+            }
+        }
+        Ok(())
+    }
+    
+    /// Check the block pays exactly one coinbase, as its first transaction, of the scheduled reward
+    /// at its height. Fees are burned rather than paid to the miner, so nothing is allowed on top.
+    pub fn verify_coinbase(&self) -> Result<(), String> {
+        let Some(coinbase) = self.transactions.first().filter(|tx| tx.transaction_type == "mining_reward") else {
+            return Err("first transaction is not a coinbase".to_string());
+        };
+        let rewards = self.transactions.iter().filter(|tx| tx.transaction_type == "mining_reward").count();
+        if rewards != 1 {
+            return Err(format!("{} reward outputs, expected exactly one", rewards));
+        }
+        let scheduled = crate::consensus::MiningRewardSystem::new().reward_at_height(self.height);
+        if coinbase.amount != scheduled {
+            return Err(format!("coinbase pays {} but the scheduled reward is {}", coinbase.amount, scheduled));
+        }
+        Ok(())
+    }
+    
+    /// Cumulative difficulty this block must carry on top of `parent`
+    pub fn expected_cumulative_difficulty(&self, parent: Option<&Block>) -> u64 {
+        parent
+            .map(|p| p.cumulative_difficulty)
+            .unwrap_or(0)
+            .saturating_add(self.difficulty)
+    }
+    
+    /// Set cumulative difficulty from the parent block
+    pub fn link_to_parent(&mut self, parent: Option<&Block>) {
+        self.cumulative_difficulty = self.expected_cumulative_difficulty(parent);
+    }
+    
+    /// Whether the stored hash is the one computed from the canonical bytes
     pub fn is_valid_hash(&self) -> bool {
-        // Check if hash is not the fake pattern (0x{:064x} from height + 12345)
-        let fake_hash = format!("0x{:064x}", self.height + 12345);
-        self.hash != fake_hash && self.hash.len() == 66 && self.hash.starts_with("0x")
+        self.get_hash_bytes().is_ok() && self.hash == self.canonical_hash()
     }
     
     /// Get hash as bytes for verification
@@ -173,9 +1019,25 @@ impl WalletTransaction {
             timestamp: chrono::Utc::now().timestamp() as u64,
             transaction_type: "mining_reward".to_string(),
             block_height,
+            nonce: 0,
+            fee: 0,
+            signature: None,
         }
     }
     
+    /// Mining reward that rejects a malformed recipient, zero amount or bad hash
+    pub fn try_new_mining_reward(address: String, amount: u64, hash: String, block_height: u64) -> Result<Self, TxError> {
+        validate_tx_address("recipient", &address)?;
+        validate_tx_fields(amount, &hash)?;
+        Ok(Self::new_mining_reward(address, amount, hash, block_height))
+    }
+
+    /// Transfer that rejects malformed addresses, zero amount or bad hash
+    pub fn try_new_transfer(from: String, to: String, amount: u64, hash: String, block_height: u64) -> Result<Self, TxError> {
+        validate_transfer(&from, &to, amount, &hash)?;
+        Ok(Self::new_transfer(from, to, amount, hash, block_height))
+    }
+    
     pub fn new_transfer(from: String, to: String, amount: u64, hash: String, block_height: u64) -> Self {
         Self {
             hash,
@@ -185,37 +1047,301 @@ impl WalletTransaction {
             timestamp: chrono::Utc::now().timestamp() as u64,
             transaction_type: "transfer".to_string(),
             block_height,
+            nonce: 0,
+            fee: 0,
+            signature: None,
         }
     }
+
+    /// The coinbase of the block at `block_height`, numbered with the height and identified by its content hash
+    pub fn coinbase(address: String, amount: u64, block_height: u64) -> Self {
+        let mut tx = Self::new_mining_reward(address, amount, String::new(), block_height);
+        tx.nonce = block_height;
+        tx.hash = tx.content_hash();
+        tx
+    }
+
+    /// Hash recomputed from the fields; a block only accepts a transaction stored under this hash
+    pub fn content_hash(&self) -> String {
+        transfer_content_hash(&self.transaction_type, &self.from, &self.to, self.amount, self.nonce, sender_fee(self))
+    }
+
+    /// The consensus transaction the sender signed, with the public key `signature` starts with;
+    /// None when the signature or hash is missing or malformed
+    pub fn signed_consensus_transaction(&self) -> Option<crate::consensus::vortex_consensus::Transaction> {
+        let signature = self.signature.as_ref().filter(|signature| signature.len() > TRANSFER_PUBLIC_KEY_LEN)?;
+        let (public_key, signature) = signature.split_at(TRANSFER_PUBLIC_KEY_LEN);
+        let mut hash = [0u8; 32];
+        hex::decode_to_slice(self.hash.strip_prefix("0x")?, &mut hash).ok()?;
+        Some(crate::consensus::vortex_consensus::Transaction {
+            hash,
+            from: public_key.to_vec(),
+            to: self.to.clone().into_bytes(),
+            amount: self.amount,
+            nonce: self.nonce,
+            signature: signature.to_vec(),
+            vortex_fee: sender_fee(self) as f64,
+        })
+    }
+
+    /// Whether the key behind the `from` address signed this transfer, content hash included
+    pub fn has_valid_signature(&self) -> bool {
+        self.signed_consensus_transaction().is_some_and(|signed| {
+            crate::wallet::key_manager::KeyManager::address_from_public_key(&signed.from) == self.from
+                && signed.verify_signature()
+        })
+    }
 }
 
 /// RPC Storage Manager - handles all persistent data for RPC server
 pub struct RPCStorage;
 
 impl RPCStorage {
+    /// Send every call to `db` instead of the RPC_DATA_DIR database until the guard drops.
+    /// Waits while another override is installed, so callers never see each other's data.
+    pub fn use_database(db: Arc<LedgerDB>) -> DatabaseOverride {
+        let exclusive = RPC_DB_OVERRIDE_LOCK.lock().unwrap_or_else(std::sync::PoisonError::into_inner);
+        *RPC_DB_OVERRIDE.write().unwrap_or_else(std::sync::PoisonError::into_inner) = Some(db);
+        DatabaseOverride { _exclusive: exclusive }
+    }
+
+    /// Sync the RPC database to disk, e.g. before the process exits
+    pub async fn flush() -> Result<(), StorageError> {
+        rpc_db().flush().await
+    }
+
     /// Balance operations
     pub async fn get_balance(address: &str) -> Result<u64, StorageError> {
-        RPC_DB.get_balance(address).await
+        rpc_db().get_balance(address).await
+    }
+
+    /// Balances of several addresses in one call; a malformed address or failed read
+    /// is reported for that address alone. Unknown addresses have a zero balance.
+    pub async fn get_balances(addresses: &[String]) -> std::collections::BTreeMap<String, BalanceLookup> {
+        let mut balances = std::collections::BTreeMap::new();
+        for address in addresses {
+            let lookup = match validate_tx_address("address", address) {
+                Err(e) => BalanceLookup::Error { error: e.to_string() },
+                Ok(()) => match Self::get_balance(address).await {
+                    Ok(balance) => BalanceLookup::Balance { balance },
+                    Err(e) => BalanceLookup::Error { error: format!("Failed to get balance: {}", e) },
+                },
+            };
+            balances.insert(address.clone(), lookup);
+        }
+        balances
     }
 
+    /// Every balance write moves the tracked supply by the change it makes
     pub async fn set_balance(address: &str, balance: u64) -> Result<(), StorageError> {
-        RPC_DB.set_balance(address, balance).await
+        let _guard = SUPPLY_LOCK.lock().await;
+        let previous = rpc_db().get_balance(address).await?;
+        rpc_db().set_balance(address, balance).await?;
+
+        let tracked = rpc_db().get_u64(TRACKED_SUPPLY_KEY).await?.unwrap_or(0) as i128;
+        let updated = (tracked + balance as i128 - previous as i128).clamp(0, u64::MAX as i128) as u64;
+        rpc_db().set(TRACKED_SUPPLY_KEY, updated).await
+    }
+
+    /// Last transfer nonce accepted from `address`; 0 before its first transfer
+    pub async fn get_account_nonce(address: &str) -> Result<u64, StorageError> {
+        Ok(rpc_db().get_u64(&format!("nonce:{}", address)).await?.unwrap_or(0))
+    }
+
+    pub async fn set_account_nonce(address: &str, nonce: u64) -> Result<(), StorageError> {
+        rpc_db().set(&format!("nonce:{}", address), nonce).await
+    }
+
+    /// Recompute issued supply from genesis and mining rewards and compare it with the sum of the
+    /// stored balances of every address holding a genesis allocation or party to a transaction.
+    /// The log is read a page at a time and balances are summed a page at a time under the supply
+    /// lock, so transfers wait on one page at most; one that lands between pages can show up as a
+    /// discrepancy that a rerun clears.
+    pub async fn audit_supply() -> Result<SupplyAudit, StorageError> {
+        let genesis_supply = rpc_db().get_u64(GENESIS_SUPPLY_KEY).await?.unwrap_or(0);
+
+        let allocation_prefix = Self::genesis_allocation_key("");
+        let mut holders: std::collections::BTreeSet<String> = rpc_db()
+            .scan_prefix(allocation_prefix.as_bytes(), usize::MAX).await?
+            .into_iter()
+            .filter_map(|(key, _)| String::from_utf8(key[allocation_prefix.len()..].to_vec()).ok())
+            .collect();
+        // Balances migrated off pre-checksum addresses live under the address they moved to
+        holders.extend(rpc_db().scan_prefix(Self::address_alias_key("").as_bytes(), usize::MAX).await?
+            .into_iter()
+            .filter_map(|(_, upgraded)| String::from_utf8(upgraded).ok()));
+
+        let mut totals = (0, 0);
+        let mut cursor = None;
+        loop {
+            let page = Self::get_transactions_page(cursor.as_deref(), SUPPLY_AUDIT_PAGE).await?;
+            totals = SupplyAudit::tally(&page.transactions, totals);
+            holders.extend(page.transactions.iter().flat_map(indexed_parties).map(str::to_string));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        let holders: Vec<String> = holders.into_iter().collect();
+        let mut balance_supply = 0u64;
+        for page in holders.chunks(SUPPLY_AUDIT_PAGE) {
+            let _guard = SUPPLY_LOCK.lock().await;
+            for address in page {
+                balance_supply = balance_supply.saturating_add(Self::get_balance(address).await?);
+            }
+        }
+        let tracked_supply = rpc_db().get_u64(TRACKED_SUPPLY_KEY).await?.unwrap_or(0);
+        let (mined_rewards, fees_burned) = totals;
+        Ok(SupplyAudit::from_totals(genesis_supply, mined_rewards, fees_burned, balance_supply, tracked_supply))
+    }
+
+    /// Apply a signed delta to a balance, failing instead of clamping or wrapping
+    pub fn apply_balance_delta(address: &str, current: u64, delta: i64) -> Result<u64, StorageError> {
+        let amount = delta.unsigned_abs();
+        if delta < 0 {
+            current.checked_sub(amount).ok_or_else(|| StorageError::BalanceUnderflow {
+                address: address.to_string(),
+                balance: current,
+                amount,
+            })
+        } else {
+            current.checked_add(amount).ok_or_else(|| StorageError::BalanceOverflow {
+                address: address.to_string(),
+                balance: current,
+                amount,
+            })
+        }
     }
 
-    pub async fn update_balance(address: &str, delta: i64) -> Result<u64, StorageError> {
-        let current = Self::get_balance(address).await?;
-        let new_balance = if delta < 0 {
-            current.saturating_sub((-delta) as u64)
+    /// Balance with immature mining rewards separated out. Rewards are only paid in blocks, and
+    /// only the blocks inside the maturity window can still hold immature ones, so just those are read.
+    pub async fn get_balance_breakdown(address: &str) -> Result<BalanceBreakdown, StorageError> {
+        let balance = Self::get_balance(address).await?;
+        let tip_height = Self::get_block_height().await?;
+        let maturity = *COINBASE_MATURITY;
+        let transactions: Vec<WalletTransaction> = if maturity == 0 {
+            Vec::new()
         } else {
-            current + (delta as u64)
+            // Fewer than `maturity` confirmations means mined above tip + 1 - maturity
+            Self::get_blocks_range((tip_height + 2).saturating_sub(maturity), tip_height).await?
+                .into_iter()
+                .flat_map(|block| block.transactions)
+                .collect()
         };
+        Ok(BalanceBreakdown::compute(address, balance, &transactions, tip_height, maturity))
+    }
+
+    /// Adjust a balance by `delta`; the stored balance is left unchanged on underflow or overflow
+    pub async fn update_balance(address: &str, delta: i64) -> Result<u64, StorageError> {
+        let _guards = lock_addresses(&[address]).await;
+        let current = Self::get_balance(address).await?;
+        let new_balance = Self::apply_balance_delta(address, current, delta)?;
         Self::set_balance(address, new_balance).await?;
         Ok(new_balance)
     }
 
+    /// Debit `amount + fee` from `from` and credit `amount` to `to` with both addresses locked.
+    /// Nothing changes when `from` cannot cover the debit. Returns the new (sender, receiver) balances.
+    pub async fn transfer(from: &str, to: &str, amount: u64, fee: u64) -> Result<(u64, u64), StorageError> {
+        let _guards = lock_addresses(&[from, to]).await;
+        let _supply_guard = SUPPLY_LOCK.lock().await;
+        let (entries, balances) = Self::transfer_entries(from, to, amount, fee).await?;
+        rpc_db().put_batch(&entries).await?;
+        Ok(balances)
+    }
+
+    /// Apply a submitted transfer: move its balances, store the transaction and advance the
+    /// sender's nonce in one atomic write, so a failure leaves no partial record behind.
+    /// `pending` is the signed consensus copy, added to the persisted mempool in the same write.
+    pub async fn record_transfer(
+        tx: &WalletTransaction,
+        fee: u64,
+        pending: Option<&crate::consensus::vortex_consensus::Transaction>,
+    ) -> Result<(u64, u64), StorageError> {
+        let _guards = Self::lock_transfer(&tx.from, &tx.to).await;
+        Self::record_transfer_locked(tx, fee, pending).await
+    }
+
+    /// Lock a transfer's sender and recipient, for a caller that checks the transfer against
+    /// their state and then records it with `record_transfer_locked` before releasing them
+    pub async fn lock_transfer(from: &str, to: &str) -> Vec<tokio::sync::OwnedMutexGuard<()>> {
+        lock_addresses(&[from, to]).await
+    }
+
+    /// As `record_transfer`, for a caller holding both addresses' locks from `lock_transfer`
+    pub async fn record_transfer_locked(
+        tx: &WalletTransaction,
+        fee: u64,
+        pending: Option<&crate::consensus::vortex_consensus::Transaction>,
+    ) -> Result<(u64, u64), StorageError> {
+        let _supply_guard = SUPPLY_LOCK.lock().await;
+        let _mempool_guard = match pending {
+            Some(_) => Some(MEMPOOL_LOCK.lock().await),
+            None => None,
+        };
+        let (mut entries, balances) = Self::transfer_entries(&tx.from, &tx.to, tx.amount, fee).await?;
+        let now = chrono::Utc::now().timestamp() as u64;
+        // The sender's lock is held, so the day's total cannot move underneath us
+        let day = spend_day(now);
+        let spent = Self::get_daily_spend(&tx.from, day).await?;
+        entries.push((daily_spend_key(&tx.from, day).into_bytes(), spent.saturating_add(tx.amount).to_le_bytes().to_vec()));
+        if let Some(transaction) = pending {
+            entries.push(Self::pending_entry(&PendingTransaction {
+                transaction: transaction.clone(),
+                received_at: now,
+                valid_until: now.saturating_add(*MEMPOOL_TX_TTL),
+            })?);
+        }
+
+        let _log_guard = TX_LOG_LOCK.lock().await;
+        let log_len = Self::tx_log_len_locked().await?;
+        entries.extend(Self::tx_append_entries(tx, log_len)?);
+        entries.push((format!("nonce:{}", tx.from).into_bytes(), tx.nonce.to_le_bytes().to_vec()));
+        rpc_db().put_batch(&entries).await?;
+        Self::sync_address_index_locked(log_len + 1).await?;
+        Ok(balances)
+    }
+
+    /// Value `address` sent on UTC day `day`, counted against the daily transfer limit
+    pub async fn get_daily_spend(address: &str, day: u64) -> Result<u64, StorageError> {
+        Ok(rpc_db().get_u64(&daily_spend_key(address, day)).await?.unwrap_or(0))
+    }
+
+    /// Balance writes for a transfer, plus the tracked supply shrinking by the burned fee.
+    /// Callers must hold the locks of both addresses and `SUPPLY_LOCK`.
+    async fn transfer_entries(from: &str, to: &str, amount: u64, fee: u64) -> Result<(Vec<(Vec<u8>, Vec<u8>)>, (u64, u64)), StorageError> {
+        let sender_balance = Self::get_balance(from).await?;
+        let debit = amount.checked_add(fee).ok_or_else(|| StorageError::BalanceOverflow {
+            address: from.to_string(),
+            balance: amount,
+            amount: fee,
+        })?;
+        let sender_new = sender_balance.checked_sub(debit).ok_or_else(|| StorageError::BalanceUnderflow {
+            address: from.to_string(),
+            balance: sender_balance,
+            amount: debit,
+        })?;
+        let receiver_balance = if from == to { sender_new } else { Self::get_balance(to).await? };
+        let receiver_new = receiver_balance.checked_add(amount).ok_or_else(|| StorageError::BalanceOverflow {
+            address: to.to_string(),
+            balance: receiver_balance,
+            amount,
+        })?;
+
+        let tracked = rpc_db().get_u64(TRACKED_SUPPLY_KEY).await?.unwrap_or(0).saturating_sub(fee);
+        // A self-transfer only pays the fee; the later entry wins in the batch
+        let entries = vec![
+            (from.as_bytes().to_vec(), sender_new.to_le_bytes().to_vec()),
+            (to.as_bytes().to_vec(), receiver_new.to_le_bytes().to_vec()),
+            (TRACKED_SUPPLY_KEY.as_bytes().to_vec(), tracked.to_le_bytes().to_vec()),
+        ];
+        Ok((entries, if from == to { (receiver_new, receiver_new) } else { (sender_new, receiver_new) }))
+    }
+
     pub async fn get_device_balance(device_id: &str, address: &str) -> Result<u64, StorageError> {
         let key = format!("device_balance:{}:{}", device_id, address);
-        match RPC_DB.get_u64(&key).await {
+        match rpc_db().get_u64(&key).await {
             Ok(Some(value)) => Ok(value),
             Ok(None) => Ok(0),
             Err(e) => Err(e),
@@ -224,7 +1350,7 @@ impl RPCStorage {
 
     pub async fn set_device_balance(device_id: &str, address: &str, balance: u64) -> Result<(), StorageError> {
         let key = format!("device_balance:{}:{}", device_id, address);
-        RPC_DB.set(&key, balance).await
+        rpc_db().set(&key, balance).await
     }
 
     // Overload functions for device_id only (uses stored device address)
@@ -247,7 +1373,7 @@ impl RPCStorage {
     /// Device-specific operations
     pub async fn get_device_mining_status(device_id: &str) -> Result<bool, StorageError> {
         let key = format!("device_mining:{}", device_id);
-        match RPC_DB.get(key.as_bytes()).await? {
+        match rpc_db().get(key.as_bytes()).await? {
             Some(bytes) => Ok(bytes[0] == 1),
             None => Ok(false),
         }
@@ -256,12 +1382,12 @@ impl RPCStorage {
     pub async fn set_device_mining_status(device_id: &str, status: bool) -> Result<(), StorageError> {
         let key = format!("device_mining:{}", device_id);
         let value = if status { [1u8] } else { [0u8] };
-        RPC_DB.put(key.as_bytes(), &value).await
+        rpc_db().put(key.as_bytes(), &value).await
     }
 
     pub async fn get_device_session(device_id: &str) -> Result<Option<(String, u64)>, StorageError> {
         let key = format!("device_session:{}", device_id);
-        match RPC_DB.get(key.as_bytes()).await? {
+        match rpc_db().get(key.as_bytes()).await? {
             Some(bytes) => {
                 let session_data: (String, u64) = serde_json::from_slice(&bytes)
                     .map_err(|e| StorageError::Serialization(e.to_string()))?;
@@ -276,11 +1402,11 @@ impl RPCStorage {
         let session_data = (session_id.to_string(), timestamp);
         let value = serde_json::to_vec(&session_data)
             .map_err(|e| StorageError::Serialization(e.to_string()))?;
-        RPC_DB.put(key.as_bytes(), &value).await?;
+        rpc_db().put(key.as_bytes(), &value).await?;
         
         // Update session keys registry
         let session_keys_key = b"session_keys_registry";
-        let mut session_keys: Vec<String> = match RPC_DB.get(session_keys_key).await? {
+        let mut session_keys: Vec<String> = match rpc_db().get(session_keys_key).await? {
             Some(data) => serde_json::from_slice(&data).unwrap_or_default(),
             None => Vec::new(),
         };
@@ -289,7 +1415,7 @@ impl RPCStorage {
             session_keys.push(device_id.to_string());
             let registry_data = serde_json::to_vec(&session_keys)
                 .map_err(|e| StorageError::Serialization(e.to_string()))?;
-            RPC_DB.put(session_keys_key, &registry_data).await?;
+            rpc_db().put(session_keys_key, &registry_data).await?;
         }
         
         Ok(())
@@ -297,11 +1423,11 @@ impl RPCStorage {
 
     pub async fn remove_device_session(device_id: &str) -> Result<(), StorageError> {
         let key = format!("device_session:{}", device_id);
-        RPC_DB.delete(key.as_bytes()).await?;
+        rpc_db().delete(key.as_bytes()).await?;
         
         // Remove from session keys registry
         let session_keys_key = b"session_keys_registry";
-        let mut session_keys: Vec<String> = match RPC_DB.get(session_keys_key).await? {
+        let mut session_keys: Vec<String> = match rpc_db().get(session_keys_key).await? {
             Some(data) => serde_json::from_slice(&data).unwrap_or_default(),
             None => Vec::new(),
         };
@@ -309,14 +1435,14 @@ impl RPCStorage {
         session_keys.retain(|id| id != device_id);
         let registry_data = serde_json::to_vec(&session_keys)
             .map_err(|e| StorageError::Serialization(e.to_string()))?;
-        RPC_DB.put(session_keys_key, &registry_data).await?;
+        rpc_db().put(session_keys_key, &registry_data).await?;
         
         Ok(())
     }
 
     pub async fn get_device_address(device_id: &str) -> Result<Option<String>, StorageError> {
         let key = format!("device_addr:{}", device_id);
-        match RPC_DB.get(key.as_bytes()).await? {
+        match rpc_db().get(key.as_bytes()).await? {
             Some(bytes) => {
                 let address = String::from_utf8(bytes)
                     .map_err(|e| StorageError::Serialization(e.to_string()))?;
@@ -328,11 +1454,11 @@ impl RPCStorage {
 
     pub async fn set_device_address(device_id: &str, address: &str) -> Result<(), StorageError> {
         let key = format!("device_addr:{}", device_id);
-        RPC_DB.put(key.as_bytes(), address.as_bytes()).await?;
+        rpc_db().put(key.as_bytes(), address.as_bytes()).await?;
         
         // Update device IDs registry
         let device_registry_key = b"device_ids_registry";
-        let mut device_ids: Vec<String> = match RPC_DB.get(device_registry_key).await? {
+        let mut device_ids: Vec<String> = match rpc_db().get(device_registry_key).await? {
             Some(data) => serde_json::from_slice(&data).unwrap_or_default(),
             None => Vec::new(),
         };
@@ -341,7 +1467,7 @@ impl RPCStorage {
             device_ids.push(device_id.to_string());
             let registry_data = serde_json::to_vec(&device_ids)
                 .map_err(|e| StorageError::Serialization(e.to_string()))?;
-            RPC_DB.put(device_registry_key, &registry_data).await?;
+            rpc_db().put(device_registry_key, &registry_data).await?;
         }
         
         Ok(())
@@ -349,11 +1475,11 @@ impl RPCStorage {
 
     pub async fn remove_device_address(device_id: &str) -> Result<(), StorageError> {
         let key = format!("device_addr:{}", device_id);
-        RPC_DB.delete(key.as_bytes()).await?;
+        rpc_db().delete(key.as_bytes()).await?;
         
         // Remove from device IDs registry
         let device_registry_key = b"device_ids_registry";
-        let mut device_ids: Vec<String> = match RPC_DB.get(device_registry_key).await? {
+        let mut device_ids: Vec<String> = match rpc_db().get(device_registry_key).await? {
             Some(data) => serde_json::from_slice(&data).unwrap_or_default(),
             None => Vec::new(),
         };
@@ -361,14 +1487,14 @@ impl RPCStorage {
         device_ids.retain(|id| id != device_id);
         let registry_data = serde_json::to_vec(&device_ids)
             .map_err(|e| StorageError::Serialization(e.to_string()))?;
-        RPC_DB.put(device_registry_key, &registry_data).await?;
+        rpc_db().put(device_registry_key, &registry_data).await?;
         
         Ok(())
     }
 
     pub async fn get_device_first_mining(device_id: &str) -> Result<bool, StorageError> {
         let key = format!("device_first_mining:{}", device_id);
-        match RPC_DB.get(key.as_bytes()).await? {
+        match rpc_db().get(key.as_bytes()).await? {
             Some(bytes) => Ok(bytes[0] == 1),
             None => Ok(false),
         }
@@ -377,13 +1503,13 @@ impl RPCStorage {
     pub async fn set_device_first_mining(device_id: &str, has_mined: bool) -> Result<(), StorageError> {
         let key = format!("device_first_mining:{}", device_id);
         let value = if has_mined { [1u8] } else { [0u8] };
-        RPC_DB.put(key.as_bytes(), &value).await
+        rpc_db().put(key.as_bytes(), &value).await
     }
 
     /// Wallet data operations
     pub async fn get_wallet_data(device_id: &str) -> Result<Option<serde_json::Value>, StorageError> {
         let key = format!("wallet_data:{}", device_id);
-        match RPC_DB.get(key.as_bytes()).await? {
+        match rpc_db().get(key.as_bytes()).await? {
             Some(bytes) => {
                 let wallet_data: serde_json::Value = serde_json::from_slice(&bytes)
                     .map_err(|e| StorageError::Serialization(e.to_string()))?;
@@ -397,7 +1523,7 @@ impl RPCStorage {
         let key = format!("wallet_data:{}", device_id);
         let value = serde_json::to_vec(wallet_data)
             .map_err(|e| StorageError::Serialization(e.to_string()))?;
-        RPC_DB.put(key.as_bytes(), &value).await
+        rpc_db().put(key.as_bytes(), &value).await
     }
 
     pub async fn save_device_wallet(device_id: &str, wallet_data: &str) -> Result<(), StorageError> {
@@ -406,73 +1532,267 @@ impl RPCStorage {
         Self::set_wallet_data(device_id, &wallet_json).await
     }
 
-    pub async fn get_device_wallet(device_id: &str) -> Result<Option<serde_json::Value>, StorageError> {
-        Self::get_wallet_data(device_id).await
+    pub async fn get_device_wallet(device_id: &str) -> Result<Option<serde_json::Value>, StorageError> {
+        Self::get_wallet_data(device_id).await
+    }
+
+    pub async fn remove_device_wallet(device_id: &str) -> Result<(), StorageError> {
+        let key = format!("wallet_data:{}", device_id);
+        rpc_db().delete(key.as_bytes()).await
+    }
+
+    /// Private key operations; keys are encrypted at rest under a key derived from the device PIN.
+    /// Entries stored before encryption are only returned once `pin` verifies, and are sealed then.
+    pub async fn get_device_private_key(device_id: &str, pin: &str) -> Result<Option<String>, StorageError> {
+        let key = format!("device_private_key:{}", device_id);
+        let Some(bytes) = rpc_db().get(key.as_bytes()).await? else {
+            return Ok(None);
+        };
+        let stored = String::from_utf8(bytes)
+            .map_err(|e| StorageError::Serialization(e.to_string()))?;
+        let private_key = match decrypt_private_key(&stored, pin)? {
+            Some(private_key) if stored.starts_with(ENCRYPTED_KEY_PREFIX) => return Ok(Some(private_key)),
+            Some(private_key) => private_key,
+            None if Self::verify_device_pin(device_id, pin).await? => stored,
+            None => return Err(StorageError::Decryption("wrong PIN".to_string())),
+        };
+        Self::set_device_private_key(device_id, &private_key, pin).await?;
+        Ok(Some(private_key))
+    }
+
+    pub async fn has_device_private_key(device_id: &str) -> Result<bool, StorageError> {
+        let key = format!("device_private_key:{}", device_id);
+        Ok(rpc_db().get(key.as_bytes()).await?.is_some())
+    }
+
+    pub async fn set_device_private_key(device_id: &str, private_key: &str, pin: &str) -> Result<(), StorageError> {
+        let key = format!("device_private_key:{}", device_id);
+        let sealed = encrypt_private_key(private_key, pin)?;
+        rpc_db().put(key.as_bytes(), sealed.as_bytes()).await
+    }
+
+    pub async fn remove_device_private_key(device_id: &str) -> Result<(), StorageError> {
+        let key = format!("device_private_key:{}", device_id);
+        rpc_db().delete(key.as_bytes()).await
+    }
+
+    /// Transaction operations
+    pub async fn add_transaction(tx: &WalletTransaction) -> Result<(), StorageError> {
+        let key = format!("tx:{}", tx.hash);
+        let _guard = TX_LOG_LOCK.lock().await;
+        let log_len = Self::tx_log_len_locked().await?;
+        
+        // Check if transaction already exists
+        let is_new_tx = rpc_db().get(key.as_bytes()).await?.is_none();
+        
+        let value = serde_json::to_vec(tx)
+            .map_err(|e| StorageError::Serialization(e.to_string()))?;
+        if !is_new_tx {
+            return rpc_db().put(key.as_bytes(), &value).await;
+        }
+
+        rpc_db().put_batch(&Self::tx_append_entries(tx, log_len)?).await?;
+        Self::sync_address_index_locked(log_len + 1).await
+    }
+
+    /// Writes storing a new transaction and appending it to the log at `log_len`; O(1) regardless
+    /// of log size. Callers must hold `TX_LOG_LOCK`.
+    fn tx_append_entries(tx: &WalletTransaction, log_len: u64) -> Result<Vec<(Vec<u8>, Vec<u8>)>, StorageError> {
+        let value = serde_json::to_vec(tx)
+            .map_err(|e| StorageError::Serialization(e.to_string()))?;
+        Ok(vec![
+            (format!("tx:{}", tx.hash).into_bytes(), value),
+            (Self::tx_log_key(log_len).into_bytes(), tx.hash.as_bytes().to_vec()),
+            (Self::tx_seq_key(&tx.hash).into_bytes(), log_len.to_le_bytes().to_vec()),
+            (b"transaction_count".to_vec(), (log_len + 1).to_le_bytes().to_vec()),
+        ])
+    }
+
+    fn address_tx_key(address: &str, n: u64) -> String {
+        format!("addr_tx:{}:{}", address, n)
+    }
+
+    fn address_tx_count_key(address: &str) -> String {
+        format!("addr_tx_count:{}", address)
+    }
+
+    /// Index log entries not yet in the per-address index. Callers must hold `TX_LOG_LOCK`.
+    /// Also backfills transactions stored before the index existed.
+    async fn sync_address_index_locked(log_len: u64) -> Result<(), StorageError> {
+        let synced = rpc_db().get_u64(ADDRESS_INDEX_SYNCED_KEY).await?.unwrap_or(0);
+        if synced >= log_len {
+            return Ok(());
+        }
+
+        for seq in synced..log_len {
+            let tx = match Self::get_transaction_hash_at(seq).await? {
+                Some(hash) => Self::get_transaction(&hash).await?,
+                None => None,
+            };
+            if let Some(tx) = tx {
+                for address in indexed_parties(&tx) {
+                    let count_key = Self::address_tx_count_key(address);
+                    let n = rpc_db().get_u64(&count_key).await?.unwrap_or(0);
+                    rpc_db().put(Self::address_tx_key(address, n).as_bytes(), tx.hash.as_bytes()).await?;
+                    rpc_db().set(&count_key, n + 1).await?;
+                }
+            }
+        }
+        rpc_db().set(ADDRESS_INDEX_SYNCED_KEY, log_len).await
     }
 
-    pub async fn remove_device_wallet(device_id: &str) -> Result<(), StorageError> {
-        let key = format!("wallet_data:{}", device_id);
-        RPC_DB.delete(key.as_bytes()).await
+    /// Number of transactions involving `address`
+    pub async fn get_address_transaction_count(address: &str) -> Result<u64, StorageError> {
+        let _guard = TX_LOG_LOCK.lock().await;
+        let log_len = Self::tx_log_len_locked().await?;
+        Self::sync_address_index_locked(log_len).await?;
+        Ok(rpc_db().get_u64(&Self::address_tx_count_key(address)).await?.unwrap_or(0))
     }
 
-    /// Private key operations
-    pub async fn get_device_private_key(device_id: &str) -> Result<Option<String>, StorageError> {
-        let key = format!("device_private_key:{}", device_id);
-        match RPC_DB.get(key.as_bytes()).await? {
+    /// Hash of the `n`th transaction involving `address`, in log order
+    pub async fn get_address_transaction_hash_at(address: &str, n: u64) -> Result<Option<String>, StorageError> {
+        match rpc_db().get(Self::address_tx_key(address, n).as_bytes()).await? {
             Some(bytes) => {
-                let private_key = String::from_utf8(bytes)
+                let hash = String::from_utf8(bytes)
                     .map_err(|e| StorageError::Serialization(e.to_string()))?;
-                Ok(Some(private_key))
+                Ok(Some(hash))
             },
             None => Ok(None),
         }
     }
 
-    pub async fn set_device_private_key(device_id: &str, encrypted_private_key: &str) -> Result<(), StorageError> {
-        let key = format!("device_private_key:{}", device_id);
-        RPC_DB.put(key.as_bytes(), encrypted_private_key.as_bytes()).await
+    /// Transactions involving `address`, newest first, skipping the `offset` most recent.
+    /// Reads only the address's own index entries, never the global log.
+    pub async fn get_transactions_for_address(address: &str, limit: usize, offset: usize) -> Result<Vec<WalletTransaction>, StorageError> {
+        let count = Self::get_address_transaction_count(address).await?;
+        let newest = count.saturating_sub(offset as u64);
+        let oldest = newest.saturating_sub(limit as u64);
+
+        let mut transactions = Vec::with_capacity((newest - oldest) as usize);
+        for n in (oldest..newest).rev() {
+            if let Some(hash) = Self::get_address_transaction_hash_at(address, n).await? {
+                if let Some(tx) = Self::get_transaction(&hash).await? {
+                    transactions.push(tx);
+                }
+            }
+        }
+        Ok(transactions)
     }
 
-    pub async fn remove_device_private_key(device_id: &str) -> Result<(), StorageError> {
-        let key = format!("device_private_key:{}", device_id);
-        RPC_DB.delete(key.as_bytes()).await
+    /// `paginate_history` over an address's indexed history, reading only the requested page
+    pub async fn get_address_history_page(
+        address: &str,
+        limit: Option<usize>,
+        cursor: Option<usize>,
+        cap: usize,
+    ) -> Result<HistoryPage<WalletTransaction>, StorageError> {
+        let cap = cap.max(1);
+        let limit = limit.unwrap_or(cap).min(cap);
+        let total_count = Self::get_address_transaction_count(address).await? as usize;
+        let start = cursor.unwrap_or(0).min(total_count);
+        let end = start.saturating_add(limit).min(total_count);
+
+        let items = Self::get_transactions_for_address(address, end - start, start).await?;
+        let next_cursor = if end < total_count { Some(end) } else { None };
+        Ok(HistoryPage { items, next_cursor, total_count })
+    }
+
+    /// Balance of `address` after every block up to and including `height`,
+    /// replayed from its genesis allocation and indexed transactions
+    pub async fn balance_at_height(address: &str, height: u64) -> Result<u64, StorageError> {
+        let mut balance = rpc_db().get_u64(&Self::genesis_allocation_key(address)).await?.unwrap_or(0) as i128;
+
+        let count = Self::get_address_transaction_count(address).await?;
+        for n in 0..count {
+            let tx = match Self::get_address_transaction_hash_at(address, n).await? {
+                Some(hash) => Self::get_transaction(&hash).await?,
+                None => None,
+            };
+            if let Some(tx) = tx.filter(|tx| tx.block_height <= height) {
+                balance += balance_delta(&tx, address);
+            }
+        }
+
+        Ok(balance.clamp(0, u64::MAX as i128) as u64)
     }
 
-    /// Transaction operations
-    pub async fn add_transaction(tx: &WalletTransaction) -> Result<(), StorageError> {
-        let key = format!("tx:{}", tx.hash);
-        
-        // Check if transaction already exists
-        let _tx_exists = RPC_DB.get(key.as_bytes()).await?.is_some();
-        
-        let value = serde_json::to_vec(tx)
-            .map_err(|e| StorageError::Serialization(e.to_string()))?;
-        RPC_DB.put(key.as_bytes(), &value).await?;
+    fn genesis_allocation_key(address: &str) -> String {
+        format!("genesis_allocation:{}", address)
+    }
+
+    fn tx_log_key(seq: u64) -> String {
+        format!("tx_log:{}", seq)
+    }
+
+    fn tx_seq_key(hash: &str) -> String {
+        format!("tx_seq:{}", hash)
+    }
+
+    /// Position of `hash` in the transaction log. Hashes logged before the position index
+    /// existed are found by walking the log back from the newest entry, then indexed.
+    async fn transaction_seq(hash: &str) -> Result<Option<u64>, StorageError> {
+        if let Some(seq) = rpc_db().get_u64(&Self::tx_seq_key(hash)).await? {
+            return Ok(Some(seq));
+        }
+        for seq in (0..Self::get_transaction_count().await?).rev() {
+            if Self::get_transaction_hash_at(seq).await?.as_deref() == Some(hash) {
+                rpc_db().set(&Self::tx_seq_key(hash), seq).await?;
+                return Ok(Some(seq));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Length of the transaction log. Callers must hold `TX_LOG_LOCK`.
+    async fn tx_log_len_locked() -> Result<u64, StorageError> {
+        if let Some(len) = rpc_db().get_u64("transaction_count").await? {
+            return Ok(len);
+        }
         
-        // Update transaction hashes registry
-        let tx_registry_key = b"transaction_hashes_registry";
-        let mut tx_hashes: Vec<String> = match RPC_DB.get(tx_registry_key).await? {
+        // First use: move hashes from the legacy registry blob into the log
+        let legacy: Vec<String> = match rpc_db().get(LEGACY_TX_REGISTRY_KEY).await? {
             Some(data) => serde_json::from_slice(&data).unwrap_or_default(),
             None => Vec::new(),
         };
-        
-        let is_new_tx = !tx_hashes.contains(&tx.hash);
-        if is_new_tx {
-            tx_hashes.push(tx.hash.clone());
-            let registry_data = serde_json::to_vec(&tx_hashes)
-                .map_err(|e| StorageError::Serialization(e.to_string()))?;
-            RPC_DB.put(tx_registry_key, &registry_data).await?;
-            
-            // Only increment counter for truly new transactions
-            Self::increment_transaction_count().await?;
+        for (seq, hash) in legacy.iter().enumerate() {
+            rpc_db().put_batch(&[
+                (Self::tx_log_key(seq as u64).into_bytes(), hash.as_bytes().to_vec()),
+                (Self::tx_seq_key(hash).into_bytes(), (seq as u64).to_le_bytes().to_vec()),
+            ]).await?;
+        }
+        let len = legacy.len() as u64;
+        Self::set_transaction_count(len).await?;
+        rpc_db().delete(LEGACY_TX_REGISTRY_KEY).await?;
+        Ok(len)
+    }
+
+    /// Transaction hashes in insertion order
+    pub async fn get_transaction_hashes() -> Result<Vec<String>, StorageError> {
+        let len = Self::get_transaction_count().await?;
+        let mut hashes = Vec::with_capacity(len as usize);
+        for seq in 0..len {
+            if let Some(hash) = Self::get_transaction_hash_at(seq).await? {
+                hashes.push(hash);
+            }
+        }
+        Ok(hashes)
+    }
+
+    /// Hash at position `seq` of the transaction log
+    pub async fn get_transaction_hash_at(seq: u64) -> Result<Option<String>, StorageError> {
+        match rpc_db().get(Self::tx_log_key(seq).as_bytes()).await? {
+            Some(bytes) => {
+                let hash = String::from_utf8(bytes)
+                    .map_err(|e| StorageError::Serialization(e.to_string()))?;
+                Ok(Some(hash))
+            },
+            None => Ok(None),
         }
-        
-        Ok(())
     }
 
     pub async fn get_transaction(hash: &str) -> Result<Option<WalletTransaction>, StorageError> {
         let key = format!("tx:{}", hash);
-        match RPC_DB.get(key.as_bytes()).await? {
+        match rpc_db().get(key.as_bytes()).await? {
             Some(bytes) => {
                 let tx: WalletTransaction = serde_json::from_slice(&bytes)
                     .map_err(|e| StorageError::Serialization(e.to_string()))?;
@@ -483,12 +1803,8 @@ impl RPCStorage {
     }
 
     pub async fn get_latest_transactions(limit: usize) -> Result<Vec<WalletTransaction>, StorageError> {
-        // Get list of all transaction hashes from registry
-        let tx_registry_key = b"transaction_hashes_registry";
-        let tx_hashes: Vec<String> = match RPC_DB.get(tx_registry_key).await? {
-            Some(data) => serde_json::from_slice(&data).unwrap_or_default(),
-            None => Vec::new(),
-        };
+        // Get list of all transaction hashes from the log
+        let tx_hashes = Self::get_transaction_hashes().await?;
         
         let mut transactions = Vec::new();
         
@@ -507,57 +1823,116 @@ impl RPCStorage {
         Ok(transactions)
     }
 
-    /// Block height operations
+    /// Transactions logged before `before_hash` (from the newest when None), newest first, at most
+    /// `limit`. Pages are read by log position, so each reads only its own entries, and transactions
+    /// logged meanwhile never shift an older page.
+    pub async fn get_transactions_page(before_hash: Option<&str>, limit: usize) -> Result<TransactionPage, StorageError> {
+        let end = match before_hash {
+            Some(cursor) => Self::transaction_seq(cursor).await?
+                .ok_or_else(|| StorageError::NotFound(format!("transaction cursor {}", cursor)))?,
+            None => Self::get_transaction_count().await?,
+        };
+        let start = end.saturating_sub(limit as u64);
+
+        let mut transactions = Vec::with_capacity((end - start) as usize);
+        for seq in (start..end).rev() {
+            if let Some(hash) = Self::get_transaction_hash_at(seq).await? {
+                if let Some(tx) = Self::get_transaction(&hash).await? {
+                    transactions.push(tx);
+                }
+            }
+        }
+        let next_cursor = match start {
+            0 => None,
+            _ => Self::get_transaction_hash_at(start).await?,
+        };
+
+        Ok(TransactionPage { transactions, next_cursor })
+    }
+
+    /// Height of the highest stored block. Only `store_block` advances it,
+    /// so every endpoint reporting height agrees with what is actually stored.
     pub async fn get_block_height() -> Result<u64, StorageError> {
-        match RPC_DB.get(b"block_height").await? {
+        match rpc_db().get(b"block_height").await? {
             Some(bytes) => {
                 let mut arr = [0u8; 8];
                 arr.copy_from_slice(&bytes[..8.min(bytes.len())]);
                 Ok(u64::from_le_bytes(arr))
             },
-            None => Ok(1), // Genesis block
+            None => Ok(0), // Nothing stored yet
         }
     }
 
-    pub async fn set_block_height(height: u64) -> Result<(), StorageError> {
-        RPC_DB.put(b"block_height", &height.to_le_bytes()).await
-    }
-
-    pub async fn increment_block_height() -> Result<u64, StorageError> {
-        let current = Self::get_block_height().await?;
-        let new_height = current + 1;
-        Self::set_block_height(new_height).await?;
-        Ok(new_height)
+    /// Height the next mined block will take; pending until `store_block` succeeds
+    pub async fn next_block_height() -> Result<u64, StorageError> {
+        Ok(Self::get_block_height().await? + 1)
     }
 
     /// Transaction count operations
     pub async fn get_transaction_count() -> Result<u64, StorageError> {
-        // Get count from registry for accuracy
-        let tx_registry_key = b"transaction_hashes_registry";
-        match RPC_DB.get(tx_registry_key).await? {
-            Some(data) => {
-                let tx_hashes: Vec<String> = serde_json::from_slice(&data).unwrap_or_default();
-                Ok(tx_hashes.len() as u64)
-            },
-            None => Ok(0),
-        }
+        // The transaction log length is the count of unique transactions
+        let _guard = TX_LOG_LOCK.lock().await;
+        Self::tx_log_len_locked().await
+    }
+
+    /// Move hashes from the legacy single-blob registry into the transaction log, returning the
+    /// log length. A no-op once the log exists; the RPC server runs it before serving routes.
+    pub async fn migrate_legacy_transaction_registry() -> Result<u64, StorageError> {
+        let _guard = TX_LOG_LOCK.lock().await;
+        Self::tx_log_len_locked().await
     }
 
-    pub async fn set_transaction_count(count: u64) -> Result<(), StorageError> {
-        RPC_DB.put(b"transaction_count", &count.to_le_bytes()).await
+    async fn set_transaction_count(count: u64) -> Result<(), StorageError> {
+        rpc_db().put(b"transaction_count", &count.to_le_bytes()).await
     }
 
-    pub async fn increment_transaction_count() -> Result<u64, StorageError> {
-        let current = Self::get_transaction_count().await?;
-        let new_count = current + 1;
-        Self::set_transaction_count(new_count).await?;
-        Ok(new_count)
+    fn address_alias_key(legacy: &str) -> String {
+        format!("address_alias:{}", legacy)
+    }
+
+    /// Move balances stored under addresses generated before checksums to the checksummed address
+    /// the same key derives today, recording each old address so requests still using it resolve.
+    /// Returns how many balances moved; the RPC server runs it before serving routes.
+    pub async fn migrate_legacy_addresses() -> Result<usize, StorageError> {
+        use crate::wallet::key_manager::{AddressError, KeyManager, ADDRESS_PREFIX};
+
+        let mut moved = 0;
+        for (key, value) in rpc_db().scan_prefix(ADDRESS_PREFIX.as_bytes(), usize::MAX).await? {
+            let Ok(legacy) = String::from_utf8(key) else { continue };
+            if value.len() != 8 || KeyManager::validate_address(&legacy) != Err(AddressError::ChecksumMismatch) {
+                continue;
+            }
+            let Ok(upgraded) = KeyManager::upgrade_legacy_address(&legacy) else { continue };
+
+            let _guards = lock_addresses(&[legacy.as_str(), upgraded.as_str()]).await;
+            let balance = Self::get_balance(&legacy).await?;
+            let current = Self::get_balance(&upgraded).await?;
+            let merged = current.checked_add(balance).ok_or_else(|| StorageError::BalanceOverflow {
+                address: upgraded.clone(),
+                balance: current,
+                amount: balance,
+            })?;
+            rpc_db().write_batch(&[legacy.clone().into_bytes()], &[
+                (upgraded.clone().into_bytes(), merged.to_le_bytes().to_vec()),
+                (Self::address_alias_key(&legacy).into_bytes(), upgraded.into_bytes()),
+            ]).await?;
+            moved += 1;
+        }
+        Ok(moved)
+    }
+
+    /// The address a migrated pre-checksum `address` moved to, or `address` itself
+    pub async fn current_address(address: &str) -> Result<String, StorageError> {
+        match rpc_db().get(Self::address_alias_key(address).as_bytes()).await? {
+            Some(bytes) => String::from_utf8(bytes).map_err(|e| StorageError::Serialization(e.to_string())),
+            None => Ok(address.to_string()),
+        }
     }
 
     /// Device registration operations
     pub async fn get_device_registration(device_id: &str) -> Result<Option<serde_json::Value>, StorageError> {
         let key = format!("device_reg:{}", device_id);
-        match RPC_DB.get(key.as_bytes()).await? {
+        match rpc_db().get(key.as_bytes()).await? {
             Some(bytes) => {
                 let reg_data: serde_json::Value = serde_json::from_slice(&bytes)
                     .map_err(|e| StorageError::Serialization(e.to_string()))?;
@@ -571,19 +1946,19 @@ impl RPCStorage {
         let key = format!("device_reg:{}", device_id);
         let value = serde_json::to_vec(reg_data)
             .map_err(|e| StorageError::Serialization(e.to_string()))?;
-        RPC_DB.put(key.as_bytes(), &value).await
+        rpc_db().put(key.as_bytes(), &value).await
     }
 
     pub async fn remove_device_registration(device_id: &str) -> Result<(), StorageError> {
         let key = format!("device_reg:{}", device_id);
-        RPC_DB.delete(key.as_bytes()).await
+        rpc_db().delete(key.as_bytes()).await
     }
 
     /// Get all device addresses that are currently mining
     pub async fn get_all_mining_devices() -> Result<Vec<String>, StorageError> {
         // Get list of all device IDs from registry
         let device_registry_key = b"device_ids_registry";
-        let device_ids: Vec<String> = match RPC_DB.get(device_registry_key).await? {
+        let device_ids: Vec<String> = match rpc_db().get(device_registry_key).await? {
             Some(data) => serde_json::from_slice(&data).unwrap_or_default(),
             None => Vec::new(),
         };
@@ -609,7 +1984,7 @@ impl RPCStorage {
     pub async fn get_all_device_addresses() -> Result<std::collections::HashMap<String, String>, StorageError> {
         // Get list of all device IDs from registry
         let device_registry_key = b"device_ids_registry";
-        let device_ids: Vec<String> = match RPC_DB.get(device_registry_key).await? {
+        let device_ids: Vec<String> = match rpc_db().get(device_registry_key).await? {
             Some(data) => serde_json::from_slice(&data).unwrap_or_default(),
             None => Vec::new(),
         };
@@ -629,7 +2004,7 @@ impl RPCStorage {
     pub async fn get_device_id_by_address(address: &str) -> Result<Option<String>, StorageError> {
         // Get list of all device IDs from registry
         let device_registry_key = b"device_ids_registry";
-        let device_ids: Vec<String> = match RPC_DB.get(device_registry_key).await? {
+        let device_ids: Vec<String> = match rpc_db().get(device_registry_key).await? {
             Some(data) => serde_json::from_slice(&data).unwrap_or_default(),
             None => Vec::new(),
         };
@@ -649,7 +2024,7 @@ impl RPCStorage {
     /// Get session registry
     pub async fn get_session_registry() -> Result<Vec<String>, StorageError> {
         let session_keys_key = b"session_keys_registry";
-        let session_keys: Vec<String> = match RPC_DB.get(session_keys_key).await? {
+        let session_keys: Vec<String> = match rpc_db().get(session_keys_key).await? {
             Some(data) => serde_json::from_slice(&data).unwrap_or_default(),
             None => Vec::new(),
         };
@@ -676,7 +2051,7 @@ impl RPCStorage {
     /// Device PIN management operations
     pub async fn get_device_pin(device_id: &str) -> Result<String, StorageError> {
         let key = format!("device_pin:{}", device_id);
-        match RPC_DB.get(key.as_bytes()).await? {
+        match rpc_db().get(key.as_bytes()).await? {
             Some(bytes) => {
                 let pin_hash = String::from_utf8(bytes)
                     .map_err(|e| StorageError::Serialization(e.to_string()))?;
@@ -686,109 +2061,724 @@ impl RPCStorage {
         }
     }
 
-    pub async fn set_device_pin(device_id: &str, pin_hash: &str) -> Result<(), StorageError> {
+    /// Store an Argon2id hash of `pin`, never the PIN (or anything a key could be derived from)
+    pub async fn set_device_pin(device_id: &str, pin: &str) -> Result<(), StorageError> {
         let key = format!("device_pin:{}", device_id);
-        RPC_DB.put(key.as_bytes(), pin_hash.as_bytes()).await
+        rpc_db().put(key.as_bytes(), hash_pin(pin)?.as_bytes()).await
+    }
+
+    /// Whether `pin` matches the device's stored PIN. PINs stored as-is before hashing are
+    /// compared by digest and re-stored hashed on a match.
+    pub async fn verify_device_pin(device_id: &str, pin: &str) -> Result<bool, StorageError> {
+        use argon2::password_hash::{PasswordHash, PasswordVerifier};
+        let stored = Self::get_device_pin(device_id).await?;
+        if let Ok(hash) = PasswordHash::new(&stored) {
+            return Ok(argon2::Argon2::default().verify_password(pin.as_bytes(), &hash).is_ok());
+        }
+        if Sha256::digest(stored.as_bytes()) != Sha256::digest(pin.as_bytes()) {
+            return Ok(false);
+        }
+        Self::set_device_pin(device_id, pin).await?;
+        Ok(true)
     }
 
     pub async fn remove_device_pin(device_id: &str) -> Result<(), StorageError> {
         let key = format!("device_pin:{}", device_id);
-        RPC_DB.delete(key.as_bytes()).await
+        rpc_db().delete(key.as_bytes()).await
     }
 
     pub async fn get_device_failed_attempts(device_id: &str) -> Result<u32, StorageError> {
         let key = format!("device_failed_attempts:{}", device_id);
-        match RPC_DB.get_u64(&key).await {
+        match rpc_db().get_u64(&key).await {
             Ok(Some(value)) => Ok(value as u32),
             Ok(None) => Ok(0),
             Err(e) => Err(e),
         }
     }
 
-    pub async fn set_device_failed_attempts(device_id: &str, attempts: u32) -> Result<(), StorageError> {
-        let key = format!("device_failed_attempts:{}", device_id);
-        RPC_DB.set(&key, attempts as u64).await
+    pub async fn set_device_failed_attempts(device_id: &str, attempts: u32) -> Result<(), StorageError> {
+        let key = format!("device_failed_attempts:{}", device_id);
+        rpc_db().set(&key, attempts as u64).await
+    }
+
+    pub async fn get_device_lockout(device_id: &str) -> Result<u64, StorageError> {
+        let key = format!("device_lockout:{}", device_id);
+        match rpc_db().get_u64(&key).await {
+            Ok(Some(value)) => Ok(value),
+            Ok(None) => Ok(0),
+            Err(e) => Err(e),
+        }
+    }
+
+    pub async fn set_device_lockout(device_id: &str, lockout_time: u64) -> Result<(), StorageError> {
+        let key = format!("device_lockout:{}", device_id);
+        rpc_db().set(&key, lockout_time).await
+    }
+
+    /// Cleanup operations
+    pub async fn cleanup_old_sessions(max_age_seconds: u64) -> Result<u64, StorageError> {
+        let current_time = chrono::Utc::now().timestamp() as u64;
+        let cutoff_time = current_time.saturating_sub(max_age_seconds);
+        
+        // Get list of all device session keys from a registry
+        let session_keys_key = b"session_keys_registry";
+        let session_keys: Vec<String> = match rpc_db().get(session_keys_key).await? {
+            Some(data) => serde_json::from_slice(&data).unwrap_or_default(),
+            None => Vec::new(),
+        };
+        
+        let mut keys_to_delete = Vec::new();
+        let mut remaining_keys = Vec::new();
+        
+        for device_id in session_keys {
+            let session_key = format!("device_session:{}", device_id);
+            if let Ok(Some((_, timestamp))) = Self::get_device_session(&device_id).await {
+                if timestamp < cutoff_time {
+                    keys_to_delete.push(session_key);
+                } else {
+                    remaining_keys.push(device_id);
+                }
+            }
+        }
+        
+        // Delete expired sessions
+        let cleaned_count = keys_to_delete.len() as u64;
+        for key in &keys_to_delete {
+            rpc_db().delete(key.as_bytes()).await?;
+        }
+        
+        // Update the registry with remaining keys
+        let remaining_data = serde_json::to_vec(&remaining_keys)
+            .map_err(|e| StorageError::Serialization(e.to_string()))?;
+        rpc_db().put(session_keys_key, &remaining_data).await?;
+        
+        Ok(cleaned_count)
+    }
+
+    /// Block storage operations
+    pub async fn store_block(block: &Block) -> Result<(), StorageError> {
+        Self::store_block_applying(block, false).await
+    }
+
+    /// Store a block whose first transaction is its coinbase, applying the balance effects of
+    /// all its transactions (the coinbase and every transfer) in the same atomic write as the
+    /// block itself. A coinbase off the reward schedule or a transfer that overdraws its sender
+    /// rejects the whole block, and a block that no longer extends the stored tip is rejected as stale.
+    pub async fn store_mined_block(block: &Block) -> Result<(), StorageError> {
+        Self::store_block_applying(block, true).await
+    }
+
+    async fn store_block_applying(block: &Block, apply_balances: bool) -> Result<(), StorageError> {
+        // Held from the tip checks through the tip update so concurrent stores cannot interleave
+        let _tip_guard = BLOCK_TIP_LOCK.lock().await;
+
+        // Every mined block must carry a hash below its difficulty target; genesis is exempt
+        if block.height > 0 && !block.has_valid_pow() {
+            return Err(StorageError::InvalidBlock(format!(
+                "block {} hash {} does not meet difficulty {}",
+                block.height, block.hash, block.difficulty
+            )));
+        }
+
+        // Balances are only ever credited with the scheduled coinbase
+        if apply_balances {
+            block.verify_coinbase()
+                .map_err(|reason| StorageError::InvalidBlock(format!("block {}: {}", block.height, reason)))?;
+        }
+
+        // A block directly above a stored one must name it as parent; genesis and blocks above a gap are exempt
+        if block.height > 0 {
+            if let Some(previous) = Self::get_block_by_height(block.height - 1).await? {
+                if block.parent_hash != previous.hash {
+                    return Err(StorageError::InvalidBlock(format!(
+                        "block {} parent hash {} does not match block {} hash {}",
+                        block.height, block.parent_hash, previous.height, previous.hash
+                    )));
+                }
+            }
+        }
+
+        // Validate (or fill in) cumulative difficulty against the stored parent
+        let parent = Self::find_parent_block(block.height).await?;
+
+        // A mined block must claim a free height directly on the current tip
+        if apply_balances {
+            let tip_hash = parent.as_ref().map_or_else(|| "0".repeat(64), |parent| parent.hash.clone());
+            if Self::get_block_by_height(block.height).await?.is_some() || block.parent_hash != tip_hash {
+                return Err(StorageError::StaleBlock(format!(
+                    "block {} does not extend the stored tip {}",
+                    block.height, tip_hash
+                )));
+            }
+        }
+        let expected = block.expected_cumulative_difficulty(parent.as_ref());
+        if block.cumulative_difficulty != 0 && block.cumulative_difficulty != expected {
+            return Err(StorageError::InvalidBlock(format!(
+                "block {} cumulative difficulty {} does not match expected {}",
+                block.height, block.cumulative_difficulty, expected
+            )));
+        }
+        let mut block = block.clone();
+        block.cumulative_difficulty = expected;
+        let block = &block;
+
+        // The block, its hash index entry, its balance effects and its undo data land in one write
+        let mut batch = Self::block_entries(block)?;
+        let mut deletes = Vec::new();
+        let mut _credit_guards = None;
+        if apply_balances {
+            let addresses = Self::balance_addresses(block);
+            let guards = lock_addresses(&addresses).await;
+            let supply_guard = SUPPLY_LOCK.lock().await;
+
+            let mut balances = std::collections::HashMap::new();
+            for address in &addresses {
+                balances.insert(address.to_string(), Self::get_balance(address).await?);
+            }
+            let undo = BlockUndo {
+                balances: balances.clone(),
+                pending: Self::pending_records_in(block).await?,
+                settled: Self::settled_transfers(block, &std::collections::HashSet::new()).await?,
+            };
+            crate::chain_verify::apply_block_spends(&Self::without_settled(block, &undo.settled), &mut balances).map_err(|overdraft| {
+                StorageError::BalanceUnderflow { address: overdraft.address, balance: overdraft.balance, amount: overdraft.amount }
+            })?;
+            let tracked = rpc_db().get_u64(TRACKED_SUPPLY_KEY).await?.unwrap_or(0)
+                .saturating_add(Self::minted_in(block))
+                .saturating_sub(Self::burned_in(block, &undo.settled));
+
+            batch.extend(balances.into_iter().map(|(address, balance)| (address.into_bytes(), balance.to_le_bytes().to_vec())));
+            batch.push((TRACKED_SUPPLY_KEY.as_bytes().to_vec(), tracked.to_le_bytes().to_vec()));
+            batch.push(Self::block_undo_entry(block, &undo)?);
+            deletes.extend(Self::finalized_undo_key(block.height).await?);
+            _credit_guards = Some((guards, supply_guard));
+        }
+        rpc_db().write_batch(&deletes, &batch).await?;
+        drop(_credit_guards);
+
+        // Advance the tip; blocks stored below it (gaps, resubmissions) leave it unchanged
+        let tip_missing = rpc_db().get(b"block_height").await?.is_none();
+        if tip_missing || block.height > Self::get_block_height().await? {
+            rpc_db().put(b"block_height", &block.height.to_le_bytes()).await?;
+        }
+
+        Self::record_difficulty(&DifficultyPoint {
+            height: block.height,
+            difficulty: block.difficulty,
+            timestamp: block.timestamp,
+        }).await?;
+        
+        // Also store each transaction individually
+        for tx in &block.transactions {
+            Self::add_transaction(tx).await?;
+        }
+
+        // No receivers is not an error
+        let _ = BLOCK_EVENTS.send(block.clone());
+        Ok(())
+    }
+
+    /// Move the stored chain onto another branch in one atomic write: `reverted` (the stored tip
+    /// first) are rolled back from their undo data, and `applied` (oldest first, the first one
+    /// building on the block the reverted ones forked from) are stored with their balance effects.
+    /// Reverted transactions that no applied block includes again lose their records and go back
+    /// to the persisted mempool; their mempool records are returned. A reverted block without undo
+    /// data or an applied transfer that overdraws its sender rejects the whole update.
+    pub async fn apply_chain_update(reverted: &[Block], applied: &[Block]) -> Result<Vec<PendingTransaction>, StorageError> {
+        let _tip_guard = BLOCK_TIP_LOCK.lock().await;
+
+        let Some(first) = applied.first() else {
+            return Err(StorageError::InvalidBlock("chain update applies no blocks".to_string()));
+        };
+        for block in applied {
+            block.verify_transactions()
+                .and_then(|()| block.verify_coinbase())
+                .map_err(|reason| StorageError::InvalidBlock(format!("block {}: {}", block.height, reason)))?;
+        }
+        let expected_tip = reverted.first().map_or(&first.parent_hash, |block| &block.hash);
+        let stored_tip = Self::get_block_by_height(Self::get_block_height().await?).await?;
+        if stored_tip.as_ref().map(|block| &block.hash) != Some(expected_tip) {
+            return Err(StorageError::StaleBlock(format!(
+                "chain update from {} does not start at the stored tip",
+                expected_tip
+            )));
+        }
+
+        let addresses: Vec<&str> = reverted.iter().chain(applied).flat_map(Self::balance_addresses).collect();
+        let address_guards = lock_addresses(&addresses).await;
+        let supply_guard = SUPPLY_LOCK.lock().await;
+        let mempool_guard = MEMPOOL_LOCK.lock().await;
+
+        let mut balances = std::collections::HashMap::new();
+        for address in &addresses {
+            balances.insert(address.to_string(), Self::get_balance(address).await?);
+        }
+        let mut tracked = rpc_db().get_u64(TRACKED_SUPPLY_KEY).await?.unwrap_or(0);
+        let reapplied: std::collections::HashSet<&str> = applied.iter()
+            .flat_map(|block| block.transactions.iter().map(|tx| tx.hash.as_str()))
+            .collect();
+
+        let mut deletes = Vec::new();
+        let mut returned = Vec::new();
+        // Stored transactions whose balance effects the revert undoes, so an applied block spends them again
+        let mut respent = std::collections::HashSet::new();
+        for block in reverted {
+            let undo = Self::get_block_undo(&block.hash).await?.ok_or_else(|| {
+                StorageError::InvalidBlock(format!("block {} has no undo data to revert", block.height))
+            })?;
+            deletes.extend([
+                format!("block:{}", block.height).into_bytes(),
+                Self::block_hash_key(&block.hash).into_bytes(),
+                Self::block_undo_key(&block.hash).into_bytes(),
+                format!("difficulty:{}", block.height).into_bytes(),
+            ]);
+            // Transfers settled at submission keep their records; the block never moved their balances
+            for tx in block.transactions.iter().filter(|tx| !undo.settled.contains(&tx.hash)) {
+                respent.insert(tx.hash.clone());
+                if !reapplied.contains(tx.hash.as_str()) {
+                    deletes.push(format!("tx:{}", tx.hash).into_bytes());
+                    deletes.push(Self::tx_seq_key(&tx.hash).into_bytes());
+                }
+            }
+            balances.extend(undo.balances);
+            tracked = tracked.saturating_sub(Self::minted_in(block)).saturating_add(Self::burned_in(block, &undo.settled));
+            returned.extend(undo.pending);
+        }
+
+        let mut puts = Vec::new();
+        for block in applied {
+            let undo = BlockUndo {
+                balances: Self::balance_addresses(block).into_iter()
+                    .map(|address| (address.to_string(), balances.get(address).copied().unwrap_or(0)))
+                    .collect(),
+                pending: Self::pending_records_in_update(block, &returned).await?,
+                settled: Self::settled_transfers(block, &respent).await?,
+            };
+            crate::chain_verify::apply_block_spends(&Self::without_settled(block, &undo.settled), &mut balances).map_err(|overdraft| {
+                StorageError::BalanceUnderflow { address: overdraft.address, balance: overdraft.balance, amount: overdraft.amount }
+            })?;
+            tracked = tracked.saturating_add(Self::minted_in(block)).saturating_sub(Self::burned_in(block, &undo.settled));
+            // Mined transactions leave the mempool, whether they were held there or just returned to it
+            returned.retain(|pending| !undo.pending.iter().any(|mined| mined.transaction.hash == pending.transaction.hash));
+            deletes.extend(undo.pending.iter().map(|mined| Self::pending_key(&mined.transaction.hash)));
+
+            puts.extend(Self::block_entries(block)?);
+            puts.push(Self::block_undo_entry(block, &undo)?);
+            let point = DifficultyPoint { height: block.height, difficulty: block.difficulty, timestamp: block.timestamp };
+            puts.push((
+                format!("difficulty:{}", block.height).into_bytes(),
+                serde_json::to_vec(&point).map_err(|e| StorageError::Serialization(e.to_string()))?,
+            ));
+            // Only blocks below the fork point are still stored once this write lands
+            if block.height.checked_sub(MAX_REORG_DEPTH + 1).is_some_and(|finalized| finalized < first.height) {
+                deletes.extend(Self::finalized_undo_key(block.height).await?);
+            }
+        }
+        // Reverted transactions no applied block mined again go back; ones still held keep their record
+        for pending in &returned {
+            let key = Self::pending_key(&pending.transaction.hash);
+            if rpc_db().get(&key).await?.is_none() {
+                puts.push((key, serde_json::to_vec(pending).map_err(|e| StorageError::Serialization(e.to_string()))?));
+            }
+        }
+
+        let new_tip = applied.last().expect("checked non-empty above");
+        puts.extend(balances.into_iter().map(|(address, balance)| (address.into_bytes(), balance.to_le_bytes().to_vec())));
+        puts.push((TRACKED_SUPPLY_KEY.as_bytes().to_vec(), tracked.to_le_bytes().to_vec()));
+        puts.push((b"block_height".to_vec(), new_tip.height.to_le_bytes().to_vec()));
+        rpc_db().write_batch(&deletes, &puts).await?;
+        drop((address_guards, supply_guard, mempool_guard));
+
+        for block in applied {
+            for tx in &block.transactions {
+                Self::add_transaction(tx).await?;
+            }
+            let _ = BLOCK_EVENTS.send(block.clone());
+        }
+        Ok(returned)
+    }
+
+    /// Stored block and hash index entries for `block`
+    fn block_entries(block: &Block) -> Result<Vec<(Vec<u8>, Vec<u8>)>, StorageError> {
+        let serialized = serde_json::to_vec(block)
+            .map_err(|e| StorageError::Serialization(e.to_string()))?;
+        Ok(vec![
+            (format!("block:{}", block.height).into_bytes(), serialized),
+            (Self::block_hash_key(&block.hash).into_bytes(), block.height.to_le_bytes().to_vec()),
+        ])
+    }
+
+    /// Addresses whose balances `block`'s transactions change
+    fn balance_addresses(block: &Block) -> Vec<&str> {
+        let mut addresses = Vec::new();
+        for tx in &block.transactions {
+            match tx.transaction_type.as_str() {
+                "genesis" => {}
+                "mining_reward" => addresses.push(tx.to.as_str()),
+                _ => addresses.extend([tx.from.as_str(), tx.to.as_str()]),
+            }
+        }
+        addresses
+    }
+
+    /// Total paid out by `block`'s coinbase
+    fn minted_in(block: &Block) -> u64 {
+        block.transactions.iter()
+            .filter(|tx| tx.transaction_type == "mining_reward")
+            .fold(0u64, |total, tx| total.saturating_add(tx.amount))
+    }
+
+    /// Fees `block` burns; transfers settled at submission already paid theirs
+    fn burned_in(block: &Block, settled: &[String]) -> u64 {
+        block.transactions.iter()
+            .filter(|tx| !matches!(tx.transaction_type.as_str(), "mining_reward" | "genesis"))
+            .filter(|tx| !settled.contains(&tx.hash))
+            .fold(0u64, |total, tx| total.saturating_add(sender_fee(tx)))
+    }
+
+    /// Persisted mempool records for transactions `block` includes
+    async fn pending_records_in(block: &Block) -> Result<Vec<PendingTransaction>, StorageError> {
+        let mut records = Vec::new();
+        for tx in &block.transactions {
+            if let Some(pending) = Self::get_pending_transaction(&tx.hash).await? {
+                records.push(pending);
+            }
+        }
+        Ok(records)
+    }
+
+    /// `pending_records_in`, also finding records a chain update is returning to the mempool
+    async fn pending_records_in_update(block: &Block, returned: &[PendingTransaction]) -> Result<Vec<PendingTransaction>, StorageError> {
+        let mut records = Self::pending_records_in(block).await?;
+        for pending in returned {
+            let hash = format!("0x{}", hex::encode(pending.transaction.hash));
+            let included = block.transactions.iter().any(|tx| tx.hash == hash);
+            if included && !records.iter().any(|held| held.transaction.hash == pending.transaction.hash) {
+                records.push(pending.clone());
+            }
+        }
+        Ok(records)
+    }
+
+    /// Transfers in `block` that already have a stored record, i.e. were settled when submitted over RPC.
+    /// Hashes in `respent` are stored transfers a reverted block had applied; they count as unsettled.
+    async fn settled_transfers(block: &Block, respent: &std::collections::HashSet<String>) -> Result<Vec<String>, StorageError> {
+        let mut settled = Vec::new();
+        for tx in &block.transactions {
+            if matches!(tx.transaction_type.as_str(), "genesis" | "mining_reward") || respent.contains(&tx.hash) {
+                continue;
+            }
+            if rpc_db().get(format!("tx:{}", tx.hash).as_bytes()).await?.is_some() {
+                settled.push(tx.hash.clone());
+            }
+        }
+        Ok(settled)
+    }
+
+    /// `block` without the transfers in `settled`, whose balance effects are already stored
+    fn without_settled(block: &Block, settled: &[String]) -> Block {
+        let mut spends = block.clone();
+        spends.transactions.retain(|tx| !settled.contains(&tx.hash));
+        spends
+    }
+
+    fn block_undo_key(hash: &str) -> String {
+        format!("undo:{}", hash)
+    }
+
+    fn block_undo_entry(block: &Block, undo: &BlockUndo) -> Result<(Vec<u8>, Vec<u8>), StorageError> {
+        let serialized = serde_json::to_vec(undo)
+            .map_err(|e| StorageError::Serialization(e.to_string()))?;
+        Ok((Self::block_undo_key(&block.hash).into_bytes(), serialized))
+    }
+
+    async fn get_block_undo(hash: &str) -> Result<Option<BlockUndo>, StorageError> {
+        match rpc_db().get(Self::block_undo_key(hash).as_bytes()).await? {
+            Some(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| StorageError::Serialization(e.to_string())),
+            None => Ok(None),
+        }
+    }
+
+    /// Undo data of the stored block that falls out of the reorg window once a block lands at `height`
+    async fn finalized_undo_key(height: u64) -> Result<Option<Vec<u8>>, StorageError> {
+        let Some(finalized) = height.checked_sub(MAX_REORG_DEPTH + 1) else {
+            return Ok(None);
+        };
+        Ok(Self::get_block_by_height(finalized).await?.map(|block| Self::block_undo_key(&block.hash).into_bytes()))
+    }
+
+    /// Nearest stored block below `height` (heights may have gaps)
+    pub async fn find_parent_block(height: u64) -> Result<Option<Block>, StorageError> {
+        let mut current = height;
+        while current > 0 {
+            current -= 1;
+            if let Some(block) = Self::get_block_by_height(current).await? {
+                return Ok(Some(block));
+            }
+        }
+        Ok(None)
+    }
+
+    fn block_hash_key(hash: &str) -> String {
+        format!("block_hash:{}", hash)
+    }
+
+    /// Stored block with hash `hash`; a hash whose height has since been replaced is not found
+    pub async fn get_block_by_hash(hash: &str) -> Result<Option<Block>, StorageError> {
+        Self::backfill_block_hash_index().await?;
+        let Some(height) = rpc_db().get_u64(&Self::block_hash_key(hash)).await? else {
+            return Ok(None);
+        };
+        Ok(Self::get_block_by_height(height).await?.filter(|block| block.hash == hash))
+    }
+
+    /// Index the hashes of blocks stored before the hash index existed. Runs once per database;
+    /// later blocks are indexed as they are stored.
+    pub async fn backfill_block_hash_index() -> Result<(), StorageError> {
+        if rpc_db().get(BLOCK_HASH_INDEX_MARKER).await?.is_some() {
+            return Ok(());
+        }
+        let _guard = BLOCK_HASH_BACKFILL_LOCK.lock().await;
+        if rpc_db().get(BLOCK_HASH_INDEX_MARKER).await?.is_some() {
+            return Ok(());
+        }
+
+        let mut batch = Vec::new();
+        for (_, value) in rpc_db().scan_prefix(b"block:", usize::MAX).await? {
+            let block: Block = serde_json::from_slice(&value)
+                .map_err(|e| StorageError::Serialization(e.to_string()))?;
+            batch.push((Self::block_hash_key(&block.hash).into_bytes(), block.height.to_le_bytes().to_vec()));
+        }
+        log::info!("Backfilled the block hash index for {} stored blocks", batch.len());
+        batch.push((BLOCK_HASH_INDEX_MARKER.to_vec(), vec![1]));
+        rpc_db().put_batch(&batch).await
+    }
+
+    pub async fn get_block_by_height(height: u64) -> Result<Option<Block>, StorageError> {
+        let key = format!("block:{}", height);
+        match rpc_db().get(key.as_bytes()).await? {
+            Some(data) => {
+                let block: Block = serde_json::from_slice(&data)
+                    .map_err(|e| StorageError::Serialization(e.to_string()))?;
+                Ok(Some(block))
+            },
+            None => Ok(None),
+        }
+    }
+
+    fn pending_key(hash: &[u8; 32]) -> Vec<u8> {
+        format!("{}{}", MEMPOOL_TX_PREFIX, hex::encode(hash)).into_bytes()
     }
 
-    pub async fn get_device_lockout(device_id: &str) -> Result<u64, StorageError> {
-        let key = format!("device_lockout:{}", device_id);
-        match RPC_DB.get_u64(&key).await {
-            Ok(Some(value)) => Ok(value),
-            Ok(None) => Ok(0),
-            Err(e) => Err(e),
+    fn pending_entry(pending: &PendingTransaction) -> Result<(Vec<u8>, Vec<u8>), StorageError> {
+        let serialized = serde_json::to_vec(pending)
+            .map_err(|e| StorageError::Serialization(e.to_string()))?;
+        Ok((Self::pending_key(&pending.transaction.hash), serialized))
+    }
+
+    /// Persisted mempool record of a transaction hash ("0x"-prefixed hex)
+    async fn get_pending_transaction(hash: &str) -> Result<Option<PendingTransaction>, StorageError> {
+        let key = format!("{}{}", MEMPOOL_TX_PREFIX, hash.trim_start_matches("0x").to_lowercase());
+        match rpc_db().get(key.as_bytes()).await? {
+            Some(bytes) => serde_json::from_slice(&bytes)
+                .map(Some)
+                .map_err(|e| StorageError::Serialization(e.to_string())),
+            None => Ok(None),
         }
     }
 
-    pub async fn set_device_lockout(device_id: &str, lockout_time: u64) -> Result<(), StorageError> {
-        let key = format!("device_lockout:{}", device_id);
-        RPC_DB.set(&key, lockout_time).await
+    /// Persisted mempool, oldest first
+    pub async fn load_pending_transactions() -> Result<Vec<PendingTransaction>, StorageError> {
+        let mut records = rpc_db().scan_prefix(MEMPOOL_TX_PREFIX.as_bytes(), usize::MAX).await?
+            .into_iter()
+            .map(|(_, bytes)| serde_json::from_slice::<PendingTransaction>(&bytes)
+                .map_err(|e| StorageError::Serialization(e.to_string())))
+            .collect::<Result<Vec<_>, _>>()?;
+        records.sort_by_key(|pending| pending.received_at);
+        Ok(records)
     }
 
-    /// Cleanup operations
-    pub async fn cleanup_old_sessions(max_age_seconds: u64) -> Result<u64, StorageError> {
-        let current_time = chrono::Utc::now().timestamp() as u64;
-        let cutoff_time = current_time.saturating_sub(max_age_seconds);
-        
-        // Get list of all device session keys from a registry
-        let session_keys_key = b"session_keys_registry";
-        let session_keys: Vec<String> = match RPC_DB.get(session_keys_key).await? {
-            Some(data) => serde_json::from_slice(&data).unwrap_or_default(),
-            None => Vec::new(),
+    /// Split a mempool persisted as one blob into a record per transaction. Returns how many
+    /// records moved; nodes run it before restoring their mempool.
+    pub async fn migrate_legacy_mempool() -> Result<usize, StorageError> {
+        let _guard = MEMPOOL_LOCK.lock().await;
+        let Some(bytes) = rpc_db().get(LEGACY_MEMPOOL_KEY).await? else {
+            return Ok(0);
         };
-        
-        let mut keys_to_delete = Vec::new();
-        let mut remaining_keys = Vec::new();
-        
-        for device_id in session_keys {
-            let session_key = format!("device_session:{}", device_id);
-            if let Ok(Some((_, timestamp))) = Self::get_device_session(&device_id).await {
-                if timestamp < cutoff_time {
-                    keys_to_delete.push(session_key);
-                } else {
-                    remaining_keys.push(device_id);
+        let records: Vec<PendingTransaction> = serde_json::from_slice(&bytes)
+            .map_err(|e| StorageError::Serialization(e.to_string()))?;
+        let mut puts = Vec::with_capacity(records.len());
+        for pending in &records {
+            if rpc_db().get(&Self::pending_key(&pending.transaction.hash)).await?.is_none() {
+                puts.push(Self::pending_entry(pending)?);
+            }
+        }
+        rpc_db().write_batch(&[LEGACY_MEMPOOL_KEY.to_vec()], &puts).await?;
+        Ok(puts.len())
+    }
+
+    /// Persist a transaction entering the mempool, valid for MEMPOOL_TX_TTL seconds from `now`;
+    /// already-persisted ones keep their first-seen time
+    pub async fn add_pending_transaction(
+        tx: &crate::consensus::vortex_consensus::Transaction,
+        now: u64,
+    ) -> Result<(), StorageError> {
+        let _guard = MEMPOOL_LOCK.lock().await;
+        if rpc_db().get(&Self::pending_key(&tx.hash)).await?.is_some() {
+            return Ok(());
+        }
+        let (key, value) = Self::pending_entry(&PendingTransaction {
+            transaction: tx.clone(),
+            received_at: now,
+            valid_until: now.saturating_add(*MEMPOOL_TX_TTL),
+        })?;
+        rpc_db().put(&key, &value).await
+    }
+
+    /// Unexpired pending transactions, oldest first
+    pub async fn get_mempool(now: u64) -> Result<Vec<MempoolEntry>, StorageError> {
+        Ok(Self::load_pending_transactions().await?
+            .iter()
+            .filter(|p| !p.is_expired(now))
+            .map(|p| p.to_entry(now))
+            .collect())
+    }
+
+    /// Forget persisted transactions that were mined or dropped
+    pub async fn remove_pending_transactions(hashes: &[[u8; 32]]) -> Result<(), StorageError> {
+        if hashes.is_empty() {
+            return Ok(());
+        }
+        let _guard = MEMPOOL_LOCK.lock().await;
+        let keys: Vec<Vec<u8>> = hashes.iter().map(Self::pending_key).collect();
+        rpc_db().delete_batch(&keys).await
+    }
+
+    /// Remember that a transaction was evicted unmined at `valid_until`; the `expired_at:` entry
+    /// orders records by expiry so pruning reads only the ones past the retention window
+    pub async fn record_expired_transaction(hash: &[u8; 32], valid_until: u64) -> Result<(), StorageError> {
+        let hash = format!("0x{}", hex::encode(hash));
+        let mut by_expiry = crate::storage::ordered_key("expired_at:", valid_until);
+        by_expiry.extend_from_slice(format!(":{}", hash).as_bytes());
+        rpc_db().put_batch(&[
+            (format!("expired_tx:{}", hash).into_bytes(), valid_until.to_le_bytes().to_vec()),
+            (by_expiry, Vec::new()),
+        ]).await
+    }
+
+    /// Delete expired-transaction records whose expiry is more than EXPIRED_TX_RETENTION seconds
+    /// before `now`; returns how many were pruned
+    pub async fn prune_expired_transaction_records(now: u64) -> Result<usize, StorageError> {
+        let cutoff = now.saturating_sub(*EXPIRED_TX_RETENTION);
+        let mut pruned = 0;
+        loop {
+            let stale = rpc_db().scan_range(
+                b"expired_at:",
+                &crate::storage::ordered_key("expired_at:", cutoff),
+                EXPIRED_TX_PRUNE_BATCH,
+            ).await?;
+            if stale.is_empty() {
+                return Ok(pruned);
+            }
+            let mut keys = Vec::with_capacity(stale.len() * 2);
+            for (key, _) in &stale {
+                // expired_at:<20-digit expiry>:<hash>
+                if let Some(hash) = key.get("expired_at:".len() + 21..) {
+                    keys.push([b"expired_tx:".as_slice(), hash].concat());
                 }
+                keys.push(key.clone());
             }
+            rpc_db().delete_batch(&keys).await?;
+            pruned += stale.len();
         }
-        
-        // Delete expired sessions
-        let cleaned_count = keys_to_delete.len() as u64;
-        for key in &keys_to_delete {
-            RPC_DB.delete(key.as_bytes()).await?;
+    }
+
+    /// Drop persisted transactions past their expiry, recording each as expired, and prune expired
+    /// records past the retention window; returns the newly expired hashes
+    pub async fn expire_pending_transactions(now: u64) -> Result<Vec<[u8; 32]>, StorageError> {
+        Self::prune_expired_transaction_records(now).await?;
+        let _guard = MEMPOOL_LOCK.lock().await;
+        let expired: Vec<PendingTransaction> = Self::load_pending_transactions().await?
+            .into_iter()
+            .filter(|p| p.is_expired(now))
+            .collect();
+        for pending in &expired {
+            Self::record_expired_transaction(&pending.transaction.hash, pending.expires_at()).await?;
         }
-        
-        // Update the registry with remaining keys
-        let remaining_data = serde_json::to_vec(&remaining_keys)
-            .map_err(|e| StorageError::Serialization(e.to_string()))?;
-        RPC_DB.put(session_keys_key, &remaining_data).await?;
-        
-        Ok(cleaned_count)
+        let hashes: Vec<[u8; 32]> = expired.iter().map(|p| p.transaction.hash).collect();
+        let keys: Vec<Vec<u8>> = hashes.iter().map(Self::pending_key).collect();
+        rpc_db().delete_batch(&keys).await?;
+        Ok(hashes)
+    }
+
+    /// Mempool status of a transaction hash ("0x"-prefixed hex); None if this node never held it unmined
+    pub async fn pending_status(hash: &str, now: u64) -> Result<Option<PendingStatus>, StorageError> {
+        let hash = format!("0x{}", hash.trim_start_matches("0x").to_lowercase());
+        if let Some(pending) = Self::get_pending_transaction(&hash).await? {
+            let valid_until = pending.expires_at();
+            return Ok(Some(if pending.is_expired(now) {
+                PendingStatus::Expired { valid_until }
+            } else {
+                PendingStatus::Pending { valid_until }
+            }));
+        }
+        Ok(rpc_db().get_u64(&format!("expired_tx:{}", hash)).await?
+            .map(|valid_until| PendingStatus::Expired { valid_until }))
     }
 
-    /// Block storage operations
-    pub async fn store_block(block: &Block) -> Result<(), StorageError> {
-        let key = format!("block:{}", block.height);
-        let serialized = serde_json::to_string(block)
+    /// Index a block's difficulty so history queries don't deserialize whole blocks
+    pub async fn record_difficulty(point: &DifficultyPoint) -> Result<(), StorageError> {
+        let serialized = serde_json::to_vec(point)
             .map_err(|e| StorageError::Serialization(e.to_string()))?;
-        RPC_DB.put(key.as_bytes(), serialized.as_bytes()).await?;
-        
-        // Also store each transaction individually
-        for tx in &block.transactions {
-            Self::add_transaction(tx).await?;
-        }
-        
+        rpc_db().put(format!("difficulty:{}", point.height).as_bytes(), &serialized).await?;
         Ok(())
     }
 
-    pub async fn get_block_by_height(height: u64) -> Result<Option<Block>, StorageError> {
-        let key = format!("block:{}", height);
-        match RPC_DB.get(key.as_bytes()).await? {
-            Some(data) => {
-                let block: Block = serde_json::from_slice(&data)
-                    .map_err(|e| StorageError::Serialization(e.to_string()))?;
-                Ok(Some(block))
-            },
-            None => Ok(None),
+    /// Per-height difficulty for `from..=to`, ascending, capped at MAX_DIFFICULTY_HISTORY_SPAN heights.
+    /// Blocks stored before the index existed are read from the block itself.
+    pub async fn get_difficulty_history(from: u64, to: u64) -> Result<Vec<DifficultyPoint>, StorageError> {
+        let to = to.min(from.saturating_add(MAX_DIFFICULTY_HISTORY_SPAN - 1));
+        let mut points = Vec::new();
+
+        for height in from..=to {
+            match rpc_db().get(format!("difficulty:{}", height).as_bytes()).await? {
+                Some(data) => {
+                    let point: DifficultyPoint = serde_json::from_slice(&data)
+                        .map_err(|e| StorageError::Serialization(e.to_string()))?;
+                    points.push(point);
+                },
+                None => {
+                    if let Some(block) = Self::get_block_by_height(height).await? {
+                        points.push(DifficultyPoint {
+                            height,
+                            difficulty: block.difficulty,
+                            timestamp: block.timestamp,
+                        });
+                    }
+                },
+            }
         }
+        Ok(points)
+    }
+
+    /// Difficulty of the tip and the retarget the last DIFFICULTY_WINDOW block times call for.
+    /// The retarget is advisory: blocks must still declare BLOCK_DIFFICULTY (see `Block::expected_difficulty`).
+    pub async fn get_difficulty_status() -> Result<DifficultyStatus, StorageError> {
+        use crate::consensus::{DifficultyAdjuster, DIFFICULTY_WINDOW, TARGET_BLOCK_TIME_SECS};
+
+        let tip = Self::get_block_height().await?;
+        let points = Self::get_difficulty_history(tip.saturating_sub(DIFFICULTY_WINDOW as u64), tip).await?;
+        let timestamps: Vec<u64> = points.iter().map(|p| p.timestamp).collect();
+        let difficulty = points.last().map(|p| p.difficulty).unwrap_or(BLOCK_DIFFICULTY as u64);
+
+        let adjuster = DifficultyAdjuster::new(TARGET_BLOCK_TIME_SECS, DIFFICULTY_WINDOW as u64);
+        Ok(DifficultyStatus {
+            difficulty,
+            next_difficulty: adjuster.next_difficulty(difficulty, &timestamps, TARGET_BLOCK_TIME_SECS),
+            avg_block_time: DifficultyAdjuster::average_block_time(&timestamps),
+        })
     }
 
     pub async fn get_blocks_range(start_height: u64, end_height: u64) -> Result<Vec<Block>, StorageError> {
@@ -835,6 +2825,48 @@ impl RPCStorage {
 
 
 
+    /// Blocks below `before_height` (newest first) or, when paging forward, above `after_height`
+    /// (oldest first), at most `limit`. Heights are the cursor, so blocks appended at the tip
+    /// while a client pages backward never shift or repeat entries.
+    pub async fn get_blocks_page(
+        before_height: Option<u64>,
+        after_height: Option<u64>,
+        limit: usize,
+    ) -> Result<BlockPage, StorageError> {
+        let tip = Self::get_block_height().await?;
+        let mut blocks = Vec::new();
+
+        if let (Some(after), None) = (after_height, before_height) {
+            let mut height = after.saturating_add(1);
+            while blocks.len() < limit && height <= tip {
+                if let Some(block) = Self::get_block_by_height(height).await? {
+                    blocks.push(block);
+                }
+                height += 1;
+            }
+            let next_cursor = match blocks.last() {
+                Some(last) if blocks.len() == limit && last.height < tip => Some(last.height),
+                _ => None,
+            };
+            return Ok(BlockPage { blocks, next_cursor });
+        }
+
+        // Heights in after_height+1..before_height, walked down from the top
+        let floor = after_height.map(|h| h.saturating_add(1)).unwrap_or(0);
+        let mut height = before_height.unwrap_or(tip.saturating_add(1));
+        while blocks.len() < limit && height > floor {
+            height -= 1;
+            if let Some(block) = Self::get_block_by_height(height).await? {
+                blocks.push(block);
+            }
+        }
+        let next_cursor = match blocks.last() {
+            Some(last) if blocks.len() == limit && last.height > floor => Some(last.height),
+            _ => None,
+        };
+        Ok(BlockPage { blocks, next_cursor })
+    }
+
     pub async fn create_genesis_block() -> Result<(), StorageError> {
         // Check if genesis block already exists
         if Self::get_block_by_height(0).await?.is_some() {
@@ -847,22 +2879,25 @@ impl RPCStorage {
             match std::fs::read_to_string(genesis_config_path) {
                 Ok(config_data) => {
                     if let Ok(genesis_config) = serde_json::from_str::<serde_json::Value>(&config_data) {
+                        // Refuse to write a genesis whose allocations do not add up to the supply
+                        crate::genesis::validate_genesis(&genesis_config)
+                            .map_err(|e| StorageError::InvalidBlock(format!("genesis config {}: {}", genesis_config_path, e)))?;
                         // Initialize ecosystem wallets with genesis allocations
-                        if let Some(alloc) = genesis_config["alloc"].as_object() {
-                            for (address, allocation) in alloc {
-                                if let Some(balance_str) = allocation["balance"].as_str() {
-                                    // Convert from wei (18 decimals) to microFVC (6 decimals)
-                                    if let Ok(balance_wei) = balance_str.parse::<u128>() {
-                                        let balance_fvc = (balance_wei / 1_000_000_000_000u128) as u64; // Convert wei to microFVC
-                                        if let Err(e) = Self::set_balance(address, balance_fvc).await {
-                                            println!("Warning: Failed to set genesis balance for {}: {}", address, e);
-                                        } else {
-                                            println!("✅ Genesis allocation: {} = {} FVC", address, balance_fvc as f64 / 1_000_000.0);
-                                        }
-                                    }
-                                }
+                        let allocations = genesis_allocations(&genesis_config);
+                        let genesis_supply = allocations.balances.values().fold(0u64, |sum, b| sum.saturating_add(*b));
+                        rpc_db().set(GENESIS_SUPPLY_KEY, genesis_supply).await?;
+                        for (address, balance_fvc) in allocations.balances {
+                            // Kept separately so historical balances can replay from genesis
+                            rpc_db().set(&Self::genesis_allocation_key(&address), balance_fvc).await?;
+                            if let Err(e) = Self::set_balance(&address, balance_fvc).await {
+                                println!("Warning: Failed to set genesis balance for {}: {}", address, e);
+                            } else {
+                                println!("✅ Genesis allocation: {} = {} FVC", address, balance_fvc as f64 / 1_000_000.0);
                             }
                         }
+                        if allocations.rounding_loss_wei > 0 {
+                            println!("⚠️  Genesis rounding loss: {} wei truncated below 1 microFVC", allocations.rounding_loss_wei);
+                        }
                     }
                 },
                 Err(e) => println!("Warning: Failed to read genesis config: {}", e)
@@ -888,12 +2923,83 @@ impl RPCStorage {
             timestamp: genesis_timestamp, // Use consistent timestamp
             transaction_type: "genesis".to_string(),
             block_height: 0,
+            nonce: 0,
+            fee: 0,
+            signature: None,
         };
         
         genesis_block.add_transaction(genesis_tx);
+        genesis_block.hash = genesis_block.canonical_hash();
         Self::store_block(&genesis_block).await
     }
 
+    /// Address label operations
+    pub async fn get_address_label(address: &str) -> Result<Option<String>, StorageError> {
+        let key = format!("address_label:{}", address);
+        match rpc_db().get(key.as_bytes()).await? {
+            Some(bytes) => {
+                let label = String::from_utf8(bytes)
+                    .map_err(|e| StorageError::Serialization(e.to_string()))?;
+                Ok(Some(label))
+            },
+            None => Ok(None),
+        }
+    }
+
+    pub async fn set_address_label(address: &str, label: &str) -> Result<(), StorageError> {
+        let key = format!("address_label:{}", address);
+        rpc_db().put(key.as_bytes(), label.as_bytes()).await
+    }
+
+    pub async fn clear_address_label(address: &str) -> Result<(), StorageError> {
+        let key = format!("address_label:{}", address);
+        rpc_db().delete(key.as_bytes()).await
+    }
+
+    /// API keys are stored by SHA-256 hash only, mapped to their scope name
+    pub async fn get_api_key_scope(key_hash: &str) -> Result<Option<String>, StorageError> {
+        let key = format!("api_key:{}", key_hash);
+        match rpc_db().get(key.as_bytes()).await? {
+            Some(bytes) => {
+                let scope = String::from_utf8(bytes)
+                    .map_err(|e| StorageError::Serialization(e.to_string()))?;
+                Ok(Some(scope))
+            },
+            None => Ok(None),
+        }
+    }
+
+    pub async fn set_api_key_scope(key_hash: &str, scope: &str) -> Result<(), StorageError> {
+        let key = format!("api_key:{}", key_hash);
+        rpc_db().put(key.as_bytes(), scope.as_bytes()).await
+    }
+
+    pub async fn remove_api_key(key_hash: &str) -> Result<(), StorageError> {
+        let key = format!("api_key:{}", key_hash);
+        rpc_db().delete(key.as_bytes()).await
+    }
+
+    /// Label the ecosystem wallets, keeping any label an admin has already set
+    pub async fn preload_ecosystem_labels() -> Result<(), StorageError> {
+        for (address, label) in ECOSYSTEM_ADDRESS_LABELS {
+            if Self::get_address_label(address).await?.is_none() {
+                Self::set_address_label(address, label).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Balance and explorer label for an address
+    pub async fn get_account_info(address: &str) -> Result<serde_json::Value, StorageError> {
+        let balance = Self::get_balance(address).await?;
+        let label = Self::get_address_label(address).await?;
+        Ok(serde_json::json!({
+            "address": address,
+            "balance": balance,
+            "label": label
+        }))
+    }
+
     /// Get network information
     pub async fn get_network_info() -> Result<serde_json::Value, StorageError> {
         let block_height = Self::get_block_height().await.unwrap_or(1);
@@ -902,25 +3008,30 @@ impl RPCStorage {
         // Get real active nodes count from cached cluster health
         let active_nodes = crate::node_health::get_active_nodes_count();
         
-        // Calculate Smart Rate and vPoW indicators
-        let smart_rate = Self::calculate_smart_rate(block_height, transaction_count).await;
-        let vortex_energy_rate = Self::calculate_vortex_energy_rate(block_height, transaction_count).await;
-        let fractal_contribution_score = Self::calculate_fractal_contribution_score(block_height, transaction_count).await;
-        let mathematical_efficiency_index = Self::calculate_mathematical_efficiency_index(block_height).await;
-        let network_harmony_factor = Self::calculate_network_harmony_factor(block_height, transaction_count).await;
+        // Smart Rate and vPoW indicators
+        let smart_rate = crate::smart_rate::compute(block_height, transaction_count);
+        let cumulative_difficulty = Self::get_latest_blocks(1).await
+            .ok()
+            .and_then(|blocks| blocks.first().map(|b| b.cumulative_difficulty))
+            .unwrap_or(0);
+        let avg_block_time = Self::get_difficulty_status().await
+            .ok()
+            .and_then(|status| status.avg_block_time);
         
         Ok(serde_json::json!({
             "latest_block_height": block_height,
+            "cumulative_difficulty": cumulative_difficulty,
             "active_nodes": active_nodes,
             "total_supply": 3600900000u64,
             "circulating_supply": 3583900000u64,
             "transaction_count": transaction_count,
-            "avg_block_time": 5,
-            "network_smart_rate": smart_rate,
-            "avg_vortex_energy_rate": vortex_energy_rate,
-            "avg_fractal_contribution_score": fractal_contribution_score,
-            "mathematical_efficiency_index": mathematical_efficiency_index,
-            "network_harmony_factor": network_harmony_factor
+            "avg_block_time": avg_block_time,
+            "network_smart_rate": smart_rate.smart_rate,
+            "avg_vortex_energy_rate": smart_rate.vortex_energy_rate,
+            "avg_fractal_contribution_score": smart_rate.fractal_contribution_score,
+            "mathematical_efficiency_index": smart_rate.mathematical_efficiency_index,
+            "network_harmony_factor": smart_rate.network_harmony_factor,
+            "smart_rate_components": smart_rate
         }))
     }
 
@@ -938,152 +3049,942 @@ impl RPCStorage {
             Err(_) => 1
         };
         
-        let smart_rate = Self::calculate_smart_rate(block_height, transaction_count).await;
+        let smart_rate = crate::smart_rate::compute(block_height, transaction_count).smart_rate;
+        let difficulty = Self::get_difficulty_status().await?;
         
         Ok(serde_json::json!({
             "blocks_mined": block_height,
             "total_transactions": transaction_count,
             "network_smart_rate": smart_rate,
             "smart_rate_unit": "ss/s",
-            "difficulty": 1000000,
+            "difficulty": difficulty.difficulty,
+            "next_difficulty": difficulty.next_difficulty,
+            "avg_block_time": difficulty.avg_block_time,
             "active_miners": active_miners,
             "last_block_time": chrono::Utc::now().timestamp()
         }))
     }
 
-    /// Calculate Vortex Energy Rate (VER)
-    async fn calculate_vortex_energy_rate(block_height: u64, transaction_count: u64) -> f64 {
-        if block_height == 0 {
-            return 0.0;
+    /// Smart Rate of the stored chain, with its component breakdown
+    pub async fn get_smart_rate() -> Result<crate::smart_rate::SmartRate, StorageError> {
+        let block_height = Self::get_block_height().await?;
+        let transaction_count = Self::get_transaction_count().await?;
+        Ok(crate::smart_rate::compute(block_height, transaction_count))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chain(difficulties: &[u32]) -> Vec<Block> {
+        let mut blocks: Vec<Block> = Vec::new();
+        for (height, &difficulty) in difficulties.iter().enumerate() {
+            let mut block = Block::new_with_real_hash_and_timestamp(
+                height as u64,
+                "miner".to_string(),
+                blocks.last().map(|b| b.hash.clone()).unwrap_or_default(),
+                [height as u8; 32],
+                0,
+                difficulty,
+                1_700_000_000 + height as u64 * 5,
+            );
+            block.link_to_parent(blocks.last());
+            blocks.push(block);
         }
-        
-        // Base transaction throughput
-        let throughput = transaction_count as f64 / block_height as f64;
-        
-        // Energy efficiency factor (simulated)
-        let energy_efficiency = 0.85; // 85% efficiency
-        
-        // Network load factor
-        let network_load = (throughput / 10.0).min(1.0); // Normalize to max 10 tx/block
-        
-        // Vortex Energy Rate calculation
-        let ver = throughput * energy_efficiency * (1.0 + network_load) * 100.0;
-        
-        // Ensure minimum value and round
-        ver.max(1.0).min(1000.0)
+        blocks
     }
 
-    /// Calculate Fractal Contribution Score (FCS)
-    async fn calculate_fractal_contribution_score(block_height: u64, transaction_count: u64) -> f64 {
-        if block_height <= 1 {
-            return 0.0;
+    #[test]
+    fn test_cumulative_difficulty_is_monotonic() {
+        let blocks = chain(&[1, 2, 2, 3, 1]);
+
+        assert_eq!(blocks[0].cumulative_difficulty, 1);
+        assert_eq!(blocks.last().unwrap().cumulative_difficulty, 9);
+        for pair in blocks.windows(2) {
+            assert!(pair[1].cumulative_difficulty > pair[0].cumulative_difficulty);
+            assert_eq!(pair[1].cumulative_difficulty, pair[1].expected_cumulative_difficulty(Some(&pair[0])));
         }
-        
-        // Fractal depth based on block height
-        let fractal_depth = (block_height as f64).log2().floor();
-        
-        // Transaction density
-        let tx_density = transaction_count as f64 / block_height as f64;
-        
-        // Fractal pattern strength
-        let pattern_strength = (fractal_depth / 20.0).min(1.0); // Normalize to max depth 20
-        
-        // Contribution score calculation
-        let fcs = tx_density * (1.0 + pattern_strength) * fractal_depth * 10.0;
-        
-        // Ensure minimum value and cap at 1000
-        fcs.max(1.0).min(1000.0)
     }
 
-    /// Calculate Mathematical Efficiency Index (MEI)
-    async fn calculate_mathematical_efficiency_index(block_height: u64) -> f64 {
-        let transaction_count = Self::get_transaction_count().await.unwrap_or(0);
-        
-        if block_height == 0 {
-            return 0.0;
+    #[test]
+    fn test_fork_choice_prefers_heavier_tip() {
+        // The longer fork has less total work than the shorter, harder one
+        let long_fork = chain(&[1, 1, 1, 1, 1]);
+        let heavy_fork = chain(&[1, 4, 4]);
+        let long_tip = long_fork.last().unwrap();
+        let heavy_tip = heavy_fork.last().unwrap();
+
+        assert!(long_tip.height > heavy_tip.height);
+        assert_eq!(select_heavier_tip(long_tip, heavy_tip).hash, heavy_tip.hash);
+        assert_eq!(select_heavier_tip(heavy_tip, long_tip).hash, heavy_tip.hash);
+    }
+
+    #[test]
+    fn test_history_below_cap() {
+        let history: Vec<u32> = (0..10).collect();
+        let page = paginate_history(history, Some(5), None, 50);
+
+        assert_eq!(page.items, vec![0, 1, 2, 3, 4]);
+        assert_eq!(page.next_cursor, Some(5));
+        assert_eq!(page.total_count, 10);
+
+        let history: Vec<u32> = (0..3).collect();
+        let page = paginate_history(history, Some(5), None, 50);
+        assert_eq!(page.items.len(), 3);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn test_history_at_cap() {
+        let history: Vec<u32> = (0..50).collect();
+        let page = paginate_history(history, Some(50), None, 50);
+
+        assert_eq!(page.items.len(), 50);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn test_history_above_cap_returns_cursor() {
+        let history: Vec<u32> = (0..120).collect();
+        let page = paginate_history(history.clone(), Some(500), None, 50);
+
+        assert_eq!(page.items.len(), 50);
+        assert_eq!(page.next_cursor, Some(50));
+
+        let page = paginate_history(history.clone(), Some(500), page.next_cursor, 50);
+        assert_eq!(page.items.first(), Some(&50));
+        assert_eq!(page.next_cursor, Some(100));
+
+        let page = paginate_history(history, Some(500), page.next_cursor, 50);
+        assert_eq!(page.items.len(), 20);
+        assert_eq!(page.next_cursor, None);
+    }
+
+    #[test]
+    fn test_genesis_rounding_loss_is_reported() {
+        let config = serde_json::json!({
+            "alloc": {
+                "fvcexact": { "balance": "5000000000000000000" },
+                "fvcodd": { "balance": "1000000000000123456" },
+                "fvcdust": { "balance": "999999999999" }
+            }
+        });
+
+        let allocations = genesis_allocations(&config);
+        assert_eq!(allocations.balances["fvcexact"], 5_000_000);
+        assert_eq!(allocations.balances["fvcodd"], 1_000_000);
+        assert_eq!(allocations.balances["fvcdust"], 0);
+        assert_eq!(allocations.rounding_loss_wei, 123_456 + 999_999_999_999);
+    }
+
+    #[test]
+    fn test_genesis_even_allocations_have_no_loss() {
+        let config = serde_json::json!({
+            "alloc": { "fvcexact": { "balance": "2000000000000" } }
+        });
+
+        let allocations = genesis_allocations(&config);
+        assert_eq!(allocations.balances["fvcexact"], 2);
+        assert_eq!(allocations.rounding_loss_wei, 0);
+    }
+
+    #[test]
+    fn test_finalized_exactly_at_confirmation_depth() {
+        let depth = 4;
+        let block_height = 100;
+
+        assert!(!is_finalized(block_height, 101, depth));
+        assert!(!is_finalized(block_height, 102, depth));
+        assert_eq!(confirmations(block_height, 103), depth);
+        assert!(is_finalized(block_height, 103, depth));
+        assert!(is_finalized(block_height, 104, depth));
+        assert_eq!(confirmations(block_height, 99), 0);
+    }
+
+    #[test]
+    fn test_confirmation_depth_exceeds_reorg_depth() {
+        assert!(*CONFIRMATION_DEPTH > MAX_REORG_DEPTH);
+        // A block that could still be reorganized out is never finalized
+        assert!(!is_finalized(10, 10 + MAX_REORG_DEPTH - 1, *CONFIRMATION_DEPTH));
+    }
+
+    #[test]
+    fn test_balance_delta_normal_update() {
+        assert_eq!(RPCStorage::apply_balance_delta("fvcalice", 100, 50).unwrap(), 150);
+        assert_eq!(RPCStorage::apply_balance_delta("fvcalice", 100, -100).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_balance_delta_underflow_is_error() {
+        let err = RPCStorage::apply_balance_delta("fvcalice", 10, -11).unwrap_err();
+        assert!(matches!(err, StorageError::BalanceUnderflow { balance: 10, amount: 11, .. }));
+        assert!(RPCStorage::apply_balance_delta("fvcalice", u64::MAX, i64::MIN).is_ok());
+    }
+
+    #[test]
+    fn test_balance_delta_overflow_is_error() {
+        let err = RPCStorage::apply_balance_delta("fvcalice", u64::MAX, 1).unwrap_err();
+        assert!(matches!(err, StorageError::BalanceOverflow { amount: 1, .. }));
+    }
+
+    /// Point RPC storage at a throwaway directory before its first use
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_transfers_never_overdraw() {
+        let _db = use_test_db();
+        let sender = "fvcconcurrentsender";
+        let receiver = "fvcconcurrentreceiver";
+        // Each transfer costs 150; the sender can afford exactly 10
+        RPCStorage::set_balance(sender, 1_500).await.unwrap();
+        RPCStorage::set_balance(receiver, 0).await.unwrap();
+
+        let handles: Vec<_> = (0..50)
+            .map(|_| tokio::spawn(async move { RPCStorage::transfer(sender, receiver, 100, 50).await }))
+            .collect();
+        let mut succeeded = 0;
+        for handle in handles {
+            match handle.await.unwrap() {
+                Ok(_) => succeeded += 1,
+                Err(e) => assert!(matches!(e, StorageError::BalanceUnderflow { .. }), "unexpected error: {}", e),
+            }
         }
-        
-        // Processing efficiency
-        let throughput = transaction_count as f64 / block_height as f64;
-        
-        // Mathematical constants
-        let golden_ratio = 1.618033988749; // φ (phi)
-        let euler_number = std::f64::consts::E; // e
-        
-        // Computational complexity factor
-        let complexity_factor = (block_height as f64).log10() / 10.0; // Normalize log scale
-        
-        // Mathematical efficiency calculation
-        let mei = throughput * golden_ratio * (1.0 + complexity_factor) * euler_number * 10.0;
-        
-        // Ensure minimum value and cap at 1000
-        mei.max(1.0).min(1000.0)
+
+        assert_eq!(succeeded, 10);
+        assert_eq!(RPCStorage::get_balance(sender).await.unwrap(), 0);
+        assert_eq!(RPCStorage::get_balance(receiver).await.unwrap(), 1_000);
+    }
+
+    #[tokio::test]
+    async fn test_recorded_transfer_is_all_or_nothing() {
+        let _db = use_test_db();
+        let sender = "fvc00000000000000000000000000000000c228emyl";
+        let receiver = "fvc00000000000000000000000000000000c229emyl";
+        RPCStorage::set_balance(sender, 1_000).await.unwrap();
+
+        let mut overdraw = WalletTransaction::new_transfer(sender.to_string(), receiver.to_string(), 990, "0xc2280001".to_string(), 1);
+        overdraw.nonce = 1;
+        let result = RPCStorage::record_transfer(&overdraw, 50, None).await;
+        assert!(matches!(result, Err(StorageError::BalanceUnderflow { .. })));
+        assert!(RPCStorage::get_transaction(&overdraw.hash).await.unwrap().is_none());
+        assert_eq!(RPCStorage::get_account_nonce(sender).await.unwrap(), 0);
+        assert_eq!(RPCStorage::get_balance(sender).await.unwrap(), 1_000);
+
+        let mut tx = WalletTransaction::new_transfer(sender.to_string(), receiver.to_string(), 900, "0xc2280002".to_string(), 1);
+        tx.nonce = 1;
+        assert_eq!(RPCStorage::record_transfer(&tx, 50, None).await.unwrap(), (50, 900));
+        assert!(RPCStorage::get_transaction(&tx.hash).await.unwrap().is_some());
+        assert_eq!(RPCStorage::get_account_nonce(sender).await.unwrap(), 1);
+        assert_eq!(RPCStorage::get_address_transaction_count(receiver).await.unwrap(), 1);
+        // Only the applied transfer counts toward the sender's day
+        let today = spend_day(chrono::Utc::now().timestamp() as u64);
+        assert_eq!(RPCStorage::get_daily_spend(sender, today).await.unwrap(), 900);
+    }
+
+    #[tokio::test]
+    async fn test_each_test_db_starts_empty() {
+        let address = "fvc00000000000000000000000000000000d801emyl";
+        {
+            let _db = use_test_db();
+            RPCStorage::set_balance(address, 5).await.unwrap();
+            assert_eq!(RPCStorage::get_balance(address).await.unwrap(), 5);
+        }
+        let _db = use_test_db();
+        assert_eq!(RPCStorage::get_balance(address).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_address_label_set_get_clear() {
+        let _db = use_test_db();
+        let address = "fvclabeltest000000000000000000000000000emyl";
+
+        assert_eq!(RPCStorage::get_address_label(address).await.unwrap(), None);
+        RPCStorage::set_address_label(address, "Test Exchange").await.unwrap();
+        assert_eq!(RPCStorage::get_address_label(address).await.unwrap().as_deref(), Some("Test Exchange"));
+
+        RPCStorage::clear_address_label(address).await.unwrap();
+        assert_eq!(RPCStorage::get_address_label(address).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_account_info_includes_label() {
+        let _db = use_test_db();
+        RPCStorage::preload_ecosystem_labels().await.unwrap();
+        let (address, label) = ECOSYSTEM_ADDRESS_LABELS[0];
+
+        let account = RPCStorage::get_account_info(address).await.unwrap();
+        assert_eq!(account["address"], address);
+        assert_eq!(account["label"], label);
+
+        let unlabeled = RPCStorage::get_account_info("fvcnolabel").await.unwrap();
+        assert!(unlabeled["label"].is_null());
+    }
+
+    fn reward(address: &str, amount: u64, block_height: u64) -> WalletTransaction {
+        WalletTransaction::new_mining_reward(address.to_string(), amount, format!("r{}", block_height), block_height)
     }
 
-    /// Calculate Network Harmony Factor (NHF)
-    async fn calculate_network_harmony_factor(block_height: u64, transaction_count: u64) -> f64 {
-        if block_height == 0 {
-            return 0.0;
+    #[test]
+    fn test_supply_audit_flags_phantom_balance() {
+        let mut transfer = WalletTransaction::new_transfer(native('a'), native('b'), 400, "0xsupply".to_string(), 3);
+        transfer.fee = 25;
+        let txs = vec![reward(&native('a'), 1_000, 1), reward(&native('b'), 1_000, 2), transfer];
+        let genesis = 5_000;
+
+        // Balances: a = 5_000 + 1_000 - 425, b = 1_000 + 400
+        let consistent = SupplyAudit::compute(genesis, &txs, 5_575 + 1_400, 5_575 + 1_400);
+        assert_eq!(consistent.total_issued, 7_000);
+        assert_eq!(consistent.fees_burned, 25);
+        assert_eq!(consistent.discrepancy, 0);
+        assert!(consistent.is_consistent());
+
+        let phantom = SupplyAudit::compute(genesis, &txs, 5_575 + 1_400 + 300, 5_575 + 1_400 + 300);
+        assert_eq!(phantom.discrepancy, 300);
+        assert!(!phantom.is_consistent());
+
+        // A balance written behind the counter's back shows up even though the counter looks right
+        let drifted = SupplyAudit::compute(genesis, &txs, 5_575 + 1_400 + 300, 5_575 + 1_400);
+        assert_eq!(drifted.discrepancy, 300);
+        assert!(!drifted.is_consistent());
+    }
+
+    #[test]
+    fn test_reward_matures_at_boundary() {
+        let txs = vec![reward("fvcminer", 100, 10), reward("fvcother", 500, 10)];
+        let maturity = 5;
+
+        // Mined at 10: four confirmations at tip 13, five at tip 14
+        let before = BalanceBreakdown::compute("fvcminer", 250, &txs, 13, maturity);
+        assert_eq!(before, BalanceBreakdown { balance: 250, spendable_balance: 150, immature_balance: 100 });
+
+        let at = BalanceBreakdown::compute("fvcminer", 250, &txs, 14, maturity);
+        assert_eq!(at, BalanceBreakdown { balance: 250, spendable_balance: 250, immature_balance: 0 });
+    }
+
+    #[tokio::test]
+    async fn test_difficulty_history_follows_adjuster() {
+        use crate::consensus::DifficultyAdjuster;
+        let _db = use_test_db();
+
+        // 5s target, retarget every 4 blocks: fast, fast, slow, on-target
+        let adjuster = DifficultyAdjuster::new(5, 4);
+        let block_times = [2u64, 2, 2, 2, 1, 1, 1, 1, 20, 20, 20, 20, 5, 5, 5, 5];
+        let base_height = 90_000;
+
+        let mut difficulty = 1000;
+        let mut timestamp = 1_700_000_000;
+        let mut expected = Vec::new();
+        for (i, window) in block_times.chunks(4).enumerate() {
+            for (j, time) in window.iter().enumerate() {
+                timestamp += time;
+                let point = DifficultyPoint {
+                    height: base_height + (i * 4 + j) as u64,
+                    difficulty,
+                    timestamp,
+                };
+                RPCStorage::record_difficulty(&point).await.unwrap();
+                expected.push(point);
+            }
+            difficulty = adjuster.calculate_new_difficulty(difficulty, window);
         }
-        
-        // Block production consistency
-        let block_consistency = if block_height > 10 {
-            let recent_blocks = std::cmp::min(block_height, 100);
-            recent_blocks as f64 / 100.0
-        } else {
-            block_height as f64 / 10.0
+
+        let history = RPCStorage::get_difficulty_history(base_height, base_height + 15).await.unwrap();
+        assert_eq!(history, expected);
+
+        let per_window: Vec<u64> = history.iter().step_by(4).map(|p| p.difficulty).collect();
+        assert_eq!(per_window, vec![1000, 2500, 10000, 2500]);
+
+        let tail = RPCStorage::get_difficulty_history(base_height + 12, base_height + 100).await.unwrap();
+        assert_eq!(tail.len(), 4);
+        assert!(tail.iter().all(|p| p.difficulty == 2500));
+    }
+
+    fn native(c: char) -> String {
+        format!("fvc{}emyl", c.to_string().repeat(36))
+    }
+
+    #[test]
+    fn test_try_new_transfer_valid() {
+        let tx = WalletTransaction::try_new_transfer(native('a'), native('b'), 500, "0xabc".to_string(), 7).unwrap();
+        assert_eq!(tx.transaction_type, "transfer");
+        assert_eq!(tx.amount, 500);
+        assert_eq!(tx.block_height, 7);
+
+        let reward = WalletTransaction::try_new_mining_reward(native('c'), 100, "0xdef".to_string(), 8).unwrap();
+        assert_eq!(reward.transaction_type, "mining_reward");
+    }
+
+    #[test]
+    fn test_try_new_transfer_rejects_invalid_input() {
+        let bad_sender = WalletTransaction::try_new_transfer("fvcshort".to_string(), native('b'), 1, "0x1".to_string(), 1);
+        assert!(matches!(bad_sender, Err(TxError::InvalidAddress { field: "sender", .. })));
+
+        let bad_recipient = WalletTransaction::try_new_transfer(native('a'), format!("fvc{}emyl", "z".repeat(36)), 1, "0x1".to_string(), 1);
+        assert!(matches!(bad_recipient, Err(TxError::InvalidAddress { field: "recipient", .. })));
+
+        let zero = WalletTransaction::try_new_transfer(native('a'), native('b'), 0, "0x1".to_string(), 1);
+        assert_eq!(zero.unwrap_err(), TxError::ZeroAmount);
+
+        let to_self = WalletTransaction::try_new_transfer(native('a'), native('a'), 1, "0x1".to_string(), 1);
+        assert_eq!(to_self.unwrap_err(), TxError::SelfTransfer);
+
+        let long_hash = WalletTransaction::try_new_transfer(native('a'), native('b'), 1, "f".repeat(129), 1);
+        assert!(matches!(long_hash, Err(TxError::InvalidField { field: "hash", .. })));
+
+        let empty_hash = WalletTransaction::try_new_mining_reward(native('a'), 1, String::new(), 1);
+        assert!(matches!(empty_hash, Err(TxError::InvalidField { field: "hash", .. })));
+
+        let bad_miner = WalletTransaction::try_new_mining_reward("Mining-Reward".to_string(), 1, "0x1".to_string(), 1);
+        assert!(matches!(bad_miner, Err(TxError::InvalidAddress { .. })));
+    }
+
+    #[tokio::test]
+    async fn test_balance_at_height_matches_running_sum() {
+        let _db = use_test_db();
+        let address = "fvchistoricalbalance";
+        let txs = vec![
+            WalletTransaction::new_mining_reward(address.to_string(), 1_000, "0xhb1".to_string(), 1),
+            WalletTransaction::new_transfer("fvchistoricalpeer".to_string(), address.to_string(), 500, "0xhb2".to_string(), 2),
+            WalletTransaction::new_transfer("fvchistoricalpeer".to_string(), "fvchistoricalother".to_string(), 70, "0xhb3".to_string(), 3),
+            WalletTransaction::new_transfer(address.to_string(), "fvchistoricalpeer".to_string(), 200, "0xhb4".to_string(), 4),
+            WalletTransaction::new_mining_reward(address.to_string(), 1_000, "0xhb5".to_string(), 6),
+        ];
+        for tx in &txs {
+            RPCStorage::add_transaction(tx).await.unwrap();
+        }
+
+        assert_eq!(RPCStorage::get_address_transaction_count(address).await.unwrap(), 4);
+
+        let mut running = 0i128;
+        for height in 0..=7u64 {
+            running += txs.iter()
+                .filter(|tx| tx.block_height == height)
+                .map(|tx| balance_delta(tx, address))
+                .sum::<i128>();
+            let historical = RPCStorage::balance_at_height(address, height).await.unwrap();
+            assert_eq!(historical as i128, running, "height {}", height);
+        }
+
+        assert_eq!(RPCStorage::balance_at_height(address, 0).await.unwrap(), 0);
+        assert_eq!(RPCStorage::balance_at_height(address, 3).await.unwrap(), 1_500);
+        assert_eq!(RPCStorage::balance_at_height(address, 5).await.unwrap(), 1_500 - 200 - DEVICE_TRANSFER_FEE);
+    }
+
+    #[test]
+    fn test_transaction_round_trips_between_representations() {
+        let mut stored = WalletTransaction::new_transfer(native('a'), native('b'), 750, "0xround".to_string(), 42);
+        stored.nonce = 7;
+        stored.fee = 25;
+        stored.signature = Some(vec![1, 2, 3, 4]);
+
+        let shared: crate::shared::WalletTransaction = stored.clone().into();
+        assert_eq!(shared.signature.as_deref(), Some(&[1u8, 2, 3, 4][..]));
+        assert_eq!(shared.fee, 25);
+        assert_eq!(shared.block_height, 42);
+        assert_eq!(shared.hash.as_deref(), Some("0xround"));
+        assert_eq!(WalletTransaction::from(shared.clone()), stored);
+
+        let original = crate::shared::WalletTransaction::new_transfer(native('c'), native('d'), 10, 3, Some(vec![9; 64]), "0xshared".to_string());
+        let back: crate::shared::WalletTransaction = WalletTransaction::from(original.clone()).into();
+        assert_eq!(back, original);
+    }
+
+    #[test]
+    fn test_legacy_stored_transaction_deserializes() {
+        let legacy = r#"{"hash":"0xold","from":"a","to":"b","amount":5,"timestamp":1,"transaction_type":"transfer","block_height":3}"#;
+        let tx: WalletTransaction = serde_json::from_str(legacy).unwrap();
+        assert_eq!((tx.nonce, tx.fee, tx.signature), (0, 0, None));
+        assert_eq!(sender_fee(&tx), DEVICE_TRANSFER_FEE);
+    }
+
+    #[test]
+    fn test_new_block_carries_canonical_hash() {
+        let block = Block::new(5, "fvcminer".to_string(), "0".repeat(64));
+        assert!(block.is_valid_hash());
+        assert_ne!(block.hash, format!("0x{:064x}", 5 + 12345));
+
+        let mut forged = block.clone();
+        forged.hash = format!("0x{:064x}", 5 + 12345);
+        assert!(!forged.is_valid_hash());
+    }
+
+    #[test]
+    fn test_claimed_difficulty_is_committed_to_the_hash() {
+        let block = solve(Block::new_with_timestamp(7, "fvcminer".to_string(), "0".repeat(64), 1_700_000_007));
+        assert!(block.has_valid_pow());
+
+        // Raising the claimed work without re-mining breaks the hash
+        let mut inflated = block.clone();
+        inflated.difficulty = 50;
+        assert_ne!(inflated.canonical_hash(), block.hash);
+        assert!(!inflated.has_valid_pow());
+    }
+
+    #[test]
+    fn test_block_must_declare_the_expected_difficulty() {
+        // Any nonce meets a target of 0, so these carry no work at all
+        let unmined = |difficulty: u64| {
+            let mut block = Block::new_with_timestamp(8, "fvcminer".to_string(), "0".repeat(64), 1_700_000_008);
+            block.difficulty = difficulty;
+            block.hash = block.canonical_hash();
+            block
         };
-        
-        // Transaction flow smoothness
-        let tx_flow = (transaction_count as f64 / block_height as f64) / 10.0; // Normalize to expected 10 tx/block
-        let transaction_smoothness = tx_flow.min(1.0);
-        
-        // Network synchronization (simulated)
-        let sync_factor = 0.95; // 95% network sync
-        
-        // Harmony calculation with weighted average
-        let harmony = (block_consistency * 0.4 + transaction_smoothness * 0.4 + sync_factor * 0.2) * 1000.0;
-        
-        // Ensure minimum value and cap at 1000
-        harmony.max(1.0).min(1000.0)
+        assert!(!unmined(0).has_valid_pow());
+        // Truncates to 0 as a u32 while adding 2^32 to cumulative difficulty
+        let wide = unmined(1 << 32);
+        assert_eq!(wide.expected_cumulative_difficulty(None), 1 << 32);
+        assert!(!wide.has_valid_pow());
+        // Easier than the schedule, even with the work done for it
+        let mut easy = Block::new_with_timestamp(8, "fvcminer".to_string(), "0".repeat(64), 1_700_000_008);
+        easy.difficulty = 1;
+        for nonce in 0u64.. {
+            easy.nonce = nonce;
+            easy.hash = easy.canonical_hash();
+            if crate::crypto::fractal_hash::FractalPoW::new(1, BLOCK_FRACTAL_LEVELS).meets_difficulty(&easy.get_hash_bytes().unwrap()) {
+                break;
+            }
+        }
+        assert!(!easy.has_valid_pow());
     }
 
-    /// Calculate Smart Rate
-    async fn calculate_smart_rate(block_height: u64, transaction_count: u64) -> f64 {
-        // Calculate individual components
-        let vortex_energy = Self::calculate_vortex_energy_rate(block_height, transaction_count).await;
-        let fractal_score = Self::calculate_fractal_contribution_score(block_height, transaction_count).await;
-        let efficiency_index = Self::calculate_mathematical_efficiency_index(block_height).await;
-        let harmony_factor = Self::calculate_network_harmony_factor(block_height, transaction_count).await;
-        
-        // Normalize components to 0-1 range
-        let normalized_ver = (vortex_energy / 1000.0).min(1.0);
-        let normalized_fcs = (fractal_score / 1000.0).min(1.0);
-        let normalized_mei = (efficiency_index / 1000.0).min(1.0);
-        let normalized_nhf = (harmony_factor / 1000.0).min(1.0);
-        
-        // Weights for each component (sum = 1.0)
-        let w_ver = 0.30; // Vortex Energy Rate weight
-        let w_fcs = 0.25; // Fractal Contribution Score weight
-        let w_mei = 0.25; // Mathematical Efficiency Index weight
-        let w_nhf = 0.20; // Network Harmony Factor weight
-        
-        // Calculate weighted geometric mean
-        let weighted_geometric_mean = (normalized_ver.powf(w_ver) * 
-                                     normalized_fcs.powf(w_fcs) * 
-                                     normalized_mei.powf(w_mei) * 
-                                     normalized_nhf.powf(w_nhf)).max(0.001);
-        
-        // Base Smart Rate (in Smart Steps per Second)
-        let base_smart_rate = 1000.0;
-        
-        // Vortex Pattern based on block height
-        let vortex_pattern = 1.0 + (0.1 * ((block_height as f64 * 0.618).sin() + 1.0));
-        
-        // Final Smart Rate calculation
-        let smart_rate = base_smart_rate * weighted_geometric_mean * vortex_pattern;
-        
-        // Round to 2 decimal places
-        (smart_rate * 100.0).round() / 100.0
+    fn mined_block(height: u64, parent_hash: String) -> Block {
+        let mut block = Block::new_with_timestamp(height, "fvcminer".to_string(), parent_hash, 1_700_000_000 + height);
+        block.add_transaction(reward("fvcminer", 1, height));
+        solve(block)
+    }
+
+    fn solve(mut block: Block) -> Block {
+        for nonce in 0u64.. {
+            block.nonce = nonce;
+            block.hash = block.canonical_hash();
+            if block.has_valid_pow() {
+                break;
+            }
+        }
+        block
+    }
+
+    #[tokio::test]
+    async fn test_mined_block_pays_only_the_scheduled_coinbase() {
+        let _db = use_test_db();
+        let first = format!("fvc{:0>36}emyl", "ec0a1");
+        let second = format!("fvc{:0>36}emyl", "ec0a2");
+        let parent = mined_block(96_199, "0".repeat(64));
+        RPCStorage::store_block(&parent).await.unwrap();
+        let scheduled = crate::consensus::MiningRewardSystem::new().reward_at_height(96_200);
+        let block_paying = |parent: &Block, rewards: Vec<WalletTransaction>| {
+            let height = parent.height + 1;
+            let mut block = Block::new_with_timestamp(height, "fvcminer".to_string(), parent.hash.clone(), 1_700_000_000 + height);
+            for tx in rewards {
+                block.add_transaction(tx);
+            }
+            solve(block)
+        };
+
+        // Two outputs, even ones adding up to the schedule, or one inflated output mint nothing
+        let split = block_paying(&parent, vec![
+            WalletTransaction::coinbase(first.clone(), scheduled / 2, 96_200),
+            WalletTransaction::coinbase(second.clone(), scheduled / 2, 96_200),
+        ]);
+        let inflated = block_paying(&parent, vec![WalletTransaction::coinbase(first.clone(), scheduled * 10, 96_200)]);
+        for block in [split, inflated] {
+            assert!(matches!(RPCStorage::store_mined_block(&block).await, Err(StorageError::InvalidBlock(_))));
+        }
+        assert!(RPCStorage::get_block_by_height(96_200).await.unwrap().is_none());
+        assert_eq!(RPCStorage::get_balance(&first).await.unwrap(), 0);
+        assert_eq!(RPCStorage::get_balance(&second).await.unwrap(), 0);
+
+        let block = block_paying(&parent, vec![WalletTransaction::coinbase(first.clone(), scheduled, 96_200)]);
+        RPCStorage::store_mined_block(&block).await.unwrap();
+        assert_eq!(RPCStorage::get_balance(&first).await.unwrap(), scheduled);
+
+        // Resubmitting the same solution finds its height taken and credits nothing
+        let resubmitted = RPCStorage::store_mined_block(&block).await;
+        assert!(matches!(resubmitted, Err(StorageError::StaleBlock(_))));
+        assert_eq!(RPCStorage::get_balance(&first).await.unwrap(), scheduled);
+
+        // A chain update is held to the same schedule
+        let next = crate::consensus::MiningRewardSystem::new().reward_at_height(96_201);
+        let inflated = block_paying(&block, vec![WalletTransaction::coinbase(second.clone(), next + 1, 96_201)]);
+        let result = RPCStorage::apply_chain_update(&[], &[inflated]).await;
+        assert!(matches!(result, Err(StorageError::InvalidBlock(_))));
+        assert_eq!(RPCStorage::get_balance(&second).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_mined_block_with_overdraft_is_not_stored() {
+        let _db = use_test_db();
+        let miner = format!("fvc{:0>36}emyl", "ec0b1");
+        let broke = format!("fvc{:0>36}emyl", "ec0b2");
+        let parent = mined_block(96_210, "0".repeat(64));
+        RPCStorage::store_block(&parent).await.unwrap();
+        let mut block = Block::new_with_timestamp(96_211, "fvcminer".to_string(), parent.hash, 1_700_096_211);
+        let reward = crate::consensus::MiningRewardSystem::new().reward_at_height(96_211);
+        block.add_transaction(WalletTransaction::coinbase(miner.clone(), reward, 96_211));
+        block.add_transaction(WalletTransaction::new_transfer(broke, miner.clone(), 10, "0xec0b0002".to_string(), 96_211));
+        let result = RPCStorage::store_mined_block(&solve(block)).await;
+
+        assert!(matches!(result, Err(StorageError::BalanceUnderflow { .. })));
+        assert!(RPCStorage::get_block_by_height(96_211).await.unwrap().is_none());
+        assert_eq!(RPCStorage::get_balance(&miner).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_pages_follow_the_log_without_gaps() {
+        let _db = use_test_db();
+        let hash = |i: u64| format!("0x7a9e{:04}", i);
+        for i in 0..=25 {
+            let tx = WalletTransaction::new_transfer(native('a'), native('b'), 1, hash(i), 1);
+            RPCStorage::add_transaction(&tx).await.unwrap();
+        }
+
+        // Page back from the newest of ours; other tests may log transactions in between
+        let mut seen = Vec::new();
+        let mut cursor = Some(hash(25));
+        while let Some(before) = cursor {
+            let page = RPCStorage::get_transactions_page(Some(&before), 10).await.unwrap();
+            assert!(page.transactions.len() <= 10);
+            seen.extend(page.transactions.into_iter().map(|tx| tx.hash));
+            if seen.contains(&hash(0)) {
+                break;
+            }
+            cursor = page.next_cursor;
+        }
+        let ours: Vec<String> = seen.iter().filter(|h| h.starts_with("0x7a9e")).cloned().collect();
+        assert_eq!(ours, (0..25).rev().map(hash).collect::<Vec<_>>());
+        let unique: std::collections::HashSet<&String> = seen.iter().collect();
+        assert_eq!(unique.len(), seen.len());
+
+        assert!(matches!(
+            RPCStorage::get_transactions_page(Some("0x7a9e-unknown"), 10).await,
+            Err(StorageError::NotFound(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_expired_records_pruned_after_retention() {
+        let _db = use_test_db();
+        let now = chrono::Utc::now().timestamp() as u64;
+        let stale = [0xe1; 32];
+        let recent = [0xe2; 32];
+        RPCStorage::record_expired_transaction(&stale, now - *EXPIRED_TX_RETENTION - 1).await.unwrap();
+        RPCStorage::record_expired_transaction(&recent, now - 10).await.unwrap();
+
+        RPCStorage::prune_expired_transaction_records(now).await.unwrap();
+        let status = |hash: [u8; 32]| RPCStorage::pending_status(&hex::encode(hash), now);
+        assert_eq!(status(stale).await.unwrap(), None);
+        assert_eq!(status(recent).await.unwrap(), Some(PendingStatus::Expired { valid_until: now - 10 }));
+    }
+
+    #[tokio::test]
+    async fn test_hash_lookup_backfills_blocks_stored_before_the_index() {
+        let _db = use_test_db();
+        let block = mined_block(96_100, "0".repeat(64));
+        RPCStorage::store_block(&block).await.unwrap();
+        // As a database written before the hash index existed
+        rpc_db().delete(RPCStorage::block_hash_key(&block.hash).as_bytes()).await.unwrap();
+        rpc_db().delete(BLOCK_HASH_INDEX_MARKER).await.unwrap();
+
+        let found = RPCStorage::get_block_by_hash(&block.hash).await.unwrap().unwrap();
+        assert_eq!((found.height, found.hash), (96_100, block.hash.clone()));
+        assert_eq!(rpc_db().get_u64(&RPCStorage::block_hash_key(&block.hash)).await.unwrap(), Some(96_100));
+    }
+
+    #[tokio::test]
+    async fn test_height_endpoints_agree_after_storing_blocks() {
+        let _db = use_test_db();
+        let base = 700_000;
+        let mut parent_hash = "0".repeat(64);
+
+        for height in base..base + 4 {
+            let block = mined_block(height, parent_hash);
+            RPCStorage::store_block(&block).await.unwrap();
+            parent_hash = block.hash;
+        }
+        // Re-storing an older block must not move the tip backwards
+        let stale = RPCStorage::get_block_by_height(base + 1).await.unwrap().unwrap();
+        RPCStorage::store_block(&stale).await.unwrap();
+
+        let stored = RPCStorage::get_block_height().await.unwrap();
+        assert!(stored >= base + 3);
+        assert_eq!(RPCStorage::next_block_height().await.unwrap(), stored + 1);
+
+        let network_info = RPCStorage::get_network_info().await.unwrap();
+        assert_eq!(network_info["latest_block_height"], stored);
+        let stats = RPCStorage::get_stats().await.unwrap();
+        assert_eq!(stats["blocks_mined"], stored);
+        let latest = RPCStorage::get_latest_blocks(1).await.unwrap();
+        assert_eq!(latest[0].height, stored);
+    }
+
+    #[tokio::test]
+    async fn test_block_must_extend_stored_parent() {
+        let _db = use_test_db();
+        let base = 95_000;
+        let first = mined_block(base, "0".repeat(64));
+        RPCStorage::store_block(&first).await.unwrap();
+        let second = mined_block(base + 1, first.hash.clone());
+        RPCStorage::store_block(&second).await.unwrap();
+        assert_eq!(RPCStorage::get_block_by_height(base + 1).await.unwrap().unwrap().hash, second.hash);
+
+        // Parent hash that is not the stored block below
+        let broken = mined_block(base + 2, first.hash.clone());
+        let err = RPCStorage::store_block(&broken).await.unwrap_err();
+        assert!(matches!(err, StorageError::InvalidBlock(_)), "{}", err);
+        assert!(RPCStorage::get_block_by_height(base + 2).await.unwrap().is_none());
+
+        // Nor may a stored block be replaced by one that breaks the link
+        let replacement = mined_block(base + 1, "f".repeat(64));
+        assert!(RPCStorage::store_block(&replacement).await.is_err());
+        assert_eq!(RPCStorage::get_block_by_height(base + 1).await.unwrap().unwrap().hash, second.hash);
+    }
+
+    #[tokio::test]
+    async fn test_block_pages_cover_history_exactly_once() {
+        let _db = use_test_db();
+        let base = 600_000;
+        let mut parent_hash = "0".repeat(64);
+        for height in base..base + 250 {
+            let block = mined_block(height, parent_hash);
+            RPCStorage::store_block(&block).await.unwrap();
+            parent_hash = block.hash;
+        }
+
+        // Backward from the top of the range, with a block landing above it mid-way
+        let mut seen = Vec::new();
+        let mut cursor = Some(base + 250);
+        for page in 0..5 {
+            let result = RPCStorage::get_blocks_page(cursor, None, 50).await.unwrap();
+            assert_eq!(result.blocks.len(), 50);
+            seen.extend(result.blocks.iter().map(|b| b.height));
+            cursor = result.next_cursor;
+            if page == 1 {
+                RPCStorage::store_block(&mined_block(base + 250, parent_hash.clone())).await.unwrap();
+            }
+        }
+        let expected: Vec<u64> = (base..base + 250).rev().collect();
+        assert_eq!(seen, expected);
+
+        // Forward from just below the range
+        let mut seen = Vec::new();
+        let mut cursor = base - 1;
+        for _ in 0..5 {
+            let result = RPCStorage::get_blocks_page(None, Some(cursor), 50).await.unwrap();
+            seen.extend(result.blocks.iter().map(|b| b.height));
+            cursor = result.next_cursor.unwrap();
+        }
+        assert_eq!(seen, (base..base + 250).collect::<Vec<u64>>());
+
+        // Both bounds: only the window between them
+        let window = RPCStorage::get_blocks_page(Some(base + 10), Some(base + 4), 50).await.unwrap();
+        let heights: Vec<u64> = window.blocks.iter().map(|b| b.height).collect();
+        assert_eq!(heights, vec![base + 9, base + 8, base + 7, base + 6, base + 5]);
+        assert_eq!(window.next_cursor, None);
+    }
+
+    #[tokio::test]
+    async fn test_device_private_key_encrypted_at_rest() {
+        let _db = use_test_db();
+        let device_id = "device-key-roundtrip";
+        let private_key = "ab".repeat(32);
+        RPCStorage::set_device_private_key(device_id, &private_key, "pin-hash-1").await.unwrap();
+
+        let raw = rpc_db().get(format!("device_private_key:{}", device_id).as_bytes()).await.unwrap().unwrap();
+        let raw = String::from_utf8(raw).unwrap();
+        assert!(raw.starts_with(ENCRYPTED_KEY_PREFIX));
+        assert!(!raw.contains(&private_key));
+
+        let read = RPCStorage::get_device_private_key(device_id, "pin-hash-1").await.unwrap();
+        assert_eq!(read, Some(private_key));
+        assert!(RPCStorage::has_device_private_key(device_id).await.unwrap());
+        assert_eq!(RPCStorage::get_device_private_key("device-key-missing", "pin-hash-1").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_device_private_key_wrong_pin_fails() {
+        let _db = use_test_db();
+        let device_id = "device-key-wrong-pin";
+        RPCStorage::set_device_private_key(device_id, &"cd".repeat(32), "pin-hash-1").await.unwrap();
+
+        let err = RPCStorage::get_device_private_key(device_id, "pin-hash-2").await.unwrap_err();
+        assert!(matches!(err, StorageError::Decryption(_)));
+    }
+
+    #[tokio::test]
+    async fn test_device_pin_stored_as_argon2_hash() {
+        let _db = use_test_db();
+        let device_id = "device-pin-hashed";
+        RPCStorage::set_device_pin(device_id, "pin-hash-1").await.unwrap();
+
+        let stored = RPCStorage::get_device_pin(device_id).await.unwrap();
+        assert!(stored.starts_with("$argon2id$"));
+        assert!(!stored.contains("pin-hash-1"));
+        assert!(RPCStorage::verify_device_pin(device_id, "pin-hash-1").await.unwrap());
+        assert!(!RPCStorage::verify_device_pin(device_id, "pin-hash-2").await.unwrap());
+
+        // A PIN stored as-is before hashing still verifies, and is hashed from then on
+        let legacy_id = "device-pin-legacy";
+        rpc_db().put(format!("device_pin:{}", legacy_id).as_bytes(), b"pin-hash-1").await.unwrap();
+        assert!(!RPCStorage::verify_device_pin(legacy_id, "pin-hash-2").await.unwrap());
+        assert!(RPCStorage::verify_device_pin(legacy_id, "pin-hash-1").await.unwrap());
+        assert!(RPCStorage::get_device_pin(legacy_id).await.unwrap().starts_with("$argon2id$"));
+    }
+
+    #[tokio::test]
+    async fn test_legacy_plaintext_key_needs_verified_pin() {
+        let _db = use_test_db();
+        let device_id = "device-key-legacy-plaintext";
+        let private_key = "ef".repeat(32);
+        let key = format!("device_private_key:{}", device_id);
+        rpc_db().put(key.as_bytes(), private_key.as_bytes()).await.unwrap();
+
+        // No PIN set: nothing can verify
+        assert!(RPCStorage::get_device_private_key(device_id, "pin-hash-1").await.is_err());
+
+        RPCStorage::set_device_pin(device_id, "pin-hash-1").await.unwrap();
+        let err = RPCStorage::get_device_private_key(device_id, "pin-hash-2").await.unwrap_err();
+        assert!(matches!(err, StorageError::Decryption(_)));
+
+        let read = RPCStorage::get_device_private_key(device_id, "pin-hash-1").await.unwrap();
+        assert_eq!(read, Some(private_key.clone()));
+        let raw = String::from_utf8(rpc_db().get(key.as_bytes()).await.unwrap().unwrap()).unwrap();
+        assert!(raw.starts_with(ENCRYPTED_KEY_PREFIX));
+        assert!(!raw.contains(&private_key));
+    }
+
+    #[test]
+    fn test_plaintext_key_detection() {
+        assert!(looks_like_plaintext_key(&"0f".repeat(32)));
+        assert!(looks_like_plaintext_key(&format!("0x{}", "0F".repeat(32))));
+        assert!(!looks_like_plaintext_key(&encrypt_private_key(&"0f".repeat(32), "pin").unwrap()));
+        assert!(!looks_like_plaintext_key("c2FsdGVkX1+encrypted=="));
+    }
+
+    #[test]
+    fn test_vortex_pattern_wrong_length_rejected() {
+        assert!(VortexPatternConfig::new(vec![1.0; 5]).is_err());
+        assert!(VortexPatternConfig::parse("1.0,1.2,1.4,1.8,1.7,1.5,1.1").is_err());
+        assert!(VortexPatternConfig::parse("1.0,1.2,1.4,1.8,1.7,-1.5").is_err());
+        assert_eq!(
+            VortexPatternConfig::parse("1.0, 1.2, 1.4, 1.8, 1.7, 1.5").unwrap(),
+            VortexPatternConfig::default()
+        );
+    }
+
+    #[test]
+    fn test_maturity_window_is_configurable() {
+        let txs = vec![reward("fvcminer", 100, 10)];
+
+        let short = BalanceBreakdown::compute("fvcminer", 100, &txs, 14, 5);
+        assert_eq!(short.immature_balance, 0);
+
+        let long = BalanceBreakdown::compute("fvcminer", 100, &txs, 14, 20);
+        assert_eq!(long, BalanceBreakdown { balance: 100, spendable_balance: 0, immature_balance: 100 });
+    }
+
+    #[tokio::test]
+    async fn test_mempool_lists_pending_until_mined() {
+        use crate::consensus::vortex_consensus::Transaction;
+        use crate::wallet::key_manager::KeyManager;
+        let _db = use_test_db();
+
+        let sender = KeyManager::new();
+        let now = 1_700_000_000;
+        let pending: Vec<Transaction> = (0..3u8)
+            .map(|i| Transaction {
+                hash: [0x88, i, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0x5a],
+                from: sender.get_public_key(),
+                to: b"fvc00000000000000000000000000000000a0a0emyl".to_vec(),
+                amount: 1_000 + i as u64,
+                nonce: i as u64,
+                signature: Vec::new(),
+                vortex_fee: 0.25,
+            })
+            .collect();
+        for (i, tx) in pending.iter().enumerate() {
+            RPCStorage::add_pending_transaction(tx, now + i as u64).await.unwrap();
+        }
+
+        let hashes: Vec<String> = pending.iter().map(|tx| format!("0x{}", hex::encode(tx.hash))).collect();
+        let ours = |entries: Vec<MempoolEntry>| -> Vec<MempoolEntry> {
+            entries.into_iter().filter(|e| hashes.contains(&e.hash)).collect()
+        };
+
+        let listed = ours(RPCStorage::get_mempool(now + 10).await.unwrap());
+        assert_eq!(listed.len(), 3);
+        assert_eq!(listed[0].from, sender.get_address());
+        assert_eq!(listed[0].to, "fvc00000000000000000000000000000000a0a0emyl");
+        assert_eq!(listed[0].age_secs, 10);
+        assert_eq!(listed[2].age_secs, 8);
+        assert_eq!(listed.iter().map(|e| e.fee).sum::<f64>(), 0.75);
+
+        // Mining the first two takes them out; the third stays pending
+        RPCStorage::remove_pending_transactions(&[pending[0].hash, pending[1].hash]).await.unwrap();
+        let listed = ours(RPCStorage::get_mempool(now + 20).await.unwrap());
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].amount, 1_002);
+        // Each transaction has its own record, so removing some leaves the rest untouched
+        assert!(rpc_db().get(&RPCStorage::pending_key(&pending[0].hash)).await.unwrap().is_none());
+        assert!(rpc_db().get(&RPCStorage::pending_key(&pending[2].hash)).await.unwrap().is_some());
+
+        RPCStorage::remove_pending_transactions(&[pending[2].hash]).await.unwrap();
+        assert!(ours(RPCStorage::get_mempool(now + 30).await.unwrap()).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_batched_balances_report_errors_per_address() {
+        let _db = use_test_db();
+        let funded = "fvc00000000000000000000000000000000b0c1emyl";
+        let unknown = "fvc00000000000000000000000000000000b0c2emyl";
+        let malformed = "fvc-not-an-address";
+        RPCStorage::set_balance(funded, 2_500).await.unwrap();
+
+        let addresses: Vec<String> = [funded, unknown, malformed].iter().map(|a| a.to_string()).collect();
+        let balances = RPCStorage::get_balances(&addresses).await;
+
+        assert_eq!(balances.len(), 3);
+        assert_eq!(balances[funded], BalanceLookup::Balance { balance: 2_500 });
+        assert_eq!(balances[unknown], BalanceLookup::Balance { balance: 0 });
+        assert!(matches!(&balances[malformed], BalanceLookup::Error { error } if error.contains(malformed)));
+
+        let json = serde_json::to_value(&balances).unwrap();
+        assert_eq!(json[funded]["balance"], 2_500);
+        assert!(json[malformed]["error"].is_string());
+    }
+
+    #[tokio::test]
+    async fn test_address_history_read_from_index() {
+        let _db = use_test_db();
+        let address = "fvc00000000000000000000000000000000b0e1emyl";
+        let others = ["fvc00000000000000000000000000000000b0e2emyl", "fvc00000000000000000000000000000000b0e3emyl"];
+
+        // 200 of 500 transactions involve `address`, interleaved with the rest
+        let mut ours = Vec::new();
+        for i in 0..500u64 {
+            let (from, to) = if i % 5 < 2 {
+                if i % 2 == 0 { (address, others[0]) } else { (others[1], address) }
+            } else {
+                (others[0], others[1])
+            };
+            let tx = WalletTransaction::new_transfer(from.to_string(), to.to_string(), 1 + i, format!("0xaddrindex{:04}", i), 1);
+            RPCStorage::add_transaction(&tx).await.unwrap();
+            if from == address || to == address {
+                ours.push(tx.hash);
+            }
+        }
+        assert_eq!(ours.len(), 200);
+        assert_eq!(RPCStorage::get_address_transaction_count(address).await.unwrap(), 200);
+
+        let all = RPCStorage::get_transactions_for_address(address, 1_000, 0).await.unwrap();
+        assert_eq!(all.len(), 200);
+        let newest_first: Vec<String> = ours.iter().rev().cloned().collect();
+        assert_eq!(all.iter().map(|tx| tx.hash.clone()).collect::<Vec<_>>(), newest_first);
+
+        // Pages of 30 cover the history exactly once
+        let mut paged = Vec::new();
+        for offset in (0..200).step_by(30) {
+            paged.extend(RPCStorage::get_transactions_for_address(address, 30, offset).await.unwrap().into_iter().map(|tx| tx.hash));
+        }
+        assert_eq!(paged, newest_first);
+        assert!(RPCStorage::get_transactions_for_address(address, 10, 200).await.unwrap().is_empty());
     }
-}
\ No newline at end of file
+}
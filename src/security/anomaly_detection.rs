@@ -1,7 +1,13 @@
 use std::collections::{HashMap, VecDeque};
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
 use serde::{Serialize, Deserialize};
 use ndarray::{Array2, ArrayView1};
 use ndarray_rand::RandomExt;
+use crate::rpc_storage::WalletTransaction;
+
+/// Flagged transactions kept for the security-events endpoint
+const MAX_FLAGGED_TRANSACTIONS: usize = 1000;
 
 /// Advanced anomaly detection system for attack patterns
 pub struct AnomalyDetector {
@@ -9,6 +15,59 @@ pub struct AnomalyDetector {
     thresholds: AnomalyThresholds,
     history: VecDeque<SecurityEvent>,
     ml_engine: MlEngine,
+    tx_watch: TransactionWatchConfig,
+    tx_activity: Mutex<ActivityLog>,
+    flagged: Mutex<VecDeque<FlaggedTransaction>>,
+}
+
+/// Limits for live transaction monitoring
+#[derive(Debug, Clone)]
+pub struct TransactionWatchConfig {
+    /// More transfers than this from one address within a second is a RateSpike
+    pub max_transfers_per_second: usize,
+    /// An amount this many times the sender's running average is an AmountSpike
+    pub amount_spike_factor: f64,
+    /// Transfers seen from an address before its average is trusted
+    pub min_history: u64,
+    /// An address idle this long is forgotten, history and all
+    pub activity_ttl: Duration,
+}
+
+impl Default for TransactionWatchConfig {
+    fn default() -> Self {
+        Self {
+            max_transfers_per_second: 5,
+            amount_spike_factor: 10.0,
+            min_history: 3,
+            activity_ttl: Duration::from_secs(3600),
+        }
+    }
+}
+
+/// Sender history behind `observe_transaction`
+#[derive(Debug)]
+struct AddressActivity {
+    recent: VecDeque<Instant>,
+    transfers: u64,
+    average_amount: f64,
+    last_seen: Instant,
+}
+
+/// Every tracked sender, swept of idle ones at most once per `activity_ttl`
+#[derive(Debug)]
+struct ActivityLog {
+    senders: HashMap<String, AddressActivity>,
+    swept_at: Instant,
+}
+
+/// A transaction the detector flagged
+#[derive(Debug, Clone, Serialize)]
+pub struct FlaggedTransaction {
+    pub tx_hash: String,
+    pub address: String,
+    pub amount: u64,
+    pub observed_at: u64,
+    pub detection: DetectionResult,
 }
 
 /// Detection model structure
@@ -32,6 +91,12 @@ pub enum AttackPattern {
     TopologySybil,
     ConsensusSplit,
     FractalForgery,
+    /// One address sending transfers faster than the configured rate
+    RateSpike,
+    /// A transfer far above the sender's usual amount
+    AmountSpike,
+    /// One RPC client using up its request budget
+    RequestFlood,
 }
 
 /// Pattern detector
@@ -72,6 +137,8 @@ pub enum EventType {
     PeerConnection,
     EnergyUpdate,
     FractalUpdate,
+    /// A request to the RPC API; its first feature is the client's request budget usage
+    RpcRequest,
 }
 
 /// Feature vector for ML
@@ -109,6 +176,87 @@ pub enum Severity {
     Critical,
 }
 
+/// Response to a detected anomaly, ordered from least to most aggressive
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AnomalyAction {
+    /// Record the detection and let the request through
+    LogOnly,
+    /// Put the source under the stricter throttle rate limit
+    Throttle,
+    /// Block the request
+    Reject,
+}
+
+impl std::str::FromStr for AnomalyAction {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "log" | "log_only" | "logonly" => Ok(AnomalyAction::LogOnly),
+            "throttle" => Ok(AnomalyAction::Throttle),
+            "reject" => Ok(AnomalyAction::Reject),
+            other => Err(format!("unknown anomaly action '{}' (expected log_only, throttle or reject)", other)),
+        }
+    }
+}
+
+/// Operator-chosen action per anomaly severity
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AnomalyResponsePolicy {
+    pub low: AnomalyAction,
+    pub medium: AnomalyAction,
+    pub high: AnomalyAction,
+    pub critical: AnomalyAction,
+}
+
+impl Default for AnomalyResponsePolicy {
+    fn default() -> Self {
+        Self {
+            low: AnomalyAction::LogOnly,
+            medium: AnomalyAction::LogOnly,
+            high: AnomalyAction::Throttle,
+            critical: AnomalyAction::Reject,
+        }
+    }
+}
+
+impl AnomalyResponsePolicy {
+    /// Same action for every severity
+    pub fn uniform(action: AnomalyAction) -> Self {
+        Self { low: action, medium: action, high: action, critical: action }
+    }
+
+    pub fn action_for(&self, severity: &Severity) -> AnomalyAction {
+        match severity {
+            Severity::Low => self.low,
+            Severity::Medium => self.medium,
+            Severity::High => self.high,
+            Severity::Critical => self.critical,
+        }
+    }
+
+    /// Most aggressive action required by `detections`, or None when nothing was detected
+    pub fn decide(&self, detections: &[DetectionResult]) -> Option<AnomalyAction> {
+        detections.iter().map(|d| self.action_for(&d.severity)).max()
+    }
+
+    /// Load from ANOMALY_RESPONSE_{LOW,MEDIUM,HIGH,CRITICAL}, keeping defaults for unset severities
+    pub fn from_env() -> Result<Self, String> {
+        let mut policy = Self::default();
+        for (name, slot) in [
+            ("ANOMALY_RESPONSE_LOW", &mut policy.low),
+            ("ANOMALY_RESPONSE_MEDIUM", &mut policy.medium),
+            ("ANOMALY_RESPONSE_HIGH", &mut policy.high),
+            ("ANOMALY_RESPONSE_CRITICAL", &mut policy.critical),
+        ] {
+            if let Ok(value) = std::env::var(name) {
+                *slot = value.parse().map_err(|e| format!("{}: {}", name, e))?;
+            }
+        }
+        Ok(policy)
+    }
+}
+
 /// Model types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ModelType {
@@ -160,11 +308,18 @@ pub struct LSTMAnomalyModel {
 
 impl AnomalyDetector {
     pub fn new() -> Self {
+        Self::with_transaction_watch(TransactionWatchConfig::default())
+    }
+
+    pub fn with_transaction_watch(tx_watch: TransactionWatchConfig) -> Self {
         let mut detector = Self {
             patterns: HashMap::new(),
             thresholds: AnomalyThresholds::default(),
             history: VecDeque::with_capacity(10000),
             ml_engine: MlEngine::new(),
+            tx_watch,
+            tx_activity: Mutex::new(ActivityLog { senders: HashMap::new(), swept_at: Instant::now() }),
+            flagged: Mutex::new(VecDeque::new()),
         };
         
         detector.initialize_patterns();
@@ -191,6 +346,12 @@ impl AnomalyDetector {
             detector: detect_fractal_replay,
             threshold: 0.9,
         });
+
+        // RPC request flood detector
+        self.patterns.insert(AttackPattern::RequestFlood, PatternDetector {
+            detector: detect_request_flood,
+            threshold: 0.8,
+        });
     }
 
     /// Initialize ML models
@@ -245,6 +406,119 @@ impl AnomalyDetector {
         results
     }
 
+    /// Run only the rule-based pattern detectors, without recording the event or consulting ML models
+    pub fn match_patterns(&self, event: &SecurityEvent) -> Vec<DetectionResult> {
+        self.patterns
+            .values()
+            .map(|detector| (detector.detector, detector.threshold))
+            .filter_map(|(detect, threshold)| {
+                let result = detect(event);
+                (result.confidence > threshold).then_some(result)
+            })
+            .collect()
+    }
+
+    /// Track `tx.from`'s send rate and amounts; returns (and records) any RateSpike or AmountSpike
+    pub fn observe_transaction(&self, tx: &WalletTransaction) -> Vec<DetectionResult> {
+        let now = Instant::now();
+        let mut detections = Vec::new();
+        {
+            let mut activity = self.tx_activity.lock().unwrap_or_else(PoisonError::into_inner);
+            let ttl = self.tx_watch.activity_ttl;
+            if now.duration_since(activity.swept_at) >= ttl {
+                activity.senders.retain(|_, sender| now.duration_since(sender.last_seen) < ttl);
+                activity.swept_at = now;
+            }
+            let sender = activity.senders.entry(tx.from.clone()).or_insert_with(|| AddressActivity {
+                recent: VecDeque::new(),
+                transfers: 0,
+                average_amount: 0.0,
+                last_seen: now,
+            });
+            sender.last_seen = now;
+            while let Some(&seen) = sender.recent.front() {
+                if now.duration_since(seen) < Duration::from_secs(1) {
+                    break;
+                }
+                sender.recent.pop_front();
+            }
+            sender.recent.push_back(now);
+
+            let rate = sender.recent.len();
+            let max_rate = self.tx_watch.max_transfers_per_second;
+            if rate > max_rate {
+                let confidence = (rate as f64 / (2 * max_rate.max(1)) as f64).min(1.0);
+                detections.push(DetectionResult {
+                    pattern: AttackPattern::RateSpike,
+                    confidence,
+                    severity: self.score_to_severity(confidence),
+                    evidence: vec![Evidence {
+                        metric: "transfers_per_second".to_string(),
+                        value: rate as f64,
+                        expected_range: (0.0, max_rate as f64),
+                        deviation: (rate - max_rate) as f64,
+                    }],
+                    recommendations: vec![
+                        "Throttle transfers from this address".to_string(),
+                        "Check the sender for automated spam".to_string(),
+                    ],
+                });
+            }
+
+            if sender.transfers >= self.tx_watch.min_history && sender.average_amount > 0.0 {
+                let ratio = tx.amount as f64 / sender.average_amount;
+                if ratio > self.tx_watch.amount_spike_factor {
+                    let confidence = (1.0 - self.tx_watch.amount_spike_factor / ratio).clamp(0.0, 1.0);
+                    detections.push(DetectionResult {
+                        pattern: AttackPattern::AmountSpike,
+                        confidence,
+                        severity: self.score_to_severity(confidence),
+                        evidence: vec![Evidence {
+                            metric: "amount_to_average_ratio".to_string(),
+                            value: ratio,
+                            expected_range: (0.0, self.tx_watch.amount_spike_factor),
+                            deviation: ratio - self.tx_watch.amount_spike_factor,
+                        }],
+                        recommendations: vec![
+                            "Confirm the transfer with the account owner".to_string(),
+                            "Check the sender key for compromise".to_string(),
+                        ],
+                    });
+                }
+            }
+
+            sender.transfers += 1;
+            sender.average_amount += (tx.amount as f64 - sender.average_amount) / sender.transfers as f64;
+        }
+
+        if !detections.is_empty() {
+            let observed_at = std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_secs();
+            let mut flagged = self.flagged.lock().unwrap_or_else(PoisonError::into_inner);
+            for detection in &detections {
+                if flagged.len() == MAX_FLAGGED_TRANSACTIONS {
+                    flagged.pop_front();
+                }
+                flagged.push_back(FlaggedTransaction {
+                    tx_hash: tx.hash.clone(),
+                    address: tx.from.clone(),
+                    amount: tx.amount,
+                    observed_at,
+                    detection: detection.clone(),
+                });
+            }
+        }
+        detections
+    }
+
+    /// Most recent flagged transactions, newest first
+    pub fn flagged_transactions(&self, limit: usize) -> Vec<FlaggedTransaction> {
+        let flagged = self.flagged.lock().unwrap_or_else(PoisonError::into_inner);
+        flagged.iter().rev().take(limit).cloned().collect()
+    }
+
     /// Score to severity mapping
     fn score_to_severity(&self, score: f64) -> Severity {
         match score {
@@ -406,6 +680,38 @@ fn detect_sybil_attack(event: &SecurityEvent) -> DetectionResult {
     }
 }
 
+/// RPC request flood detector function
+fn detect_request_flood(event: &SecurityEvent) -> DetectionResult {
+    let usage = match event.event_type {
+        EventType::RpcRequest => event.features.values.first().copied().unwrap_or(0.0),
+        _ => 0.0,
+    };
+    if usage > 0.8 {
+        DetectionResult {
+            pattern: AttackPattern::RequestFlood,
+            confidence: usage.min(1.0),
+            severity: Severity::High,
+            evidence: vec![Evidence {
+                metric: "request_rate_usage".to_string(),
+                value: usage,
+                expected_range: (0.0, 0.8),
+                deviation: usage - 0.8,
+            }],
+            recommendations: vec![
+                "Throttle the client".to_string(),
+            ],
+        }
+    } else {
+        DetectionResult {
+            pattern: AttackPattern::RequestFlood,
+            confidence: 0.0,
+            severity: Severity::Low,
+            evidence: vec![],
+            recommendations: vec![],
+        }
+    }
+}
+
 /// Eclipse attack detector function
 fn detect_eclipse_attack(event: &SecurityEvent) -> DetectionResult {
     if let EventType::TopologyChange = event.event_type {
@@ -724,4 +1030,97 @@ mod tests {
         assert_eq!(result.pattern, AttackPattern::SybilCluster);
         assert_eq!(result.confidence, 0.85);
     }
+
+    fn transfer(from: &str, amount: u64, n: u64) -> WalletTransaction {
+        WalletTransaction::new_transfer(
+            from.to_string(),
+            "fvc00000000000000000000000000000000c002emyl".to_string(),
+            amount,
+            format!("0xwatch{}{}", from, n),
+            1,
+        )
+    }
+
+    #[test]
+    fn test_transfer_burst_flags_rate_spike() {
+        let detector = AnomalyDetector::new();
+        let sender = "fvc00000000000000000000000000000000d001emyl";
+        let max = TransactionWatchConfig::default().max_transfers_per_second;
+
+        for n in 0..max as u64 {
+            assert!(detector.observe_transaction(&transfer(sender, 100, n)).is_empty());
+        }
+        let detections = detector.observe_transaction(&transfer(sender, 100, max as u64));
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].pattern, AttackPattern::RateSpike);
+
+        // Other senders are tracked separately
+        assert!(detector.observe_transaction(&transfer("fvc00000000000000000000000000000000d002emyl", 100, 0)).is_empty());
+
+        let flagged = detector.flagged_transactions(10);
+        assert_eq!(flagged.len(), 1);
+        assert_eq!(flagged[0].address, sender);
+    }
+
+    #[test]
+    fn test_large_amount_flags_amount_spike() {
+        let detector = AnomalyDetector::with_transaction_watch(TransactionWatchConfig {
+            max_transfers_per_second: 100,
+            ..TransactionWatchConfig::default()
+        });
+        let sender = "fvc00000000000000000000000000000000d003emyl";
+        for n in 0..3 {
+            assert!(detector.observe_transaction(&transfer(sender, 1_000, n)).is_empty());
+        }
+        assert!(detector.observe_transaction(&transfer(sender, 5_000, 3)).is_empty());
+
+        let detections = detector.observe_transaction(&transfer(sender, 1_000_000, 4));
+        assert_eq!(detections.len(), 1);
+        assert_eq!(detections[0].pattern, AttackPattern::AmountSpike);
+    }
+
+    #[test]
+    fn test_idle_senders_are_forgotten() {
+        let detector = AnomalyDetector::with_transaction_watch(TransactionWatchConfig {
+            activity_ttl: Duration::from_millis(50),
+            ..TransactionWatchConfig::default()
+        });
+        let idle = "fvc00000000000000000000000000000000d004emyl";
+        let active = "fvc00000000000000000000000000000000d005emyl";
+        for n in 0..3 {
+            detector.observe_transaction(&transfer(idle, 1_000, n));
+        }
+        assert_eq!(detector.tx_activity.lock().unwrap().senders.len(), 1);
+
+        std::thread::sleep(Duration::from_millis(60));
+        detector.observe_transaction(&transfer(active, 1_000, 0));
+        let activity = detector.tx_activity.lock().unwrap();
+        assert!(!activity.senders.contains_key(idle));
+        assert!(activity.senders.contains_key(active));
+        drop(activity);
+
+        // The forgotten sender starts over, so its old average no longer applies
+        assert!(detector.observe_transaction(&transfer(idle, 1_000_000, 3)).is_empty());
+    }
+
+    #[test]
+    fn test_policy_takes_most_aggressive_action() {
+        let detection = |severity| DetectionResult {
+            pattern: AttackPattern::SybilCluster,
+            confidence: 0.9,
+            severity,
+            evidence: vec![],
+            recommendations: vec![],
+        };
+        let policy = AnomalyResponsePolicy::default();
+
+        assert_eq!(policy.decide(&[]), None);
+        assert_eq!(policy.decide(&[detection(Severity::Low)]), Some(AnomalyAction::LogOnly));
+        assert_eq!(
+            policy.decide(&[detection(Severity::Low), detection(Severity::Critical), detection(Severity::High)]),
+            Some(AnomalyAction::Reject)
+        );
+        assert_eq!("throttle".parse::<AnomalyAction>(), Ok(AnomalyAction::Throttle));
+        assert!("ban".parse::<AnomalyAction>().is_err());
+    }
 }
\ No newline at end of file
@@ -1,6 +1,9 @@
 use std::collections::HashMap;
+use rand::Rng;
 use serde::{Serialize, Deserialize};
 use serde_json::json;
+use crate::rpc_storage::{merkle_root, Block, WalletTransaction};
+use crate::utils::digital_root;
 
 /// Chaos testing framework for fractal properties
 pub struct ChaosTester {
@@ -203,6 +206,32 @@ impl ChaosTester {
         report
     }
 
+    /// Build `iterations` random blocks, perturb them, and check that hashing is deterministic,
+    /// merkle roots depend only on the transactions, and digital roots are self-similar
+    pub fn run_fractal_invariants(&self, iterations: usize) -> ChaosReport {
+        let mut rng = rand::thread_rng();
+        let mut report = ChaosReport::new();
+
+        for iteration in 0..iterations {
+            let block = random_block(&mut rng);
+            for (invariant, details) in check_block_invariants(&block, &mut rng) {
+                report.violations.push(InvariantViolation {
+                    iteration,
+                    invariant: invariant.to_string(),
+                    details,
+                });
+            }
+        }
+
+        report.iterations = iterations;
+        report.summary = format!(
+            "{} iterations, {} invariant violations",
+            iterations,
+            report.violations.len()
+        );
+        report
+    }
+
     /// Get appropriate generator for test
     fn get_generator_for_test(&self, property: &FractalProperty) -> String {
         match property {
@@ -366,6 +395,121 @@ impl ChaosTester {
     }
 }
 
+fn random_hash(rng: &mut impl Rng) -> String {
+    format!("0x{}", hex::encode(rng.gen::<[u8; 32]>()))
+}
+
+/// A block with random header fields and up to 7 transfers
+fn random_block(rng: &mut impl Rng) -> Block {
+    let mut block = Block::new_with_timestamp(
+        rng.gen_range(1..10_000_000),
+        format!("miner-{:016x}", rng.gen::<u64>()),
+        random_hash(rng),
+        rng.gen_range(1_600_000_000..2_000_000_000),
+    );
+    block.nonce = rng.gen();
+    for _ in 0..rng.gen_range(0..8) {
+        let tx = WalletTransaction::new_transfer(
+            format!("sender-{:08x}", rng.gen::<u32>()),
+            format!("recipient-{:08x}", rng.gen::<u32>()),
+            rng.gen_range(1..1_000_000_000),
+            random_hash(rng),
+            block.height,
+        );
+        block.add_transaction(tx);
+    }
+    block
+}
+
+/// Copy of `block` with one header field changed; transactions are untouched
+fn perturb_header(block: &Block, rng: &mut impl Rng) -> (Block, &'static str) {
+    let mut perturbed = block.clone();
+    let field = match rng.gen_range(0..5) {
+        0 => {
+            perturbed.nonce = perturbed.nonce.wrapping_add(rng.gen_range(1..u64::MAX));
+            "nonce"
+        }
+        1 => {
+            perturbed.timestamp ^= 1u64 << rng.gen_range(0..32u32);
+            "timestamp"
+        }
+        2 => {
+            perturbed.height += rng.gen_range(1..1000);
+            "height"
+        }
+        3 => {
+            perturbed.miner.push('x');
+            "miner"
+        }
+        _ => {
+            perturbed.parent_hash = random_hash(rng);
+            "parent_hash"
+        }
+    };
+    (perturbed, field)
+}
+
+fn digit_sum(mut n: u64) -> u64 {
+    let mut sum = 0;
+    while n > 0 {
+        sum += n % 10;
+        n /= 10;
+    }
+    sum
+}
+
+/// Every invariant `block` breaks, as (invariant, details)
+fn check_block_invariants(block: &Block, rng: &mut impl Rng) -> Vec<(&'static str, String)> {
+    let mut violations = Vec::new();
+
+    let hash = block.canonical_hash();
+    if block.clone().canonical_hash() != hash {
+        violations.push(("hash_determinism", format!("block {} hashed differently twice", block.height)));
+    }
+    let (perturbed, field) = perturb_header(block, rng);
+    if perturbed.canonical_hash() == hash {
+        violations.push(("hash_determinism", format!("changing {} left hash {} unchanged", field, hash)));
+    }
+
+    let root = block.merkle_root();
+    let tx_hashes: Vec<String> = block.transactions.iter().map(|tx| tx.hash.clone()).collect();
+    if merkle_root(&tx_hashes) != root || perturbed.merkle_root() != root {
+        violations.push(("merkle_root_stability", format!("merkle root moved after changing {}", field)));
+    }
+    if !block.transactions.is_empty() {
+        let mut tampered = block.clone();
+        let index = rng.gen_range(0..tampered.transactions.len());
+        tampered.transactions[index].hash = random_hash(rng);
+        if tampered.merkle_root() == root {
+            violations.push(("merkle_root_stability", format!("replacing transaction {} kept the merkle root", index)));
+        }
+    }
+
+    for n in [block.height, block.nonce, block.timestamp] {
+        let root = digital_root(n);
+        if (n > 0 && !(1..=9).contains(&root)) || root != digital_root(digit_sum(n)) {
+            violations.push(("digital_root_self_similarity", format!("digital root of {} is {}", n, root)));
+        }
+    }
+    let sum_root = digital_root(block.height + block.timestamp);
+    if sum_root != digital_root(digital_root(block.height) + digital_root(block.timestamp)) {
+        violations.push((
+            "digital_root_self_similarity",
+            format!("digital root of {} + {} does not follow from its parts", block.height, block.timestamp),
+        ));
+    }
+
+    violations
+}
+
+/// An invariant broken during `run_fractal_invariants`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvariantViolation {
+    pub iteration: usize,
+    pub invariant: String,
+    pub details: String,
+}
+
 /// Statistics structure
 #[derive(Debug, Clone)]
 struct Statistics {
@@ -377,6 +521,10 @@ struct Statistics {
 pub struct ChaosReport {
     pub results: Vec<FractalPropertyTest>,
     pub summary: String,
+    #[serde(default)]
+    pub iterations: usize,
+    #[serde(default)]
+    pub violations: Vec<InvariantViolation>,
 }
 
 impl ChaosReport {
@@ -384,9 +532,16 @@ impl ChaosReport {
         Self {
             results: Vec::new(),
             summary: String::new(),
+            iterations: 0,
+            violations: Vec::new(),
         }
     }
 
+    /// Whether no invariant was violated
+    pub fn is_clean(&self) -> bool {
+        self.violations.is_empty()
+    }
+
     pub fn add_result(&mut self, result: FractalPropertyTest) {
         self.results.push(result);
     }
@@ -402,6 +557,13 @@ mod tests {
         assert!(!tester.test_cases.is_empty());
     }
 
+    #[test]
+    fn test_fractal_invariants_hold_on_valid_blocks() {
+        let report = ChaosTester::new().run_fractal_invariants(1000);
+        assert_eq!(report.iterations, 1000);
+        assert!(report.is_clean(), "{:?}", report.violations);
+    }
+
     #[test]
     fn test_fractal_properties() {
         let config = ChaosConfig {
@@ -3,7 +3,7 @@ use crate::security::{
     verification::FormalVerifier,
     chaos_testing::{ChaosTester, ChaosReport},
     monitoring::SecurityMonitor,
-    anomaly_detection::{AnomalyDetector, AnomalyReport},
+    anomaly_detection::{AnomalyDetector, AnomalyReport, AnomalyResponsePolicy},
 };
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -30,6 +30,8 @@ pub struct SecurityConfig {
     pub audit_sample_size: usize,
     pub chaos_failure_threshold: f64,
     pub monitoring_interval_secs: u64,
+    #[serde(default)]
+    pub anomaly_response: AnomalyResponsePolicy,
 }
 
 impl Default for SecurityConfig {
@@ -43,6 +45,7 @@ impl Default for SecurityConfig {
             audit_sample_size: 1000,
             chaos_failure_threshold: 0.7,
             monitoring_interval_secs: 30,
+            anomaly_response: AnomalyResponsePolicy::default(),
         }
     }
 }
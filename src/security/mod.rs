@@ -10,7 +10,7 @@ pub mod integration;
 
 pub use audit::{VortexAuditor, MathematicalProof};
 pub use verification::{FormalVerifier, TLAProof};
-pub use chaos_testing::{ChaosTester, FractalPropertyTest};
+pub use chaos_testing::{ChaosTester, ChaosReport, FractalPropertyTest, InvariantViolation};
 pub use monitoring::SecurityMonitor;
-pub use anomaly_detection::{AnomalyDetector, AttackPattern};
+pub use anomaly_detection::{AnomalyDetector, AttackPattern, AnomalyAction, AnomalyResponsePolicy, FlaggedTransaction, TransactionWatchConfig};
 pub use integration::{SecurityFramework, SecurityConfig, ValidationResult};
\ No newline at end of file
@@ -1,9 +1,19 @@
-use std::collections::HashMap;
-use std::sync::{Arc, Mutex};
-use std::time::Duration;
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{Duration, Instant};
+use axum::{
+    extract::{MatchedPath, Request, State},
+    http::StatusCode,
+    middleware::Next,
+    response::Response,
+};
 use serde::{Serialize, Deserialize};
 use serde_json::json;
 
+/// Latency samples kept per endpoint for the percentile estimates
+const LATENCY_WINDOW: usize = 1024;
+
 /// Real-time security monitoring system
 pub struct SecurityMonitor {
     metrics: Arc<Mutex<SecurityMetrics>>,
@@ -11,6 +21,27 @@ pub struct SecurityMonitor {
     vortex_monitor: VortexEnergyMonitor,
     anomaly_detector: AnomalyDetector,
     alert_system: AlertSystem,
+    endpoint_metrics: Mutex<HashMap<String, EndpointMetrics>>,
+    transfer_failures: AtomicU64,
+    started_at: Instant,
+}
+
+/// Raw request counters for one endpoint
+#[derive(Debug, Default)]
+struct EndpointMetrics {
+    requests: u64,
+    errors: u64,
+    latencies_ms: VecDeque<f64>,
+}
+
+/// Request summary for one endpoint; errors are responses with status >= 400
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EndpointStats {
+    pub requests: u64,
+    pub errors: u64,
+    pub error_rate_percent: f64,
+    pub p50_latency_ms: f64,
+    pub p99_latency_ms: f64,
 }
 
 /// Security metrics structure
@@ -187,7 +218,73 @@ impl SecurityMonitor {
             vortex_monitor: VortexEnergyMonitor::new(),
             anomaly_detector: AnomalyDetector::new(),
             alert_system: AlertSystem::new(),
+            endpoint_metrics: Mutex::new(HashMap::new()),
+            transfer_failures: AtomicU64::new(0),
+            started_at: Instant::now(),
+        }
+    }
+
+    /// Count one request to `endpoint` and keep its latency for the percentiles
+    pub fn record_request(&self, endpoint: &str, status: StatusCode, latency: Duration) {
+        let mut endpoints = self.endpoint_metrics.lock().unwrap_or_else(PoisonError::into_inner);
+        let metrics = endpoints.entry(endpoint.to_string()).or_default();
+        metrics.requests += 1;
+        if status.is_client_error() || status.is_server_error() {
+            metrics.errors += 1;
+        }
+        if metrics.latencies_ms.len() == LATENCY_WINDOW {
+            metrics.latencies_ms.pop_front();
         }
+        metrics.latencies_ms.push_back(latency.as_secs_f64() * 1000.0);
+    }
+
+    /// Count a transfer the node refused or failed to apply
+    pub fn record_transfer_failure(&self) {
+        self.transfer_failures.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn transfer_failures(&self) -> u64 {
+        self.transfer_failures.load(Ordering::Relaxed)
+    }
+
+    pub fn uptime(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Per-endpoint counters, error rates and p50/p99 latencies over the recent window
+    pub fn endpoint_stats(&self) -> HashMap<String, EndpointStats> {
+        let endpoints = self.endpoint_metrics.lock().unwrap_or_else(PoisonError::into_inner);
+        endpoints.iter().map(|(endpoint, metrics)| {
+            let mut latencies: Vec<f64> = metrics.latencies_ms.iter().copied().collect();
+            latencies.sort_by(f64::total_cmp);
+            let stats = EndpointStats {
+                requests: metrics.requests,
+                errors: metrics.errors,
+                error_rate_percent: metrics.errors as f64 / metrics.requests.max(1) as f64 * 100.0,
+                p50_latency_ms: percentile(&latencies, 50.0),
+                p99_latency_ms: percentile(&latencies, 99.0),
+            };
+            (endpoint.clone(), stats)
+        }).collect()
+    }
+
+    /// Overall request health: degraded above a 5% error rate or a 1s p99 on any endpoint
+    pub fn request_health(&self) -> serde_json::Value {
+        let stats = self.endpoint_stats();
+        let total_requests: u64 = stats.values().map(|s| s.requests).sum();
+        let total_errors: u64 = stats.values().map(|s| s.errors).sum();
+        let error_rate = total_errors as f64 / total_requests.max(1) as f64 * 100.0;
+        let worst_p99 = stats.values().map(|s| s.p99_latency_ms).fold(0.0, f64::max);
+
+        json!({
+            "status": if error_rate < 5.0 && worst_p99 < 1000.0 { "healthy" } else { "degraded" },
+            "uptime_seconds": self.started_at.elapsed().as_secs(),
+            "total_requests": total_requests,
+            "total_errors": total_errors,
+            "error_rate_percent": error_rate,
+            "worst_p99_latency_ms": worst_p99,
+            "active_endpoints": stats.len(),
+        })
     }
 
     /// Start continuous monitoring
@@ -452,6 +549,38 @@ impl SecurityMonitor {
     }
 }
 
+/// Nearest-rank percentile of already sorted samples
+fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+lazy_static::lazy_static! {
+    pub static ref SECURITY_MONITOR: Arc<SecurityMonitor> = Arc::new(SecurityMonitor::new());
+}
+
+/// Record each request's route, status and latency on the monitor. Apply with `route_layer`
+/// so the matched route template (e.g. `/api/blocks/:height`) is the endpoint key.
+pub async fn request_metrics_middleware(
+    State(monitor): State<Arc<SecurityMonitor>>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let path = request.extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let endpoint = format!("{} {}", request.method(), path);
+
+    let started = Instant::now();
+    let response = next.run(request).await;
+    monitor.record_request(&endpoint, response.status(), started.elapsed());
+    response
+}
+
 impl SecurityMetrics {
     pub fn new() -> Self {
         Self {
@@ -705,6 +834,42 @@ mod tests {
         assert!(monitor.metrics.lock().unwrap().fractal_dimension > 0.0);
     }
 
+    #[tokio::test]
+    async fn test_middleware_counts_requests_per_endpoint() {
+        use axum::{body::Body, routing::get, Router};
+        use tower::ServiceExt;
+
+        let monitor = Arc::new(SecurityMonitor::new());
+        let app = Router::new()
+            .route("/ok", get(|| async { "ok" }))
+            .route("/fail", get(|| async { StatusCode::INTERNAL_SERVER_ERROR }))
+            .route("/item/:id", get(|| async { "item" }))
+            .route_layer(axum::middleware::from_fn_with_state(monitor.clone(), request_metrics_middleware));
+
+        for uri in ["/ok", "/ok", "/ok", "/fail", "/item/1", "/item/2"] {
+            let request = axum::http::Request::get(uri).body(Body::empty()).unwrap();
+            app.clone().oneshot(request).await.unwrap();
+        }
+
+        let stats = monitor.endpoint_stats();
+        assert_eq!(stats.len(), 3);
+        assert_eq!(stats["GET /ok"].requests, 3);
+        assert_eq!(stats["GET /ok"].errors, 0);
+        assert_eq!(stats["GET /fail"].errors, 1);
+        assert_eq!(stats["GET /fail"].error_rate_percent, 100.0);
+        assert_eq!(stats["GET /item/:id"].requests, 2);
+        assert!(stats["GET /ok"].p99_latency_ms >= stats["GET /ok"].p50_latency_ms);
+        assert_eq!(monitor.request_health()["total_requests"], 6);
+    }
+
+    #[test]
+    fn test_percentile_nearest_rank() {
+        let samples: Vec<f64> = (1..=100).map(f64::from).collect();
+        assert_eq!(percentile(&samples, 50.0), 50.0);
+        assert_eq!(percentile(&samples, 99.0), 99.0);
+        assert_eq!(percentile(&[], 99.0), 0.0);
+    }
+
     #[test]
     fn test_anomaly_detection() {
         let _detector = AnomalyDetector::new();
@@ -1,6 +1,10 @@
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
 use serde_json::json;
+use crate::consensus::VortexMath;
+
+/// Powers of two checked against the vortex pattern; 2^63 is the largest that fits a u64
+const VORTEX_CYCLE_POWERS: u32 = 64;
 
 /// Formal verification system using TLA+ and Coq
 pub struct FormalVerifier {
@@ -95,8 +99,29 @@ impl FormalVerifier {
             ],
         };
 
+        // Vortex cycle: doubling mod 9 walks 1-2-4-8-7-5 and returns to 1
+        let vortex_cycle_spec = TLASpecification {
+            name: "VortexCycle".to_string(),
+            variables: vec![
+                "position".to_string(),
+                "value".to_string(),
+            ],
+            initial_state: r"Init == /\ position = 0
+                          /\ value = 1".to_string(),
+            next_state: r"Next == /\ value' = (2 * value) % 9
+                        /\ position' = (position + 1) % 6".to_string(),
+            temporal_properties: vec![
+                r"Cycle == [](position = 0 => value = 1)".to_string(),
+            ],
+            invariants: vec![
+                r"PatternInvariant == value = Pattern[position]".to_string(),
+                r"DigitalRootInvariant == \A k \in Nat: DigitalRoot(2^k) = Pattern[k % 6]".to_string(),
+            ],
+        };
+
         self.tla_specifications.insert("safety".to_string(), safety_spec);
         self.tla_specifications.insert("liveness".to_string(), liveness_spec);
+        self.tla_specifications.insert("vortex_cycle".to_string(), vortex_cycle_spec);
     }
 
     /// Generate TLA+ proof for vortex consensus properties
@@ -111,9 +136,61 @@ impl FormalVerifier {
         }
     }
 
+    /// Check `math`'s base pattern against the vortex cycle: each step doubles the last mod 9,
+    /// the sixth step returns to the start, and the digital root of 2^k is pattern[k % 6].
+    /// Every failed check is a counterexample in the returned proof.
+    pub fn verify_vortex_cycle(&self, math: &VortexMath) -> TLAProof {
+        let started = std::time::Instant::now();
+        let pattern = math.base_pattern();
+        let mut counterexamples = Vec::new();
+        let mut states_explored = 0u64;
+
+        if pattern[0] != 1 {
+            counterexamples.push(format!("cycle starts at {} instead of 1", pattern[0]));
+        }
+        for (position, &value) in pattern.iter().enumerate() {
+            states_explored += 1;
+            let next = pattern[(position + 1) % pattern.len()];
+            if u16::from(value) * 2 % 9 != u16::from(next) {
+                counterexamples.push(format!(
+                    "step {}: 2 * {} mod 9 is {}, pattern has {}",
+                    position, value, u16::from(value) * 2 % 9, next
+                ));
+            }
+        }
+
+        for k in 0..VORTEX_CYCLE_POWERS {
+            states_explored += 1;
+            let root = math.reduce_to_digital_root(1u64 << k);
+            let expected = pattern[k as usize % pattern.len()];
+            if root != expected {
+                counterexamples.push(format!("digital root of 2^{} is {}, pattern has {}", k, root, expected));
+            }
+        }
+
+        let result = if counterexamples.is_empty() { "VERIFIED" } else { "VIOLATED" };
+        TLAProof {
+            property_name: "vortex_cycle".to_string(),
+            tla_spec: self.tla_specifications.get("vortex_cycle").cloned(),
+            proof_obligations: self.generate_proof_obligations("vortex_cycle"),
+            model_checking: ModelCheckingResult {
+                property_name: "vortex_cycle".to_string(),
+                states_explored,
+                counterexamples,
+                verification_time_ms: started.elapsed().as_millis() as u64,
+                result: result.to_string(),
+            },
+        }
+    }
+
     /// Generate proof obligations
     fn generate_proof_obligations(&self, property_name: &str) -> Vec<String> {
         match property_name {
+            "vortex_cycle" => vec![
+                "Prove that doubling mod 9 maps each pattern value to the next".to_string(),
+                "Prove that the cycle closes after six steps".to_string(),
+                "Prove that the digital root of 2^k is pattern[k mod 6]".to_string(),
+            ],
             "safety" => vec![
                 "Prove that conflicting blocks cannot be committed at same height".to_string(),
                 "Prove that vortex scores remain consistent across validators".to_string(),
@@ -259,6 +336,10 @@ impl TLAProof {
     pub fn model_checking(&self) -> &ModelCheckingResult {
         &self.model_checking
     }
+
+    pub fn is_verified(&self) -> bool {
+        self.model_checking.result == "VERIFIED" && self.model_checking.counterexamples.is_empty()
+    }
 }
 
 /// Model checking result
@@ -294,6 +375,27 @@ mod tests {
         assert!(report.recommendations.len() >= 3);
     }
 
+    #[test]
+    fn test_canonical_vortex_cycle_verifies() {
+        let verifier = FormalVerifier::new();
+        let proof = verifier.verify_vortex_cycle(&VortexMath::new());
+
+        assert!(proof.is_verified(), "{:?}", proof.model_checking().counterexamples);
+        assert_eq!(proof.property_name, "vortex_cycle");
+        assert!(proof.tla_spec.is_some());
+        assert_eq!(proof.model_checking().states_explored, 6 + u64::from(VORTEX_CYCLE_POWERS));
+    }
+
+    #[test]
+    fn test_corrupted_vortex_cycle_fails() {
+        let verifier = FormalVerifier::new();
+        let proof = verifier.verify_vortex_cycle(&VortexMath::with_pattern([1, 2, 4, 8, 5, 7]));
+
+        assert!(!proof.is_verified());
+        assert_eq!(proof.model_checking().result, "VIOLATED");
+        assert!(proof.model_checking().counterexamples.iter().any(|c| c.contains("digital root of 2^4")));
+    }
+
     #[test]
     fn test_tla_specifications() {
         let verifier = FormalVerifier::new();
@@ -0,0 +1,98 @@
+use axum::{
+    body::Body,
+    extract::Request,
+    http::header::{CONTENT_LENGTH, CONTENT_TYPE},
+    middleware::Next,
+    response::{Json, Response},
+};
+use serde_json::{json, Value};
+
+/// Largest response body the middleware will rewrite
+const MAX_REWRITE_BYTES: usize = 1024 * 1024;
+
+/// Node clock in UNIX seconds
+pub fn server_time() -> u64 {
+    chrono::Utc::now().timestamp() as u64
+}
+
+/// GET /api/v1/time: node clock for client skew detection
+pub async fn time_endpoint() -> Json<Value> {
+    let now = chrono::Utc::now();
+    Json(json!({
+        "success": true,
+        "server_time": now.timestamp(),
+        "server_time_ms": now.timestamp_millis()
+    }))
+}
+
+/// Adds `server_time` to JSON object responses; other responses pass through unchanged
+pub async fn server_time_middleware(request: Request, next: Next) -> Response {
+    let response = next.run(request).await;
+    let is_json = response
+        .headers()
+        .get(CONTENT_TYPE)
+        .map_or(false, |v| v.as_bytes().starts_with(b"application/json"));
+    if !is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let bytes = match axum::body::to_bytes(body, MAX_REWRITE_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            log::warn!("Could not buffer response to add server_time: {}", e);
+            parts.headers.remove(CONTENT_LENGTH);
+            return Response::from_parts(parts, Body::empty());
+        }
+    };
+
+    match serde_json::from_slice::<Value>(&bytes) {
+        Ok(Value::Object(mut body)) => {
+            body.insert("server_time".to_string(), json!(server_time()));
+            parts.headers.remove(CONTENT_LENGTH);
+            Response::from_parts(parts, Body::from(Value::Object(body).to_string()))
+        }
+        _ => Response::from_parts(parts, Body::from(bytes)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{http::StatusCode, routing::{get, post}, Router};
+    use tower::ServiceExt;
+    use crate::tx_submission::SubmissionResult;
+
+    async fn body_json(response: Response) -> Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_time_endpoint_and_send_responses_carry_server_time() {
+        let app = Router::new()
+            .route("/api/v1/time", get(time_endpoint))
+            .route(
+                "/api/v1/wallet/send",
+                post(|| async { SubmissionResult::rejected(None, "Amount must be greater than zero") })
+                    .layer(axum::middleware::from_fn(server_time_middleware)),
+            );
+
+        let before = server_time();
+        let response = app.clone()
+            .oneshot(axum::http::Request::get("/api/v1/time").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let time = body_json(response).await["server_time"].as_u64().unwrap();
+        assert!(time >= before && time <= server_time());
+
+        let response = app
+            .oneshot(axum::http::Request::post("/api/v1/wallet/send").body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = body_json(response).await;
+        assert_eq!(body["status"], "rejected");
+        assert!(body["server_time"].as_u64().unwrap() >= before);
+    }
+}
@@ -2,9 +2,110 @@
 use once_cell::sync::Lazy;
 use tokio::sync::RwLock as TokioRwLock;
 use serde::{Deserialize, Serialize};
+use crate::input_validation::InputValidator;
 
-// Shared transaction structure
-#[derive(Clone, Debug, Serialize, Deserialize)]
+// Longest transaction hash accepted at construction
+pub const MAX_TX_HASH_LEN: usize = 128;
+
+// Errors raised when building a transaction from untrusted input
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum TxError {
+    #[error("Invalid {field} address '{address}': {reason}")]
+    InvalidAddress { field: &'static str, address: String, reason: String },
+    #[error("Amount must be greater than zero")]
+    ZeroAmount,
+    #[error("Sender and recipient must be different addresses")]
+    SelfTransfer,
+    #[error("{field} is empty or longer than {max} characters")]
+    InvalidField { field: &'static str, max: usize },
+    #[error("Amount {amount} exceeds the per-transaction maximum of {max}")]
+    AmountTooLarge { amount: u64, max: u64 },
+    #[error("Daily transfer limit of {limit} exceeded for {address}: {spent} already sent today")]
+    DailyLimitExceeded { address: String, limit: u64, spent: u64 },
+    #[error("Transfer is not signed")]
+    Unsigned,
+    #[error("Signature does not match sender {address}")]
+    InvalidSignature { address: String },
+    #[error("Fee {fee} is below the minimum of {min}")]
+    FeeTooLow { fee: u64, min: u64 },
+}
+
+// Operator caps on transfer value in microFVC (env: MAX_TX_AMOUNT, DAILY_TX_LIMIT); unset means unlimited
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct TransferLimits {
+    pub max_per_tx: Option<u64>,
+    pub daily_limit: Option<u64>,
+}
+
+impl TransferLimits {
+    pub fn from_env() -> Self {
+        let read = |name: &str| std::env::var(name).ok().and_then(|v| v.trim().parse::<u64>().ok());
+        Self {
+            max_per_tx: read("MAX_TX_AMOUNT"),
+            daily_limit: read("DAILY_TX_LIMIT"),
+        }
+    }
+
+    pub fn check_amount(&self, amount: u64) -> Result<(), TxError> {
+        match self.max_per_tx {
+            Some(max) if amount > max => Err(TxError::AmountTooLarge { amount, max }),
+            _ => Ok(()),
+        }
+    }
+
+    // Whether `amount` fits in what `from` may still send today, `spent` being what it already sent
+    pub fn check_daily(&self, from: &str, spent: u64, amount: u64) -> Result<(), TxError> {
+        match self.daily_limit {
+            Some(limit) if spent.saturating_add(amount) > limit => {
+                Err(TxError::DailyLimitExceeded { address: from.to_string(), limit, spent })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+pub static TRANSFER_LIMITS: Lazy<TransferLimits> = Lazy::new(TransferLimits::from_env);
+
+pub const SECONDS_PER_DAY: u64 = 86400;
+
+// UTC day number that outgoing value is counted against for TransferLimits::daily_limit
+pub fn spend_day(now: u64) -> u64 {
+    now / SECONDS_PER_DAY
+}
+
+// Native address check shared by every transaction constructor
+pub fn validate_tx_address(field: &'static str, address: &str) -> Result<(), TxError> {
+    InputValidator::validate_fvchain_address(address).map_err(|e| TxError::InvalidAddress {
+        field,
+        address: address.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+// Amount and hash limits shared by every transaction constructor
+pub fn validate_tx_fields(amount: u64, hash: &str) -> Result<(), TxError> {
+    if amount == 0 {
+        return Err(TxError::ZeroAmount);
+    }
+    if hash.is_empty() || hash.len() > MAX_TX_HASH_LEN {
+        return Err(TxError::InvalidField { field: "hash", max: MAX_TX_HASH_LEN });
+    }
+    Ok(())
+}
+
+// Validated transfer checks, shared by both WalletTransaction types
+pub fn validate_transfer(from: &str, to: &str, amount: u64, hash: &str) -> Result<(), TxError> {
+    validate_tx_address("sender", from)?;
+    validate_tx_address("recipient", to)?;
+    if from == to {
+        return Err(TxError::SelfTransfer);
+    }
+    validate_tx_fields(amount, hash)?;
+    TRANSFER_LIMITS.check_amount(amount)
+}
+
+// Shared transaction structure; converts losslessly to and from rpc_storage::WalletTransaction
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct WalletTransaction {
     pub from: String,
     pub to: String,
@@ -14,6 +115,14 @@ pub struct WalletTransaction {
     pub timestamp: u64,
     pub signature: Option<Vec<u8>>,
     pub hash: Option<String>,
+    #[serde(default = "default_transaction_type")]
+    pub transaction_type: String,
+    #[serde(default)]
+    pub block_height: u64,
+}
+
+fn default_transaction_type() -> String {
+    "transfer".to_string()
 }
 
 // Global shared transaction storage
@@ -21,10 +130,7 @@ pub static TRANSACTIONS: Lazy<TokioRwLock<Vec<WalletTransaction>>> = Lazy::new(|
     TokioRwLock::new(Vec::new())
 });
 
-// Global shared block height
-pub static BLOCK_HEIGHT: Lazy<TokioRwLock<u64>> = Lazy::new(|| {
-    TokioRwLock::new(1) // Start from block 1 (genesis is 0)
-});
+// Block height is not tracked here: RPCStorage::get_block_height is the single source of truth
 
 // Helper functions for transaction management
 impl WalletTransaction {
@@ -38,9 +144,24 @@ impl WalletTransaction {
             timestamp: chrono::Utc::now().timestamp() as u64,
             signature: None,
             hash: Some(hash),
+            transaction_type: "mining_reward".to_string(),
+            block_height: 0,
         }
     }
     
+    // Mining reward that rejects a malformed recipient, zero amount or bad hash
+    pub fn try_new_mining_reward(to: String, amount: u64, hash: String) -> Result<Self, TxError> {
+        validate_tx_address("recipient", &to)?;
+        validate_tx_fields(amount, &hash)?;
+        Ok(Self::new_mining_reward(to, amount, hash))
+    }
+
+    // Transfer that rejects malformed addresses, zero amount or bad hash
+    pub fn try_new_transfer(from: String, to: String, amount: u64, fee: u64, signature: Option<Vec<u8>>, hash: String) -> Result<Self, TxError> {
+        validate_transfer(&from, &to, amount, &hash)?;
+        Ok(Self::new_transfer(from, to, amount, fee, signature, hash))
+    }
+    
     pub fn new_transfer(from: String, to: String, amount: u64, fee: u64, signature: Option<Vec<u8>>, hash: String) -> Self {
         Self {
             from,
@@ -51,6 +172,8 @@ impl WalletTransaction {
             timestamp: chrono::Utc::now().timestamp() as u64,
             signature,
             hash: Some(hash),
+            transaction_type: default_transaction_type(),
+            block_height: 0,
         }
     }
 }
@@ -72,19 +195,35 @@ pub async fn get_latest_transactions(limit: usize) -> Vec<WalletTransaction> {
     txs[start..].to_vec()
 }
 
-// Block height management functions
-pub async fn increment_block_height() -> u64 {
-    let mut height = BLOCK_HEIGHT.write().await;
-    *height += 1;
-    *height
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-pub async fn get_block_height() -> u64 {
-    let height = BLOCK_HEIGHT.read().await;
-    *height
-}
+    const LIMITS: TransferLimits = TransferLimits { max_per_tx: Some(5_000_000), daily_limit: Some(8_000_000) };
 
-pub async fn set_block_height(new_height: u64) {
-    let mut height = BLOCK_HEIGHT.write().await;
-    *height = new_height;
-}
\ No newline at end of file
+    #[test]
+    fn test_transfer_at_and_over_max() {
+        assert_eq!(LIMITS.check_amount(5_000_000), Ok(()));
+        assert_eq!(
+            LIMITS.check_amount(5_000_001),
+            Err(TxError::AmountTooLarge { amount: 5_000_001, max: 5_000_000 })
+        );
+        assert_eq!(TransferLimits::default().check_amount(u64::MAX), Ok(()));
+    }
+
+    #[test]
+    fn test_daily_limit_across_transfers() {
+        assert_eq!(LIMITS.check_daily("fvcsender", 0, 5_000_000), Ok(()));
+        assert_eq!(LIMITS.check_daily("fvcsender", 5_000_000, 3_000_000), Ok(()));
+        assert_eq!(
+            LIMITS.check_daily("fvcsender", 8_000_000, 1),
+            Err(TxError::DailyLimitExceeded { address: "fvcsender".to_string(), limit: 8_000_000, spent: 8_000_000 })
+        );
+        assert_eq!(TransferLimits::default().check_daily("fvcsender", u64::MAX, 1), Ok(()));
+
+        // Counts roll over at midnight UTC
+        let now = 1_700_000_000;
+        assert_eq!(spend_day(now), spend_day(now - now % SECONDS_PER_DAY));
+        assert_eq!(spend_day(now) + 1, spend_day(now + SECONDS_PER_DAY));
+    }
+}
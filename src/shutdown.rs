@@ -0,0 +1,146 @@
+use std::future::Future;
+use std::net::SocketAddr;
+use std::time::Duration;
+use axum::Router;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use crate::rpc_storage::RPCStorage;
+use crate::storage::StorageError;
+use crate::tx_submission::wait_for_in_flight;
+
+/// How long open connections get to finish after a shutdown signal before the server stops anyway
+pub const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
+
+/// Resolves on Ctrl-C (SIGINT) or SIGTERM, the signal orchestrators send to stop a process
+pub async fn shutdown_signal() {
+    let interrupt = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            log::error!("Failed to listen for Ctrl-C: {}", e);
+            std::future::pending::<()>().await;
+        }
+    };
+    first_signal(interrupt, terminate_signal()).await
+}
+
+#[cfg(unix)]
+async fn terminate_signal() {
+    use tokio::signal::unix::{signal, SignalKind};
+    match signal(SignalKind::terminate()) {
+        Ok(mut terminate) => {
+            terminate.recv().await;
+        }
+        Err(e) => {
+            log::error!("Failed to listen for SIGTERM: {}", e);
+            std::future::pending::<()>().await;
+        }
+    }
+}
+
+#[cfg(not(unix))]
+async fn terminate_signal() {
+    std::future::pending::<()>().await
+}
+
+/// Resolves once either signal does; tests pass their own futures in place of the OS signals
+pub async fn first_signal(interrupt: impl Future<Output = ()>, terminate: impl Future<Output = ()>) {
+    tokio::select! {
+        _ = interrupt => log::info!("Interrupted, draining in-flight requests"),
+        _ = terminate => log::info!("Terminated, draining in-flight requests"),
+    }
+}
+
+/// Wait for transfers being submitted, then sync storage to disk
+pub async fn finish_shutdown() -> Result<(), StorageError> {
+    wait_for_in_flight().await;
+    RPCStorage::flush().await
+}
+
+/// Serve `app` until `signal` resolves, then stop accepting connections, give in-flight
+/// requests up to `grace` to complete, and `finish_shutdown`
+pub async fn serve_until<F>(listener: TcpListener, app: Router, signal: F, grace: Duration) -> std::io::Result<()>
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let (signalled_tx, signalled_rx) = oneshot::channel::<()>();
+    let server = axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .with_graceful_shutdown(async move {
+            signal.await;
+            let _ = signalled_tx.send(());
+        });
+    // Long-lived streams would otherwise hold shutdown open forever
+    let grace_expired = async move {
+        match signalled_rx.await {
+            Ok(()) => tokio::time::sleep(grace).await,
+            Err(_) => std::future::pending::<()>().await,
+        }
+    };
+
+    tokio::select! {
+        result = server => result?,
+        _ = grace_expired => log::warn!("Connections still open after {:?}, stopping anyway", grace),
+    }
+
+    finish_shutdown().await.map_err(std::io::Error::other)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_storage::use_test_db;
+    use axum::{extract::Path, routing::post};
+    use crate::rpc_storage::sender_fee;
+    use crate::tx_submission::{build_transfer, submit_new_transfer};
+
+    const SENDER: &str = "fvc00000000000000000000000000000000e001emyl";
+    const RECIPIENT: &str = "fvc00000000000000000000000000000000e002emyl";
+    const BATCH: u64 = 200;
+    const AMOUNT: u64 = 100;
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_sigterm_during_transfers_leaves_no_partial_balances() {
+        let _db = use_test_db();
+        const INITIAL: u64 = 10_000_000;
+        RPCStorage::set_balance(SENDER, INITIAL).await.unwrap();
+
+        // Stands in for SIGTERM; Ctrl-C never comes
+        let (terminate_tx, terminate_rx) = oneshot::channel::<()>();
+        let signal = first_signal(std::future::pending(), async move {
+            let _ = terminate_rx.await;
+        });
+
+        let app = Router::new().route("/send/:nonce", post(|Path(nonce): Path<u64>| async move {
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            submit_new_transfer("transfer", SENDER.to_string(), RECIPIENT.to_string(), AMOUNT, Some(nonce)).await.1
+        }));
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let server = tokio::spawn(serve_until(listener, app, signal, Duration::from_secs(5)));
+
+        let client = reqwest::Client::new();
+        let mut terminate_tx = Some(terminate_tx);
+        for nonce in 1..=BATCH {
+            if nonce == 5 {
+                let _ = terminate_tx.take().unwrap().send(());
+            }
+            if client.post(format!("{}/send/{}", url, nonce)).send().await.is_err() {
+                break;
+            }
+        }
+        server.await.unwrap().unwrap();
+
+        let mut accepted = 0;
+        for nonce in 1..=BATCH {
+            let hash = build_transfer("transfer", SENDER.to_string(), RECIPIENT.to_string(), AMOUNT, nonce).unwrap().hash;
+            if RPCStorage::get_transaction(&hash).await.unwrap().is_some() {
+                accepted += 1;
+            }
+        }
+        assert!(accepted >= 4 && accepted < BATCH, "{} transfers accepted", accepted);
+
+        // Every stored transfer moved its full amount and fee; nothing else moved
+        let fee = sender_fee(&build_transfer("transfer", SENDER.to_string(), RECIPIENT.to_string(), AMOUNT, 1).unwrap());
+        assert_eq!(RPCStorage::get_balance(SENDER).await.unwrap(), INITIAL - accepted * (AMOUNT + fee));
+        assert_eq!(RPCStorage::get_balance(RECIPIENT).await.unwrap(), accepted * AMOUNT);
+        assert_eq!(RPCStorage::get_account_nonce(SENDER).await.unwrap(), accepted);
+    }
+}
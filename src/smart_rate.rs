@@ -0,0 +1,183 @@
+use serde::{Deserialize, Serialize};
+use crate::rpc_storage::{VortexPatternConfig, VORTEX_PATTERN};
+
+/// Smart Rate of a chain whose components are all at their maximum, before the vortex pattern
+pub const BASE_SMART_RATE: f64 = 1000.0;
+
+/// Upper bound of every component; each is divided by it to normalize into 0..=1
+pub const COMPONENT_SCALE: f64 = 1000.0;
+
+/// Component weights in the weighted geometric mean; they sum to 1.0
+pub const VORTEX_ENERGY_WEIGHT: f64 = 0.30;
+pub const FRACTAL_CONTRIBUTION_WEIGHT: f64 = 0.25;
+pub const EFFICIENCY_WEIGHT: f64 = 0.25;
+pub const HARMONY_WEIGHT: f64 = 0.20;
+
+/// Floor on the weighted geometric mean so an idle chain still reports a small rate
+const MIN_WEIGHTED_MEAN: f64 = 0.001;
+
+/// Smart Rate (in smart steps per second) for one chain state, with the components it was built from.
+/// Every component is on a 1..=1000 scale, or 0 when the chain is too short to measure it.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SmartRate {
+    pub block_height: u64,
+    pub transaction_count: u64,
+    pub vortex_energy_rate: f64,
+    pub fractal_contribution_score: f64,
+    pub mathematical_efficiency_index: f64,
+    pub network_harmony_factor: f64,
+    /// Vortex pattern multiplier at `block_height`
+    pub pattern_multiplier: f64,
+    /// Rounded to two decimals
+    pub smart_rate: f64,
+}
+
+/// Smart Rate of a chain at `block_height` holding `tx_count` transactions, under the configured vortex pattern
+pub fn compute(block_height: u64, tx_count: u64) -> SmartRate {
+    compute_with(block_height, tx_count, &VORTEX_PATTERN)
+}
+
+/// Smart Rate under an explicit vortex pattern
+pub fn compute_with(block_height: u64, tx_count: u64, pattern: &VortexPatternConfig) -> SmartRate {
+    let vortex_energy_rate = vortex_energy_rate(block_height, tx_count);
+    let fractal_contribution_score = fractal_contribution_score(block_height, tx_count);
+    let mathematical_efficiency_index = mathematical_efficiency_index(block_height, tx_count);
+    let network_harmony_factor = network_harmony_factor(block_height, tx_count);
+    SmartRate {
+        block_height,
+        transaction_count: tx_count,
+        vortex_energy_rate,
+        fractal_contribution_score,
+        mathematical_efficiency_index,
+        network_harmony_factor,
+        pattern_multiplier: pattern.multiplier(block_height),
+        smart_rate: from_components(
+            vortex_energy_rate,
+            fractal_contribution_score,
+            mathematical_efficiency_index,
+            network_harmony_factor,
+            block_height,
+            pattern,
+        ),
+    }
+}
+
+/// Combine components (0..=1000 each) into a weighted geometric mean, scale it by BASE_SMART_RATE
+/// and the vortex pattern multiplier, and round to two decimals
+pub fn from_components(
+    vortex_energy: f64,
+    fractal_score: f64,
+    efficiency_index: f64,
+    harmony_factor: f64,
+    block_height: u64,
+    pattern: &VortexPatternConfig,
+) -> f64 {
+    let normalize = |component: f64| (component / COMPONENT_SCALE).min(1.0);
+    let weighted_geometric_mean = (normalize(vortex_energy).powf(VORTEX_ENERGY_WEIGHT)
+        * normalize(fractal_score).powf(FRACTAL_CONTRIBUTION_WEIGHT)
+        * normalize(efficiency_index).powf(EFFICIENCY_WEIGHT)
+        * normalize(harmony_factor).powf(HARMONY_WEIGHT))
+        .max(MIN_WEIGHTED_MEAN);
+
+    let smart_rate = BASE_SMART_RATE * weighted_geometric_mean * pattern.multiplier(block_height);
+    (smart_rate * 100.0).round() / 100.0
+}
+
+/// Average transactions per block
+fn throughput(block_height: u64, tx_count: u64) -> f64 {
+    tx_count as f64 / block_height as f64
+}
+
+/// Vortex Energy Rate (VER): throughput at 85% energy efficiency, boosted by load up to 10 tx/block
+pub fn vortex_energy_rate(block_height: u64, tx_count: u64) -> f64 {
+    if block_height == 0 {
+        return 0.0;
+    }
+    let throughput = throughput(block_height, tx_count);
+    let network_load = (throughput / 10.0).min(1.0);
+    (throughput * 0.85 * (1.0 + network_load) * 100.0).clamp(1.0, 1000.0)
+}
+
+/// Fractal Contribution Score (FCS): transaction density scaled by the chain's binary fractal depth
+pub fn fractal_contribution_score(block_height: u64, tx_count: u64) -> f64 {
+    if block_height <= 1 {
+        return 0.0;
+    }
+    let fractal_depth = (block_height as f64).log2().floor();
+    let pattern_strength = (fractal_depth / 20.0).min(1.0);
+    (throughput(block_height, tx_count) * (1.0 + pattern_strength) * fractal_depth * 10.0).clamp(1.0, 1000.0)
+}
+
+/// Mathematical Efficiency Index (MEI): throughput scaled by φ·e and a log-height complexity factor
+pub fn mathematical_efficiency_index(block_height: u64, tx_count: u64) -> f64 {
+    if block_height == 0 {
+        return 0.0;
+    }
+    let complexity_factor = (block_height as f64).log10() / 10.0;
+    let mei = throughput(block_height, tx_count)
+        * crate::math::GOLDEN_RATIO
+        * (1.0 + complexity_factor)
+        * std::f64::consts::E
+        * 10.0;
+    mei.clamp(1.0, 1000.0)
+}
+
+/// Network Harmony Factor (NHF): 40% block consistency, 40% transaction flow, 20% network sync
+pub fn network_harmony_factor(block_height: u64, tx_count: u64) -> f64 {
+    if block_height == 0 {
+        return 0.0;
+    }
+    let block_consistency = if block_height > 10 {
+        block_height.min(100) as f64 / 100.0
+    } else {
+        block_height as f64 / 10.0
+    };
+    let transaction_smoothness = (throughput(block_height, tx_count) / 10.0).min(1.0);
+    let sync_factor = 0.95;
+    ((block_consistency * 0.4 + transaction_smoothness * 0.4 + sync_factor * 0.2) * 1000.0).clamp(1.0, 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_storage::DEFAULT_VORTEX_PATTERN_MULTIPLIERS;
+
+    #[test]
+    fn test_vortex_pattern_drives_smart_rate() {
+        let default = VortexPatternConfig::default();
+        let doubled = VortexPatternConfig::new(
+            DEFAULT_VORTEX_PATTERN_MULTIPLIERS.iter().map(|m| m * 2.0).collect()
+        ).unwrap();
+
+        for height in 0..12 {
+            let base = from_components(500.0, 400.0, 300.0, 200.0, height, &default);
+            assert_eq!(base, from_components(500.0, 400.0, 300.0, 200.0, height, &default));
+
+            let changed = from_components(500.0, 400.0, 300.0, 200.0, height, &doubled);
+            assert!((changed - base * 2.0).abs() <= 0.01, "height {}: {} vs {}", height, changed, base);
+        }
+
+        // Heights one sequence length apart share a multiplier
+        assert_eq!(default.multiplier(3), 1.8);
+        assert_eq!(default.multiplier(9), default.multiplier(3));
+    }
+
+    #[test]
+    fn test_breakdown_reproduces_smart_rate() {
+        let pattern = VortexPatternConfig::default();
+        for (height, tx_count) in [(0, 0), (1, 1), (2, 7), (1_000, 2_500), (710_000, 1_420_000)] {
+            let rate = compute_with(height, tx_count, &pattern);
+            assert_eq!(rate, compute_with(height, tx_count, &pattern));
+            assert_eq!(rate.pattern_multiplier, pattern.multiplier(height));
+            assert_eq!(rate.smart_rate, from_components(
+                rate.vortex_energy_rate,
+                rate.fractal_contribution_score,
+                rate.mathematical_efficiency_index,
+                rate.network_harmony_factor,
+                height,
+                &pattern,
+            ));
+        }
+        assert!((VORTEX_ENERGY_WEIGHT + FRACTAL_CONTRIBUTION_WEIGHT + EFFICIENCY_WEIGHT + HARMONY_WEIGHT - 1.0).abs() < 1e-12);
+    }
+}
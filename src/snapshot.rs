@@ -0,0 +1,226 @@
+use std::path::Path;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::storage::{LedgerDB, StorageError};
+
+/// Archive format written by `take_snapshot`
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+/// Balances compared against the archive after a restore
+pub const BALANCE_SAMPLE_SIZE: usize = 32;
+
+#[derive(Debug, Error)]
+pub enum SnapshotError {
+    #[error(transparent)]
+    Storage(#[from] StorageError),
+    #[error("Snapshot I/O failed: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Malformed snapshot: {0}")]
+    Format(String),
+    #[error("Unsupported snapshot version {0}")]
+    UnsupportedVersion(u32),
+    #[error("Target database is not empty; restore needs a fresh data directory")]
+    NotEmpty,
+    #[error("Restored chain does not match the snapshot: {0}")]
+    Mismatch(String),
+}
+
+/// One database record, hex-encoded
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SnapshotRecord {
+    pub key: String,
+    pub value: String,
+}
+
+/// Every record of an RPC storage database: blocks (`block:*`), transactions (`tx:*`),
+/// balances and the indexes that tie them together
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChainSnapshot {
+    pub version: u32,
+    pub block_height: u64,
+    pub block_count: usize,
+    pub transaction_count: usize,
+    pub records: Vec<SnapshotRecord>,
+}
+
+fn stored_height(value: Option<Vec<u8>>) -> Result<u64, SnapshotError> {
+    match value {
+        Some(bytes) => <[u8; 8]>::try_from(bytes.as_slice())
+            .map(u64::from_le_bytes)
+            .map_err(|_| SnapshotError::Format(format!("block_height is {} bytes, expected 8", bytes.len()))),
+        None => Ok(0),
+    }
+}
+
+/// Balances are stored under the bare address as a u64
+fn is_balance(key: &[u8], value: &[u8]) -> bool {
+    key.starts_with(b"fvc") && value.len() == 8
+}
+
+/// Export the whole database; the node must be stopped so the records are consistent
+pub async fn take_snapshot(db: &LedgerDB) -> Result<ChainSnapshot, SnapshotError> {
+    let entries = db.scan_prefix(b"", usize::MAX).await?;
+    Ok(ChainSnapshot {
+        version: SNAPSHOT_VERSION,
+        block_height: stored_height(db.get(b"block_height").await?)?,
+        block_count: entries.iter().filter(|(key, _)| key.starts_with(b"block:")).count(),
+        transaction_count: entries.iter().filter(|(key, _)| key.starts_with(b"tx:")).count(),
+        records: entries
+            .into_iter()
+            .map(|(key, value)| SnapshotRecord { key: hex::encode(key), value: hex::encode(value) })
+            .collect(),
+    })
+}
+
+/// Load `snapshot` into an empty database, then `verify_restore`
+pub async fn restore_snapshot(db: &LedgerDB, snapshot: &ChainSnapshot) -> Result<(), SnapshotError> {
+    if snapshot.version != SNAPSHOT_VERSION {
+        return Err(SnapshotError::UnsupportedVersion(snapshot.version));
+    }
+    if !db.scan_prefix(b"", usize::MAX).await?.is_empty() {
+        return Err(SnapshotError::NotEmpty);
+    }
+
+    // Decode everything before writing, and write in one batch, so a failed restore leaves the
+    // database empty and can simply be retried
+    let mut entries = Vec::with_capacity(snapshot.records.len());
+    for record in &snapshot.records {
+        let key = hex::decode(&record.key).map_err(|e| SnapshotError::Format(e.to_string()))?;
+        let value = hex::decode(&record.value).map_err(|e| SnapshotError::Format(e.to_string()))?;
+        entries.push((key, value));
+    }
+    db.put_batch(&entries).await?;
+
+    verify_restore(db, snapshot).await
+}
+
+/// Check the restored height and block count, and an evenly spread sample of balances
+pub async fn verify_restore(db: &LedgerDB, snapshot: &ChainSnapshot) -> Result<(), SnapshotError> {
+    let height = stored_height(db.get(b"block_height").await?)?;
+    if height != snapshot.block_height {
+        return Err(SnapshotError::Mismatch(format!(
+            "latest height {} but snapshot has {}",
+            height, snapshot.block_height
+        )));
+    }
+
+    let blocks = db.scan_prefix(b"block:", usize::MAX).await?.len();
+    if blocks != snapshot.block_count {
+        return Err(SnapshotError::Mismatch(format!("{} blocks but snapshot has {}", blocks, snapshot.block_count)));
+    }
+
+    let mut balances = Vec::new();
+    for record in &snapshot.records {
+        let key = hex::decode(&record.key).map_err(|e| SnapshotError::Format(e.to_string()))?;
+        let value = hex::decode(&record.value).map_err(|e| SnapshotError::Format(e.to_string()))?;
+        if is_balance(&key, &value) {
+            balances.push((key, value));
+        }
+    }
+    let step = (balances.len() / BALANCE_SAMPLE_SIZE).max(1);
+    for (key, value) in balances.iter().step_by(step) {
+        if db.get(key).await?.as_ref() != Some(value) {
+            return Err(SnapshotError::Mismatch(format!(
+                "balance of {} differs",
+                String::from_utf8_lossy(key)
+            )));
+        }
+    }
+    Ok(())
+}
+
+pub fn write_snapshot(path: impl AsRef<Path>, snapshot: &ChainSnapshot) -> Result<(), SnapshotError> {
+    let data = serde_json::to_vec(snapshot).map_err(|e| SnapshotError::Format(e.to_string()))?;
+    std::fs::write(path, data)?;
+    Ok(())
+}
+
+pub fn read_snapshot(path: impl AsRef<Path>) -> Result<ChainSnapshot, SnapshotError> {
+    let data = std::fs::read(path)?;
+    serde_json::from_slice(&data).map_err(|e| SnapshotError::Format(e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_storage::{Block, WalletTransaction};
+
+    fn address(n: u64) -> String {
+        format!("fvc{:036x}emyl", n)
+    }
+
+    /// A 20-block chain with one transfer per block, written the way RPC storage lays it out
+    async fn build_chain(db: &LedgerDB) {
+        let mut parent_hash = "0x0".to_string();
+        for height in 0..20u64 {
+            let mut block = Block::new_with_timestamp(height, address(0), parent_hash.clone(), 1_700_000_000 + height * 5);
+            let tx = WalletTransaction::new_transfer(address(0), address(height + 1), 1_000 * (height + 1), format!("0xtx{}", height), height);
+            db.put(format!("tx:{}", tx.hash).as_bytes(), &serde_json::to_vec(&tx).unwrap()).await.unwrap();
+            block.add_transaction(tx);
+            db.put(format!("block:{}", height).as_bytes(), &serde_json::to_vec(&block).unwrap()).await.unwrap();
+            db.set_balance(&address(height + 1), 1_000 * (height + 1)).await.unwrap();
+            parent_hash = block.hash.clone();
+        }
+        db.set_balance(&address(0), 1_000_000).await.unwrap();
+        db.put(b"block_height", &19u64.to_le_bytes()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_snapshot_round_trip() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let source = LedgerDB::open(source_dir.path()).unwrap();
+        build_chain(&source).await;
+
+        let snapshot = take_snapshot(&source).await.unwrap();
+        assert_eq!(snapshot.block_height, 19);
+        assert_eq!(snapshot.block_count, 20);
+        assert_eq!(snapshot.transaction_count, 20);
+
+        let archive = tempfile::NamedTempFile::new().unwrap();
+        write_snapshot(archive.path(), &snapshot).unwrap();
+        let loaded = read_snapshot(archive.path()).unwrap();
+        assert_eq!(loaded, snapshot);
+
+        let target_dir = tempfile::tempdir().unwrap();
+        let target = LedgerDB::open(target_dir.path()).unwrap();
+        restore_snapshot(&target, &loaded).await.unwrap();
+
+        assert_eq!(target.get(b"block_height").await.unwrap(), Some(19u64.to_le_bytes().to_vec()));
+        for height in 0..20u64 {
+            let key = format!("block:{}", height);
+            assert_eq!(target.get(key.as_bytes()).await.unwrap(), source.get(key.as_bytes()).await.unwrap());
+            assert_eq!(target.get_balance(&address(height + 1)).await.unwrap(), 1_000 * (height + 1));
+        }
+        assert_eq!(target.scan_prefix(b"", usize::MAX).await.unwrap(), source.scan_prefix(b"", usize::MAX).await.unwrap());
+
+        // A second restore would mix two chains
+        assert!(matches!(restore_snapshot(&target, &loaded).await, Err(SnapshotError::NotEmpty)));
+    }
+
+    #[tokio::test]
+    async fn test_malformed_snapshot_leaves_target_empty() {
+        let source_dir = tempfile::tempdir().unwrap();
+        let source = LedgerDB::open(source_dir.path()).unwrap();
+        source.put(b"block_height", &[1, 2, 3]).await.unwrap();
+        assert!(matches!(take_snapshot(&source).await, Err(SnapshotError::Format(_))));
+
+        let mut snapshot = ChainSnapshot {
+            version: SNAPSHOT_VERSION,
+            block_height: 0,
+            block_count: 0,
+            transaction_count: 0,
+            records: vec![SnapshotRecord { key: hex::encode("fvcgood"), value: hex::encode(5u64.to_le_bytes()) }],
+        };
+        snapshot.records.push(SnapshotRecord { key: hex::encode("fvcbad"), value: "not hex".to_string() });
+
+        let target_dir = tempfile::tempdir().unwrap();
+        let target = LedgerDB::open(target_dir.path()).unwrap();
+        assert!(matches!(restore_snapshot(&target, &snapshot).await, Err(SnapshotError::Format(_))));
+        assert!(target.scan_prefix(b"", usize::MAX).await.unwrap().is_empty());
+
+        // Fixing the archive and retrying works on the untouched database
+        snapshot.records.pop();
+        restore_snapshot(&target, &snapshot).await.unwrap();
+        assert_eq!(target.get(b"fvcgood").await.unwrap(), Some(5u64.to_le_bytes().to_vec()));
+    }
+}
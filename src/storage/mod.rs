@@ -1,7 +1,9 @@
+use db_key::Key;
+use leveldb::batch::{Batch, Writebatch};
 use leveldb::database::Database;
+use leveldb::iterator::{Iterable, LevelDBIterator};
 use leveldb::kv::KV;
 use leveldb::options::{Options, WriteOptions, ReadOptions};
-// Iterator imports removed - not currently used
 use std::path::Path;
 use thiserror::Error;
 use std::sync::Arc;
@@ -16,11 +18,60 @@ pub enum StorageError {
     Serialization(String),
     #[error("Not found: {0}")]
     NotFound(String),
+    #[error("Invalid block: {0}")]
+    InvalidBlock(String),
+    #[error("Stale block: {0}")]
+    StaleBlock(String),
+    #[error("Balance underflow for {address}: {balance} - {amount}")]
+    BalanceUnderflow { address: String, balance: u64, amount: u64 },
+    #[error("Balance overflow for {address}: {balance} + {amount}")]
+    BalanceOverflow { address: String, balance: u64, amount: u64 },
+    #[error("Decryption failed: {0}")]
+    Decryption(String),
+}
+
+/// `prefix` followed by `n` zero-padded to 20 digits, so numeric order matches LevelDB's bytewise order
+pub fn ordered_key(prefix: &str, n: u64) -> Vec<u8> {
+    format!("{}{:020}", prefix, n).into_bytes()
+}
+
+/// Full byte key; LevelDB stores and orders it bytewise
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct BytesKey(pub Vec<u8>);
+
+impl Key for BytesKey {
+    fn from_u8(key: &[u8]) -> Self {
+        BytesKey(key.to_vec())
+    }
+
+    fn as_slice<T, F: Fn(&[u8]) -> T>(&self, f: F) -> T {
+        f(&self.0)
+    }
+}
+
+/// Marker recording the key layout of a database; scans never return it
+const KEY_FORMAT_KEY: &[u8] = b"__key_format__";
+/// Full byte keys only
+const KEY_FORMAT_BYTES: &[u8] = b"bytes";
+/// Full byte keys, with reads falling back to the 32-bit hashed keys written before the switch
+const KEY_FORMAT_HASHED_FALLBACK: &[u8] = b"hashed-fallback";
+
+/// Key a value was stored under before full byte keys: the big-endian `i32` of its `DefaultHasher` hash
+fn legacy_key(key: &[u8]) -> BytesKey {
+    use std::collections::hash_map::DefaultHasher;
+    use std::hash::{Hash, Hasher};
+    let mut hasher = DefaultHasher::new();
+    key.hash(&mut hasher);
+    BytesKey((hasher.finish() as i32).to_be_bytes().to_vec())
 }
 
 /// Simple ledger/UTXO storage backed by LevelDB
+///
+/// Databases written before full byte keys keep their values under hashed keys. Point reads fall
+/// back to those keys and deletes remove them too; scans only see entries written since the upgrade.
 pub struct LedgerDB {
-    db: Arc<RwLock<Database<i32>>>, // protected DB for async context
+    db: Arc<RwLock<Database<BytesKey>>>, // protected DB for async context
+    legacy_keys: bool,
 }
 
 impl LedgerDB {
@@ -28,27 +79,55 @@ impl LedgerDB {
     pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, StorageError> {
         let mut opts = Options::new();
         opts.create_if_missing = true;
-        let db = Database::open(path.as_ref(), opts)?;
+        let db: Database<BytesKey> = Database::open(path.as_ref(), opts)?;
+        let legacy_keys = match db.get(ReadOptions::new(), BytesKey(KEY_FORMAT_KEY.to_vec()))? {
+            Some(format) => format == KEY_FORMAT_HASHED_FALLBACK,
+            None => {
+                // Entries in a database without the marker were written under hashed keys
+                let legacy_keys = db.iter(ReadOptions::new()).next().is_some();
+                let format = if legacy_keys { KEY_FORMAT_HASHED_FALLBACK } else { KEY_FORMAT_BYTES };
+                db.put(WriteOptions::new(), BytesKey(KEY_FORMAT_KEY.to_vec()), format)?;
+                legacy_keys
+            }
+        };
         Ok(Self {
             db: Arc::new(RwLock::new(db)),
+            legacy_keys,
         })
     }
 
-    /// Convert byte key to i32 key for LevelDB
-    fn bytes_to_key(key: &[u8]) -> i32 {
-        use std::collections::hash_map::DefaultHasher;
-        use std::hash::{Hash, Hasher};
-        let mut hasher = DefaultHasher::new();
-        key.hash(&mut hasher);
-        hasher.finish() as i32
-    }
-
     /// Put arbitrary key/value pair
     pub async fn put(&self, key: &[u8], value: &[u8]) -> Result<(), StorageError> {
         let db = self.db.write().await;
         let write_opts = WriteOptions::new();
-        let key_i32 = Self::bytes_to_key(key);
-        db.put(write_opts, key_i32, value)?;
+        db.put(write_opts, BytesKey(key.to_vec()), value)?;
+        Ok(())
+    }
+
+    /// Put several key/value pairs in one atomic write; either all land or none do
+    pub async fn put_batch(&self, entries: &[(Vec<u8>, Vec<u8>)]) -> Result<(), StorageError> {
+        self.write_batch(&[], entries).await
+    }
+
+    /// Delete several keys in one atomic write
+    pub async fn delete_batch(&self, keys: &[Vec<u8>]) -> Result<(), StorageError> {
+        self.write_batch(keys, &[]).await
+    }
+
+    /// Delete `deletes`, then put `puts`, in one atomic write; a key in both ends up with the put value
+    pub async fn write_batch(&self, deletes: &[Vec<u8>], puts: &[(Vec<u8>, Vec<u8>)]) -> Result<(), StorageError> {
+        let db = self.db.write().await;
+        let mut batch = Writebatch::new();
+        for key in deletes {
+            batch.delete(BytesKey(key.clone()));
+            if self.legacy_keys {
+                batch.delete(legacy_key(key));
+            }
+        }
+        for (key, value) in puts {
+            batch.put(BytesKey(key.clone()), value);
+        }
+        db.write(WriteOptions::new(), &batch)?;
         Ok(())
     }
 
@@ -56,13 +135,63 @@ impl LedgerDB {
     pub async fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, StorageError> {
         let db = self.db.read().await;
         let read_opts = ReadOptions::new();
-        let key_i32 = Self::bytes_to_key(key);
-        match db.get(read_opts, key_i32)? {
+        match db.get(read_opts, BytesKey(key.to_vec()))? {
             Some(value) => Ok(Some(value)),
+            None if self.legacy_keys => Ok(db.get(ReadOptions::new(), legacy_key(key))?),
             None => Ok(None),
         }
     }
 
+    /// Up to `limit` key/value pairs whose key starts with `prefix`, in key order
+    pub async fn scan_prefix(&self, prefix: &[u8], limit: usize) -> Result<Vec<(Vec<u8>, Vec<u8>)>, StorageError> {
+        let db = self.db.read().await;
+        let start = BytesKey(prefix.to_vec());
+        let entries = db
+            .iter(ReadOptions::new())
+            .from(&start)
+            .take_while(|(key, _)| key.0.starts_with(prefix))
+            .filter(|(key, _)| key.0 != KEY_FORMAT_KEY)
+            .take(limit)
+            .map(|(key, value)| (key.0, value))
+            .collect();
+        Ok(entries)
+    }
+
+    /// Up to `limit` key/value pairs with `start <= key < end`, in ascending key order
+    pub async fn scan_range(&self, start: &[u8], end: &[u8], limit: usize) -> Result<Vec<(Vec<u8>, Vec<u8>)>, StorageError> {
+        let db = self.db.read().await;
+        let from = BytesKey(start.to_vec());
+        let entries = db
+            .iter(ReadOptions::new())
+            .from(&from)
+            .take_while(|(key, _)| key.0.as_slice() < end)
+            .filter(|(key, _)| key.0 != KEY_FORMAT_KEY)
+            .take(limit)
+            .map(|(key, value)| (key.0, value))
+            .collect();
+        Ok(entries)
+    }
+
+    /// Up to `limit` key/value pairs with `start <= key < end`, in descending key order
+    pub async fn scan_range_rev(&self, start: &[u8], end: &[u8], limit: usize) -> Result<Vec<(Vec<u8>, Vec<u8>)>, StorageError> {
+        let db = self.db.read().await;
+        let iter = db.iter(ReadOptions::new()).reverse();
+        // Seeking lands on the first key >= `end` (yielded first, then skipped), or nowhere when
+        // every key is below `end`, in which case the walk starts from the last key
+        iter.seek(&BytesKey(end.to_vec()));
+        if !iter.valid() {
+            iter.seek_to_last();
+        }
+        let entries = iter
+            .skip_while(|(key, _)| key.0.as_slice() >= end)
+            .take_while(|(key, _)| key.0.as_slice() >= start)
+            .filter(|(key, _)| key.0 != KEY_FORMAT_KEY)
+            .take(limit)
+            .map(|(key, value)| (key.0, value))
+            .collect();
+        Ok(entries)
+    }
+
     /// Set a string key with u64 value
     pub async fn set(&self, key: &str, value: u64) -> Result<(), StorageError> {
         let key_bytes = key.as_bytes();
@@ -87,12 +216,23 @@ impl LedgerDB {
         }
     }
 
+    /// Sync everything written so far to disk; a synced write flushes the log behind all earlier writes
+    pub async fn flush(&self) -> Result<(), StorageError> {
+        let db = self.db.write().await;
+        let mut write_opts = WriteOptions::new();
+        write_opts.sync = true;
+        db.delete(write_opts, BytesKey(b"__flush__".to_vec()))?;
+        Ok(())
+    }
+
     /// Delete key
     pub async fn delete(&self, key: &[u8]) -> Result<(), StorageError> {
+        if self.legacy_keys {
+            return self.delete_batch(&[key.to_vec()]).await;
+        }
         let db = self.db.write().await;
         let write_opts = WriteOptions::new();
-        let key_i32 = Self::bytes_to_key(key);
-        db.delete(write_opts, key_i32)?;
+        db.delete(write_opts, BytesKey(key.to_vec()))?;
         Ok(())
     }
 
@@ -213,4 +353,112 @@ impl LedgerDB {
             None => Ok(0),
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_values_written_under_hashed_keys_stay_readable() {
+        let dir = tempfile::tempdir().unwrap();
+        let address = "fvc000000000000000000000000000000000a11emyl";
+        // A database as written before full byte keys
+        {
+            let mut opts = Options::new();
+            opts.create_if_missing = true;
+            let old: Database<i32> = Database::open(dir.path(), opts).unwrap();
+            let hashed = |key: &[u8]| i32::from_u8(&legacy_key(key).0);
+            old.put(WriteOptions::new(), hashed(address.as_bytes()), &750u64.to_le_bytes()).unwrap();
+            old.put(WriteOptions::new(), hashed(b"block_height"), &12u64.to_le_bytes()).unwrap();
+        }
+
+        let db = LedgerDB::open(dir.path()).unwrap();
+        assert_eq!(db.get_balance(address).await.unwrap(), 750);
+        assert_eq!(db.get_u64("block_height").await.unwrap(), Some(12));
+
+        // New writes shadow the hashed entry, and deleting removes both
+        db.set_balance(address, 800).await.unwrap();
+        assert_eq!(db.get_balance(address).await.unwrap(), 800);
+        db.delete(address.as_bytes()).await.unwrap();
+        assert_eq!(db.get(address.as_bytes()).await.unwrap(), None);
+
+        // The fallback survives reopening, and the marker never shows up in scans
+        drop(db);
+        let db = LedgerDB::open(dir.path()).unwrap();
+        assert_eq!(db.get_u64("block_height").await.unwrap(), Some(12));
+        assert!(db.scan_prefix(b"__", usize::MAX).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_new_database_ignores_hashed_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = LedgerDB::open(dir.path()).unwrap();
+        db.put(&legacy_key(b"block_height").0, &12u64.to_le_bytes()).await.unwrap();
+
+        assert_eq!(db.get(b"block_height").await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_distinct_keys_never_collide() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = LedgerDB::open(dir.path()).unwrap();
+
+        for i in 0..100_000u64 {
+            db.put(format!("key:{}", i).as_bytes(), &i.to_le_bytes()).await.unwrap();
+        }
+        for i in 0..100_000u64 {
+            assert_eq!(db.get_u64(&format!("key:{}", i)).await.unwrap(), Some(i));
+        }
+        for i in 0..100_000u64 {
+            assert_eq!(db.get(format!("absent:{}", i).as_bytes()).await.unwrap(), None);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_scan_prefix_returns_keys_in_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = LedgerDB::open(dir.path()).unwrap();
+
+        for height in (0..=50u64).rev() {
+            db.put(&ordered_key("block:", height), &height.to_le_bytes()).await.unwrap();
+        }
+        // Neighbours on either side of the prefix
+        db.put(b"bloc", b"x").await.unwrap();
+        db.put(b"block;0", b"x").await.unwrap();
+        db.put(b"blocks", b"x").await.unwrap();
+
+        let entries = db.scan_prefix(b"block:", usize::MAX).await.unwrap();
+        let expected: Vec<Vec<u8>> = (0..=50u64).map(|h| ordered_key("block:", h)).collect();
+        assert_eq!(entries.iter().map(|(k, _)| k.clone()).collect::<Vec<_>>(), expected);
+        for (height, (_, value)) in entries.iter().enumerate() {
+            assert_eq!(value, &(height as u64).to_le_bytes().to_vec());
+        }
+        assert_eq!(db.scan_prefix(b"block:", 5).await.unwrap().len(), 5);
+        assert!(db.scan_prefix(b"missing:", usize::MAX).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_range_scans_respect_numeric_order_and_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let db = LedgerDB::open(dir.path()).unwrap();
+        for height in [1u64, 9, 10, 11, 100] {
+            db.put(&ordered_key("block:", height), b"x").await.unwrap();
+        }
+        db.put(b"blocks", b"x").await.unwrap();
+
+        let heights = |entries: Vec<(Vec<u8>, Vec<u8>)>| -> Vec<u64> {
+            entries.iter().map(|(k, _)| std::str::from_utf8(&k[6..]).unwrap().parse().unwrap()).collect()
+        };
+        let (start, end) = (ordered_key("block:", 9), ordered_key("block:", 100));
+        assert_eq!(heights(db.scan_range(&start, &end, usize::MAX).await.unwrap()), vec![9, 10, 11]);
+        assert_eq!(heights(db.scan_range(&start, &end, 2).await.unwrap()), vec![9, 10]);
+        assert_eq!(heights(db.scan_range_rev(&start, &end, 2).await.unwrap()), vec![11, 10]);
+        // An exclusive end that is not itself stored
+        let end = ordered_key("block:", 50);
+        assert_eq!(heights(db.scan_range_rev(&ordered_key("block:", 0), &end, 10).await.unwrap()), vec![11, 10, 9, 1]);
+
+        db.delete_batch(&[ordered_key("block:", 9), ordered_key("block:", 10)]).await.unwrap();
+        assert_eq!(heights(db.scan_range(&start, &end, 10).await.unwrap()), vec![11]);
+    }
+}
@@ -0,0 +1,573 @@
+use axum::{http::StatusCode, response::{IntoResponse, Json, Response}};
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tokio::sync::RwLock;
+use crate::consensus::vortex_consensus::Transaction;
+use crate::fee_estimate::validate_fee;
+use crate::rpc_storage::{sender_fee, RPCStorage, WalletTransaction};
+pub use crate::rpc_storage::transfer_content_hash;
+use crate::shared::{spend_day, validate_transfer, TxError, TRANSFER_LIMITS};
+use crate::storage::StorageError;
+use crate::wallet::key_manager::KeyManager;
+
+/// Held shared by every submission in progress; shutdown takes it exclusively to wait them out
+static IN_FLIGHT: Lazy<RwLock<()>> = Lazy::new(|| RwLock::new(()));
+
+/// Outcome of a send request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SubmissionStatus {
+    /// Newly stored and applied (201)
+    Accepted,
+    /// Same content hash already stored; nothing was applied again (200)
+    Duplicate,
+    /// Not applied (400, or 500 for a server-side failure)
+    Rejected,
+}
+
+/// Canonical result returned by every send endpoint
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubmissionResult {
+    pub status: SubmissionStatus,
+    pub transaction_hash: Option<String>,
+    pub reason: Option<String>,
+    http_status: StatusCode,
+}
+
+impl SubmissionResult {
+    pub fn accepted(hash: String) -> Self {
+        Self { status: SubmissionStatus::Accepted, transaction_hash: Some(hash), reason: None, http_status: StatusCode::CREATED }
+    }
+
+    pub fn duplicate(hash: String) -> Self {
+        Self { status: SubmissionStatus::Duplicate, transaction_hash: Some(hash), reason: None, http_status: StatusCode::OK }
+    }
+
+    pub fn rejected(hash: Option<String>, reason: impl Into<String>) -> Self {
+        Self { status: SubmissionStatus::Rejected, transaction_hash: hash, reason: Some(reason.into()), http_status: StatusCode::BAD_REQUEST }
+    }
+
+    /// Rejected because the node failed, not because of the request
+    pub fn failed(hash: Option<String>, reason: impl Into<String>) -> Self {
+        Self { http_status: StatusCode::INTERNAL_SERVER_ERROR, ..Self::rejected(hash, reason) }
+    }
+
+    pub fn http_status(&self) -> StatusCode {
+        self.http_status
+    }
+
+    pub fn to_json(&self) -> Value {
+        let rejected = self.status == SubmissionStatus::Rejected;
+        json!({
+            "success": !rejected,
+            "status": self.status,
+            "transaction_hash": self.transaction_hash,
+            "reason": self.reason,
+            "error": if rejected { self.reason.as_deref() } else { None }
+        })
+    }
+}
+
+impl IntoResponse for SubmissionResult {
+    fn into_response(self) -> Response {
+        (self.http_status, Json(self.to_json())).into_response()
+    }
+}
+
+/// Validated transfer paying the default fee, identified by its content hash
+pub fn build_transfer(
+    transaction_type: &str,
+    from: String,
+    to: String,
+    amount: u64,
+    nonce: u64,
+) -> Result<WalletTransaction, TxError> {
+    build_transfer_with_fee(transaction_type, from, to, amount, nonce, None)
+}
+
+/// As `build_transfer`, paying `fee` instead of the default when given; the fee is part of the content hash
+pub fn build_transfer_with_fee(
+    transaction_type: &str,
+    from: String,
+    to: String,
+    amount: u64,
+    nonce: u64,
+    fee: Option<u64>,
+) -> Result<WalletTransaction, TxError> {
+    let mut tx = WalletTransaction::new_transfer(from, to, amount, String::new(), 1);
+    tx.transaction_type = transaction_type.to_string();
+    tx.nonce = nonce;
+    tx.fee = fee.unwrap_or_default();
+    let hash = transfer_content_hash(transaction_type, &tx.from, &tx.to, amount, nonce, sender_fee(&tx));
+    validate_transfer(&tx.from, &tx.to, amount, &hash)?;
+    tx.hash = hash;
+    Ok(tx)
+}
+
+/// The key `private_key` holds, provided it derives the `from` address
+pub fn authorize_sender(from: &str, private_key: &str) -> Result<KeyManager, TxError> {
+    if private_key.trim().is_empty() {
+        return Err(TxError::Unsigned);
+    }
+    let invalid = || TxError::InvalidSignature { address: from.to_string() };
+    let key = KeyManager::from_private_key_hex(private_key.trim()).map_err(|_| invalid())?;
+    if key.get_address() != from {
+        return Err(invalid());
+    }
+    Ok(key)
+}
+
+/// `tx` as a consensus transaction signed with the sender's key, for gossip to peers.
+/// It keeps the transfer's content hash so a block including the transfer clears it from every mempool.
+pub fn gossip_transaction(tx: &WalletTransaction, private_key: &str) -> Result<Transaction, TxError> {
+    let key = authorize_sender(&tx.from, private_key)?;
+    let invalid = || TxError::InvalidSignature { address: tx.from.clone() };
+    let mut hash = [0u8; 32];
+    hex::decode_to_slice(tx.hash.trim_start_matches("0x"), &mut hash).map_err(|_| invalid())?;
+    let mut gossip = Transaction {
+        hash,
+        from: key.get_public_key(),
+        to: tx.to.clone().into_bytes(),
+        amount: tx.amount,
+        nonce: tx.nonce,
+        signature: Vec::new(),
+        vortex_fee: sender_fee(tx) as f64,
+    };
+    gossip.signature = key.sign(&gossip.signing_payload()).map_err(|_| invalid())?;
+    Ok(gossip)
+}
+
+/// Build and submit a transfer. Without a client nonce the sender's next nonce is assigned,
+/// so only submissions carrying their own nonce are retry-safe.
+pub async fn submit_new_transfer(
+    transaction_type: &str,
+    from: String,
+    to: String,
+    amount: u64,
+    nonce: Option<u64>,
+) -> (Option<WalletTransaction>, SubmissionResult) {
+    submit_new_transfer_with_fee(transaction_type, from, to, amount, nonce, None).await
+}
+
+/// As `submit_new_transfer`, paying `fee` instead of the default when given; it must meet MIN_TRANSFER_FEE
+pub async fn submit_new_transfer_with_fee(
+    transaction_type: &str,
+    from: String,
+    to: String,
+    amount: u64,
+    nonce: Option<u64>,
+    fee: Option<u64>,
+) -> (Option<WalletTransaction>, SubmissionResult) {
+    submit_new(transaction_type, from, to, amount, nonce, fee, None).await
+}
+
+/// As `submit_new_transfer_with_fee`, also signing the transfer with the sender's `private_key`
+/// so it enters the persisted mempool with its settlement and stays listed there until mined.
+/// The stored transfer carries that signature, as a block would.
+pub async fn submit_new_signed_transfer(
+    transaction_type: &str,
+    from: String,
+    to: String,
+    amount: u64,
+    nonce: Option<u64>,
+    fee: Option<u64>,
+    private_key: &str,
+) -> (Option<WalletTransaction>, SubmissionResult) {
+    submit_new(transaction_type, from, to, amount, nonce, fee, Some(private_key)).await
+}
+
+async fn submit_new(
+    transaction_type: &str,
+    from: String,
+    to: String,
+    amount: u64,
+    nonce: Option<u64>,
+    fee: Option<u64>,
+    private_key: Option<&str>,
+) -> (Option<WalletTransaction>, SubmissionResult) {
+    if let Some(fee) = fee {
+        if let Err(e) = validate_fee(fee) {
+            return (None, SubmissionResult::rejected(None, e.to_string()));
+        }
+    }
+    let _in_flight = IN_FLIGHT.read().await;
+    // Sender and recipient stay locked from nonce assignment until the transfer is written
+    let _guards = RPCStorage::lock_transfer(&from, &to).await;
+    let nonce = match nonce {
+        Some(nonce) => nonce,
+        None => match RPCStorage::get_account_nonce(&from).await {
+            Ok(current) => current + 1,
+            Err(e) => return (None, SubmissionResult::failed(None, format!("Failed to read nonce: {}", e))),
+        },
+    };
+
+    match build_transfer_with_fee(transaction_type, from, to, amount, nonce, fee) {
+        Ok(mut tx) => {
+            // Signed as built, under the nonce it is stored with
+            let pending = match private_key.map(|key| gossip_transaction(&tx, key)).transpose() {
+                Ok(pending) => pending,
+                Err(e) => return (None, SubmissionResult::rejected(Some(tx.hash), e.to_string())),
+            };
+            if let Some(gossip) = &pending {
+                tx.signature = Some([gossip.from.as_slice(), gossip.signature.as_slice()].concat());
+            }
+            let result = submit_locked(&tx, pending.as_ref()).await;
+            (Some(tx), result)
+        }
+        Err(e) => (None, SubmissionResult::rejected(None, e.to_string())),
+    }
+}
+
+/// Store `tx` and move its amount plus fee. A transaction already stored under the same hash is a
+/// duplicate; otherwise its nonce must be exactly one past the sender's last accepted nonce.
+pub async fn submit_transfer(tx: &WalletTransaction) -> SubmissionResult {
+    let _in_flight = IN_FLIGHT.read().await;
+    let _guards = RPCStorage::lock_transfer(&tx.from, &tx.to).await;
+    submit_locked(tx, None).await
+}
+
+/// Wait until no transfer is being submitted
+pub async fn wait_for_in_flight() {
+    let _guard = IN_FLIGHT.write().await;
+}
+
+/// Check and record `tx`; the caller holds its sender's and recipient's locks from `RPCStorage::lock_transfer`,
+/// so a concurrent retry or a second send from the same address sees this one's outcome
+async fn submit_locked(tx: &WalletTransaction, pending: Option<&Transaction>) -> SubmissionResult {
+    let hash = tx.hash.clone();
+    match RPCStorage::get_transaction(&hash).await {
+        Ok(Some(_)) => return SubmissionResult::duplicate(hash),
+        Ok(None) => {}
+        Err(e) => return SubmissionResult::failed(Some(hash), format!("Failed to check for duplicates: {}", e)),
+    }
+
+    match RPCStorage::get_account_nonce(&tx.from).await {
+        Ok(current) if tx.nonce != current.saturating_add(1) => {
+            return SubmissionResult::rejected(Some(hash), "invalid nonce");
+        }
+        Ok(_) => {}
+        Err(e) => return SubmissionResult::failed(Some(hash), format!("Failed to read nonce: {}", e)),
+    }
+
+    // Immature mining rewards cannot be spent yet
+    let fee = sender_fee(tx);
+    let Some(total_required) = tx.amount.checked_add(fee) else {
+        return SubmissionResult::rejected(Some(hash), format!("Amount {} plus fee {} overflows", tx.amount, fee));
+    };
+    match RPCStorage::get_balance_breakdown(&tx.from).await {
+        Ok(breakdown) if breakdown.spendable_balance < total_required => {
+            return SubmissionResult::rejected(Some(hash), format!(
+                "Insufficient balance. Required: {} (including {} fee), Available: {}",
+                total_required, fee, breakdown.spendable_balance
+            ));
+        }
+        Ok(_) => {}
+        Err(e) => return SubmissionResult::failed(Some(hash), format!("Failed to check balance: {}", e)),
+    }
+    match RPCStorage::get_balance(&tx.to).await {
+        Ok(balance) if balance.checked_add(tx.amount).is_none() => {
+            return SubmissionResult::rejected(Some(hash), format!(
+                "Transfer of {} would overflow the recipient's balance of {}",
+                tx.amount, balance
+            ));
+        }
+        Ok(_) => {}
+        Err(e) => return SubmissionResult::failed(Some(hash), format!("Failed to check balance: {}", e)),
+    }
+
+    // The day's total is stored with each applied transfer, so the cap survives a restart
+    let today = spend_day(chrono::Utc::now().timestamp() as u64);
+    match RPCStorage::get_daily_spend(&tx.from, today).await {
+        Ok(spent) => {
+            if let Err(e) = TRANSFER_LIMITS.check_daily(&tx.from, spent, tx.amount) {
+                return SubmissionResult::rejected(Some(hash), e.to_string());
+            }
+        }
+        Err(e) => return SubmissionResult::failed(Some(hash), format!("Failed to read daily spend: {}", e)),
+    }
+
+    // The transfer settles now, so it belongs to history from the next block on
+    let settle_height = match RPCStorage::next_block_height().await {
+        Ok(height) => height,
+        Err(e) => return SubmissionResult::failed(Some(hash), format!("Failed to read block height: {}", e)),
+    };
+    let tx = &WalletTransaction { block_height: settle_height, ..tx.clone() };
+
+    // Balances, the transaction record, the nonce bump and the mempool record land in one write
+    match RPCStorage::record_transfer_locked(tx, fee, pending).await {
+        Ok(_) => {}
+        Err(e @ (StorageError::BalanceUnderflow { .. } | StorageError::BalanceOverflow { .. })) => {
+            return SubmissionResult::rejected(Some(hash), e.to_string());
+        }
+        Err(e) => {
+            log::error!("Failed to apply transfer {}: {}", hash, e);
+            return SubmissionResult::failed(Some(hash), "Failed to apply transfer");
+        }
+    }
+    SubmissionResult::accepted(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rpc_storage::use_test_db;
+    use crate::consensus::vortex_consensus::Mempool;
+    use crate::fee_estimate::MIN_TRANSFER_FEE;
+
+    const SENDER: &str = "fvc00000000000000000000000000000000b001emyl";
+    const RECIPIENT: &str = "fvc00000000000000000000000000000000b002emyl";
+
+    #[tokio::test]
+    async fn test_submit_accepted_then_duplicate_then_rejected() {
+        let _db = use_test_db();
+        RPCStorage::set_balance(SENDER, 10_000).await.unwrap();
+
+        let tx = build_transfer("transfer", SENDER.to_string(), RECIPIENT.to_string(), 4_000, 1).unwrap();
+        let first = submit_transfer(&tx).await;
+        assert_eq!(first.status, SubmissionStatus::Accepted);
+        assert_eq!(first.http_status(), StatusCode::CREATED);
+        assert_eq!(first.transaction_hash.as_deref(), Some(tx.hash.as_str()));
+
+        // A network retry rebuilds the same content hash and moves nothing
+        let retry_tx = build_transfer("transfer", SENDER.to_string(), RECIPIENT.to_string(), 4_000, 1).unwrap();
+        let retry = submit_transfer(&retry_tx).await;
+        assert_eq!(retry.status, SubmissionStatus::Duplicate);
+        assert_eq!(retry.http_status(), StatusCode::OK);
+        assert_eq!(retry.transaction_hash, first.transaction_hash);
+        assert_eq!(RPCStorage::get_balance(SENDER).await.unwrap(), 10_000 - 4_000 - sender_fee(&tx));
+        assert_eq!(RPCStorage::get_balance(RECIPIENT).await.unwrap(), 4_000);
+
+        let overdraw = build_transfer("transfer", SENDER.to_string(), RECIPIENT.to_string(), 9_000, 2).unwrap();
+        let rejected = submit_transfer(&overdraw).await;
+        assert_eq!(rejected.status, SubmissionStatus::Rejected);
+        assert_eq!(rejected.http_status(), StatusCode::BAD_REQUEST);
+        assert_eq!(rejected.to_json()["status"], "rejected");
+        assert!(rejected.reason.unwrap().contains("Insufficient balance"));
+    }
+
+    #[tokio::test]
+    async fn test_overflowing_transfers_are_rejected() {
+        let _db = use_test_db();
+        let sender = "fvc00000000000000000000000000000000b00aemyl";
+        let recipient = "fvc00000000000000000000000000000000b00bemyl";
+        RPCStorage::set_balance(sender, u64::MAX).await.unwrap();
+
+        // u64::MAX + fee would wrap to a total the balance covers
+        let tx = build_transfer("transfer", sender.to_string(), recipient.to_string(), u64::MAX, 1).unwrap();
+        let result = submit_transfer(&tx).await;
+        assert_eq!(result.status, SubmissionStatus::Rejected);
+        assert_eq!(result.http_status(), StatusCode::BAD_REQUEST);
+        assert!(result.reason.unwrap().contains("overflows"));
+        assert_eq!(RPCStorage::get_balance(sender).await.unwrap(), u64::MAX);
+        assert_eq!(RPCStorage::get_balance(recipient).await.unwrap(), 0);
+        assert!(RPCStorage::get_transaction(&tx.hash).await.unwrap().is_none());
+
+        // A credit that would wrap the recipient's balance
+        RPCStorage::set_balance(recipient, u64::MAX - 10).await.unwrap();
+        let tx = build_transfer("transfer", sender.to_string(), recipient.to_string(), 100, 1).unwrap();
+        let result = submit_transfer(&tx).await;
+        assert_eq!(result.status, SubmissionStatus::Rejected);
+        assert!(result.reason.unwrap().contains("overflow the recipient"));
+        assert_eq!(RPCStorage::get_balance(recipient).await.unwrap(), u64::MAX - 10);
+        assert_eq!(RPCStorage::get_account_nonce(sender).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_back_to_back_sends_get_distinct_hashes() {
+        let _db = use_test_db();
+        let sender = "fvc00000000000000000000000000000000b005emyl";
+        let recipient = "fvc00000000000000000000000000000000b006emyl";
+        RPCStorage::set_balance(sender, 100_000).await.unwrap();
+
+        let mut hashes = Vec::new();
+        for _ in 0..2 {
+            let (tx, result) = submit_new_transfer("transfer", sender.to_string(), recipient.to_string(), 500, None).await;
+            assert_eq!(result.status, SubmissionStatus::Accepted);
+            let hash = tx.unwrap().hash;
+            assert_eq!(hash.len(), 2 + 64);
+            hashes.push(hash);
+        }
+
+        assert_ne!(hashes[0], hashes[1]);
+        for hash in &hashes {
+            let stored = RPCStorage::get_transaction(hash).await.unwrap().unwrap();
+            assert_eq!((stored.from.as_str(), stored.amount), (sender, 500));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_replayed_and_skipped_nonces_rejected() {
+        let _db = use_test_db();
+        let sender = "fvc00000000000000000000000000000000b007emyl";
+        let recipient = "fvc00000000000000000000000000000000b008emyl";
+        RPCStorage::set_balance(sender, 100_000).await.unwrap();
+
+        let first = build_transfer("transfer", sender.to_string(), recipient.to_string(), 1_000, 1).unwrap();
+        assert_eq!(submit_transfer(&first).await.status, SubmissionStatus::Accepted);
+        assert_eq!(RPCStorage::get_account_nonce(sender).await.unwrap(), 1);
+
+        // Replay: nonce 1 again with different content
+        let replay = build_transfer("transfer", sender.to_string(), recipient.to_string(), 2_000, 1).unwrap();
+        let result = submit_transfer(&replay).await;
+        assert_eq!(result.status, SubmissionStatus::Rejected);
+        assert_eq!(result.to_json()["success"], false);
+        assert_eq!(result.to_json()["error"], "invalid nonce");
+
+        // Gap: nonce 3 before 2
+        let gap = build_transfer("transfer", sender.to_string(), recipient.to_string(), 1_000, 3).unwrap();
+        assert_eq!(submit_transfer(&gap).await.reason.as_deref(), Some("invalid nonce"));
+
+        assert_eq!(RPCStorage::get_account_nonce(sender).await.unwrap(), 1);
+        assert_eq!(RPCStorage::get_balance(recipient).await.unwrap(), 1_000);
+    }
+
+    #[test]
+    fn test_fee_is_part_of_the_content_hash() {
+        let build = |fee| build_transfer_with_fee("transfer", SENDER.to_string(), RECIPIENT.to_string(), 1_000, 1, fee).unwrap();
+        assert_eq!(build(None).hash, build(Some(MIN_TRANSFER_FEE)).hash);
+        assert_ne!(build(None).hash, build(Some(MIN_TRANSFER_FEE * 2)).hash);
+    }
+
+    #[test]
+    fn test_higher_fee_transfer_leaves_mempool_first() {
+        let sender = KeyManager::new();
+        let key = hex::encode(sender.get_private_key());
+        let to = "fvc00000000000000000000000000000000b00eemyl";
+        let standard = build_transfer("transfer", sender.get_address(), to.to_string(), 1_000, 1).unwrap();
+        let priority = build_transfer_with_fee("transfer", sender.get_address(), to.to_string(), 1_000, 2, Some(MIN_TRANSFER_FEE * 3)).unwrap();
+
+        let mut mempool = Mempool::new();
+        for tx in [&standard, &priority] {
+            let gossip = gossip_transaction(tx, &key).unwrap();
+            assert!(gossip.verify_signature());
+            mempool.add(gossip);
+        }
+        let order: Vec<String> = mempool.take_top(2).iter().map(|tx| format!("0x{}", hex::encode(tx.hash))).collect();
+        assert_eq!(order, vec![priority.hash, standard.hash]);
+    }
+
+    #[tokio::test]
+    async fn test_balance_must_cover_amount_plus_chosen_fee() {
+        let _db = use_test_db();
+        let sender = "fvc00000000000000000000000000000000b01cemyl";
+        let recipient = "fvc00000000000000000000000000000000b01demyl";
+        RPCStorage::set_balance(sender, 1_000 + MIN_TRANSFER_FEE).await.unwrap();
+
+        let (_, result) = submit_new_transfer_with_fee("transfer", sender.to_string(), recipient.to_string(), 1_000, Some(1), Some(MIN_TRANSFER_FEE * 2)).await;
+        assert_eq!(result.status, SubmissionStatus::Rejected);
+        assert!(result.reason.unwrap().contains("Insufficient balance"));
+        assert_eq!(RPCStorage::get_balance(sender).await.unwrap(), 1_000 + MIN_TRANSFER_FEE);
+
+        let (_, result) = submit_new_transfer_with_fee("transfer", sender.to_string(), recipient.to_string(), 1_000, Some(1), Some(MIN_TRANSFER_FEE)).await;
+        assert_eq!(result.status, SubmissionStatus::Accepted);
+        assert_eq!(RPCStorage::get_balance(sender).await.unwrap(), 0);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn test_concurrent_sends_never_overdraw() {
+        let _db = use_test_db();
+        let sender = "fvc00000000000000000000000000000000b003emyl";
+        let recipient = "fvc00000000000000000000000000000000b004emyl";
+        let cost = 1_000 + sender_fee(&build_transfer("transfer", sender.to_string(), recipient.to_string(), 1_000, 1).unwrap());
+        RPCStorage::set_balance(sender, cost * 10).await.unwrap();
+
+        let handles: Vec<_> = (0..50)
+            .map(|_| tokio::spawn(async move {
+                submit_new_transfer("transfer", sender.to_string(), recipient.to_string(), 1_000, None).await.1.status
+            }))
+            .collect();
+        let mut accepted = 0;
+        for handle in handles {
+            if handle.await.unwrap() == SubmissionStatus::Accepted {
+                accepted += 1;
+            }
+        }
+
+        assert_eq!(accepted, 10);
+        assert_eq!(RPCStorage::get_balance(sender).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_sends_wait_only_for_their_own_addresses() {
+        let _db = use_test_db();
+        let (busy, idle) = ("fvc00000000000000000000000000000000b253emyl", "fvc00000000000000000000000000000000b254emyl");
+        let recipient = "fvc00000000000000000000000000000000b255emyl";
+        RPCStorage::set_balance(busy, 100_000).await.unwrap();
+        RPCStorage::set_balance(idle, 100_000).await.unwrap();
+
+        let held = RPCStorage::lock_transfer(busy, busy).await;
+        let wait = std::time::Duration::from_millis(200);
+        let send = |from: &'static str| submit_new_transfer("transfer", from.to_string(), recipient.to_string(), 1_000, None);
+        assert!(tokio::time::timeout(wait, send(busy)).await.is_err());
+        let (_, result) = tokio::time::timeout(wait, send(idle)).await.unwrap();
+        assert_eq!(result.status, SubmissionStatus::Accepted);
+
+        drop(held);
+        assert_eq!(send(busy).await.1.status, SubmissionStatus::Accepted);
+        assert_eq!(RPCStorage::get_balance(recipient).await.unwrap(), 2_000);
+    }
+
+    #[tokio::test]
+    async fn test_fee_override_checked_against_minimum() {
+        let _db = use_test_db();
+        let sender = "fvc00000000000000000000000000000000b00cemyl";
+        let recipient = "fvc00000000000000000000000000000000b00demyl";
+        RPCStorage::set_balance(sender, 100_000).await.unwrap();
+
+        let (tx, result) = submit_new_transfer_with_fee("transfer", sender.to_string(), recipient.to_string(), 1_000, Some(1), Some(1)).await;
+        assert!(tx.is_none());
+        assert_eq!(result.status, SubmissionStatus::Rejected);
+        assert!(result.reason.unwrap().contains("below the minimum"));
+        assert_eq!(RPCStorage::get_account_nonce(sender).await.unwrap(), 0);
+
+        let (tx, result) = submit_new_transfer_with_fee("transfer", sender.to_string(), recipient.to_string(), 1_000, Some(1), Some(5_000)).await;
+        assert_eq!(result.status, SubmissionStatus::Accepted);
+        assert_eq!(tx.unwrap().fee, 5_000);
+        assert_eq!(RPCStorage::get_balance(sender).await.unwrap(), 100_000 - 1_000 - 5_000);
+    }
+
+    #[tokio::test]
+    async fn test_signed_transfer_is_listed_in_mempool_until_mined() {
+        use crate::rpc_storage::MempoolEntry;
+        let _db = use_test_db();
+        let sender = KeyManager::new();
+        let from = sender.get_address();
+        let to = "fvc00000000000000000000000000000000b288emyl";
+        let key = hex::encode(sender.get_private_key());
+        RPCStorage::set_balance(&from, 100_000).await.unwrap();
+
+        let mut submitted = Vec::new();
+        for amount in [1_000, 2_000] {
+            let (tx, result) = submit_new_signed_transfer("transfer", from.clone(), to.to_string(), amount, None, None, &key).await;
+            assert_eq!(result.status, SubmissionStatus::Accepted);
+            submitted.push(tx.unwrap());
+        }
+        // Another key cannot put a transfer from this address into the mempool
+        let intruder = hex::encode(KeyManager::new().get_private_key());
+        let (_, result) = submit_new_signed_transfer("transfer", from.clone(), to.to_string(), 500, None, None, &intruder).await;
+        assert_eq!(result.status, SubmissionStatus::Rejected);
+
+        let ours = |entries: Vec<MempoolEntry>| -> Vec<MempoolEntry> {
+            let mut ours: Vec<MempoolEntry> = entries.into_iter().filter(|e| e.from == from).collect();
+            ours.sort_by_key(|e| e.nonce);
+            ours
+        };
+        let later = chrono::Utc::now().timestamp() as u64 + 10;
+        let listed = ours(RPCStorage::get_mempool(later).await.unwrap());
+        assert_eq!(listed.len(), 2);
+        for (entry, tx) in listed.iter().zip(&submitted) {
+            assert_eq!(entry.hash, tx.hash);
+            assert_eq!((entry.to.as_str(), entry.amount, entry.nonce), (to, tx.amount, tx.nonce));
+            assert_eq!(entry.fee, sender_fee(tx) as f64);
+            assert!(entry.age_secs >= 10);
+        }
+
+        // Mining the first takes it out; the second stays pending
+        let mut mined = [0u8; 32];
+        hex::decode_to_slice(submitted[0].hash.trim_start_matches("0x"), &mut mined).unwrap();
+        RPCStorage::remove_pending_transactions(&[mined]).await.unwrap();
+        let listed = ours(RPCStorage::get_mempool(later).await.unwrap());
+        assert_eq!(listed.iter().map(|e| e.hash.as_str()).collect::<Vec<_>>(), vec![submitted[1].hash.as_str()]);
+    }
+}
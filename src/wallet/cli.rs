@@ -1,5 +1,6 @@
 use clap::{Parser, Subcommand};
 use crate::wallet::Wallet;
+use crate::wallet::MnemonicScheme;
 use std::path::PathBuf;
 
 #[derive(Parser)]
@@ -86,6 +87,16 @@ pub enum Commands {
     Import {
         path: PathBuf,
     },
+    
+    /// Restore a wallet from its 24-word recovery phrase
+    ImportMnemonic {
+        phrase: String,
+
+        /// Use the derivation of wallets restored before phrases encoded the key, without
+        /// asking the node which one holds funds
+        #[arg(long)]
+        legacy: bool,
+    },
 }
 
 impl Cli {
@@ -93,7 +104,7 @@ impl Cli {
         match &self.command {
             Commands::Create { save_to, mnemonic } => {
                 let wallet = if let Some(mnemonic) = mnemonic {
-                    Wallet::from_mnemonic(mnemonic, &self.rpc_url)
+                    Wallet::restore_from_mnemonic(mnemonic, &self.rpc_url).await?
                 } else {
                     Wallet::new(&self.rpc_url)
                 };
@@ -108,8 +119,7 @@ impl Cli {
                 }
                 
                 if mnemonic.is_none() {
-                    let mnemonic = Wallet::generate_mnemonic();
-                    println!("Mnemonic: {}", mnemonic);
+                    println!("Mnemonic: {}", wallet.to_mnemonic());
                     println!("⚠️  Save this mnemonic securely!");
                 }
             }
@@ -236,6 +246,22 @@ impl Cli {
                 println!("Address: {}", wallet.get_address());
                 println!("Balance: {} FVC", wallet.get_balance().await?);
             }
+            
+            Commands::ImportMnemonic { phrase, legacy } => {
+                let wallet = if *legacy {
+                    Wallet::from_mnemonic_with(phrase, MnemonicScheme::LegacySeed, &self.rpc_url)?
+                } else {
+                    Wallet::restore_from_mnemonic(phrase, &self.rpc_url).await?
+                };
+                
+                if let Some(save_path) = &self.wallet_file {
+                    wallet.save_to_file(save_path.to_str().unwrap())?;
+                    println!("Wallet saved to: {:?}", save_path);
+                }
+                
+                println!("Wallet restored successfully!");
+                println!("Address: {}", wallet.get_address());
+            }
         }
         
         Ok(())
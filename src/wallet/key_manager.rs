@@ -1,18 +1,89 @@
 use serde::{Serialize, Deserialize};
 use std::fs;
 use sha3::{Sha3_256, Digest};
-use secp256k1::{Secp256k1, SecretKey, PublicKey, Message, ecdsa::Signature};
+use sha2::Sha512;
+use hmac::{Hmac, Mac};
+use secp256k1::{Secp256k1, SecretKey, PublicKey, Message, Scalar, ecdsa::Signature};
 use bip39::Mnemonic;
 use rand::rngs::OsRng;
 use rand::RngCore;
+use thiserror::Error;
 use crate::crypto::fractal_hash::FractalHasher;
 
+/// Address prefix for native FVChain addresses
+pub const ADDRESS_PREFIX: &str = "fvc";
+/// Address suffix for native FVChain addresses
+pub const ADDRESS_SUFFIX: &str = "emyl";
+/// Bytes of address body hashed into the checksum
+pub const ADDRESS_BODY_LEN: usize = 16;
+/// Trailing checksum bytes before the suffix
+pub const ADDRESS_CHECKSUM_LEN: usize = 2;
+/// Full address length: prefix + 36 hex + suffix
+pub const ADDRESS_LEN: usize = 3 + 2 * (ADDRESS_BODY_LEN + ADDRESS_CHECKSUM_LEN) + 4;
+
+/// HMAC key for the master key derived from a seed
+const HD_SEED_KEY: &[u8] = b"FVChain seed";
+/// Child indices at or above this are hardened (derived from the private key)
+pub const HARDENED_OFFSET: u32 = 0x8000_0000;
+
+/// HMAC-SHA512 as used by BIP32 derivation
+fn hmac_sha512(key: &[u8], data: &[u8]) -> [u8; 64] {
+    let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(data);
+    let mut out = [0u8; 64];
+    out.copy_from_slice(&mac.finalize().into_bytes());
+    out
+}
+
+/// Why an address failed validation
+#[derive(Debug, Error, PartialEq)]
+pub enum AddressError {
+    #[error("Address must start with 'fvc'")]
+    WrongPrefix,
+    #[error("Address must end with 'emyl'")]
+    WrongSuffix,
+    #[error("Address must be 43 characters, got {0}")]
+    WrongLength(usize),
+    #[error("Address must be hexadecimal between 'fvc' and 'emyl'")]
+    NonHex,
+    #[error("Address checksum does not match; check for a typo")]
+    ChecksumMismatch,
+}
+
+/// Words in a recovery phrase: 256 bits of private key plus an 8-bit checksum
+pub const MNEMONIC_WORDS: usize = 24;
+
+/// How a recovery phrase maps to a private key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MnemonicScheme {
+    /// The phrase encodes the private key itself, as written by `to_mnemonic`
+    Entropy,
+    /// Phrases restored before `to_mnemonic` existed: SHA3-256 of the first half of the BIP39 seed
+    LegacySeed,
+}
+
+/// Why a wallet could not be restored
+#[derive(Debug, Error, PartialEq)]
+pub enum WalletError {
+    #[error("Invalid mnemonic: {0}")]
+    InvalidMnemonic(String),
+    #[error("Mnemonic checksum does not match; check the words for a typo")]
+    ChecksumMismatch,
+    #[error("Mnemonic must have 24 words, got {0}")]
+    WrongWordCount(usize),
+    #[error("Mnemonic does not encode a valid private key")]
+    InvalidPrivateKey,
+}
+
 /// Production-ready KeyManager with secp256k1 cryptography and 160-bit FVChain addresses
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KeyManager {
     private_key: Vec<u8>,
     public_key: Vec<u8>,
     address: String,
+    /// BIP32 chain code; set for keys created by `from_seed` or `derive_child`
+    #[serde(default)]
+    chain_code: Option<[u8; 32]>,
     #[serde(skip)]
     secp: Secp256k1<secp256k1::All>,
 }
@@ -40,12 +111,31 @@ impl KeyManager {
             private_key,
             public_key: public_key_bytes,
             address,
+            chain_code: None,
             secp,
         }
     }
     
-    /// Generate native FVChain address using Fractal-Vortex mathematics (NATIVE FORMAT: 42 chars)
+    /// Native FVChain address for a public key
+    pub fn address_from_public_key(public_key: &[u8]) -> String {
+        Self::generate_fvchain_address(public_key)
+    }
+    
+    /// Address `public_key` had before addresses carried a checksum: fvc + hex of all 18 derived bytes + emyl
+    pub fn legacy_address_from_public_key(public_key: &[u8]) -> String {
+        format!("{}{}{}", ADDRESS_PREFIX, hex::encode(Self::derive_address_bytes(public_key)), ADDRESS_SUFFIX)
+    }
+
+    /// Generate native FVChain address using Fractal-Vortex mathematics (NATIVE FORMAT: 43 chars)
     fn generate_fvchain_address(public_key: &[u8]) -> String {
+        let address_bytes = Self::derive_address_bytes(public_key);
+        let mut body = [0u8; ADDRESS_BODY_LEN];
+        body.copy_from_slice(&address_bytes[..ADDRESS_BODY_LEN]);
+        Self::address_with_checksum(&body)
+    }
+
+    /// The 18 address bytes derived from `public_key`; the current format keeps the first 16 as its body
+    fn derive_address_bytes(public_key: &[u8]) -> [u8; ADDRESS_BODY_LEN + ADDRESS_CHECKSUM_LEN] {
         // Apply Fractal-Vortex transformation to public key
         let mut fractal_hasher = FractalHasher::new(3); // 3 fractal iterations
         let vortex_hash = fractal_hasher.fractal_hash(public_key);
@@ -60,7 +150,7 @@ impl KeyManager {
         }
         
         // Generate 144-bit address using digital root mathematics (18 bytes = 36 hex chars)
-        let mut address_bytes = [0u8; 18]; // 144 bits = 18 bytes for native format
+        let mut address_bytes = [0u8; ADDRESS_BODY_LEN + ADDRESS_CHECKSUM_LEN];
         
         // Apply digital root transformation
         for i in 0..18 {
@@ -82,42 +172,109 @@ impl KeyManager {
         for (i, byte) in address_bytes.iter_mut().enumerate() {
             *byte ^= ((energy_signature >> (i % 8)) & 0xFF) as u8;
         }
-        
-        // Format as FVChain NATIVE address with "fvc" prefix and "emyl" suffix (43 chars total)
-        format!("fvc{}emyl", hex::encode(address_bytes))
+        address_bytes
+    }
+
+    fn address_checksum(body: &[u8]) -> [u8; ADDRESS_CHECKSUM_LEN] {
+        let hash = Sha3_256::digest(body);
+        let mut checksum = [0u8; ADDRESS_CHECKSUM_LEN];
+        checksum.copy_from_slice(&hash[..ADDRESS_CHECKSUM_LEN]);
+        checksum
+    }
+
+    /// Native address for a 16-byte body: fvc + hex(body) + hex(first 2 bytes of SHA3-256(body)) + emyl
+    pub fn address_with_checksum(body: &[u8; ADDRESS_BODY_LEN]) -> String {
+        format!(
+            "{}{}{}{}",
+            ADDRESS_PREFIX,
+            hex::encode(body),
+            hex::encode(Self::address_checksum(body)),
+            ADDRESS_SUFFIX
+        )
     }
     
-    /// Create KeyManager from mnemonic with full cryptographic security
-    pub fn from_mnemonic(mnemonic: &str) -> Self {
-        let mnemonic = Mnemonic::parse(mnemonic)
-            .expect("Invalid mnemonic");
-        let seed = mnemonic.to_seed("");
-        
+    fn from_secret_key(secret_key: SecretKey, chain_code: Option<[u8; 32]>) -> Self {
         let secp = Secp256k1::new();
-        
-        // Derive private key from seed using HMAC-SHA512
-        let mut hasher = Sha3_256::new();
-        hasher.update(&seed[..32]);
-        let hash = hasher.finalize();
-        
-        let secret_key = SecretKey::from_slice(&hash)
-            .expect("Invalid private key from mnemonic");
-        let private_key = secret_key.secret_bytes().to_vec();
-        
-        // Generate public key
-        let public_key = PublicKey::from_secret_key(&secp, &secret_key);
-        let public_key_bytes = public_key.serialize().to_vec();
-        
-        // Generate FVChain address
-        let address = Self::generate_fvchain_address(&public_key_bytes);
-        
+        let public_key = PublicKey::from_secret_key(&secp, &secret_key).serialize().to_vec();
         Self {
-            private_key,
-            public_key: public_key_bytes,
-            address,
+            private_key: secret_key.secret_bytes().to_vec(),
+            address: Self::generate_fvchain_address(&public_key),
+            public_key,
+            chain_code,
             secp,
         }
     }
+
+    /// HD master key for `seed` (BIP32-style, HMAC-SHA512 keyed with "FVChain seed")
+    pub fn from_seed(seed: &[u8]) -> Self {
+        let mac = hmac_sha512(HD_SEED_KEY, seed);
+        let secret_key = SecretKey::from_slice(&mac[..32])
+            .expect("seed produced an invalid master key");
+        let mut chain_code = [0u8; 32];
+        chain_code.copy_from_slice(&mac[32..]);
+        Self::from_secret_key(secret_key, Some(chain_code))
+    }
+
+    /// Deterministic child key at `index`; indices from HARDENED_OFFSET up are hardened.
+    /// Keys not created from a seed use SHA3-256 of their private key as chain code.
+    pub fn derive_child(&self, index: u32) -> KeyManager {
+        let chain_code = self.chain_code.unwrap_or_else(|| Sha3_256::digest(&self.private_key).into());
+
+        let mut data = Vec::with_capacity(37);
+        if index >= HARDENED_OFFSET {
+            data.push(0);
+            data.extend_from_slice(&self.private_key);
+        } else {
+            data.extend_from_slice(&self.public_key);
+        }
+        data.extend_from_slice(&index.to_be_bytes());
+
+        let mac = hmac_sha512(&chain_code, &data);
+        let mut tweak = [0u8; 32];
+        tweak.copy_from_slice(&mac[..32]);
+        let mut child_chain_code = [0u8; 32];
+        child_chain_code.copy_from_slice(&mac[32..]);
+
+        let parent = SecretKey::from_slice(&self.private_key).expect("KeyManager holds a valid private key");
+        match Scalar::from_be_bytes(tweak).ok().and_then(|tweak| parent.add_tweak(&tweak).ok()) {
+            Some(child) => Self::from_secret_key(child, Some(child_chain_code)),
+            // As in BIP32, an out-of-range result (probability ~2^-127) skips to the next index
+            None => self.derive_child(index.wrapping_add(1)),
+        }
+    }
+
+    /// BIP39 recovery phrase (24 words) encoding this key's private key
+    pub fn to_mnemonic(&self) -> String {
+        Mnemonic::from_entropy(&self.private_key)
+            .expect("32-byte private key is valid BIP39 entropy")
+            .to_string()
+    }
+
+    /// Restore a KeyManager from the phrase produced by `to_mnemonic`, checking its checksum word
+    pub fn from_mnemonic(phrase: &str) -> Result<Self, WalletError> {
+        Self::from_mnemonic_with(phrase, MnemonicScheme::Entropy)
+    }
+
+    /// Restore a KeyManager from a recovery phrase under the given scheme
+    pub fn from_mnemonic_with(phrase: &str, scheme: MnemonicScheme) -> Result<Self, WalletError> {
+        let mnemonic = Mnemonic::parse(phrase).map_err(|e| match e {
+            bip39::Error::InvalidChecksum => WalletError::ChecksumMismatch,
+            e => WalletError::InvalidMnemonic(e.to_string()),
+        })?;
+
+        let secret = match scheme {
+            MnemonicScheme::Entropy => {
+                if mnemonic.word_count() != MNEMONIC_WORDS {
+                    return Err(WalletError::WrongWordCount(mnemonic.word_count()));
+                }
+                mnemonic.to_entropy()
+            }
+            // Legacy phrases could be any BIP39 length
+            MnemonicScheme::LegacySeed => Sha3_256::digest(&mnemonic.to_seed("")[..32]).to_vec(),
+        };
+        let secret_key = SecretKey::from_slice(&secret).map_err(|_| WalletError::InvalidPrivateKey)?;
+        Ok(Self::from_secret_key(secret_key, None))
+    }
     
     /// Load KeyManager from file
     pub fn from_file(path: &str) -> Result<Self, Box<dyn std::error::Error>> {
@@ -170,14 +327,19 @@ impl KeyManager {
     
     /// Verify signature using secp256k1 ECDSA
     pub fn verify(&self, data: &[u8], signature: &[u8]) -> bool {
-        if let Ok(public_key) = PublicKey::from_slice(&self.public_key) {
+        Self::verify_with_public_key(&self.public_key, data, signature)
+    }
+    
+    /// Verify a signature against an arbitrary serialized secp256k1 public key
+    pub fn verify_with_public_key(public_key: &[u8], data: &[u8], signature: &[u8]) -> bool {
+        if let Ok(public_key) = PublicKey::from_slice(public_key) {
             if let Ok(sig) = Signature::from_compact(signature) {
                 // Hash data with same Fractal-Vortex enhancement
                 let mut fractal_hasher = FractalHasher::new(2);
                 let vortex_hash = fractal_hasher.fractal_hash(data);
                 
                 if let Ok(message) = Message::from_digest_slice(&vortex_hash.fractal_hash) {
-                    return self.secp.verify_ecdsa(&message, &sig, &public_key).is_ok();
+                    return Secp256k1::verification_only().verify_ecdsa(&message, &sig, &public_key).is_ok();
                 }
             }
         }
@@ -212,6 +374,7 @@ impl KeyManager {
             private_key: private_key_bytes,
             public_key: public_key_bytes,
             address,
+            chain_code: None,
             secp,
         })
     }
@@ -222,31 +385,44 @@ impl KeyManager {
             private_key: vec![0u8; 32],
             public_key: vec![0u8; 33],
             address: address.to_string(),
+            chain_code: None,
             secp: Secp256k1::new(),
         }
     }
     
-    /// Validate FVChain address format (NATIVE FORMAT: fvc + 36 hex + emyl)
-    pub fn validate_address(address: &str) -> bool {
-        if !address.starts_with("fvc") {
-            return false;
+    /// Validate a native FVChain address: fvc + 32 hex body + 4 hex checksum + emyl
+    pub fn validate_address(address: &str) -> Result<(), AddressError> {
+        let bytes = Self::decode_address(address)?;
+        let (body, checksum) = bytes.split_at(ADDRESS_BODY_LEN);
+        if checksum != Self::address_checksum(body) {
+            return Err(AddressError::ChecksumMismatch);
         }
-        
-        if !address.ends_with("emyl") {
-            return false;
+        Ok(())
+    }
+
+    /// Re-encode an address generated before checksums in the current format. Both formats start
+    /// with the same 16 derived bytes, so the result is the address the same key derives today.
+    pub fn upgrade_legacy_address(address: &str) -> Result<String, AddressError> {
+        let bytes = Self::decode_address(address)?;
+        let mut body = [0u8; ADDRESS_BODY_LEN];
+        body.copy_from_slice(&bytes[..ADDRESS_BODY_LEN]);
+        Ok(Self::address_with_checksum(&body))
+    }
+
+    /// The 18 bytes between prefix and suffix, checksum not yet checked
+    fn decode_address(address: &str) -> Result<Vec<u8>, AddressError> {
+        if !address.starts_with(ADDRESS_PREFIX) {
+            return Err(AddressError::WrongPrefix);
         }
-        
-        if address.len() != 43 {
-            return false;
+        if !address.ends_with(ADDRESS_SUFFIX) {
+            return Err(AddressError::WrongSuffix);
         }
-        
-        // Extract hex part (remove fvc prefix and emyl suffix)
-        let hex_part = &address[3..address.len()-4];
-        if hex_part.len() != 36 {
-            return false;
+        if address.len() != ADDRESS_LEN {
+            return Err(AddressError::WrongLength(address.len()));
         }
-        
-        hex::decode(hex_part).is_ok()
+
+        let hex_part = &address[ADDRESS_PREFIX.len()..address.len() - ADDRESS_SUFFIX.len()];
+        hex::decode(hex_part).map_err(|_| AddressError::NonHex)
     }
     
     /// Get address checksum for validation
@@ -273,7 +449,73 @@ mod tests {
         let km = KeyManager::new();
         assert!(km.get_address().starts_with("fvc"));
         assert_eq!(km.get_address().len(), 43); // "fvc" + 36 hex chars + "emyl"
-        assert!(KeyManager::validate_address(&km.get_address()));
+        assert_eq!(KeyManager::validate_address(&km.get_address()), Ok(()));
+    }
+
+    #[test]
+    fn test_hd_derivation_is_deterministic() {
+        let seed = [0x42u8; 64];
+        let master = KeyManager::from_seed(&seed);
+        assert_eq!(master.get_address(), KeyManager::from_seed(&seed).get_address());
+
+        let first = master.derive_child(0);
+        assert_eq!(first.get_address(), KeyManager::from_seed(&seed).derive_child(0).get_address());
+        assert_eq!(first.get_private_key(), master.derive_child(0).get_private_key());
+        assert_eq!(KeyManager::validate_address(&first.get_address()), Ok(()));
+
+        // Different indices, hardened or not, and different seeds give different accounts
+        let addresses = [
+            master.get_address(),
+            first.get_address(),
+            master.derive_child(1).get_address(),
+            master.derive_child(HARDENED_OFFSET).get_address(),
+            first.derive_child(0).get_address(),
+            KeyManager::from_seed(&[0x43u8; 64]).derive_child(0).get_address(),
+        ];
+        for (i, a) in addresses.iter().enumerate() {
+            for b in &addresses[i + 1..] {
+                assert_ne!(a, b);
+            }
+        }
+
+        // Children sign with keys matching their address
+        let signature = first.sign(b"account switch").unwrap();
+        assert!(KeyManager::verify_with_public_key(&first.get_public_key(), b"account switch", &signature));
+    }
+
+    #[test]
+    fn test_address_validation() {
+        let address = KeyManager::address_with_checksum(&[0x5a; ADDRESS_BODY_LEN]);
+        assert_eq!(address.len(), ADDRESS_LEN);
+        assert_eq!(KeyManager::validate_address(&address), Ok(()));
+
+        let wrong_prefix = format!("fvx{}", &address[3..]);
+        assert_eq!(KeyManager::validate_address(&wrong_prefix), Err(AddressError::WrongPrefix));
+
+        let too_short = format!("{}emyl", &address[..address.len() - 6]);
+        assert_eq!(KeyManager::validate_address(&too_short), Err(AddressError::WrongLength(ADDRESS_LEN - 2)));
+
+        let non_hex = format!("fvc{}{}", "z".repeat(36), ADDRESS_SUFFIX);
+        assert_eq!(KeyManager::validate_address(&non_hex), Err(AddressError::NonHex));
+
+        // One mistyped character in the body is caught by the checksum
+        let mut typo = address.clone();
+        typo.replace_range(10..11, "b");
+        assert_ne!(typo, address);
+        assert_eq!(KeyManager::validate_address(&typo), Err(AddressError::ChecksumMismatch));
+    }
+
+    #[test]
+    fn test_legacy_address_upgrades_to_current_address() {
+        // Generated for this key before addresses carried a checksum
+        let key = KeyManager::from_private_key_hex(&"01".repeat(32)).unwrap();
+        let legacy = "fvc0465565855505d5d1e48796270777780c1a2emyl";
+        assert_eq!(KeyManager::legacy_address_from_public_key(&key.get_public_key()), legacy);
+        assert_eq!(key.get_address(), "fvc0465565855505d5d1e4879627077778081fdemyl");
+
+        assert_eq!(KeyManager::validate_address(legacy), Err(AddressError::ChecksumMismatch));
+        assert_eq!(KeyManager::upgrade_legacy_address(legacy), Ok(key.get_address()));
+        assert_eq!(KeyManager::upgrade_legacy_address("fvc123emyl"), Err(AddressError::WrongLength(10)));
     }
     
     #[test]
@@ -292,7 +534,56 @@ mod tests {
     #[test]
     fn test_mnemonic() {
         let mnemonic = KeyManager::generate_mnemonic();
-        let km = KeyManager::from_mnemonic(&mnemonic);
+        let km = KeyManager::from_mnemonic(&mnemonic).unwrap();
         assert!(km.get_address().starts_with("fvc"));
     }
+
+    #[test]
+    fn test_mnemonic_round_trip() {
+        let km = KeyManager::new();
+        let phrase = km.to_mnemonic();
+        assert_eq!(phrase.split_whitespace().count(), MNEMONIC_WORDS);
+
+        let restored = KeyManager::from_mnemonic(&phrase).unwrap();
+        assert_eq!(restored.get_private_key(), km.get_private_key());
+        assert_eq!(restored.get_address(), km.get_address());
+        assert_eq!(restored.to_mnemonic(), phrase);
+    }
+
+    #[test]
+    fn test_altered_mnemonic_word_fails() {
+        let km = KeyManager::from_private_key_hex(&"11".repeat(32)).unwrap();
+        let phrase = km.to_mnemonic();
+        let mut words: Vec<&str> = phrase.split_whitespace().collect();
+
+        // The last word carries the checksum bits; swapping it for its neighbour in the
+        // word list keeps the key bits and breaks the checksum
+        let english = bip39::Language::English;
+        let last = english.find_word(words[MNEMONIC_WORDS - 1]).unwrap() as usize;
+        words[MNEMONIC_WORDS - 1] = english.word_list()[last ^ 1];
+        assert_eq!(KeyManager::from_mnemonic(&words.join(" ")).err(), Some(WalletError::ChecksumMismatch));
+
+        words[0] = "vortexx";
+        assert!(matches!(KeyManager::from_mnemonic(&words.join(" ")), Err(WalletError::InvalidMnemonic(_))));
+
+        let short = phrase.split_whitespace().take(12).collect::<Vec<_>>().join(" ");
+        assert!(KeyManager::from_mnemonic(&short).is_err());
+    }
+
+    #[test]
+    fn test_legacy_seed_mnemonic_vector() {
+        let phrase = "legal winner thank year wave sausage worth useful legal winner thank year \
+                      wave sausage worth useful legal winner thank year wave sausage worth title";
+
+        // Address a wallet restored from this phrase before it encoded the key, shown then
+        // without a checksum
+        let legacy = KeyManager::from_mnemonic_with(phrase, MnemonicScheme::LegacySeed).unwrap();
+        assert_eq!(legacy.get_address(), "fvc58585c555e5e66657d7d707e7a83828af4c7emyl");
+        assert_eq!(KeyManager::legacy_address_from_public_key(&legacy.get_public_key()), "fvc58585c555e5e66657d7d707e7a83828a9999emyl");
+
+        // The same words under the current scheme are the key 0x7f7f..7f
+        let current = KeyManager::from_mnemonic(phrase).unwrap();
+        assert_eq!(current.get_private_key(), vec![0x7f; 32]);
+        assert_eq!(current.get_address(), "fvc544c545c5a61686669767379807e858c117cemyl");
+    }
 }
\ No newline at end of file
@@ -4,8 +4,8 @@ pub mod rpc_client;
 pub mod wallet;
 pub mod cli;
 
-pub use key_manager::KeyManager;
+pub use key_manager::{KeyManager, AddressError, MnemonicScheme, WalletError};
 pub use transaction::{WalletTransaction, TransactionBuilder};
-pub use rpc_client::{RpcClient, BalanceResponse, NetworkInfo, TransactionStatus};
+pub use rpc_client::{RpcClient, BalanceResponse, ConfirmationStatus, NetworkInfo, TransactionStatus};
 pub use wallet::Wallet;
 pub use cli::run_cli;
\ No newline at end of file
@@ -1,7 +1,10 @@
 use serde::{Serialize, Deserialize};
 
+use rand::Rng;
 use reqwest;
 use std::collections::HashMap;
+use std::time::Duration;
+use crate::rpc_storage::{Block, WalletTransaction as ChainTransaction};
 use crate::wallet::transaction::WalletTransaction;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -29,9 +32,97 @@ pub struct TransactionStatus {
     pub confirmations: u64,
 }
 
+impl TransactionStatus {
+    /// Where this transaction stands, final once `final_depth` blocks deep
+    pub fn confirmation_status(&self, final_depth: u64) -> ConfirmationStatus {
+        match self.block_height {
+            Some(_) => ConfirmationStatus::from_depth(self.confirmations, final_depth),
+            None => ConfirmationStatus::Pending,
+        }
+    }
+}
+
+/// How deeply a transaction is buried in the chain
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ConfirmationStatus {
+    /// Not yet in a block
+    Pending,
+    /// In a block `depth` confirmations deep; the tip block has depth 1
+    Confirmed { depth: u64 },
+    /// Buried at least the finality depth and can no longer be reorganized out
+    Final,
+}
+
+impl ConfirmationStatus {
+    /// Status of a transaction with `depth` confirmations; zero means it is still pending
+    pub fn from_depth(depth: u64, final_depth: u64) -> Self {
+        match depth {
+            0 => ConfirmationStatus::Pending,
+            d if d >= final_depth => ConfirmationStatus::Final,
+            d => ConfirmationStatus::Confirmed { depth: d },
+        }
+    }
+
+    /// Status of a transaction mined at `block_height` when the chain tip is `tip_height`
+    pub fn at_height(block_height: u64, tip_height: u64, final_depth: u64) -> Self {
+        let depth = if block_height > tip_height { 0 } else { tip_height - block_height + 1 };
+        Self::from_depth(depth, final_depth)
+    }
+
+    /// Legacy `status` string of the wallet endpoints
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConfirmationStatus::Pending => "pending",
+            ConfirmationStatus::Confirmed { .. } => "confirmed",
+            ConfirmationStatus::Final => "finalized",
+        }
+    }
+}
+
+/// How requests are retried after a connection failure, timeout, 429 or 5xx reply
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Total attempts, including the first
+    pub max_attempts: u32,
+    /// Delay before the first retry; doubled for each retry after it
+    pub base_delay: Duration,
+    /// Upper bound of the random delay added to each backoff
+    pub jitter: Duration,
+    /// Also retry POSTs. Off by default: a resent transfer could be applied twice.
+    pub retry_posts: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(200),
+            jitter: Duration::from_millis(100),
+            retry_posts: false,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Single attempt, no retries
+    pub fn none() -> Self {
+        Self { max_attempts: 1, ..Self::default() }
+    }
+
+    /// Backoff before retry number `retry` (1 for the first retry)
+    fn delay(&self, retry: u32) -> Duration {
+        let backoff = self.base_delay.saturating_mul(1 << retry.saturating_sub(1).min(16));
+        let jitter_ms = self.jitter.as_millis() as u64;
+        let jitter = if jitter_ms == 0 { 0 } else { rand::thread_rng().gen_range(0..=jitter_ms) };
+        backoff + Duration::from_millis(jitter)
+    }
+}
+
 pub struct RpcClient {
     pub base_url: String,
     client: reqwest::Client,
+    retry: RetryPolicy,
 }
 
 impl RpcClient {
@@ -39,28 +130,68 @@ impl RpcClient {
         Self {
             base_url: base_url.to_string(),
             client: reqwest::Client::new(),
+            retry: RetryPolicy::default(),
         }
     }
 
+    /// Use `retry` instead of the default retry policy
+    pub fn with_retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// Send the request built by `build`, rebuilding and resending it under the retry policy
+    /// while failures are transient. The last reply is returned even if it is a 5xx.
+    async fn send(
+        &self,
+        build: impl Fn() -> reqwest::RequestBuilder,
+        idempotent: bool,
+    ) -> Result<reqwest::Response, reqwest::Error> {
+        let max_attempts = if idempotent || self.retry.retry_posts { self.retry.max_attempts.max(1) } else { 1 };
+        let mut attempt = 1;
+        loop {
+            let result = build().send().await;
+            let transient = match &result {
+                Ok(response) => {
+                    response.status().is_server_error() || response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS
+                }
+                Err(e) => e.is_connect() || e.is_timeout(),
+            };
+            if !transient || attempt >= max_attempts {
+                return result;
+            }
+            let delay = self.retry.delay(attempt);
+            log::debug!("RPC attempt {}/{} failed, retrying in {:?}", attempt, max_attempts, delay);
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    async fn get(&self, url: &str) -> Result<reqwest::Response, reqwest::Error> {
+        self.send(|| self.client.get(url), true).await
+    }
+
+    async fn post<T: Serialize>(&self, url: &str, body: &T) -> Result<reqwest::Response, reqwest::Error> {
+        self.send(|| self.client.post(url).json(body), false).await
+    }
+
     pub async fn get_balance(&self, address: &str) -> Result<BalanceResponse, Box<dyn std::error::Error>> {
         let url = format!("{}/balance/{}" , self.base_url, address);
-        let response = self.client.get(&url).send().await?;
+        let response = self.get(&url).await?;
         let balance: BalanceResponse = response.json().await?;
         Ok(balance)
     }
 
     pub async fn get_network_info(&self) -> Result<NetworkInfo, Box<dyn std::error::Error>> {
         let url = format!("{}/network/info" , self.base_url);
-        let response = self.client.get(&url).send().await?;
+        let response = self.get(&url).await?;
         let info: NetworkInfo = response.json().await?;
         Ok(info)
     }
 
     pub async fn send_transaction(&self, transaction: &WalletTransaction) -> Result<String, Box<dyn std::error::Error>> {
         let url = format!("{}/transaction" , self.base_url);
-        let response = self.client.post(&url)
-            .json(transaction)
-            .send().await?;
+        let response = self.post(&url, transaction).await?;
         
         let result: HashMap<String, String> = response.json().await?;
         Ok(result.get("hash").unwrap_or(&"unknown".to_string()).clone())
@@ -68,37 +199,35 @@ impl RpcClient {
 
     pub async fn get_transaction_status(&self, hash: &str) -> Result<TransactionStatus, Box<dyn std::error::Error>> {
         let url = format!("{}/transaction/{}" , self.base_url, hash);
-        let response = self.client.get(&url).send().await?;
+        let response = self.get(&url).await?;
         let status: TransactionStatus = response.json().await?;
         Ok(status)
     }
 
     pub async fn get_gas_price(&self) -> Result<u64, Box<dyn std::error::Error>> {
         let url = format!("{}/gas-price" , self.base_url);
-        let response = self.client.get(&url).send().await?;
+        let response = self.get(&url).await?;
         let result: HashMap<String, u64> = response.json().await?;
         Ok(result.get("gas_price").copied().unwrap_or(1000))
     }
 
     pub async fn get_validators(&self) -> Result<Vec<String>, Box<dyn std::error::Error>> {
         let url = format!("{}/validators" , self.base_url);
-        let response = self.client.get(&url).send().await?;
+        let response = self.get(&url).await?;
         let validators: Vec<String> = response.json().await?;
         Ok(validators)
     }
 
     pub async fn get_staking_info(&self, address: &str) -> Result<HashMap<String, u64>, Box<dyn std::error::Error>> {
         let url = format!("{}/staking/{}" , self.base_url, address);
-        let response = self.client.get(&url).send().await?;
+        let response = self.get(&url).await?;
         let staking_info: HashMap<String, u64> = response.json().await?;
         Ok(staking_info)
     }
 
     pub async fn estimate_gas(&self, transaction: &WalletTransaction) -> Result<u64, Box<dyn std::error::Error>> {
         let url = format!("{}/estimate-gas" , self.base_url);
-        let response = self.client.post(&url)
-            .json(transaction)
-            .send().await?;
+        let response = self.post(&url, transaction).await?;
         
         let result: HashMap<String, u64> = response.json().await?;
         Ok(result.get("gas_estimate").copied().unwrap_or(21000))
@@ -106,15 +235,237 @@ impl RpcClient {
 
     pub async fn get_block_height(&self) -> Result<u64, Box<dyn std::error::Error>> {
         let url = format!("{}/block/height" , self.base_url);
-        let response = self.client.get(&url).send().await?;
+        let response = self.get(&url).await?;
         let result: HashMap<String, u64> = response.json().await?;
         Ok(result.get("height").copied().unwrap_or(0))
     }
 
+    /// GET a node endpoint's JSON envelope; a 5xx status or an `"success": false` reply becomes the error
+    async fn get_envelope(&self, path: &str) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        let url = format!("{}{}", self.base_url, path);
+        let response = self.get(&url).await?;
+        let status = response.status();
+        if status.is_server_error() {
+            return Err(format!("Node returned {}", status).into());
+        }
+
+        let body: serde_json::Value = response.json().await?;
+        if body.get("success").and_then(|s| s.as_bool()) == Some(false) {
+            let error = body.get("error").and_then(|e| e.as_str()).unwrap_or("request failed");
+            return Err(error.to_string().into());
+        }
+        Ok(body)
+    }
+
+    /// Block at `height` with its confirmations, as served by `/blocks/:height`
+    pub async fn get_block(&self, height: u64) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.get_envelope(&format!("/blocks/{}", height)).await
+    }
+
+    /// Stored block at `height`, taken from the `get_block` reply; a missing block is an error
+    pub async fn get_block_by_height(&self, height: u64) -> Result<Block, Box<dyn std::error::Error>> {
+        let mut reply = self.get_block(height).await?;
+        let block = reply.get_mut("block").map(serde_json::Value::take).ok_or("Reply has no block")?;
+        Ok(serde_json::from_value(block)?)
+    }
+
+    /// Total, spendable and immature balance of `address`, as served by `/balance/:address`
+    pub async fn get_balance_breakdown(&self, address: &str) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.get_envelope(&format!("/balance/{}", address)).await
+    }
+
+    /// Transaction with its finality status, as served by `/transaction/:hash`
+    pub async fn get_transaction_envelope(&self, hash: &str) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.get_envelope(&format!("/transaction/{}", hash)).await
+    }
+
+    #[deprecated(note = "use `get_transaction_envelope`, or `get_chain_transaction` for the typed transaction")]
+    pub async fn get_transaction(&self, hash: &str) -> Result<serde_json::Value, Box<dyn std::error::Error>> {
+        self.get_transaction_envelope(hash).await
+    }
+
+    /// Transaction `hash` as recorded in a block; None when the node has not mined it
+    /// (unknown, still pending or expired)
+    pub async fn get_chain_transaction(&self, hash: &str) -> Result<Option<ChainTransaction>, Box<dyn std::error::Error>> {
+        let mut reply = match self.get_transaction_envelope(hash).await {
+            Ok(reply) => reply,
+            Err(e) if e.to_string() == "Transaction not found" => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        match reply.get_mut("transaction").map(serde_json::Value::take) {
+            Some(transaction) => Ok(Some(serde_json::from_value(transaction)?)),
+            None => Ok(None),
+        }
+    }
+
     pub async fn get_latest_transactions(&self, limit: usize) -> Result<Vec<WalletTransaction>, Box<dyn std::error::Error>> {
         let url = format!("{}/transactions/latest?limit={}" , self.base_url, limit);
-        let response = self.client.get(&url).send().await?;
+        let response = self.get(&url).await?;
         let transactions: Vec<WalletTransaction> = response.json().await?;
         Ok(transactions)
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Default finality depth of the node's wallet endpoints
+    const FINAL_DEPTH: u64 = 6;
+
+    #[test]
+    fn test_just_mined_transaction_has_depth_one() {
+        let status = ConfirmationStatus::at_height(120, 120, FINAL_DEPTH);
+        assert_eq!(status, ConfirmationStatus::Confirmed { depth: 1 });
+        assert_eq!(status.as_str(), "confirmed");
+        assert_eq!(
+            serde_json::to_value(status).unwrap(),
+            serde_json::json!({ "state": "confirmed", "depth": 1 })
+        );
+        assert_eq!(ConfirmationStatus::at_height(120, 124, FINAL_DEPTH), ConfirmationStatus::Confirmed { depth: 5 });
+    }
+
+    #[test]
+    fn test_deep_transaction_is_final() {
+        assert_eq!(ConfirmationStatus::at_height(120, 125, FINAL_DEPTH), ConfirmationStatus::Final);
+        assert_eq!(ConfirmationStatus::at_height(1, 10_000, FINAL_DEPTH), ConfirmationStatus::Final);
+        assert_eq!(ConfirmationStatus::Final.as_str(), "finalized");
+
+        let mined = TransactionStatus {
+            hash: "0xabc".to_string(),
+            status: "finalized".to_string(),
+            block_height: Some(1),
+            confirmations: 40,
+        };
+        assert_eq!(mined.confirmation_status(FINAL_DEPTH), ConfirmationStatus::Final);
+
+        let pending = TransactionStatus { block_height: None, confirmations: 0, ..mined };
+        assert_eq!(pending.confirmation_status(FINAL_DEPTH), ConfirmationStatus::Pending);
+    }
+
+    fn chain_block() -> Block {
+        let mut block = Block::new_with_timestamp(42, "fvcminer".to_string(), "0".repeat(64), 1_700_000_000);
+        block.add_transaction(transfer());
+        block.hash = block.canonical_hash();
+        block
+    }
+
+    fn transfer() -> ChainTransaction {
+        ChainTransaction::new_transfer(
+            "fvc00000000000000000000000000000000c001emyl".to_string(),
+            "fvc00000000000000000000000000000000c002emyl".to_string(),
+            5_000,
+            "0xabc".to_string(),
+            42,
+        )
+    }
+
+    /// Node serving a block at height 42 and transaction 0xabc, a pending 0xfeed, and a 500 for height 500 and 0xboom
+    async fn mock_node() -> String {
+        use axum::{extract::Path, http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+        use serde_json::json;
+
+        fn failure(error: &str) -> axum::response::Response {
+            (StatusCode::INTERNAL_SERVER_ERROR, Json(json!({ "success": false, "error": error }))).into_response()
+        }
+        let app = Router::new()
+            .route("/blocks/:height", get(|Path(height): Path<u64>| async move {
+                match height {
+                    42 => Json(json!({ "success": true, "block": chain_block(), "confirmations": 1 })).into_response(),
+                    500 => failure("Failed to get block: storage offline"),
+                    _ => Json(json!({ "success": false, "error": "Block not found" })).into_response(),
+                }
+            }))
+            .route("/transaction/:hash", get(|Path(hash): Path<String>| async move {
+                match hash.as_str() {
+                    "0xabc" => Json(json!({ "success": true, "status": "confirmed", "transaction": transfer() })).into_response(),
+                    "0xfeed" => Json(json!({ "success": true, "status": "pending", "valid_until": 1_700_086_400u64 })).into_response(),
+                    "0xboom" => failure("Failed to get transaction: storage offline"),
+                    _ => Json(json!({ "success": false, "error": "Transaction not found" })).into_response(),
+                }
+            }));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        format!("http://{}", addr)
+    }
+
+    #[tokio::test]
+    async fn test_typed_block_lookup() {
+        let client = RpcClient::new(&mock_node().await);
+
+        assert_eq!(client.get_block_by_height(42).await.unwrap(), chain_block());
+        assert_eq!(client.get_block_by_height(7).await.unwrap_err().to_string(), "Block not found");
+        let err = client.get_block_by_height(500).await.unwrap_err();
+        assert!(err.to_string().contains("500"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn test_typed_transaction_lookup() {
+        let client = RpcClient::new(&mock_node().await);
+
+        assert_eq!(client.get_chain_transaction("0xabc").await.unwrap(), Some(transfer()));
+        assert_eq!(client.get_chain_transaction("0xmissing").await.unwrap(), None);
+        assert_eq!(client.get_chain_transaction("0xfeed").await.unwrap(), None);
+        assert!(client.get_chain_transaction("0xboom").await.is_err());
+    }
+
+    /// Node that answers 503 to its first `failures` requests and serves block 42 afterwards;
+    /// returns its URL and the number of requests it has seen
+    async fn flaky_node(failures: usize) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use axum::{http::StatusCode, response::IntoResponse, routing::get, Json, Router};
+        use serde_json::json;
+        use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+
+        let hits = Arc::new(AtomicUsize::new(0));
+        let seen = hits.clone();
+        let reply = move || {
+            let hits = hits.clone();
+            async move {
+                if hits.fetch_add(1, Ordering::SeqCst) < failures {
+                    return (StatusCode::SERVICE_UNAVAILABLE, "node busy").into_response();
+                }
+                Json(json!({ "success": true, "block": chain_block() })).into_response()
+            }
+        };
+        let app = Router::new()
+            .route("/blocks/:height", get(reply.clone()))
+            .route("/transaction", axum::routing::post(reply));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+        (format!("http://{}", addr), seen)
+    }
+
+    fn fast_retry() -> RetryPolicy {
+        RetryPolicy { max_attempts: 3, base_delay: Duration::from_millis(1), jitter: Duration::from_millis(1), retry_posts: false }
+    }
+
+    #[tokio::test]
+    async fn test_get_recovers_from_transient_failures() {
+        use std::sync::atomic::Ordering;
+
+        let (url, hits) = flaky_node(2).await;
+        let client = RpcClient::new(&url).with_retry(fast_retry());
+        assert_eq!(client.get_block_by_height(42).await.unwrap(), chain_block());
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+
+        // Out of attempts: the last 503 is reported
+        let (url, hits) = flaky_node(5).await;
+        let client = RpcClient::new(&url).with_retry(fast_retry());
+        assert!(client.get_block_by_height(42).await.is_err());
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_transfers_not_retried_by_default() {
+        use std::sync::atomic::Ordering;
+
+        let (url, hits) = flaky_node(2).await;
+        let client = RpcClient::new(&url).with_retry(fast_retry());
+        let transfer = crate::wallet::transaction::TransactionBuilder::new("fvc00000000000000000000000000000000c001emyl".to_string(), 0)
+            .transfer("fvc00000000000000000000000000000000c002emyl".to_string(), 5_000);
+        let _ = client.send_transaction(&transfer).await;
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+    }
+}
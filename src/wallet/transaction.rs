@@ -1,5 +1,6 @@
 use serde::{Serialize, Deserialize};
 use sha3::{Sha3_256, Digest};
+use crate::wallet::key_manager::KeyManager;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletTransaction {
@@ -92,6 +93,27 @@ impl WalletTransaction {
         format!("0x{:x}", result)
     }
 
+    /// Bytes covered by the sender's signature: every field except the signature and hash
+    pub fn canonical_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        for part in [&self.from, &self.to] {
+            data.extend_from_slice(part.as_bytes());
+            data.push(0);
+        }
+        data.extend_from_slice(&self.amount.to_le_bytes());
+        data.extend_from_slice(&self.nonce.to_le_bytes());
+        data.extend_from_slice(&self.fee.to_le_bytes());
+        data.extend_from_slice(&self.timestamp.to_le_bytes());
+        data
+    }
+
+    /// Sign the canonical bytes with `key`
+    pub fn sign_with(&mut self, key: &KeyManager) -> Result<(), Box<dyn std::error::Error>> {
+        let signature = key.sign(&self.canonical_bytes())?;
+        self.sign(signature);
+        Ok(())
+    }
+
     pub fn sign(&mut self, signature: Vec<u8>) {
         self.signature = Some(signature);
         self.hash = Some(self.calculate_hash());
@@ -103,12 +125,14 @@ impl WalletTransaction {
         self.signature.is_some()
     }
 
-    pub fn verify_signature(&self, _public_key: &[u8]) -> bool {
-        if let Some(signature) = &self.signature {
-            // Simplified signature verification - in production use proper crypto
-            !signature.is_empty()
-        } else {
-            false
+    /// True when `public_key` owns the `from` address and signed the canonical bytes
+    pub fn verify_signature(&self, public_key: &[u8]) -> bool {
+        match &self.signature {
+            Some(signature) => {
+                KeyManager::address_from_public_key(public_key) == self.from
+                    && KeyManager::verify_with_public_key(public_key, &self.canonical_bytes(), signature)
+            }
+            None => false,
         }
     }
 
@@ -129,4 +153,29 @@ impl WalletTransaction {
             "TRANSFER"
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_signature_must_come_from_sender_key() {
+        let sender = KeyManager::new();
+        let other = KeyManager::new();
+        let mut tx = TransactionBuilder::new(sender.get_address(), 1).transfer(other.get_address(), 5_000);
+        assert!(!tx.verify_signature(&sender.get_public_key()));
+
+        tx.sign_with(&sender).unwrap();
+        assert!(tx.verify_signature(&sender.get_public_key()));
+        assert!(!tx.verify_signature(&other.get_public_key()));
+
+        let mut forged = tx.clone();
+        forged.sign_with(&other).unwrap();
+        assert!(!forged.verify_signature(&other.get_public_key()));
+        assert!(!forged.verify_signature(&sender.get_public_key()));
+
+        tx.amount += 1;
+        assert!(!tx.verify_signature(&sender.get_public_key()));
+    }
+}
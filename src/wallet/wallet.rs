@@ -1,6 +1,6 @@
-use crate::wallet::key_manager::KeyManager;
+use crate::wallet::key_manager::{KeyManager, MnemonicScheme, WalletError};
 use crate::wallet::transaction::{WalletTransaction, TransactionBuilder};
-use crate::wallet::rpc_client::{RpcClient, NetworkInfo, TransactionStatus};
+use crate::wallet::rpc_client::{BalanceResponse, RpcClient, NetworkInfo, TransactionStatus};
 use std::collections::HashMap;
 
 pub struct Wallet {
@@ -23,15 +23,46 @@ impl Wallet {
         }
     }
 
-    pub fn from_mnemonic(mnemonic: &str, rpc_url: &str) -> Self {
-        let key_manager = KeyManager::from_mnemonic(mnemonic);
+    pub fn from_mnemonic(mnemonic: &str, rpc_url: &str) -> Result<Self, WalletError> {
+        Self::from_mnemonic_with(mnemonic, MnemonicScheme::Entropy, rpc_url)
+    }
+
+    pub fn from_mnemonic_with(mnemonic: &str, scheme: MnemonicScheme, rpc_url: &str) -> Result<Self, WalletError> {
+        let key_manager = KeyManager::from_mnemonic_with(mnemonic, scheme)?;
         let rpc_client = RpcClient::new(rpc_url);
         
-        Self {
+        Ok(Self {
             key_manager,
             rpc_client,
             nonce: 0,
             balance: 0,
+        })
+    }
+
+    /// Restore from a recovery phrase with the scheme the wallet was created under
+    pub async fn restore_from_mnemonic(mnemonic: &str, rpc_url: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let scheme = Self::detect_mnemonic_scheme(mnemonic, &RpcClient::new(rpc_url)).await?;
+        Ok(Self::from_mnemonic_with(mnemonic, scheme, rpc_url)?)
+    }
+
+    /// The legacy seed scheme when only its address has been used on chain, otherwise the
+    /// current one
+    pub async fn detect_mnemonic_scheme(mnemonic: &str, rpc_client: &RpcClient) -> Result<MnemonicScheme, Box<dyn std::error::Error>> {
+        let legacy = KeyManager::from_mnemonic_with(mnemonic, MnemonicScheme::LegacySeed)?;
+        let current = match KeyManager::from_mnemonic(mnemonic) {
+            Ok(key_manager) => key_manager,
+            // Phrases of other lengths, or whose words are not a valid key, can only be legacy
+            Err(WalletError::WrongWordCount(_)) | Err(WalletError::InvalidPrivateKey) => return Ok(MnemonicScheme::LegacySeed),
+            Err(e) => return Err(e.into()),
+        };
+
+        let used = |info: BalanceResponse| info.balance > 0 || info.pending_balance > 0 || info.nonce > 0;
+        if used(rpc_client.get_balance(&legacy.get_address()).await?)
+            && !used(rpc_client.get_balance(&current.get_address()).await?)
+        {
+            Ok(MnemonicScheme::LegacySeed)
+        } else {
+            Ok(MnemonicScheme::Entropy)
         }
     }
 
@@ -180,6 +211,11 @@ impl Wallet {
         KeyManager::generate_mnemonic()
     }
 
+    /// Recovery phrase for this wallet's key
+    pub fn to_mnemonic(&self) -> String {
+        self.key_manager.to_mnemonic()
+    }
+
     pub async fn get_recent_transactions(&self, limit: usize) -> Result<Vec<WalletTransaction>, Box<dyn std::error::Error>> {
         self.rpc_client.get_latest_transactions(limit).await
     }
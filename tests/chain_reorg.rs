@@ -0,0 +1,124 @@
+use fractal_vortex_chain::consensus::vortex_consensus::Transaction;
+use fractal_vortex_chain::consensus::MiningRewardSystem;
+use fractal_vortex_chain::mining::template::transfer_from_consensus;
+use fractal_vortex_chain::node::fork_choice::BlockOutcome;
+use fractal_vortex_chain::node::fractal_node::{FractalNode, NodeConfig};
+use fractal_vortex_chain::rpc_storage::{sender_fee, Block, RPCStorage, WalletTransaction};
+use fractal_vortex_chain::tx_submission::{build_transfer, gossip_transaction};
+use fractal_vortex_chain::wallet::key_manager::KeyManager;
+
+const BOB: &str = "fvc00000000000000000000000000000000b0b0emyl";
+const CAROL: &str = "fvc0000000000000000000000000000000ca401emyl";
+const ALICE_START: u64 = 100_000;
+
+/// A transfer signed by `sender`, as gossiped and as a block at `height` carries it
+fn signed_transfer(sender: &KeyManager, to: &str, amount: u64, nonce: u64, height: u64) -> (Transaction, WalletTransaction) {
+    let tx = build_transfer("transfer", sender.get_address(), to.to_string(), amount, nonce).unwrap();
+    let gossip = gossip_transaction(&tx, &hex::encode(sender.get_private_key())).unwrap();
+    let transfer = transfer_from_consensus(&gossip, height, 1_700_000_000);
+    (gossip, transfer)
+}
+
+fn mined_block(parent: &Block, miner: &str, transactions: Vec<WalletTransaction>) -> Block {
+    let height = parent.height + 1;
+    let mut block = Block::new_with_timestamp(height, miner.to_string(), parent.hash.clone(), 1_700_000_000 + height);
+    let reward = MiningRewardSystem::new().reward_at_height(height);
+    block.add_transaction(WalletTransaction::coinbase(miner.to_string(), reward, height));
+    for tx in transactions {
+        block.add_transaction(tx);
+    }
+    for nonce in 0u64.. {
+        block.nonce = nonce;
+        block.hash = block.canonical_hash();
+        if block.has_valid_pow() {
+            break;
+        }
+    }
+    block
+}
+
+#[tokio::test]
+async fn test_node_converges_on_heavier_fork() {
+    let rpc_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("RPC_DATA_DIR", rpc_dir.path());
+
+    let genesis = Block::new_with_timestamp(0, "Genesis".to_string(), "0".repeat(64), 1_700_000_000);
+    RPCStorage::store_block(&genesis).await.unwrap();
+    let alice = KeyManager::from_private_key_hex(&"0a".repeat(32)).unwrap();
+    let alice_address = alice.get_address();
+    RPCStorage::set_balance(&alice_address, ALICE_START).await.unwrap();
+    let config = NodeConfig { listen_addr: "/ip4/127.0.0.1/tcp/0".parse().unwrap(), data_dir: None, ..NodeConfig::default() };
+    let node = FractalNode::new(config).await.unwrap();
+
+    // A pending transfer that only the light fork mines
+    let (_, shared) = signed_transfer(&alice, BOB, 100, 1, 2);
+    let (pending, light_only) = signed_transfer(&alice, CAROL, 50, 2, 2);
+    let light_only_hash = light_only.hash.clone();
+    RPCStorage::add_pending_transaction(&pending, 1_700_000_000).await.unwrap();
+    node.get_consensus().write().await.add_transaction(pending.clone()).await.unwrap();
+
+    // Light fork: two blocks, carrying two transfers
+    let light_1 = mined_block(&genesis, "fvclight", vec![]);
+    let light_2 = mined_block(&light_1, "fvclight", vec![shared.clone(), light_only]);
+    for block in [&light_1, &light_2] {
+        assert!(matches!(node.accept_block(block.clone()).await.unwrap(), BlockOutcome::TipChanged(_)));
+    }
+    assert_eq!(RPCStorage::get_block_height().await.unwrap(), 2);
+    assert_eq!(node.canonical_balance_change(CAROL).await, 50);
+    assert_eq!(RPCStorage::get_balance(CAROL).await.unwrap(), 50);
+    let reward = MiningRewardSystem::new().reward_at_height(1);
+    assert_eq!(RPCStorage::get_balance("fvclight").await.unwrap(), 2 * reward);
+    assert!(RPCStorage::load_pending_transactions().await.unwrap().is_empty());
+    assert!(!node.get_consensus().read().await.has_pending_transaction(&pending.hash).await);
+
+    // Heavy fork: three blocks from genesis, re-including only the shared transfer. Its third block
+    // arrives before its second, so the fork never ties the light one on the way.
+    let heavy_1 = mined_block(&genesis, "fvcheavy", vec![]);
+    let heavy_2 = mined_block(&heavy_1, "fvcheavy", vec![shared.clone()]);
+    let heavy_3 = mined_block(&heavy_2, "fvcheavy", vec![]);
+    assert_eq!(node.accept_block(heavy_1.clone()).await.unwrap(), BlockOutcome::SideChain);
+    assert_eq!(node.accept_block(heavy_3.clone()).await.unwrap(), BlockOutcome::Orphaned);
+    assert_eq!(RPCStorage::get_block_by_height(1).await.unwrap().unwrap().hash, light_1.hash);
+
+    let BlockOutcome::TipChanged(update) = node.accept_block(heavy_2.clone()).await.unwrap() else {
+        panic!("heavier fork should become canonical");
+    };
+    assert!(update.is_reorg());
+    assert_eq!(update.fork_height, 0);
+    assert_eq!(update.reverted.len(), 2);
+    assert_eq!(
+        update.applied.iter().map(|b| b.hash.clone()).collect::<Vec<_>>(),
+        vec![heavy_1.hash.clone(), heavy_2.hash.clone(), heavy_3.hash.clone()]
+    );
+    let dropped: Vec<&str> = update.dropped_transactions().iter().map(|tx| tx.hash.as_str()).collect();
+    assert_eq!(dropped, vec![light_only_hash.as_str()]);
+
+    // Storage follows the heavier chain
+    assert_eq!(RPCStorage::get_block_height().await.unwrap(), 3);
+    assert_eq!(RPCStorage::get_block_by_height(1).await.unwrap().unwrap().hash, heavy_1.hash);
+    assert_eq!(RPCStorage::get_block_by_height(2).await.unwrap().unwrap().hash, heavy_2.hash);
+    assert_eq!(RPCStorage::get_block_by_height(3).await.unwrap().unwrap().hash, heavy_3.hash);
+
+    // Only the heavy chain's effects remain: shared transfer re-applied, light-only transfer and rewards undone
+    assert_eq!(node.canonical_balance_change(&alice_address).await, -100);
+    assert_eq!(node.canonical_balance_change(BOB).await, 100);
+    assert_eq!(node.canonical_balance_change(CAROL).await, 0);
+    assert_eq!(node.canonical_balance_change("fvclight").await, 0);
+    assert_eq!(node.canonical_balance_change("fvcheavy").await, 3 * reward as i128);
+
+    // ...and storage agrees: balances, transaction records and the mempool are rolled back too
+    assert_eq!(RPCStorage::get_balance(&alice_address).await.unwrap(), ALICE_START - 100 - sender_fee(&shared));
+    assert_eq!(RPCStorage::get_balance(BOB).await.unwrap(), 100);
+    assert_eq!(RPCStorage::get_balance(CAROL).await.unwrap(), 0);
+    assert_eq!(RPCStorage::get_balance("fvclight").await.unwrap(), 0);
+    assert_eq!(RPCStorage::get_balance("fvcheavy").await.unwrap(), 3 * reward);
+    assert!(RPCStorage::get_transaction(&light_only_hash).await.unwrap().is_none());
+    assert!(RPCStorage::get_transaction(&light_1.transactions[0].hash).await.unwrap().is_none());
+    assert!(RPCStorage::get_transaction(&shared.hash).await.unwrap().is_some());
+    let mempool = RPCStorage::load_pending_transactions().await.unwrap();
+    assert_eq!(mempool.iter().map(|p| p.transaction.hash).collect::<Vec<_>>(), vec![pending.hash]);
+    assert!(node.get_consensus().read().await.has_pending_transaction(&pending.hash).await);
+
+    // The lighter fork coming back is only kept aside
+    assert_eq!(node.accept_block(light_2).await.unwrap(), BlockOutcome::Duplicate);
+}
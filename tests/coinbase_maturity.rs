@@ -0,0 +1,55 @@
+use fractal_vortex_chain::consensus::MiningRewardSystem;
+use fractal_vortex_chain::rpc_storage::{BalanceBreakdown, Block, RPCStorage, WalletTransaction};
+
+const MINER: &str = "fvcmaturityminer";
+
+fn mined_block(parent: &Block, miner: &str) -> Block {
+    let height = parent.height + 1;
+    let mut block = Block::new_with_timestamp(height, miner.to_string(), parent.hash.clone(), 1_700_000_000 + height);
+    let reward = MiningRewardSystem::new().reward_at_height(height);
+    block.add_transaction(WalletTransaction::coinbase(miner.to_string(), reward, height));
+    for nonce in 0u64.. {
+        block.nonce = nonce;
+        block.hash = block.canonical_hash();
+        if block.has_valid_pow() {
+            break;
+        }
+    }
+    block
+}
+
+#[tokio::test]
+async fn test_breakdown_reads_rewards_from_the_maturity_window() {
+    let rpc_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("RPC_DATA_DIR", rpc_dir.path());
+    std::env::set_var("COINBASE_MATURITY", "3");
+
+    let genesis = Block::new_with_timestamp(0, "Genesis".to_string(), "0".repeat(64), 1_700_000_000);
+    RPCStorage::store_block(&genesis).await.unwrap();
+    let reward = MiningRewardSystem::new().reward_at_height(1);
+    let block1 = mined_block(&genesis, MINER);
+    RPCStorage::store_mined_block(&block1).await.unwrap();
+    let block2 = mined_block(&block1, MINER);
+    RPCStorage::store_mined_block(&block2).await.unwrap();
+
+    // Tip 2: both rewards have fewer than three confirmations
+    assert_eq!(
+        RPCStorage::get_balance_breakdown(MINER).await.unwrap(),
+        BalanceBreakdown { balance: 2 * reward, spendable_balance: 0, immature_balance: 2 * reward }
+    );
+
+    // Tip 3: block 1 reaches three confirmations and leaves the window
+    let block3 = mined_block(&block2, "fvcother");
+    RPCStorage::store_mined_block(&block3).await.unwrap();
+    assert_eq!(
+        RPCStorage::get_balance_breakdown(MINER).await.unwrap(),
+        BalanceBreakdown { balance: 2 * reward, spendable_balance: reward, immature_balance: reward }
+    );
+
+    let block4 = mined_block(&block3, "fvcother");
+    RPCStorage::store_mined_block(&block4).await.unwrap();
+    assert_eq!(
+        RPCStorage::get_balance_breakdown(MINER).await.unwrap(),
+        BalanceBreakdown { balance: 2 * reward, spendable_balance: 2 * reward, immature_balance: 0 }
+    );
+}
@@ -0,0 +1,24 @@
+use axum::http::{HeaderMap, StatusCode};
+use fractal_vortex_chain::api_auth::{authorize, env_api_key_scopes, hash_key, Scope, API_KEY_HEADER};
+use fractal_vortex_chain::rpc_storage::RPCStorage;
+
+#[tokio::test]
+async fn test_env_keys_authorize_without_being_stored() {
+    let rpc_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("RPC_DATA_DIR", rpc_dir.path());
+    std::env::set_var("FVC_ADMIN_API_KEY", "env-admin-key");
+    std::env::set_var("FVC_MINING_API_KEY", "env-mining-key");
+
+    assert_eq!(env_api_key_scopes(), vec![Scope::Mining, Scope::Admin]);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(API_KEY_HEADER, "env-admin-key".parse().unwrap());
+    assert_eq!(authorize(&headers, Scope::Admin).await.unwrap().scope, Scope::Admin);
+    headers.insert(API_KEY_HEADER, "env-mining-key".parse().unwrap());
+    assert_eq!(authorize(&headers, Scope::Admin).await.unwrap_err().status(), StatusCode::FORBIDDEN);
+
+    // Nothing from the environment reaches the database
+    for key in ["env-admin-key", "env-mining-key"] {
+        assert_eq!(RPCStorage::get_api_key_scope(&hash_key(key)).await.unwrap(), None);
+    }
+}
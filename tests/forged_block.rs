@@ -0,0 +1,83 @@
+use fractal_vortex_chain::consensus::vortex_consensus::Transaction;
+use fractal_vortex_chain::consensus::MiningRewardSystem;
+use fractal_vortex_chain::mining::template::transfer_from_consensus;
+use fractal_vortex_chain::node::fractal_node::{FractalNode, NodeConfig, NodeError};
+use fractal_vortex_chain::rpc_storage::{sender_fee, Block, RPCStorage, WalletTransaction};
+use fractal_vortex_chain::tx_submission::build_transfer;
+use fractal_vortex_chain::wallet::key_manager::KeyManager;
+
+const MALLORY_PAYEE: &str = "fvc00000000000000000000000000000000b0b0emyl";
+
+fn mined_block(parent: &Block, transactions: Vec<WalletTransaction>) -> Block {
+    let height = parent.height + 1;
+    let mut block = Block::new_with_timestamp(height, "fvcforger".to_string(), parent.hash.clone(), 1_700_000_000 + height);
+    let reward = MiningRewardSystem::new().reward_at_height(height);
+    block.add_transaction(WalletTransaction::coinbase("fvcforger".to_string(), reward, height));
+    for tx in transactions {
+        block.add_transaction(tx);
+    }
+    for nonce in 0u64.. {
+        block.nonce = nonce;
+        block.hash = block.canonical_hash();
+        if block.has_valid_pow() {
+            break;
+        }
+    }
+    block
+}
+
+/// `amount` out of `from`'s address as a block carries it, signed with `signer`'s key
+fn transfer_signed_by(from: &KeyManager, signer: &KeyManager, amount: u64) -> WalletTransaction {
+    let tx = build_transfer("transfer", from.get_address(), MALLORY_PAYEE.to_string(), amount, 1).unwrap();
+    let mut hash = [0u8; 32];
+    hex::decode_to_slice(&tx.hash[2..], &mut hash).unwrap();
+    let mut gossip = Transaction {
+        hash,
+        from: signer.get_public_key(),
+        to: tx.to.clone().into_bytes(),
+        amount,
+        nonce: tx.nonce,
+        signature: Vec::new(),
+        vortex_fee: sender_fee(&tx) as f64,
+    };
+    gossip.signature = signer.sign(&gossip.signing_payload()).unwrap();
+    WalletTransaction { from: tx.from, ..transfer_from_consensus(&gossip, 1, 1_700_000_000) }
+}
+
+#[tokio::test]
+async fn test_block_with_forged_transfer_is_rejected() {
+    let rpc_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("RPC_DATA_DIR", rpc_dir.path());
+
+    let genesis = Block::new_with_timestamp(0, "Genesis".to_string(), "0".repeat(64), 1_700_000_000);
+    RPCStorage::store_block(&genesis).await.unwrap();
+    let victim = KeyManager::from_private_key_hex(&"0b".repeat(32)).unwrap();
+    let mallory = KeyManager::from_private_key_hex(&"0c".repeat(32)).unwrap();
+    RPCStorage::set_balance(&victim.get_address(), 1_000_000).await.unwrap();
+    let config = NodeConfig { listen_addr: "/ip4/127.0.0.1/tcp/0".parse().unwrap(), data_dir: None, ..NodeConfig::default() };
+    let node = FractalNode::new(config).await.unwrap();
+
+    let honest = transfer_signed_by(&victim, &victim, 1_000);
+    assert!(honest.has_valid_signature());
+
+    // Signed by someone other than the sender
+    let stolen = transfer_signed_by(&victim, &mallory, 900_000);
+    // The victim's real signature with the amount raised under the same hash
+    let inflated = WalletTransaction { amount: 900_000, ..honest.clone() };
+    // No signature at all
+    let unsigned = WalletTransaction { signature: None, ..honest.clone() };
+
+    for forged in [stolen, inflated, unsigned] {
+        let block = mined_block(&genesis, vec![forged]);
+        let result = node.accept_block(block).await;
+        assert!(matches!(result, Err(NodeError::InvalidBlock(_))), "forged block accepted: {:?}", result);
+    }
+    assert_eq!(RPCStorage::get_block_height().await.unwrap(), 0);
+    assert_eq!(RPCStorage::get_balance(&victim.get_address()).await.unwrap(), 1_000_000);
+    assert_eq!(RPCStorage::get_balance(MALLORY_PAYEE).await.unwrap(), 0);
+
+    // The same block with the honest transfer goes through
+    let block = mined_block(&genesis, vec![honest.clone()]);
+    node.accept_block(block).await.unwrap();
+    assert_eq!(RPCStorage::get_balance(MALLORY_PAYEE).await.unwrap(), 1_000);
+}
@@ -0,0 +1,56 @@
+use fractal_vortex_chain::consensus::MiningRewardSystem;
+use fractal_vortex_chain::rpc_storage::{sender_fee, Block, RPCStorage, WalletTransaction};
+use fractal_vortex_chain::tx_submission::{build_transfer, submit_transfer, SubmissionStatus};
+
+const ALICE: &str = "fvc0000000000000000000000000000000a11ceemyl";
+const BOB: &str = "fvc00000000000000000000000000000000b0b0emyl";
+
+fn mined_block(parent: &Block, miner: &str) -> Block {
+    let height = parent.height + 1;
+    let mut block = Block::new_with_timestamp(height, miner.to_string(), parent.hash.clone(), 1_700_000_000 + height);
+    let reward = MiningRewardSystem::new().reward_at_height(height);
+    block.add_transaction(WalletTransaction::coinbase(miner.to_string(), reward, height));
+    for nonce in 0u64.. {
+        block.nonce = nonce;
+        block.hash = block.canonical_hash();
+        if block.has_valid_pow() {
+            break;
+        }
+    }
+    block
+}
+
+#[tokio::test]
+async fn test_balance_at_height_between_settled_transfers() {
+    let rpc_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("RPC_DATA_DIR", rpc_dir.path());
+    std::env::set_var("COINBASE_MATURITY", "0");
+
+    let genesis = Block::new_with_timestamp(0, "Genesis".to_string(), "0".repeat(64), 1_700_000_000);
+    RPCStorage::store_block(&genesis).await.unwrap();
+    let block1 = mined_block(&genesis, ALICE);
+    let reward = MiningRewardSystem::new().reward_at_height(1);
+    RPCStorage::store_mined_block(&block1).await.unwrap();
+
+    // Settled on top of block 1, then two more blocks, then a second transfer
+    let first = build_transfer("transfer", ALICE.to_string(), BOB.to_string(), 1_000, 1).unwrap();
+    assert_eq!(submit_transfer(&first).await.status, SubmissionStatus::Accepted);
+    let block2 = mined_block(&block1, "fvcminer");
+    RPCStorage::store_mined_block(&block2).await.unwrap();
+    let block3 = mined_block(&block2, "fvcminer");
+    RPCStorage::store_mined_block(&block3).await.unwrap();
+    let second = build_transfer("transfer", ALICE.to_string(), BOB.to_string(), 2_000, 2).unwrap();
+    assert_eq!(submit_transfer(&second).await.status, SubmissionStatus::Accepted);
+
+    let after_first = reward - 1_000 - sender_fee(&first);
+    let after_second = after_first - 2_000 - sender_fee(&second);
+    assert_eq!(RPCStorage::balance_at_height(ALICE, 0).await.unwrap(), 0);
+    assert_eq!(RPCStorage::balance_at_height(ALICE, 1).await.unwrap(), reward);
+    assert_eq!(RPCStorage::balance_at_height(ALICE, 2).await.unwrap(), after_first);
+    // Between the two transfers only the first has happened
+    assert_eq!(RPCStorage::balance_at_height(ALICE, 3).await.unwrap(), after_first);
+    assert_eq!(RPCStorage::balance_at_height(BOB, 3).await.unwrap(), 1_000);
+    assert_eq!(RPCStorage::balance_at_height(ALICE, 4).await.unwrap(), after_second);
+    assert_eq!(RPCStorage::balance_at_height(ALICE, 4).await.unwrap(), RPCStorage::get_balance(ALICE).await.unwrap());
+    assert_eq!(RPCStorage::balance_at_height(BOB, 4).await.unwrap(), 3_000);
+}
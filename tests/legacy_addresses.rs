@@ -0,0 +1,32 @@
+use fractal_vortex_chain::rpc_storage::RPCStorage;
+use fractal_vortex_chain::tx_submission::authorize_sender;
+use fractal_vortex_chain::wallet::key_manager::KeyManager;
+
+// Generated for the key 0x0101..01 before addresses carried a checksum
+const LEGACY: &str = "fvc0465565855505d5d1e48796270777780c1a2emyl";
+const CURRENT: &str = "fvc0465565855505d5d1e4879627077778081fdemyl";
+
+#[tokio::test]
+async fn test_legacy_address_balance_moves_to_current_address() {
+    let rpc_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("RPC_DATA_DIR", rpc_dir.path());
+
+    RPCStorage::set_balance(LEGACY, 5_000).await.unwrap();
+    RPCStorage::set_balance(CURRENT, 1_000).await.unwrap();
+
+    assert_eq!(RPCStorage::migrate_legacy_addresses().await.unwrap(), 1);
+    assert_eq!(RPCStorage::get_balance(CURRENT).await.unwrap(), 6_000);
+    assert_eq!(RPCStorage::get_balance(LEGACY).await.unwrap(), 0);
+    assert_eq!(RPCStorage::current_address(LEGACY).await.unwrap(), CURRENT);
+    assert_eq!(RPCStorage::current_address(CURRENT).await.unwrap(), CURRENT);
+
+    // Running it again moves nothing
+    assert_eq!(RPCStorage::migrate_legacy_addresses().await.unwrap(), 0);
+    assert_eq!(RPCStorage::get_balance(CURRENT).await.unwrap(), 6_000);
+
+    // The wallet that held the legacy address still signs for it once resolved
+    let key = "01".repeat(32);
+    assert_eq!(KeyManager::from_private_key_hex(&key).unwrap().get_address(), CURRENT);
+    let from = RPCStorage::current_address(LEGACY).await.unwrap();
+    authorize_sender(&from, &key).unwrap();
+}
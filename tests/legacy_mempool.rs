@@ -0,0 +1,45 @@
+use fractal_vortex_chain::consensus::vortex_consensus::Transaction;
+use fractal_vortex_chain::rpc_storage::{PendingTransaction, RPCStorage};
+use fractal_vortex_chain::storage::LedgerDB;
+
+fn pending(seed: u8, received_at: u64) -> PendingTransaction {
+    PendingTransaction {
+        transaction: Transaction {
+            hash: [seed; 32],
+            from: vec![2; 33],
+            to: b"fvc00000000000000000000000000000000a0a0emyl".to_vec(),
+            amount: 100 + seed as u64,
+            nonce: seed as u64,
+            signature: Vec::new(),
+            vortex_fee: 0.5,
+        },
+        received_at,
+        valid_until: 0,
+    }
+}
+
+#[tokio::test]
+async fn test_legacy_mempool_blob_splits_into_records() {
+    let rpc_dir = tempfile::tempdir().unwrap();
+    let now = chrono::Utc::now().timestamp() as u64;
+    let records = vec![pending(1, now - 20), pending(2, now - 10)];
+
+    // A database written while the mempool was stored as one blob
+    {
+        let legacy = LedgerDB::open(rpc_dir.path()).unwrap();
+        legacy.put_batch(&[(b"mempool".to_vec(), serde_json::to_vec(&records).unwrap())]).await.unwrap();
+    }
+    std::env::set_var("RPC_DATA_DIR", rpc_dir.path());
+
+    assert_eq!(RPCStorage::migrate_legacy_mempool().await.unwrap(), 2);
+    assert_eq!(RPCStorage::migrate_legacy_mempool().await.unwrap(), 0);
+
+    let loaded = RPCStorage::load_pending_transactions().await.unwrap();
+    assert_eq!(loaded.iter().map(|p| p.transaction.hash).collect::<Vec<_>>(), vec![[1; 32], [2; 32]]);
+
+    // Records now come and go one at a time
+    RPCStorage::add_pending_transaction(&pending(3, now).transaction, now).await.unwrap();
+    RPCStorage::remove_pending_transactions(&[[1; 32]]).await.unwrap();
+    let loaded = RPCStorage::load_pending_transactions().await.unwrap();
+    assert_eq!(loaded.iter().map(|p| p.transaction.hash).collect::<Vec<_>>(), vec![[2; 32], [3; 32]]);
+}
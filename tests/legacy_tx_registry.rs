@@ -0,0 +1,33 @@
+use fractal_vortex_chain::rpc_storage::{RPCStorage, WalletTransaction};
+use fractal_vortex_chain::storage::LedgerDB;
+
+#[tokio::test]
+async fn test_legacy_registry_is_readable_after_startup_migration() {
+    let rpc_dir = tempfile::tempdir().unwrap();
+    let hashes: Vec<String> = (0..3).map(|i| format!("0x1e6a{:04}", i)).collect();
+
+    // A database written before the transaction log: records plus the single-blob registry
+    {
+        let legacy = LedgerDB::open(rpc_dir.path()).unwrap();
+        let mut entries = vec![(
+            b"transaction_hashes_registry".to_vec(),
+            serde_json::to_vec(&hashes).unwrap(),
+        )];
+        for hash in &hashes {
+            let tx = WalletTransaction::new_transfer("fvcsender".to_string(), "fvcreceiver".to_string(), 1, hash.clone(), 1);
+            entries.push((format!("tx:{}", hash).into_bytes(), serde_json::to_vec(&tx).unwrap()));
+        }
+        legacy.put_batch(&entries).await.unwrap();
+    }
+    std::env::set_var("RPC_DATA_DIR", rpc_dir.path());
+
+    assert_eq!(RPCStorage::migrate_legacy_transaction_registry().await.unwrap(), 3);
+    // Running it again on the migrated database changes nothing
+    assert_eq!(RPCStorage::migrate_legacy_transaction_registry().await.unwrap(), 3);
+
+    assert_eq!(RPCStorage::get_transaction_hashes().await.unwrap(), hashes);
+    let page = RPCStorage::get_transactions_page(None, 10).await.unwrap();
+    let newest_first: Vec<String> = hashes.iter().rev().cloned().collect();
+    assert_eq!(page.transactions.iter().map(|tx| tx.hash.clone()).collect::<Vec<_>>(), newest_first);
+    assert!(RPCStorage::get_transaction(&hashes[1]).await.unwrap().is_some());
+}
@@ -0,0 +1,30 @@
+use fractal_vortex_chain::consensus::{MiningEngine, MiningRewardSystem};
+use fractal_vortex_chain::rpc_storage::RPCStorage;
+
+const MINER: &str = "fvc00000000000000000000000000000000e1e1emyl";
+
+#[tokio::test]
+async fn test_mined_blocks_credit_coinbase_to_miner() {
+    let rpc_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("RPC_DATA_DIR", rpc_dir.path());
+
+    let mut engine = MiningEngine::new();
+    let mut blocks = Vec::new();
+    for _ in 0..3 {
+        blocks.push(engine.mine_block(MINER, Vec::new()).await.unwrap());
+    }
+
+    let rewards = MiningRewardSystem::new();
+    for block in &blocks {
+        let coinbase = &block.transactions[0];
+        assert_eq!(coinbase.transaction_type, "mining_reward");
+        assert_eq!(coinbase.to, MINER);
+        assert_eq!(coinbase.amount, rewards.reward_at_height(block.height));
+    }
+    assert_eq!(blocks.iter().map(|b| b.height).collect::<Vec<_>>(), vec![1, 2, 3]);
+    assert_eq!(engine.get_block_height(), 3);
+
+    let expected: u64 = (1..=3).map(|height| rewards.reward_at_height(height)).sum();
+    assert_eq!(RPCStorage::get_balance(MINER).await.unwrap(), expected);
+    assert_eq!(RPCStorage::get_block_height().await.unwrap(), 3);
+}
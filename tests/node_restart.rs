@@ -0,0 +1,71 @@
+use std::time::Duration;
+use fractal_vortex_chain::network::build_listen_addr;
+use fractal_vortex_chain::node::fractal_node::{FractalNode, NodeConfig};
+use fractal_vortex_chain::rpc_storage::RPCStorage;
+
+fn free_port() -> u16 {
+    std::net::TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+}
+
+fn node_config(port: u16, bootstrap_port: Option<u16>, data_dir: Option<&std::path::Path>) -> NodeConfig {
+    NodeConfig {
+        listen_addr: build_listen_addr("127.0.0.1", port).unwrap(),
+        bootstrap_nodes: bootstrap_port.map(|p| build_listen_addr("127.0.0.1", p).unwrap()).into_iter().collect(),
+        energy_threshold: 1000.0,
+        fractal_levels: 5,
+        max_peers: 50,
+        sync_interval: 30,
+        data_dir: data_dir.map(|dir| dir.to_path_buf()),
+    }
+}
+
+/// Poll `condition` every 100ms until it holds or `timeout` runs out
+async fn wait_for<F, Fut>(timeout: Duration, mut condition: F) -> bool
+where
+    F: FnMut() -> Fut,
+    Fut: std::future::Future<Output = bool>,
+{
+    let deadline = tokio::time::Instant::now() + timeout;
+    while tokio::time::Instant::now() < deadline {
+        if condition().await {
+            return true;
+        }
+        tokio::time::sleep(Duration::from_millis(100)).await;
+    }
+    false
+}
+
+async fn has_peers(node: &FractalNode) -> bool {
+    !node.get_state().read().await.connected_peers.is_empty()
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_restarted_node_rejoins_and_mines() {
+    let rpc_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("RPC_DATA_DIR", rpc_dir.path());
+    let node_dir = tempfile::tempdir().unwrap();
+
+    let hub_port = free_port();
+    let mut hub = FractalNode::new(node_config(hub_port, None, None)).await.unwrap();
+    hub.start_network().await.unwrap();
+
+    let config = node_config(free_port(), Some(hub_port), Some(node_dir.path()));
+    let node = FractalNode::launch(config, 3, Duration::from_millis(200)).await.unwrap();
+    assert!(wait_for(Duration::from_secs(10), || has_peers(&node)).await, "node never reached the hub");
+    assert!(node.is_mining());
+
+    let height_before = RPCStorage::get_block_height().await.unwrap();
+    let node = node.restart(5, Duration::from_millis(200)).await.unwrap();
+    assert!(node.is_mining());
+    assert!(wait_for(Duration::from_secs(10), || has_peers(&node)).await, "restarted node did not rejoin");
+    assert!(
+        wait_for(Duration::from_secs(15), || async {
+            RPCStorage::get_block_height().await.unwrap() > height_before
+        }).await,
+        "restarted node did not resume mining"
+    );
+
+    let mut node = node;
+    node.shutdown().await.unwrap();
+    hub.shutdown().await.unwrap();
+}
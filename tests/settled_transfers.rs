@@ -0,0 +1,55 @@
+use fractal_vortex_chain::consensus::MiningRewardSystem;
+use fractal_vortex_chain::rpc_storage::{sender_fee, Block, RPCStorage, WalletTransaction};
+use fractal_vortex_chain::tx_submission::{build_transfer, submit_transfer, SubmissionStatus};
+
+const ALICE: &str = "fvc0000000000000000000000000000000a11ceemyl";
+const BOB: &str = "fvc00000000000000000000000000000000b0b0emyl";
+
+fn mined_block(parent: &Block, miner: &str, transactions: Vec<WalletTransaction>) -> Block {
+    let height = parent.height + 1;
+    let mut block = Block::new_with_timestamp(height, miner.to_string(), parent.hash.clone(), 1_700_000_000 + height);
+    let reward = MiningRewardSystem::new().reward_at_height(height);
+    block.add_transaction(WalletTransaction::coinbase(miner.to_string(), reward, height));
+    for tx in transactions {
+        block.add_transaction(tx);
+    }
+    for nonce in 0u64.. {
+        block.nonce = nonce;
+        block.hash = block.canonical_hash();
+        if block.has_valid_pow() {
+            break;
+        }
+    }
+    block
+}
+
+#[tokio::test]
+async fn test_transfer_settled_at_submission_is_not_applied_again_when_mined() {
+    let rpc_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("RPC_DATA_DIR", rpc_dir.path());
+
+    let genesis = Block::new_with_timestamp(0, "Genesis".to_string(), "0".repeat(64), 1_700_000_000);
+    RPCStorage::store_block(&genesis).await.unwrap();
+    RPCStorage::set_balance(ALICE, 10_000).await.unwrap();
+
+    let tx = build_transfer("transfer", ALICE.to_string(), BOB.to_string(), 1_000, 1).unwrap();
+    assert_eq!(submit_transfer(&tx).await.status, SubmissionStatus::Accepted);
+    let settled = 10_000 - 1_000 - sender_fee(&tx);
+    assert_eq!(RPCStorage::get_balance(ALICE).await.unwrap(), settled);
+
+    // The gossiped copy comes back in a mined block; only the reward moves balances
+    let mined = mined_block(&genesis, "fvcminer", vec![WalletTransaction { block_height: 1, ..tx.clone() }]);
+    RPCStorage::store_mined_block(&mined).await.unwrap();
+    assert_eq!(RPCStorage::get_balance(ALICE).await.unwrap(), settled);
+    assert_eq!(RPCStorage::get_balance(BOB).await.unwrap(), 1_000);
+    let reward = MiningRewardSystem::new().reward_at_height(1);
+    assert_eq!(RPCStorage::get_balance("fvcminer").await.unwrap(), reward);
+
+    // Reorganizing the block out undoes the reward but not the settlement
+    let replacement = mined_block(&genesis, "fvcother", vec![]);
+    RPCStorage::apply_chain_update(std::slice::from_ref(&mined), &[replacement]).await.unwrap();
+    assert_eq!(RPCStorage::get_balance(ALICE).await.unwrap(), settled);
+    assert_eq!(RPCStorage::get_balance(BOB).await.unwrap(), 1_000);
+    assert_eq!(RPCStorage::get_balance("fvcminer").await.unwrap(), 0);
+    assert!(RPCStorage::get_transaction(&tx.hash).await.unwrap().is_some());
+}
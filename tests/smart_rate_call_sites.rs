@@ -0,0 +1,28 @@
+use fractal_vortex_chain::consensus::MiningEngine;
+use fractal_vortex_chain::rpc_storage::RPCStorage;
+
+const MINER: &str = "fvc00000000000000000000000000000000e1e2emyl";
+
+#[tokio::test]
+async fn test_every_call_site_reports_the_same_smart_rate() {
+    let rpc_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("RPC_DATA_DIR", rpc_dir.path());
+
+    let mut engine = MiningEngine::new();
+    for _ in 0..4 {
+        engine.mine_block(MINER, Vec::new()).await.unwrap();
+    }
+
+    // What the RPC server's Smart Rate endpoints serve
+    let stored = RPCStorage::get_smart_rate().await.unwrap();
+    assert_eq!((stored.block_height, stored.transaction_count), (4, 4));
+
+    let info = RPCStorage::get_network_info().await.unwrap();
+    assert_eq!(info["network_smart_rate"].as_f64().unwrap(), stored.smart_rate);
+    assert_eq!(info["smart_rate_components"], serde_json::to_value(stored).unwrap());
+
+    let stats = RPCStorage::get_stats().await.unwrap();
+    assert_eq!(stats["network_smart_rate"].as_f64().unwrap(), stored.smart_rate);
+
+    assert_eq!(engine.get_mining_info().network_smart_rate, stored.smart_rate);
+}
@@ -0,0 +1,31 @@
+use fractal_vortex_chain::rpc_storage::{RPCStorage, WalletTransaction};
+
+const MINER: &str = "fvc0000000000000000000000000000000a0d17emyl";
+
+#[tokio::test]
+async fn test_supply_audit_sums_stored_balances() {
+    let rpc_dir = tempfile::tempdir().unwrap();
+    std::env::set_var("RPC_DATA_DIR", rpc_dir.path());
+
+    // A reward on record that was never credited
+    let reward = WalletTransaction::new_mining_reward(MINER.to_string(), 1_000, "0xa0d10001".to_string(), 1);
+    RPCStorage::add_transaction(&reward).await.unwrap();
+    let audit = RPCStorage::audit_supply().await.unwrap();
+    assert_eq!(audit.expected_supply, 1_000);
+    assert_eq!(audit.balance_supply, 0);
+    assert_eq!(audit.discrepancy, -1_000);
+    assert!(!audit.is_consistent());
+
+    RPCStorage::set_balance(MINER, 1_000).await.unwrap();
+    let audit = RPCStorage::audit_supply().await.unwrap();
+    assert_eq!(audit.balance_supply, 1_000);
+    assert_eq!(audit.tracked_supply, 1_000);
+    assert!(audit.is_consistent());
+
+    // Value that no transaction accounts for
+    RPCStorage::set_balance(MINER, 1_250).await.unwrap();
+    let audit = RPCStorage::audit_supply().await.unwrap();
+    assert_eq!(audit.balance_supply, 1_250);
+    assert_eq!(audit.discrepancy, 250);
+    assert!(!audit.is_consistent());
+}